@@ -1,15 +1,16 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use ucd_parse::{self, Age};
+use ucd_parse::Age;
 
 use crate::args::ArgMatches;
 use crate::error::Result;
-use crate::util::PropertyValues;
+use crate::util::{self, json_string, PropertyValues};
 
 pub fn command(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
     let propvals = PropertyValues::from_ucd_dir(&dir)?;
-    let ages: Vec<Age> = ucd_parse::parse(&dir)?;
+    let filter = args.filter(|name| propvals.canonical("Age", name))?;
+    let ages: Vec<Age> = util::parse(&dir)?;
 
     let mut by_age: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
     for x in &ages {
@@ -20,10 +21,69 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
             .extend(x.codepoints.into_iter().map(|c| c.value()));
     }
 
+    if args.is_present("list-properties") {
+        return list(&propvals, &by_age, args.is_present("json"));
+    }
+    filter.check_included(by_age.keys())?;
+    let by_age: BTreeMap<String, BTreeSet<u32>> =
+        by_age.into_iter().filter(|(name, _)| filter.contains(name)).collect();
+
     let mut wtr = args.writer("age")?;
-    wtr.names(by_age.keys())?;
+    if args.is_present("combined") {
+        wtr.ranges_to_combined(args.name(), &by_age)?;
+    } else {
+        wtr.names(by_age.keys())?;
+        for (name, set) in by_age {
+            wtr.ranges(&name, &set)?;
+        }
+    }
+    wtr.finish()?;
+    Ok(())
+}
+
+/// Print each age in the given map, oldest first, along with its version
+/// number and the number of codepoints that were added in it.
+fn list(
+    propvals: &PropertyValues,
+    by_age: &BTreeMap<String, BTreeSet<u32>>,
+    json: bool,
+) -> Result<()> {
+    let mut ages = vec![];
     for (name, set) in by_age {
-        wtr.ranges(&name, &set)?;
+        let version = propvals.abbreviation("Age", name)?;
+        ages.push((version_key(&version), version, name, set.len()));
     }
+    ages.sort();
+
+    if !json {
+        for (_, version, name, count) in ages {
+            println!("{} (version: {}, codepoints: {})", name, version, count);
+        }
+        return Ok(());
+    }
+    println!("[");
+    for (i, (_, version, name, count)) in ages.iter().enumerate() {
+        println!(
+            "  {{\"name\": {}, \"version\": {}, \"codepoints\": {}}}{}",
+            json_string(name),
+            json_string(version),
+            count,
+            if i + 1 == ages.len() { "" } else { "," },
+        );
+    }
+    println!("]");
     Ok(())
 }
+
+/// Return a key that sorts version numbers like `3.2` and `14.0`
+/// numerically. Anything that isn't a version number sorts last.
+fn version_key(version: &str) -> Vec<u64> {
+    let mut key = vec![];
+    for part in version.split('.') {
+        match part.parse() {
+            Ok(n) => key.push(n),
+            Err(_) => return vec![u64::MAX],
+        }
+    }
+    key
+}
@@ -40,16 +40,36 @@ Project home page: https://github.com/BurntSushi/ucd-generate";
 const ABOUT_BIDI_CLASS: &'static str = "\
 bidi-class produces one table of Unicode codepoint ranges for each
 possible Bidi_Class value.
+
+The values are read from extracted/DerivedBidiClass.txt when it exists, with
+its @missing lines giving the values of the codepoints that it doesn't list.
+Otherwise, they are derived from UnicodeData.txt along with the rules for the
+default values of unlisted codepoints, which can be asked for explicitly with
+--from-unicode-data. --validate-against-derived implies --from-unicode-data.
 ";
 
 const ABOUT_CANONICAL_COMBINING_CLASS: &'static str = "\
 canonical-combining-class produces one table of Unicode codepoint ranges for
 each possible Canonical_Combining_Class value.
+
+With --bmp-index, the classes of the codepoints in the BMP are instead written
+as two arrays: NAME_BMP_PAGES holds each distinct block of 256 classes, and
+NAME_BMP_INDEX maps the upper byte of a codepoint to its block. The classes of
+the remaining codepoints are written as ranges in NAME_SUPPLEMENTARY. A
+function named after the table returns the class of any codepoint.
 ";
 
 const ABOUT_GENERAL_CATEGORY: &'static str = "\
 general-category produces one table of Unicode codepoint ranges for each
 possible General_Category value.
+
+With --utf8-trie, a single state machine is emitted that maps the UTF-8
+encoding of each codepoint directly to the index of its value in NAME_ENUM.
+Bytes are mapped to equivalence classes by NAME_BYTE_CLASSES, and
+NAME_TRANSITIONS holds a row of classes for each state. A function named after
+the table returns the value of the codepoint at the start of a byte slice
+along with its length in bytes. Codepoints without a value have the value
+NAME_NONE, and invalid UTF-8 has the value NAME_NONE with a length of 1.
 ";
 
 const ABOUT_SCRIPT: &'static str = "\
@@ -60,6 +80,17 @@ value.
 const ABOUT_SCRIPT_EXTENSION: &'static str = "\
 script-extension produces one table of Unicode codepoint ranges for each
 possible Script_Extension value.
+
+With --bitset-enum, a single table is emitted instead, which maps codepoint
+ranges to sets of scripts. The scripts in each set are indices into the
+emitted SCRIPT_EXTENSION_ENUM table, which lists scripts in the same order as
+the SCRIPT_ENUM table emitted by 'script --enum'. This permits one Script enum
+to serve both the Script and Script_Extensions properties.
+
+Each distinct set of scripts is emitted once in SCRIPT_EXTENSION_SETS, with its
+indices in ascending order and the sets themselves sorted, so every script of a
+codepoint is found with one lookup and equal sets have the same index. With
+--per-script, the table of codepoint ranges for each script is emitted as well.
 ";
 
 const ABOUT_JOINING_TYPE: &'static str = "\
@@ -67,6 +98,16 @@ joining-type produces one table of Unicode codepoint ranges for each
 possible Joining_Type value.
 ";
 
+const ABOUT_JOINING_GROUP: &'static str = "\
+joining-group produces one table of Unicode codepoint ranges for each
+Joining_Group value in ArabicShaping.txt. Codepoints that aren't in any table
+have no joining group.
+
+With --rust-enum, the enum has a variant for every value in
+PropertyValueAliases.txt, along with a table of their aliases, as_str and
+abbreviation methods, and a FromStr impl that accepts any alias.
+";
+
 const ABOUT_AGE: &'static str = "\
 age produces a table for each discrete Unicode age. Each table includes the
 codepoints that were added for that age. Tables can be emitted as a sorted
@@ -77,11 +118,107 @@ const ABOUT_BIDI_MIRRORING_GLYPH: &'static str = "\
 bidi-mirroring-glyph produces a table that maps codepoints that have the
 Bidi_Mirrored=Yes property to another codepoint that typically has a glyph that
 is the mirror image of the original codepoint's glyph.
+
+Some mappings are marked as [BEST FIT], which means that the mirrored glyph is
+only an approximation. Use --no-best-fit to leave them out of the table.
+
+With --without-glyph, a second table, NAME_WITHOUT_GLYPH, lists the codepoints
+with Bidi_Mirrored=Yes that are not in the mapping table. Renderers must mirror
+these codepoints' glyphs themselves, if at all.
 ";
 
 const ABOUT_PROP_BOOL: &'static str = "\
 property-bool produces possibly many tables for boolean properties. Tables can
 be emitted as a sorted sequence of ranges, an FST or a trie.
+
+With --changes-when, a single CHANGES_WHEN table is emitted instead. It maps
+codepoints to a bitmask of the Changes_When_* properties they have:
+
+  1   Changes_When_Lowercased
+  2   Changes_When_Uppercased
+  4   Changes_When_Titlecased
+  8   Changes_When_Casefolded
+  16  Changes_When_Casemapped
+  32  Changes_When_NFKC_Casefolded
+
+Codepoints without any of these properties are absent from the table. So, for
+example, a string is already lowercase if none of its codepoints have bit 1 set.
+";
+
+const ABOUT_EMOJI: &'static str = "\
+emoji emits a table for each of the emoji properties of codepoints defined by
+UTS #51: Emoji, Emoji_Presentation, Emoji_Modifier, Emoji_Modifier_Base,
+Emoji_Component and Extended_Pictographic. These are read from
+emoji/emoji-data.txt. Each table is named after its property, e.g.,
+EMOJI_PRESENTATION, and can be emitted as a sorted sequence of ranges, an FST
+or a trie.
+
+With --sequences, a table is also emitted for each of the properties of emoji
+sequences, which are read from emoji/emoji-sequences.txt and
+emoji/emoji-zwj-sequences.txt: Basic_Emoji, Emoji_Keycap_Sequence,
+RGI_Emoji_Flag_Sequence, RGI_Emoji_Tag_Sequence, RGI_Emoji_Modifier_Sequence and
+RGI_Emoji_ZWJ_Sequence, along with RGI_Emoji, which is the union of all of
+them. Each of these tables is a sorted slice of strings (or an FST set), so
+whether a string is an RGI emoji can be tested with a binary search.
+
+With --flag-pairs, a RGI_EMOJI_FLAG_PAIRS table is also emitted, which is a
+sorted slice of the pairs of Regional Indicators that make up each
+RGI_Emoji_Flag_Sequence, along with a rgi_emoji_flag_pairs_contains function
+that tests whether two Regional Indicators are a valid flag.
+
+Use --list-properties to see every table this command can emit, and --include
+or --exclude to choose among them.
+";
+
+const ABOUT_VALIDITY: &'static str = "\
+validity emits a table of the codepoints that are acceptable in text that is
+stricter than merely valid UTF-8. By default, this is every Unicode scalar value
+(i.e., every codepoint except surrogates) that is not a noncharacter, according
+to the Noncharacter_Code_Point property. Private use and unassigned codepoints
+can also be excluded. This is useful for sanitizers and protocol validators.
+
+The flags for this command are similar as the flags for perl-word. Note that
+only range tables (as a slice, trie or FST) are supported; a DFA is not.
+";
+
+const ABOUT_TERMINAL_WIDTH: &'static str = "\
+terminal-width emits a single table that maps every codepoint to the number of
+columns (0, 1 or 2) it occupies in a terminal. This is meant for generating
+wcwidth-style lookup tables rather than curating them by hand. The width of a
+codepoint is determined by the first rule below that applies to it:
+
+  0  General_Category Cc, Mn, Me or Cf (except U+00AD SOFT HYPHEN), and the
+     Hangul medial vowel and final consonant jamo (U+1160..U+11FF and
+     U+D7B0..U+D7FF), which combine into the preceding syllable.
+  2  East_Asian_Width W or F, or Emoji_Presentation=Yes. With
+     --ambiguous-wide, East_Asian_Width A is also treated as wide. Codepoints
+     not listed in EastAsianWidth.txt get the East_Asian_Width of the last
+     @missing line in it that covers them, e.g., W for reserved codepoints in
+     the CJK ideograph blocks.
+  1  Everything else.
+
+This requires emoji-data.txt to be present in the UCD directory.
+";
+
+const ABOUT_SETOP: &'static str = "\
+setop emits a table of the codepoints that result from combining the sets of
+codepoints matched by property specs with union, intersection and difference.
+For example, to get every letter in the Greek script:
+
+    ucd-generate setop <ucd-dir> Greek --intersect gc=Letter
+
+Or every alphabetic codepoint outside of ASCII:
+
+    ucd-generate setop <ucd-dir> Alphabetic --difference ASCII
+
+Operations are applied from left to right, in the order they are given.
+
+A spec is either 'property=value' or a bare name. The supported properties
+are General_Category, Script, Script_Extensions and the boolean properties
+supported by property-bool, where the value of a boolean property is Yes or
+No. A bare name is looked up as a General_Category value, then a Script value
+and then a boolean property name. The special names Any, ASCII and Assigned
+are also recognized. Names are matched loosely, according to UAX44-LM3.
 ";
 
 const ABOUT_PERL_WORD: &'static str = "\
@@ -108,6 +245,17 @@ Jamo_Short_Name property value. The value is encoded in the least significant
 bytes (up to 3).
 
 Since the table is so small, the slice table is faster to search.
+
+With --reverse, the constants used by the Hangul syllable algorithms (S_BASE,
+L_COUNT and so on) are emitted instead, along with a table for each of the
+leading consonants (NAME_L), vowels (NAME_V) and trailing consonants (NAME_T)
+that maps a Jamo short name to its codepoint. These can be used to turn a name
+like 'HANGUL SYLLABLE PWILH' back into a codepoint. Note that the leading
+consonant IEUNG has an empty short name.
+
+With --rust-match, a function is emitted instead of a table, which maps a
+codepoint to its short name with a match expression, e.g.,
+jamo_short_name(0x1100) returns Some(\"G\"). This avoids searching a table.
 ";
 
 const ABOUT_NAMES: &'static str = "\
@@ -115,7 +263,93 @@ names emits a table of all character names in the UCD, including aliases and
 names that are algorithmically generated such as Hangul syllables and
 ideographs. Flags can be provided to tweak this behavior.
 
+Algorithmically generated names other than Hangul syllables are taken from
+every range with a '*' pattern in extracted/DerivedName.txt. This covers CJK,
+Tangut and Khitan ideographs, Nushu characters and so on. If that file is
+missing, then only the CJK unified ideograph ranges known to ucd-util are used.
+
 This table maps character names to codepoints.
+
+With --emit-consts-only, only constants derived from the names are emitted,
+such as the length of the longest name, for consumers that generate names
+algorithmically themselves. The counts follow the other flags, e.g., the
+Hangul syllables aren't counted with --no-hangul, but the Hangul syllable and
+ideograph ranges are always emitted.
+
+With --fst-dir, --low-memory keeps peak memory use modest by never holding
+all names, or the FST, in memory at once. The FST is identical either way.
+";
+
+const ABOUT_DERIVED_NAME: &'static str = "\
+derived-name emits a table of the codepoint ranges whose names are derived
+algorithmically from a prefix and the codepoint's hexadecimal value, as listed
+with a trailing '*' in the UCD's extracted/DerivedName.txt file. For example,
+U+4E00 is in a range with the prefix \"CJK UNIFIED IDEOGRAPH-\".
+
+The table consists of (start, end, prefix) triples, sorted by codepoint. A
+name_of function is also emitted, which returns the name of any codepoint in
+one of the ranges. This avoids materializing names for every codepoint in
+the large ideograph ranges.
+";
+
+const ABOUT_NAMED_SEQUENCES: &'static str = "\
+named-sequences emits a table of the named character sequences listed in the
+UCD's NamedSequences.txt file, such as \"KEYCAP NUMBER SIGN\". Each name is
+mapped to its sequence of codepoints, and the table is sorted by name.
+
+With --flat-table, each sequence is written as a fixed size array padded with
+a sentinel, instead of as a slice.
+";
+
+const ABOUT_COLLATION: &'static str = "\
+collation emits the Default Unicode Collation Element Table (DUCET) of UTS #10,
+as read from allkeys.txt. This file is not part of the UCD, so it must be
+copied into the UCD directory from https://www.unicode.org/Public/UCA/.
+
+Each collation element is packed into a u32: the primary weight in the top 16
+bits, followed by 9 bits of secondary weight, 6 bits of tertiary weight and a
+bit that is set for variable elements. The elements of every entry are written
+to a single _ELEMENTS table, and each entry refers to its elements by their
+start and length there. Entries for single codepoints, contractions and the
+ranges given by @implicitweights lines are written to separate tables.
+
+With --no-contractions, only the entries for single codepoints are written.
+This is required with --binary.
+";
+
+const ABOUT_CANONICAL_COMPOSITION: &'static str = "\
+canonical-composition emits a table of the canonical compositions used by NFC,
+which map a pair of codepoints to the codepoint they compose. These are the
+canonical decompositions into pairs in UnicodeData.txt, minus the codepoints
+with the Full_Composition_Exclusion property in DerivedNormalizationProps.txt.
+Hangul syllables are composed algorithmically, and aren't included.
+
+The table is sorted by pair, and a compose(a, b) function that looks pairs up
+is emitted along with it. With --two-level, the table is instead indexed by
+the first codepoint of each pair, and a _PAIRS table holds the second
+codepoint and composition of each pair.
+";
+
+const ABOUT_MATRIX: &'static str = "\
+matrix runs a command once for each of the given UCD directories, writing the
+tables of each Unicode version to their own directory in OUT_DIR, e.g.,
+unicode_14_0 and unicode_15_1. This is useful for libraries that let the
+Unicode version be chosen at compile time. The command is given after --,
+without a UCD directory or an output directory. For example:
+
+    ucd-generate matrix OUT_DIR --ucd-dir ucd-14 --ucd-dir ucd-15.1 \\
+      -- general-category --enum
+
+Each version directory gets a mod.rs declaring its tables. OUT_DIR/mod.rs
+declares each version module behind a feature named after it, e.g.,
+unicode-15-1, and re-exports the newest version whose feature is enabled.
+
+With --threads, several versions are generated at once. The files written
+don't depend on the number of threads, and when more than one version fails,
+the error of the newest one is reported.
+
+matrix doesn't support --check or --config-header, either for itself or for
+the command.
 ";
 
 const ABOUT_TEST_UNICODE_DATA: &'static str = "\
@@ -133,12 +367,36 @@ property name.
 const ABOUT_PROPERTY_VALUES: &'static str = "\
 property-values emits a table of all property values and their aliases that map
 to a canonical property value.
+
+With --rust-enum, a Rust enum of canonical values is emitted for each property
+instead, e.g., GeneralCategory for General_Category. Each enum has an as_str
+method that returns the canonical value and implements FromStr, which accepts
+any alias of a value. Aliases are matched according to UAX44-LM3, so case,
+spaces, underscores, hyphens and an \"is\" prefix are ignored. Use --include to
+choose the properties to emit.
+
+With --emit-from-str, a FROM_STR table is emitted instead. It maps every name
+that may appear in \\p{...} to the path of the table generated for it, as a
+string, so that a small resolver can route lookups without its own list of
+aliases. Names are normalized according to UAX44-LM3, and are either
+property=value (e.g., gc=lu or script=greek) or a bare General_Category value,
+Script value or boolean property (e.g., lu, greek or alphabetic). Paths assume
+that each table was generated with its default name by one of the
+general-category, script, script-extension, property-bool, age, bidi-class,
+canonical-combining-class, joining-type, grapheme-cluster-break, word-break or
+sentence-break commands, e.g., general_category::UPPERCASE_LETTER or
+prop_list::ALPHABETIC.
 ";
 
 const ABOUT_CASE_FOLDING_SIMPLE: &'static str = "\
 case-folding emits a table of Simple case folding mappings from codepoint
 to codepoint. When codepoints are mapped according to this table, then case
 differences (according to Unicode) are eliminated.
+
+With --reverse, the table is inverted instead: it maps each codepoint that is
+the simple case folding of another to the sorted list of every codepoint that
+folds to it. Since the list is sorted, its first element is the smallest such
+codepoint, which is convenient for picking a canonical source.
 ";
 const ABOUT_CASE_MAPPING: &'static str = "\
 case-mapping emits case mapping tables, which map from a codepoint to a
@@ -147,20 +405,214 @@ text between lower, upper, and title cases.
 
 This command currently has no support for emitting the conditional case
 mapping data, and can only produce the unconditional mapping tables.
+
+With --simple --latin1-fast-path, each table is preceded by a dense array for
+the first 256 codepoints, which case conversion routines typically special
+case for speed. A function named after the table (e.g., lower) consults the
+array for those codepoints and binary searches the table otherwise. Codepoints
+without a mapping are mapped to themselves.
+
+With --titlecase-classes, a TITLECASE_CLASS table is also emitted for use by
+titlecasing routines. It maps codepoints to a bitset, where 1 means Cased and
+2 means Case_Ignorable. Codepoints that are neither are absent, so a missing
+codepoint has the value 0.
+
+With --reverse, each table is inverted and named with a _REVERSE suffix, e.g.,
+LOWER_REVERSE maps each lowercase codepoint to the sorted list of codepoints
+that lowercase to it. Mappings to more than one codepoint (which only come from
+SpecialCasing.txt) are left out, since they can't be inverted into a map keyed
+by codepoint.
+
+Use --include to emit only some of the tables, and --rename to give a table a
+different name, e.g., --rename LOWER=TO_LOWER. With --split-tables, each table
+is written to a file of its own in the output directory, named after the table
+(e.g., to_lower.rs), so that a consumer can pull in only the table it needs.
 ";
+
 const ABOUT_GRAPHEME_CLUSTER_BREAK: &'static str = "\
 grapheme-cluster-break emits the table of property values and their
 corresponding codepoints for the Grapheme_Cluster_Break property.
+
+With --utf8-trie, a state machine is emitted that classifies the codepoint at
+the start of a UTF-8 byte slice without decoding it. Codepoints that aren't
+listed (i.e., Other) have the value of NAME_NONE. See general-category for a
+description of the tables.
 ";
 
 const ABOUT_WORD_BREAK: &'static str = "\
 word-break emits the table of property values and their corresponding
 codepoints for the Word_Break property.
+
+With --utf8-trie, a state machine is emitted that classifies the codepoint at
+the start of a UTF-8 byte slice without decoding it, which is useful in the
+inner loop of a word segmenter. Codepoints that aren't listed (i.e., Other)
+have the value of NAME_NONE.
 ";
 
 const ABOUT_SENTENCE_BREAK: &'static str = "\
 sentence-break emits the table of property values and their corresponding
 codepoints for the Sentence_Break property.
+
+With --utf8-trie, the values are instead written as a state machine over the
+bytes of UTF-8, as with grapheme-cluster-break.
+";
+
+const ABOUT_INDIC_CONJUNCT_BREAK: &'static str = "\
+indic-conjunct-break emits the table of property values and their
+corresponding codepoints for the Indic_Conjunct_Break (InCB) property, which
+the grapheme cluster boundary rules use to keep Indic conjuncts together (rule
+GB9c). The values are read from DerivedCoreProperties.txt, which lists them as
+of Unicode 15.1. Codepoints that aren't listed have the value None.
+
+This supports the same output formats as grapheme-cluster-break.
+";
+
+const ABOUT_LINE_BREAK_PAIRS: &'static str = "\
+line-break-pairs emits the pair table of the line breaking algorithm in UAX #14,
+which says whether a break is allowed between each pair of Line_Break classes.
+It is derived from the rules in UAX #14, for the classes listed for the
+Line_Break property in PropertyValueAliases.txt, and is emitted along with an
+enum of the classes and an enum of what is allowed between them. Classes that
+aren't in the table, such as SP and those resolved by rule LB1, are left out.
+
+Only the parts of each rule that can be decided from a pair of classes are
+applied, just like in the example pair table of UAX #14.
+";
+
+const ABOUT_LINE_BREAK_TEST: &'static str = "\
+line-break-test emits the conformance tests for the line break algorithm from
+the UCD's auxiliary/LineBreakTest.txt file. Each test is the expected sequence
+of lines that its input is broken into.
+";
+
+const ABOUT_BIDI_TEST: &'static str = "\
+bidi-test emits the conformance tests for the bidi algorithm from the UCD's
+BidiTest.txt file. Tests are grouped by their expected levels and reordering,
+as they are in BidiTest.txt, and the input of each test is a sequence of bidi
+classes along with the paragraph directions to run the test with.
+";
+
+const ABOUT_BIDI_CHARACTER_TEST: &'static str = "\
+bidi-character-test emits the conformance tests for the bidi algorithm from
+the UCD's BidiCharacterTest.txt file. Each test consists of the input text,
+the paragraph direction, and the expected paragraph level, levels and
+reordering.
+";
+
+const ABOUT_NORMALIZATION_TEST: &'static str = "\
+normalization-test emits the conformance tests for the normalization forms from
+the UCD's NormalizationTest.txt file. Each test consists of the part of the
+file it's in, followed by the source string and its NFC, NFD, NFKC and NFKD
+forms. Every codepoint that isn't the source of a test in part 1 is unchanged
+by all four normalization forms.
+";
+
+const ABOUT_EXPLAIN: &'static str = "\
+explain prints the value of a property for a single codepoint, along with where
+that value came from: either the UCD file that lists it, or the default rule
+that applies to codepoints that aren't listed. When a default depends on other
+properties, such as Joining_Type depending on General_Category, then the values
+of those properties are explained too.
+
+The codepoint is written in hex, with or without a U+ prefix, e.g., U+0600. Use
+--list-properties to see which properties can be explained, along with the UCD
+files and other properties that each one is derived from. These are derived the
+same way as the tables written by the corresponding commands.
+";
+
+const ABOUT_COMPLETIONS: &'static str = "\
+completions prints a script that completes the subcommands and flags of
+ucd-generate in the given shell, e.g.,
+
+    ucd-generate completions bash > ucd-generate.bash
+";
+
+const ABOUT_MAN: &'static str = "\
+man prints the man page of ucd-generate, or of one of its subcommands, in roff.
+Pages are made from the same text as --help, so they're always up to date.
+
+With --out-dir, the pages of ucd-generate and of every subcommand are instead
+written to the given directory as ucd-generate.1, ucd-generate-age.1 and so on,
+e.g., for packaging.
+";
+
+const ABOUT_INFO: &'static str = "\
+info prints a summary of a UCD directory: the Unicode version it is for, which
+of the files that ucd-generate knows about are present (along with the number
+of rows in each), and which subcommands can be used with it. This is useful
+when working with partial or mirrored copies of the UCD.
+";
+
+const ABOUT_REGEN: &'static str = "\
+regen runs the commands that generated the given files again on the given UCD
+directory, which makes updating every generated file for a new Unicode version
+a single command. Each file must have been generated with --config-header,
+which records its command in a comment of the form:
+
+    // ucd-generate-config: {\"version\": ..., \"args\": [...], ...}
+
+Files written to the same directory by the same command are regenerated by
+running it once. A file that was written to stdout is only replaced if its
+contents changed. For example:
+
+    ucd-generate regen ucd-15.1 src/unicode_tables/*.rs
+
+With --diff, nothing is written. Instead, a unified diff of each file that
+would change is printed, and regen fails if any would.
+";
+
+const ABOUT_SCHEMA: &'static str = "\
+schema prints, as JSON, the shape of every kind of table that ucd-generate can
+emit, so that tools that consume the generated Rust source (e.g., in other
+languages) can adapt to it and detect breaking changes between releases.
+
+For each kind of table and each output format it supports (slice, chars,
+trie-set, group-by-plane and fst), this lists the files written and every
+top-level item in the Rust module: the name and type of each const or static,
+and the signature of each fn, enum or impl. These are found by actually writing
+an example table named EXAMPLE_TABLE (as if with --name EXAMPLE_TABLE), so item
+names derived from the table name are shown in terms of it, e.g.,
+EXAMPLE_TABLE_ENUM, or ExampleTable for a Rust enum. Enum values are named Alpha and Beta. The commands
+that can write each kind of table, depending on their flags, are listed too.
+
+The schema_version field only changes when the JSON printed by this command
+changes shape. The version field is the version of ucd-generate.
+";
+
+const ABOUT_GOLDEN_TESTS: &'static str = "\
+golden-tests emits a Rust file of tests for the boolean property tables written
+by property-bool, meant to be checked in alongside those tables (e.g., in the
+tests directory of the crate that contains them). Each test asserts that its
+table is sorted, that it has the expected number of ranges and codepoints, and
+that a handful of codepoints (some well known ones, along with the boundaries
+of a few of the table's ranges) are classified as they are in the UCD. This
+catches manual edits and partial regenerations of the tables.
+
+The path of the module containing the tables must be given with --module, e.g.,
+--module my_crate::tables::prop_list. The tests work whether or not the tables
+were written with --chars. Other output formats aren't supported.
+";
+
+const ABOUT_NFKC_CASEFOLD: &'static str = "\
+nfkc-casefold emits a table of the NFKC_Casefold mappings, as listed in the
+UCD's DerivedNormalizationProps.txt file. These mappings are used for caseless
+matching of identifiers (see UAX #31). Codepoints that are not in the table
+map to themselves. Some codepoints map to the empty string.
+
+With --simple, the NFKC_Simple_Casefold mappings are emitted instead, which
+are available in UCD 15.1 and newer.
+";
+
+const ABOUT_DO_NOT_EMIT: &'static str = "\
+do-not-emit emits tables of the sequences listed in the UCD's DoNotEmit.txt
+file (added in Unicode 16.0.0), each mapped to its preferred replacement. These
+are sequences that input methods and other text producers should not emit,
+and are useful for linters that suggest the preferred spelling of a string.
+
+One table of strings to strings is emitted for each type of entry, e.g.,
+DEPRECATED or PREFERRED_SPELLING. Use --list-types to see the types found in
+the file, and --include or --exclude to choose among them. With --combined, a
+single table of all included entries is emitted instead.
 ";
 
 /// Build a clap application.
@@ -178,12 +630,124 @@ pub fn app() -> App<'static, 'static> {
          cannot be written as a character literal, then it is \
          silently dropped.",
     );
+    let flag_annotate = Arg::with_name("annotate")
+        .long("annotate")
+        .conflicts_with_all(&["fst-dir", "binary", "trie-set", "c"])
+        .help(
+            "Follow each range in tables of codepoint ranges with a comment \
+             naming its first and last characters, for reviewing generated \
+             tables. Names are read from extracted/DerivedName.txt, or from \
+             UnicodeData.txt when it is missing.",
+        );
+    let flag_sample = Arg::with_name("sample")
+        .long("sample")
+        .takes_value(true)
+        .value_name("N|START..END")
+        .help(
+            "Write only a deterministic sample of each codepoint table, for \
+             documentation and examples. Either the first N ranges of each \
+             table are kept, or only the codepoints in the hexadecimal \
+             window START..END, e.g., 0..7F. The header notes that the \
+             tables are a sample.",
+        );
     let flag_combined = Arg::with_name("combined").long("combined").help(
         "Emit a single table with all included codepoint ranges. You might \
         want to use this option when checking if characters belong to a \
         subset of categories, since only one table will need to be checked. \
-        Searching the combined table should be simpler and more efficient.",
+        Searching the combined table should be simpler and more efficient. \
+        The combined table is the union of all included values, so a \
+        codepoint belonging to more than one of them appears once. Use \
+        --combined-disjoint to reject such codepoints instead.",
     );
+    let flag_combined_disjoint = Arg::with_name("combined-disjoint")
+        .long("combined-disjoint")
+        .requires("combined")
+        .help(
+            "When emitting a combined table, fail if any codepoint belongs \
+             to more than one of the included values.",
+        );
+    let flag_overlap = Arg::with_name("overlap")
+        .long("overlap")
+        .takes_value(true)
+        .possible_values(&["error", "first", "last"])
+        .help(
+            "How to pick the value of a codepoint that belongs to more than \
+             one of the included values, when emitting a table that maps \
+             each codepoint to one value. Values are ordered by name. The \
+             default is 'last'. 'error' fails instead, and also makes \
+             --combined reject such codepoints.",
+        );
+    let flag_overlap_priority = Arg::with_name("overlap-priority")
+        .long("overlap-priority")
+        .takes_value(true)
+        .value_name("VALUES")
+        .use_delimiter(true)
+        .help(
+            "A comma separated list of values, in order of decreasing \
+             priority, that win when a codepoint belongs to more than one \
+             value. A listed value always wins over an unlisted one. \
+             --overlap decides between unlisted values.",
+        );
+    let flag_enum_aliases = Arg::with_name("enum-aliases")
+        .long("enum-aliases")
+        .requires("enum")
+        .help(
+            "Along with --enum, make the NAME_ENUM table list abbreviated \
+             property values and emit a parallel NAME_ENUM_LONG table of \
+             long property values. A NAME_ENUM_HASH constant is also \
+             emitted, which changes whenever the order of the values does.",
+        );
+    let flag_enum_hash = Arg::with_name("enum-hash")
+        .long("enum-hash")
+        .takes_value(true)
+        .requires("enum-aliases")
+        .help(
+            "Pin the expected NAME_ENUM_HASH (in hexadecimal). The generated \
+             code fails to compile if the enum values have been reordered, \
+             added or removed since the hash was recorded.",
+        );
+    let flag_enum_non_exhaustive = Arg::with_name("enum-non-exhaustive")
+        .long("enum-non-exhaustive")
+        .requires("rust-enum")
+        .help("Mark the generated Rust enum as #[non_exhaustive].");
+    let flag_enum_discriminants = Arg::with_name("enum-discriminants")
+        .long("enum-discriminants")
+        .requires("rust-enum")
+        .help(
+            "Give every variant of the generated Rust enum an explicit \
+               discriminant.",
+        );
+    let flag_intern_strings =
+        Arg::with_name("intern-strings").long("intern-strings").help(
+            "Write the value names of NAME_ENUM tables as (start, len) spans \
+             into a string pool, POOL, that is written to strings.rs and \
+             shared with the tables that other commands write to the same \
+             directory. Existing strings in the pool are reused and new \
+             ones are appended, so tables written earlier stay valid. This \
+             requires --out-dir or --fst-dir.",
+        );
+    let flag_enum_manifest = Arg::with_name("enum-manifest")
+        .long("enum-manifest")
+        .takes_value(true)
+        .value_name("FILE")
+        .requires("rust-enum")
+        .help(
+            "Order the variants of the generated Rust enum by when they were \
+             first seen, according to FILE, instead of alphabetically. New \
+             variants are added to the end of the enum and recorded in FILE, \
+             which is created if it doesn't exist. With \
+             --enum-discriminants, each discriminant is the variant's \
+             position in FILE, so it stays the same across Unicode versions.",
+        );
+    let flag_enum_other = Arg::with_name("enum-other")
+        .long("enum-other")
+        .takes_value(true)
+        .value_name("NAME")
+        .help(
+            "With --enum or --rust-enum, group the codepoints of every value \
+             removed by --include or --exclude under a single value with \
+             this name, instead of leaving them out of the table.",
+        );
     let flag_short_names = Arg::with_name("short-names")
         .long("short-names")
         .help("Use the abbreviated property names in generated files.");
@@ -191,18 +755,331 @@ pub fn app() -> App<'static, 'static> {
         "Write codepoint sets as a compressed trie. \
          Code using this trie depends on the ucd_trie crate.",
     );
+    let flag_format = Arg::with_name("format")
+        .long("format")
+        .takes_value(true)
+        .possible_values(&["ranges", "trie-set", "auto", "css-unicode-range"])
+        .conflicts_with_all(&[
+            "trie-set",
+            "fst-dir",
+            "binary",
+            "c",
+            "regex-syntax",
+        ])
+        .help(
+            "How to write codepoint sets. 'ranges' writes sorted slices of \
+             ranges, which is the default, and 'trie-set' is the same as \
+             --trie-set. 'auto' chooses for each set, according to \
+             --optimize, and records the choice in a comment above it. \
+             'css-unicode-range' writes each set as a string in the syntax \
+             of the CSS unicode-range descriptor, e.g., \
+             \"U+0000-00FF, U+0131\", for font subsetting tools.",
+        );
+    let flag_optimize = Arg::with_name("optimize")
+        .long("optimize")
+        .takes_value(true)
+        .possible_values(&["size", "speed"])
+        .conflicts_with_all(&["trie-set", "fst-dir", "binary", "c"])
+        .help(
+            "What --format auto optimizes for: 'size' picks the format \
+             with the smallest table and 'speed' the one with the fewest \
+             reads per lookup. The default is 'size'. Giving this implies \
+             --format auto.",
+        );
+    let flag_profile = Arg::with_name("profile")
+        .long("profile")
+        .takes_value(true)
+        .possible_values(&["wasm"])
+        .conflicts_with_all(&[
+            "trie-set",
+            "format",
+            "optimize",
+            "fst-dir",
+            "binary",
+            "group-by-plane",
+            "emit-iter",
+            "c",
+            "regex-syntax",
+        ])
+        .help(
+            "Write tables suited to a particular kind of build. 'wasm' \
+             writes every set as a range of indices into one ALL_RANGES \
+             slice and the BY_NAME index as offsets, so that no table needs \
+             relocations, along with functions that only use core to look \
+             sets up. The total size of the tables is reported too.",
+        );
+    let flag_utf8_trie = Arg::with_name("utf8-trie")
+        .long("utf8-trie")
+        .conflicts_with_all(&[
+            "enum",
+            "rust-enum",
+            "combined",
+            "fst-dir",
+            "trie-set",
+            "group-by-plane",
+        ])
+        .help(
+            "Emit a state machine that maps the UTF-8 encoding of every \
+             codepoint directly to its value, along with a function that \
+             classifies the codepoint at the start of a byte slice without \
+             decoding it.",
+        );
+    let flag_validate_utf8_tables = Arg::with_name("validate-utf8-tables")
+        .long("validate-utf8-tables")
+        .requires("utf8-trie")
+        .help(
+            "Along with --utf8-trie, emit a test module that runs the \
+             generated function on a fixed pseudo-random sample of inputs, \
+             including invalid UTF-8, and compares each result with the one \
+             expected from the table the state machine was built from.",
+        );
+    let flag_regex_syntax = Arg::with_name("regex-syntax")
+        .long("regex-syntax")
+        .conflicts_with_all(&[
+            "enum",
+            "rust-enum",
+            "combined",
+            "fst-dir",
+            "trie-set",
+            "group-by-plane",
+            "binary",
+            "c",
+            "utf8-trie",
+        ])
+        .help(
+            "Lay out tables the way regex-syntax's unicode_tables module \
+             does, so that they can replace the tables it vendors. This \
+             implies --chars.",
+        );
+    let flag_emit_iter = Arg::with_name("emit-iter")
+        .long("emit-iter")
+        .conflicts_with_all(&["trie-set", "fst-dir"])
+        .help(
+            "Follow each set with a NAME_iter function that lazily iterates \
+             over the characters in the set, in order.",
+        );
+    let flag_group_by_plane = Arg::with_name("group-by-plane")
+        .long("group-by-plane")
+        .conflicts_with_all(&["trie-set", "fst-dir"])
+        .help(
+            "Split each table of codepoint ranges into one table per Unicode \
+             plane, e.g., NAME_BMP and NAME_SMP, along with a NAME_PLANES \
+             index of (plane, table) pairs. Tables for planes that are never \
+             referred to can then be eliminated as dead code.",
+        );
+    let flag_by_name_fn = Arg::with_name("by-name-fn")
+        .long("by-name-fn")
+        .conflicts_with("fst-dir")
+        .help(
+            "Follow the BY_NAME index, which is sorted by name, with a \
+             set_by_name function that looks up a set by a name that is \
+             matched loosely (as in UAX44-LM3), e.g., Upper_Case finds \
+             Uppercase.",
+        );
+    let flag_case_fold_keys = Arg::with_name("case-fold-keys")
+        .long("case-fold-keys")
+        .takes_value(true)
+        .possible_values(&["ascii", "simple"])
+        .conflicts_with("fst-dir")
+        .help(
+            "Case fold the keys of the table, and emit a function that looks \
+             up keys case insensitively without allocating. 'ascii' \
+             lowercases ASCII letters, while 'simple' uses the simple case \
+             folding in CaseFolding.txt. It is an error for keys with \
+             different values to fold to the same key.",
+        );
+    let flag_c = Arg::with_name("c")
+        .long("c")
+        .requires("trie-set")
+        .conflicts_with("fst-dir")
+        .help(
+            "Write C source code instead of Rust source code. This is only \
+             supported for --trie-set. The output defines a \
+             `struct ucd_trie_set` for each set, along with a \
+             `ucd_trie_set_contains` function for querying them.",
+        );
+    let flag_c_uint_type = Arg::with_name("c-uint-type")
+        .long("c-uint-type")
+        .takes_value(true)
+        .possible_values(&["exact", "least"])
+        .value_name("KIND")
+        .requires("c")
+        .help(
+            "Set which fixed-width integer types the C output uses. exact \
+             (the default) uses the exact-width types, e.g., uint64_t, and \
+             least uses the minimum-width types, e.g., uint_least64_t, which \
+             every C99 implementation has. Files included in the same \
+             translation unit must use the same kind of types.",
+        );
+    let flag_c_enum =
+        Arg::with_name("c-enum").long("c-enum").requires("c").help(
+            "When writing C, also write an enum with a constant for each \
+             value of the property, which is the position of its set in \
+             BY_NAME.",
+        );
     let flag_fst_dir = Arg::with_name("fst-dir")
         .long("fst-dir")
         .help("Emit the table as a FST in Rust source code.")
         .takes_value(true);
+    let flag_binary = Arg::with_name("binary")
+        .long("binary")
+        .requires("out-dir")
+        .conflicts_with_all(&[
+            "fst-dir",
+            "trie-set",
+            "group-by-plane",
+            "chars",
+            "c",
+        ])
+        .help(
+            "Write sets and maps of codepoints as packed little endian \
+             binary files in the output directory, along with typed views \
+             of them. The record types derive the zerocopy and bytemuck \
+             traits when the including crate enables the feature of the \
+             same name.",
+        );
+    let flag_rust_match = Arg::with_name("rust-match")
+        .long("rust-match")
+        .conflicts_with_all(&["fst-dir", "binary", "latin1-fast-path"])
+        .help("Emit a function that uses a match to map between codepoints.");
+    let flag_latin1_fast_path = Arg::with_name("latin1-fast-path")
+        .long("latin1-fast-path")
+        .conflicts_with_all(&["fst-dir", "binary"])
+        .help(
+            "Emit each codepoint to codepoint map as a dense 256 entry array \
+             for U+0000..U+00FF, a table for the remaining codepoints and a \
+             function that looks up a codepoint in either.",
+        );
+    let flag_verify_fst = Arg::with_name("verify-fst")
+        .long("verify-fst")
+        .requires("fst-dir")
+        .help(
+            "After writing each FST, read it back from disk and check that \
+             it contains every key of the FST that was built, with the same \
+             value.",
+        );
+    let flag_fst_max_file_size = Arg::with_name("fst-max-file-size")
+        .long("fst-max-file-size")
+        .takes_value(true)
+        .value_name("BYTES")
+        .requires("fst-dir")
+        .help(
+            "Split each FST that would be bigger than BYTES into several \
+             files, each holding a contiguous range of keys. The table is \
+             still a single lazy static, but it holds an array of FSTs, and a \
+             NAME_shard function returns the FST that a given key belongs to.",
+        );
+    let flag_loader = Arg::with_name("loader")
+        .long("loader")
+        .takes_value(true)
+        .possible_values(&["once_cell", "lazy_static", "oncelock", "fn"])
+        .value_name("LOADER")
+        .requires("fst-dir")
+        .help(
+            "Set how the generated code loads each FST written with \
+             --fst-dir. once_cell (the default, as before this flag existed) \
+             and lazy_static emit lazy statics that need the crate of the \
+             same name. oncelock emits a function that loads the FST \
+             into a std::sync::OnceLock once, and fn emits a function that \
+             returns a new FST each time, which is cheap since it borrows \
+             the embedded bytes. Both functions are named after the table.",
+        );
+    let flag_fst_hash_names = Arg::with_name("fst-hash-names")
+        .long("fst-hash-names")
+        .requires("fst-dir")
+        .help(
+            "Suffix the file name of each FST written to --fst-dir with a \
+             hash of its contents. This avoids collisions when the FSTs of \
+             several commands are written to the same directory.",
+        );
+    let flag_force_write =
+        Arg::with_name("force-write").long("force-write").help(
+            "Rewrite every file in --fst-dir or --out-dir, even those whose \
+             contents have not changed. By default, unchanged files are left \
+             alone so that their modification times are preserved.",
+        );
+    let flag_out_dir = Arg::with_name("out-dir")
+        .long("out-dir")
+        .takes_value(true)
+        .conflicts_with("fst-dir")
+        .help(
+            "Write the Rust source code to a file named after the module in \
+             the given directory, instead of to stdout.",
+        );
+    let flag_emit = Arg::with_name("emit")
+        .long("emit")
+        .takes_value(true)
+        .possible_values(&["single", "mod+data"])
+        .default_value("single")
+        .help(
+            "How to lay out the generated Rust source code. 'single' writes \
+             everything to one file. 'mod+data' moves the entries of each \
+             data table into its own NAME.data.rs file, which the module \
+             pulls in with include!, so that the module only contains types, \
+             constants and functions. 'mod+data' requires --out-dir or \
+             --fst-dir.",
+        );
+    let flag_split_min_len = Arg::with_name("split-min-len")
+        .long("split-min-len")
+        .takes_value(true)
+        .help(
+            "With --emit mod+data, only move tables with at least this many \
+             entries into data files. Smaller tables are written inline. \
+             Defaults to 0.",
+        );
+    let flag_max_value_len = Arg::with_name("max-value-len")
+        .long("max-value-len")
+        .takes_value(true)
+        .help(
+            "Fail if any string value is longer than the given number of \
+             bytes. With --fst-dir, values longer than 8 bytes are stored \
+             in a separate NAME_VALUES table that the FST maps into.",
+        );
     let flag_flat_table =
         Arg::with_name("flat-table").long("flat-table").help(
             "When emitting a map of a single codepoint to multiple \
              codepoints, emit entries as `(u32, [u32; 3])` instead of as \
              `(u32, &[u32])` (replacing `u32` with `char` if `--chars` is \
-             passed). Conceptually unoccupied indices of the array will \
-             contain `!0u32` (for u32) or `\\u{0}` (for `char`).",
+             passed). If any mapping has more than 3 codepoints, the arrays \
+             are as long as the longest mapping instead. Conceptually \
+             unoccupied indices of the array will contain `!0u32` (for u32) \
+             or `\\u{0}` (for `char`). The ucd-util crate's case_lookup \
+             functions look up entries with the padding trimmed off.",
+        );
+    let flag_flat_table_pad = Arg::with_name("flat-table-pad")
+        .long("flat-table-pad")
+        .takes_value(true)
+        .value_name("HEX")
+        .requires("flat-table")
+        .help(
+            "The hexadecimal codepoint used to fill unoccupied indices of \
+             --flat-table arrays, instead of `!0u32` (or `\\u{0}` for \
+             `char`). It is an error if the sentinel occurs in any mapping.",
         );
+    let flag_flat_table_trim = Arg::with_name("flat-table-trim")
+        .long("flat-table-trim")
+        .requires("flat-table")
+        .help(
+            "Along with each --flat-table, emit its padding sentinel as a \
+             `NAME_PAD` constant and a `name_trim` function that returns \
+             the occupied prefix of an entry's array.",
+        );
+    let flag_emit_len_consts =
+        Arg::with_name("emit-len-consts").long("emit-len-consts").help(
+            "Emit a `NAME_LEN: usize` constant after each slice table with \
+             the number of entries in that table. When more than one table \
+             is emitted, a `TABLE_LENS` slice of `(name, length)` pairs is \
+             also emitted.",
+        );
+    let flag_validate_against_derived =
+        Arg::with_name("validate-against-derived")
+            .long("validate-against-derived")
+            .help(
+                "Cross-check the generated values against the corresponding \
+                 file in the UCD's extracted directory, and fail if any \
+                 codepoint disagrees. If the extracted file is missing, a \
+                 warning is printed and no check is done.",
+            );
     let ucd_dir = Arg::with_name("ucd-dir")
         .required(true)
         .help("Directory containing the Unicode character database files.");
@@ -214,12 +1091,62 @@ pub fn app() -> App<'static, 'static> {
         .about("Create the Bidi_Class property tables.")
         .before_help(ABOUT_BIDI_CLASS)
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
         .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
         .arg(flag_name("BIDI_CLASS"))
         .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
         .arg(flag_trie_set.clone())
+        .arg(flag_format.clone())
+        .arg(flag_optimize.clone())
+        .arg(flag_profile.clone())
+        .arg(flag_emit_iter.clone())
+        .arg(flag_group_by_plane.clone())
+        .arg(flag_by_name_fn.clone())
+        .arg(flag_c.clone())
+        .arg(flag_c_uint_type.clone())
+        .arg(flag_c_enum.clone())
         .arg(flag_short_names.clone())
         .arg(flag_combined.clone())
+        .arg(flag_combined_disjoint.clone())
+        .arg(flag_overlap.clone())
+        .arg(flag_overlap_priority.clone())
+        .arg(flag_enum_aliases.clone())
+        .arg(flag_enum_hash.clone())
+        .arg(flag_validate_against_derived.clone())
+        .arg(
+            Arg::with_name("from-derived")
+                .long("from-derived")
+                .conflicts_with_all(&[
+                    "from-unicode-data",
+                    "validate-against-derived",
+                ])
+                .help(
+                    "Read the values from extracted/DerivedBidiClass.txt, \
+                     and fail if it doesn't exist. This is the default when \
+                     it does.",
+                ),
+        )
+        .arg(
+            Arg::with_name("from-unicode-data")
+                .long("from-unicode-data")
+                .help(
+                    "Derive the values from UnicodeData.txt and the rules \
+                     for default values, even if \
+                     extracted/DerivedBidiClass.txt exists.",
+                ),
+        )
+        .arg(flag_intern_strings.clone())
         .arg(
             Arg::with_name("enum").long("enum").help(
                 "Emit a single table that maps codepoints to bidi class.",
@@ -228,6 +1155,9 @@ pub fn app() -> App<'static, 'static> {
         .arg(Arg::with_name("rust-enum").long("rust-enum").help(
             "Emit a Rust enum and a table that maps codepoints to bidi class.",
         ))
+        .arg(flag_enum_non_exhaustive.clone())
+        .arg(flag_enum_discriminants.clone())
+        .arg(flag_enum_manifest.clone())
         .arg(
             Arg::with_name("list-classes")
                 .long("list-classes")
@@ -241,12 +1171,37 @@ pub fn app() -> App<'static, 'static> {
             .about("Create Unicode Bidi Mirroring Glyph table.")
             .before_help(ABOUT_BIDI_MIRRORING_GLYPH)
             .arg(ucd_dir.clone())
+            .arg(flag_emit_len_consts.clone())
+            .arg(flag_out_dir.clone())
+            .arg(flag_emit.clone())
+            .arg(flag_split_min_len.clone())
             .arg(flag_fst_dir.clone())
+            .arg(flag_verify_fst.clone())
+            .arg(flag_loader.clone())
+            .arg(flag_binary.clone())
+            .arg(flag_fst_hash_names.clone())
+            .arg(flag_fst_max_file_size.clone())
+            .arg(flag_force_write.clone())
             .arg(flag_name("BIDI_MIRRORING_GLYPH"))
             .arg(flag_chars.clone())
+            .arg(flag_annotate.clone())
+            .arg(flag_sample.clone())
             .arg(flag_trie_set.clone())
-            .arg(Arg::with_name("rust-match").long("rust-match").help(
-                "Emit a function that uses a match to map between codepoints.",
+            .arg(flag_format.clone())
+            .arg(flag_optimize.clone())
+            .arg(flag_profile.clone())
+            .arg(flag_emit_iter.clone())
+            .arg(flag_group_by_plane.clone())
+            .arg(flag_c.clone())
+            .arg(flag_c_uint_type.clone())
+            .arg(flag_rust_match.clone())
+            .arg(flag_latin1_fast_path.clone())
+            .arg(Arg::with_name("no-best-fit").long("no-best-fit").help(
+                "Exclude mappings that BidiMirroring.txt marks as [BEST FIT].",
+            ))
+            .arg(Arg::with_name("without-glyph").long("without-glyph").help(
+                "Also emit a table of the Bidi_Mirrored=Yes codepoints that \
+                 have no mapping, named NAME_WITHOUT_GLYPH.",
             ));
     let cmd_canonical_combining_class =
         SubCommand::with_name("canonical-combining-class")
@@ -256,10 +1211,37 @@ pub fn app() -> App<'static, 'static> {
             .about("Create the Canonical_Combining_Class table.")
             .before_help(ABOUT_CANONICAL_COMBINING_CLASS)
             .arg(ucd_dir.clone())
+            .arg(flag_emit_len_consts.clone())
+            .arg(flag_out_dir.clone())
+            .arg(flag_emit.clone())
+            .arg(flag_split_min_len.clone())
             .arg(flag_fst_dir.clone())
+            .arg(flag_verify_fst.clone())
+            .arg(flag_loader.clone())
+            .arg(flag_binary.clone())
+            .arg(flag_fst_hash_names.clone())
+            .arg(flag_fst_max_file_size.clone())
+            .arg(flag_force_write.clone())
             .arg(flag_name("CANONICAL_COMBINING_CLASS"))
             .arg(flag_chars.clone())
+            .arg(flag_annotate.clone())
+            .arg(flag_sample.clone())
             .arg(flag_trie_set.clone())
+            .arg(flag_format.clone())
+            .arg(flag_optimize.clone())
+            .arg(flag_profile.clone())
+            .arg(flag_emit_iter.clone())
+            .arg(flag_group_by_plane.clone())
+            .arg(flag_by_name_fn.clone())
+            .arg(flag_c.clone())
+            .arg(flag_c_uint_type.clone())
+            .arg(flag_c_enum.clone())
+            .arg(flag_combined.clone())
+            .arg(flag_combined_disjoint.clone())
+            .arg(flag_overlap.clone())
+            .arg(flag_overlap_priority.clone())
+            .arg(flag_validate_against_derived.clone())
+            .arg(flag_intern_strings.clone())
             .arg(Arg::with_name("enum").long("enum").help(
                 "Emit a single table that maps codepoints to canonical \
                  combining class.",
@@ -268,6 +1250,27 @@ pub fn app() -> App<'static, 'static> {
                 "Emit a Rust enum and a table that maps codepoints to \
                  canonical combining class.",
             ))
+            .arg(flag_enum_non_exhaustive.clone())
+            .arg(flag_enum_discriminants.clone())
+            .arg(flag_enum_manifest.clone())
+            .arg(
+                Arg::with_name("bmp-index")
+                    .long("bmp-index")
+                    .conflicts_with_all(&[
+                        "enum",
+                        "rust-enum",
+                        "combined",
+                        "fst-dir",
+                        "trie-set",
+                    ])
+                    .help(
+                        "Emit a direct index of the combining class of every \
+                         codepoint in the BMP, a table of ranges for the \
+                         remaining codepoints and a function that looks up \
+                         the class of a codepoint in constant time for the \
+                         BMP.",
+                    ),
+            )
             .arg(Arg::with_name("list-classes").long("list-classes").help(
                 "List all of the canonical combining class names with \
                  abbreviations.",
@@ -279,11 +1282,40 @@ pub fn app() -> App<'static, 'static> {
         .about("Create the General_Category property tables.")
         .before_help(ABOUT_GENERAL_CATEGORY)
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
         .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
         .arg(flag_name("GENERAL_CATEGORY"))
         .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
+        .arg(flag_regex_syntax.clone())
         .arg(flag_trie_set.clone())
+        .arg(flag_format.clone())
+        .arg(flag_optimize.clone())
+        .arg(flag_profile.clone())
+        .arg(flag_emit_iter.clone())
+        .arg(flag_group_by_plane.clone())
+        .arg(flag_by_name_fn.clone())
+        .arg(flag_c.clone())
+        .arg(flag_c_uint_type.clone())
+        .arg(flag_c_enum.clone())
         .arg(flag_combined.clone())
+        .arg(flag_combined_disjoint.clone())
+        .arg(flag_overlap.clone())
+        .arg(flag_overlap_priority.clone())
+        .arg(flag_enum_aliases.clone())
+        .arg(flag_enum_hash.clone())
+        .arg(flag_enum_other.clone())
+        .arg(flag_intern_strings.clone())
         .arg(
             Arg::with_name("enum").long("enum").help(
                 "Emit a single table that maps codepoints to categories.",
@@ -292,14 +1324,23 @@ pub fn app() -> App<'static, 'static> {
         .arg(Arg::with_name("rust-enum").long("rust-enum").help(
             "Emit a Rust enum and a table that maps codepoints to categories.",
         ))
+        .arg(flag_utf8_trie.clone())
+        .arg(flag_validate_utf8_tables.clone())
+        .arg(flag_enum_non_exhaustive.clone())
+        .arg(flag_enum_discriminants.clone())
+        .arg(flag_enum_manifest.clone())
         .arg(Arg::with_name("include").long("include").takes_value(true).help(
             "A comma separated list of categories to include. \
-             When absent, all categories are included.",
+             When absent, all categories are included. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
         ))
         .arg(Arg::with_name("exclude").long("exclude").takes_value(true).help(
             "A comma separated list of categories to exclude. \
              When absent, no categories are excluded. This overrides \
-             categories specified with the --include flag.",
+             categories specified with the --include flag. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
         ))
         .arg(
             Arg::with_name("list-categories")
@@ -313,11 +1354,40 @@ pub fn app() -> App<'static, 'static> {
         .about("Create the Script property tables.")
         .before_help(ABOUT_SCRIPT)
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
         .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
         .arg(flag_name("SCRIPT"))
         .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
+        .arg(flag_regex_syntax.clone())
         .arg(flag_trie_set.clone())
+        .arg(flag_format.clone())
+        .arg(flag_optimize.clone())
+        .arg(flag_profile.clone())
+        .arg(flag_emit_iter.clone())
+        .arg(flag_group_by_plane.clone())
+        .arg(flag_by_name_fn.clone())
+        .arg(flag_c.clone())
+        .arg(flag_c_uint_type.clone())
+        .arg(flag_c_enum.clone())
         .arg(flag_combined.clone())
+        .arg(flag_combined_disjoint.clone())
+        .arg(flag_overlap.clone())
+        .arg(flag_overlap_priority.clone())
+        .arg(flag_enum_aliases.clone())
+        .arg(flag_enum_hash.clone())
+        .arg(flag_enum_other.clone())
+        .arg(flag_intern_strings.clone())
         .arg(
             Arg::with_name("enum")
                 .long("enum")
@@ -326,14 +1396,21 @@ pub fn app() -> App<'static, 'static> {
         .arg(Arg::with_name("rust-enum").long("rust-enum").help(
             "Emit a Rust enum and a table that maps codepoints to scripts.",
         ))
+        .arg(flag_enum_non_exhaustive.clone())
+        .arg(flag_enum_discriminants.clone())
+        .arg(flag_enum_manifest.clone())
         .arg(Arg::with_name("include").long("include").takes_value(true).help(
             "A comma separated list of scripts to include. \
-             When absent, all scripts are included.",
+             When absent, all scripts are included. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
         ))
         .arg(Arg::with_name("exclude").long("exclude").takes_value(true).help(
             "A comma separated list of scripts to exclude. \
              When absent, no scripts are excluded. This overrides \
-             scripts specified with the --include flag.",
+             scripts specified with the --include flag. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
         ))
         .arg(
             Arg::with_name("list-scripts")
@@ -347,19 +1424,70 @@ pub fn app() -> App<'static, 'static> {
         .about("Create the Script_Extension property tables.")
         .before_help(ABOUT_SCRIPT_EXTENSION)
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
         .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
         .arg(flag_name("SCRIPT_EXTENSION"))
         .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
+        .arg(flag_regex_syntax.clone())
         .arg(flag_trie_set.clone())
+        .arg(flag_format.clone())
+        .arg(flag_optimize.clone())
+        .arg(flag_profile.clone())
+        .arg(flag_emit_iter.clone())
+        .arg(flag_group_by_plane.clone())
+        .arg(flag_by_name_fn.clone())
+        .arg(flag_c.clone())
+        .arg(flag_c_uint_type.clone())
+        .arg(flag_c_enum.clone())
+        .arg(flag_combined.clone())
+        .arg(flag_combined_disjoint.clone())
+        .arg(flag_overlap.clone())
+        .arg(flag_overlap_priority.clone())
+        .arg(flag_intern_strings.clone())
+        .arg(
+            Arg::with_name("bitset-enum")
+                .long("bitset-enum")
+                .conflicts_with_all(&["include", "exclude", "combined"])
+                .help(
+                    "Emit a single table that maps codepoints to sets of \
+                     indices into a list of scripts shared with \
+                     'script --enum'.",
+                ),
+        )
+        .arg(
+            Arg::with_name("per-script")
+                .long("per-script")
+                .requires("bitset-enum")
+                .help(
+                    "Along with --bitset-enum, also emit a table of codepoint \
+                     ranges for each script, as without --bitset-enum, for \
+                     code that looks up one script at a time.",
+                ),
+        )
         .arg(Arg::with_name("include").long("include").takes_value(true).help(
             "A comma separated list of script extensions to include. \
-             When absent, all scripts extensions are included.",
+             When absent, all scripts extensions are included. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
         ))
         .arg(Arg::with_name("exclude").long("exclude").takes_value(true).help(
             "A comma separated list of script extensions to exclude. \
              When absent, no script extensions are excluded. This \
              overrides script extensions specified with the --include \
-             flag.",
+             flag. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
         ))
         .arg(
             Arg::with_name("list-script-extensions")
@@ -376,13 +1504,60 @@ pub fn app() -> App<'static, 'static> {
         .about("Create Unicode Age tables.")
         .before_help(ABOUT_AGE)
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
         .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
+        .arg(flag_name("AGE"))
         .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
+        .arg(flag_regex_syntax.clone())
         .arg(flag_trie_set.clone())
+        .arg(flag_format.clone())
+        .arg(flag_optimize.clone())
+        .arg(flag_profile.clone())
+        .arg(flag_emit_iter.clone())
+        .arg(flag_group_by_plane.clone())
+        .arg(flag_by_name_fn.clone())
+        .arg(flag_c.clone())
+        .arg(flag_c_uint_type.clone())
+        .arg(flag_c_enum.clone())
+        .arg(flag_combined.clone())
+        .arg(flag_combined_disjoint.clone())
+        .arg(flag_overlap.clone())
+        .arg(flag_overlap_priority.clone())
+        .arg(Arg::with_name("include").long("include").takes_value(true).help(
+            "A comma separated list of ages to include, such as \
+             'V1_1,2.0'. When absent, all ages are included. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
+        ))
+        .arg(Arg::with_name("exclude").long("exclude").takes_value(true).help(
+            "A comma separated list of ages to exclude. \
+             When absent, no ages are excluded. This overrides \
+             ages specified with the --include flag. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
+        ))
         .arg(Arg::with_name("list-properties").long("list-properties").help(
-            "List the properties that can be generated with this \
-             command.",
-        ));
+            "List the ages that can be generated with this command, along \
+             with their version numbers and the number of codepoints \
+             added in each.",
+        ))
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .requires("list-properties")
+                .help("Print the list of ages as JSON."),
+        );
     let cmd_joining_type =
         SubCommand::with_name("joining-type")
             .author(clap::crate_authors!())
@@ -391,18 +1566,98 @@ pub fn app() -> App<'static, 'static> {
             .about("Create the Joining_Type property tables.")
             .before_help(ABOUT_JOINING_TYPE)
             .arg(ucd_dir.clone())
+            .arg(flag_emit_len_consts.clone())
+            .arg(flag_out_dir.clone())
+            .arg(flag_emit.clone())
+            .arg(flag_split_min_len.clone())
             .arg(flag_fst_dir.clone())
+            .arg(flag_verify_fst.clone())
+            .arg(flag_loader.clone())
+            .arg(flag_binary.clone())
+            .arg(flag_fst_hash_names.clone())
+            .arg(flag_fst_max_file_size.clone())
+            .arg(flag_force_write.clone())
             .arg(flag_name("JOINING_TYPE"))
             .arg(flag_chars.clone())
+            .arg(flag_annotate.clone())
+            .arg(flag_sample.clone())
             .arg(flag_trie_set.clone())
+            .arg(flag_format.clone())
+            .arg(flag_optimize.clone())
+            .arg(flag_profile.clone())
+            .arg(flag_emit_iter.clone())
+            .arg(flag_group_by_plane.clone())
+            .arg(flag_by_name_fn.clone())
+            .arg(flag_c.clone())
+            .arg(flag_c_uint_type.clone())
+            .arg(flag_c_enum.clone())
             .arg(flag_combined.clone())
+            .arg(flag_combined_disjoint.clone())
+            .arg(flag_overlap.clone())
+            .arg(flag_overlap_priority.clone())
+            .arg(flag_enum_aliases.clone())
+            .arg(flag_enum_hash.clone())
+            .arg(flag_validate_against_derived.clone())
+            .arg(flag_intern_strings.clone())
             .arg(Arg::with_name("enum").long("enum").help(
                 "Emit a single table that maps codepoints to joining type.",
             ))
             .arg(Arg::with_name("rust-enum").long("rust-enum").help(
                 "Emit a Rust enum and a table that maps codepoints to \
                  joining type.",
-            ));
+            ))
+            .arg(flag_enum_non_exhaustive.clone())
+            .arg(flag_enum_discriminants.clone())
+            .arg(flag_enum_manifest.clone());
+    let cmd_joining_group = SubCommand::with_name("joining-group")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create the Joining_Group property tables.")
+        .before_help(ABOUT_JOINING_GROUP)
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
+        .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
+        .arg(flag_name("JOINING_GROUP"))
+        .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
+        .arg(flag_trie_set.clone())
+        .arg(flag_format.clone())
+        .arg(flag_optimize.clone())
+        .arg(flag_profile.clone())
+        .arg(flag_emit_iter.clone())
+        .arg(flag_group_by_plane.clone())
+        .arg(flag_by_name_fn.clone())
+        .arg(flag_c.clone())
+        .arg(flag_c_uint_type.clone())
+        .arg(flag_c_enum.clone())
+        .arg(flag_combined.clone())
+        .arg(flag_combined_disjoint.clone())
+        .arg(flag_overlap.clone())
+        .arg(flag_overlap_priority.clone())
+        .arg(flag_enum_aliases.clone())
+        .arg(flag_enum_hash.clone())
+        .arg(flag_intern_strings.clone())
+        .arg(Arg::with_name("enum").long("enum").help(
+            "Emit a single table that maps codepoints to joining group.",
+        ))
+        .arg(Arg::with_name("rust-enum").long("rust-enum").help(
+            "Emit a Rust enum and a table that maps codepoints to \
+                 joining type.",
+        ))
+        .arg(flag_enum_non_exhaustive.clone())
+        .arg(flag_enum_discriminants.clone())
+        .arg(flag_enum_manifest.clone());
     let cmd_prop_bool = SubCommand::with_name("property-bool")
         .author(clap::crate_authors!())
         .version(clap::crate_version!())
@@ -410,21 +1665,127 @@ pub fn app() -> App<'static, 'static> {
         .about("Create boolean property tables.")
         .before_help(ABOUT_PROP_BOOL)
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
         .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
+        .arg(flag_name("PROPERTY_BOOL"))
         .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
+        .arg(flag_regex_syntax.clone())
         .arg(flag_trie_set.clone())
+        .arg(flag_format.clone())
+        .arg(flag_optimize.clone())
+        .arg(flag_profile.clone())
+        .arg(flag_emit_iter.clone())
+        .arg(flag_group_by_plane.clone())
+        .arg(flag_by_name_fn.clone())
+        .arg(flag_c.clone())
+        .arg(flag_c_uint_type.clone())
+        .arg(flag_combined.clone())
+        .arg(flag_combined_disjoint.clone())
+        .arg(flag_overlap.clone())
+        .arg(flag_overlap_priority.clone())
         .arg(Arg::with_name("include").long("include").takes_value(true).help(
             "A comma separated list of properties to include. \
-             When absent, all available properties are included.",
+             When absent, all available properties are included. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
         ))
         .arg(Arg::with_name("exclude").long("exclude").takes_value(true).help(
             "A comma separated list of properties to exclude. \
              When absent, no properties are excluded. This overrides \
-             properties specified with the --include flag.",
+             properties specified with the --include flag. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
         ))
         .arg(Arg::with_name("list-properties").long("list-properties").help(
             "List the properties that can be generated with this \
              command.",
+        ))
+        .arg(
+            Arg::with_name("changes-when")
+                .long("changes-when")
+                .conflicts_with_all(&[
+                    "include",
+                    "exclude",
+                    "combined",
+                    "trie-set",
+                    "c",
+                    "group-by-plane",
+                ])
+                .help(
+                    "Emit a single table that maps codepoints to a bitmask of \
+                     their Changes_When_* properties. From the lowest bit, \
+                     these are Lowercased, Uppercased, Titlecased, \
+                     Casefolded, Casemapped and NFKC_Casefolded. A constant \
+                     is emitted for each bit, e.g., CHANGES_WHEN_LOWERCASED.",
+                ),
+        );
+    let cmd_emoji = SubCommand::with_name("emoji")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create tables for emoji properties and sequences.")
+        .before_help(ABOUT_EMOJI)
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
+        .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
+        .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
+        .arg(flag_trie_set.clone())
+        .arg(flag_format.clone())
+        .arg(flag_optimize.clone())
+        .arg(flag_profile.clone())
+        .arg(flag_emit_iter.clone())
+        .arg(flag_group_by_plane.clone())
+        .arg(flag_by_name_fn.clone())
+        .arg(flag_c.clone())
+        .arg(flag_c_uint_type.clone())
+        .arg(Arg::with_name("include").long("include").takes_value(true).help(
+            "A comma separated list of properties to include. \
+             When absent, all available properties are included. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
+        ))
+        .arg(Arg::with_name("exclude").long("exclude").takes_value(true).help(
+            "A comma separated list of properties to exclude. \
+             When absent, no properties are excluded. This overrides \
+             properties specified with the --include flag. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
+        ))
+        .arg(Arg::with_name("list-properties").long("list-properties").help(
+            "List the properties that can be generated with this \
+             command.",
+        ))
+        .arg(Arg::with_name("sequences").long("sequences").help(
+            "Also emit a table of strings for each emoji sequence \
+             property, from emoji-sequences.txt and \
+             emoji-zwj-sequences.txt.",
+        ))
+        .arg(Arg::with_name("flag-pairs").long("flag-pairs").help(
+            "Also emit a table of the pairs of Regional Indicators of \
+             every RGI flag emoji, with a function that tests whether a \
+             pair is a flag.",
         ));
     let cmd_perl_word = SubCommand::with_name("perl-word")
         .author(clap::crate_authors!())
@@ -433,10 +1794,148 @@ pub fn app() -> App<'static, 'static> {
         .about("Create a boolean property table for the \\w character class.")
         .before_help(ABOUT_PERL_WORD)
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
         .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
         .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
+        .arg(flag_regex_syntax.clone())
         .arg(flag_trie_set.clone())
+        .arg(flag_format.clone())
+        .arg(flag_optimize.clone())
+        .arg(flag_profile.clone())
+        .arg(flag_emit_iter.clone())
+        .arg(flag_group_by_plane.clone())
+        .arg(flag_c.clone())
+        .arg(flag_c_uint_type.clone())
         .arg(flag_name("PERL_WORD"));
+    let flag_setop = |name: &'static str, help: &'static str| {
+        Arg::with_name(name)
+            .long(name)
+            .takes_value(true)
+            .value_name("SPEC")
+            .multiple(true)
+            .number_of_values(1)
+            .help(help)
+    };
+    let cmd_setop = SubCommand::with_name("setop")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create a table by combining sets of codepoints.")
+        .before_help(ABOUT_SETOP)
+        .arg(ucd_dir.clone())
+        .arg(
+            Arg::with_name("spec")
+                .required(true)
+                .help("The property spec to start with."),
+        )
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
+        .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
+        .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
+        .arg(flag_trie_set.clone())
+        .arg(flag_format.clone())
+        .arg(flag_optimize.clone())
+        .arg(flag_profile.clone())
+        .arg(flag_emit_iter.clone())
+        .arg(flag_group_by_plane.clone())
+        .arg(flag_c.clone())
+        .arg(flag_c_uint_type.clone())
+        .arg(flag_name("SETOP"))
+        .arg(flag_setop("union", "Add the codepoints matched by SPEC."))
+        .arg(flag_setop(
+            "intersect",
+            "Keep only the codepoints also matched by SPEC.",
+        ))
+        .arg(flag_setop(
+            "difference",
+            "Remove the codepoints matched by SPEC.",
+        ));
+    let cmd_validity = SubCommand::with_name("validity")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create a table of valid codepoints, excluding noncharacters.")
+        .before_help(ABOUT_VALIDITY)
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
+        .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
+        .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
+        .arg(flag_trie_set.clone())
+        .arg(flag_format.clone())
+        .arg(flag_optimize.clone())
+        .arg(flag_profile.clone())
+        .arg(flag_emit_iter.clone())
+        .arg(flag_group_by_plane.clone())
+        .arg(flag_c.clone())
+        .arg(flag_c_uint_type.clone())
+        .arg(flag_name("VALIDITY"))
+        .arg(
+            Arg::with_name("exclude-private-use")
+                .long("exclude-private-use")
+                .help("Exclude private use codepoints (Co)."),
+        )
+        .arg(
+            Arg::with_name("exclude-unassigned")
+                .long("exclude-unassigned")
+                .help("Exclude unassigned codepoints (Cn)."),
+        );
+    let cmd_terminal_width = SubCommand::with_name("terminal-width")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create a table of terminal column widths.")
+        .before_help(ABOUT_TERMINAL_WIDTH)
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
+        .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_force_write.clone())
+        .arg(flag_chars.clone())
+        .arg(flag_annotate.clone())
+        .arg(flag_sample.clone())
+        .arg(flag_name("TERMINAL_WIDTH"))
+        .arg(
+            Arg::with_name("ambiguous-wide")
+                .long("ambiguous-wide")
+                .help("Treat East_Asian_Width=Ambiguous as wide (2 columns)."),
+        );
     let cmd_jamo_short_name = SubCommand::with_name("jamo-short-name")
         .author(clap::crate_authors!())
         .version(clap::crate_version!())
@@ -444,9 +1943,32 @@ pub fn app() -> App<'static, 'static> {
         .about("Create the Jamo_Short_Name property table.")
         .before_help(ABOUT_JAMO_SHORT_NAME)
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
         .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
         .arg(flag_chars.clone())
-        .arg(flag_name("JAMO_SHORT_NAME"));
+        .arg(flag_name("JAMO_SHORT_NAME"))
+        .arg(flag_max_value_len.clone())
+        .arg(Arg::with_name("reverse").long("reverse").help(
+            "Emit the Hangul syllable constants and tables that map Jamo \
+             short names to codepoints, split by L, V and T.",
+        ))
+        .arg(
+            Arg::with_name("rust-match")
+                .long("rust-match")
+                .conflicts_with_all(&["fst-dir", "reverse"])
+                .help(
+                    "Emit a function that uses a match to map codepoints to \
+                     their short names.",
+                ),
+        );
     let cmd_names =
         SubCommand::with_name("names")
             .author(clap::crate_authors!())
@@ -455,9 +1977,19 @@ pub fn app() -> App<'static, 'static> {
             .about("Create a mapping from character name to codepoint.")
             .before_help(ABOUT_NAMES)
             .arg(ucd_dir.clone())
+            .arg(flag_emit_len_consts.clone())
+            .arg(flag_out_dir.clone())
+            .arg(flag_emit.clone())
+            .arg(flag_split_min_len.clone())
             .arg(flag_fst_dir.clone())
+            .arg(flag_verify_fst.clone())
+            .arg(flag_loader.clone())
+            .arg(flag_fst_hash_names.clone())
+            .arg(flag_fst_max_file_size.clone())
+            .arg(flag_force_write.clone())
             .arg(flag_chars.clone().conflicts_with("tagged"))
             .arg(flag_name("NAMES"))
+            .arg(flag_case_fold_keys.clone())
             .arg(Arg::with_name("no-aliases").long("no-aliases").help(
                 "Ignore all character name aliases. When used, every name \
                  maps to exactly one codepoint.",
@@ -465,6 +1997,11 @@ pub fn app() -> App<'static, 'static> {
             .arg(Arg::with_name("no-ideograph").long("no-ideograph").help(
                 "Do not include algorithmically generated ideograph names.",
             ))
+            .arg(Arg::with_name("no-derived").long("no-derived").help(
+                "Do not include algorithmically generated names that are \
+                 neither ideographs nor Hangul syllables, such as Nushu \
+                 characters.",
+            ))
             .arg(Arg::with_name("no-hangul").long("no-hangul").help(
                 "Do not include algorithmically generated Hangul syllable \
                  names.",
@@ -476,11 +2013,154 @@ pub fn app() -> App<'static, 'static> {
                  UnicodeData.txt. Bit 34 indicates the name is from \
                  NameAliases.txt. \
                  Bit 35 indicates the name is a Hangul syllable. Bit 36 \
-                 indicates the name is an ideograph.",
+                 indicates the name is an ideograph. Bit 37 indicates the \
+                 name is some other algorithmically generated name. A \
+                 constant is also emitted for each of these bits, along with \
+                 a function that splits a value into its tag and codepoint.",
             ))
-            .arg(Arg::with_name("normalize").long("normalize").help(
-                "Normalize all character names according to UAX44-LM2.",
+            .arg(
+                Arg::with_name("normalize").long("normalize").help(
+                    "Normalize all character names according to UAX44-LM2.",
+                ),
+            )
+            .arg(
+                Arg::with_name("emit-consts-only")
+                    .long("emit-consts-only")
+                    .conflicts_with_all(&["tagged", "fst-dir", "low-memory"])
+                    .help(
+                        "Emit constants describing the names instead of the \
+                         table: the number of names, the number of named \
+                         codepoints, the length of the longest name, the \
+                         number of Hangul syllables, and the Hangul syllable \
+                         and ideograph ranges.",
+                    ),
+            )
+            .arg(
+                Arg::with_name("low-memory")
+                    .long("low-memory")
+                    .requires("fst-dir")
+                    .conflicts_with_all(&[
+                        "fst-hash-names",
+                        "fst-max-file-size",
+                        "case-fold-keys",
+                    ])
+                    .help(
+                        "Build the FST without holding every name in memory. \
+                         Names are sorted in batches that are written to \
+                         temporary files, which are merged as the FST is \
+                         written to its file.",
+                    ),
+            );
+    let cmd_derived_name = SubCommand::with_name("derived-name")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create a table of algorithmically derived name ranges.")
+        .before_help(ABOUT_DERIVED_NAME)
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
+        .arg(flag_chars.clone())
+        .arg(flag_name("DERIVED_NAME"));
+    let cmd_named_sequences = SubCommand::with_name("named-sequences")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create a mapping from named sequence to codepoints.")
+        .before_help(ABOUT_NAMED_SEQUENCES)
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
+        .arg(flag_chars.clone())
+        .arg(flag_name("NAMED_SEQUENCES"))
+        .arg(flag_case_fold_keys.clone())
+        .arg(flag_flat_table.clone())
+        .arg(flag_flat_table_pad.clone())
+        .arg(flag_flat_table_trim.clone())
+        .arg(
+            Arg::with_name("normalize")
+                .long("normalize")
+                .help("Normalize all sequence names according to UAX44-LM2."),
+        );
+    let cmd_collation = SubCommand::with_name("collation")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create tables of the DUCET collation elements.")
+        .before_help(ABOUT_COLLATION)
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_name("DUCET"))
+        .arg(Arg::with_name("no-contractions").long("no-contractions").help(
+            "Only write the entries for single codepoints, leaving out \
+             every contraction.",
+        ));
+    let cmd_canonical_composition =
+        SubCommand::with_name("canonical-composition")
+            .author(clap::crate_authors!())
+            .version(clap::crate_version!())
+            .template(TEMPLATE_SUB)
+            .about("Create a table of the canonical compositions of NFC.")
+            .before_help(ABOUT_CANONICAL_COMPOSITION)
+            .arg(ucd_dir.clone())
+            .arg(flag_emit_len_consts.clone())
+            .arg(flag_out_dir.clone())
+            .arg(flag_emit.clone())
+            .arg(flag_split_min_len.clone())
+            .arg(flag_chars.clone())
+            .arg(flag_name("CANONICAL_COMPOSITION"))
+            .arg(Arg::with_name("two-level").long("two-level").help(
+                "Index the table by the first codepoint of each pair, with \
+                 the rest of each pair in a separate _PAIRS table.",
             ));
+    let cmd_matrix = SubCommand::with_name("matrix")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Run a command for several Unicode versions at once.")
+        .before_help(ABOUT_MATRIX)
+        .arg(
+            Arg::with_name("OUT_DIR")
+                .required(true)
+                .help("The directory to write each version's tables to."),
+        )
+        .arg(
+            Arg::with_name("ucd-dir")
+                .long("ucd-dir")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .required(true)
+                .help(
+                    "A UCD directory to run the command on. Can be \
+                     specified multiple times, once for each version.",
+                ),
+        )
+        .arg(
+            Arg::with_name("threads")
+                .long("threads")
+                .takes_value(true)
+                .value_name("N")
+                .help(
+                    "The number of versions to generate at once. This \
+                     defaults to 1.",
+                ),
+        )
+        .arg(
+            Arg::with_name("command")
+                .multiple(true)
+                .last(true)
+                .required(true)
+                .help("The command to run, along with its flags."),
+        );
     let cmd_property_names = SubCommand::with_name("property-names")
         .author(clap::crate_authors!())
         .version(clap::crate_version!())
@@ -488,17 +2168,26 @@ pub fn app() -> App<'static, 'static> {
         .about("Create the canonical property name table.")
         .before_help(ABOUT_PROPERTY_NAMES)
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
         .arg(flag_name("PROPERTY_NAMES"))
+        .arg(flag_case_fold_keys.clone())
         .arg(Arg::with_name("include").long("include").takes_value(true).help(
             "A comma separated list of property names to include. \
-             When absent, all property names are included.",
+             When absent, all property names are included. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
         ))
         .arg(
             Arg::with_name("exclude").long("exclude").takes_value(true).help(
                 "A comma separated list of property names to exclude. \
                  When absent, no property names are excluded. This \
                  overrides property names specified with the --include \
-                 flag.",
+                 flag. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
             ),
         );
     let cmd_property_values = SubCommand::with_name("property-values")
@@ -508,18 +2197,52 @@ pub fn app() -> App<'static, 'static> {
         .about("Create the canonical property value table.")
         .before_help(ABOUT_PROPERTY_VALUES)
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
         .arg(flag_name("PROPERTY_VALUES"))
+        .arg(flag_case_fold_keys.clone())
+        .arg(
+            Arg::with_name("emit-from-str")
+                .long("emit-from-str")
+                .conflicts_with("rust-enum")
+                .help(
+                    "Emit a table that maps every name that \\p{...} may be \
+                     given to the path of the table generated for it by \
+                     another command, e.g., gc=lu to \
+                     general_category::UPPERCASE_LETTER.",
+                ),
+        )
+        .arg(
+            Arg::with_name("rust-enum")
+                .long("rust-enum")
+                .conflicts_with("case-fold-keys")
+                .help(
+                    "Emit a Rust enum of the canonical values of each \
+                     property instead of a table. Each enum implements \
+                     FromStr, which accepts any alias of a value using loose \
+                     matching.",
+                ),
+        )
+        .arg(flag_enum_non_exhaustive.clone())
+        .arg(flag_enum_discriminants.clone())
+        .arg(flag_enum_manifest.clone())
         .arg(Arg::with_name("include").long("include").takes_value(true).help(
             "A comma separated list of property names to include. \
              When absent, all property values for all properties are \
-             included.",
+             included. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
         ))
         .arg(
             Arg::with_name("exclude").long("exclude").takes_value(true).help(
                 "A comma separated list of property names to exclude. \
                  When absent, no property values are excluded. This \
                  overrides property names specified with the --include \
-                 flag.",
+                 flag. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
             ),
         );
     let cmd_case_folding_simple = SubCommand::with_name("case-folding-simple")
@@ -529,9 +2252,31 @@ pub fn app() -> App<'static, 'static> {
         .about("Create a case folding table using the simple mapping.")
         .before_help(ABOUT_CASE_FOLDING_SIMPLE)
         .arg(flag_name("CASE_FOLDING_SIMPLE"))
+        .arg(flag_sample.clone())
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
         .arg(flag_fst_dir.clone())
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_binary.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
         .arg(flag_chars.clone())
+        .arg(flag_regex_syntax.clone().requires("all-pairs"))
+        .arg(
+            flag_rust_match
+                .clone()
+                .conflicts_with_all(&["reverse", "all-pairs"]),
+        )
+        .arg(
+            flag_latin1_fast_path
+                .clone()
+                .conflicts_with_all(&["reverse", "all-pairs"]),
+        )
         .arg(
             Arg::with_name("circular")
                 .long("circular")
@@ -541,7 +2286,80 @@ pub fn app() -> App<'static, 'static> {
             "Emit a table where each codepoint includes all possible \
              Simple mappings.",
         ))
-        .arg(flag_flat_table.clone().requires("all-pairs"));
+        .arg(
+            Arg::with_name("reverse")
+                .long("reverse")
+                .conflicts_with_all(&["circular", "all-pairs"])
+                .help(
+                    "Emit a table that maps each codepoint to the sorted \
+                     list of codepoints that fold to it.",
+                ),
+        )
+        .arg(flag_flat_table.clone().conflicts_with("circular"))
+        .arg(flag_flat_table_pad.clone())
+        .arg(flag_flat_table_trim.clone())
+        .arg(Arg::with_name("turkic").long("turkic").help(
+            "Also emit a NAME_TURKIC table of the Turkic (T) mappings, which \
+             implementations for the tr and az locales apply before the \
+             mappings in the main table.",
+        ));
+    let cmd_nfkc_casefold = SubCommand::with_name("nfkc-casefold")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create a table of the NFKC_Casefold mappings.")
+        .before_help(ABOUT_NFKC_CASEFOLD)
+        .arg(flag_name("NFKC_CASEFOLD"))
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
+        .arg(flag_chars.clone())
+        .arg(flag_flat_table.clone())
+        .arg(flag_flat_table_pad.clone())
+        .arg(flag_flat_table_trim.clone())
+        .arg(
+            Arg::with_name("simple")
+                .long("simple")
+                .help("Emit the NFKC_Simple_Casefold mappings instead."),
+        );
+    let cmd_do_not_emit = SubCommand::with_name("do-not-emit")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about(
+            "Create tables of discouraged sequences and their replacements.",
+        )
+        .before_help(ABOUT_DO_NOT_EMIT)
+        .arg(flag_name("DO_NOT_EMIT"))
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
+        .arg(Arg::with_name("include").long("include").takes_value(true).help(
+            "A comma separated list of types to include. When absent, all \
+             types are included. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
+        ))
+        .arg(Arg::with_name("exclude").long("exclude").takes_value(true).help(
+            "A comma separated list of types to exclude. When absent, no \
+             types are excluded. This overrides types specified with the \
+             --include flag. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
+        ))
+        .arg(
+            Arg::with_name("list-types")
+                .long("list-types")
+                .help("List the types of entries found in DoNotEmit.txt."),
+        )
+        .arg(Arg::with_name("combined").long("combined").help(
+            "Emit a single table, named by --name, with the entries of \
+             every included type.",
+        ));
     let cmd_case_mapping = SubCommand::with_name("case-mapping")
         .author(clap::crate_authors!())
         .version(clap::crate_version!())
@@ -552,8 +2370,20 @@ pub fn app() -> App<'static, 'static> {
         )
         .before_help(ABOUT_CASE_MAPPING)
         .arg(flag_name("CASE_MAPPING"))
+        .arg(flag_sample.clone())
         .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone())
+        .arg(flag_fst_dir.clone().requires("simple"))
+        .arg(flag_verify_fst.clone())
+        .arg(flag_loader.clone())
+        .arg(flag_fst_hash_names.clone())
+        .arg(flag_fst_max_file_size.clone())
+        .arg(flag_force_write.clone())
         .arg(flag_chars.clone())
+        .arg(flag_rust_match.clone().requires("simple"))
         .arg(Arg::with_name("simple").long("simple").help(
             "Only emit the simple case mapping tables \
              (emit maps of codepoint to codepoint, \
@@ -574,58 +2404,313 @@ pub fn app() -> App<'static, 'static> {
                      When absent, all case mapping are included.",
                 ),
         )
-        .arg(flag_flat_table.clone().conflicts_with("simple"));
+        .arg(
+            Arg::with_name("rename")
+                .long("rename")
+                .value_name("OLD=NEW")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help(
+                    "Rename an emitted table, e.g., --rename LOWER=TO_LOWER. \
+                     Can be specified multiple times.",
+                ),
+        )
+        .arg(
+            Arg::with_name("split-tables")
+                .long("split-tables")
+                .requires("out-dir")
+                .help(
+                    "Write each table to its own file in the output \
+                     directory, named after the table (e.g., lower.rs).",
+                ),
+        )
+        .arg(flag_flat_table.clone().conflicts_with("simple"))
+        .arg(
+            Arg::with_name("reverse")
+                .long("reverse")
+                .conflicts_with_all(&["latin1-fast-path", "titlecase-classes"])
+                .help(
+                    "Emit LOWER_REVERSE, UPPER_REVERSE and TITLE_REVERSE \
+                     tables instead, which map each codepoint to the sorted \
+                     list of codepoints whose mapping is that codepoint.",
+                ),
+        )
+        .arg(flag_flat_table_pad.clone())
+        .arg(flag_flat_table_trim.clone())
+        .arg(flag_latin1_fast_path.clone().requires("simple"))
+        .arg(Arg::with_name("self-check").long("self-check").help(
+            "Follow each table with compile time assertions that its keys \
+             are sorted and that its keys and values are valid scalar \
+             values. This also makes it an error for a codepoint to be \
+             unrepresentable with --chars, instead of dropping it.",
+        ))
+        .arg(
+            Arg::with_name("titlecase-classes")
+                .long("titlecase-classes")
+                .help(
+                    "Also emit a TITLECASE_CLASS table that maps each \
+                     codepoint to a bitset of its Cased (1) and \
+                     Case_Ignorable (2) properties.",
+                ),
+        );
 
-    let cmd_grapheme_cluster_break =
-        SubCommand::with_name("grapheme-cluster-break")
-            .author(clap::crate_authors!())
-            .version(clap::crate_version!())
-            .template(TEMPLATE_SUB)
-            .about("Create a table for each Grapheme_Cluster_Break value.")
-            .before_help(ABOUT_GRAPHEME_CLUSTER_BREAK)
-            .arg(flag_name("GRAPHEME_CLUSTER_BREAK"))
-            .arg(ucd_dir.clone())
-            .arg(flag_fst_dir.clone())
-            .arg(flag_chars.clone())
-            .arg(flag_trie_set.clone())
-            .arg(
-                Arg::with_name("enum").long("enum").help(
+    // The UAX #29 break property commands all support the same flags.
+    let cmd_break_property =
+        |cmd: &'static str, about, before_help, default_name| {
+            SubCommand::with_name(cmd)
+                .author(clap::crate_authors!())
+                .version(clap::crate_version!())
+                .template(TEMPLATE_SUB)
+                .about(about)
+                .before_help(before_help)
+                .arg(flag_name(default_name))
+                .arg(flag_sample.clone())
+                .arg(ucd_dir.clone())
+                .arg(flag_emit_len_consts.clone())
+                .arg(flag_out_dir.clone())
+                .arg(flag_emit.clone())
+                .arg(flag_split_min_len.clone())
+                .arg(flag_fst_dir.clone())
+                .arg(flag_verify_fst.clone())
+                .arg(flag_loader.clone())
+                .arg(flag_binary.clone())
+                .arg(flag_fst_hash_names.clone())
+                .arg(flag_fst_max_file_size.clone())
+                .arg(flag_force_write.clone())
+                .arg(flag_chars.clone())
+                .arg(flag_regex_syntax.clone())
+                .arg(flag_trie_set.clone())
+                .arg(flag_format.clone())
+                .arg(flag_optimize.clone())
+                .arg(flag_profile.clone())
+                .arg(flag_emit_iter.clone())
+                .arg(flag_group_by_plane.clone())
+                .arg(flag_by_name_fn.clone())
+                .arg(flag_c.clone())
+                .arg(flag_c_uint_type.clone())
+                .arg(flag_c_enum.clone())
+                .arg(flag_combined.clone())
+                .arg(flag_combined_disjoint.clone())
+                .arg(flag_overlap.clone())
+                .arg(flag_overlap_priority.clone())
+                .arg(flag_intern_strings.clone())
+                .arg(Arg::with_name("enum").long("enum").help(
                     "Emit a single table that maps codepoints to values.",
+                ))
+                .arg(Arg::with_name("rust-enum").long("rust-enum").help(
+                    "Emit a Rust enum and a table that maps codepoints to \
+                     values.",
+                ))
+                .arg(flag_utf8_trie.clone())
+                .arg(flag_validate_utf8_tables.clone())
+                .arg(flag_enum_non_exhaustive.clone())
+                .arg(flag_enum_discriminants.clone())
+                .arg(flag_enum_manifest.clone())
+        };
+    let cmd_grapheme_cluster_break = cmd_break_property(
+        "grapheme-cluster-break",
+        "Create a table for each Grapheme_Cluster_Break value.",
+        ABOUT_GRAPHEME_CLUSTER_BREAK,
+        "GRAPHEME_CLUSTER_BREAK",
+    );
+    let cmd_word_break = cmd_break_property(
+        "word-break",
+        "Create a table for each Word_Break value.",
+        ABOUT_WORD_BREAK,
+        "WORD_BREAK",
+    );
+    let cmd_sentence_break = cmd_break_property(
+        "sentence-break",
+        "Create a table for each Sentence_Break value.",
+        ABOUT_SENTENCE_BREAK,
+        "SENTENCE_BREAK",
+    );
+    let cmd_indic_conjunct_break = cmd_break_property(
+        "indic-conjunct-break",
+        "Create a table for each Indic_Conjunct_Break value.",
+        ABOUT_INDIC_CONJUNCT_BREAK,
+        "INDIC_CONJUNCT_BREAK",
+    );
+
+    let cmd_line_break_pairs = SubCommand::with_name("line-break-pairs")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create the pair table of the line breaking algorithm.")
+        .before_help(ABOUT_LINE_BREAK_PAIRS)
+        .arg(flag_name("LINE_BREAK"))
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone());
+    let cmd_line_break_test = SubCommand::with_name("line-break-test")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create a table of the line break conformance tests.")
+        .before_help(ABOUT_LINE_BREAK_TEST)
+        .arg(flag_name("LINE_BREAK_TEST"))
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone());
+    let cmd_bidi_test = SubCommand::with_name("bidi-test")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create a table of the tests in BidiTest.txt.")
+        .before_help(ABOUT_BIDI_TEST)
+        .arg(flag_name("BIDI_TEST"))
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone());
+    let cmd_bidi_character_test = SubCommand::with_name("bidi-character-test")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create a table of the tests in BidiCharacterTest.txt.")
+        .before_help(ABOUT_BIDI_CHARACTER_TEST)
+        .arg(flag_name("BIDI_CHARACTER_TEST"))
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone());
+    let cmd_normalization_test = SubCommand::with_name("normalization-test")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create a table of the tests in NormalizationTest.txt.")
+        .before_help(ABOUT_NORMALIZATION_TEST)
+        .arg(flag_name("NORMALIZATION_TEST"))
+        .arg(ucd_dir.clone())
+        .arg(flag_emit_len_consts.clone())
+        .arg(flag_out_dir.clone())
+        .arg(flag_emit.clone())
+        .arg(flag_split_min_len.clone());
+    let cmd_golden_tests = SubCommand::with_name("golden-tests")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Create tests of boolean property tables.")
+        .before_help(ABOUT_GOLDEN_TESTS)
+        .arg(ucd_dir.clone())
+        .arg(flag_out_dir.clone())
+        .arg(
+            Arg::with_name("module")
+                .long("module")
+                .takes_value(true)
+                .required(true)
+                .value_name("PATH")
+                .help(
+                    "The path of the module containing the tables under \
+                     test, e.g., my_crate::tables::prop_list.",
                 ),
-            );
-    let cmd_word_break = SubCommand::with_name("word-break")
+        )
+        .arg(Arg::with_name("include").long("include").takes_value(true).help(
+            "A comma separated list of properties to test. \
+             When absent, all available properties are tested. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
+        ))
+        .arg(
+            Arg::with_name("exclude").long("exclude").takes_value(true).help(
+                "A comma separated list of properties to not test. \
+             This overrides properties specified with the --include flag. \
+             Names with * or ? are glob patterns that match \
+             canonical names loosely.",
+            ),
+        );
+    let cmd_explain = SubCommand::with_name("explain")
         .author(clap::crate_authors!())
         .version(clap::crate_version!())
         .template(TEMPLATE_SUB)
-        .about("Create a table for each Word_Break value.")
-        .before_help(ABOUT_WORD_BREAK)
-        .arg(flag_name("WORD_BREAK"))
+        .about("Explain where a codepoint's property value comes from.")
+        .before_help(ABOUT_EXPLAIN)
         .arg(ucd_dir.clone())
-        .arg(flag_fst_dir.clone())
-        .arg(flag_chars.clone())
-        .arg(flag_trie_set.clone())
         .arg(
-            Arg::with_name("enum")
-                .long("enum")
-                .help("Emit a single table that maps codepoints to values."),
+            Arg::with_name("property")
+                .required_unless("list-properties")
+                .help("The property to explain, e.g., Bidi_Class or bc."),
+        )
+        .arg(
+            Arg::with_name("codepoint")
+                .required_unless("list-properties")
+                .help("The codepoint to explain, e.g., U+0600."),
+        )
+        .arg(Arg::with_name("list-properties").long("list-properties").help(
+            "List the properties that can be explained, along with what \
+             each one is derived from.",
+        ));
+    let cmd_info = SubCommand::with_name("info")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Summarize the contents of a UCD directory.")
+        .before_help(ABOUT_INFO)
+        .arg(ucd_dir.clone())
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .help("Print the summary as JSON instead of text."),
         );
-    let cmd_sentence_break = SubCommand::with_name("sentence-break")
+    let cmd_schema = SubCommand::with_name("schema")
         .author(clap::crate_authors!())
         .version(clap::crate_version!())
         .template(TEMPLATE_SUB)
-        .about("Create a table for each Sentence_Break value.")
-        .before_help(ABOUT_SENTENCE_BREAK)
-        .arg(flag_name("SENTENCE_BREAK"))
+        .about("Print the shape of each kind of table as JSON.")
+        .before_help(ABOUT_SCHEMA);
+    let cmd_regen = SubCommand::with_name("regen")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Run the commands that generated the given files again.")
+        .before_help(ABOUT_REGEN)
         .arg(ucd_dir.clone())
-        .arg(flag_fst_dir.clone())
-        .arg(flag_chars.clone())
-        .arg(flag_trie_set.clone())
         .arg(
-            Arg::with_name("enum")
-                .long("enum")
-                .help("Emit a single table that maps codepoints to values."),
+            Arg::with_name("file")
+                .required(true)
+                .multiple(true)
+                .help("A file generated with --config-header."),
+        )
+        .arg(Arg::with_name("diff").long("diff").help(
+            "Print a diff of each file that would change instead of \
+             writing it, and fail if any would change.",
+        ));
+    let cmd_completions = SubCommand::with_name("completions")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Print a shell completion script.")
+        .before_help(ABOUT_COMPLETIONS)
+        .arg(
+            Arg::with_name("shell")
+                .required(true)
+                .possible_values(&clap::Shell::variants())
+                .help("The shell to complete ucd-generate in."),
+        );
+    let cmd_man = SubCommand::with_name("man")
+        .author(clap::crate_authors!())
+        .version(clap::crate_version!())
+        .template(TEMPLATE_SUB)
+        .about("Print man pages in roff.")
+        .before_help(ABOUT_MAN)
+        .arg(
+            Arg::with_name("command")
+                .conflicts_with("out-dir")
+                .help("The subcommand to print the man page of."),
+        )
+        .arg(
+            Arg::with_name("out-dir")
+                .long("out-dir")
+                .takes_value(true)
+                .help("Write every man page to the given directory."),
         );
-
     let cmd_test_unicode_data = SubCommand::with_name("test-unicode-data")
         .author(clap::crate_authors!())
         .version(clap::crate_version!())
@@ -642,24 +2727,169 @@ pub fn app() -> App<'static, 'static> {
         .template(TEMPLATE)
         .max_term_width(100)
         .setting(AppSettings::UnifiedHelpMessage)
+        .arg(
+            Arg::with_name("columns")
+                .long("columns")
+                .global(true)
+                .takes_value(true)
+                .value_name("N")
+                .help(
+                    "The column limit to wrap generated tables at, on a \
+                     best effort basis. Defaults to 79.",
+                ),
+        )
+        .arg(
+            Arg::with_name("indent")
+                .long("indent")
+                .global(true)
+                .takes_value(true)
+                .value_name("STR")
+                .help(
+                    "The string to use for each level of indentation, in \
+                     place of two spaces. It must consist of spaces or \
+                     tabs, where a tab may be written as '\\t'.",
+                ),
+        )
+        .arg(
+            Arg::with_name("assert-nonempty")
+                .long("assert-nonempty")
+                .global(true)
+                .help(
+                    "Fail instead of writing a table with no entries, which \
+                     usually means that a filter didn't match what was \
+                     intended.",
+                ),
+        )
+        .arg(Arg::with_name("manifest").long("manifest").global(true).help(
+            "When writing to a directory, also write a manifest.json \
+                 with the size, SHA-256 hash, generating command and Unicode \
+                 version of each file written. Entries for files written by \
+                 other commands are kept.",
+        ))
+        .arg(
+            Arg::with_name("max-age")
+                .long("max-age")
+                .global(true)
+                .takes_value(true)
+                .value_name("VERSION")
+                .help(
+                    "Leave the codepoints introduced after the given Unicode \
+                     version (e.g., 12.0), according to DerivedAge.txt, out \
+                     of every table. This applies to the codepoints that \
+                     tables are keyed by, to both codepoints of pair tables \
+                     and to the codepoints that names map to.",
+                ),
+        )
+        .arg(
+            Arg::with_name("where")
+                .long("where")
+                .global(true)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("PROP[=VALUE]")
+                .help(
+                    "Leave every codepoint that doesn't have the given \
+                     property value out of every table, e.g., \
+                     Script=Greek. Without a value, PROP is a boolean \
+                     property, e.g., Extended_Pictographic. This applies \
+                     to the same codepoints as --max-age. When given more \
+                     than once, only codepoints matching all of them are \
+                     kept.",
+                ),
+        )
+        .arg(
+            Arg::with_name("no-u16-ranges")
+                .long("no-u16-ranges")
+                .global(true)
+                .help(
+                    "Write the ranges of every set as u32 pairs. By default, \
+                     a set whose codepoints all fit in the Basic \
+                     Multilingual Plane is written as u16 pairs instead, \
+                     which makes its table half as big but its type depend \
+                     on its contents.",
+                ),
+        )
+        .arg(
+            Arg::with_name("config-header")
+                .long("config-header")
+                .global(true)
+                .help(
+                    "Record the command, output location, Unicode version \
+                     and ucd-generate version in a ucd-generate-config \
+                     comment in the header of each generated file, so that \
+                     regen can run the command again.",
+                ),
+        )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .global(true)
+                .takes_value(true)
+                .value_name("PATH")
+                .help(
+                    "Instead of writing anything, compare the generated \
+                     output with the existing file at PATH, print a unified \
+                     diff if it differs and exit with an error. With \
+                     --out-dir or --fst-dir, PATH is instead the directory \
+                     whose files are compared. It is an error to give this \
+                     to a command that doesn't write any tables.",
+                ),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .long("quiet")
+                .global(true)
+                .conflicts_with("verbose")
+                .help("Don't report each file that is written on stderr."),
+        )
+        .arg(Arg::with_name("verbose").long("verbose").global(true).help(
+            "Report progress on stderr: how long each UCD file took \
+                     to parse, and how long each table took to build.",
+        ))
         .subcommand(cmd_bidi_class)
         .subcommand(cmd_canonical_combining_class)
         .subcommand(cmd_general_category)
         .subcommand(cmd_script)
         .subcommand(cmd_script_extension)
         .subcommand(cmd_joining_type)
+        .subcommand(cmd_joining_group)
         .subcommand(cmd_age)
         .subcommand(cmd_bidi_mirroring_glyph)
         .subcommand(cmd_prop_bool)
+        .subcommand(cmd_emoji)
         .subcommand(cmd_perl_word)
+        .subcommand(cmd_validity)
+        .subcommand(cmd_setop)
+        .subcommand(cmd_terminal_width)
         .subcommand(cmd_jamo_short_name)
         .subcommand(cmd_names)
+        .subcommand(cmd_derived_name)
+        .subcommand(cmd_named_sequences)
+        .subcommand(cmd_collation)
+        .subcommand(cmd_canonical_composition)
+        .subcommand(cmd_matrix)
         .subcommand(cmd_property_names)
         .subcommand(cmd_property_values)
         .subcommand(cmd_case_folding_simple)
         .subcommand(cmd_case_mapping)
+        .subcommand(cmd_nfkc_casefold)
+        .subcommand(cmd_do_not_emit)
         .subcommand(cmd_grapheme_cluster_break)
         .subcommand(cmd_word_break)
         .subcommand(cmd_sentence_break)
+        .subcommand(cmd_indic_conjunct_break)
+        .subcommand(cmd_line_break_pairs)
+        .subcommand(cmd_line_break_test)
+        .subcommand(cmd_bidi_test)
+        .subcommand(cmd_bidi_character_test)
+        .subcommand(cmd_normalization_test)
+        .subcommand(cmd_golden_tests)
+        .subcommand(cmd_explain)
+        .subcommand(cmd_info)
+        .subcommand(cmd_schema)
+        .subcommand(cmd_regen)
+        .subcommand(cmd_completions)
+        .subcommand(cmd_man)
         .subcommand(cmd_test_unicode_data)
 }
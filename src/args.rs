@@ -1,11 +1,17 @@
+use std::collections::BTreeSet;
 use std::ffi::OsStr;
 use std::ops;
 
 use clap;
 
+use crate::derive::Engine;
 use crate::error::Result;
-use crate::util::Filter;
-use crate::writer::{Writer, WriterBuilder};
+use crate::regen::{Config, Output};
+use crate::util::{CharNames, Filter};
+use crate::writer::{
+    CUintType, CaseFold, FstLoader, MaxAge, Optimize, Overlap, Sample, Where,
+    Writer, WriterBuilder,
+};
 
 /// Wraps clap matches and provides convenient accessors to various parameters.
 pub struct ArgMatches<'a>(&'a clap::ArgMatches<'a>);
@@ -32,20 +38,313 @@ impl<'a> ArgMatches<'a> {
     pub fn writer(&self, name: &str) -> Result<Writer> {
         let mut builder = WriterBuilder::new(name);
         builder
-            .columns(79)
-            .char_literals(self.is_present("chars"))
-            .trie_set(self.is_present("trie-set"));
+            .columns(self.usize_value("columns")?.unwrap_or(79) as u64)
+            .indent(&self.indent()?)
+            .char_literals(
+                self.is_present("chars") || self.is_present("regex-syntax"),
+            )
+            .trie_set(
+                self.is_present("trie-set")
+                    || self.value_of("format") == Some("trie-set"),
+            )
+            .optimize(self.optimize()?)
+            .wasm(self.value_of("profile") == Some("wasm"))
+            .emit_len_consts(self.is_present("emit-len-consts"))
+            .emit_iter(self.is_present("emit-iter"))
+            .combined_disjoint(self.is_present("combined-disjoint"))
+            .overlap(self.overlap()?)
+            .overlap_priority(
+                self.values_of("overlap-priority").into_iter().flatten(),
+            )
+            .group_by_plane(self.is_present("group-by-plane"))
+            .u16_ranges(!self.is_present("no-u16-ranges"))
+            .css_unicode_range(
+                self.value_of("format") == Some("css-unicode-range"),
+            )
+            .by_name_fn(self.is_present("by-name-fn"))
+            .flat_table_pad(self.flat_table_pad()?)
+            .flat_table_trim(self.is_present("flat-table-trim"))
+            .fst_hash_names(self.is_present("fst-hash-names"))
+            .fst_max_file_size(self.usize_value("fst-max-file-size")?)
+            .fst_loader(self.fst_loader()?)
+            .sample(self.sample()?)
+            .max_age(self.max_age()?)
+            .only(self.only()?)
+            .verify_fst(self.is_present("verify-fst"))
+            .enum_hash(self.hex_value("enum-hash")?)
+            .enum_non_exhaustive(self.is_present("enum-non-exhaustive"))
+            .enum_discriminants(self.is_present("enum-discriminants"))
+            .enum_manifest(self.value_of_os("enum-manifest"))
+            .force_write(self.is_present("force-write"))
+            .max_value_len(self.usize_value("max-value-len")?)
+            .self_check(self.is_present("self-check"))
+            .rust_match(self.is_present("rust-match"))
+            .latin1_fast_path(self.is_present("latin1-fast-path"))
+            .validate_utf8_tables(self.is_present("validate-utf8-tables"))
+            .annotate(self.annotate()?)
+            .assert_nonempty(self.is_present("assert-nonempty"))
+            .manifest(self.is_present("manifest"))
+            .check(self.is_present("check"))
+            .quiet(self.is_present("quiet"))
+            .regex_syntax(self.is_present("regex-syntax"))
+            .case_fold_keys(self.case_fold_keys()?)
+            .split_data(self.split_data()?)
+            .intern_strings(self.is_present("intern-strings"))
+            .c_output(self.is_present("c"))
+            .c_uint_type(self.c_uint_type()?)
+            .c_enum(self.is_present("c-enum"))
+            .binary(self.is_present("binary"));
         // Some of the functionality of this crate works with a partial ucd
         // directory.
-        match ucd_parse::ucd_directory_version(self.ucd_dir()?) {
+        let version = match crate::util::ucd_version(self.ucd_dir()?) {
             Ok((major, minor, patch)) => {
-                builder.ucd_version(major, minor, patch)
+                builder.ucd_version(major, minor, patch);
+                (major, minor, patch)
             }
             Err(e) => return err!("Failed to determine UCD version: {}", e),
         };
-        match self.value_of_os("fst-dir") {
-            None => Ok(builder.from_stdout()),
-            Some(x) => builder.from_fst_dir(x),
+        if self.is_present("config-header") {
+            let output = if self.is_present("fst-dir") {
+                Output::FstDir
+            } else if self.is_present("out-dir") {
+                Output::OutDir
+            } else {
+                Output::Stdout
+            };
+            let config =
+                Config::from_env(self.ucd_dir()?, output, Some(version))?;
+            builder.config(Some(config));
+        }
+        // With --check, the output is compared with the files in the path
+        // given to it instead of being written.
+        let check = self.value_of_os("check");
+        match (self.value_of_os("fst-dir"), self.value_of_os("out-dir")) {
+            (Some(x), _) => builder.from_fst_dir(check.unwrap_or(x)),
+            (None, Some(x)) => builder.from_out_dir(check.unwrap_or(x)),
+            (None, None) if self.is_present("manifest") => {
+                err!("--manifest requires --out-dir or --fst-dir")
+            }
+            (None, None) => match check {
+                Some(path) => Ok(builder.from_file(path)),
+                None => Ok(builder.from_stdout()),
+            },
+        }
+    }
+
+    /// Return the indentation unit given to --indent, with `\t` replaced by
+    /// a tab. This defaults to two spaces.
+    fn indent(&self) -> Result<String> {
+        let indent = match self.value_of_lossy("indent") {
+            None => return Ok("  ".to_string()),
+            Some(indent) => indent.replace("\\t", "\t"),
+        };
+        if indent.is_empty() || !indent.chars().all(|c| c == ' ' || c == '\t')
+        {
+            return err!(
+                "invalid --indent value {:?}: must be spaces or tabs",
+                indent
+            );
+        }
+        Ok(indent)
+    }
+
+    /// Return how to case fold the keys of string tables, according to
+    /// --case-fold-keys.
+    fn case_fold_keys(&self) -> Result<Option<CaseFold>> {
+        match self.value_of("case-fold-keys") {
+            None => Ok(None),
+            Some("ascii") => Ok(Some(CaseFold::Ascii)),
+            Some("simple") => {
+                let folds =
+                    crate::case_folding::simple_folds(self.ucd_dir()?)?;
+                Ok(Some(CaseFold::Simple(folds)))
+            }
+            Some(x) => err!("invalid --case-fold-keys value {:?}", x),
+        }
+    }
+
+    /// Return how to resolve codepoints with more than one value, according
+    /// to --overlap.
+    fn overlap(&self) -> Result<Overlap> {
+        match self.value_of("overlap") {
+            None | Some("last") => Ok(Overlap::Last),
+            Some("first") => Ok(Overlap::First),
+            Some("error") => Ok(Overlap::Error),
+            Some(x) => err!("invalid --overlap value {:?}", x),
+        }
+    }
+
+    /// Return how the generated code loads each FST, according to
+    /// --loader.
+    fn fst_loader(&self) -> Result<FstLoader> {
+        match self.value_of("loader") {
+            None | Some("once_cell") => Ok(FstLoader::OnceCell),
+            Some("lazy_static") => Ok(FstLoader::LazyStatic),
+            Some("oncelock") => Ok(FstLoader::OnceLock),
+            Some("fn") => Ok(FstLoader::Fn),
+            Some(x) => err!("invalid --loader value {:?}", x),
+        }
+    }
+
+    fn c_uint_type(&self) -> Result<CUintType> {
+        match self.value_of("c-uint-type") {
+            None | Some("exact") => Ok(CUintType::Exact),
+            Some("least") => Ok(CUintType::Least),
+            Some(x) => err!("invalid --c-uint-type value {:?}", x),
+        }
+    }
+
+    /// Return the codepoints to leave out of every table according to
+    /// --max-age, which are those that DerivedAge.txt says were introduced
+    /// after the given version.
+    fn max_age(&self) -> Result<Option<MaxAge>> {
+        let max = match self.value_of("max-age") {
+            None => return Ok(None),
+            Some(max) => max,
+        };
+        let version = match parse_age(max) {
+            Some(version) => version,
+            None => return err!("invalid --max-age value {:?}", max),
+        };
+        let ages: Vec<ucd_parse::Age> = crate::util::parse(self.ucd_dir()?)?;
+        let mut newer = vec![];
+        for age in &ages {
+            match parse_age(&age.age) {
+                Some(v) if v > version => {
+                    let range = age.codepoints.range();
+                    newer.push((range.start.value(), range.end.value()));
+                }
+                Some(_) => {}
+                None => {
+                    return err!("invalid age {:?} in DerivedAge.txt", age.age)
+                }
+            }
+        }
+        Ok(Some(MaxAge::new(version, newer)))
+    }
+
+    /// Return the filter that every table is restricted to according to
+    /// --where, which keeps the codepoints that match every one given.
+    fn only(&self) -> Result<Option<Where>> {
+        let filters = match self.values_of("where") {
+            None => return Ok(None),
+            Some(filters) => filters,
+        };
+        let mut engine = Engine::new(self.ucd_dir()?)?;
+        let mut descriptions = vec![];
+        let mut kept: Option<BTreeSet<u32>> = None;
+        for filter in filters {
+            let (property, value) = match filter.split_once('=') {
+                Some((property, value)) => (property, Some(value.trim())),
+                None => (filter, None),
+            };
+            let (description, codepoints) =
+                engine.codepoints_where(property.trim(), value)?;
+            kept = Some(match kept {
+                None => codepoints,
+                Some(kept) => {
+                    kept.intersection(&codepoints).copied().collect()
+                }
+            });
+            descriptions.push(description);
+        }
+        Ok(kept.map(|kept| Where::new(&descriptions.join(" and "), &kept)))
+    }
+
+    /// Return which part of each codepoint table to write, according to
+    /// --sample.
+    fn sample(&self) -> Result<Option<Sample>> {
+        let sample = match self.value_of("sample") {
+            None => return Ok(None),
+            Some(sample) => sample,
+        };
+        if let Some((start, end)) = sample.split_once("..") {
+            let parse = |cp: &str| u32::from_str_radix(cp.trim(), 16).ok();
+            return match (parse(start), parse(end)) {
+                (Some(start), Some(end)) if start <= end => {
+                    Ok(Some(Sample::Window(start, end)))
+                }
+                _ => err!("invalid --sample window {:?}", sample),
+            };
+        }
+        match sample.parse() {
+            Ok(count) if count > 0 => Ok(Some(Sample::Ranges(count))),
+            _ => err!("invalid --sample value {:?}", sample),
+        }
+    }
+
+    /// Return what to optimize for when choosing the format of each set,
+    /// according to --format and --optimize, if it is chosen automatically.
+    fn optimize(&self) -> Result<Option<Optimize>> {
+        let format = self.value_of("format");
+        if format.is_some() && format != Some("auto") {
+            if self.is_present("optimize") {
+                return err!("--optimize requires --format auto");
+            }
+            return Ok(None);
+        }
+        match self.value_of("optimize") {
+            None if format.is_none() => Ok(None),
+            None | Some("size") => Ok(Some(Optimize::Size)),
+            Some("speed") => Ok(Some(Optimize::Speed)),
+            Some(x) => err!("invalid --optimize value {:?}", x),
+        }
+    }
+
+    /// Return the names of characters to annotate tables with, if
+    /// --annotate is given.
+    fn annotate(&self) -> Result<Option<CharNames>> {
+        if !self.is_present("annotate") {
+            return Ok(None);
+        }
+        Ok(Some(CharNames::from_ucd_dir(self.ucd_dir()?)?))
+    }
+
+    /// Return the sentinel given to --flat-table-pad, if present.
+    fn flat_table_pad(&self) -> Result<Option<u32>> {
+        match self.hex_value("flat-table-pad")? {
+            None => Ok(None),
+            Some(pad) if pad <= u32::MAX as u64 => Ok(Some(pad as u32)),
+            Some(pad) => err!("invalid --flat-table-pad value {:X}", pad),
+        }
+    }
+
+    /// Return the minimum length of the tables that are moved into data
+    /// files, if --emit mod+data was given.
+    fn split_data(&self) -> Result<Option<usize>> {
+        if self.value_of("emit") != Some("mod+data") {
+            return Ok(None);
+        }
+        if !self.is_present("fst-dir") && !self.is_present("out-dir") {
+            return err!("--emit mod+data requires --out-dir or --fst-dir");
+        }
+        Ok(Some(self.usize_value("split-min-len")?.unwrap_or(0)))
+    }
+
+    /// Return the non-negative integer given to the named flag, if present.
+    pub fn usize_value(&self, name: &str) -> Result<Option<usize>> {
+        let n = match self.value_of_lossy(name) {
+            None => return Ok(None),
+            Some(n) => n,
+        };
+        match n.parse() {
+            Ok(n) => Ok(Some(n)),
+            Err(err) => err!("invalid --{} value {:?}: {}", name, n, err),
+        }
+    }
+
+    /// Return the hexadecimal value given to the named flag, if present.
+    ///
+    /// The value may optionally be prefixed with `U+` or `0x`.
+    fn hex_value(&self, name: &str) -> Result<Option<u64>> {
+        let hex = match self.value_of_lossy(name) {
+            None => return Ok(None),
+            Some(hex) => hex,
+        };
+        let hex = hex.trim_start_matches("U+").trim_start_matches("0x");
+        match u64::from_str_radix(hex, 16) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) => err!("invalid --{} value {:?}: {}", name, hex, err),
         }
     }
 
@@ -68,3 +367,10 @@ impl<'a> ArgMatches<'a> {
         )
     }
 }
+
+/// Parse a Unicode version with a major and an optional minor version,
+/// e.g., `12.0` or `12`, as used by DerivedAge.txt.
+fn parse_age(age: &str) -> Option<(u64, u64)> {
+    let (major, minor) = age.split_once('.').unwrap_or((age, "0"));
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
@@ -1,10 +1,16 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use ucd_parse::{self, CoreProperty, UnicodeData};
+use ucd_parse::{
+    self, extracted::DerivedBidiClass, Codepoint, CodepointRange,
+    CoreProperty, Missing, UcdFile,
+};
 
 use crate::args::ArgMatches;
+use crate::derive::{Derived, Engine};
 use crate::error::Result;
-use crate::util::{print_property_values, PropertyValues};
+use crate::util::{
+    self, enum_aliases, print_property_values, validate_against_derived,
+};
 
 // Bidi Class (listing UnicodeData.txt, field 4: see UAX #44:
 // https://www.unicode.org/reports/tr44/) Unlike other properties, unassigned
@@ -42,6 +48,9 @@ use crate::util::{print_property_values, PropertyValues};
 //
 //  All code points not explicitly listed for Bidi_Class
 //  have the value Left_To_Right (L).
+//
+// These ranges are only used when `extracted/DerivedBidiClass.txt` isn't
+// available. Otherwise, the ranges are read from its `@missing` annotations.
 const DEFAULT_CLASS_ASSIGNMENTS: &[(u32, u32, &str)] = &[
     (0x0600, 0x07BF, "AL"),
     (0x0860, 0x086F, "AL"),
@@ -70,15 +79,14 @@ const DEFAULT_CLASS_ASSIGNMENTS: &[(u32, u32, &str)] = &[
 
 pub fn command(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
-    let propvals = PropertyValues::from_ucd_dir(&dir)?;
-    let rows: Vec<UnicodeData> = ucd_parse::parse(&dir)?;
-    let core_prop: Vec<CoreProperty> = ucd_parse::parse(&dir)?;
+    let mut engine = Engine::new(dir)?;
+    let propvals = engine.propvals();
     let use_short_names = args.is_present("short-names");
-    let bidi_class_name = |short_name: &str| {
+    let bidi_class_name = |name: &str| {
         if use_short_names {
-            Ok(short_name.to_string())
+            propvals.abbreviation("bc", name)
         } else {
-            propvals.canonical("bc", short_name)
+            propvals.canonical("bc", name)
         }
     };
 
@@ -88,16 +96,138 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
         return print_property_values(&propvals, "Bidi_Class");
     }
 
-    // Collect each bidi class into an ordered set.
+    // Validating against the extracted file only makes sense if the values
+    // weren't read from it.
+    let from_unicode_data = args.is_present("from-unicode-data")
+        || args.is_present("validate-against-derived");
+    if args.is_present("from-derived")
+        && !DerivedBidiClass::file_path(dir).exists()
+    {
+        return err!(
+            "--from-derived requires {}",
+            DerivedBidiClass::file_path(dir).display()
+        );
+    }
+    let derived = if from_unicode_data {
+        std::rc::Rc::new(derive_from_unicode_data(&mut engine)?)
+    } else {
+        engine.property("Bidi_Class")?
+    };
     let mut by_type: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
+    for (name, set) in &derived.values {
+        by_type.insert(bidi_class_name(name)?, set.clone());
+    }
+
+    if args.is_present("validate-against-derived") {
+        validate_against_derived(dir, &by_type, |row: &DerivedBidiClass| {
+            bidi_class_name(&row.bidi_class)
+        })?;
+    }
+
+    let mut wtr = args.writer("bidi_class")?;
+    if args.is_present("enum-aliases") {
+        let aliases = enum_aliases(&propvals, "bc", by_type.keys(), None)?;
+        wtr.ranges_to_enum_with_aliases(args.name(), &by_type, &aliases)?;
+    } else if args.is_present("enum") {
+        wtr.ranges_to_enum(args.name(), &by_type)?;
+    } else if args.is_present("rust-enum") {
+        let variants = by_type.keys().map(String::as_str).collect::<Vec<_>>();
+        wtr.ranges_to_rust_enum(args.name(), &variants, &by_type)?;
+    } else if args.is_present("combined") {
+        wtr.ranges_to_combined(args.name(), &by_type)?;
+    } else {
+        wtr.names(by_type.keys())?;
+        for (name, set) in by_type {
+            wtr.ranges(&name, &set)?;
+        }
+    }
+
+    wtr.finish()?;
+    Ok(())
+}
+
+/// Derive the Bidi_Class of every codepoint. Values use their canonical
+/// names.
+///
+/// The values are read from `extracted/DerivedBidiClass.txt` if it exists,
+/// and are otherwise derived from UnicodeData.txt.
+pub fn derive(engine: &mut Engine<'_>) -> Result<Derived> {
+    if DerivedBidiClass::file_path(engine.dir()).exists() {
+        derive_from_extracted(engine)
+    } else {
+        derive_from_unicode_data(engine)
+    }
+}
+
+/// Read the Bidi_Class of every codepoint from
+/// `extracted/DerivedBidiClass.txt`, which lists every codepoint whose class
+/// isn't given by its `@missing` lines.
+fn derive_from_extracted(engine: &mut Engine<'_>) -> Result<Derived> {
+    let dir = engine.dir();
+    let propvals = engine.propvals();
+    let rows: Vec<DerivedBidiClass> = util::parse(dir)?;
+
+    let mut derived = Derived::default();
+    let listed = derived.rule("listed in extracted/DerivedBidiClass.txt", &[]);
+    let mut seen = BTreeSet::new();
+    for row in &rows {
+        let bc = propvals.canonical("bc", &row.bidi_class)?;
+        for cp in row.codepoints.into_iter().map(|c| c.value()) {
+            seen.insert(cp);
+            derived.assign(listed, cp, &bc);
+        }
+    }
+
+    // Later @missing lines take precedence over earlier ones.
+    let missing: Vec<Missing> =
+        ucd_parse::parse_missing::<_, DerivedBidiClass>(dir)?;
+    let mut defaults = vec![];
+    for m in missing.iter().rev() {
+        let range = m.codepoints.range();
+        let (start, end) = (range.start.value(), range.end.value());
+        let name = propvals.canonical("bc", &m.value)?;
+        let why = format!(
+            "not listed in extracted/DerivedBidiClass.txt, and unlisted \
+             codepoints in {:04X}..{:04X} are {} according to an @missing \
+             line in it",
+            start, end, name
+        );
+        defaults.push((start, end, derived.rule(why, &[]), name));
+    }
+    for cp in CodepointRange::all().into_iter().map(Codepoint::value) {
+        if seen.contains(&cp) {
+            continue;
+        }
+        match defaults.iter().find(|&&(s, e, _, _)| s <= cp && cp <= e) {
+            Some(&(_, _, rule, ref name)) => derived.assign(rule, cp, name),
+            None => {
+                return err!(
+                    "U+{:04X} isn't listed in extracted/DerivedBidiClass.txt, \
+                     and none of its @missing lines cover it",
+                    cp
+                )
+            }
+        }
+    }
+    Ok(derived)
+}
+
+/// Derive the Bidi_Class of every codepoint from UnicodeData.txt, including
+/// the codepoints that aren't listed in it, according to the rules for
+/// their default values.
+pub fn derive_from_unicode_data(engine: &mut Engine<'_>) -> Result<Derived> {
+    let dir = engine.dir();
+    let propvals = engine.propvals();
+    let rows = engine.unicode_data()?;
+    let core_prop: Vec<CoreProperty> = util::parse(dir)?;
+
+    let mut derived = Derived::default();
+    let listed = derived.rule("listed in UnicodeData.txt", &[]);
     let mut assigned = BTreeSet::new();
-    for row in rows {
+    for row in rows.iter() {
         assigned.insert(row.codepoint.value());
-        let bc = bidi_class_name(&row.bidi_class)?;
-        by_type
-            .entry(bc)
-            .or_insert(BTreeSet::new())
-            .insert(row.codepoint.value());
+        let bc = propvals.canonical("bc", &row.bidi_class)?;
+        derived.assign(listed, row.codepoint.value(), &bc);
     }
 
     // Process the codepoints that are not listed as per the notes in
@@ -115,50 +245,100 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
         }
     }
 
+    // Prefer the default ranges documented by the data itself, and fall back
+    // to our hard-coded list if the extracted file isn't available.
+    let missing = default_class_assignments(dir)?;
+    let (defaults, source) = match missing {
+        Some(ref missing) => (
+            missing
+                .iter()
+                .map(|&(start, end, ref class)| (start, end, class.as_str()))
+                .collect(),
+            "an @missing line in extracted/DerivedBidiClass.txt",
+        ),
+        None => (
+            DEFAULT_CLASS_ASSIGNMENTS.to_vec(),
+            "the defaults built into ucd-generate, since \
+             extracted/DerivedBidiClass.txt is missing",
+        ),
+    };
+    let mut default_rules = vec![];
+    for &(start, end, class) in &defaults {
+        let name = propvals.canonical("bc", class)?;
+        let why = format!(
+            "not listed in UnicodeData.txt, and unlisted codepoints in \
+             {:04X}..{:04X} are {} according to {}",
+            start, end, name, source
+        );
+        default_rules.push((derived.rule(why, &[]), name));
+    }
+    let boundary_neutral = derived.rule(
+        "not listed in UnicodeData.txt, and is a Default_Ignorable_Code_Point \
+         or Noncharacter_Code_Point",
+        &[],
+    );
+    let left_to_right = derived
+        .rule("not listed in UnicodeData.txt, so the default applies", &[]);
+
     // Process unassigned codepoints
-    let left_to_right_name = bidi_class_name("L")?;
-    let boundary_neutral_name = bidi_class_name("BN")?;
-    for cp in 0..=0x10FFFF {
+    let left_to_right_name = propvals.canonical("bc", "L")?;
+    let boundary_neutral_name = propvals.canonical("bc", "BN")?;
+    for cp in CodepointRange::all().into_iter().map(Codepoint::value) {
         if assigned.contains(&cp) {
             continue;
         }
         // Check if this code point is in the default Bidi classes
-        if let Some(class) = lookup_unassigned(cp, DEFAULT_CLASS_ASSIGNMENTS) {
-            let name = bidi_class_name(class)?;
-            by_type.get_mut(&name).unwrap().insert(cp);
+        if let Some(i) = lookup_unassigned(cp, &defaults) {
+            let (rule, ref name) = default_rules[i];
+            derived.assign(rule, cp, name);
         } else if maybe_boundary_neutral.contains(&cp) {
-            by_type.get_mut(&boundary_neutral_name).unwrap().insert(cp);
+            derived.assign(boundary_neutral, cp, &boundary_neutral_name);
         } else {
             // All others get assigned Left_To_Right
-            by_type.get_mut(&left_to_right_name).unwrap().insert(cp);
+            derived.assign(left_to_right, cp, &left_to_right_name);
         }
     }
+    Ok(derived)
+}
 
-    let mut wtr = args.writer("bidi_class")?;
-    if args.is_present("enum") {
-        wtr.ranges_to_enum(args.name(), &by_type)?;
-    } else if args.is_present("rust-enum") {
-        let variants = by_type.keys().map(String::as_str).collect::<Vec<_>>();
-        wtr.ranges_to_rust_enum(args.name(), &variants, &by_type)?;
-    } else if args.is_present("combined") {
-        wtr.ranges_to_combined(args.name(), &by_type)?;
-    } else {
-        wtr.names(by_type.keys())?;
-        for (name, set) in by_type {
-            wtr.ranges(&name, &set)?;
+/// Read the default Bidi class ranges from the `@missing` annotations in
+/// `extracted/DerivedBidiClass.txt`.
+///
+/// The ranges are returned in order of precedence. The catch-all annotation
+/// covering all codepoints is omitted, since it is handled separately in
+/// order to give Boundary_Neutral codepoints their class. If the file doesn't
+/// exist, then `None` is returned.
+fn default_class_assignments(
+    dir: &std::ffi::OsStr,
+) -> Result<Option<Vec<(u32, u32, String)>>> {
+    let missing: Vec<Missing> =
+        match ucd_parse::parse_missing::<_, DerivedBidiClass>(dir) {
+            Ok(missing) => missing,
+            Err(err) if err.is_io_error() => return Ok(None),
+            Err(err) => return Err(From::from(err)),
+        };
+    let mut defaults = vec![];
+    for m in missing.iter().rev() {
+        let range = m.codepoints.range();
+        if range == CodepointRange::all() {
+            continue;
         }
+        defaults.push((
+            range.start.value(),
+            range.end.value(),
+            m.value.clone(),
+        ));
     }
-
-    Ok(())
+    Ok(Some(defaults))
 }
 
-/// Look up a code point in the unassigned default Bidi classes.
-fn lookup_unassigned<'a>(
+/// Look up a code point in the unassigned default Bidi classes, and return
+/// the index of the first range containing it.
+fn lookup_unassigned(
     codepoint: u32,
-    defaults: &[(u32, u32, &'a str)],
-) -> Option<&'a str> {
+    defaults: &[(u32, u32, &str)],
+) -> Option<usize> {
     defaults
         .iter()
-        .find(|&&(start, end, _)| start <= codepoint && codepoint <= end)
-        .map(|&(_, _, bidi_class)| bidi_class)
+        .position(|&(start, end, _)| start <= codepoint && codepoint <= end)
 }
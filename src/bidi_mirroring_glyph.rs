@@ -1,27 +1,40 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
-use ucd_parse::{self, BidiMirroring};
+use ucd_parse::{BidiMirroring, UnicodeData, UnicodeDataExpander};
 
 use crate::args::ArgMatches;
 use crate::error::Result;
+use crate::util;
 
 pub fn command(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
-    let rows: Vec<BidiMirroring> = ucd_parse::parse(&dir)?;
+    let rows: Vec<BidiMirroring> = util::parse(dir)?;
 
+    let no_best_fit = args.is_present("no-best-fit");
     let table: BTreeMap<_, _> = rows
         .into_iter()
+        .filter(|mapping| !(no_best_fit && mapping.best_fit))
         .map(|mapping| {
             (mapping.codepoint.value(), mapping.bidi_mirroring_glyph.value())
         })
         .collect();
 
     let mut wtr = args.writer("bidi_mirroring_glyph")?;
-    if args.is_present("rust-match") {
-        wtr.codepoint_to_codepoint_fn(args.name(), &table)?;
-    } else {
-        wtr.codepoint_to_codepoint(args.name(), &table)?;
+    wtr.codepoint_to_codepoint(args.name(), &table)?;
+    if args.is_present("without-glyph") {
+        // Every Bidi_Mirrored=Yes codepoint that isn't in the table above.
+        // If best fit mappings were excluded, then this includes them.
+        let unexpanded: Vec<UnicodeData> = util::parse(dir)?;
+        let without_glyph: BTreeSet<u32> =
+            UnicodeDataExpander::new(unexpanded)
+                .filter(|row| row.bidi_mirrored)
+                .map(|row| row.codepoint.value())
+                .filter(|cp| !table.contains_key(cp))
+                .collect();
+        let name = format!("{}_WITHOUT_GLYPH", args.name());
+        wtr.ranges(&name, &without_glyph)?;
     }
 
+    wtr.finish()?;
     Ok(())
 }
@@ -0,0 +1,25 @@
+use ucd_parse::{self, BidiCharacterTest};
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let tests = ucd_parse::parse_bidi_tests(dir)?;
+
+    let mut wtr = args.writer("bidi_test")?;
+    wtr.bidi_tests(args.name(), &tests)?;
+    wtr.finish()?;
+    Ok(())
+}
+
+pub fn command_character(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let tests: Vec<BidiCharacterTest> = util::parse(dir)?;
+
+    let mut wtr = args.writer("bidi_character_test")?;
+    wtr.bidi_character_tests(args.name(), &tests)?;
+    wtr.finish()?;
+    Ok(())
+}
@@ -1,78 +1,111 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use ucd_parse::{self, GraphemeClusterBreak, SentenceBreak, WordBreak};
+use ucd_parse::{
+    Codepoints, CoreProperty, GraphemeClusterBreak, LineBreakTest,
+    SentenceBreak, UcdFile, WordBreak,
+};
 
 use crate::args::ArgMatches;
 use crate::error::Result;
+use crate::util;
 
 pub fn grapheme_cluster(args: ArgMatches<'_>) -> Result<()> {
-    let ucd_dir = args.ucd_dir()?;
-    let vals: Vec<GraphemeClusterBreak> = ucd_parse::parse(&ucd_dir)?;
+    command(
+        args,
+        "grapheme_cluster_break",
+        "Other",
+        |x: &GraphemeClusterBreak| Some((x.codepoints, &x.value)),
+    )
+}
 
-    let mut byval: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
-    for x in &vals {
-        byval
-            .entry(x.value.clone())
-            .or_insert(BTreeSet::new())
-            .extend(x.codepoints.into_iter().map(|c| c.value()));
-    }
+pub fn word(args: ArgMatches<'_>) -> Result<()> {
+    command(args, "word_break", "Other", |x: &WordBreak| {
+        Some((x.codepoints, &x.value))
+    })
+}
 
-    let mut wtr = args.writer("grapheme_cluster_break")?;
-    if args.is_present("enum") {
-        wtr.ranges_to_enum(args.name(), &byval)?;
-    } else {
-        wtr.names(byval.keys())?;
-        for (val, set) in byval {
-            wtr.ranges(&val, &set)?;
+pub fn sentence(args: ArgMatches<'_>) -> Result<()> {
+    command(args, "sentence_break", "Other", |x: &SentenceBreak| {
+        Some((x.codepoints, &x.value))
+    })
+}
+
+/// Write the tables for Indic_Conjunct_Break, which is used by rule GB9c of
+/// the grapheme cluster boundary rules. Its values are listed in
+/// DerivedCoreProperties.txt as of Unicode 15.1.
+pub fn indic_conjunct(args: ArgMatches<'_>) -> Result<()> {
+    command(args, "indic_conjunct_break", "None", |x: &CoreProperty| {
+        match x.value {
+            Some(ref value) if x.property == "InCB" => {
+                Some((x.codepoints, value))
+            }
+            _ => None,
         }
-    }
-    Ok(())
+    })
 }
 
-pub fn word(args: ArgMatches<'_>) -> Result<()> {
+/// Write the tables for one of the UAX #29 break properties, where `value`
+/// returns the codepoints and property value of a single row, if it has
+/// one. Codepoints that aren't listed have the value `default`.
+///
+/// All of the break property commands share this, so that they support the
+/// same output formats.
+fn command<D: UcdFile>(
+    args: ArgMatches<'_>,
+    module: &str,
+    default: &str,
+    value: impl Fn(&D) -> Option<(Codepoints, &String)>,
+) -> Result<()> {
     let ucd_dir = args.ucd_dir()?;
-    let vals: Vec<WordBreak> = ucd_parse::parse(&ucd_dir)?;
+    let vals: Vec<D> = util::parse(ucd_dir)?;
 
     let mut byval: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
-    for x in &vals {
+    for (codepoints, val) in vals.iter().filter_map(value) {
         byval
-            .entry(x.value.clone())
+            .entry(val.clone())
             .or_insert(BTreeSet::new())
-            .extend(x.codepoints.into_iter().map(|c| c.value()));
+            .extend(codepoints.into_iter().map(|c| c.value()));
+    }
+    if byval.is_empty() {
+        return err!(
+            "found no values in {}",
+            D::relative_file_path().display()
+        );
     }
 
-    let mut wtr = args.writer("word_break")?;
-    if args.is_present("enum") {
+    let mut wtr = args.writer(module)?;
+    if args.is_present("utf8-trie") {
+        wtr.ranges_to_utf8_trie(args.name(), &byval)?;
+    } else if args.is_present("enum") {
         wtr.ranges_to_enum(args.name(), &byval)?;
+    } else if args.is_present("rust-enum") {
+        // Codepoints that aren't listed have the default value, so make
+        // sure there is a variant for it.
+        let mut variants = vec![];
+        if !byval.contains_key(default) {
+            variants.push(default);
+        }
+        variants.extend(byval.keys().map(String::as_str));
+        wtr.ranges_to_rust_enum(args.name(), &variants, &byval)?;
+    } else if args.is_present("combined") {
+        wtr.ranges_to_combined(args.name(), &byval)?;
     } else {
         wtr.names(byval.keys())?;
         for (val, set) in byval {
             wtr.ranges(&val, &set)?;
         }
     }
+    wtr.finish()?;
     Ok(())
 }
 
-pub fn sentence(args: ArgMatches<'_>) -> Result<()> {
+pub fn line_test(args: ArgMatches<'_>) -> Result<()> {
     let ucd_dir = args.ucd_dir()?;
-    let vals: Vec<SentenceBreak> = ucd_parse::parse(&ucd_dir)?;
+    let tests: Vec<LineBreakTest> = util::parse(ucd_dir)?;
+    let tests: Vec<Vec<String>> = tests.into_iter().map(|t| t.lines).collect();
 
-    let mut byval: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
-    for x in &vals {
-        byval
-            .entry(x.value.clone())
-            .or_insert(BTreeSet::new())
-            .extend(x.codepoints.into_iter().map(|c| c.value()));
-    }
-
-    let mut wtr = args.writer("sentence_break")?;
-    if args.is_present("enum") {
-        wtr.ranges_to_enum(args.name(), &byval)?;
-    } else {
-        wtr.names(byval.keys())?;
-        for (val, set) in byval {
-            wtr.ranges(&val, &set)?;
-        }
-    }
+    let mut wtr = args.writer("line_break_test")?;
+    wtr.break_tests(args.name(), &tests)?;
+    wtr.finish()?;
     Ok(())
 }
@@ -1,15 +1,19 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use ucd_parse::{self, UnicodeData};
+use ucd_parse::{
+    extracted::DerivedCombiningClass, Codepoint, CodepointRange, UnicodeData,
+};
 
 use crate::args::ArgMatches;
 use crate::error::Result;
-use crate::util::{print_property_values, PropertyValues};
+use crate::util::{
+    self, print_property_values, validate_against_derived, PropertyValues,
+};
 
 pub fn command(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
     let propvals = PropertyValues::from_ucd_dir(&dir)?;
-    let rows: Vec<UnicodeData> = ucd_parse::parse(&dir)?;
+    let rows: Vec<UnicodeData> = util::parse(&dir)?;
     let ccc_name = |ccc: u8| {
         propvals.canonical("canonicalcombiningclass", &ccc.to_string())
     };
@@ -41,14 +45,36 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
     // - All code points not explicitly listed for Canonical_Combining_Class
     //   have the value Not_Reordered (0).
     let not_reordered_name = ccc_name(0)?;
-    for cp in 0..=0x10FFFF {
+    for cp in CodepointRange::all().into_iter().map(Codepoint::value) {
         if !assigned.contains(&cp) {
             by_name.get_mut(&not_reordered_name).unwrap().insert(cp);
         }
     }
 
+    if args.is_present("validate-against-derived") {
+        validate_against_derived(
+            dir,
+            &by_name,
+            |row: &DerivedCombiningClass| {
+                propvals
+                    .canonical("canonicalcombiningclass", &row.combining_class)
+            },
+        )?;
+    }
+
     let mut wtr = args.writer("canonical_combining_class")?;
-    if args.is_present("enum") {
+    if args.is_present("bmp-index") {
+        let mut map = BTreeMap::new();
+        for (&ccc, name) in &name_map {
+            if ccc == 0 {
+                continue;
+            }
+            for &cp in &by_name[name] {
+                map.insert(cp, ccc as u64);
+            }
+        }
+        wtr.codepoint_to_u8_bmp_index(args.name(), &map)?;
+    } else if args.is_present("enum") {
         wtr.ranges_to_enum(args.name(), &by_name)?;
     } else if args.is_present("rust-enum") {
         wtr.ranges_to_rust_enum_with_custom_discriminants(
@@ -56,6 +82,8 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
             &name_map,
             &by_name,
         )?;
+    } else if args.is_present("combined") {
+        wtr.ranges_to_combined(args.name(), &by_name)?;
     } else {
         wtr.names(by_name.keys())?;
         for (name, set) in by_name {
@@ -63,5 +91,6 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
         }
     }
 
+    wtr.finish()?;
     Ok(())
 }
@@ -0,0 +1,52 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ucd_parse::{DerivedNormalizationProperty, UnicodeData};
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let rows: Vec<UnicodeData> = util::parse(dir)?;
+    let props: Vec<DerivedNormalizationProperty> = util::parse(dir)?;
+
+    let mut excluded = BTreeSet::new();
+    for prop in &props {
+        if prop.property == "Full_Composition_Exclusion" {
+            excluded.extend(prop.codepoints.into_iter().map(|cp| cp.value()));
+        }
+    }
+    if excluded.is_empty() {
+        return err!(
+            "found no Full_Composition_Exclusion codepoints in \
+             DerivedNormalizationProps.txt"
+        );
+    }
+
+    // Every canonical decomposition into a pair composes back to the
+    // original codepoint, unless it is excluded from composition. Hangul
+    // syllables are composed algorithmically, and aren't included.
+    let mut map = BTreeMap::new();
+    for row in &rows {
+        let decomposition = &row.decomposition;
+        let cp = row.codepoint.value();
+        if decomposition.tag.is_some()
+            || decomposition.len != 2
+            || excluded.contains(&cp)
+        {
+            continue;
+        }
+        let pair = (
+            decomposition.mapping[0].value(),
+            decomposition.mapping[1].value(),
+        );
+        map.insert(pair, cp);
+    }
+
+    let mut wtr = args.writer("canonical_composition")?;
+    let two_level = args.is_present("two-level");
+    wtr.codepoint_pairs_to_codepoint(args.name(), &map, two_level)?;
+    wtr.finish()?;
+    Ok(())
+}
@@ -1,14 +1,22 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
 
-use ucd_parse::{self, CaseFold, CaseStatus, Codepoint};
+use ucd_parse::{CaseFold, CaseStatus, Codepoint};
 
 use crate::args::ArgMatches;
 use crate::error::Result;
+use crate::util;
 
 pub fn command(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
+    if args.is_present("flat-table")
+        && !args.is_present("all-pairs")
+        && !args.is_present("reverse")
+    {
+        return err!("--flat-table requires --all-pairs or --reverse");
+    }
     let case_folding: BTreeMap<Codepoint, Vec<CaseFold>> =
-        ucd_parse::parse_many_by_codepoint(dir)?;
+        util::parse_many_by_codepoint(dir)?;
 
     let compute_all_pairs =
         args.is_present("all-pairs") || args.is_present("circular");
@@ -66,12 +74,36 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
             }
         }
         wtr.codepoint_to_codepoint(args.name(), &equiv)?;
+    } else if args.is_present("reverse") {
+        let mut reversed: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+        for (&k, &v) in &table {
+            reversed.entry(v).or_default().insert(k);
+        }
+        let flat = args.is_present("flat-table");
+        wtr.multi_codepoint_to_codepoint(args.name(), &reversed, flat)?;
     } else if args.is_present("all-pairs") {
         let flat = args.is_present("flat-table");
         wtr.multi_codepoint_to_codepoint(args.name(), &table_all, flat)?;
     } else {
         wtr.codepoint_to_codepoint(args.name(), &table)?;
     }
+    if args.is_present("turkic") {
+        // The Turkic mappings override the common mappings of the same
+        // codepoints, so they're kept out of the main table and written to
+        // their own table instead.
+        let mut turkic = BTreeMap::new();
+        for (&cp, case_folds) in &case_folding {
+            for case_fold in case_folds {
+                if case_fold.status == CaseStatus::Special {
+                    assert_eq!(case_fold.mapping.len(), 1);
+                    turkic.insert(cp.value(), case_fold.mapping[0].value());
+                }
+            }
+        }
+        let name = format!("{}_TURKIC", args.name());
+        wtr.codepoint_to_codepoint(&name, &turkic)?;
+    }
+    wtr.finish()?;
     Ok(())
 }
 
@@ -79,6 +111,19 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
 /// on the mapping's status. If `full` is true, then full case mappings are
 /// selected, otherwise simple case mappings are selected. If there are
 /// multiple valid choices, then an error is returned.
+/// Return the simple case folding of every codepoint that has one.
+pub fn simple_folds(ucd_dir: &OsStr) -> Result<BTreeMap<u32, u32>> {
+    let case_folding: BTreeMap<Codepoint, Vec<CaseFold>> =
+        util::parse_many_by_codepoint(ucd_dir)?;
+    let mut folds = BTreeMap::new();
+    for (cp, case_folds) in &case_folding {
+        if let Some(case_fold) = choose_fold(case_folds, false)? {
+            folds.insert(cp.value(), case_fold.mapping[0].value());
+        }
+    }
+    Ok(folds)
+}
+
 fn choose_fold(
     case_folds: &[CaseFold],
     full: bool,
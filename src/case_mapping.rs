@@ -1,18 +1,19 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
 
 use ucd_parse::{SpecialCaseMapping, UcdFile, UnicodeData};
 
 use crate::args::ArgMatches;
 use crate::error::Result;
+use crate::util;
+use crate::writer::{rust_const_name, Writer};
 
 pub fn command(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
     let mut lower_map: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
     let mut upper_map: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
     let mut title_map: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
-    let mut wtr = args.writer("case_mapping")?;
-    for item in UnicodeData::from_dir(dir)? {
-        let item = item?;
+    for item in util::parse::<_, UnicodeData>(dir)? {
         if let Some(lower) = item.simple_lowercase_mapping {
             lower_map.insert(item.codepoint.value(), vec![lower.value()]);
         }
@@ -30,23 +31,22 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
         vec!["LOWER", "UPPER", "TITLE"]
     };
 
-    if args.is_present("simple") {
-        let upper_map =
-            upper_map.into_iter().map(|(k, v)| (k, v[0])).collect();
-        let lower_map =
-            lower_map.into_iter().map(|(k, v)| (k, v[0])).collect();
-        let title_map =
-            title_map.into_iter().map(|(k, v)| (k, v[0])).collect();
-
-        for name in includes {
-            match name {
-                "LOWER" => wtr.codepoint_to_codepoint("LOWER", &lower_map)?,
-                "UPPER" => wtr.codepoint_to_codepoint("UPPER", &upper_map)?,
-                "TITlE" => wtr.codepoint_to_codepoint("TITlE", &title_map)?,
-                _ => (),
+    let mut tables: Vec<String> = includes
+        .iter()
+        .map(|name| {
+            if args.is_present("reverse") {
+                format!("{}_REVERSE", name)
+            } else {
+                name.to_string()
             }
-        }
-    } else {
+        })
+        .collect();
+    if args.is_present("titlecase-classes") {
+        tables.push("TITLECASE_CLASS".to_string());
+    }
+    let mut out = Output::new(&args, &tables)?;
+
+    if !args.is_present("simple") {
         for special in SpecialCaseMapping::from_dir(&dir)? {
             let special = special?;
             if !special.conditions.is_empty() {
@@ -73,21 +73,171 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
                 );
             }
         }
+    }
+
+    if args.is_present("reverse") {
         let flat = args.is_present("flat-table");
         for name in includes {
-            match name {
-                "LOWER" => {
-                    wtr.codepoint_to_codepoints("LOWER", &lower_map, flat)?
-                }
-                "UPPER" => {
-                    wtr.codepoint_to_codepoints("UPPER", &upper_map, flat)?
+            let map = match name {
+                "LOWER" => &lower_map,
+                "UPPER" => &upper_map,
+                "TITLE" => &title_map,
+                _ => continue,
+            };
+            out.write(&format!("{}_REVERSE", name), |wtr, name| {
+                wtr.multi_codepoint_to_codepoint(name, &reverse(map), flat)
+            })?;
+        }
+    } else if args.is_present("simple") {
+        let upper_map: BTreeMap<u32, u32> =
+            upper_map.into_iter().map(|(k, v)| (k, v[0])).collect();
+        let lower_map: BTreeMap<u32, u32> =
+            lower_map.into_iter().map(|(k, v)| (k, v[0])).collect();
+        let title_map: BTreeMap<u32, u32> =
+            title_map.into_iter().map(|(k, v)| (k, v[0])).collect();
+
+        for name in includes {
+            let map = match name {
+                "LOWER" => &lower_map,
+                "UPPER" => &upper_map,
+                "TITLE" => &title_map,
+                _ => continue,
+            };
+            out.write(name, |wtr, name| {
+                wtr.codepoint_to_codepoint(name, map)
+            })?;
+        }
+    } else {
+        let flat = args.is_present("flat-table");
+        for name in includes {
+            let map = match name {
+                "LOWER" => &lower_map,
+                "UPPER" => &upper_map,
+                "TITLE" => &title_map,
+                _ => continue,
+            };
+            out.write(name, |wtr, name| {
+                wtr.codepoint_to_codepoints(name, map, flat)
+            })?;
+        }
+    }
+    if args.is_present("titlecase-classes") {
+        out.write("TITLECASE_CLASS", |wtr, name| {
+            titlecase_classes(wtr, name, dir)
+        })?;
+    }
+    out.finish()
+}
+
+/// Where the case mapping tables are written.
+///
+/// Normally, every table is written by the same writer. With
+/// --split-tables, each table is instead written to a file of its own in
+/// the output directory, named after the table.
+struct Output<'a> {
+    args: &'a ArgMatches<'a>,
+    shared: Option<Writer>,
+    /// A map from the default name of a table to the name given to it with
+    /// --rename.
+    renames: BTreeMap<String, String>,
+}
+
+impl<'a> Output<'a> {
+    /// Create a new output for the tables with the given default names.
+    fn new(args: &'a ArgMatches<'a>, tables: &[String]) -> Result<Output<'a>> {
+        let mut renames = BTreeMap::new();
+        for rename in args.values_of_lossy("rename").unwrap_or_default() {
+            let (from, to) = match rename.split_once('=') {
+                Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+                    (rust_const_name(from), rust_const_name(to))
                 }
-                "TITLE" => {
-                    wtr.codepoint_to_codepoints("TITLE", &title_map, flat)?
+                _ => {
+                    return err!(
+                        "invalid --rename value {:?}: expected OLD=NEW",
+                        rename
+                    )
                 }
-                _ => (),
+            };
+            if !tables.contains(&from) {
+                return err!(
+                    "cannot rename {}, since it isn't emitted (the tables \
+                     are {})",
+                    from,
+                    tables.join(", ")
+                );
             }
+            if renames.insert(from.clone(), to).is_some() {
+                return err!("table {} is renamed more than once", from);
+            }
+        }
+        let shared = if args.is_present("split-tables") {
+            None
+        } else {
+            Some(args.writer("case_mapping")?)
+        };
+        Ok(Output { args, shared, renames })
+    }
+
+    /// Write the table with the given default name. `write` is given the
+    /// writer to use and the name to give the table.
+    fn write(
+        &mut self,
+        name: &str,
+        write: impl FnOnce(&mut Writer, &str) -> Result<()>,
+    ) -> Result<()> {
+        let name = self.renames.get(name).map_or(name, |n| n.as_str());
+        match self.shared {
+            Some(ref mut wtr) => write(wtr, name),
+            None => {
+                let mut wtr = self.args.writer(&name.to_lowercase())?;
+                write(&mut wtr, name)?;
+                wtr.finish()
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.shared {
+            Some(mut wtr) => wtr.finish(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Invert the given case mapping, so that each codepoint maps to the
+/// codepoints that map to it. Mappings to more than one codepoint are
+/// dropped, since their targets aren't a single codepoint.
+fn reverse(map: &BTreeMap<u32, Vec<u32>>) -> BTreeMap<u32, BTreeSet<u32>> {
+    let mut reversed: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+    for (&k, vs) in map {
+        if let [v] = vs[..] {
+            reversed.entry(v).or_default().insert(k);
+        }
+    }
+    reversed
+}
+
+/// Write a table that maps every codepoint that is Cased, Case_Ignorable or
+/// both to a bitset of those properties.
+///
+/// This is all a titlecasing routine needs to know about a codepoint when
+/// looking for the first cased character of a word, and for whether any
+/// cased characters follow it.
+fn titlecase_classes(wtr: &mut Writer, name: &str, dir: &OsStr) -> Result<()> {
+    const CASED: u64 = 1;
+    const CASE_IGNORABLE: u64 = 2;
+
+    let props = crate::property_bool::parse_properties(dir)?;
+    let mut classes: BTreeMap<u32, u64> = BTreeMap::new();
+    for (name, bit) in [("Cased", CASED), ("Case_Ignorable", CASE_IGNORABLE)] {
+        let set = match props.get(name) {
+            Some(set) => set,
+            None => return err!("missing {} property", name),
+        };
+        for &cp in set {
+            *classes.entry(cp).or_insert(0) |= bit;
         }
     }
+    wtr.ranges_to_unsigned_integer(name, &classes)?;
     Ok(())
 }
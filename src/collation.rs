@@ -0,0 +1,33 @@
+use ucd_parse::AllKeysLine;
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let lines: Vec<AllKeysLine> = util::parse(dir)?;
+
+    let no_contractions = args.is_present("no-contractions");
+    let mut implicit_weights = vec![];
+    let mut entries = vec![];
+    for line in lines {
+        match line {
+            AllKeysLine::Version(_) => {}
+            AllKeysLine::ImplicitWeights(implicit) => {
+                implicit_weights.push(implicit)
+            }
+            AllKeysLine::Entry(entry) => {
+                if no_contractions && entry.codepoints.len() > 1 {
+                    continue;
+                }
+                entries.push(entry);
+            }
+        }
+    }
+
+    let mut wtr = args.writer("collation")?;
+    wtr.collation(args.name(), &implicit_weights, &entries)?;
+    wtr.finish()?;
+    Ok(())
+}
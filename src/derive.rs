@@ -0,0 +1,316 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+use std::rc::Rc;
+
+use ucd_parse::{EmojiProperty, Script, UnicodeData};
+
+use crate::error::Result;
+use crate::util::{self, PropertyValues};
+
+/// A property that can be derived by the engine, along with what it is
+/// derived from.
+pub struct Derivation {
+    /// The canonical name of the property.
+    pub name: &'static str,
+    /// The UCD files read by `derive`, for documentation.
+    pub files: &'static [&'static str],
+    /// The other properties read by `derive`. These are always derived
+    /// before this one.
+    pub properties: &'static [&'static str],
+    derive: fn(&mut Engine<'_>) -> Result<Derived>,
+}
+
+/// Every property known to the engine.
+pub const DERIVATIONS: &[Derivation] = &[
+    Derivation {
+        name: "Bidi_Class",
+        files: &[
+            "UnicodeData.txt",
+            "DerivedCoreProperties.txt",
+            "extracted/DerivedBidiClass.txt",
+        ],
+        properties: &[],
+        derive: crate::bidi_class::derive,
+    },
+    Derivation {
+        name: "General_Category",
+        files: &["UnicodeData.txt"],
+        properties: &[],
+        derive: crate::general_category::derive,
+    },
+    Derivation {
+        name: "Joining_Type",
+        files: &["ArabicShaping.txt"],
+        properties: &["General_Category"],
+        derive: crate::joining_type::derive,
+    },
+    Derivation {
+        name: "Script",
+        files: &["Scripts.txt"],
+        properties: &[],
+        derive: crate::script::derive_script,
+    },
+    Derivation {
+        name: "Script_Extensions",
+        files: &["Scripts.txt", "ScriptExtensions.txt"],
+        properties: &[],
+        derive: crate::script::derive_script_extension,
+    },
+];
+
+/// The values of a property for every codepoint, along with the rule that
+/// assigned each value.
+#[derive(Debug, Default)]
+pub struct Derived {
+    /// The codepoints that have each value of the property. A codepoint may
+    /// have more than one value for properties like Script_Extensions.
+    pub values: BTreeMap<String, BTreeSet<u32>>,
+    /// The rules that assigned values, in the order they were added. Each
+    /// codepoint is assigned values by at most one rule.
+    rules: Vec<Rule>,
+}
+
+/// A single way that a property's values are assigned to codepoints.
+#[derive(Debug)]
+struct Rule {
+    /// Where the values assigned by this rule come from, e.g., `listed in
+    /// ArabicShaping.txt`.
+    why: String,
+    /// The other properties whose values explain the values assigned by this
+    /// rule.
+    uses: &'static [&'static str],
+    /// The codepoints assigned values by this rule.
+    codepoints: BTreeSet<u32>,
+}
+
+impl Derived {
+    /// Add a rule with the given explanation, which looks at the given
+    /// properties, and return its index for use with `assign`.
+    pub fn rule<S: Into<String>>(
+        &mut self,
+        why: S,
+        uses: &'static [&'static str],
+    ) -> usize {
+        self.rules.push(Rule {
+            why: why.into(),
+            uses,
+            codepoints: BTreeSet::new(),
+        });
+        self.rules.len() - 1
+    }
+
+    /// Give the codepoint the value, as decided by the given rule.
+    pub fn assign(&mut self, rule: usize, cp: u32, value: &str) {
+        self.rules[rule].codepoints.insert(cp);
+        match self.values.get_mut(value) {
+            Some(set) => {
+                set.insert(cp);
+            }
+            None => {
+                self.values.insert(value.to_string(), BTreeSet::from([cp]));
+            }
+        }
+    }
+
+    /// Make sure that the given value is present, even if no codepoint has
+    /// it.
+    pub fn value(&mut self, value: &str) {
+        self.values.entry(value.to_string()).or_default();
+    }
+
+    /// Return the values of the given codepoint.
+    pub fn values_of(&self, cp: u32) -> Vec<&str> {
+        self.values
+            .iter()
+            .filter(|(_, set)| set.contains(&cp))
+            .map(|(value, _)| value.as_str())
+            .collect()
+    }
+
+    /// Return the explanation of the rule that assigned values to the given
+    /// codepoint, along with the properties it looks at.
+    pub fn why(&self, cp: u32) -> Option<(&str, &'static [&'static str])> {
+        self.rules
+            .iter()
+            .find(|rule| rule.codepoints.contains(&cp))
+            .map(|rule| (rule.why.as_str(), rule.uses))
+    }
+}
+
+/// An engine that derives properties from a UCD directory.
+///
+/// Each property is derived at most once, after the properties it depends
+/// on, and UCD files needed by more than one property are only parsed once.
+pub struct Engine<'a> {
+    dir: &'a OsStr,
+    propvals: Rc<PropertyValues>,
+    unicode_data: Option<Rc<Vec<UnicodeData>>>,
+    scripts: Option<Rc<Vec<Script>>>,
+    derived: BTreeMap<&'static str, Rc<Derived>>,
+    /// The properties currently being derived, used to detect cycles.
+    deriving: Vec<&'static str>,
+}
+
+impl<'a> Engine<'a> {
+    pub fn new(dir: &'a OsStr) -> Result<Engine<'a>> {
+        let propvals = PropertyValues::cached(dir)?;
+        Ok(Engine::with_property_values(dir, propvals))
+    }
+
+    /// Create an engine that uses property values that were already read
+    /// from the given directory.
+    pub fn with_property_values(
+        dir: &'a OsStr,
+        propvals: Rc<PropertyValues>,
+    ) -> Engine<'a> {
+        Engine {
+            dir,
+            propvals,
+            unicode_data: None,
+            scripts: None,
+            derived: BTreeMap::new(),
+            deriving: vec![],
+        }
+    }
+
+    /// The UCD directory that properties are derived from.
+    pub fn dir(&self) -> &'a OsStr {
+        self.dir
+    }
+
+    /// The property names and values of the UCD directory.
+    pub fn propvals(&self) -> Rc<PropertyValues> {
+        self.propvals.clone()
+    }
+
+    /// Return the rows of UnicodeData.txt, without expanding ranges.
+    pub fn unicode_data(&mut self) -> Result<Rc<Vec<UnicodeData>>> {
+        if self.unicode_data.is_none() {
+            self.unicode_data = Some(Rc::new(util::parse(self.dir)?));
+        }
+        Ok(self.unicode_data.clone().unwrap())
+    }
+
+    /// Return the rows of Scripts.txt.
+    pub fn scripts(&mut self) -> Result<Rc<Vec<Script>>> {
+        if self.scripts.is_none() {
+            self.scripts = Some(Rc::new(util::parse(self.dir)?));
+        }
+        Ok(self.scripts.clone().unwrap())
+    }
+
+    /// Return the derivation of the property with the given name, which may
+    /// be any of its aliases.
+    pub fn derivation(&self, name: &str) -> Result<&'static Derivation> {
+        let name = self.propvals.property.canonical(name)?;
+        match DERIVATIONS.iter().find(|d| d.name == name) {
+            Some(derivation) => Ok(derivation),
+            None => err!(
+                "{} can't be derived (derivable properties are {})",
+                name,
+                DERIVATIONS
+                    .iter()
+                    .map(|d| d.name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+        }
+    }
+
+    /// Return the codepoints that have the given value of the given
+    /// property, along with a description of them in canonical names, e.g.,
+    /// `Script=Greek`. The property and value may be any of their aliases.
+    ///
+    /// Without a value, the property must be a boolean property, and the
+    /// codepoints that have it are returned.
+    pub fn codepoints_where(
+        &mut self,
+        property: &str,
+        value: Option<&str>,
+    ) -> Result<(String, BTreeSet<u32>)> {
+        let value = match value {
+            None => return self.bool_property(property),
+            Some(value) => value,
+        };
+        let derivation = self.derivation(property)?;
+        // Script_Extensions doesn't have values of its own in
+        // PropertyValueAliases.txt, since they are the values of Script.
+        let of = match derivation.name {
+            "Script_Extensions" => "Script",
+            name => name,
+        };
+        let value = self.propvals.canonical(of, value)?;
+        let derived = self.property(derivation.name)?;
+        let codepoints =
+            derived.values.get(&value).cloned().unwrap_or_default();
+        Ok((format!("{}={}", derivation.name, value), codepoints))
+    }
+
+    /// Return the codepoints that have the given boolean property, along
+    /// with its canonical name. The name is matched according to UAX44-LM3.
+    ///
+    /// Boolean properties aren't derived by the engine, so they are read
+    /// on every call.
+    fn bool_property(&self, name: &str) -> Result<(String, BTreeSet<u32>)> {
+        let normalize = |name: &str| {
+            let mut name = name.to_string();
+            ucd_util::symbolic_name_normalize(&mut name);
+            name
+        };
+        let name = self
+            .propvals
+            .property
+            .canonical(name)
+            .unwrap_or_else(|_| name.to_string());
+        let key = normalize(&name);
+        let mut props = crate::property_bool::parse_properties(self.dir)?;
+        if !props.keys().any(|n| normalize(n) == key) {
+            let rows: Vec<EmojiProperty> = util::parse(self.dir)?;
+            for row in &rows {
+                props
+                    .entry(row.property.clone())
+                    .or_default()
+                    .extend(row.codepoints.into_iter().map(|c| c.value()));
+            }
+        }
+        if let Some(found) =
+            props.into_iter().find(|(n, _)| normalize(n) == key)
+        {
+            return Ok(found);
+        }
+        match DERIVATIONS.iter().find(|d| d.name == name) {
+            Some(d) => err!(
+                "{} isn't a boolean property, so it needs a value, \
+                 e.g., {}=VALUE",
+                d.name,
+                d.name
+            ),
+            None => err!("unrecognized boolean property: {:?}", name),
+        }
+    }
+
+    /// Derive the property with the given name, which may be any of its
+    /// aliases.
+    pub fn property(&mut self, name: &str) -> Result<Rc<Derived>> {
+        let derivation = self.derivation(name)?;
+        if let Some(derived) = self.derived.get(derivation.name) {
+            return Ok(derived.clone());
+        }
+        if self.deriving.contains(&derivation.name) {
+            return err!(
+                "{} depends on itself: {} -> {}",
+                derivation.name,
+                self.deriving.join(" -> "),
+                derivation.name,
+            );
+        }
+        self.deriving.push(derivation.name);
+        for &dep in derivation.properties {
+            self.property(dep)?;
+        }
+        let derived = Rc::new((derivation.derive)(self)?);
+        self.deriving.pop();
+        self.derived.insert(derivation.name, derived.clone());
+        Ok(derived)
+    }
+}
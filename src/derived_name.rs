@@ -0,0 +1,37 @@
+use ucd_parse::{extracted::DerivedName, Codepoints};
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let patterns = patterns(&util::parse(dir)?);
+
+    let mut wtr = args.writer("derived_name")?;
+    wtr.ranges_to_name_patterns(args.name(), &patterns)?;
+    wtr.finish()?;
+    Ok(())
+}
+
+/// Return the (start, end, prefix) triples of every algorithmically named
+/// range in the given rows of DerivedName.txt, sorted by codepoint.
+pub fn patterns(rows: &[DerivedName]) -> Vec<(u32, u32, String)> {
+    // DerivedName.txt lists algorithmically named ranges with a trailing `*`
+    // where the codepoint's hex value (at least 4 digits) is substituted, so
+    // we keep only those and drop the `*`.
+    let mut patterns = vec![];
+    for row in rows {
+        let prefix = match row.name.strip_suffix('*') {
+            None => continue,
+            Some(prefix) => prefix.to_string(),
+        };
+        let (start, end) = match row.codepoints {
+            Codepoints::Single(cp) => (cp.value(), cp.value()),
+            Codepoints::Range(r) => (r.start.value(), r.end.value()),
+        };
+        patterns.push((start, end, prefix));
+    }
+    patterns.sort();
+    patterns
+}
@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+use ucd_parse::{Codepoint, DoNotEmit};
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let rows: Vec<DoNotEmit> = util::parse(dir)?;
+
+    let mut by_kind: BTreeMap<String, BTreeMap<String, String>> =
+        BTreeMap::new();
+    for row in &rows {
+        by_kind
+            .entry(row.kind.clone())
+            .or_default()
+            .insert(to_string(&row.sequence)?, to_string(&row.replacement)?);
+    }
+    if args.is_present("list-types") {
+        for kind in by_kind.keys() {
+            println!("{}", kind);
+        }
+        return Ok(());
+    }
+    let kinds: Vec<&str> = by_kind.keys().map(|k| k.as_str()).collect();
+    let filter = args.filter(|name| canonical(&kinds, name))?;
+    filter.check_included(by_kind.keys())?;
+
+    let mut wtr = args.writer("do_not_emit")?;
+    if args.is_present("combined") {
+        let mut combined = BTreeMap::new();
+        for (kind, map) in &by_kind {
+            if filter.contains(kind) {
+                combined.extend(map.clone());
+            }
+        }
+        wtr.string_to_string(args.name(), &combined)?;
+    } else {
+        for (kind, map) in &by_kind {
+            if filter.contains(kind) {
+                wtr.string_to_string(kind, map)?;
+            }
+        }
+    }
+    wtr.finish()?;
+    Ok(())
+}
+
+/// Return the canonical name of the given DoNotEmit type, which is matched
+/// according to UAX44-LM3.
+fn canonical(kinds: &[&str], name: &str) -> Result<String> {
+    let normalize = |name: &str| {
+        let mut name = name.to_string();
+        ucd_util::symbolic_name_normalize(&mut name);
+        name
+    };
+    let key = normalize(name);
+    match kinds.iter().find(|&&k| normalize(k) == key) {
+        Some(&kind) => Ok(kind.to_string()),
+        None => {
+            err!("unrecognized DoNotEmit type: {:?} (see --list-types)", name)
+        }
+    }
+}
+
+/// Return the UTF-8 encoding of the given sequence of codepoints.
+fn to_string(cps: &[Codepoint]) -> Result<String> {
+    cps.iter()
+        .map(|cp| match cp.scalar() {
+            Some(ch) => Ok(ch),
+            None => err!("DoNotEmit sequence contains a surrogate: {}", cp),
+        })
+        .collect()
+}
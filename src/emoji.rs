@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ucd_parse::{self, EmojiProperty, EmojiSequence, EmojiZwjSequence};
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util;
+
+/// The emoji properties of codepoints, from emoji-data.txt.
+const PROPERTIES: &[&str] = &[
+    "Emoji",
+    "Emoji_Presentation",
+    "Emoji_Modifier",
+    "Emoji_Modifier_Base",
+    "Emoji_Component",
+    "Extended_Pictographic",
+];
+
+/// The emoji properties of strings, from emoji-sequences.txt and
+/// emoji-zwj-sequences.txt. RGI_Emoji is the union of all of the others.
+const SEQUENCE_PROPERTIES: &[&str] = &[
+    "Basic_Emoji",
+    "Emoji_Keycap_Sequence",
+    "RGI_Emoji_Flag_Sequence",
+    "RGI_Emoji_Tag_Sequence",
+    "RGI_Emoji_Modifier_Sequence",
+    "RGI_Emoji_ZWJ_Sequence",
+    "RGI_Emoji",
+];
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let mut names = PROPERTIES.to_vec();
+    if args.is_present("sequences") {
+        names.extend_from_slice(SEQUENCE_PROPERTIES);
+    }
+    if args.is_present("list-properties") {
+        for name in &names {
+            println!("{}", name);
+        }
+        return Ok(());
+    }
+    let filter = args.filter(|name| canonical(&names, name))?;
+
+    let rows: Vec<EmojiProperty> = util::parse(dir)?;
+    let mut by_name: BTreeMap<&str, BTreeSet<u32>> = BTreeMap::new();
+    for row in &rows {
+        if let Some(&name) = PROPERTIES.iter().find(|&&p| p == row.property) {
+            by_name
+                .entry(name)
+                .or_default()
+                .extend(row.codepoints.into_iter().map(|c| c.value()));
+        }
+    }
+    let sequences = if args.is_present("sequences") {
+        sequence_sets(dir)?
+    } else {
+        BTreeMap::new()
+    };
+    // Older versions of Unicode don't have every property, e.g.,
+    // Extended_Pictographic was added in 11.0.
+    let available = by_name.keys().chain(sequences.keys());
+    filter.check_included(available.clone())?;
+
+    let mut wtr = args.writer("emoji")?;
+    wtr.names(available.filter(|n| filter.contains(n)))?;
+    for &name in PROPERTIES {
+        if let Some(set) = by_name.get(name).filter(|_| filter.contains(name))
+        {
+            wtr.ranges(name, set)?;
+        }
+    }
+    for &name in SEQUENCE_PROPERTIES {
+        if let Some(set) =
+            sequences.get(name).filter(|_| filter.contains(name))
+        {
+            wtr.strings(name, set)?;
+        }
+    }
+    if args.is_present("flag-pairs") {
+        wtr.codepoint_pairs("RGI_Emoji_Flag_Pairs", &flag_pairs(dir)?)?;
+    }
+    wtr.finish()?;
+    Ok(())
+}
+
+/// Return the canonical name of the given emoji property, which is matched
+/// according to UAX44-LM3.
+fn canonical(names: &[&str], name: &str) -> Result<String> {
+    let normalize = |name: &str| {
+        let mut name = name.to_string();
+        ucd_util::symbolic_name_normalize(&mut name);
+        name
+    };
+    let key = normalize(name);
+    match names.iter().find(|&&n| normalize(n) == key) {
+        Some(&name) => Ok(name.to_string()),
+        None => err!(
+            "unrecognized emoji property: {:?} (see --list-properties)",
+            name
+        ),
+    }
+}
+
+/// Return the UTF-8 encoded sequences of each emoji sequence property.
+fn sequence_sets(
+    dir: &std::ffi::OsStr,
+) -> Result<BTreeMap<&'static str, BTreeSet<String>>> {
+    let to_string = |cps: &[ucd_parse::Codepoint]| -> Result<String> {
+        cps.iter()
+            .map(|cp| match cp.scalar() {
+                Some(ch) => Ok(ch),
+                None => err!("emoji sequence contains a surrogate: {}", cp),
+            })
+            .collect()
+    };
+    let mut sets: BTreeMap<&'static str, BTreeSet<String>> = BTreeMap::new();
+    let mut insert = |property: &str, seq: String| -> Result<()> {
+        let name = match SEQUENCE_PROPERTIES.iter().find(|&&p| p == property) {
+            Some(&name) => name,
+            None => return err!("unrecognized emoji sequence: {}", property),
+        };
+        sets.entry("RGI_Emoji").or_default().insert(seq.clone());
+        sets.entry(name).or_default().insert(seq);
+        Ok(())
+    };
+    let rows: Vec<EmojiSequence> = util::parse(dir)?;
+    for row in &rows {
+        for seq in row.codepoints.sequences() {
+            insert(&row.property, to_string(&seq)?)?;
+        }
+    }
+    let rows: Vec<EmojiZwjSequence> = util::parse(dir)?;
+    for row in &rows {
+        insert(&row.property, to_string(&row.codepoints)?)?;
+    }
+    Ok(sets)
+}
+
+/// Return the pairs of Regional Indicators of every RGI flag emoji, from
+/// emoji-sequences.txt.
+fn flag_pairs(dir: &std::ffi::OsStr) -> Result<BTreeSet<(u32, u32)>> {
+    let mut pairs = BTreeSet::new();
+    let rows: Vec<EmojiSequence> = util::parse(dir)?;
+    for row in rows.iter().filter(|r| r.property == "RGI_Emoji_Flag_Sequence")
+    {
+        for seq in row.codepoints.sequences() {
+            match seq[..] {
+                [a, b] => pairs.insert((a.value(), b.value())),
+                _ => {
+                    return err!(
+                        "flag sequence doesn't have two codepoints: {:?}",
+                        seq
+                    )
+                }
+            };
+        }
+    }
+    Ok(pairs)
+}
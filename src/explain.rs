@@ -0,0 +1,67 @@
+use ucd_parse::Codepoint;
+
+use crate::args::ArgMatches;
+use crate::derive::{Engine, DERIVATIONS};
+use crate::error::Result;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    if args.is_present("list-properties") {
+        for derivation in DERIVATIONS {
+            let mut inputs: Vec<&str> = derivation.files.to_vec();
+            inputs.extend(derivation.properties);
+            println!("{}: {}", derivation.name, inputs.join(", "));
+        }
+        return Ok(());
+    }
+    let dir = args.ucd_dir()?;
+    let property = match args.value_of("property") {
+        Some(property) => property,
+        None => return err!("missing property to explain"),
+    };
+    let codepoint = match args.value_of("codepoint") {
+        Some(codepoint) => parse_codepoint(codepoint)?,
+        None => return err!("missing codepoint to explain"),
+    };
+    let mut engine = Engine::new(dir)?;
+    explain(&mut engine, property, codepoint, 0)
+}
+
+/// Print the values of the given property for the given codepoint, along
+/// with the rule that assigned them. The values of any other properties
+/// that the rule looks at are explained too, indented one more level.
+fn explain(
+    engine: &mut Engine<'_>,
+    property: &str,
+    cp: u32,
+    depth: usize,
+) -> Result<()> {
+    let name = engine.derivation(property)?.name;
+    let derived = engine.property(name)?;
+    let indent = "  ".repeat(depth);
+    let values = derived.values_of(cp);
+    if values.is_empty() {
+        println!("{}U+{:04X} has no {} value", indent, cp, name);
+    } else {
+        println!("{}U+{:04X} {}={}", indent, cp, name, values.join(" "));
+    }
+    match derived.why(cp) {
+        None => println!("{}  not assigned by any rule", indent),
+        Some((why, uses)) => {
+            println!("{}  {}", indent, why);
+            for &other in uses {
+                explain(engine, other, cp, depth + 1)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse a codepoint written in hex, with or without a `U+` prefix.
+fn parse_codepoint(s: &str) -> Result<u32> {
+    let hex =
+        s.strip_prefix("U+").or_else(|| s.strip_prefix("u+")).unwrap_or(s);
+    match hex.parse::<Codepoint>() {
+        Ok(cp) => Ok(cp.value()),
+        Err(_) => err!("invalid codepoint: {:?} (expected e.g. U+00E9)", s),
+    }
+}
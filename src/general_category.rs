@@ -1,16 +1,19 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use ucd_parse::{self, UnicodeData, UnicodeDataExpander};
+use ucd_parse::{Codepoint, CodepointRange, UnicodeData, UnicodeDataExpander};
 
 use crate::args::ArgMatches;
+use crate::derive::{Derived, Engine};
 use crate::error::Result;
-use crate::util::{print_property_values, PropertyValues};
+use crate::util::{
+    enum_aliases, filter_enum, print_property_values, PropertyValues,
+};
 
 pub fn command(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
-    let propvals = PropertyValues::from_ucd_dir(&dir)?;
+    let mut engine = Engine::new(dir)?;
+    let propvals = engine.propvals();
     let filter = args.filter(|name| propvals.canonical("gc", name))?;
-    let unexpanded = ucd_parse::parse(&dir)?;
 
     // If we were tasked with listing the available categories, then do that
     // and quit.
@@ -18,13 +21,16 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
         return print_property_values(&propvals, "General_Category");
     }
 
-    let mut bycat = expand_into_categories(unexpanded, &propvals)?;
+    let mut bycat = engine.property("General_Category")?.values.clone();
 
     // As another special case, collect all "related" groups of categories.
     // But don't do this when printing an enumeration, because in an
     // enumeration each codepoint should belong to exactly one category, which
     // is not true if we include related categories.
-    if !args.is_present("enum") && !args.is_present("rust-enum") {
+    let is_enum = args.is_present("enum")
+        || args.is_present("rust-enum")
+        || args.is_present("utf8-trie");
+    if !is_enum {
         for (name, set) in related(&propvals, &bycat) {
             if filter.contains(&name) {
                 bycat.insert(name, set);
@@ -32,13 +38,21 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
         }
     }
     // Finally, filter out any sets according to what the user asked for.
-    let bycat = bycat
-        .into_iter()
-        .filter(|&(ref name, _)| filter.contains(name))
-        .collect();
+    filter.check_included(bycat.keys())?;
+    let other = args.value_of("enum-other");
+    let bycat: BTreeMap<String, BTreeSet<u32>> = if is_enum {
+        filter_enum(bycat, &filter, other)?
+    } else {
+        bycat.into_iter().filter(|(name, _)| filter.contains(name)).collect()
+    };
 
     let mut wtr = args.writer("general_category")?;
-    if args.is_present("enum") {
+    if args.is_present("enum-aliases") {
+        let aliases = enum_aliases(&propvals, "gc", bycat.keys(), other)?;
+        wtr.ranges_to_enum_with_aliases(args.name(), &bycat, &aliases)?;
+    } else if args.is_present("utf8-trie") {
+        wtr.ranges_to_utf8_trie(args.name(), &bycat)?;
+    } else if args.is_present("enum") {
         wtr.ranges_to_enum(args.name(), &bycat)?;
     } else if args.is_present("rust-enum") {
         let variants = bycat.keys().map(String::as_str).collect::<Vec<_>>();
@@ -52,9 +66,31 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
         }
     }
 
+    wtr.finish()?;
     Ok(())
 }
 
+/// Derive the General_Category of every codepoint. Codepoints that aren't
+/// listed in UnicodeData.txt are Unassigned.
+pub fn derive(engine: &mut Engine<'_>) -> Result<Derived> {
+    let propvals = engine.propvals();
+    let rows = engine.unicode_data()?;
+    let bycat = expand_into_categories(rows.to_vec(), &propvals)?;
+
+    let mut derived = Derived::default();
+    let listed = derived.rule("listed in UnicodeData.txt", &[]);
+    let unlisted = derived.rule("not listed in UnicodeData.txt", &[]);
+    let unassigned = propvals.canonical("gc", "unassigned")?;
+    for (name, set) in &bycat {
+        derived.value(name);
+        let rule = if *name == unassigned { unlisted } else { listed };
+        for &cp in set {
+            derived.assign(rule, cp, name);
+        }
+    }
+    Ok(derived)
+}
+
 /// Expand a list of UnicodeData rows and group by category.
 pub fn expand_into_categories(
     unexpanded: Vec<UnicodeData>,
@@ -78,7 +114,7 @@ pub fn expand_into_categories(
     // As a special case, collect all unassigned codepoints.
     let unassigned_name = propvals.canonical("gc", "unassigned")?.to_string();
     bycat.insert(unassigned_name.clone(), BTreeSet::new());
-    for cp in 0..=0x10FFFF {
+    for cp in CodepointRange::all().into_iter().map(Codepoint::value) {
         if !assigned.contains(&cp) {
             bycat.get_mut(&unassigned_name).unwrap().insert(cp);
         }
@@ -92,7 +128,7 @@ pub fn expand_into_categories(
 ///
 /// The given `cats` should correspond to the normal set of general categories,
 /// keyed by canonical name.
-fn related(
+pub fn related(
     propvals: &PropertyValues,
     cats: &BTreeMap<String, BTreeSet<u32>>,
 ) -> BTreeMap<String, BTreeSet<u32>> {
@@ -0,0 +1,50 @@
+use std::collections::BTreeSet;
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::property_bool::parse_properties;
+use crate::util::{to_ranges, PropertyNames};
+
+/// Codepoints whose classification is checked by every test, in addition
+/// to the codepoints at the boundaries of each table.
+const WELL_KNOWN: &[u32] = &[
+    0x09, 0x20, 0x30, 0x41, 0x5F, 0x61, 0xA0, 0xE9, 0x0300, 0x0391, 0x05D0,
+    0x0627, 0x0905, 0x200D, 0x3042, 0x4E00, 0xAC00, 0xFEFF, 0x1F600,
+];
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let by_name = parse_properties(dir)?;
+    let properties = PropertyNames::from_ucd_dir(dir)?;
+    let filter = args.filter(|name| properties.canonical(name))?;
+    filter.check_included(by_name.keys())?;
+    let module = args.value_of("module").unwrap();
+
+    let mut wtr = args.writer("golden_tests")?;
+    for (name, set) in &by_name {
+        if filter.contains(name) && !set.is_empty() {
+            wtr.ranges_test(name, module, set, &samples(set))?;
+        }
+    }
+    wtr.finish()?;
+    Ok(())
+}
+
+/// Return the codepoints whose membership in the given set should be
+/// tested: the well known codepoints, along with the boundaries of the
+/// first, middle and last ranges in the set and their neighbors.
+fn samples(set: &BTreeSet<u32>) -> BTreeSet<u32> {
+    let mut samples: BTreeSet<u32> = WELL_KNOWN.iter().cloned().collect();
+    let ranges = to_ranges(set.iter().cloned());
+    for &i in &[0, ranges.len() / 2, ranges.len() - 1] {
+        let (start, end) = ranges[i];
+        samples.extend(&[start, end]);
+        if start > 0 {
+            samples.insert(start - 1);
+        }
+        if end < 0x10FFFF {
+            samples.insert(end + 1);
+        }
+    }
+    samples
+}
@@ -0,0 +1,305 @@
+use std::path::Path;
+
+use ucd_parse::{
+    self, extracted::DerivedName, Age, AllKeysLine, ArabicShaping,
+    BidiCharacterTest, BidiMirroring, BidiTestLine, CaseFold, CoreProperty,
+    DerivedNormalizationProperty, DoNotEmit, EastAsianWidth, EmojiProperty,
+    EmojiSequence, EmojiZwjSequence, GraphemeClusterBreak,
+    GraphemeClusterBreakTest, JamoShortName, LineBreakTest, NameAlias,
+    NamedSequence, NormalizationTestLine, Property, PropertyAlias,
+    PropertyValueAlias, Script, ScriptExtension, SentenceBreak,
+    SentenceBreakTest, SpecialCaseMapping, UcdFile, UnicodeData, WordBreak,
+    WordBreakTest,
+};
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util::{self, json_string};
+
+/// A UCD file that ucd-generate knows how to parse.
+struct KnownFile {
+    path: &'static Path,
+    count: fn(&Path) -> Result<usize>,
+}
+
+/// What we found out about a single known file in a UCD directory.
+struct FileInfo {
+    path: &'static Path,
+    /// None when the file doesn't exist. Otherwise, the number of rows in the
+    /// file, or an error message if the file could not be parsed.
+    rows: Option<std::result::Result<usize, String>>,
+}
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = Path::new(args.ucd_dir()?);
+    let version = util::ucd_version(dir).ok();
+
+    let mut files = vec![];
+    for known in known_files() {
+        let rows = if dir.join(known.path).exists() {
+            Some((known.count)(dir).map_err(|err| err.to_string()))
+        } else {
+            None
+        };
+        files.push(FileInfo { path: known.path, rows });
+    }
+    let unavailable = |required: &[&Path]| -> Vec<&'static Path> {
+        files
+            .iter()
+            .filter(|f| required.contains(&f.path))
+            .filter(|f| !matches!(f.rows, Some(Ok(_))))
+            .map(|f| f.path)
+            .collect()
+    };
+    let commands: Vec<(&str, Vec<&Path>)> = commands()
+        .into_iter()
+        .map(|(name, required)| (name, unavailable(&required)))
+        .collect();
+
+    if args.is_present("json") {
+        print_json(version, &files, &commands);
+    } else {
+        print_text(version, &files, &commands);
+    }
+    Ok(())
+}
+
+fn print_text(
+    version: Option<(u64, u64, u64)>,
+    files: &[FileInfo],
+    commands: &[(&str, Vec<&Path>)],
+) {
+    match version {
+        None => println!("Unicode version: unknown"),
+        Some((major, minor, patch)) => {
+            println!("Unicode version: {}.{}.{}", major, minor, patch)
+        }
+    }
+    println!();
+    println!("Files:");
+    for f in files {
+        let path = f.path.display();
+        match f.rows {
+            None => println!("  missing  {}", path),
+            Some(Ok(rows)) => println!("  present  {} ({} rows)", path, rows),
+            Some(Err(ref err)) => println!("  invalid  {} ({})", path, err),
+        }
+    }
+    println!();
+    println!("Subcommands:");
+    for (name, unavailable) in commands {
+        if unavailable.is_empty() {
+            println!("  usable    {}", name);
+        } else {
+            let paths: Vec<String> =
+                unavailable.iter().map(|p| p.display().to_string()).collect();
+            println!("  unusable  {} (needs {})", name, paths.join(", "));
+        }
+    }
+}
+
+fn print_json(
+    version: Option<(u64, u64, u64)>,
+    files: &[FileInfo],
+    commands: &[(&str, Vec<&Path>)],
+) {
+    println!("{{");
+    match version {
+        None => println!("  \"version\": null,"),
+        Some((major, minor, patch)) => {
+            println!("  \"version\": \"{}.{}.{}\",", major, minor, patch)
+        }
+    }
+    println!("  \"files\": [");
+    for (i, f) in files.iter().enumerate() {
+        let path = json_string(&f.path.display().to_string());
+        let (present, rows, error) = match f.rows {
+            None => (false, "null".to_string(), "null".to_string()),
+            Some(Ok(rows)) => (true, rows.to_string(), "null".to_string()),
+            Some(Err(ref err)) => (true, "null".to_string(), json_string(err)),
+        };
+        println!(
+            "    {{\"path\": {}, \"present\": {}, \"rows\": {}, \
+             \"error\": {}}}{}",
+            path,
+            present,
+            rows,
+            error,
+            if i + 1 == files.len() { "" } else { "," },
+        );
+    }
+    println!("  ],");
+    println!("  \"subcommands\": [");
+    for (i, (name, unavailable)) in commands.iter().enumerate() {
+        let needs: Vec<String> = unavailable
+            .iter()
+            .map(|p| json_string(&p.display().to_string()))
+            .collect();
+        println!(
+            "    {{\"name\": {}, \"usable\": {}, \"needs\": [{}]}}{}",
+            json_string(name),
+            unavailable.is_empty(),
+            needs.join(", "),
+            if i + 1 == commands.len() { "" } else { "," },
+        );
+    }
+    println!("  ]");
+    println!("}}");
+}
+
+/// Count the rows in the given UCD file.
+fn count<D: UcdFile>(ucd_dir: &Path) -> Result<usize> {
+    let mut rows = 0;
+    for result in D::from_dir(ucd_dir)? {
+        result?;
+        rows += 1;
+    }
+    Ok(rows)
+}
+
+fn known<D: UcdFile>() -> KnownFile {
+    KnownFile { path: D::relative_file_path(), count: count::<D> }
+}
+
+fn path<D: UcdFile>() -> &'static Path {
+    D::relative_file_path()
+}
+
+fn known_files() -> Vec<KnownFile> {
+    vec![
+        known::<Age>(),
+        known::<ArabicShaping>(),
+        known::<BidiCharacterTest>(),
+        known::<BidiMirroring>(),
+        known::<BidiTestLine>(),
+        known::<CaseFold>(),
+        known::<CoreProperty>(),
+        known::<DerivedNormalizationProperty>(),
+        known::<DoNotEmit>(),
+        known::<EastAsianWidth>(),
+        known::<EmojiProperty>(),
+        known::<EmojiSequence>(),
+        known::<EmojiZwjSequence>(),
+        known::<GraphemeClusterBreak>(),
+        known::<JamoShortName>(),
+        known::<NameAlias>(),
+        known::<Property>(),
+        known::<PropertyAlias>(),
+        known::<PropertyValueAlias>(),
+        known::<Script>(),
+        known::<ScriptExtension>(),
+        known::<SentenceBreak>(),
+        known::<SpecialCaseMapping>(),
+        known::<UnicodeData>(),
+        known::<WordBreak>(),
+        known::<GraphemeClusterBreakTest>(),
+        known::<LineBreakTest>(),
+        known::<SentenceBreakTest>(),
+        known::<WordBreakTest>(),
+        known::<DerivedName>(),
+    ]
+}
+
+/// Return each subcommand along with the files it requires.
+///
+/// Every subcommand that writes tables also requires PropList.txt, since
+/// that is where the Unicode version is read from.
+fn commands() -> Vec<(&'static str, Vec<&'static Path>)> {
+    let prop = path::<Property>();
+    let aliases = [path::<PropertyAlias>(), path::<PropertyValueAlias>()];
+    let with_aliases = |paths: &[&'static Path]| {
+        let mut all = vec![prop];
+        all.extend_from_slice(&aliases);
+        all.extend_from_slice(paths);
+        all
+    };
+    let with_prop = |paths: &[&'static Path]| {
+        let mut all = vec![prop];
+        all.extend_from_slice(paths);
+        all
+    };
+    let bool_props =
+        with_aliases(&[path::<CoreProperty>(), path::<UnicodeData>()]);
+    vec![
+        (
+            "bidi-class",
+            with_aliases(&[path::<UnicodeData>(), path::<CoreProperty>()]),
+        ),
+        (
+            "bidi-mirroring-glyph",
+            with_prop(&[path::<BidiMirroring>(), path::<UnicodeData>()]),
+        ),
+        ("canonical-combining-class", with_aliases(&[path::<UnicodeData>()])),
+        ("general-category", with_aliases(&[path::<UnicodeData>()])),
+        ("script", with_aliases(&[path::<Script>()])),
+        (
+            "script-extension",
+            with_aliases(&[path::<Script>(), path::<ScriptExtension>()]),
+        ),
+        ("property-bool", bool_props.clone()),
+        ("emoji", with_prop(&[path::<EmojiProperty>()])),
+        ("age", with_aliases(&[path::<Age>()])),
+        ("perl-word", bool_props.clone()),
+        ("validity", bool_props.clone()),
+        ("setop", bool_props.clone()),
+        ("golden-tests", bool_props),
+        ("explain", with_aliases(&[])),
+        (
+            "terminal-width",
+            with_prop(&[
+                path::<UnicodeData>(),
+                path::<EastAsianWidth>(),
+                path::<EmojiProperty>(),
+            ]),
+        ),
+        ("jamo-short-name", with_prop(&[path::<JamoShortName>()])),
+        (
+            "joining-type",
+            with_aliases(&[path::<ArabicShaping>(), path::<UnicodeData>()]),
+        ),
+        ("joining-group", with_aliases(&[path::<ArabicShaping>()])),
+        (
+            "names",
+            with_prop(&[
+                path::<UnicodeData>(),
+                path::<JamoShortName>(),
+                path::<NameAlias>(),
+            ]),
+        ),
+        ("derived-name", with_prop(&[path::<DerivedName>()])),
+        ("named-sequences", with_prop(&[path::<NamedSequence>()])),
+        ("collation", with_prop(&[path::<AllKeysLine>()])),
+        (
+            "canonical-composition",
+            with_prop(&[
+                path::<UnicodeData>(),
+                path::<DerivedNormalizationProperty>(),
+            ]),
+        ),
+        ("property-names", with_prop(&[path::<PropertyAlias>()])),
+        ("property-values", with_aliases(&[])),
+        ("case-folding-simple", with_prop(&[path::<CaseFold>()])),
+        (
+            "case-mapping",
+            with_prop(&[path::<UnicodeData>(), path::<SpecialCaseMapping>()]),
+        ),
+        (
+            "nfkc-casefold",
+            with_prop(&[path::<DerivedNormalizationProperty>()]),
+        ),
+        ("do-not-emit", with_prop(&[path::<DoNotEmit>()])),
+        (
+            "grapheme-cluster-break",
+            with_prop(&[path::<GraphemeClusterBreak>()]),
+        ),
+        ("word-break", with_prop(&[path::<WordBreak>()])),
+        ("sentence-break", with_prop(&[path::<SentenceBreak>()])),
+        ("indic-conjunct-break", with_prop(&[path::<CoreProperty>()])),
+        ("line-break-pairs", with_prop(&[path::<PropertyValueAlias>()])),
+        ("line-break-test", with_prop(&[path::<LineBreakTest>()])),
+        ("bidi-test", with_prop(&[path::<BidiTestLine>()])),
+        ("bidi-character-test", with_prop(&[path::<BidiCharacterTest>()])),
+        ("normalization-test", with_prop(&[path::<NormalizationTestLine>()])),
+        ("test-unicode-data", vec![path::<UnicodeData>()]),
+    ]
+}
@@ -9,10 +9,64 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
     let map = jamo_map(&Path::new(dir))?;
     let mut wtr = args.writer("jamo_short_name")?;
-    wtr.codepoint_to_string(args.name(), &map)?;
+    if args.is_present("reverse") {
+        let [l, v, t] = split_by_class(&map)?;
+        let (l_base, v_base) = (first(&l), first(&v));
+        // T_BASE is one less than the first trailing consonant, since a
+        // T index of 0 means that there is no trailing consonant.
+        let t_base = first(&t) - 1;
+        wtr.hangul_syllable_constants(
+            (l_base, l.len() as u32),
+            (v_base, v.len() as u32),
+            (t_base, t.len() as u32 + 1),
+        )?;
+        for (suffix, class) in [("L", l), ("V", v), ("T", t)] {
+            let name = format!("{}_{}", args.name(), suffix);
+            wtr.string_to_codepoint(&name, &class)?;
+        }
+    } else if args.is_present("rust-match") {
+        wtr.codepoint_to_string_fn(args.name(), &map)?;
+    } else {
+        wtr.codepoint_to_string(args.name(), &map)?;
+    }
+    wtr.finish()?;
     Ok(())
 }
 
+/// Split the Jamo short names into maps from name to codepoint, for each of
+/// the leading consonants (L), vowels (V) and trailing consonants (T).
+///
+/// Jamo.txt lists each of these classes as one contiguous range of
+/// codepoints, in that order.
+fn split_by_class(
+    map: &BTreeMap<u32, String>,
+) -> Result<[BTreeMap<String, u32>; 3]> {
+    let mut classes: Vec<BTreeMap<String, u32>> = vec![];
+    let mut last = None;
+    for (&cp, name) in map {
+        if last.map_or(true, |last| last + 1 != cp) {
+            classes.push(BTreeMap::new());
+        }
+        last = Some(cp);
+        let class = classes.last_mut().unwrap();
+        if class.insert(name.clone(), cp).is_some() {
+            return err!("duplicate Jamo short name {:?} at {:04X}", name, cp);
+        }
+    }
+    match <[BTreeMap<String, u32>; 3]>::try_from(classes) {
+        Ok(classes) => Ok(classes),
+        Err(classes) => err!(
+            "expected 3 contiguous ranges of Jamo (L, V and T), but found {}",
+            classes.len()
+        ),
+    }
+}
+
+/// Return the smallest codepoint in the given class.
+fn first(class: &BTreeMap<String, u32>) -> u32 {
+    class.values().cloned().min().unwrap()
+}
+
 fn jamo_map(dir: &Path) -> Result<BTreeMap<u32, String>> {
     let jamo_map = ucd_parse::parse_by_codepoint::<_, JamoShortName>(dir)?;
     let mut map = BTreeMap::new();
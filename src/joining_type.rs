@@ -1,31 +1,113 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use ucd_parse::{self, ArabicShaping};
+use ucd_parse::{
+    extracted::DerivedJoiningType, ArabicShaping, Codepoint, CodepointRange,
+};
 
 use crate::args::ArgMatches;
+use crate::derive::{Derived, Engine};
 use crate::error::Result;
-use crate::general_category;
-use crate::util::PropertyValues;
+use crate::util::{
+    self, enum_aliases, validate_against_derived, PropertyValues,
+};
 
 pub fn command(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
-    let propvals = PropertyValues::from_ucd_dir(&dir)?;
-    let rows: Vec<ArabicShaping> = ucd_parse::parse(&dir)?;
-    let unexpanded_gc = ucd_parse::parse(&dir)?;
-    let gc =
-        general_category::expand_into_categories(unexpanded_gc, &propvals)?;
+    let mut engine = Engine::new(dir)?;
+    let propvals = engine.propvals();
+    let by_type = engine.property("Joining_Type")?.values.clone();
 
-    // Collect each joining type into an ordered set.
-    let mut by_type: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
+    if args.is_present("validate-against-derived") {
+        validate_against_derived(
+            dir,
+            &by_type,
+            |row: &DerivedJoiningType| {
+                propvals.canonical("jt", &row.joining_type)
+            },
+        )?;
+    }
+
+    let mut wtr = args.writer("joining_type")?;
+    if args.is_present("enum-aliases") {
+        let aliases = enum_aliases(&propvals, "jt", by_type.keys(), None)?;
+        wtr.ranges_to_enum_with_aliases(args.name(), &by_type, &aliases)?;
+    } else if args.is_present("enum") {
+        wtr.ranges_to_enum(args.name(), &by_type)?;
+    } else if args.is_present("rust-enum") {
+        let variants = by_type.keys().map(String::as_str).collect::<Vec<_>>();
+        wtr.ranges_to_rust_enum(args.name(), &variants, &by_type)?;
+    } else if args.is_present("combined") {
+        wtr.ranges_to_combined(args.name(), &by_type)?;
+    } else {
+        wtr.names(by_type.keys())?;
+        for (name, set) in by_type {
+            wtr.ranges(&name, &set)?;
+        }
+    }
+
+    wtr.finish()?;
+    Ok(())
+}
+
+pub fn command_joining_group(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let propvals = PropertyValues::from_ucd_dir(dir)?;
+    let no_group = propvals.canonical("jg", "No_Joining_Group")?;
+
+    // Codepoints that aren't listed, or that are listed without a group,
+    // have no joining group, so they're left out of every table.
+    let mut by_group: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
+    let rows: Vec<ArabicShaping> = util::parse(dir)?;
+    for row in rows {
+        let jg = propvals.canonical("jg", &row.joining_group)?;
+        if jg != no_group {
+            by_group.entry(jg).or_default().insert(row.codepoint.value());
+        }
+    }
+
+    let mut wtr = args.writer("joining_group")?;
+    if args.is_present("enum-aliases") {
+        let aliases = enum_aliases(&propvals, "jg", by_group.keys(), None)?;
+        wtr.ranges_to_enum_with_aliases(args.name(), &by_group, &aliases)?;
+    } else if args.is_present("enum") {
+        wtr.ranges_to_enum(args.name(), &by_group)?;
+    } else if args.is_present("rust-enum") {
+        // Every value is a variant, including those no codepoint has, so
+        // that the enum doesn't depend on which groups are in use.
+        let values = propvals.values("jg")?;
+        let variants: BTreeSet<&str> =
+            values.values().map(String::as_str).collect();
+        let variants: Vec<&str> = variants.into_iter().collect();
+        wtr.ranges_to_rust_enum(args.name(), &variants, &by_group)?;
+        let abbreviations = &propvals.abbreviation["Joining_Group"];
+        wtr.rust_enum_names(args.name(), values, abbreviations)?;
+    } else if args.is_present("combined") {
+        wtr.ranges_to_combined(args.name(), &by_group)?;
+    } else {
+        wtr.names(by_group.keys())?;
+        for (name, set) in by_group {
+            wtr.ranges(&name, &set)?;
+        }
+    }
+
+    wtr.finish()?;
+    Ok(())
+}
+
+/// Derive the Joining_Type of every codepoint, including the codepoints that
+/// aren't listed in ArabicShaping.txt.
+pub fn derive(engine: &mut Engine<'_>) -> Result<Derived> {
+    let propvals = engine.propvals();
+    let rows: Vec<ArabicShaping> = util::parse(engine.dir())?;
+    let gc = engine.property("General_Category")?;
+
+    let mut derived = Derived::default();
+    let listed = derived.rule("listed in ArabicShaping.txt", &[]);
     let mut assigned = BTreeSet::new();
     for row in rows {
         assigned.insert(row.codepoint.value());
-        let jt =
-            propvals.canonical("jt", row.joining_type.as_str())?.to_string();
-        by_type
-            .entry(jt)
-            .or_insert(BTreeSet::new())
-            .insert(row.codepoint.value());
+        let jt = propvals.canonical("jt", row.joining_type.as_str())?;
+        derived.assign(listed, row.codepoint.value(), &jt);
     }
     // Process the codepoints that are not listed as per the note in
     // ArabicShaping.txt:
@@ -36,13 +118,23 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
     // - Those that are not explicitly listed and that are of General Category
     //   Mn, Me, or Cf have joining type T.
     // - All others not explicitly listed have joining type U.
+    let transparent = derived.rule(
+        "not listed in ArabicShaping.txt, and has a General_Category of Mn, \
+         Me or Cf",
+        &["General_Category"],
+    );
+    let non_joining = derived.rule(
+        "not listed in ArabicShaping.txt, and doesn't have a \
+         General_Category of Mn, Me or Cf",
+        &["General_Category"],
+    );
     let transparent_name = propvals.canonical("jt", "transparent")?;
     let non_joining_name = propvals.canonical("jt", "non_joining")?;
     let transparent_categories = ["Mn", "Me", "Cf"]
         .iter()
-        .map(|cat| propvals.canonical("gc", cat).map(|name| &gc[&name]))
+        .map(|cat| propvals.canonical("gc", cat).map(|name| &gc.values[&name]))
         .collect::<Result<Vec<_>>>()?;
-    for cp in 0..=0x10FFFF {
+    for cp in CodepointRange::all().into_iter().map(Codepoint::value) {
         if assigned.contains(&cp) {
             continue;
         }
@@ -50,26 +142,10 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
         // map to the Transparent joining type. Otherwise add to the
         // Non_Joining type.
         if transparent_categories.iter().any(|cat| cat.contains(&cp)) {
-            by_type.get_mut(&transparent_name).unwrap().insert(cp);
+            derived.assign(transparent, cp, &transparent_name);
         } else {
-            by_type.get_mut(&non_joining_name).unwrap().insert(cp);
-        }
-    }
-
-    let mut wtr = args.writer("joining_type")?;
-    if args.is_present("enum") {
-        wtr.ranges_to_enum(args.name(), &by_type)?;
-    } else if args.is_present("rust-enum") {
-        let variants = by_type.keys().map(String::as_str).collect::<Vec<_>>();
-        wtr.ranges_to_rust_enum(args.name(), &variants, &by_type)?;
-    } else if args.is_present("combined") {
-        wtr.ranges_to_combined(args.name(), &by_type)?;
-    } else {
-        wtr.names(by_type.keys())?;
-        for (name, set) in by_type {
-            wtr.ranges(&name, &set)?;
+            derived.assign(non_joining, cp, &non_joining_name);
         }
     }
-
-    Ok(())
+    Ok(derived)
 }
@@ -0,0 +1,199 @@
+use ucd_parse::PropertyValueAlias;
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util;
+
+/// The classes in the pair table, in the order they are given in UAX #14.
+const PAIR_CLASSES: &[&str] = &[
+    "OP", "CL", "CP", "QU", "GL", "NS", "EX", "SY", "IS", "PR", "PO", "NU",
+    "AL", "HL", "ID", "IN", "HY", "BA", "BB", "B2", "ZW", "CM", "WJ", "H2",
+    "H3", "JL", "JV", "JT", "RI", "EB", "EM", "ZWJ", "AK", "AP", "AS", "VF",
+    "VI",
+];
+
+/// The classes that aren't in the pair table, either because LB1 resolves
+/// them to other classes or because they're handled before the table is
+/// consulted, e.g., mandatory breaks and spaces.
+const OTHER_CLASSES: &[&str] =
+    &["AI", "BK", "CB", "CJ", "CR", "LF", "NL", "SA", "SG", "SP", "XX"];
+
+/// What is allowed between two classes, in the order of their variants in
+/// the generated code.
+const OUTCOMES: &[(&str, &str)] = &[
+    ("Direct", "A break is allowed, even without spaces in between."),
+    ("Indirect", "A break is only allowed when there are spaces in between."),
+    (
+        "CombiningIndirect",
+        "A combining mark, which may only be broken before after spaces.",
+    ),
+    (
+        "CombiningProhibited",
+        "A combining mark, which may never be broken before.",
+    ),
+    ("Prohibited", "A break is never allowed, even with spaces in between."),
+];
+const DIRECT: usize = 0;
+const INDIRECT: usize = 1;
+const COMBINING_INDIRECT: usize = 2;
+const COMBINING_PROHIBITED: usize = 3;
+const PROHIBITED: usize = 4;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let values: Vec<PropertyValueAlias> = util::parse(dir)?;
+    let mut present = vec![];
+    for value in values.iter().filter(|v| v.property == "lb") {
+        let class = value.abbreviation.as_str();
+        if !PAIR_CLASSES.contains(&class) && !OTHER_CLASSES.contains(&class) {
+            return err!(
+                "unrecognized Line_Break class {} ({}), which the pair \
+                 table rules don't know about",
+                class,
+                value.long
+            );
+        }
+        present.push(class);
+    }
+    let classes: Vec<&str> = PAIR_CLASSES
+        .iter()
+        .copied()
+        .filter(|class| present.contains(class))
+        .collect();
+    if classes.is_empty() {
+        return err!(
+            "found no Line_Break classes in PropertyValueAliases.txt"
+        );
+    }
+
+    let table: Vec<Vec<usize>> = classes
+        .iter()
+        .map(|&before| {
+            classes.iter().map(|&after| pair(before, after)).collect()
+        })
+        .collect();
+    let classes: Vec<String> = classes.iter().map(|c| c.to_string()).collect();
+    let mut wtr = args.writer("line_break")?;
+    wtr.pair_table(
+        args.name(),
+        &classes,
+        "BreakOpportunity",
+        OUTCOMES,
+        &table,
+    )?;
+    wtr.finish()?;
+    Ok(())
+}
+
+/// Return the index of the outcome in `OUTCOMES` of the class `before`
+/// followed by the class `after`, as in section 7.3 of UAX #14.
+fn pair(before: &str, after: &str) -> usize {
+    // LB9 attaches combining marks to whatever precedes them, except after
+    // a ZW, where LB8 applies. After spaces, LB10 treats them as AL.
+    if after == "CM" || after == "ZWJ" {
+        return if before == "ZW" {
+            DIRECT
+        } else if prohibited(before, "AL", true) {
+            COMBINING_PROHIBITED
+        } else {
+            COMBINING_INDIRECT
+        };
+    }
+    if prohibited(before, after, true) {
+        PROHIBITED
+    } else if prohibited(before, after, false) {
+        INDIRECT
+    } else {
+        DIRECT
+    }
+}
+
+/// Return true when the rules of UAX #14 prohibit a break between the
+/// class `a` followed by the class `b`, with spaces in between if `spaces`
+/// is true.
+///
+/// Only the parts of each rule that can be decided from a pair of classes
+/// are applied. For example, LB30 is applied regardless of East Asian
+/// Width, and LB21a is left out entirely.
+fn prohibited(a: &str, b: &str, spaces: bool) -> bool {
+    let is = |class: &str, classes: &[&str]| classes.contains(&class);
+
+    // LB7 and LB8.
+    if b == "ZW" {
+        return true;
+    }
+    if a == "ZW" {
+        return false;
+    }
+    // LB8a.
+    if a == "ZWJ" && !spaces {
+        return true;
+    }
+    // LB10.
+    let a = if is(a, &["CM", "ZWJ"]) { "AL" } else { a };
+    // LB11 through LB17, which apply with or without spaces in between
+    // unless they say otherwise.
+    if b == "WJ" || (a == "WJ" && !spaces) {
+        return true;
+    }
+    if a == "GL" && !spaces {
+        return true;
+    }
+    if b == "GL" && !spaces && !is(a, &["BA", "HY"]) {
+        return true;
+    }
+    if is(b, &["CL", "CP", "EX", "IS", "SY"]) {
+        return true;
+    }
+    if a == "OP"
+        || (a == "QU" && b == "OP")
+        || (is(a, &["CL", "CP"]) && b == "NS")
+        || (a == "B2" && b == "B2")
+    {
+        return true;
+    }
+    // LB18: everything else breaks after spaces.
+    if spaces {
+        return false;
+    }
+    let alpha = ["AL", "HL"];
+    let jamo = ["JL", "JV", "JT", "H2", "H3"];
+    let rules = [
+        // LB19.
+        a == "QU" || b == "QU",
+        // LB21 and LB21b.
+        is(b, &["BA", "HY", "NS"]) || a == "BB",
+        a == "SY" && b == "HL",
+        // LB22.
+        b == "IN",
+        // LB23 and LB23a.
+        (is(a, &alpha) && b == "NU") || (a == "NU" && is(b, &alpha)),
+        (a == "PR" && is(b, &["ID", "EB", "EM"]))
+            || (is(a, &["ID", "EB", "EM"]) && b == "PO"),
+        // LB24.
+        (is(a, &["PR", "PO"]) && is(b, &alpha))
+            || (is(a, &alpha) && is(b, &["PR", "PO"])),
+        // LB25.
+        (is(a, &["CL", "CP", "NU"]) && is(b, &["PO", "PR"]))
+            || (is(a, &["PO", "PR"]) && is(b, &["OP", "NU"]))
+            || (is(a, &["HY", "IS", "NU", "SY"]) && b == "NU"),
+        // LB26 and LB27.
+        (a == "JL" && is(b, &["JL", "JV", "H2", "H3"]))
+            || (is(a, &["JV", "H2"]) && is(b, &["JV", "JT"]))
+            || (is(a, &["JT", "H3"]) && b == "JT"),
+        (is(a, &jamo) && b == "PO") || (a == "PR" && is(b, &jamo)),
+        // LB28 and LB28a.
+        is(a, &alpha) && is(b, &alpha),
+        (a == "AP" && is(b, &["AK", "AS"]))
+            || (is(a, &["AK", "AS"]) && is(b, &["VF", "VI"])),
+        // LB29 and LB30.
+        a == "IS" && is(b, &alpha),
+        (is(a, &["AL", "HL", "NU"]) && b == "OP")
+            || (a == "CP" && is(b, &["AL", "HL", "NU"])),
+        // LB30a and LB30b.
+        a == "RI" && b == "RI",
+        a == "EB" && b == "EM",
+    ];
+    // LB31 allows a break everywhere else.
+    rules.iter().any(|&rule| rule)
+}
@@ -1,7 +1,7 @@
 use std::io::{self, Write};
 use std::process;
 
-use ucd_parse::{UcdFile, UnicodeData};
+use ucd_parse::UnicodeData;
 
 use crate::args::ArgMatches;
 use crate::error::Result;
@@ -12,6 +12,15 @@ macro_rules! err {
     }
 }
 
+/// Print a progress message to stderr, but only when --verbose is given.
+macro_rules! log {
+    ($($tt:tt)*) => {
+        if crate::util::is_verbose() {
+            eprintln!($($tt)*);
+        }
+    }
+}
+
 mod app;
 mod args;
 mod error;
@@ -21,16 +30,36 @@ mod writer;
 mod age;
 mod bidi_class;
 mod bidi_mirroring_glyph;
+mod bidi_test;
 mod brk;
 mod canonical_combining_class;
+mod canonical_composition;
 mod case_folding;
 mod case_mapping;
+mod collation;
+mod derive;
+mod derived_name;
+mod do_not_emit;
+mod emoji;
+mod explain;
 mod general_category;
+mod golden_tests;
+mod info;
 mod jamo_short_name;
 mod joining_type;
+mod line_break;
+mod man;
+mod matrix;
+mod named_sequences;
 mod names;
+mod nfkc_casefold;
+mod normalization_test;
 mod property_bool;
+mod regen;
+mod schema;
 mod script;
+mod setop;
+mod terminal_width;
 
 fn main() {
     if let Err(err) = run() {
@@ -43,7 +72,53 @@ fn main() {
 }
 
 fn run() -> Result<()> {
-    let matches = app::app().get_matches();
+    run_matches(&app::app().get_matches())
+}
+
+/// The commands that never write any tables, and so can't be given --check.
+const NO_TABLES: &[&str] = &[
+    "completions",
+    "explain",
+    "info",
+    "man",
+    "regen",
+    "schema",
+    "test-unicode-data",
+];
+
+/// Run the subcommand given by the parsed command line.
+///
+/// Since --check is global, this returns an error if it's given to a command
+/// that didn't compare anything with existing files, e.g., one of
+/// `NO_TABLES` or a command run with `--list-properties`. Otherwise, a check
+/// against the wrong command would always pass.
+fn run_matches(matches: &clap::ArgMatches<'_>) -> Result<()> {
+    let (name, m) = match matches.subcommand() {
+        (name, Some(m)) => (name, m),
+        _ => return run_command(matches),
+    };
+    util::set_verbose(m.is_present("verbose"));
+    if !m.is_present("check") {
+        return run_command(matches);
+    }
+    if NO_TABLES.contains(&name) {
+        return err!(
+            "{} doesn't write any tables, so --check can't be used",
+            name
+        );
+    }
+    util::take_checked();
+    run_command(matches)?;
+    if !util::take_checked() {
+        return err!(
+            "--check was given, but {} didn't write any tables",
+            name
+        );
+    }
+    Ok(())
+}
+
+fn run_command(matches: &clap::ArgMatches<'_>) -> Result<()> {
     match matches.subcommand() {
         ("bidi-class", Some(m)) => bidi_class::command(ArgMatches::new(m)),
         ("bidi-mirroring-glyph", Some(m)) => {
@@ -62,15 +137,34 @@ fn run() -> Result<()> {
         ("property-bool", Some(m)) => {
             property_bool::command(ArgMatches::new(m))
         }
+        ("emoji", Some(m)) => emoji::command(ArgMatches::new(m)),
         ("age", Some(m)) => age::command(ArgMatches::new(m)),
         ("perl-word", Some(m)) => {
             property_bool::command_perl_word(ArgMatches::new(m))
         }
+        ("validity", Some(m)) => {
+            property_bool::command_validity(ArgMatches::new(m))
+        }
+        ("setop", Some(m)) => setop::command(ArgMatches::new(m)),
+        ("terminal-width", Some(m)) => {
+            terminal_width::command(ArgMatches::new(m))
+        }
         ("jamo-short-name", Some(m)) => {
             jamo_short_name::command(ArgMatches::new(m))
         }
         ("joining-type", Some(m)) => joining_type::command(ArgMatches::new(m)),
+        ("joining-group", Some(m)) => {
+            joining_type::command_joining_group(ArgMatches::new(m))
+        }
         ("names", Some(m)) => names::command(ArgMatches::new(m)),
+        ("derived-name", Some(m)) => derived_name::command(ArgMatches::new(m)),
+        ("collation", Some(m)) => collation::command(ArgMatches::new(m)),
+        ("canonical-composition", Some(m)) => {
+            canonical_composition::command(ArgMatches::new(m))
+        }
+        ("named-sequences", Some(m)) => {
+            named_sequences::command(ArgMatches::new(m))
+        }
         ("property-names", Some(m)) => cmd_property_names(ArgMatches::new(m)),
         ("property-values", Some(m)) => {
             cmd_property_values(ArgMatches::new(m))
@@ -79,11 +173,37 @@ fn run() -> Result<()> {
             case_folding::command(ArgMatches::new(m))
         }
         ("case-mapping", Some(m)) => case_mapping::command(ArgMatches::new(m)),
+        ("nfkc-casefold", Some(m)) => {
+            nfkc_casefold::command(ArgMatches::new(m))
+        }
+        ("do-not-emit", Some(m)) => do_not_emit::command(ArgMatches::new(m)),
         ("grapheme-cluster-break", Some(m)) => {
             brk::grapheme_cluster(ArgMatches::new(m))
         }
         ("word-break", Some(m)) => brk::word(ArgMatches::new(m)),
         ("sentence-break", Some(m)) => brk::sentence(ArgMatches::new(m)),
+        ("indic-conjunct-break", Some(m)) => {
+            brk::indic_conjunct(ArgMatches::new(m))
+        }
+        ("line-break-pairs", Some(m)) => {
+            line_break::command(ArgMatches::new(m))
+        }
+        ("line-break-test", Some(m)) => brk::line_test(ArgMatches::new(m)),
+        ("bidi-test", Some(m)) => bidi_test::command(ArgMatches::new(m)),
+        ("bidi-character-test", Some(m)) => {
+            bidi_test::command_character(ArgMatches::new(m))
+        }
+        ("normalization-test", Some(m)) => {
+            normalization_test::command(ArgMatches::new(m))
+        }
+        ("golden-tests", Some(m)) => golden_tests::command(ArgMatches::new(m)),
+        ("matrix", Some(m)) => matrix::command(ArgMatches::new(m)),
+        ("explain", Some(m)) => explain::command(ArgMatches::new(m)),
+        ("info", Some(m)) => info::command(ArgMatches::new(m)),
+        ("schema", Some(m)) => schema::command(ArgMatches::new(m)),
+        ("completions", Some(m)) => cmd_completions(ArgMatches::new(m)),
+        ("man", Some(m)) => man::command(ArgMatches::new(m)),
+        ("regen", Some(m)) => regen::command(ArgMatches::new(m)),
         ("test-unicode-data", Some(m)) => {
             cmd_test_unicode_data(ArgMatches::new(m))
         }
@@ -112,6 +232,7 @@ fn cmd_property_names(args: ArgMatches<'_>) -> Result<()> {
     }
     let mut wtr = args.writer("property_names")?;
     wtr.string_to_string(args.name(), &actual_names)?;
+    wtr.finish()?;
     Ok(())
 }
 
@@ -125,21 +246,130 @@ fn cmd_property_values(args: ArgMatches<'_>) -> Result<()> {
     let filter = args.filter(|name| names.canonical(name))?;
 
     let mut actual_values = BTreeMap::new();
-    for (k, v) in &values.value {
-        if filter.contains(k) {
-            actual_values.insert(k.to_string(), v.clone());
+    for property in values.properties() {
+        if filter.contains(property) {
+            actual_values.insert(
+                property.to_string(),
+                values.values(property)?.clone(),
+            );
         }
     }
     let mut wtr = args.writer("property_values")?;
-    wtr.string_to_string_to_string(args.name(), &actual_values)?;
+    if args.is_present("emit-from-str") {
+        let name = if args.occurrences_of("name") > 0 {
+            args.name()
+        } else {
+            "FROM_STR"
+        };
+        let table = from_str_table(dir, &values, &filter)?;
+        wtr.string_to_string(name, &table)?;
+    } else if args.is_present("rust-enum") {
+        for (property, values) in &actual_values {
+            wtr.property_value_enum(property, values)?;
+        }
+    } else {
+        wtr.string_to_string_to_string(args.name(), &actual_values)?;
+    }
+    wtr.finish()?;
+    Ok(())
+}
+
+/// The properties whose tables can be referred to by the table written by
+/// `property-values --emit-from-str`, along with the name of the module
+/// that the command for each property writes.
+const FROM_STR_MODULES: &[(&str, &str)] = &[
+    ("Age", "age"),
+    ("Bidi_Class", "bidi_class"),
+    ("Canonical_Combining_Class", "canonical_combining_class"),
+    ("General_Category", "general_category"),
+    ("Grapheme_Cluster_Break", "grapheme_cluster_break"),
+    ("Joining_Type", "joining_type"),
+    ("Script", "script"),
+    ("Script_Extensions", "script_extension"),
+    ("Sentence_Break", "sentence_break"),
+    ("Word_Break", "word_break"),
+];
+
+/// Return a map from every name that `\p{...}` may be given to the path of
+/// the generated table for it, e.g., `general_category::UPPERCASE_LETTER`.
+///
+/// Names are normalized according to UAX44-LM3. They are either
+/// `property=value`, or a bare boolean property, General_Category value or
+/// Script value. When a bare name is ambiguous, General_Category is
+/// preferred over Script, which is preferred over boolean properties.
+fn from_str_table(
+    dir: &std::ffi::OsStr,
+    values: &crate::util::PropertyValues,
+    filter: &crate::util::Filter,
+) -> Result<std::collections::BTreeMap<String, String>> {
+    use crate::writer::rust_const_name;
+    use std::collections::BTreeMap;
+
+    let path = |module: &str, canonical: &str| {
+        format!("{}::{}", module, rust_const_name(canonical))
+    };
+    let mut table = BTreeMap::new();
+    let bools = property_bool::parse_properties(dir)?;
+    for (alias, canonical) in &values.property.0 {
+        if bools.contains_key(canonical) && filter.contains(canonical) {
+            table.insert(alias.clone(), path("prop_list", canonical));
+        }
+    }
+    for &(property, module) in FROM_STR_MODULES {
+        let by_alias = match values.value.get(property) {
+            Some(by_alias) if filter.contains(property) => by_alias,
+            _ => continue,
+        };
+        for (alias, canonical) in &values.property.0 {
+            if canonical != property {
+                continue;
+            }
+            for (value, canonical_value) in by_alias {
+                table.insert(
+                    format!("{}={}", alias, value),
+                    path(module, canonical_value),
+                );
+            }
+        }
+    }
+    // Bare values are written in reverse order of preference, so that the
+    // preferred property overwrites the others.
+    for &property in &["Script", "General_Category"] {
+        let module = match FROM_STR_MODULES.iter().find(|p| p.0 == property) {
+            Some(&(_, module)) if filter.contains(property) => module,
+            _ => continue,
+        };
+        if let Some(by_alias) = values.value.get(property) {
+            for (value, canonical_value) in by_alias {
+                table.insert(value.clone(), path(module, canonical_value));
+            }
+        }
+    }
+    Ok(table)
+}
+
+fn cmd_completions(args: ArgMatches<'_>) -> Result<()> {
+    let mut shell = match args.value_of("shell").unwrap().parse() {
+        Ok(shell) => shell,
+        Err(err) => return err!("{}", err),
+    };
+    let mut stdout = io::stdout();
+    if let clap::Shell::Zsh = shell {
+        // clap's zsh completions require every flag that a flag conflicts
+        // with to be defined by the same subcommand, which isn't true of the
+        // flags shared between subcommands. zsh can use the bash completions
+        // instead.
+        writeln!(stdout, "autoload -U +X bashcompinit && bashcompinit")?;
+        shell = clap::Shell::Bash;
+    }
+    app::app().gen_completions_to("ucd-generate", shell, &mut stdout);
     Ok(())
 }
 
 fn cmd_test_unicode_data(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
     let mut stdout = io::stdout();
-    for result in UnicodeData::from_dir(dir)? {
-        let x: UnicodeData = result?;
+    for x in util::parse::<_, UnicodeData>(dir)? {
         writeln!(stdout, "{}", x)?;
     }
     Ok(())
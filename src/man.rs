@@ -0,0 +1,242 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let commands = commands()?;
+    let dir = match args.value_of_os("out-dir") {
+        Some(dir) => Path::new(dir),
+        None => {
+            let page = page(args.value_of("command"), &commands)?;
+            io::stdout().write_all(page.as_bytes())?;
+            return Ok(());
+        }
+    };
+    fs::create_dir_all(dir)?;
+    let mut pages = vec![("ucd-generate".to_string(), page(None, &commands)?)];
+    for (name, _) in &commands {
+        let page = page(Some(name), &commands)?;
+        pages.push((format!("ucd-generate-{}", name), page));
+    }
+    for (name, page) in pages {
+        let path = dir.join(format!("{}.1", name));
+        fs::write(&path, page)?;
+        if !args.is_present("quiet") {
+            eprintln!("wrote {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Return the help of the given subcommand, or of ucd-generate itself, as
+/// printed by --help.
+fn help(command: Option<&str>) -> Result<String> {
+    let mut argv = vec!["ucd-generate"];
+    argv.extend(command);
+    argv.push("--help");
+    // Without wrapping, each description is on one line, and the man page
+    // viewer wraps it instead.
+    let app = crate::app::app().set_term_width(0);
+    match app.get_matches_from_safe(argv) {
+        Err(err) if err.kind == clap::ErrorKind::HelpDisplayed => {
+            Ok(err.message)
+        }
+        Err(err) => Err(err.into()),
+        Ok(_) => err!("--help did not print help"),
+    }
+}
+
+/// Return the name and summary of every subcommand, as listed in the help
+/// of ucd-generate.
+fn commands() -> Result<Vec<(String, String)>> {
+    let help = help(None)?;
+    let mut commands = vec![];
+    for section in sections(&help) {
+        if section.heading != "SUBCOMMANDS" {
+            continue;
+        }
+        for item in section.items {
+            if item.head != "help" {
+                commands.push((item.head, item.description.join(" ")));
+            }
+        }
+    }
+    Ok(commands)
+}
+
+/// Return the man page of the given subcommand, or of ucd-generate itself,
+/// in roff.
+fn page(
+    command: Option<&str>,
+    commands: &[(String, String)],
+) -> Result<String> {
+    let (name, summary) = match command {
+        None => ("ucd-generate".to_string(), env!("CARGO_PKG_DESCRIPTION")),
+        Some(command) => match commands.iter().find(|(n, _)| n == command) {
+            Some((_, summary)) => {
+                (format!("ucd-generate-{}", command), summary.as_str())
+            }
+            None => return err!("unrecognized command: {}", command),
+        },
+    };
+    let help = help(command)?;
+
+    let mut page = String::new();
+    page.push_str(&format!(
+        ".TH {} 1 \"\" \"ucd-generate {}\"\n",
+        name.to_uppercase(),
+        clap::crate_version!()
+    ));
+    page.push_str(".SH NAME\n");
+    page.push_str(&format!(
+        "{} \\- {}\n",
+        name,
+        escape(
+            summary
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .trim_end_matches('.')
+        )
+    ));
+    // The synopsis comes before the description, unlike in --help.
+    let mut sections = sections(&help);
+    if let Some(i) = sections.iter().position(|s| s.heading == "USAGE") {
+        let usage = sections.remove(i);
+        sections.insert(0, usage);
+    }
+    for section in sections {
+        if section.text.is_empty() && section.items.is_empty() {
+            continue;
+        }
+        let heading = match &*section.heading {
+            "" => "DESCRIPTION",
+            "USAGE" => "SYNOPSIS",
+            "ARGS" => "ARGUMENTS",
+            "SUBCOMMANDS" => "COMMANDS",
+            heading => heading,
+        };
+        page.push_str(&format!(".SH {}\n", heading));
+        for line in &section.text {
+            if line.is_empty() {
+                page.push_str(".PP\n");
+            } else if line.starts_with(' ') {
+                // Indented lines are examples, which are kept as they are.
+                page.push_str(&format!(".nf\n{}\n.fi\n", escape(line)));
+            } else {
+                page.push_str(&format!("{}\n", escape(line)));
+            }
+        }
+        for item in &section.items {
+            page.push_str(".TP\n");
+            page.push_str(&format!("{}\n", item_head(&item.head)));
+            for line in &item.description {
+                page.push_str(&format!("{}\n", escape(line)));
+            }
+        }
+    }
+    if command.is_some() {
+        page.push_str(".SH SEE ALSO\n\\fBucd-generate\\fR(1)\n");
+    }
+    Ok(page)
+}
+
+/// A section of the help printed by --help, e.g., `OPTIONS:`.
+#[derive(Debug, Default)]
+struct Section {
+    /// The heading without its colon, which is empty for the text before
+    /// the first heading.
+    heading: String,
+    /// The lines of text that aren't part of an item, where an empty line
+    /// separates paragraphs and an indented line is an example.
+    text: Vec<String>,
+    items: Vec<Item>,
+}
+
+/// A single flag, argument or subcommand in a section of the help.
+#[derive(Debug, Default)]
+struct Item {
+    /// The flag, argument or subcommand, e.g., `-h, --help` or `<ucd-dir>`.
+    head: String,
+    description: Vec<String>,
+}
+
+/// Split the given help into its sections.
+///
+/// Each item in a section starts on a line indented by at most 8 spaces,
+/// and is separated from its description either by at least two spaces or
+/// by a line break. Lines indented further continue the description.
+fn sections(help: &str) -> Vec<Section> {
+    let mut sections = vec![Section::default()];
+    for line in help.lines() {
+        let section = sections.last_mut().unwrap();
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if indent == 0 && !trimmed.is_empty() && trimmed.ends_with(':') {
+            let heading = trimmed.trim_end_matches(':').to_string();
+            sections.push(Section { heading, ..Section::default() });
+        } else if section.heading.is_empty() {
+            // The help of ucd-generate itself starts with its version and
+            // author, which the man page already has.
+            let version = format!("ucd-generate {}", clap::crate_version!());
+            if line != version && line != clap::crate_authors!() {
+                section.text.push(line.trim_end().to_string());
+            }
+        } else if trimmed.is_empty() {
+            continue;
+        } else if section.heading == "USAGE" {
+            section.text.push(trimmed.to_string());
+        } else if indent <= 8 {
+            let (head, rest) = match trimmed.find("  ") {
+                Some(i) => (&trimmed[..i], trimmed[i..].trim()),
+                None => (trimmed, ""),
+            };
+            let description =
+                if rest.is_empty() { vec![] } else { vec![rest.to_string()] };
+            section.items.push(Item { head: head.to_string(), description });
+        } else if let Some(item) = section.items.last_mut() {
+            item.description.push(trimmed.to_string());
+        } else {
+            section.text.push(trimmed.to_string());
+        }
+    }
+    for section in &mut sections {
+        while section.text.first().is_some_and(|l| l.is_empty()) {
+            section.text.remove(0);
+        }
+        while section.text.last().is_some_and(|l| l.is_empty()) {
+            section.text.pop();
+        }
+    }
+    sections
+}
+
+/// Format the head of an item, with flags and subcommands in bold and
+/// value names in italics.
+fn item_head(head: &str) -> String {
+    let words: Vec<String> = head
+        .split(' ')
+        .map(|word| {
+            let (word, comma) = match word.strip_suffix(',') {
+                Some(word) => (word, ","),
+                None => (word, ""),
+            };
+            let font = if word.starts_with('<') { "I" } else { "B" };
+            format!("\\f{}{}\\fR{}", font, escape(word), comma)
+        })
+        .collect();
+    words.join(" ")
+}
+
+/// Escape the given text for roff.
+fn escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    if text.starts_with('.') || text.starts_with('\'') {
+        format!("\\&{}", text)
+    } else {
+        text
+    }
+}
@@ -0,0 +1,165 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::writer::WriterBuilder;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let out_dir = Path::new(args.value_of_os("OUT_DIR").unwrap());
+    let command: Vec<OsString> =
+        args.values_of_os("command").unwrap().map(|x| x.to_owned()).collect();
+    if command[0] == "matrix" {
+        return err!("matrix cannot run itself");
+    }
+    if args.is_present("config-header") {
+        return err!(
+            "matrix cannot be given --config-header, since it doesn't \
+             record the command of each version"
+        );
+    }
+    if args.is_present("check") {
+        return err!(
+            "matrix cannot be given --check, since it doesn't compare the \
+             files that it writes for each version with existing ones"
+        );
+    }
+    for arg in &command[1..] {
+        let arg = arg.to_string_lossy();
+        if arg == "--check" || arg.starts_with("--check=") {
+            return err!(
+                "the command given to matrix cannot have --check, since \
+                 each version is written to its own directory"
+            );
+        }
+        if arg == "--config-header" {
+            return err!(
+                "the command given to matrix cannot have --config-header, \
+                 since matrix doesn't record the command of each version"
+            );
+        }
+        if arg.starts_with("--out-dir") || arg.starts_with("--fst-dir") {
+            return err!(
+                "the command given to matrix cannot have {}, since \
+                 each version is written to its own directory",
+                arg
+            );
+        }
+    }
+
+    // Each version is written to a directory named after it, and the
+    // dispatch module prefers the newest version whose feature is enabled.
+    let mut versions = vec![];
+    for dir in args.values_of_os("ucd-dir").unwrap() {
+        let version = crate::util::ucd_version(dir)?;
+        if versions.iter().any(|&(v, _)| v == version) {
+            let (major, minor, patch) = version;
+            return err!(
+                "more than one UCD directory has version {}.{}.{}",
+                major,
+                minor,
+                patch
+            );
+        }
+        versions.push((version, dir));
+    }
+    versions.sort_by_key(|&(version, _)| std::cmp::Reverse(version));
+
+    let threads = match args.usize_value("threads")? {
+        None => 1,
+        Some(0) => return err!("--threads must be at least 1"),
+        Some(threads) => threads,
+    };
+
+    let mut runs = vec![];
+    for &((major, minor, patch), dir) in &versions {
+        let mut module = format!("unicode_{}_{}", major, minor);
+        if patch > 0 {
+            module.push_str(&format!("_{}", patch));
+        }
+        let version_dir = out_dir.join(&module);
+        fs::create_dir_all(&version_dir)?;
+
+        let mut argv: Vec<OsString> = vec!["ucd-generate".into()];
+        argv.push(command[0].clone());
+        argv.push(dir.to_owned());
+        argv.extend(command[1..].iter().cloned());
+        argv.push("--out-dir".into());
+        argv.push(version_dir.into_os_string());
+        runs.push((module, argv));
+    }
+    let argvs: Vec<&[OsString]> =
+        runs.iter().map(|(_, argv)| argv.as_slice()).collect();
+    run_all(&argvs, threads)?;
+
+    // The module files are written after every run, in version order, so
+    // that they don't depend on how the runs were scheduled.
+    let mut dispatch = vec![];
+    for (&((major, minor, patch), _), (module, _)) in versions.iter().zip(runs)
+    {
+        let version_dir = out_dir.join(&module);
+        let mut modules = vec![];
+        for entry in fs::read_dir(&version_dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(true, |ext| ext != "rs") {
+                continue;
+            }
+            let stem = path.file_stem().unwrap().to_string_lossy();
+            if stem != "mod" {
+                modules.push(stem.into_owned());
+            }
+        }
+        modules.sort();
+        let mut builder = WriterBuilder::new("mod");
+        builder.ucd_version(major, minor, patch);
+        let mut wtr = builder.from_out_dir(&version_dir)?;
+        wtr.modules(&modules)?;
+        wtr.finish()?;
+        dispatch.push((module.replace('_', "-"), module));
+    }
+    let mut wtr = WriterBuilder::new("mod").from_out_dir(out_dir)?;
+    wtr.version_dispatch(&dispatch)?;
+    wtr.finish()?;
+    Ok(())
+}
+
+/// Run each of the given command lines, with up to the given number of them
+/// running at once.
+///
+/// Runs are started in order, and the error of the first run that failed is
+/// returned. With more than one thread, the remaining runs are finished
+/// before returning, so that which error is returned doesn't depend on how
+/// the runs were scheduled.
+fn run_all(argvs: &[&[OsString]], threads: usize) -> Result<()> {
+    let run = |argv: &[OsString]| -> Result<()> {
+        let matches = crate::app::app().get_matches_from_safe(argv)?;
+        crate::run_matches(&matches)
+    };
+    if threads == 1 {
+        return argvs.iter().try_for_each(|argv| run(argv));
+    }
+
+    let next = AtomicUsize::new(0);
+    let results: Vec<Mutex<Option<Result<()>>>> =
+        argvs.iter().map(|_| Mutex::new(None)).collect();
+    thread::scope(|scope| {
+        for _ in 0..threads.min(argvs.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                if i >= argvs.len() {
+                    break;
+                }
+                let result = run(argvs[i]);
+                *results[i].lock().unwrap() = Some(result);
+            });
+        }
+    });
+    for result in results {
+        result.into_inner().unwrap().unwrap()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use std::collections::BTreeMap;
+
+use ucd_parse::NamedSequence;
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let rows: Vec<NamedSequence> = util::parse(dir)?;
+
+    let mut map: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+    for row in rows {
+        let mut name = row.name;
+        if args.is_present("normalize") {
+            ucd_util::character_name_normalize(&mut name);
+        }
+        if map.contains_key(&name) {
+            return err!("duplicate named sequence {:?}", name);
+        }
+        let codepoints = row.codepoints.iter().map(|cp| cp.value()).collect();
+        map.insert(name, codepoints);
+    }
+
+    let mut wtr = args.writer("named_sequences")?;
+    let flat = args.is_present("flat-table");
+    wtr.string_to_codepoints(args.name(), &map, flat)?;
+    wtr.finish()?;
+    Ok(())
+}
@@ -1,26 +1,77 @@
-use std::{collections::BTreeMap, path::Path};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
 
-use ucd_parse::{self, Codepoint, NameAlias, UnicodeData};
+use ucd_parse::{
+    self, extracted::DerivedName, Codepoint, NameAlias, UnicodeData,
+};
 use ucd_util;
 
 use crate::args::ArgMatches;
 use crate::error::Result;
+use crate::util::{self, TempDir};
 
 pub fn command(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
     let jamo_short_name_map = crate::jamo_short_name::table(Path::new(dir))?;
-    let data = ucd_parse::parse_by_codepoint(&dir)?;
+    let data = util::parse_by_codepoint(&dir)?;
     let aliases = if args.is_present("no-aliases") {
         None
     } else {
-        Some(ucd_parse::parse_many_by_codepoint(&dir)?)
+        Some(util::parse_many_by_codepoint(&dir)?)
     };
+    let patterns = derived_patterns(Path::new(dir))?;
+    let jamo_short_name_table =
+        crate::jamo_short_name::table_ref(&jamo_short_name_map);
+    let options = NameOptions {
+        ideograph: !args.is_present("no-ideograph"),
+        derived: !args.is_present("no-derived"),
+        hangul: !args.is_present("no-hangul"),
+    };
+    if args.is_present("low-memory") {
+        let normalize = args.is_present("normalize");
+        let tagged = args.is_present("tagged");
+        let mut wtr = args.writer("names")?;
+        let mut sorter = ExternalSort::new()?;
+        let mut result = Ok(());
+        each_name(
+            &data,
+            &aliases,
+            &jamo_short_name_table,
+            &patterns,
+            &options,
+            |mut name, tag, cp| {
+                // The writer can't filter the sorted names as they're
+                // written, so codepoints left out by --max-age or --where are
+                // dropped here.
+                if result.is_err() || wtr.leaves_out(cp) {
+                    return;
+                }
+                if normalize {
+                    ucd_util::character_name_normalize(&mut name);
+                }
+                let value =
+                    if tagged { tag.with_codepoint(cp) } else { cp as u64 };
+                result = sorter.push(name, value);
+            },
+        );
+        result?;
+        wtr.string_to_u64_sorted(args.name(), sorter.finish()?)?;
+        if tagged {
+            write_tags(&mut wtr, args.name())?;
+        }
+        wtr.finish()?;
+        return Ok(());
+    }
+
     let mut names = names_to_codepoint(
         &data,
         &aliases,
-        &crate::jamo_short_name::table_ref(&jamo_short_name_map),
-        !args.is_present("no-ideograph"),
-        !args.is_present("no-hangul"),
+        &jamo_short_name_table,
+        &patterns,
+        &options,
     );
     if args.is_present("normalize") {
         names = names
@@ -33,12 +84,34 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
     }
 
     let mut wtr = args.writer("names")?;
-    if args.is_present("tagged") {
+    if args.is_present("emit-consts-only") {
+        // The algorithmic ranges are written even when their names are left
+        // out, since they are what a consumer needs to generate them.
+        let ideographs: Vec<(u32, u32)> = patterns
+            .iter()
+            .filter(|(_, _, prefix)| prefix.ends_with("IDEOGRAPH-"))
+            .map(|&(start, end, _)| (start, end))
+            .collect();
+        let map =
+            names.into_iter().map(|(name, (_, cp))| (name, cp)).collect();
+        wtr.name_constants(
+            args.name(),
+            &map,
+            ucd_util::RANGE_HANGUL_SYLLABLE,
+            &ideographs,
+        )?;
+    } else if args.is_present("tagged") {
         let mut map = BTreeMap::new();
         for (name, (tag, cp)) in names {
-            map.insert(name, tag.with_codepoint(cp));
+            // The writer can't tell codepoints apart from tags in these
+            // values, so codepoints left out by --max-age or --where are
+            // dropped here.
+            if !wtr.leaves_out(cp) {
+                map.insert(name, tag.with_codepoint(cp));
+            }
         }
         wtr.string_to_u64(args.name(), &map)?;
+        write_tags(&mut wtr, args.name())?;
     } else {
         let mut map = BTreeMap::new();
         for (name, (_, cp)) in names {
@@ -46,6 +119,7 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
         }
         wtr.string_to_codepoint(args.name(), &map)?;
     }
+    wtr.finish()?;
     Ok(())
 }
 
@@ -63,18 +137,113 @@ enum NameTag {
     Hangul,
     /// The name is an algorithmically generated ideograph.
     Ideograph,
+    /// The name is algorithmically generated from some other prefix in
+    /// DerivedName.txt, e.g., a Nushu character.
+    Derived,
 }
 
 impl NameTag {
-    fn with_codepoint(&self, cp: u32) -> u64 {
+    /// Every tag, in the order of their bits.
+    const ALL: &'static [NameTag] = &[
+        NameTag::Explicit,
+        NameTag::Alias,
+        NameTag::Hangul,
+        NameTag::Ideograph,
+        NameTag::Derived,
+    ];
+
+    /// The bit set in the values of names with this tag.
+    fn bit(&self) -> u64 {
+        use self::NameTag::*;
+        match *self {
+            Explicit => 1 << 33,
+            Alias => 1 << 34,
+            Hangul => 1 << 35,
+            Ideograph => 1 << 36,
+            Derived => 1 << 37,
+        }
+    }
+
+    /// The name of this tag, for the generated code.
+    fn name(&self) -> &'static str {
         use self::NameTag::*;
         match *self {
-            Explicit => (1 << 33) | (cp as u64),
-            Alias => (1 << 34) | (cp as u64),
-            Hangul => (1 << 35) | (cp as u64),
-            Ideograph => (1 << 36) | (cp as u64),
+            Explicit => "Explicit",
+            Alias => "Alias",
+            Hangul => "Hangul",
+            Ideograph => "Ideograph",
+            Derived => "Derived",
         }
     }
+
+    /// A description of this tag, for the generated code.
+    fn description(&self) -> &'static str {
+        use self::NameTag::*;
+        match *self {
+            Explicit => "The name is listed explicitly in UnicodeData.txt.",
+            Alias => "The name was taken from NameAliases.txt.",
+            Hangul => {
+                "The name is an algorithmically generated Hangul syllable."
+            }
+            Ideograph => "The name is an algorithmically generated ideograph.",
+            Derived => {
+                "The name is generated from a prefix in DerivedName.txt."
+            }
+        }
+    }
+
+    fn with_codepoint(&self, cp: u32) -> u64 {
+        self.bit() | (cp as u64)
+    }
+}
+
+/// Write the constants and decoder for the tags written by `--tagged`.
+fn write_tags(wtr: &mut crate::writer::Writer, name: &str) -> Result<()> {
+    let tags: Vec<(&str, &str, u64)> = NameTag::ALL
+        .iter()
+        .map(|tag| (tag.name(), tag.description(), tag.bit()))
+        .collect();
+    wtr.codepoint_tags(name, &tags)
+}
+
+/// Return the (start, end, prefix) triples of every algorithmically named
+/// range, excluding Hangul syllables.
+///
+/// These are read from extracted/DerivedName.txt. If that file doesn't exist,
+/// then this falls back to the ideograph ranges known to ucd-util, sorted
+/// and with the prefix that ucd-util gives each of them.
+fn derived_patterns(ucd_dir: &Path) -> Result<Vec<(u32, u32, String)>> {
+    let rows: Vec<DerivedName> = match ucd_parse::parse(ucd_dir) {
+        Ok(rows) => rows,
+        Err(err) => match *err.kind() {
+            ucd_parse::ErrorKind::Io(_) => {
+                let mut patterns: Vec<(u32, u32, String)> =
+                    ucd_util::RANGE_IDEOGRAPH
+                        .iter()
+                        .map(|&(start, end)| {
+                            let name =
+                                ucd_util::ideograph_name(start).unwrap();
+                            let prefix = name.trim_end_matches(|c: char| {
+                                c.is_ascii_hexdigit()
+                            });
+                            (start, end, prefix.to_string())
+                        })
+                        .collect();
+                patterns.sort();
+                patterns.dedup();
+                return Ok(patterns);
+            }
+            _ => return Err(From::from(err)),
+        },
+    };
+    Ok(crate::derived_name::patterns(&rows))
+}
+
+/// Which algorithmically generated names to include.
+struct NameOptions {
+    ideograph: bool,
+    derived: bool,
+    hangul: bool,
 }
 
 /// Build one big map in memory from every possible name of a character to its
@@ -86,9 +255,36 @@ fn names_to_codepoint(
     data: &BTreeMap<Codepoint, UnicodeData>,
     aliases: &Option<BTreeMap<Codepoint, Vec<NameAlias>>>,
     jamo_short_name_table: &[(u32, &str)],
-    ideograph: bool,
-    hangul: bool,
+    patterns: &[(u32, u32, String)],
+    options: &NameOptions,
 ) -> BTreeMap<String, (NameTag, u32)> {
+    let mut map = BTreeMap::new();
+    each_name(
+        data,
+        aliases,
+        jamo_short_name_table,
+        patterns,
+        options,
+        |name, tag, cp| {
+            map.insert(name, (tag, cp));
+        },
+    );
+    map
+}
+
+/// Call `f` with every possible name of a character, along with its tag and
+/// codepoint.
+///
+/// The same name may be given more than once, in which case the last one
+/// given should be used.
+fn each_name<F: FnMut(String, NameTag, u32)>(
+    data: &BTreeMap<Codepoint, UnicodeData>,
+    aliases: &Option<BTreeMap<Codepoint, Vec<NameAlias>>>,
+    jamo_short_name_table: &[(u32, &str)],
+    patterns: &[(u32, u32, String)],
+    options: &NameOptions,
+    mut f: F,
+) {
     // The order in which we write names is important, since there is some
     // overlap.
     //
@@ -99,12 +295,10 @@ fn names_to_codepoint(
     // Additionally, write the algorithmically generated names after
     // everything, so that even if a algorithmically generated name matches
     // an Explicit/Alias name, its tag will indicate that it is generated.
-    let mut map = BTreeMap::new();
     if let Some(ref alias_map) = *aliases {
         for (cp, aliases) in alias_map {
             for name_alias in aliases {
-                let v = (NameTag::Alias, cp.value());
-                map.insert(name_alias.alias.clone(), v);
+                f(name_alias.alias.clone(), NameTag::Alias, cp.value());
             }
         }
     }
@@ -112,28 +306,239 @@ fn names_to_codepoint(
         let isnull = datum.name.is_empty()
             || (datum.name.starts_with('<') && datum.name.ends_with('>'));
         if !isnull {
-            let v = (NameTag::Explicit, cp.value());
-            map.insert(datum.name.clone(), v);
+            f(datum.name.clone(), NameTag::Explicit, cp.value());
         }
     }
-    if ideograph {
-        for &(start, end) in ucd_util::RANGE_IDEOGRAPH {
-            for cp in start..end + 1 {
-                let v = (NameTag::Ideograph, cp);
-                map.insert(ucd_util::ideograph_name(cp).unwrap(), v);
-            }
+    for &(start, end, ref prefix) in patterns {
+        let is_ideograph = prefix.ends_with("IDEOGRAPH-");
+        if (is_ideograph && !options.ideograph)
+            || (!is_ideograph && !options.derived)
+        {
+            continue;
+        }
+        for cp in start..end + 1 {
+            let tag = if is_ideograph {
+                NameTag::Ideograph
+            } else {
+                NameTag::Derived
+            };
+            f(format!("{}{:04X}", prefix, cp), tag, cp);
         }
     }
-    if hangul {
+    if options.hangul {
         for &(start, end) in ucd_util::RANGE_HANGUL_SYLLABLE {
             for cp in start..end + 1 {
-                let v = (NameTag::Hangul, cp);
-                map.insert(
-                    ucd_util::hangul_name(jamo_short_name_table, cp).unwrap(),
-                    v,
-                );
+                let name =
+                    ucd_util::hangul_name(jamo_short_name_table, cp).unwrap();
+                f(name, NameTag::Hangul, cp);
             }
         }
     }
-    map
+}
+
+/// The number of names that are sorted in memory at a time.
+const RUN_LEN: usize = 1 << 15;
+
+/// Sorts names without holding all of them in memory.
+///
+/// Names are buffered until there are `RUN_LEN` of them, which are then
+/// sorted and written to a temporary file. Once every name has been pushed,
+/// the files are merged. When the same name is pushed more than once, the
+/// value pushed last wins, just like inserting into a map.
+struct ExternalSort {
+    dir: TempDir,
+    run: Vec<(String, u64)>,
+    runs: Vec<PathBuf>,
+}
+
+impl ExternalSort {
+    fn new() -> Result<ExternalSort> {
+        let dir = util::temp_path("ucd-generate-names");
+        Ok(ExternalSort { dir: TempDir::new(dir)?, run: vec![], runs: vec![] })
+    }
+
+    fn push(&mut self, name: String, value: u64) -> Result<()> {
+        self.run.push((name, value));
+        if self.run.len() >= RUN_LEN {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Sort and write the buffered names to a new temporary file.
+    fn flush(&mut self) -> Result<()> {
+        // This sort is stable, so equal names remain in the order in which
+        // they were pushed.
+        self.run.sort_by(|a, b| a.0.cmp(&b.0));
+        let path = self.dir.0.join(format!("run-{}", self.runs.len()));
+        let mut wtr = BufWriter::new(File::create(&path)?);
+        for (i, (name, value)) in self.run.iter().enumerate() {
+            if self.run.get(i + 1).is_some_and(|next| next.0 == *name) {
+                continue;
+            }
+            wtr.write_all(&(name.len() as u32).to_le_bytes())?;
+            wtr.write_all(name.as_bytes())?;
+            wtr.write_all(&value.to_le_bytes())?;
+        }
+        wtr.flush()?;
+        self.run.clear();
+        self.runs.push(path);
+        Ok(())
+    }
+
+    /// Return an iterator over all names pushed, in sorted order.
+    fn finish(mut self) -> Result<Merge> {
+        self.flush()?;
+        let mut merge =
+            Merge { _dir: self.dir, readers: vec![], heap: BinaryHeap::new() };
+        for path in &self.runs {
+            merge.readers.push(BufReader::new(File::open(path)?));
+            merge.next_from(merge.readers.len() - 1)?;
+        }
+        Ok(merge)
+    }
+}
+
+/// A merge of the sorted runs written by an `ExternalSort`.
+struct Merge {
+    /// Keeps the runs on disk until the merge is done.
+    _dir: TempDir,
+    readers: Vec<BufReader<File>>,
+    /// The next name from each run, along with the run's index. Ties
+    /// between equal names are broken by the index, so that names from
+    /// later runs come last.
+    heap: BinaryHeap<Reverse<(String, usize, u64)>>,
+}
+
+impl Merge {
+    /// Read the next name from the given run, if any, into the heap.
+    fn next_from(&mut self, run: usize) -> Result<()> {
+        let rdr = &mut self.readers[run];
+        let mut len = [0; 4];
+        match rdr.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        }
+        let mut name = vec![0; u32::from_le_bytes(len) as usize];
+        rdr.read_exact(&mut name)?;
+        let mut value = [0; 8];
+        rdr.read_exact(&mut value)?;
+        let name = match String::from_utf8(name) {
+            Ok(name) => name,
+            Err(_) => return err!("invalid UTF-8 in sorted run {}", run),
+        };
+        self.heap.push(Reverse((name, run, u64::from_le_bytes(value))));
+        Ok(())
+    }
+}
+
+impl Iterator for Merge {
+    type Item = Result<(String, u64)>;
+
+    fn next(&mut self) -> Option<Result<(String, u64)>> {
+        let Reverse((name, run, mut value)) = self.heap.pop()?;
+        if let Err(err) = self.next_from(run) {
+            return Some(Err(err));
+        }
+        while self.heap.peek().is_some_and(|Reverse(x)| x.0 == name) {
+            let Reverse((_, run, later)) = self.heap.pop().unwrap();
+            value = later;
+            if let Err(err) = self.next_from(run) {
+                return Some(Err(err));
+            }
+        }
+        Some(Ok((name, value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use crate::util::{temp_path, TempDir};
+
+    /// Create a UCD directory with a few named codepoints, one of which
+    /// (U+20AC) is newer than Unicode 1.1.
+    fn ucd_dir() -> TempDir {
+        let dir = TempDir::new(temp_path("ucd-generate-names-test")).unwrap();
+        let files = [
+            ("PropList.txt", "# PropList-14.0.0.txt\n"),
+            (
+                "UnicodeData.txt",
+                "0041;LATIN CAPITAL LETTER A;Lu;0;L;;;;;N;;;;0061;\n\
+                 20AC;EURO SIGN;Sc;0;ET;;;;;N;;;;;\n",
+            ),
+            ("DerivedAge.txt", "0041 ; 1.1\n20AC ; 2.1\n"),
+            ("NameAliases.txt", ""),
+            ("Jamo.txt", ""),
+        ];
+        for (name, contents) in files {
+            fs::write(dir.0.join(name), contents).unwrap();
+        }
+        dir
+    }
+
+    /// Run ucd-generate with the given arguments after the command name
+    /// and UCD directory.
+    fn run(ucd_dir: &Path, args: &[&str]) {
+        let mut argv =
+            vec!["ucd-generate", "names", ucd_dir.to_str().unwrap()];
+        argv.extend(args);
+        argv.push("--quiet");
+        let matches = crate::app::app().get_matches_from_safe(argv).unwrap();
+        crate::run_matches(&matches).unwrap();
+    }
+
+    /// Return the keys of the FST written to the given file.
+    fn fst_keys(path: &Path) -> Vec<String> {
+        let map = fst::Map::new(fs::read(path).unwrap()).unwrap();
+        map.stream().into_str_keys().unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn ideographs_without_derived_name() {
+        let ucd = ucd_dir();
+        let out = TempDir::new(temp_path("ucd-generate-names-out")).unwrap();
+        let out_dir = out.0.to_str().unwrap();
+        run(&ucd.0, &["--no-hangul", "--out-dir", out_dir]);
+        let names = fs::read_to_string(out.0.join("names.rs")).unwrap();
+        assert!(names.contains("(\"CJK UNIFIED IDEOGRAPH-4E00\", 19968)"));
+        assert!(names.contains("(\"TANGUT IDEOGRAPH-17000\", 94208)"));
+        assert!(
+            names.contains("(\"CJK COMPATIBILITY IDEOGRAPH-F900\", 63744)")
+        );
+        assert!(!names.contains("CJK UNIFIED IDEOGRAPH-F900"));
+        assert!(!names.contains("CJK UNIFIED IDEOGRAPH-17000"));
+
+        run(
+            &ucd.0,
+            &["--emit-consts-only", "--no-hangul", "--out-dir", out_dir],
+        );
+        let consts = fs::read_to_string(out.0.join("names.rs")).unwrap();
+        assert!(consts.contains(
+            "  (13312, 19893), (19968, 40917), (63744, 64109), \
+             (64112, 64217),\n"
+        ));
+    }
+
+    #[test]
+    fn low_memory_max_age() {
+        let ucd = ucd_dir();
+        let out = TempDir::new(temp_path("ucd-generate-names-out")).unwrap();
+        let fst_dir = out.0.to_str().unwrap();
+        let flags = ["--no-ideograph", "--no-derived", "--no-hangul"];
+
+        run(&ucd.0, &[&flags[..], &["--fst-dir", fst_dir]].concat());
+        let all = fst_keys(&out.0.join("names.fst"));
+        assert_eq!(all, vec!["EURO SIGN", "LATIN CAPITAL LETTER A"]);
+
+        let low = [&flags[..], &["--low-memory", "--max-age", "1.1"]].concat();
+        run(&ucd.0, &[&low[..], &["--fst-dir", fst_dir]].concat());
+        let old = fst_keys(&out.0.join("names.fst"));
+        assert_eq!(old, vec!["LATIN CAPITAL LETTER A"]);
+    }
 }
@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+
+use ucd_parse::DerivedNormalizationProperty;
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let rows: Vec<DerivedNormalizationProperty> = util::parse(dir)?;
+
+    let (short, long) = if args.is_present("simple") {
+        ("NFKC_SCF", "NFKC_Simple_Casefold")
+    } else {
+        ("NFKC_CF", "NFKC_Casefold")
+    };
+    let mut map: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for row in &rows {
+        if row.property != short && row.property != long {
+            continue;
+        }
+        let mapping: Vec<u32> =
+            row.mapping()?.into_iter().map(|cp| cp.value()).collect();
+        for cp in row.codepoints.into_iter() {
+            map.insert(cp.value(), mapping.clone());
+        }
+    }
+    if map.is_empty() {
+        return err!(
+            "found no {} mappings in DerivedNormalizationProps.txt \
+             (NFKC_SCF requires UCD 15.1 or newer)",
+            short
+        );
+    }
+
+    let mut wtr = args.writer("nfkc_casefold")?;
+    let flat = args.is_present("flat-table");
+    wtr.codepoint_to_codepoints(args.name(), &map, flat)?;
+    wtr.finish()?;
+    Ok(())
+}
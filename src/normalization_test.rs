@@ -0,0 +1,12 @@
+use crate::args::ArgMatches;
+use crate::error::Result;
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let tests = ucd_parse::parse_normalization_tests(dir)?;
+
+    let mut wtr = args.writer("normalization_test")?;
+    wtr.normalization_tests(args.name(), &tests)?;
+    wtr.finish()?;
+    Ok(())
+}
@@ -2,13 +2,14 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 
 use ucd_parse::{
-    self, CoreProperty, EmojiProperty, Property, UcdFileByCodepoint,
-    UnicodeData, UnicodeDataExpander,
+    self, CodepointRange, CoreProperty, DerivedNormalizationProperty,
+    EmojiProperty, Property, UcdFileByCodepoint, UnicodeData,
+    UnicodeDataExpander,
 };
 
 use crate::args::ArgMatches;
 use crate::error::Result;
-use crate::util::{PropertyNames, PropertyValues};
+use crate::util::{self, PropertyNames, PropertyValues};
 
 pub fn command(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
@@ -22,16 +23,99 @@ pub fn command(args: ArgMatches<'_>) -> Result<()> {
         }
         return Ok(());
     }
-    let mut wtr = args.writer("prop_list")?;
-    wtr.names(by_name.keys().filter(|n| filter.contains(n)))?;
-    for (name, set) in by_name {
-        if filter.contains(&name) {
-            wtr.ranges(&name, &set)?;
+    filter.check_included(by_name.keys())?;
+    // regex-syntax calls this module property_bool instead.
+    let module = if args.is_present("regex-syntax") {
+        "property_bool"
+    } else {
+        "prop_list"
+    };
+    let mut wtr = args.writer(module)?;
+    if args.is_present("changes-when") {
+        let name = if args.occurrences_of("name") > 0 {
+            args.name()
+        } else {
+            "CHANGES_WHEN"
+        };
+        let bits = changes_when(dir, &by_name)?;
+        wtr.ranges_to_unsigned_integer(name, &bits)?;
+        let descriptions: Vec<String> = CHANGES_WHEN
+            .iter()
+            .map(|p| format!("Set for codepoints with {}.", p))
+            .collect();
+        let flags: Vec<(&str, &str)> = CHANGES_WHEN
+            .iter()
+            .zip(&descriptions)
+            .map(|(p, d)| (&p["Changes_When_".len()..], d.as_str()))
+            .collect();
+        wtr.bit_flags(name, &flags)?;
+    } else if args.is_present("combined") {
+        let by_name: BTreeMap<String, BTreeSet<u32>> = by_name
+            .into_iter()
+            .filter(|(name, _)| filter.contains(name))
+            .collect();
+        wtr.ranges_to_combined(args.name(), &by_name)?;
+    } else {
+        wtr.names(by_name.keys().filter(|n| filter.contains(n)))?;
+        for (name, set) in by_name {
+            if filter.contains(&name) {
+                wtr.ranges(&name, &set)?;
+            }
         }
     }
+    wtr.finish()?;
     Ok(())
 }
 
+/// The Changes_When_* properties, in the order of their bits in the table
+/// emitted by --changes-when, so that the property at index `i` is the bit
+/// `1 << i`. A constant named after each property without its
+/// `Changes_When_` prefix is emitted for its bit, e.g.,
+/// `CHANGES_WHEN_LOWERCASED`. Changes_When_NFKC_Casefolded is the only one
+/// found in DerivedNormalizationProps.txt instead of
+/// DerivedCoreProperties.txt.
+const CHANGES_WHEN: &[&str] = &[
+    "Changes_When_Lowercased",
+    "Changes_When_Uppercased",
+    "Changes_When_Titlecased",
+    "Changes_When_Casefolded",
+    "Changes_When_Casemapped",
+    "Changes_When_NFKC_Casefolded",
+];
+
+/// Return a map from each codepoint to a bitmask of the Changes_When_*
+/// properties that it has. Codepoints without any of them are absent.
+fn changes_when(
+    dir: &std::ffi::OsStr,
+    props: &BTreeMap<String, BTreeSet<u32>>,
+) -> Result<BTreeMap<u32, u64>> {
+    let rows: Vec<DerivedNormalizationProperty> = util::parse(dir)?;
+    let mut cwkcf = BTreeSet::new();
+    for row in &rows {
+        if row.property == "Changes_When_NFKC_Casefolded"
+            || row.property == "CWKCF"
+        {
+            cwkcf.extend(row.codepoints.into_iter().map(|c| c.value()));
+        }
+    }
+
+    let mut bits: BTreeMap<u32, u64> = BTreeMap::new();
+    for (i, &name) in CHANGES_WHEN.iter().enumerate() {
+        let set = match props.get(name) {
+            Some(set) => set,
+            None if name == "Changes_When_NFKC_Casefolded" => &cwkcf,
+            None => return err!("missing {} property", name),
+        };
+        if set.is_empty() {
+            return err!("missing {} property", name);
+        }
+        for &cp in set {
+            *bits.entry(cp).or_insert(0) |= 1 << i;
+        }
+    }
+    Ok(bits)
+}
+
 pub fn command_perl_word(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
     let props = parse_properties(&dir)?;
@@ -48,10 +132,43 @@ pub fn command_perl_word(args: ArgMatches<'_>) -> Result<()> {
 
     let mut wtr = args.writer("perl_word")?;
     wtr.ranges(args.name(), &perlword)?;
+    wtr.finish()?;
+    Ok(())
+}
+
+pub fn command_validity(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let props = parse_properties(dir)?;
+    let gencats = parse_general_categories(dir)?;
+
+    // Start with every Unicode scalar value, which excludes surrogates.
+    let mut valid: BTreeSet<u32> =
+        CodepointRange::all().scalars().map(u32::from).collect();
+    match props.get("Noncharacter_Code_Point") {
+        None => {
+            return err!("could not find the Noncharacter_Code_Point property")
+        }
+        Some(nonchars) => valid.retain(|cp| !nonchars.contains(cp)),
+    }
+    if args.is_present("exclude-private-use") {
+        if let Some(private) = gencats.get("Private_Use") {
+            valid.retain(|cp| !private.contains(cp));
+        }
+    }
+    if args.is_present("exclude-unassigned") {
+        // Every codepoint listed in UnicodeData.txt is assigned.
+        let assigned: BTreeSet<u32> =
+            gencats.values().flat_map(|set| set.iter().cloned()).collect();
+        valid.retain(|cp| assigned.contains(cp));
+    }
+
+    let mut wtr = args.writer("validity")?;
+    wtr.ranges(args.name(), &valid)?;
+    wtr.finish()?;
     Ok(())
 }
 
-fn parse_properties<P: AsRef<Path>>(
+pub fn parse_properties<P: AsRef<Path>>(
     ucd_dir: P,
 ) -> Result<BTreeMap<String, BTreeSet<u32>>> {
     // TODO: PropList.txt and DerivedCoreProperties.txt cover the majority
@@ -64,7 +181,7 @@ fn parse_properties<P: AsRef<Path>>(
 
     let mut by_name: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
 
-    let prop_list: Vec<Property> = ucd_parse::parse(&ucd_dir)?;
+    let prop_list: Vec<Property> = util::parse(&ucd_dir)?;
     for x in &prop_list {
         by_name
             .entry(x.property.clone())
@@ -72,8 +189,9 @@ fn parse_properties<P: AsRef<Path>>(
             .extend(x.codepoints.into_iter().map(|c| c.value()));
     }
 
-    let core_prop: Vec<CoreProperty> = ucd_parse::parse(&ucd_dir)?;
-    for x in &core_prop {
+    let core_prop: Vec<CoreProperty> = util::parse(&ucd_dir)?;
+    // Rows with a value (i.e., InCB) aren't binary properties.
+    for x in core_prop.iter().filter(|x| x.value.is_none()) {
         by_name
             .entry(x.property.clone())
             .or_insert(BTreeSet::new())
@@ -81,7 +199,7 @@ fn parse_properties<P: AsRef<Path>>(
     }
 
     // Add Bidi_Mirrored
-    let unicode_data: Vec<UnicodeData> = ucd_parse::parse(&ucd_dir)?;
+    let unicode_data: Vec<UnicodeData> = util::parse(&ucd_dir)?;
     let bidi_mirrored =
         unicode_data.iter().fold(BTreeSet::new(), |mut set, x| {
             if x.bidi_mirrored {
@@ -122,7 +240,7 @@ fn parse_general_categories<P: AsRef<Path>>(
     ucd_dir: P,
 ) -> Result<BTreeMap<String, BTreeSet<u32>>> {
     let propvals = PropertyValues::from_ucd_dir(&ucd_dir)?;
-    let unexpanded = ucd_parse::parse(&ucd_dir)?;
+    let unexpanded = util::parse(&ucd_dir)?;
     // Expand all of our UnicodeData rows. This results in one big list of
     // all assigned codepoints.
     let rows: Vec<_> = UnicodeDataExpander::new(unexpanded).collect();
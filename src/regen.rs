@@ -0,0 +1,377 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util;
+
+/// The prefix of the comment that records a `Config` in the header of a
+/// generated file.
+const PREFIX: &str = "// ucd-generate-config: ";
+
+/// The argument that stands for the UCD directory in `Config::args`.
+const UCD_DIR: &str = "{ucd-dir}";
+
+/// The value of --out-dir or --fst-dir in `Config::args`, which stands for
+/// the directory that the file is in.
+const OUT_DIR: &str = "{out-dir}";
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let ucd_dir = args.ucd_dir()?;
+    let diff = args.is_present("diff");
+
+    // Every file written to the same directory by the same command has the
+    // same config, and the command only needs to run once for all of them.
+    let mut runs = BTreeSet::new();
+    for path in args.values_of_os("file").unwrap() {
+        let path = PathBuf::from(path);
+        let config = Config::from_file(&path)?;
+        if config.version != env!("CARGO_PKG_VERSION") {
+            log!(
+                "{} was generated by ucd-generate {}",
+                path.display(),
+                config.version
+            );
+        }
+        let target = match config.output {
+            Output::Stdout => path,
+            Output::OutDir | Output::FstDir => match path.parent() {
+                Some(dir) if dir != Path::new("") => dir.to_path_buf(),
+                _ => PathBuf::from("."),
+            },
+        };
+        runs.insert((config, target));
+    }
+
+    let exe = env::current_exe()?;
+    let quiet = args.is_present("quiet");
+    let mut failed = 0;
+    for (config, target) in &runs {
+        let out_dir = match config.output {
+            Output::Stdout => target.parent().unwrap_or(Path::new(".")),
+            Output::OutDir | Output::FstDir => target,
+        };
+        let mut argv = config.argv(ucd_dir, out_dir);
+        if diff {
+            argv.push("--check".into());
+            argv.push(target.into());
+        }
+        log!("running {} {:?}", exe.display(), argv);
+        let mut cmd = process::Command::new(&exe);
+        cmd.args(&argv);
+        // Output written to stdout is captured, so that the file is only
+        // touched when its contents change. With --quiet, stderr is only
+        // shown if the command fails, since it reports each file written.
+        if config.output != Output::Stdout || diff {
+            cmd.stdout(process::Stdio::inherit());
+        }
+        if !quiet {
+            cmd.stderr(process::Stdio::inherit());
+        }
+        let output = cmd.output()?;
+        if !output.status.success() {
+            io::stderr().write_all(&output.stderr)?;
+            eprintln!("regenerating {} failed", target.display());
+            failed += 1;
+        } else if config.output == Output::Stdout && !diff {
+            let changed = crate::writer::write_if_changed(
+                target,
+                &output.stdout,
+                false,
+            )?;
+            if changed && !quiet {
+                eprintln!("wrote {}", target.display());
+            }
+        }
+    }
+    if failed > 0 {
+        return err!("{} of {} command(s) failed", failed, runs.len());
+    }
+    Ok(())
+}
+
+/// The command that generated a file, as recorded in its header by
+/// --config-header, which is enough to run it again on another UCD
+/// directory.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Config {
+    /// The arguments given to ucd-generate, without --check, and with the
+    /// UCD directory and output directory replaced by `UCD_DIR` and
+    /// `OUT_DIR`.
+    pub args: Vec<String>,
+    /// Where the output was written.
+    pub output: Output,
+    /// The version of ucd-generate.
+    pub version: String,
+    /// The version of the UCD directory, if it could be determined.
+    pub unicode_version: Option<(u64, u64, u64)>,
+}
+
+/// Where a generated file was written.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Output {
+    /// To stdout, which was presumably redirected to the file.
+    Stdout,
+    /// To the directory given to --out-dir.
+    OutDir,
+    /// To the directory given to --fst-dir.
+    FstDir,
+}
+
+impl Output {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            Output::Stdout => "stdout",
+            Output::OutDir => "out-dir",
+            Output::FstDir => "fst-dir",
+        }
+    }
+}
+
+impl Config {
+    /// Return the config of the command that this program was run with,
+    /// given the UCD directory and output that it was parsed to have.
+    ///
+    /// The UCD directory is the first argument equal to `ucd_dir` that
+    /// isn't the value of a flag.
+    pub fn from_env(
+        ucd_dir: &OsStr,
+        output: Output,
+        unicode_version: Option<(u64, u64, u64)>,
+    ) -> Result<Config> {
+        let mut args = vec![];
+        let mut found_ucd_dir = false;
+        let mut argv = env::args_os().skip(1);
+        while let Some(arg) = argv.next() {
+            let x = arg.to_string_lossy();
+            if x == "--check" {
+                argv.next();
+            } else if x.starts_with("--check=")
+                || crate::writer::UNRECORDED_FLAGS.contains(&&*x)
+            {
+            } else if x == "--out-dir" || x == "--fst-dir" {
+                args.push(x.into_owned());
+                args.push(OUT_DIR.to_string());
+                argv.next();
+            } else if x.starts_with("--out-dir=")
+                || x.starts_with("--fst-dir=")
+            {
+                let flag = &x[..x.find('=').unwrap()];
+                args.push(format!("{}={}", flag, OUT_DIR));
+            } else if !found_ucd_dir && arg == ucd_dir {
+                args.push(UCD_DIR.to_string());
+                found_ucd_dir = true;
+            } else {
+                args.push(x.into_owned());
+            }
+        }
+        if !found_ucd_dir {
+            return err!(
+                "--config-header could not find the UCD directory in the \
+                 command line"
+            );
+        }
+        Ok(Config {
+            args,
+            output,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            unicode_version,
+        })
+    }
+
+    /// Find the config in the header of the given generated file.
+    pub fn from_file(path: &Path) -> Result<Config> {
+        let contents = fs::read(path)?;
+        let contents = String::from_utf8_lossy(&contents);
+        let line = match contents.lines().find(|l| l.starts_with(PREFIX)) {
+            Some(line) => line,
+            None => {
+                return err!(
+                    "{} has no ucd-generate-config line, which is written \
+                     when it is generated with --config-header",
+                    path.display()
+                )
+            }
+        };
+        match Config::from_comment(line) {
+            Some(config) => Ok(config),
+            None => err!(
+                "{} has an invalid ucd-generate-config line",
+                path.display()
+            ),
+        }
+    }
+
+    /// Return the comment that records this config.
+    pub fn to_comment(&self) -> String {
+        let args: Vec<String> =
+            self.args.iter().map(|arg| util::json_string(arg)).collect();
+        let unicode_version = match self.unicode_version {
+            None => "null".to_string(),
+            Some((major, minor, patch)) => {
+                format!("\"{}.{}.{}\"", major, minor, patch)
+            }
+        };
+        format!(
+            "{}{{\"version\": {}, \"unicode_version\": {}, \
+             \"output\": \"{}\", \"args\": [{}]}}",
+            PREFIX,
+            util::json_string(&self.version),
+            unicode_version,
+            self.output.as_str(),
+            args.join(", "),
+        )
+    }
+
+    /// Parse a comment written by `to_comment`.
+    pub fn from_comment(line: &str) -> Option<Config> {
+        let json = line.strip_prefix(PREFIX)?;
+        let mut p = Parser(json.trim().chars().peekable());
+        let (mut version, mut unicode_version, mut output) =
+            (None, None, None);
+        let mut args = None;
+        p.expect('{')?;
+        loop {
+            let key = p.string()?;
+            p.expect(':')?;
+            match &*key {
+                "version" => version = Some(p.string()?),
+                "unicode_version" if p.null() => {
+                    unicode_version = Some(None);
+                }
+                "unicode_version" => {
+                    let v = p.string()?;
+                    let mut parts = v.split('.').map(|x| x.parse().ok());
+                    let major = parts.next()??;
+                    let minor = parts.next()??;
+                    let patch = parts.next()??;
+                    unicode_version = Some(Some((major, minor, patch)));
+                }
+                "output" => {
+                    output = Some(match &*p.string()? {
+                        "stdout" => Output::Stdout,
+                        "out-dir" => Output::OutDir,
+                        "fst-dir" => Output::FstDir,
+                        _ => return None,
+                    });
+                }
+                "args" => args = Some(p.strings()?),
+                _ => return None,
+            }
+            if !p.eat(',') {
+                break;
+            }
+        }
+        p.expect('}')?;
+        Some(Config {
+            args: args?,
+            output: output?,
+            version: version?,
+            unicode_version: unicode_version?,
+        })
+    }
+
+    /// Return the arguments to run this config's command with, given the
+    /// UCD directory and the directory that the file is in.
+    fn argv(&self, ucd_dir: &OsStr, out_dir: &Path) -> Vec<OsString> {
+        self.args
+            .iter()
+            .map(|arg| {
+                if arg == UCD_DIR {
+                    return ucd_dir.to_owned();
+                } else if arg == OUT_DIR {
+                    return out_dir.as_os_str().to_owned();
+                }
+                match arg.strip_suffix(OUT_DIR) {
+                    Some(flag) if flag.ends_with('=') => {
+                        let mut arg = OsString::from(flag);
+                        arg.push(out_dir);
+                        arg
+                    }
+                    _ => OsString::from(arg),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A parser for the subset of JSON written by `Config::to_comment`.
+struct Parser<'a>(std::iter::Peekable<std::str::Chars<'a>>);
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.0.peek().is_some_and(|c| c.is_whitespace()) {
+            self.0.next();
+        }
+    }
+
+    /// Consume the given character if it is next, ignoring whitespace.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.0.peek() == Some(&c) {
+            self.0.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Option<()> {
+        if self.eat(c) {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn null(&mut self) -> bool {
+        self.skip_whitespace();
+        if self.0.clone().take(4).eq("null".chars()) {
+            self.0.nth(3);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.0.next()? {
+                '"' => return Some(s),
+                '\\' => s.push(match self.0.next()? {
+                    'n' => '\n',
+                    't' => '\t',
+                    'u' => {
+                        let hex: String = self.0.by_ref().take(4).collect();
+                        char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+                    }
+                    c => c,
+                }),
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn strings(&mut self) -> Option<Vec<String>> {
+        self.expect('[')?;
+        let mut strings = vec![];
+        if self.eat(']') {
+            return Some(strings);
+        }
+        loop {
+            strings.push(self.string()?);
+            if !self.eat(',') {
+                break;
+            }
+        }
+        self.expect(']')?;
+        Some(strings)
+    }
+}
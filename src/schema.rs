@@ -0,0 +1,626 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use ucd_parse::{AllKeysEntry, AllKeysLine};
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util::{json_string, TempDir};
+use crate::writer::{Writer, WriterBuilder};
+
+/// The version of the format printed by this command. This only changes
+/// when the JSON itself changes shape, not when the tables it describes do.
+const SCHEMA_VERSION: u32 = 1;
+
+/// A kind of table, along with a function that writes an example of it.
+///
+/// Every example table is named `EXAMPLE_TABLE`, as if it were given with
+/// `--name EXAMPLE_TABLE`, so its items are named after that (e.g.,
+/// `ExampleTable` for Rust enums). Enum values are named `Alpha` and `Beta`.
+struct Kind {
+    name: &'static str,
+    write: fn(&mut Writer) -> Result<()>,
+}
+
+/// An output format, along with how to configure a writer for it.
+struct Format {
+    name: &'static str,
+    fst: bool,
+    configure: fn(&mut WriterBuilder),
+}
+
+/// The items and files produced by writing one kind of table in one format.
+#[derive(Eq, PartialEq)]
+struct Shape {
+    items: Vec<Item>,
+    files: Vec<String>,
+}
+
+/// A single top-level item in a generated Rust module.
+#[derive(Eq, PartialEq)]
+enum Item {
+    /// A `const` or `static`, with its name and type.
+    Value { item: &'static str, name: String, ty: String },
+    /// Any other item, such as a `fn`, `enum` or `impl`, with its signature.
+    Other { item: &'static str, signature: String },
+}
+
+pub fn command(_: ArgMatches<'_>) -> Result<()> {
+    let mut kinds = vec![];
+    for kind in kinds_of_tables() {
+        let mut shapes: Vec<(&str, Shape)> = vec![];
+        for format in formats() {
+            let shape = match shape(&kind, &format)? {
+                None => continue,
+                Some(shape) => shape,
+            };
+            // Formats that a kind of table doesn't support, but that don't
+            // cause an error either, produce the default shape again.
+            if shapes.iter().any(|(_, s)| *s == shape) {
+                continue;
+            }
+            shapes.push((format.name, shape));
+        }
+        kinds.push((kind.name, shapes));
+    }
+
+    println!("{{");
+    println!("  \"schema_version\": {},", SCHEMA_VERSION);
+    println!("  \"version\": {},", json_string(clap::crate_version!()));
+    println!("  \"tables\": [");
+    for (i, (kind, shapes)) in kinds.iter().enumerate() {
+        println!("    {{\"kind\": {}, \"formats\": [", json_string(kind));
+        for (j, (format, shape)) in shapes.iter().enumerate() {
+            let items: Vec<String> =
+                shape.items.iter().map(item_json).collect();
+            let files: Vec<String> =
+                shape.files.iter().map(|f| json_string(f)).collect();
+            println!(
+                "      {{\"format\": {}, \"files\": [{}], \"items\": [",
+                json_string(format),
+                files.join(", "),
+            );
+            for (k, item) in items.iter().enumerate() {
+                let comma = if k + 1 == items.len() { "" } else { "," };
+                println!("        {}{}", item, comma);
+            }
+            let comma = if j + 1 == shapes.len() { "" } else { "," };
+            println!("      ]}}{}", comma);
+        }
+        let comma = if i + 1 == kinds.len() { "" } else { "," };
+        println!("    ]}}{}", comma);
+    }
+    println!("  ],");
+    println!("  \"commands\": [");
+    for (i, &(command, kinds)) in COMMANDS.iter().enumerate() {
+        let kinds: Vec<String> =
+            kinds.iter().map(|k| json_string(k)).collect();
+        let comma = if i + 1 == COMMANDS.len() { "" } else { "," };
+        println!(
+            "    {{\"name\": {}, \"kinds\": [{}]}}{}",
+            json_string(command),
+            kinds.join(", "),
+            comma,
+        );
+    }
+    println!("  ]");
+    println!("}}");
+    Ok(())
+}
+
+fn item_json(item: &Item) -> String {
+    match *item {
+        Item::Value { item, ref name, ref ty } => format!(
+            "{{\"item\": {}, \"name\": {}, \"type\": {}}}",
+            json_string(item),
+            json_string(name),
+            json_string(ty),
+        ),
+        Item::Other { item, ref signature } => format!(
+            "{{\"item\": {}, \"signature\": {}}}",
+            json_string(item),
+            json_string(signature),
+        ),
+    }
+}
+
+/// Write an example of the given kind of table in the given format, and
+/// return the shape of what was written. If the kind of table can't be
+/// written in that format, then return `None`.
+fn shape(kind: &Kind, format: &Format) -> Result<Option<Shape>> {
+    let dir = TempDir::new(
+        std::env::temp_dir()
+            .join(format!("ucd-generate-schema-{}", std::process::id())),
+    )?;
+    let mut builder = WriterBuilder::new("example");
+    builder.quiet(true);
+    (format.configure)(&mut builder);
+    let mut wtr = if format.fst {
+        builder.from_fst_dir(&dir.0)?
+    } else {
+        builder.from_out_dir(&dir.0)?
+    };
+    if (kind.write)(&mut wtr).and_then(|()| wtr.finish()).is_err() {
+        return Ok(None);
+    }
+
+    let mut files = BTreeSet::new();
+    for entry in fs::read_dir(&dir.0)? {
+        files.insert(entry?.file_name().to_string_lossy().into_owned());
+    }
+    let source = fs::read_to_string(dir.0.join("example.rs"))?;
+    Ok(Some(Shape {
+        items: parse_items(&source),
+        files: files.into_iter().collect(),
+    }))
+}
+
+/// Parse the top-level items in the given generated Rust source code.
+fn parse_items(source: &str) -> Vec<Item> {
+    let mut items = vec![];
+    let mut lines = source.lines().peekable();
+    while let Some(line) = lines.next() {
+        let mut decl = line.to_string();
+        let is_value =
+            line.starts_with("pub const ") || line.starts_with("pub static ");
+        // The type of a value may be wrapped onto the lines after its name,
+        // so join them until the start of its definition.
+        while is_value && !decl.contains(" = ") && !decl.ends_with(" =") {
+            match lines.next_if(|l| l.starts_with(' ') || l.starts_with(')')) {
+                None => break,
+                Some(next) => {
+                    decl.push(' ');
+                    decl.push_str(next.trim());
+                }
+            }
+        }
+        let decl = decl.replace("( ", "(").replace(", )", ")");
+        if let Some(item) = parse_item(&decl) {
+            items.push(item);
+        }
+    }
+    items
+}
+
+/// Parse a single top-level item declaration, if it is one.
+fn parse_item(decl: &str) -> Option<Item> {
+    for &item in &["const", "static"] {
+        let rest = match decl.strip_prefix(&format!("pub {} ", item)) {
+            None => continue,
+            Some(rest) => rest,
+        };
+        let (name, rest) = rest.split_once(": ")?;
+        let ty = match rest.find(" = ") {
+            Some(i) => &rest[..i],
+            None => rest.trim_end_matches(" ="),
+        };
+        let (name, ty) = (name.to_string(), ty.to_string());
+        return Some(Item::Value { item, name, ty });
+    }
+    let item = if let Some(rest) = decl.strip_prefix("pub ") {
+        rest.split(' ').next()?
+    } else if decl.starts_with("impl") {
+        "impl"
+    } else {
+        return None;
+    };
+    let item = match item {
+        "fn" => "fn",
+        "enum" => "enum",
+        "struct" => "struct",
+        "mod" => "mod",
+        "type" => "type",
+        "impl" => "impl",
+        _ => return None,
+    };
+    let signature = decl.trim_end_matches('{').trim_end().to_string();
+    Some(Item::Other { item, signature })
+}
+
+fn formats() -> Vec<Format> {
+    vec![
+        Format { name: "slice", fst: false, configure: |_| {} },
+        Format {
+            name: "u16-ranges",
+            fst: false,
+            configure: |b| {
+                b.u16_ranges(true);
+            },
+        },
+        Format {
+            name: "chars",
+            fst: false,
+            configure: |b| {
+                b.char_literals(true);
+            },
+        },
+        Format {
+            name: "trie-set",
+            fst: false,
+            configure: |b| {
+                b.trie_set(true);
+            },
+        },
+        Format {
+            name: "group-by-plane",
+            fst: false,
+            configure: |b| {
+                b.group_by_plane(true);
+            },
+        },
+        Format {
+            name: "binary",
+            fst: false,
+            configure: |b| {
+                b.binary(true);
+            },
+        },
+        Format { name: "fst", fst: true, configure: |_| {} },
+    ]
+}
+
+fn set() -> BTreeSet<u32> {
+    (0x41..=0x5A).collect()
+}
+
+fn enum_map() -> BTreeMap<String, BTreeSet<u32>> {
+    let mut map = BTreeMap::new();
+    map.insert("Alpha".to_string(), (0x41..=0x5A).collect());
+    map.insert("Beta".to_string(), (0x61..=0x7A).collect());
+    map
+}
+
+fn kinds_of_tables() -> Vec<Kind> {
+    vec![
+        Kind {
+            name: "ranges",
+            write: |w| {
+                w.names(["EXAMPLE_TABLE"])?;
+                w.ranges("EXAMPLE_TABLE", &set())
+            },
+        },
+        Kind {
+            name: "ranges_to_enum",
+            write: |w| w.ranges_to_enum("EXAMPLE_TABLE", &enum_map()),
+        },
+        Kind {
+            name: "ranges_to_rust_enum",
+            write: |w| {
+                w.ranges_to_rust_enum(
+                    "EXAMPLE_TABLE",
+                    &["Alpha", "Beta"],
+                    &enum_map(),
+                )
+            },
+        },
+        Kind {
+            name: "ranges_to_combined",
+            write: |w| w.ranges_to_combined("EXAMPLE_TABLE", &enum_map()),
+        },
+        Kind {
+            name: "ranges_to_utf8_trie",
+            write: |w| w.ranges_to_utf8_trie("EXAMPLE_TABLE", &enum_map()),
+        },
+        Kind {
+            name: "ranges_to_unsigned_integer",
+            write: |w| {
+                let map = set().into_iter().map(|cp| (cp, 1)).collect();
+                w.ranges_to_unsigned_integer("EXAMPLE_TABLE", &map)
+            },
+        },
+        Kind {
+            name: "codepoint_to_codepoint",
+            write: |w| {
+                let map =
+                    set().into_iter().map(|cp| (cp, cp + 0x20)).collect();
+                w.codepoint_to_codepoint("EXAMPLE_TABLE", &map)
+            },
+        },
+        Kind {
+            name: "codepoint_to_codepoints",
+            write: |w| {
+                let map =
+                    set().into_iter().map(|cp| (cp, vec![cp, cp])).collect();
+                w.codepoint_to_codepoints("EXAMPLE_TABLE", &map, false)
+            },
+        },
+        Kind {
+            name: "codepoint_to_codepoints_flat",
+            write: |w| {
+                let map =
+                    set().into_iter().map(|cp| (cp, vec![cp, cp])).collect();
+                w.codepoint_to_codepoints("EXAMPLE_TABLE", &map, true)
+            },
+        },
+        Kind {
+            name: "codepoint_pairs",
+            write: |w| {
+                let pairs = set().into_iter().map(|cp| (cp, cp)).collect();
+                w.codepoint_pairs("EXAMPLE_TABLE", &pairs)
+            },
+        },
+        Kind {
+            name: "codepoint_pairs_to_codepoint",
+            write: |w| {
+                let map = set().into_iter().map(|cp| ((cp, cp), cp)).collect();
+                w.codepoint_pairs_to_codepoint("EXAMPLE_TABLE", &map, false)
+            },
+        },
+        Kind {
+            name: "codepoint_pairs_to_codepoint_two_level",
+            write: |w| {
+                let map = set().into_iter().map(|cp| ((cp, cp), cp)).collect();
+                w.codepoint_pairs_to_codepoint("EXAMPLE_TABLE", &map, true)
+            },
+        },
+        Kind {
+            name: "codepoint_to_string",
+            write: |w| {
+                let map =
+                    set().into_iter().map(|cp| (cp, cp.to_string())).collect();
+                w.codepoint_to_string("EXAMPLE_TABLE", &map)
+            },
+        },
+        Kind {
+            name: "codepoint_to_string_fn",
+            write: |w| {
+                let map =
+                    set().into_iter().map(|cp| (cp, cp.to_string())).collect();
+                w.codepoint_to_string_fn("EXAMPLE_TABLE", &map)
+            },
+        },
+        Kind {
+            name: "collation",
+            write: |w| {
+                let entries: Vec<AllKeysEntry> = [
+                    "0041 ; [.1C47.0020.0008]",
+                    "0041 0301 ; [.1C48.0020.0008]",
+                ]
+                .iter()
+                .map(|line| match line.parse().unwrap() {
+                    AllKeysLine::Entry(entry) => entry,
+                    _ => unreachable!(),
+                })
+                .collect();
+                w.collation("EXAMPLE_TABLE", &[], &entries)
+            },
+        },
+        Kind {
+            name: "string_to_codepoint",
+            write: |w| {
+                let map =
+                    set().into_iter().map(|cp| (cp.to_string(), cp)).collect();
+                w.string_to_codepoint("EXAMPLE_TABLE", &map)
+            },
+        },
+        Kind {
+            name: "string_to_codepoints",
+            write: |w| {
+                let map = set()
+                    .into_iter()
+                    .map(|cp| (cp.to_string(), vec![cp, cp]))
+                    .collect();
+                w.string_to_codepoints("EXAMPLE_TABLE", &map, false)
+            },
+        },
+        Kind {
+            name: "string_to_codepoints_flat",
+            write: |w| {
+                let map = set()
+                    .into_iter()
+                    .map(|cp| (cp.to_string(), vec![cp, cp]))
+                    .collect();
+                w.string_to_codepoints("EXAMPLE_TABLE", &map, true)
+            },
+        },
+        Kind {
+            name: "string_to_u64",
+            write: |w| {
+                let map = set()
+                    .into_iter()
+                    .map(|cp| (cp.to_string(), cp as u64))
+                    .collect();
+                w.string_to_u64("EXAMPLE_TABLE", &map)
+            },
+        },
+        Kind {
+            name: "string_to_string",
+            write: |w| {
+                let mut map = BTreeMap::new();
+                map.insert("a".to_string(), "Alpha".to_string());
+                w.string_to_string("EXAMPLE_TABLE", &map)
+            },
+        },
+        Kind {
+            name: "string_to_string_to_string",
+            write: |w| {
+                let mut values = BTreeMap::new();
+                values.insert("a".to_string(), "Alpha".to_string());
+                let mut map = BTreeMap::new();
+                map.insert("EXAMPLE_TABLE".to_string(), values);
+                w.string_to_string_to_string("EXAMPLE_TABLE", &map)
+            },
+        },
+        Kind {
+            name: "pair_table",
+            write: |w| {
+                let classes = ["Alpha".to_string(), "Beta".to_string()];
+                let outcomes = [("Allowed", ""), ("Prohibited", "")];
+                let table = vec![vec![0, 1], vec![1, 0]];
+                w.pair_table(
+                    "EXAMPLE_TABLE",
+                    &classes,
+                    "ExampleTableOutcome",
+                    &outcomes,
+                    &table,
+                )
+            },
+        },
+        Kind {
+            name: "strings",
+            write: |w| {
+                let set = ["a", "b"].iter().map(|s| s.to_string()).collect();
+                w.strings("EXAMPLE_TABLE", &set)
+            },
+        },
+    ]
+}
+
+/// The kinds of tables that each command can write, depending on its flags.
+///
+/// Commands that write test data rather than tables aren't included.
+const COMMANDS: &[(&str, &[&str])] = &[
+    ("age", &["ranges", "ranges_to_combined"]),
+    (
+        "bidi-class",
+        &[
+            "ranges",
+            "ranges_to_enum",
+            "ranges_to_rust_enum",
+            "ranges_to_combined",
+        ],
+    ),
+    ("bidi-mirroring-glyph", &["ranges", "codepoint_to_codepoint"]),
+    (
+        "canonical-combining-class",
+        &[
+            "ranges",
+            "ranges_to_enum",
+            "ranges_to_rust_enum",
+            "ranges_to_combined",
+        ],
+    ),
+    (
+        "case-folding-simple",
+        &[
+            "codepoint_to_codepoint",
+            "codepoint_to_codepoints",
+            "codepoint_to_codepoints_flat",
+        ],
+    ),
+    (
+        "case-mapping",
+        &[
+            "codepoint_to_codepoint",
+            "codepoint_to_codepoints",
+            "codepoint_to_codepoints_flat",
+            "ranges_to_unsigned_integer",
+        ],
+    ),
+    (
+        "canonical-composition",
+        &[
+            "codepoint_pairs_to_codepoint",
+            "codepoint_pairs_to_codepoint_two_level",
+        ],
+    ),
+    ("collation", &["collation"]),
+    ("do-not-emit", &["string_to_string"]),
+    ("emoji", &["ranges", "strings", "codepoint_pairs"]),
+    (
+        "general-category",
+        &[
+            "ranges",
+            "ranges_to_enum",
+            "ranges_to_rust_enum",
+            "ranges_to_combined",
+            "ranges_to_utf8_trie",
+        ],
+    ),
+    (
+        "grapheme-cluster-break",
+        &[
+            "ranges",
+            "ranges_to_enum",
+            "ranges_to_rust_enum",
+            "ranges_to_combined",
+            "ranges_to_utf8_trie",
+        ],
+    ),
+    (
+        "jamo-short-name",
+        &[
+            "codepoint_to_string",
+            "codepoint_to_string_fn",
+            "string_to_codepoint",
+        ],
+    ),
+    (
+        "indic-conjunct-break",
+        &[
+            "ranges",
+            "ranges_to_enum",
+            "ranges_to_rust_enum",
+            "ranges_to_combined",
+            "ranges_to_utf8_trie",
+        ],
+    ),
+    (
+        "joining-group",
+        &[
+            "ranges",
+            "ranges_to_enum",
+            "ranges_to_rust_enum",
+            "ranges_to_combined",
+        ],
+    ),
+    (
+        "joining-type",
+        &[
+            "ranges",
+            "ranges_to_enum",
+            "ranges_to_rust_enum",
+            "ranges_to_combined",
+        ],
+    ),
+    ("line-break-pairs", &["pair_table"]),
+    (
+        "named-sequences",
+        &["string_to_codepoints", "string_to_codepoints_flat"],
+    ),
+    ("names", &["string_to_codepoint", "string_to_u64"]),
+    (
+        "nfkc-casefold",
+        &["codepoint_to_codepoints", "codepoint_to_codepoints_flat"],
+    ),
+    ("perl-word", &["ranges"]),
+    (
+        "property-bool",
+        &["ranges", "ranges_to_combined", "ranges_to_unsigned_integer"],
+    ),
+    ("property-names", &["string_to_string"]),
+    ("property-values", &["string_to_string", "string_to_string_to_string"]),
+    (
+        "script",
+        &[
+            "ranges",
+            "ranges_to_enum",
+            "ranges_to_rust_enum",
+            "ranges_to_combined",
+        ],
+    ),
+    ("script-extension", &["ranges", "ranges_to_combined"]),
+    (
+        "sentence-break",
+        &[
+            "ranges",
+            "ranges_to_enum",
+            "ranges_to_rust_enum",
+            "ranges_to_combined",
+            "ranges_to_utf8_trie",
+        ],
+    ),
+    ("setop", &["ranges"]),
+    ("terminal-width", &["ranges_to_unsigned_integer"]),
+    ("validity", &["ranges"]),
+    (
+        "word-break",
+        &[
+            "ranges",
+            "ranges_to_enum",
+            "ranges_to_rust_enum",
+            "ranges_to_combined",
+            "ranges_to_utf8_trie",
+        ],
+    ),
+];
@@ -1,10 +1,14 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
 
-use ucd_parse::{self, Script, ScriptExtension};
+use ucd_parse::{Codepoint, CodepointRange, Script, ScriptExtension};
 
 use crate::args::ArgMatches;
+use crate::derive::{Derived, Engine};
 use crate::error::Result;
-use crate::util::{print_property_values, PropertyValues};
+use crate::util::{
+    self, enum_aliases, filter_enum, print_property_values, PropertyValues,
+};
 
 pub fn command_script(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
@@ -16,7 +20,7 @@ pub fn command_script(args: ArgMatches<'_>) -> Result<()> {
     }
 
     let mut by_name: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
-    let scripts: Vec<Script> = ucd_parse::parse(&dir)?;
+    let scripts: Vec<Script> = util::parse(&dir)?;
     for x in &scripts {
         by_name
             .entry(x.script.clone())
@@ -24,14 +28,31 @@ pub fn command_script(args: ArgMatches<'_>) -> Result<()> {
             .extend(x.codepoints.into_iter().map(|c| c.value()));
     }
 
+    filter.check_included(by_name.keys())?;
+    let other = args.value_of("enum-other");
+    if args.is_present("enum") || args.is_present("rust-enum") {
+        by_name = filter_enum(by_name, &filter, other)?;
+    }
+
     let mut wtr = args.writer("script")?;
-    if args.is_present("enum") {
+    if args.is_present("enum-aliases") {
+        let aliases =
+            enum_aliases(&propvals, "Script", by_name.keys(), other)?;
+        wtr.ranges_to_enum_with_aliases(args.name(), &by_name, &aliases)?;
+    } else if args.is_present("enum") {
         wtr.ranges_to_enum(args.name(), &by_name)?;
     } else if args.is_present("rust-enum") {
-        let mut variants = vec!["Unknown"];
+        let mut variants = vec![];
+        if !by_name.contains_key("Unknown") {
+            variants.push("Unknown");
+        }
         variants.extend(by_name.keys().map(String::as_str));
         wtr.ranges_to_rust_enum(args.name(), &variants, &by_name)?;
     } else if args.is_present("combined") {
+        let by_name: BTreeMap<String, BTreeSet<u32>> = by_name
+            .into_iter()
+            .filter(|(name, _)| filter.contains(name))
+            .collect();
         wtr.ranges_to_combined(args.name(), &by_name)?;
     } else {
         wtr.names(by_name.keys().filter(|n| filter.contains(n)))?;
@@ -42,22 +63,79 @@ pub fn command_script(args: ArgMatches<'_>) -> Result<()> {
         }
     }
 
+    wtr.finish()?;
     Ok(())
 }
 
 pub fn command_script_extension(args: ArgMatches<'_>) -> Result<()> {
     let dir = args.ucd_dir()?;
-    let propvals = PropertyValues::from_ucd_dir(&dir)?;
+    let propvals = PropertyValues::from_ucd_dir(dir)?;
     let filter = args.filter(|name| propvals.canonical("Script", name))?;
 
     if args.is_present("list-script-extensions") {
         return print_property_values(&propvals, "Script");
     }
 
+    let mut engine = Engine::with_property_values(dir, Rc::new(propvals));
+    let scripts = engine.scripts()?;
+    let by_name = engine.property("Script_Extensions")?.values.clone();
+    filter.check_included(by_name.keys())?;
+
+    let mut wtr = args.writer("script_extension")?;
+    if args.is_present("bitset-enum") {
+        // The variants are the scripts in Scripts.txt, in the same order
+        // used by `script --enum`, so that one enum serves both tables.
+        let variants = script_names(&scripts);
+        let mut sets_by_cp: BTreeMap<u32, BTreeSet<usize>> = BTreeMap::new();
+        for (name, set) in &by_name {
+            let i = match variants.binary_search(name) {
+                Ok(i) => i,
+                Err(_) => {
+                    return err!(
+                        "script extension {:?} is not in Scripts.txt",
+                        name
+                    )
+                }
+            };
+            for &cp in set {
+                sets_by_cp.entry(cp).or_default().insert(i);
+            }
+        }
+        wtr.ranges_to_enum_sets(args.name(), &variants, &sets_by_cp)?;
+        if args.is_present("per-script") {
+            wtr.names(by_name.keys())?;
+            for (name, set) in &by_name {
+                wtr.ranges(name, set)?;
+            }
+        }
+    } else if args.is_present("combined") {
+        let by_name: BTreeMap<String, BTreeSet<u32>> = by_name
+            .into_iter()
+            .filter(|(name, _)| filter.contains(name))
+            .collect();
+        wtr.ranges_to_combined(args.name(), &by_name)?;
+    } else {
+        wtr.names(by_name.keys().filter(|n| filter.contains(n)))?;
+        for (name, set) in by_name {
+            if filter.contains(&name) {
+                wtr.ranges(&name, &set)?;
+            }
+        }
+    }
+    wtr.finish()?;
+    Ok(())
+}
+
+/// Return the set of codepoints for each Script_Extensions value, given the
+/// rows of Scripts.txt and ScriptExtensions.txt.
+pub fn script_extension_sets(
+    propvals: &PropertyValues,
+    scripts: &[Script],
+    exts: &[ScriptExtension],
+) -> Result<BTreeMap<String, BTreeSet<u32>>> {
     let mut by_name: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
     let mut seen: BTreeSet<u32> = BTreeSet::new();
-    let exts: Vec<ScriptExtension> = ucd_parse::parse(&dir)?;
-    for x in &exts {
+    for x in exts {
         seen.extend(x.codepoints.into_iter().map(|c| c.value()));
         for name in &x.scripts {
             let name = propvals.canonical("Script", name)?;
@@ -71,8 +149,7 @@ pub fn command_script_extension(args: ArgMatches<'_>) -> Result<()> {
     // ScriptExtensions.txt does not list every codepoint. Omitted codepoints
     // default to the set of scripts containing exactly one element: its
     // corresponding Script value. c.f. UAX #24 S4.2.
-    let scripts: Vec<Script> = ucd_parse::parse(&dir)?;
-    for x in &scripts {
+    for x in scripts {
         if !by_name.contains_key(&x.script) {
             by_name.insert(x.script.clone(), BTreeSet::new());
         }
@@ -82,13 +159,66 @@ pub fn command_script_extension(args: ArgMatches<'_>) -> Result<()> {
             }
         }
     }
+    Ok(by_name)
+}
 
-    let mut wtr = args.writer("script_extension")?;
-    wtr.names(by_name.keys().filter(|n| filter.contains(n)))?;
-    for (name, set) in by_name {
-        if filter.contains(&name) {
-            wtr.ranges(&name, &set)?;
+/// Derive the Script of every codepoint. Codepoints that aren't listed in
+/// Scripts.txt are Unknown.
+pub fn derive_script(engine: &mut Engine<'_>) -> Result<Derived> {
+    let scripts = engine.scripts()?;
+    let mut derived = Derived::default();
+    let listed = derived.rule("listed in Scripts.txt", &[]);
+    let unlisted = derived.rule("not listed in Scripts.txt", &[]);
+    let mut assigned = BTreeSet::new();
+    for x in scripts.iter() {
+        for cp in x.codepoints.into_iter().map(|c| c.value()) {
+            assigned.insert(cp);
+            derived.assign(listed, cp, &x.script);
         }
     }
-    Ok(())
+    for cp in CodepointRange::all().into_iter().map(Codepoint::value) {
+        if !assigned.contains(&cp) {
+            derived.assign(unlisted, cp, "Unknown");
+        }
+    }
+    Ok(derived)
+}
+
+/// Derive the Script_Extensions of every codepoint, as computed by
+/// `script_extension_sets`. Codepoints that are in neither Scripts.txt nor
+/// ScriptExtensions.txt have no value.
+pub fn derive_script_extension(engine: &mut Engine<'_>) -> Result<Derived> {
+    let propvals = engine.propvals();
+    let scripts = engine.scripts()?;
+    let exts: Vec<ScriptExtension> = util::parse(engine.dir())?;
+    let by_name = script_extension_sets(&propvals, &scripts, &exts)?;
+
+    let mut derived = Derived::default();
+    let listed = derived.rule("listed in ScriptExtensions.txt", &[]);
+    let unlisted = derived.rule(
+        "not listed in ScriptExtensions.txt, so it is the same as Script",
+        &["Script"],
+    );
+    let seen: BTreeSet<u32> = exts
+        .iter()
+        .flat_map(|x| x.codepoints.into_iter().map(|c| c.value()))
+        .collect();
+    for (name, set) in &by_name {
+        derived.value(name);
+        for &cp in set {
+            let rule = if seen.contains(&cp) { listed } else { unlisted };
+            derived.assign(rule, cp, name);
+        }
+    }
+    Ok(derived)
+}
+
+/// Return the sorted names of the scripts in Scripts.txt.
+///
+/// The position of each name is the index used for that script by both
+/// `script --enum` and `script-extension --bitset-enum`.
+fn script_names(scripts: &[Script]) -> Vec<String> {
+    let names: BTreeSet<&str> =
+        scripts.iter().map(|x| x.script.as_str()).collect();
+    names.into_iter().map(String::from).collect()
 }
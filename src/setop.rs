@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+
+use ucd_parse::{Codepoint, CodepointRange, Script, ScriptExtension};
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util::{self, PropertyValues};
+
+/// An operation that combines two sets of codepoints.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SetOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl SetOp {
+    /// Combine the given sets according to this operation.
+    pub fn apply(self, a: &BTreeSet<u32>, b: &BTreeSet<u32>) -> BTreeSet<u32> {
+        match self {
+            SetOp::Union => a.union(b).cloned().collect(),
+            SetOp::Intersection => a.intersection(b).cloned().collect(),
+            SetOp::Difference => a.difference(b).cloned().collect(),
+        }
+    }
+}
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let mut resolver = Resolver::new(dir)?;
+
+    // Each operation is applied in the order it was given on the command
+    // line, so use each value's index to interleave the different flags.
+    let mut ops = vec![];
+    for &(flag, op) in &[
+        ("union", SetOp::Union),
+        ("intersect", SetOp::Intersection),
+        ("difference", SetOp::Difference),
+    ] {
+        if let (Some(indices), Some(specs)) =
+            (args.indices_of(flag), args.values_of(flag))
+        {
+            ops.extend(indices.zip(specs).map(|(i, spec)| (i, op, spec)));
+        }
+    }
+    ops.sort_by_key(|&(i, _, _)| i);
+
+    let mut set = resolver.resolve(args.value_of("spec").unwrap())?;
+    for (_, op, spec) in ops {
+        set = op.apply(&set, &resolver.resolve(spec)?);
+    }
+
+    let mut wtr = args.writer("setop")?;
+    wtr.ranges(args.name(), &set)?;
+    wtr.finish()?;
+    Ok(())
+}
+
+/// Resolves property specs, such as `gc=Letter`, `Greek` or `Alphabetic`, to
+/// sets of codepoints.
+///
+/// The tables for each kind of property are only parsed once they're needed.
+struct Resolver<'a> {
+    dir: &'a OsStr,
+    propvals: PropertyValues,
+    general_category: Option<BTreeMap<String, BTreeSet<u32>>>,
+    script: Option<BTreeMap<String, BTreeSet<u32>>>,
+    script_extensions: Option<BTreeMap<String, BTreeSet<u32>>>,
+    bool_properties: Option<BTreeMap<String, BTreeSet<u32>>>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(dir: &'a OsStr) -> Result<Resolver<'a>> {
+        Ok(Resolver {
+            dir,
+            propvals: PropertyValues::from_ucd_dir(dir)?,
+            general_category: None,
+            script: None,
+            script_extensions: None,
+            bool_properties: None,
+        })
+    }
+
+    /// Return the set of codepoints matched by the given spec.
+    ///
+    /// A spec is either `property=value` (or `property:value`) or a bare
+    /// name. A bare name is tried as a General_Category value, then a Script
+    /// value and then a boolean property, in that order. `Any`, `ASCII` and
+    /// `Assigned` are also recognized, as in UTS#18.
+    fn resolve(&mut self, spec: &str) -> Result<BTreeSet<u32>> {
+        if let Some((prop, value)) =
+            spec.split_once('=').or_else(|| spec.split_once(':'))
+        {
+            return self.resolve_property(prop.trim(), value.trim());
+        }
+        let mut name = spec.to_string();
+        ucd_util::symbolic_name_normalize(&mut name);
+        match &*name {
+            "any" => return Ok(any()),
+            "ascii" => return Ok((0..=0x7F).collect()),
+            "assigned" => {
+                let unassigned = self.resolve_property("gc", "Cn")?;
+                return Ok(SetOp::Difference.apply(&any(), &unassigned));
+            }
+            _ => {}
+        }
+        for prop in &["General_Category", "Script"] {
+            if self.propvals.canonical(prop, spec).is_ok() {
+                return self.resolve_property(prop, spec);
+            }
+        }
+        if let Ok(prop) = self.propvals.property.canonical(spec) {
+            if self.bool_properties()?.contains_key(&prop) {
+                return self.resolve_property(&prop, "Yes");
+            }
+        }
+        err!("unrecognized property spec: {:?}", spec)
+    }
+
+    fn resolve_property(
+        &mut self,
+        prop: &str,
+        value: &str,
+    ) -> Result<BTreeSet<u32>> {
+        let prop = self.propvals.property.canonical(prop)?;
+        if !matches!(
+            &*prop,
+            "General_Category" | "Script" | "Script_Extensions"
+        ) {
+            return self.resolve_bool(&prop, value);
+        }
+        let value = self.propvals.canonical(&prop, value)?;
+        let sets = match &*prop {
+            "General_Category" => self.general_category()?,
+            "Script" => self.script()?,
+            "Script_Extensions" => self.script_extensions()?,
+            _ => unreachable!(),
+        };
+        // A value may be valid without any codepoints having it, e.g., a
+        // script with no characters in this version of Unicode.
+        Ok(sets.get(&value).cloned().unwrap_or_default())
+    }
+
+    fn resolve_bool(
+        &mut self,
+        prop: &str,
+        value: &str,
+    ) -> Result<BTreeSet<u32>> {
+        let set = match self.bool_properties()?.get(prop) {
+            Some(set) => set.clone(),
+            None => {
+                return err!(
+                    "unsupported property {:?}: only General_Category, \
+                     Script, Script_Extensions and boolean properties are \
+                     supported",
+                    prop
+                )
+            }
+        };
+        let mut value = value.to_string();
+        ucd_util::symbolic_name_normalize(&mut value);
+        match &*value {
+            "y" | "yes" | "t" | "true" => Ok(set),
+            "n" | "no" | "f" | "false" => {
+                Ok(SetOp::Difference.apply(&any(), &set))
+            }
+            _ => err!(
+                "invalid value {:?} for boolean property {:?}",
+                value,
+                prop
+            ),
+        }
+    }
+
+    fn general_category(
+        &mut self,
+    ) -> Result<&BTreeMap<String, BTreeSet<u32>>> {
+        if self.general_category.is_none() {
+            let unexpanded = util::parse(self.dir)?;
+            let mut bycat = crate::general_category::expand_into_categories(
+                unexpanded,
+                &self.propvals,
+            )?;
+            let related =
+                crate::general_category::related(&self.propvals, &bycat);
+            bycat.extend(related);
+            self.general_category = Some(bycat);
+        }
+        Ok(self.general_category.as_ref().unwrap())
+    }
+
+    fn script(&mut self) -> Result<&BTreeMap<String, BTreeSet<u32>>> {
+        if self.script.is_none() {
+            let scripts: Vec<Script> = util::parse(self.dir)?;
+            let mut by_name: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
+            for x in &scripts {
+                by_name
+                    .entry(x.script.clone())
+                    .or_default()
+                    .extend(x.codepoints.into_iter().map(|c| c.value()));
+            }
+            self.script = Some(by_name);
+        }
+        Ok(self.script.as_ref().unwrap())
+    }
+
+    fn script_extensions(
+        &mut self,
+    ) -> Result<&BTreeMap<String, BTreeSet<u32>>> {
+        if self.script_extensions.is_none() {
+            let scripts: Vec<Script> = util::parse(self.dir)?;
+            let exts: Vec<ScriptExtension> = util::parse(self.dir)?;
+            self.script_extensions =
+                Some(crate::script::script_extension_sets(
+                    &self.propvals,
+                    &scripts,
+                    &exts,
+                )?);
+        }
+        Ok(self.script_extensions.as_ref().unwrap())
+    }
+
+    fn bool_properties(&mut self) -> Result<&BTreeMap<String, BTreeSet<u32>>> {
+        if self.bool_properties.is_none() {
+            self.bool_properties =
+                Some(crate::property_bool::parse_properties(self.dir)?);
+        }
+        Ok(self.bool_properties.as_ref().unwrap())
+    }
+}
+
+/// Return the set of every codepoint.
+fn any() -> BTreeSet<u32> {
+    CodepointRange::all().into_iter().map(Codepoint::value).collect()
+}
@@ -0,0 +1,92 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ucd_parse::{
+    Codepoint, CodepointRange, EastAsianWidth, EmojiProperty, Missing,
+    UnicodeData, UnicodeDataExpander,
+};
+
+use crate::args::ArgMatches;
+use crate::error::Result;
+use crate::util;
+
+/// The medial vowel (Jungseong) and final consonant (Jongseong) Hangul jamo.
+/// These combine with a preceding leading consonant into a single syllable
+/// block, and therefore take up no columns of their own.
+const HANGUL_VT: &[(u32, u32)] = &[(0x1160, 0x11FF), (0xD7B0, 0xD7FF)];
+
+pub fn command(args: ArgMatches<'_>) -> Result<()> {
+    let dir = args.ucd_dir()?;
+    let unexpanded: Vec<UnicodeData> = util::parse(dir)?;
+    let eaw: Vec<EastAsianWidth> = util::parse(dir)?;
+    let missing: Vec<Missing> =
+        ucd_parse::parse_missing::<_, EastAsianWidth>(dir)?;
+    let emoji: Vec<EmojiProperty> = util::parse(dir)?;
+
+    let mut zero = BTreeSet::new();
+    for row in UnicodeDataExpander::new(unexpanded) {
+        let gc = row.general_category.as_str();
+        // SOFT HYPHEN is Cf, but terminals conventionally render it.
+        if matches!(gc, "Cc" | "Mn" | "Me" | "Cf")
+            && row.codepoint.value() != 0xAD
+        {
+            zero.insert(row.codepoint.value());
+        }
+    }
+    for &(start, end) in HANGUL_VT {
+        zero.extend(start..=end);
+    }
+
+    let ambiguous_wide = args.is_present("ambiguous-wide");
+    let is_wide = |width: &str| match width {
+        "W" | "F" => true,
+        "A" => ambiguous_wide,
+        _ => false,
+    };
+    // Codepoints not listed in EastAsianWidth.txt get the width of the last
+    // @missing line that covers them, e.g., W for reserved CJK codepoints,
+    // so these are applied in order before the rows that are listed.
+    let mut wide = BTreeSet::new();
+    for m in &missing {
+        let range = m.codepoints.range();
+        let cps = range.start.value()..=range.end.value();
+        if is_wide(&m.value) {
+            wide.extend(cps);
+        } else {
+            for cp in cps {
+                wide.remove(&cp);
+            }
+        }
+    }
+    for row in &eaw {
+        let cps = row.codepoints.into_iter().map(|c| c.value());
+        if is_wide(&row.width) {
+            wide.extend(cps);
+        } else {
+            for cp in cps {
+                wide.remove(&cp);
+            }
+        }
+    }
+    for row in &emoji {
+        if row.property == "Emoji_Presentation" {
+            wide.extend(row.codepoints.into_iter().map(|c| c.value()));
+        }
+    }
+
+    let mut widths = BTreeMap::new();
+    for cp in CodepointRange::all().into_iter().map(Codepoint::value) {
+        let width = if zero.contains(&cp) {
+            0
+        } else if wide.contains(&cp) {
+            2
+        } else {
+            1
+        };
+        widths.insert(cp, width);
+    }
+
+    let mut wtr = args.writer("terminal_width")?;
+    wtr.ranges_to_unsigned_integer(args.name(), &widths)?;
+    wtr.finish()?;
+    Ok(())
+}
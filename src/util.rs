@@ -1,18 +1,211 @@
+use std::cell::{Cell, RefCell};
 use std::collections::{BTreeMap, BTreeSet};
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::str;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Instant;
 
-use ucd_parse::{PropertyAlias, PropertyValueAlias};
+#[cfg(feature = "xml")]
+use ucd_parse::UcdXml;
+use ucd_parse::{
+    Codepoint, PropertyAlias, PropertyValueAlias, UcdFile, UcdFileByCodepoint,
+};
 use ucd_util;
 
 use crate::error::Result;
 
+/// Whether progress should be reported on stderr, as requested by
+/// --verbose. See the `log!` macro.
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// The property values parsed so far, by UCD directory. Nearly every
+    /// command needs them, often more than once, so they are only parsed
+    /// once for each directory. See `PropertyValues::cached`.
+    static PROPERTY_VALUES: RefCell<BTreeMap<PathBuf, Rc<PropertyValues>>> =
+        const { RefCell::new(BTreeMap::new()) };
+
+    /// The UCD XML read so far, by UCD directory. See `xml`.
+    #[cfg(feature = "xml")]
+    static UCD_XML: RefCell<BTreeMap<PathBuf, Rc<UcdXml>>> =
+        const { RefCell::new(BTreeMap::new()) };
+
+    /// Whether a writer has compared its output with existing files for
+    /// --check since `take_checked` was last called.
+    static CHECKED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Record that a writer compared its output with existing files.
+pub fn set_checked() {
+    CHECKED.with(|c| c.set(true));
+}
+
+/// Return whether a writer compared its output with existing files since
+/// this was last called.
+pub fn take_checked() -> bool {
+    CHECKED.with(|c| c.replace(false))
+}
+
+/// Enable or disable progress reporting.
+pub fn set_verbose(yes: bool) {
+    VERBOSE.store(yes, Ordering::Relaxed);
+}
+
+/// Returns true if progress should be reported.
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Like `ucd_parse::parse`, but reports how long parsing took when
+/// --verbose is given.
+pub fn parse<P: AsRef<Path>, D: UcdFile>(ucd_dir: P) -> Result<Vec<D>> {
+    let start = Instant::now();
+    let rows: Vec<D> = match xml_rows(ucd_dir.as_ref())? {
+        Some(rows) => rows,
+        None => ucd_parse::parse(&ucd_dir)?,
+    };
+    log_parse::<D>(ucd_dir.as_ref(), rows.len(), start);
+    Ok(rows)
+}
+
+/// Like `ucd_parse::parse_by_codepoint`, but reports how long parsing took
+/// when --verbose is given.
+pub fn parse_by_codepoint<P, D>(ucd_dir: P) -> Result<BTreeMap<Codepoint, D>>
+where
+    P: AsRef<Path>,
+    D: UcdFileByCodepoint,
+{
+    let start = Instant::now();
+    let map = match xml_rows::<D>(ucd_dir.as_ref())? {
+        Some(rows) => {
+            let mut map = BTreeMap::new();
+            for row in rows {
+                for cp in row.codepoints() {
+                    map.insert(cp, row.clone());
+                }
+            }
+            map
+        }
+        None => ucd_parse::parse_by_codepoint(&ucd_dir)?,
+    };
+    log_parse::<D>(ucd_dir.as_ref(), map.len(), start);
+    Ok(map)
+}
+
+/// Like `ucd_parse::parse_many_by_codepoint`, but reports how long parsing
+/// took when --verbose is given.
+pub fn parse_many_by_codepoint<P, D>(
+    ucd_dir: P,
+) -> Result<BTreeMap<Codepoint, Vec<D>>>
+where
+    P: AsRef<Path>,
+    D: UcdFileByCodepoint,
+{
+    let start = Instant::now();
+    let map = match xml_rows::<D>(ucd_dir.as_ref())? {
+        Some(rows) => {
+            let mut map: BTreeMap<Codepoint, Vec<D>> = BTreeMap::new();
+            for row in rows {
+                for cp in row.codepoints() {
+                    map.entry(cp).or_default().push(row.clone());
+                }
+            }
+            map
+        }
+        None => ucd_parse::parse_many_by_codepoint(&ucd_dir)?,
+    };
+    log_parse::<D>(ucd_dir.as_ref(), map.len(), start);
+    Ok(map)
+}
+
+/// Return the version of the UCD in the given directory.
+///
+/// Like `ucd_parse::ucd_directory_version`, but when the directory has no
+/// PropList.txt, the version is read from its XML, if it has one.
+pub fn ucd_version<P: AsRef<Path>>(ucd_dir: P) -> Result<(u64, u64, u64)> {
+    let ucd_dir = ucd_dir.as_ref();
+    match ucd_parse::ucd_directory_version(ucd_dir) {
+        Ok(version) => Ok(version),
+        Err(err) => match xml_version(ucd_dir)? {
+            Some(version) => Ok(version),
+            None => Err(err.into()),
+        },
+    }
+}
+
+/// Return the UCD XML in the given directory, if it has one. It is only
+/// read once for each directory.
+#[cfg(feature = "xml")]
+fn xml(ucd_dir: &Path) -> Result<Option<Rc<UcdXml>>> {
+    if UcdXml::find(ucd_dir).is_none() {
+        return Ok(None);
+    }
+    if let Some(xml) = UCD_XML.with(|c| c.borrow().get(ucd_dir).cloned()) {
+        return Ok(Some(xml));
+    }
+    let start = Instant::now();
+    let xml = Rc::new(UcdXml::from_dir(ucd_dir)?);
+    log!(
+        "read the UCD XML in {} in {:.2?}",
+        ucd_dir.display(),
+        start.elapsed()
+    );
+    UCD_XML
+        .with(|c| c.borrow_mut().insert(ucd_dir.to_path_buf(), xml.clone()));
+    Ok(Some(xml))
+}
+
+#[cfg(feature = "xml")]
+fn xml_version(ucd_dir: &Path) -> Result<Option<(u64, u64, u64)>> {
+    Ok(xml(ucd_dir)?.and_then(|xml| xml.version()))
+}
+
+#[cfg(not(feature = "xml"))]
+fn xml_version(_: &Path) -> Result<Option<(u64, u64, u64)>> {
+    Ok(None)
+}
+
+/// Return the rows of the given UCD file as read from the directory's XML,
+/// but only when the file itself is missing and can be read from the XML.
+#[cfg(feature = "xml")]
+fn xml_rows<D: UcdFile>(ucd_dir: &Path) -> Result<Option<Vec<D>>> {
+    if D::file_path(ucd_dir).exists()
+        || !UcdXml::supports(D::relative_file_path())
+    {
+        return Ok(None);
+    }
+    match xml(ucd_dir)? {
+        Some(xml) => Ok(Some(xml.parse()?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(not(feature = "xml"))]
+fn xml_rows<D: UcdFile>(_: &Path) -> Result<Option<Vec<D>>> {
+    Ok(None)
+}
+
+fn log_parse<D: UcdFile>(ucd_dir: &Path, len: usize, start: Instant) {
+    log!(
+        "parsed {} ({} entries) in {:.2?}",
+        D::file_path(ucd_dir).display(),
+        len,
+        start.elapsed()
+    );
+}
+
 /// Filter is an include/exclude filter of strings specified on the command
 /// line via --include and --exclude flags.
 #[derive(Clone, Debug)]
 pub struct Filter {
     include: BTreeSet<String>,
     exclude: BTreeSet<String>,
+    /// The glob patterns given to --include and --exclude, each along with
+    /// its normalized form produced by `glob_normalize`.
+    include_globs: Vec<(String, String)>,
+    exclude_globs: Vec<(String, String)>,
 }
 
 impl Filter {
@@ -21,35 +214,121 @@ impl Filter {
     /// Each list should be a comma separated list of property names (or
     /// values), and they may be aliases. The canonicalization function given
     /// should convert all such values into their canonical form.
+    ///
+    /// Names containing `*` or `?` are instead glob patterns, which are
+    /// matched against canonical names loosely, ignoring case, spaces,
+    /// hyphens and underscores. For example, `Changes_When_*` matches every
+    /// `Changes_When_...` property.
     pub fn new<F: FnMut(&str) -> Result<String>>(
         include_list: Option<String>,
         exclude_list: Option<String>,
         mut canonicalize: F,
     ) -> Result<Filter> {
-        let (mut include, mut exclude) = (BTreeSet::new(), BTreeSet::new());
-        if let Some(include_list) = include_list {
-            for name in include_list.split(",") {
-                include.insert(canonicalize(name.trim())?.to_string());
+        let mut filter = Filter {
+            include: BTreeSet::new(),
+            exclude: BTreeSet::new(),
+            include_globs: vec![],
+            exclude_globs: vec![],
+        };
+        let lists = [
+            (include_list, &mut filter.include, &mut filter.include_globs),
+            (exclude_list, &mut filter.exclude, &mut filter.exclude_globs),
+        ];
+        for (list, names, globs) in lists {
+            for name in list.iter().flat_map(|list| list.split(',')) {
+                let name = name.trim();
+                if name.contains(['*', '?']) {
+                    globs.push((name.to_string(), glob_normalize(name)));
+                } else {
+                    names.insert(canonicalize(name)?.to_string());
+                }
             }
         }
-        if let Some(exclude_list) = exclude_list {
-            for name in exclude_list.split(",") {
-                exclude.insert(canonicalize(name.trim())?.to_string());
+        Ok(filter)
+    }
+
+    /// Return an error if any name given to --include is not one of the
+    /// given names, which should be every name that could have been emitted.
+    ///
+    /// This catches values that are valid aliases, but that have no
+    /// codepoints or that don't apply to the table being generated.
+    pub fn check_included<I: IntoIterator<Item = T>, T: AsRef<str>>(
+        &self,
+        names: I,
+    ) -> Result<()> {
+        let names: Vec<String> =
+            names.into_iter().map(|n| n.as_ref().to_string()).collect();
+        for name in &self.include {
+            if !names.contains(name) {
+                return err!(
+                    "--include value {:?} did not match anything{}",
+                    name,
+                    did_you_mean(name, names.iter().map(|n| (&**n, &**n))),
+                );
             }
         }
-        Ok(Filter { include, exclude })
+        for (glob, normalized) in &self.include_globs {
+            if !names.iter().any(|name| glob_matches(normalized, name)) {
+                return err!(
+                    "--include pattern {:?} did not match anything",
+                    glob
+                );
+            }
+        }
+        Ok(())
     }
 
     /// Whether the given name passes this filter or not.
     pub fn contains(&self, name: &str) -> bool {
-        if self.exclude.contains(name) {
+        let matches = |globs: &[(String, String)]| {
+            globs.iter().any(|(_, glob)| glob_matches(glob, name))
+        };
+        if self.exclude.contains(name) || matches(&self.exclude_globs) {
             false
+        } else if self.include.is_empty() && self.include_globs.is_empty() {
+            true
         } else {
-            self.include.is_empty() || self.include.contains(name)
+            self.include.contains(name) || matches(&self.include_globs)
         }
     }
 }
 
+/// Normalize a name for matching against a glob pattern, by lowercasing it
+/// and removing spaces, hyphens and underscores.
+fn glob_normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Returns true if the given name matches the given normalized glob, where
+/// `*` matches any number of characters and `?` matches exactly one.
+fn glob_matches(glob: &str, name: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let name: Vec<char> = glob_normalize(name).chars().collect();
+    // The position just after the last `*`, along with the position in the
+    // name it has matched up to, for backtracking.
+    let mut star: Option<(usize, usize)> = None;
+    let (mut g, mut n) = (0, 0);
+    while n < name.len() {
+        if g < glob.len() && (glob[g] == '?' || glob[g] == name[n]) {
+            g += 1;
+            n += 1;
+        } else if g < glob.len() && glob[g] == '*' {
+            g += 1;
+            star = Some((g, n));
+        } else if let Some((star_g, star_n)) = star {
+            g = star_g;
+            n = star_n + 1;
+            star = Some((star_g, n));
+        } else {
+            return false;
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
+
 /// A map from property name (including aliases) to a "canonical" or "long"
 /// version of the property name.
 ///
@@ -58,9 +337,19 @@ impl Filter {
 pub struct PropertyNames(pub BTreeMap<String, String>);
 
 impl PropertyNames {
+    /// Read the property names in the given UCD directory. If the property
+    /// values of the directory have already been read, then the names read
+    /// along with them are reused.
     pub fn from_ucd_dir<P: AsRef<Path>>(ucd_dir: P) -> Result<PropertyNames> {
         use ucd_parse::UcdFile;
 
+        let cached = PROPERTY_VALUES.with(|cache| {
+            cache.borrow().get(ucd_dir.as_ref()).map(|v| v.property.clone())
+        });
+        if let Some(names) = cached {
+            return Ok(names);
+        }
+
         let make_key = |mut value| {
             ucd_util::symbolic_name_normalize(&mut value);
             value
@@ -102,7 +391,11 @@ impl PropertyNames {
         ucd_util::symbolic_name_normalize(&mut key);
         match self.0.get(&key).map(|v| &**v) {
             Some(v) => Ok(v.to_string()),
-            None => err!("unrecognized property: {:?}", key),
+            None => err!(
+                "unrecognized property: {:?}{}",
+                key,
+                did_you_mean(&key, self.0.iter().map(|(k, v)| (&**k, &**v))),
+            ),
         }
     }
 }
@@ -115,14 +408,42 @@ impl PropertyNames {
 pub struct PropertyValues {
     pub property: PropertyNames,
     pub value: BTreeMap<String, BTreeMap<String, String>>,
+    /// A map from (property name, canonical property value) to the
+    /// abbreviated property value. Neither key is normalized.
+    pub abbreviation: BTreeMap<String, BTreeMap<String, String>>,
 }
 
 impl PropertyValues {
+    /// Read the property values in the given UCD directory.
+    ///
+    /// This is a copy of the values returned by `cached`, so the directory
+    /// is only parsed the first time.
     pub fn from_ucd_dir<P: AsRef<Path>>(ucd_dir: P) -> Result<PropertyValues> {
+        Ok((*PropertyValues::cached(ucd_dir)?).clone())
+    }
+
+    /// Return the property values in the given UCD directory, parsing them
+    /// only if they haven't been parsed already.
+    pub fn cached<P: AsRef<Path>>(ucd_dir: P) -> Result<Rc<PropertyValues>> {
+        let dir = ucd_dir.as_ref();
+        let cached =
+            PROPERTY_VALUES.with(|cache| cache.borrow().get(dir).cloned());
+        if let Some(propvals) = cached {
+            return Ok(propvals);
+        }
+        let propvals = Rc::new(PropertyValues::parse(dir)?);
+        PROPERTY_VALUES.with(|cache| {
+            cache.borrow_mut().insert(dir.to_path_buf(), propvals.clone())
+        });
+        Ok(propvals)
+    }
+
+    fn parse(ucd_dir: &Path) -> Result<PropertyValues> {
         use ucd_parse::UcdFile;
 
-        let props = PropertyNames::from_ucd_dir(&ucd_dir)?;
+        let props = PropertyNames::from_ucd_dir(ucd_dir)?;
         let mut outer_map = BTreeMap::new();
+        let mut abbrev_map = BTreeMap::new();
         for result in PropertyValueAlias::from_dir(ucd_dir)? {
             let a = result?;
             let prop = props.canonical(&a.property)?.to_string();
@@ -132,6 +453,10 @@ impl PropertyValues {
                 value
             };
 
+            abbrev_map
+                .entry(prop.clone())
+                .or_insert(BTreeMap::new())
+                .insert(canon.clone(), a.abbreviation.clone());
             let inner_map = outer_map.entry(prop).or_insert(BTreeMap::new());
             if let Some(n) = a.numeric {
                 inner_map.insert(make_key(n.to_string()), canon.clone());
@@ -147,7 +472,35 @@ impl PropertyValues {
         // but alas...
         let scripts = outer_map["Script"].clone();
         outer_map.insert("Script_Extensions".to_string(), scripts);
-        Ok(PropertyValues { property: props, value: outer_map })
+        let scripts = abbrev_map["Script"].clone();
+        abbrev_map.insert("Script_Extensions".to_string(), scripts);
+        Ok(PropertyValues {
+            property: props,
+            value: outer_map,
+            abbreviation: abbrev_map,
+        })
+    }
+
+    /// Iterate over the canonical names of every property that has values,
+    /// in sorted order.
+    pub fn properties(&self) -> impl Iterator<Item = &str> + '_ {
+        self.value.keys().map(|property| &**property)
+    }
+
+    /// Return every canonical value of the given property, in sorted order,
+    /// along with each of the aliases it may be given (including itself).
+    /// The aliases are normalized according to UAX44-LM3 and sorted. If no
+    /// such property exists, then return an error.
+    pub fn aliases<'a>(
+        &'a self,
+        property: &str,
+    ) -> Result<Vec<(&'a str, Vec<&'a str>)>> {
+        let mut by_canonical: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        // Aliases are visited in sorted order, so each list is sorted too.
+        for (alias, canonical) in self.values(property)? {
+            by_canonical.entry(&**canonical).or_default().push(&**alias);
+        }
+        Ok(by_canonical.into_iter().collect())
     }
 
     /// Return a map from property value (including aliases) to canonical
@@ -174,16 +527,106 @@ impl PropertyValues {
         let property = self.property.canonical(property)?;
         let mut value = value.to_string();
         ucd_util::symbolic_name_normalize(&mut value);
-        match self.value.get(&*property).and_then(|m| m.get(&value)) {
+        let values = self.value.get(&*property);
+        match values.and_then(|m| m.get(&value)) {
+            Some(v) => Ok(v.to_string()),
+            None => {
+                let aliases = values
+                    .into_iter()
+                    .flat_map(|m| m.iter().map(|(k, v)| (&**k, &**v)));
+                err!(
+                    "unrecognized property name/value: {:?}{}",
+                    (property, &value),
+                    did_you_mean(&value, aliases),
+                )
+            }
+        }
+    }
+
+    /// Return the abbreviated property value for the given property value
+    /// (or any of its aliases) for a specific property. If no such property
+    /// exists or if no such property value exists, then return an error.
+    pub fn abbreviation(&self, property: &str, value: &str) -> Result<String> {
+        let property = self.property.canonical(property)?;
+        let canon = self.canonical(&property, value)?;
+        match self.abbreviation.get(&*property).and_then(|m| m.get(&canon)) {
             Some(v) => Ok(v.to_string()),
             None => err!(
-                "unrecognized property name/value: {:?}",
-                (property, value)
+                "no abbreviation for property name/value: {:?}",
+                (property, canon)
             ),
         }
     }
 }
 
+/// The names of characters, used to annotate generated tables.
+///
+/// Names come from DerivedName.txt when it is available, which includes
+/// algorithmically derived names such as `CJK UNIFIED IDEOGRAPH-4E00`.
+/// Otherwise, they come from UnicodeData.txt, which lacks those.
+#[derive(Clone, Debug, Default)]
+pub struct CharNames {
+    names: BTreeMap<u32, String>,
+    /// The (start, end, prefix) triples of algorithmically named ranges,
+    /// where the name of a codepoint is the prefix followed by its hex
+    /// value.
+    patterns: Vec<(u32, u32, String)>,
+}
+
+impl CharNames {
+    pub fn from_ucd_dir<P: AsRef<Path>>(ucd_dir: P) -> Result<CharNames> {
+        use ucd_parse::{extracted::DerivedName, Codepoints, UnicodeData};
+
+        let ucd_dir = ucd_dir.as_ref();
+        let mut names = CharNames::default();
+        if !DerivedName::file_path(ucd_dir).exists() {
+            for row in parse::<_, UnicodeData>(ucd_dir)? {
+                if !row.name.starts_with('<') {
+                    names.names.insert(row.codepoint.value(), row.name);
+                }
+            }
+            return Ok(names);
+        }
+        for row in parse::<_, DerivedName>(ucd_dir)? {
+            match (row.codepoints, row.name.strip_suffix('*')) {
+                (Codepoints::Single(cp), None) => {
+                    names.names.insert(cp.value(), row.name);
+                }
+                (Codepoints::Single(cp), Some(prefix)) => {
+                    let cp = cp.value();
+                    names.patterns.push((cp, cp, prefix.to_string()));
+                }
+                (Codepoints::Range(r), prefix) => {
+                    let prefix = prefix.unwrap_or(&row.name).to_string();
+                    names.patterns.push((
+                        r.start.value(),
+                        r.end.value(),
+                        prefix,
+                    ));
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Return the name of the given codepoint, if it has one.
+    pub fn get(&self, cp: u32) -> Option<String> {
+        if let Some(name) = self.names.get(&cp) {
+            return Some(name.clone());
+        }
+        self.patterns
+            .iter()
+            .find(|&&(start, end, _)| start <= cp && cp <= end)
+            .map(|(_, _, prefix)| format!("{}{:04X}", prefix, cp))
+    }
+}
+
+impl From<BTreeMap<u32, String>> for CharNames {
+    fn from(names: BTreeMap<u32, String>) -> CharNames {
+        CharNames { names, patterns: vec![] }
+    }
+}
+
 /// Convert an iterator of codepoints into a vec of sorted ranges.
 pub fn to_ranges<I: IntoIterator<Item = u32>>(it: I) -> Vec<(u32, u32)> {
     let mut codepoints: Vec<u32> = it.into_iter().collect();
@@ -257,26 +700,433 @@ pub fn range_value_add<V: Eq>(
     ranges.push((codepoint, codepoint, value));
 }
 
+/// Return the `(abbreviation, long)` pair of each of the given values of a
+/// specific property, in order.
+///
+/// This is used to emit enum tables with both short and long names. The
+/// value named `other`, if given, is not a real property value (see
+/// `filter_enum`), and is used as both its abbreviation and long name.
+pub fn enum_aliases<I: IntoIterator<Item = T>, T: AsRef<str>>(
+    propvals: &PropertyValues,
+    property: &str,
+    values: I,
+    other: Option<&str>,
+) -> Result<Vec<(String, String)>> {
+    let mut aliases = vec![];
+    for value in values {
+        let value = value.as_ref();
+        if Some(value) == other {
+            aliases.push((value.to_string(), value.to_string()));
+            continue;
+        }
+        aliases.push((
+            propvals.abbreviation(property, value)?,
+            propvals.canonical(property, value)?,
+        ));
+    }
+    Ok(aliases)
+}
+
+/// Apply an include/exclude filter to the values of an enumeration.
+///
+/// Values rejected by the filter are removed. If `other` is given, then the
+/// codepoints of all rejected values are instead grouped under a single value
+/// with that name, so that an enum table still covers every codepoint. It is
+/// an error for `other` to name one of the values that is kept.
+pub fn filter_enum(
+    enum_map: BTreeMap<String, BTreeSet<u32>>,
+    filter: &Filter,
+    other: Option<&str>,
+) -> Result<BTreeMap<String, BTreeSet<u32>>> {
+    let mut kept = BTreeMap::new();
+    let mut rejected = BTreeSet::new();
+    for (name, set) in enum_map {
+        if filter.contains(&name) {
+            kept.insert(name, set);
+        } else {
+            rejected.extend(set);
+        }
+    }
+    if let Some(other) = other {
+        if kept.contains_key(other) {
+            return err!(
+                "cannot group excluded values under '{}', since it is \
+                 already an included value",
+                other
+            );
+        }
+        if !rejected.is_empty() {
+            kept.insert(other.to_string(), rejected);
+        }
+    }
+    Ok(kept)
+}
+
+/// Return a "did you mean" suggestion listing the names that are close to
+/// `key`, or an empty string if there are none.
+///
+/// Each candidate is a pair of a key to compare against (e.g., a normalized
+/// alias) and the name to suggest for it (e.g., its canonical name).
+fn did_you_mean<'a, I: IntoIterator<Item = (&'a str, &'a str)>>(
+    key: &str,
+    candidates: I,
+) -> String {
+    let mut normalized = key.to_string();
+    ucd_util::symbolic_name_normalize(&mut normalized);
+    let max = std::cmp::max(1, normalized.chars().count() / 3);
+    let mut close: Vec<(usize, &str)> = vec![];
+    for (candidate, name) in candidates {
+        let mut candidate = candidate.to_string();
+        ucd_util::symbolic_name_normalize(&mut candidate);
+        let dist = edit_distance(&normalized, &candidate);
+        if dist <= max && !close.iter().any(|&(_, n)| n == name) {
+            close.push((dist, name));
+        }
+    }
+    if close.is_empty() {
+        return String::new();
+    }
+    close.sort();
+    let names: Vec<&str> = close.iter().take(5).map(|&(_, n)| n).collect();
+    format!(" (did you mean {}?)", names.join(", "))
+}
+
+/// Return the Levenshtein distance between the given strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let sub = prev[j] + if ca == cb { 0 } else { 1 };
+            cur.push(sub.min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// Return the given string as a JSON string literal.
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => {
+                out.push_str(&format!("\\u{:04x}", ch as u32))
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
 /// Print the property values (and their aliases) for the given property.
 pub fn print_property_values(
     propvals: &PropertyValues,
     property: &str,
 ) -> Result<()> {
-    let by_alias = propvals.values(property)?;
-    // We basically need to reverse the mapping. Namely, we're given
-    //
-    //     alias |--> canonical
-    //
-    // But we want
-    //
-    //     canonical |--> [alias]
-    let mut by_canonical: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
-    for (alias, canonical) in by_alias {
-        by_canonical.entry(&**canonical).or_insert(vec![]).push(&**alias);
-    }
-    for (canonical, mut aliases) in by_canonical {
-        aliases.sort();
+    for (canonical, aliases) in propvals.aliases(property)? {
         println!("{} (aliases: {})", canonical, aliases.join(", "));
     }
     Ok(())
 }
+
+/// Cross-check a derived property against one of the files in the
+/// `extracted` directory of the UCD.
+///
+/// `by_name` is the map from property value to codepoints that a command
+/// computed on its own (typically from `UnicodeData.txt` plus a set of
+/// default rules). `value` maps each row of the extracted file to a property
+/// value named in the same way as the keys of `by_name`. Only codepoints
+/// explicitly listed in the extracted file are compared.
+///
+/// If the extracted file doesn't exist, then a warning is printed and no
+/// check is performed. If any codepoint disagrees, then an error is returned
+/// listing the first few mismatches.
+pub fn validate_against_derived<P, D, F>(
+    ucd_dir: P,
+    by_name: &BTreeMap<String, BTreeSet<u32>>,
+    mut value: F,
+) -> Result<()>
+where
+    P: AsRef<Path>,
+    D: ucd_parse::UcdFileByCodepoint,
+    F: FnMut(&D) -> Result<String>,
+{
+    const MAX_REPORTED: usize = 10;
+
+    let path = D::relative_file_path();
+    let rows: Vec<D> = match ucd_parse::parse(ucd_dir) {
+        Ok(rows) => rows,
+        Err(err) => match *err.kind() {
+            ucd_parse::ErrorKind::Io(_) => {
+                eprintln!(
+                    "{}. skipping validation against {}.",
+                    err,
+                    path.display(),
+                );
+                return Ok(());
+            }
+            _ => return Err(From::from(err)),
+        },
+    };
+    let mut generated: BTreeMap<u32, &str> = BTreeMap::new();
+    for (name, set) in by_name {
+        for &cp in set {
+            generated.insert(cp, name);
+        }
+    }
+    let mut mismatches = vec![];
+    let mut count = 0;
+    for row in &rows {
+        let expected = value(row)?;
+        for cp in row.codepoints() {
+            let got = generated.get(&cp.value()).copied().unwrap_or("<none>");
+            if got == expected {
+                continue;
+            }
+            count += 1;
+            if mismatches.len() < MAX_REPORTED {
+                mismatches.push(format!(
+                    "  U+{:04X}: generated {}, but {} says {}",
+                    cp.value(),
+                    got,
+                    path.display(),
+                    expected,
+                ));
+            }
+        }
+    }
+    if count > 0 {
+        return err!(
+            "found {} codepoints that disagree with {}:\n{}",
+            count,
+            path.display(),
+            mismatches.join("\n"),
+        );
+    }
+    Ok(())
+}
+
+/// A directory that is removed, along with its contents, when dropped.
+pub struct TempDir(pub PathBuf);
+
+impl TempDir {
+    /// Create the given directory, which must not already be in use, e.g.,
+    /// because its name includes the process ID.
+    pub fn new(path: PathBuf) -> Result<TempDir> {
+        fs::create_dir_all(&path)?;
+        Ok(TempDir(path))
+    }
+}
+
+/// Return a path in the system's temporary directory, starting with the
+/// given prefix, that no other call returns, even in another thread or
+/// process.
+pub fn temp_path(prefix: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "{}-{}-{}",
+        prefix,
+        std::process::id(),
+        n
+    ))
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A single step in turning one sequence of lines into another.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum DiffOp {
+    Keep,
+    Delete,
+    Insert,
+}
+
+/// The largest number of lines that may differ before `unified_diff` gives
+/// up on finding a minimal diff and replaces every line instead.
+const DIFF_MAX_EDITS: usize = 2000;
+
+/// Return a unified diff, with three lines of context, turning `old` into
+/// `new`. If either isn't valid UTF-8, then this is a one line message that
+/// the files differ.
+pub fn unified_diff(
+    old_name: &str,
+    new_name: &str,
+    old: &[u8],
+    new: &[u8],
+) -> String {
+    const CONTEXT: usize = 3;
+
+    let (old, new) = match (str::from_utf8(old), str::from_utf8(new)) {
+        (Ok(old), Ok(new)) => (old, new),
+        _ => {
+            return format!(
+                "Binary files {} and {} differ\n",
+                old_name, new_name
+            )
+        }
+    };
+    let a: Vec<&str> = old.split_inclusive('\n').collect();
+    let b: Vec<&str> = new.split_inclusive('\n').collect();
+    // Each op along with the index of the line it applies to in `a` and `b`.
+    let mut ops = vec![];
+    let (mut i, mut j) = (0, 0);
+    for op in diff_ops(&a, &b) {
+        ops.push((op, i, j));
+        match op {
+            DiffOp::Keep => {
+                i += 1;
+                j += 1;
+            }
+            DiffOp::Delete => i += 1,
+            DiffOp::Insert => j += 1,
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    let changes: Vec<usize> =
+        (0..ops.len()).filter(|&k| ops[k].0 != DiffOp::Keep).collect();
+    let mut k = 0;
+    while k < changes.len() {
+        // Extend the hunk while the next change is close enough that their
+        // context would overlap.
+        let mut last = k;
+        while last + 1 < changes.len()
+            && changes[last + 1] - changes[last] <= 2 * CONTEXT
+        {
+            last += 1;
+        }
+        let start = changes[k].saturating_sub(CONTEXT);
+        let end = (changes[last] + CONTEXT + 1).min(ops.len());
+        let hunk = &ops[start..end];
+        let old_len = hunk.iter().filter(|op| op.0 != DiffOp::Insert).count();
+        let new_len = hunk.iter().filter(|op| op.0 != DiffOp::Delete).count();
+        // An empty range is numbered by the line before it.
+        let first = |n: usize, len: usize| if len == 0 { n } else { n + 1 };
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            first(hunk[0].1, old_len),
+            old_len,
+            first(hunk[0].2, new_len),
+            new_len,
+        ));
+        for &(op, i, j) in hunk {
+            let (prefix, line) = match op {
+                DiffOp::Keep => (' ', a[i]),
+                DiffOp::Delete => ('-', a[i]),
+                DiffOp::Insert => ('+', b[j]),
+            };
+            out.push(prefix);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        k = last + 1;
+    }
+    out
+}
+
+/// Return the steps that turn `a` into `b`, using Myers' algorithm.
+fn diff_ops(a: &[&str], b: &[&str]) -> Vec<DiffOp> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (am, bm) =
+        (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops = vec![DiffOp::Keep; prefix];
+    match myers(am, bm) {
+        Some(middle) => ops.extend(middle),
+        None => {
+            ops.extend(vec![DiffOp::Delete; am.len()]);
+            ops.extend(vec![DiffOp::Insert; bm.len()]);
+        }
+    }
+    ops.extend(vec![DiffOp::Keep; suffix]);
+    ops
+}
+
+/// Return a shortest edit script turning `a` into `b`, or `None` if it needs
+/// more than `DIFF_MAX_EDITS` edits.
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<DiffOp>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let off = max + 1;
+    let mut v = vec![0isize; 2 * off as usize + 1];
+    // The furthest reaching x on each diagonal before each step, so that the
+    // path can be recovered afterwards. Only diagonals -d..=d are kept.
+    let mut trace: Vec<Vec<isize>> = vec![];
+    for d in 0..=max.min(DIFF_MAX_EDITS as isize) {
+        trace.push(v[(off - d) as usize..=(off + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let at = |k: isize| (off + k) as usize;
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                return Some(myers_path(&trace, n, m));
+            }
+        }
+    }
+    None
+}
+
+/// Walk back through the trace recorded by `myers` to find its edit script.
+fn myers_path(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<DiffOp> {
+    let mut ops = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let get = |k: isize| v[(k + d) as usize];
+        if d == 0 {
+            ops.extend((0..x).map(|_| DiffOp::Keep));
+            break;
+        }
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(DiffOp::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        ops.push(if prev_k == k + 1 {
+            DiffOp::Insert
+        } else {
+            DiffOp::Delete
+        });
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.reverse();
+    ops
+}
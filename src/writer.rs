@@ -1,30 +1,237 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::char;
 use std::collections::{BTreeMap, BTreeSet};
 use std::env;
 use std::fmt;
-use std::fs::File;
+use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::str;
+use std::time::Instant;
 
 use fst::raw::Fst;
 use fst::{MapBuilder, SetBuilder};
+use ucd_parse::{
+    AllKeysEntry, BidiCharacterTest, BidiParagraphDirection, BidiTest,
+    CollationElement, ImplicitWeights, NormalizationTest,
+};
 use ucd_trie::TrieSetOwned;
 
 use crate::error::Result;
-use crate::util;
+use crate::regen::Config;
+use crate::util::{self, CharNames, TempDir};
 
 #[derive(Clone, Debug)]
 pub struct WriterBuilder(WriterOptions);
 
+/// How to case fold the keys of string tables.
+#[derive(Clone, Debug)]
+pub enum CaseFold {
+    /// Lowercase ASCII letters only.
+    Ascii,
+    /// Apply simple Unicode case folding, given as a map from each codepoint
+    /// to its folded codepoint.
+    Simple(BTreeMap<u32, u32>),
+}
+
+/// What to optimize for when choosing how to write each set.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Optimize {
+    /// Write whichever format takes the fewest bytes.
+    Size,
+    /// Write whichever format needs the fewest memory accesses for a
+    /// lookup, breaking ties by size.
+    Speed,
+}
+
+/// How to pick the value of a codepoint that more than one value of an
+/// enumeration claims.
+///
+/// Values are ordered as they are in the enumeration, i.e., by name.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Overlap {
+    /// Return an error naming the codepoint and the values claiming it.
+    Error,
+    /// The first value claiming the codepoint wins.
+    First,
+    /// The last value claiming the codepoint wins.
+    Last,
+}
+
+/// How the generated code loads each FST.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FstLoader {
+    /// A static `once_cell::sync::Lazy`.
+    OnceCell,
+    /// A static declared with `lazy_static!`.
+    LazyStatic,
+    /// A function that initializes a `std::sync::OnceLock` the first time
+    /// it is called, and returns a reference to it.
+    OnceLock,
+    /// A function that returns a new FST each time it is called. This is
+    /// cheap, since the FST borrows the embedded bytes.
+    Fn,
+}
+
+/// Which kind of fixed-width integer types C output uses.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CUintType {
+    /// The exact-width types, e.g., `uint32_t`.
+    Exact,
+    /// The minimum-width types, e.g., `uint_least32_t`, which every C99
+    /// implementation has, unlike the exact-width types.
+    Least,
+}
+
+/// Which part of each codepoint table to write, for documentation and
+/// examples that don't need the full tables.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Sample {
+    /// Keep the first N ranges of each table, where a range is a run of
+    /// consecutive codepoints with the same value.
+    Ranges(usize),
+    /// Keep only the codepoints in the given inclusive range.
+    Window(u32, u32),
+}
+
+/// The codepoints introduced after a Unicode version, which are left out
+/// of every table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaxAge {
+    version: (u64, u64),
+    /// The sorted ranges of codepoints introduced after `version`.
+    newer: Vec<(u32, u32)>,
+}
+
+impl MaxAge {
+    /// Create a maximum age from its version, as a major and minor version,
+    /// and the ranges of codepoints introduced after it.
+    pub fn new(version: (u64, u64), mut newer: Vec<(u32, u32)>) -> MaxAge {
+        newer.sort();
+        MaxAge { version, newer }
+    }
+
+    /// Returns true if the given codepoint was introduced after this age.
+    fn excludes(&self, cp: u32) -> bool {
+        let i = self.newer.partition_point(|&(start, _)| start <= cp);
+        i > 0 && self.newer[i - 1].1 >= cp
+    }
+
+    /// Return the parts of the given inclusive range that weren't introduced
+    /// after this age.
+    fn clip(&self, start: u32, end: u32) -> Vec<(u32, u32)> {
+        let mut kept = vec![];
+        let mut next = start;
+        for &(s, e) in &self.newer {
+            if e < next || s > end {
+                continue;
+            }
+            if s > next {
+                kept.push((next, s - 1));
+            }
+            if e >= end {
+                return kept;
+            }
+            next = e + 1;
+        }
+        kept.push((next, end));
+        kept
+    }
+}
+
+/// The codepoints that match a filter given by property, which are the only
+/// codepoints kept in every table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Where {
+    /// What the kept codepoints match, e.g., `Script=Greek`.
+    description: String,
+    /// The sorted ranges of kept codepoints.
+    ranges: Vec<(u32, u32)>,
+}
+
+impl Where {
+    /// Create a filter that keeps the given codepoints, which match the
+    /// given description.
+    pub fn new(description: &str, codepoints: &BTreeSet<u32>) -> Where {
+        Where {
+            description: description.to_string(),
+            ranges: util::to_ranges(codepoints.iter().copied()),
+        }
+    }
+
+    /// Returns true if the given codepoint isn't kept by this filter.
+    fn excludes(&self, cp: u32) -> bool {
+        let i = self.ranges.partition_point(|&(start, _)| start <= cp);
+        i == 0 || self.ranges[i - 1].1 < cp
+    }
+
+    /// Return the parts of the given inclusive range that are kept by this
+    /// filter.
+    fn clip(&self, start: u32, end: u32) -> Vec<(u32, u32)> {
+        self.ranges
+            .iter()
+            .filter(|&&(s, e)| s <= end && e >= start)
+            .map(|&(s, e)| (s.max(start), e.min(end)))
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug)]
 struct WriterOptions {
     name: String,
     columns: u64,
+    indent: String,
     char_literals: bool,
     fst_dir: Option<PathBuf>,
+    out_dir: Option<PathBuf>,
     trie_set: bool,
+    optimize: Option<Optimize>,
+    wasm: bool,
+    emit_len_consts: bool,
+    emit_iter: bool,
+    combined_disjoint: bool,
+    overlap: Overlap,
+    overlap_priority: Vec<String>,
+    group_by_plane: bool,
+    u16_ranges: bool,
+    css_unicode_range: bool,
+    by_name_fn: bool,
+    flat_table_pad: Option<u32>,
+    flat_table_trim: bool,
+    fst_hash_names: bool,
+    fst_max_file_size: Option<usize>,
+    fst_loader: FstLoader,
+    sample: Option<Sample>,
+    max_age: Option<MaxAge>,
+    only: Option<Where>,
+    verify_fst: bool,
+    enum_hash: Option<u64>,
+    enum_non_exhaustive: bool,
+    enum_discriminants: bool,
+    enum_manifest: Option<PathBuf>,
+    force_write: bool,
+    max_value_len: Option<usize>,
+    self_check: bool,
+    rust_match: bool,
+    latin1_fast_path: bool,
+    validate_utf8_tables: bool,
+    annotate: Option<Rc<CharNames>>,
+    assert_nonempty: bool,
+    manifest: bool,
+    check: bool,
+    quiet: bool,
+    regex_syntax: bool,
+    case_fold_keys: Option<CaseFold>,
+    split_min_len: Option<usize>,
+    c_output: bool,
+    c_uint_type: CUintType,
+    c_enum: bool,
+    binary: bool,
     ucd_version: Option<(u64, u64, u64)>,
+    config: Option<Config>,
+    intern_strings: bool,
 }
 
 impl WriterBuilder {
@@ -36,18 +243,87 @@ impl WriterBuilder {
         WriterBuilder(WriterOptions {
             name: name.to_string(),
             columns: 79,
+            indent: "  ".to_string(),
             char_literals: false,
             fst_dir: None,
+            out_dir: None,
             trie_set: false,
+            optimize: None,
+            wasm: false,
+            emit_len_consts: false,
+            emit_iter: false,
+            combined_disjoint: false,
+            overlap: Overlap::Last,
+            overlap_priority: vec![],
+            group_by_plane: false,
+            u16_ranges: false,
+            css_unicode_range: false,
+            by_name_fn: false,
+            flat_table_pad: None,
+            flat_table_trim: false,
+            fst_hash_names: false,
+            fst_max_file_size: None,
+            fst_loader: FstLoader::OnceCell,
+            sample: None,
+            max_age: None,
+            only: None,
+            verify_fst: false,
+            enum_hash: None,
+            enum_non_exhaustive: false,
+            enum_discriminants: false,
+            enum_manifest: None,
+            force_write: false,
+            max_value_len: None,
+            self_check: false,
+            rust_match: false,
+            latin1_fast_path: false,
+            validate_utf8_tables: false,
+            annotate: None,
+            assert_nonempty: false,
+            manifest: false,
+            check: false,
+            quiet: false,
+            regex_syntax: false,
+            case_fold_keys: None,
+            split_min_len: None,
+            c_output: false,
+            c_uint_type: CUintType::Exact,
+            c_enum: false,
+            binary: false,
             ucd_version: None,
+            config: None,
+            intern_strings: false,
         })
     }
 
     /// Create a new Unicode writer from this builder's configuration.
     pub fn from_writer<W: io::Write + 'static>(&self, wtr: W) -> Writer {
         Writer {
-            wtr: LineWriter::new(Box::new(wtr)),
+            wtr: LineWriter::new(Box::new(wtr), &self.0),
             wrote_header: false,
+            table_lens: vec![],
+            out_files: BTreeSet::new(),
+            rs_file: None,
+            pending_slice: None,
+            by_name: None,
+            emitted_sets: BTreeSet::new(),
+            trie_sets: BTreeSet::new(),
+            u16_sets: BTreeSet::new(),
+            pending_sets: vec![],
+            wasm_ranges: vec![],
+            wasm_sets: BTreeMap::new(),
+            wasm_slice: None,
+            data_sizes: vec![],
+            sharded: BTreeSet::new(),
+            wrote_binary_prelude: false,
+            wrote_test_prelude: false,
+            wrote_nonzero_use: false,
+            last_table: Instant::now(),
+            written_fsts: vec![],
+            manifest: None,
+            pool: None,
+            staged: vec![],
+            staging: None,
             opts: self.0.clone(),
         }
     }
@@ -58,16 +334,62 @@ impl WriterBuilder {
     }
 
     /// Create a new Unicode writer that writes FSTs to a directory.
+    ///
+    /// The Rust source code is written to the same directory, as with
+    /// `from_out_dir`.
     pub fn from_fst_dir<P: AsRef<Path>>(&self, fst_dir: P) -> Result<Writer> {
-        let mut opts = self.0.clone();
-        opts.fst_dir = Some(fst_dir.as_ref().to_path_buf());
-        let mut fpath = fst_dir.as_ref().join(rust_module_name(&opts.name));
-        fpath.set_extension("rs");
-        Ok(Writer {
-            wtr: LineWriter::new(Box::new(File::create(fpath)?)),
+        let mut builder = self.clone();
+        builder.0.fst_dir = Some(fst_dir.as_ref().to_path_buf());
+        builder.from_out_dir(fst_dir)
+    }
+
+    /// Create a new Unicode writer that writes Rust source code to a file in
+    /// the given directory, named after the module. (With C output, the file
+    /// has a `.h` extension instead.)
+    pub fn from_out_dir<P: AsRef<Path>>(&self, out_dir: P) -> Result<Writer> {
+        let mut builder = self.clone();
+        builder.0.out_dir = Some(out_dir.as_ref().to_path_buf());
+        let mut fpath =
+            out_dir.as_ref().join(rust_module_name(&builder.0.name));
+        fpath.set_extension(if builder.0.c_output { "h" } else { "rs" });
+        Ok(builder.from_file(fpath))
+    }
+
+    /// Create a new Unicode writer that writes Rust source code to the given
+    /// file.
+    pub fn from_file<P: AsRef<Path>>(&self, path: P) -> Writer {
+        let opts = self.0.clone();
+        // The Rust source is buffered in memory and only written to disk by
+        // `finish`, so that an unchanged file can be left untouched.
+        let buf = SharedBuf::default();
+        Writer {
+            wtr: LineWriter::new(Box::new(buf.clone()), &opts),
             wrote_header: false,
+            table_lens: vec![],
+            out_files: BTreeSet::new(),
+            rs_file: Some((path.as_ref().to_path_buf(), buf)),
+            pending_slice: None,
+            by_name: None,
+            emitted_sets: BTreeSet::new(),
+            trie_sets: BTreeSet::new(),
+            u16_sets: BTreeSet::new(),
+            pending_sets: vec![],
+            wasm_ranges: vec![],
+            wasm_sets: BTreeMap::new(),
+            wasm_slice: None,
+            data_sizes: vec![],
+            sharded: BTreeSet::new(),
+            wrote_binary_prelude: false,
+            wrote_test_prelude: false,
+            wrote_nonzero_use: false,
+            last_table: Instant::now(),
+            written_fsts: vec![],
+            manifest: None,
+            pool: None,
+            staged: vec![],
+            staging: None,
             opts,
-        })
+        }
     }
 
     /// Set the column limit to use when writing Rust source code.
@@ -78,6 +400,16 @@ impl WriterBuilder {
         self
     }
 
+    /// Set the string to use for each level of indentation.
+    ///
+    /// Every two spaces of leading indentation in the default output are
+    /// replaced with the given string, so tabs can be used by passing `\t`.
+    /// This defaults to two spaces.
+    pub fn indent(&mut self, indent: &str) -> &mut WriterBuilder {
+        self.0.indent = indent.to_string();
+        self
+    }
+
     /// When printing Rust source code, emit `char` literals instead of `u32`
     /// literals. Any codepoints that aren't Unicode scalar values (i.e.,
     /// surrogate codepoints) are silently dropped when writing.
@@ -92,1138 +424,8406 @@ impl WriterBuilder {
         self.0.trie_set = yes;
         self
     }
-    /// Set what version of the UCD we're generating data from.
-    pub fn ucd_version(
+
+    /// Choose, for each set written by `ranges`, whether to write a slice of
+    /// ranges or a trie, whichever is best for the given target. The choice
+    /// and the size of each format are recorded in a comment above the set.
+    ///
+    /// Sets are always written as slices when an iterator over them is
+    /// emitted.
+    pub fn optimize(
         &mut self,
-        major: u64,
-        minor: u64,
-        patch: u64,
+        optimize: Option<Optimize>,
     ) -> &mut WriterBuilder {
-        self.0.ucd_version = Some((major, minor, patch));
+        self.0.optimize = optimize;
         self
     }
-}
 
-/// A writer of various kinds of Unicode data.
-///
-/// A writer takes as input various forms of Unicode data and writes that data
-/// in a number of different output formats.
-pub struct Writer {
-    wtr: LineWriter<Box<dyn io::Write + 'static>>,
-    wrote_header: bool,
-    opts: WriterOptions,
-}
+    /// Write tables that are friendly to WebAssembly builds.
+    ///
+    /// Every set written by `ranges` becomes a range of indices into a
+    /// single `ALL_RANGES` slice, instead of a slice of its own, and the
+    /// `BY_NAME` index refers to names and sets by their offsets instead of
+    /// by reference. Neither needs relocations. Functions that only use
+    /// `core` are written to look sets up, and the total size of the tables
+    /// is reported when writing finishes.
+    pub fn wasm(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.wasm = yes;
+        self
+    }
 
-impl Writer {
-    /// Write a sorted sequence of string names that map to Unicode set names.
-    pub fn names<I: IntoIterator<Item = T>, T: AsRef<str>>(
-        &mut self,
-        names: I,
-    ) -> Result<()> {
-        self.header()?;
-        self.separator()?;
+    /// Split every slice of codepoint ranges into one slice per Unicode
+    /// plane, e.g., `NAME_BMP` and `NAME_SMP`, along with a `NAME_PLANES`
+    /// index of `(plane, slice)` pairs.
+    ///
+    /// Applications that only need some planes can then refer to just those
+    /// slices, so that the others are never linked.
+    pub fn group_by_plane(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.group_by_plane = yes;
+        self
+    }
 
-        let ty = if self.opts.fst_dir.is_some() {
-            "::fst::Set<&'static [u8]>".to_string()
-        } else if self.opts.trie_set {
-            "&'static ::ucd_trie::TrieSet".to_string()
-        } else {
-            let charty = self.rust_codepoint_type();
-            format!("&'static [({}, {})]", charty, charty)
-        };
+    /// Write the slice of ranges of every set whose codepoints all fit in
+    /// the Basic Multilingual Plane as `(u16, u16)` pairs, which takes half
+    /// the space of `(u32, u32)` pairs.
+    ///
+    /// Since the type of each of these slices then depends on its contents,
+    /// the sets in the `BY_NAME` index are only written as `u16` pairs when
+    /// all of them fit. This has no effect on sets written in any other
+    /// format, or with char literals.
+    pub fn u16_ranges(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.u16_ranges = yes;
+        self
+    }
 
-        let mut names: Vec<String> =
-            names.into_iter().map(|name| name.as_ref().to_string()).collect();
-        names.sort();
+    /// Write every set written by `ranges` as a string constant in the
+    /// syntax of the CSS `unicode-range` descriptor, e.g.,
+    /// `U+0000-00FF, U+0131`, for font subsetting tools.
+    pub fn css_unicode_range(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.css_unicode_range = yes;
+        self
+    }
 
-        writeln!(
-            self.wtr,
-            "pub const BY_NAME: &'static [(&'static str, {})] = &[",
-            ty,
-        )?;
-        for name in names {
-            let rustname = rust_const_name(&name);
-            self.wtr.write_str(&format!("({:?}, {}), ", name, rustname))?;
-        }
-        writeln!(self.wtr, "];")?;
-        Ok(())
+    /// Follow the `BY_NAME` index with a `set_by_name` function, which looks
+    /// up a set by a name that is matched loosely as in UAX44-LM3, e.g.,
+    /// `Upper_Case` finds `Uppercase`.
+    pub fn by_name_fn(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.by_name_fn = yes;
+        self
     }
 
-    /// Write a sorted sequence of codepoints.
+    /// Emit a `NAME_LEN` constant after every slice table with the number of
+    /// entries in that table.
     ///
-    /// Note that the specific representation of ranges may differ with the
-    /// output format. For example, if the output format is a slice, then a
-    /// straight-forward slice of sorted codepoint ranges is emitted. But if
-    /// the output format is an FST or similar, then all codepoints are
-    /// explicitly represented.
-    pub fn ranges(
-        &mut self,
-        name: &str,
-        codepoints: &BTreeSet<u32>,
-    ) -> Result<()> {
-        self.header()?;
-        self.separator()?;
+    /// When more than one slice table is written, a `TABLE_LENS` manifest of
+    /// `(name, length)` pairs is also emitted by `Writer::finish`.
+    pub fn emit_len_consts(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.emit_len_consts = yes;
+        self
+    }
 
-        let name = rust_const_name(name);
-        if self.opts.fst_dir.is_some() {
-            let mut builder = SetBuilder::memory();
-            builder.extend_iter(codepoints.iter().cloned().map(u32_key))?;
-            let set = builder.into_set();
-            self.fst(&name, set.as_fst(), false)?;
-        } else if self.opts.trie_set {
-            let set: Vec<u32> = codepoints.iter().cloned().collect();
-            let trie = TrieSetOwned::from_codepoints(&set)?;
-            self.trie_set(&name, &trie)?;
-        } else {
-            let ranges = util::to_ranges(codepoints.iter().cloned());
-            self.ranges_slice(&name, &ranges)?;
-        }
-        self.wtr.flush()?;
-        Ok(())
+    /// Follow every set written by `ranges` with a `name_iter` function that
+    /// lazily iterates over each of the set's characters, in order.
+    ///
+    /// This isn't supported for sets written as tries or FSTs.
+    pub fn emit_iter(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.emit_iter = yes;
+        self
     }
 
-    fn ranges_slice(
-        &mut self,
-        name: &str,
-        table: &[(u32, u32)],
-    ) -> Result<()> {
-        let ty = self.rust_codepoint_type();
-        writeln!(
-            self.wtr,
-            "pub const {}: &'static [({}, {})] = &[",
-            name, ty, ty
-        )?;
-        for &(start, end) in table {
-            let range = (self.rust_codepoint(start), self.rust_codepoint(end));
-            if let (Some(start), Some(end)) = range {
-                self.wtr.write_str(&format!("({}, {}), ", start, end))?;
-            }
-        }
-        writeln!(self.wtr, "];")?;
-        Ok(())
+    /// When writing a combined table, return an error if any codepoint
+    /// belongs to more than one of the values being combined. By default,
+    /// overlapping values are simply unioned together.
+    pub fn combined_disjoint(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.combined_disjoint = yes;
+        self
     }
 
-    fn trie_set(&mut self, name: &str, trie: &TrieSetOwned) -> Result<()> {
-        let trie = trie.as_slice();
-        writeln!(
-            self.wtr,
-            "pub const {}: &'static ::ucd_trie::TrieSet = \
-             &::ucd_trie::TrieSet {{",
-            name
-        )?;
+    /// Set how to resolve a codepoint that belongs to more than one value
+    /// when writing a table that maps each codepoint to a single value.
+    ///
+    /// The default is `Overlap::Last`. With `Overlap::Error`, combined
+    /// tables are required to be disjoint too.
+    pub fn overlap(&mut self, overlap: Overlap) -> &mut WriterBuilder {
+        self.0.overlap = overlap;
+        self
+    }
 
-        self.wtr.indent("    ");
+    /// Set the values that win when a codepoint belongs to more than one
+    /// value, in order of decreasing priority.
+    ///
+    /// A listed value always wins over an unlisted one. When none of the
+    /// values claiming a codepoint are listed, `overlap` decides. Listed
+    /// values that aren't in a table are ignored.
+    pub fn overlap_priority<I, S>(&mut self, values: I) -> &mut WriterBuilder
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.0.overlap_priority = values.into_iter().map(Into::into).collect();
+        self
+    }
 
-        writeln!(self.wtr, "  tree1_level1: &[")?;
-        self.write_slice_u64(&trie.tree1_level1)?;
-        writeln!(self.wtr, "  ],")?;
+    /// Set the sentinel used to pad unoccupied entries of flat tables.
+    ///
+    /// When not set, `!0` is used for `u32` tables and `'\0'` is used for
+    /// `char` tables.
+    pub fn flat_table_pad(&mut self, pad: Option<u32>) -> &mut WriterBuilder {
+        self.0.flat_table_pad = pad;
+        self
+    }
 
-        writeln!(self.wtr, "  tree2_level1: &[")?;
-        self.write_slice_u8(&trie.tree2_level1)?;
-        writeln!(self.wtr, "  ],")?;
+    /// When writing a flat table, also emit the padding sentinel as a
+    /// constant along with a function that trims the padding off of an
+    /// entry's array of values.
+    pub fn flat_table_trim(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.flat_table_trim = yes;
+        self
+    }
 
-        writeln!(self.wtr, "  tree2_level2: &[")?;
-        self.write_slice_u64(&trie.tree2_level2)?;
-        writeln!(self.wtr, "  ],")?;
+    /// When writing FSTs, suffix each file name with a hash of its contents.
+    ///
+    /// This makes it possible to emit FSTs from several commands into the
+    /// same directory without one table silently overwriting another's file.
+    pub fn fst_hash_names(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.fst_hash_names = yes;
+        self
+    }
 
-        writeln!(self.wtr, "  tree3_level1: &[")?;
-        self.write_slice_u8(&trie.tree3_level1)?;
-        writeln!(self.wtr, "  ],")?;
+    /// Split every FST whose file would be bigger than `max` bytes into
+    /// several files, each holding a contiguous range of keys.
+    ///
+    /// A sharded FST is still emitted as a single lazy static (or function,
+    /// see `fst_loader`), but it holds an array of FSTs. A `name_shard` function returns the shard that a
+    /// given key belongs to.
+    pub fn fst_max_file_size(
+        &mut self,
+        max: Option<usize>,
+    ) -> &mut WriterBuilder {
+        self.0.fst_max_file_size = max;
+        self
+    }
 
-        writeln!(self.wtr, "  tree3_level2: &[")?;
-        self.write_slice_u8(&trie.tree3_level2)?;
-        writeln!(self.wtr, "  ],")?;
+    /// Set how the generated code loads each FST. This defaults to a
+    /// `once_cell` lazy static.
+    ///
+    /// With `FstLoader::OnceLock` and `FstLoader::Fn`, each FST is returned
+    /// by a function named after its table instead of being a static.
+    pub fn fst_loader(&mut self, loader: FstLoader) -> &mut WriterBuilder {
+        self.0.fst_loader = loader;
+        self
+    }
 
-        writeln!(self.wtr, "  tree3_level3: &[")?;
-        self.write_slice_u64(&trie.tree3_level3)?;
-        writeln!(self.wtr, "  ],")?;
+    /// Write only part of each table that is keyed by codepoint, as chosen
+    /// by the given sample, and say so in the header.
+    ///
+    /// Tables keyed by strings, such as names, are written in full.
+    pub fn sample(&mut self, sample: Option<Sample>) -> &mut WriterBuilder {
+        self.0.sample = sample;
+        self
+    }
 
-        writeln!(self.wtr, "}};")?;
-        Ok(())
+    /// Leave every codepoint introduced after the given age out of each
+    /// table, and say so in the header.
+    ///
+    /// This applies to the keys of tables keyed by codepoint, to both
+    /// codepoints of pair tables, and to the values of tables keyed by
+    /// strings, such as names. Test data is written in full.
+    pub fn max_age(&mut self, max_age: Option<MaxAge>) -> &mut WriterBuilder {
+        self.0.max_age = max_age;
+        self
     }
 
-    /// Write a map that associates codepoint ranges to a single value in an
-    /// enumeration. This usually emits two items: a map from codepoint range
-    /// to index and a map from index to one of the enum variants.
+    /// Leave every codepoint that doesn't match the given filter out of
+    /// each table, and say so in the header.
     ///
-    /// The given map should be a map from the enum variant value to the set
-    /// of codepoints that have that value.
-    pub fn ranges_to_enum(
-        &mut self,
-        name: &str,
-        enum_map: &BTreeMap<String, BTreeSet<u32>>,
-    ) -> Result<()> {
-        self.header()?;
-        self.separator()?;
+    /// This applies to the same codepoints as `max_age`.
+    pub fn only(&mut self, only: Option<Where>) -> &mut WriterBuilder {
+        self.0.only = only;
+        self
+    }
 
-        writeln!(
-            self.wtr,
-            "pub const {}_ENUM: &'static [&'static str] = &[",
-            rust_const_name(name)
-        )?;
-        for variant in enum_map.keys() {
-            self.wtr.write_str(&format!("{:?}, ", variant))?;
-        }
-        writeln!(self.wtr, "];")?;
+    /// After `finish` writes the FSTs, read each one back from disk and
+    /// check that it has every key of the FST that was built, with the same
+    /// value. This catches corrupted writes at generation time instead of
+    /// when the FST is first used.
+    ///
+    /// Nothing is verified in check mode, since nothing is written.
+    pub fn verify_fst(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.verify_fst = yes;
+        self
+    }
 
-        let mut map = BTreeMap::new();
-        for (i, (_, ref set)) in enum_map.iter().enumerate() {
-            map.extend(set.iter().cloned().map(|cp| (cp, i as u64)));
-        }
-        self.ranges_to_unsigned_integer(name, &map)?;
-        self.wtr.flush()?;
-        Ok(())
+    /// Pin the expected hash of enum values written by
+    /// `ranges_to_enum_with_aliases`.
+    ///
+    /// When set, the generated code includes a compile time assertion that
+    /// the emitted `_ENUM_HASH` is equal to the given hash.
+    pub fn enum_hash(&mut self, hash: Option<u64>) -> &mut WriterBuilder {
+        self.0.enum_hash = hash;
+        self
     }
 
-    /// Write a map that associates codepoint ranges to a single value in a
-    /// Rust enum.
+    /// Mark every generated Rust enum as `#[non_exhaustive]`.
+    pub fn enum_non_exhaustive(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.enum_non_exhaustive = yes;
+        self
+    }
+
+    /// Give every variant of a generated Rust enum an explicit discriminant.
+    pub fn enum_discriminants(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.enum_discriminants = yes;
+        self
+    }
+
+    /// Order the variants of generated Rust enums according to the manifest
+    /// at the given path.
     ///
-    /// The given map should be a map from the enum variant value to the set
-    /// of codepoints that have that value.
-    pub fn ranges_to_rust_enum(
+    /// The manifest records the order in which the variants of each enum were
+    /// first seen. Variants that are in the manifest keep their position
+    /// (and discriminant), while new variants are added to the end of the
+    /// enum and to the manifest. The manifest is updated by `Writer::finish`
+    /// and is created if it doesn't exist.
+    pub fn enum_manifest<P: AsRef<Path>>(
         &mut self,
-        name: &str,
-        variants: &[&str],
-        enum_map: &BTreeMap<String, BTreeSet<u32>>,
-    ) -> Result<()> {
-        self.header()?;
-        self.separator()?;
+        path: Option<P>,
+    ) -> &mut WriterBuilder {
+        self.0.enum_manifest = path.map(|p| p.as_ref().to_path_buf());
+        self
+    }
 
-        writeln!(
-            self.wtr,
-            "#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]",
-        )?;
-        let enum_name = rust_type_name(name);
-        writeln!(self.wtr, "pub enum {} {{", enum_name)?;
-        for variant in variants {
-            self.wtr.write_str(&format!("{}, ", rust_type_name(variant)))?;
-        }
-        writeln!(self.wtr, "}}\n")?;
+    /// When writing to a directory, always rewrite every file.
+    ///
+    /// By default, files whose contents would not change are left alone.
+    pub fn force_write(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.force_write = yes;
+        self
+    }
 
-        let mut map = BTreeMap::new();
-        for (variant, ref set) in enum_map.iter() {
-            map.extend(set.iter().cloned().map(|cp| (cp, variant)));
-        }
-        let ranges = util::to_range_values(
-            map.iter().map(|(&k, &v)| (k, rust_type_name(v))),
-        );
-        self.ranges_to_enum_slice(name, &enum_name, &ranges)?;
-        self.wtr.flush()?;
-        Ok(())
+    /// Set the maximum length, in bytes, of the values in string valued
+    /// maps. Writing a longer value returns an error.
+    pub fn max_value_len(&mut self, len: Option<usize>) -> &mut WriterBuilder {
+        self.0.max_value_len = len;
+        self
     }
 
-    /// Write a map that associates codepoint ranges to a single value in a
-    /// Rust enum with custom discriminants.
+    /// When writing codepoint to codepoint(s) maps as slices, follow each
+    /// table with compile time assertions that its keys are sorted and that
+    /// its keys and values are valid Unicode scalar values.
     ///
-    /// The given `variants_map` should be a map from the custom discriminant
-    /// to the enum variant value.
-    ///
-    /// The given `enum_map` should be a map from the enum variant value to the
-    /// set of codepoints that have that value.
-    pub fn ranges_to_rust_enum_with_custom_discriminants(
-        &mut self,
-        name: &str,
-        variants_map: &BTreeMap<isize, String>,
-        enum_map: &BTreeMap<String, BTreeSet<u32>>,
-    ) -> Result<()> {
-        self.header()?;
-        self.separator()?;
+    /// This also makes it an error to write a codepoint that can't be
+    /// represented in the chosen codepoint format (e.g., a surrogate with
+    /// `char_literals` enabled), instead of silently dropping it.
+    pub fn self_check(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.self_check = yes;
+        self
+    }
 
-        writeln!(
-            self.wtr,
-            "#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]",
-        )?;
-        let enum_name = rust_type_name(name);
-        writeln!(self.wtr, "pub enum {} {{", enum_name)?;
-        for (discriminant, variant) in variants_map {
-            self.wtr.write_str(&format!(
-                "{} = {}, ",
-                rust_type_name(variant),
-                discriminant
-            ))?;
-        }
-        writeln!(self.wtr, "}}\n")?;
+    /// Write codepoint to codepoint maps as a function that uses a match
+    /// expression, instead of as a table.
+    pub fn rust_match(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.rust_match = yes;
+        self
+    }
 
-        let mut map = BTreeMap::new();
-        for (variant, ref set) in enum_map.iter() {
-            map.extend(set.iter().cloned().map(|cp| (cp, variant)));
-        }
-        let ranges = util::to_range_values(
-            map.iter().map(|(&k, &v)| (k, rust_type_name(v))),
-        );
-        self.ranges_to_enum_slice(name, &enum_name, &ranges)?;
-        self.wtr.flush()?;
-        Ok(())
+    /// After each UTF-8 trie, write a test module that runs its function on
+    /// a deterministic pseudo-random sample of inputs, including invalid
+    /// UTF-8, and compares the results with the expected results computed
+    /// from the table that the trie was built from.
+    pub fn validate_utf8_tables(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.validate_utf8_tables = yes;
+        self
     }
 
-    /// Write a map that combines codepoint ranges into a single table.
+    /// Write codepoint to codepoint maps as a dense array for the first 256
+    /// codepoints and a sorted slice for the rest, along with a function
+    /// that looks up a codepoint in either.
+    pub fn latin1_fast_path(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.latin1_fast_path = yes;
+        self
+    }
+
+    /// Follow each range in tables of codepoint ranges with a comment
+    /// naming its first and last characters, using the given names.
     ///
-    /// The given map should be a map from the variant value to the set of
-    /// codepoints that have that value.
-    pub fn ranges_to_combined(
+    /// Annotated ranges are written one per line.
+    pub fn annotate(
         &mut self,
-        name: &str,
-        enum_map: &BTreeMap<String, BTreeSet<u32>>,
-    ) -> Result<()> {
-        let mut set = BTreeSet::new();
-        for other_set in enum_map.values() {
-            set.extend(other_set.iter().cloned());
-        }
-        self.ranges(name, &set)?;
-        self.wtr.flush()?;
-        Ok(())
+        names: Option<CharNames>,
+    ) -> &mut WriterBuilder {
+        self.0.annotate = names.map(Rc::new);
+        self
     }
 
-    fn ranges_to_enum_slice<S>(
-        &mut self,
-        name: &str,
-        enum_ty: &str,
-        table: &[(u32, u32, S)],
-    ) -> Result<()>
-    where
-        S: fmt::Display,
-    {
-        let cp_ty = self.rust_codepoint_type();
+    /// Return an error instead of writing a table that has no entries.
+    ///
+    /// An empty table is usually a sign of a filter that didn't match what
+    /// was intended, or of a UCD directory that is missing data.
+    pub fn assert_nonempty(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.assert_nonempty = yes;
+        self
+    }
 
-        writeln!(
-            self.wtr,
-            "pub const {}: &'static [({}, {}, {})] = &[",
-            name, cp_ty, cp_ty, enum_ty,
-        )?;
-        for (start, end, variant) in table {
-            let range =
-                (self.rust_codepoint(*start), self.rust_codepoint(*end));
-            if let (Some(start), Some(end)) = range {
-                let src = format!(
-                    "({}, {}, {}::{}), ",
-                    start, end, enum_ty, variant,
-                );
-                self.wtr.write_str(&src)?;
-            }
-        }
-        writeln!(self.wtr, "];")?;
-        Ok(())
+    /// When writing to a directory, also write a `manifest.json` that lists
+    /// the size and SHA-256 hash of every file written, along with the
+    /// command that generated it and the Unicode version.
+    ///
+    /// Entries for files written by other commands into the same directory
+    /// are kept.
+    pub fn manifest(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.manifest = yes;
+        self
     }
 
-    /// Write a map that associates ranges of codepoints with an arbitrary
-    /// integer.
+    /// Instead of writing any files, compare what would have been written
+    /// with the existing files. `finish` prints a unified diff of each file
+    /// that differs to stdout, and then returns an error if any did.
     ///
-    /// The smallest numeric type is used when applicable.
-    pub fn ranges_to_unsigned_integer(
-        &mut self,
-        name: &str,
-        map: &BTreeMap<u32, u64>,
-    ) -> Result<()> {
-        self.header()?;
-        self.separator()?;
+    /// This only applies to writers that write files, i.e., not to those
+    /// created by `from_writer` or `from_stdout`.
+    pub fn check(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.check = yes;
+        self
+    }
 
-        let name = rust_const_name(name);
-        if self.opts.fst_dir.is_some() {
-            let mut builder = MapBuilder::memory();
-            for (&k, &v) in map {
-                builder.insert(u32_key(k), v)?;
-            }
-            let map = builder.into_map();
-            self.fst(&name, map.as_fst(), true)?;
-        } else {
-            let ranges =
-                util::to_range_values(map.iter().map(|(&k, &v)| (k, v)));
-            self.ranges_to_unsigned_integer_slice(&name, &ranges)?;
-        }
-        self.wtr.flush()?;
-        Ok(())
+    /// Record the given config of the command that generated the output in
+    /// its header, so that `ucd-generate regen` can run it again.
+    pub fn config(&mut self, config: Option<Config>) -> &mut WriterBuilder {
+        self.0.config = config;
+        self
     }
 
-    fn ranges_to_unsigned_integer_slice(
-        &mut self,
-        name: &str,
-        table: &[(u32, u32, u64)],
-    ) -> Result<()> {
-        let cp_ty = self.rust_codepoint_type();
-        let num_ty = match table.iter().map(|&(_, _, n)| n).max() {
-            None => "u8",
-            Some(max_num) => smallest_unsigned_type(max_num),
-        };
+    /// Don't report each file written on stderr.
+    pub fn quiet(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.quiet = yes;
+        self
+    }
 
-        writeln!(
-            self.wtr,
-            "pub const {}: &'static [({}, {}, {})] = &[",
-            name, cp_ty, cp_ty, num_ty
-        )?;
-        for &(start, end, num) in table {
-            let range = (self.rust_codepoint(start), self.rust_codepoint(end));
-            if let (Some(start), Some(end)) = range {
-                let src = format!("({}, {}, {}), ", start, end, num);
-                self.wtr.write_str(&src)?;
-            }
-        }
-        writeln!(self.wtr, "];")?;
-        Ok(())
+    /// Lay out tables the way the `unicode_tables` module of regex-syntax
+    /// does, so that they can replace the tables vendored by it.
+    ///
+    /// Currently, this only moves each `BY_NAME` index before the tables it
+    /// refers to (see `names`) and puts each of its entries on its own line.
+    /// Callers should also enable char literals.
+    pub fn regex_syntax(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.regex_syntax = yes;
+        self
     }
 
-    /// Write a map that associates strings to strings.
+    /// Case fold the keys of string tables, and follow each such table with
+    /// a function that looks up a key case insensitively.
     ///
-    /// The only supported output format is a sorted slice, which can be
-    /// binary searched.
-    pub fn string_to_string(
+    /// The function folds its query the same way while comparing it to each
+    /// key, so it doesn't allocate. It is an error for two keys with
+    /// different values to fold to the same key. This isn't supported for
+    /// FSTs.
+    pub fn case_fold_keys(
         &mut self,
-        name: &str,
-        map: &BTreeMap<String, String>,
-    ) -> Result<()> {
-        if self.opts.fst_dir.is_some() {
-            return err!("cannot emit string->string map as an FST");
-        }
-
-        self.header()?;
-        self.separator()?;
-
-        let name = rust_const_name(name);
-        writeln!(
-            self.wtr,
-            "pub const {}: &'static [(&'static str, &'static str)] = &[",
-            name
-        )?;
-        for (k, v) in map {
-            self.wtr.write_str(&format!("({:?}, {:?}), ", k, v))?;
-        }
-        writeln!(self.wtr, "];")?;
-
-        self.wtr.flush()?;
-        Ok(())
+        fold: Option<CaseFold>,
+    ) -> &mut WriterBuilder {
+        self.0.case_fold_keys = fold;
+        self
     }
 
-    /// Write a map that associates strings to another map from strings to
-    /// strings.
+    /// Move the entries of every data table with at least `min_len` entries
+    /// into a separate `name.data.rs` file, which the table's declaration
+    /// pulls in with `include!`.
     ///
-    /// The only supported output format is a sorted slice, which can be
-    /// binary searched.
-    pub fn string_to_string_to_string(
+    /// This keeps the module itself small (types, constants and functions),
+    /// which can speed up compiling very large tables. This requires writing
+    /// to a directory (see `from_out_dir`). By default, tables are not
+    /// split.
+    pub fn split_data(
         &mut self,
-        name: &str,
-        map: &BTreeMap<String, BTreeMap<String, String>>,
-    ) -> Result<()> {
-        if self.opts.fst_dir.is_some() {
-            return err!("cannot emit string->string map as an FST");
-        }
-
-        self.header()?;
-        self.separator()?;
-
-        let name = rust_const_name(name);
-        writeln!(
-            self.wtr,
-            "pub const {}: &'static \
-             [(&'static str, \
-             &'static [(&'static str, &'static str)])] = &[",
-            name
-        )?;
-        let mut first = true;
-        for (k1, kv) in map {
-            if !first {
-                writeln!(self.wtr, "")?;
-            }
-            first = false;
+        min_len: Option<usize>,
+    ) -> &mut WriterBuilder {
+        self.0.split_min_len = min_len;
+        self
+    }
 
-            self.wtr.write_str(&format!("({:?}, &[", k1))?;
-            for (k2, v) in kv {
-                self.wtr.write_str(&format!("({:?}, {:?}), ", k2, v))?;
-            }
-            self.wtr.write_str("]), ")?;
-        }
-        writeln!(self.wtr, "];")?;
+    /// Write the value names of enum tables as `(start, len)` spans into a
+    /// string pool, `POOL`, instead of as string slices.
+    ///
+    /// The pool is written to `strings.rs` in the output directory, and is
+    /// shared by every table written there. Strings that are already in the
+    /// pool (even as part of a longer string) are reused, and new strings
+    /// are appended, so that the spans in files written by other commands
+    /// stay valid. This requires writing to a directory (see
+    /// `from_out_dir`).
+    pub fn intern_strings(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.intern_strings = yes;
+        self
+    }
 
-        self.wtr.flush()?;
-        Ok(())
+    /// Write C source code instead of Rust source code.
+    ///
+    /// This is only supported for sets written as tries (see `trie_set`).
+    /// The output defines a `struct ucd_trie_set` and a
+    /// `ucd_trie_set_contains` function for querying it. Any other kind of
+    /// table results in an error.
+    pub fn c_output(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.c_output = yes;
+        self
     }
 
-    /// Write a map that associates codepoints with other codepoints.
+    /// Set which kind of fixed-width integer types C output uses. This
+    /// defaults to the exact-width types, e.g., `uint64_t`.
     ///
-    /// This supports the FST format in addition to the standard sorted slice
-    /// format. When using an FST, the keys and values are 32-bit big endian
-    /// unsigned integers.
-    pub fn codepoint_to_codepoint(
-        &mut self,
-        name: &str,
-        map: &BTreeMap<u32, u32>,
-    ) -> Result<()> {
-        self.header()?;
-        self.separator()?;
+    /// Each array still uses the smallest width that its values fit in, and
+    /// the type of the codepoint given to `ucd_trie_set_contains` is always
+    /// 32 bits wide. Since the `struct ucd_trie_set` defined by every file
+    /// depends on this, files included in the same translation unit must
+    /// use the same kind of types.
+    pub fn c_uint_type(&mut self, ty: CUintType) -> &mut WriterBuilder {
+        self.0.c_uint_type = ty;
+        self
+    }
 
-        let name = rust_const_name(name);
-        if self.opts.fst_dir.is_some() {
-            let mut builder = MapBuilder::memory();
-            for (&k, &v) in map {
-                builder.insert(u32_key(k), v as u64)?;
-            }
-            let map = builder.into_map();
-            self.fst(&name, map.as_fst(), true)?;
-        } else {
-            let table: Vec<(u32, u32)> =
-                map.iter().map(|(&k, &v)| (k, v)).collect();
-            self.ranges_slice(&name, &table)?;
-        }
-        self.wtr.flush()?;
-        Ok(())
+    /// When writing C, also write an `enum` after the `BY_NAME` index, with
+    /// a constant for each set that is its position in the index.
+    ///
+    /// The enum is named after the writer, and each constant is the name of
+    /// the writer followed by the name of the set, in upper case, e.g.,
+    /// `GENERAL_CATEGORY_UPPERCASE_LETTER` in `enum general_category`.
+    pub fn c_enum(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.c_enum = yes;
+        self
     }
 
-    /// Write a function that associates codepoints with other codepoints.
+    /// Write sets, maps of ranges to integers (including enum maps) and
+    /// codepoint to codepoint maps as packed little endian binary files
+    /// instead of as Rust slices.
     ///
-    /// The function will use a match expression to map between codepoints.
-    /// The fallback branch of the match returns 0.
-    pub fn codepoint_to_codepoint_fn(
-        &mut self,
-        name: &str,
-        map: &BTreeMap<u32, u32>,
-    ) -> Result<()> {
-        self.header()?;
-        self.separator()?;
+    /// Each table is written to a `name.bin` file in the output directory,
+    /// which the generated code embeds with `include_bytes!`. A function
+    /// named after the table returns a typed view of the file's records.
+    /// The record types consist only of byte arrays, so they have an
+    /// alignment of 1, and derive the `zerocopy` and `bytemuck` traits
+    /// when the crate that includes them enables the feature of the same
+    /// name. This makes it possible to use them with memory mapped files
+    /// too. Other kinds of tables are written as usual. This requires
+    /// writing to a directory (see `from_out_dir`).
+    pub fn binary(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.0.binary = yes;
+        self
+    }
 
-        writeln!(self.wtr, "use std::num::NonZeroU32;")?;
-        self.separator()?;
+    /// Set what version of the UCD we're generating data from.
+    pub fn ucd_version(
+        &mut self,
+        major: u64,
+        minor: u64,
+        patch: u64,
+    ) -> &mut WriterBuilder {
+        self.0.ucd_version = Some((major, minor, patch));
+        self
+    }
+}
 
-        let fn_name = rust_fn_name(name);
-        writeln!(
-            self.wtr,
-            "pub fn {}(cp: u32) -> Option<NonZeroU32> {{",
-            fn_name
-        )?;
-        self.wtr.indent("    ");
-        self.wtr.write_str(
-            "// new_unchecked is safe as ucd-generate checks \
-             that the destination",
-        )?;
-        self.wtr.flush_line()?;
-        self.wtr.write_str(
-            "// codepoint is non-zero at \
-             code generation time.",
-        )?;
-        self.wtr.flush_line()?;
-        self.wtr.write_str("unsafe {")?;
-        self.wtr.flush_line()?;
-        self.wtr.indent("        ");
-        self.wtr.write_str("match cp {")?;
-        self.wtr.flush_line()?;
-        self.wtr.indent("            ");
-        for (from, to) in map {
-            if *to == 0 {
-                return err!(
-                    "destination codepoint must not be 0 (NUL) for \
-                     rust-match output format"
-                );
+/// A writer of various kinds of Unicode data.
+///
+/// A writer takes as input various forms of Unicode data and writes that data
+/// in a number of different output formats.
+pub struct Writer {
+    wtr: LineWriter<Box<dyn io::Write + 'static>>,
+    wrote_header: bool,
+    table_lens: Vec<(String, usize)>,
+    out_files: BTreeSet<String>,
+    rs_file: Option<(PathBuf, SharedBuf)>,
+    pending_slice: Option<PendingSlice>,
+    /// The names given to `names`, if it was called.
+    by_name: Option<Vec<String>>,
+    /// The names of the sets written by `ranges` so far.
+    emitted_sets: BTreeSet<String>,
+    /// The names of the sets written by `ranges` as tries, when the format
+    /// of each set is chosen automatically.
+    trie_sets: BTreeSet<String>,
+    /// The constant names of the sets written by `ranges` as slices of
+    /// `(u16, u16)` pairs.
+    u16_sets: BTreeSet<String>,
+    /// The sets held back by `ranges` until `finish`, so that they can all
+    /// be written in the same automatically chosen format.
+    pending_sets: Vec<(String, BTreeSet<u32>)>,
+    /// The ranges of every set written by `ranges` with the wasm profile,
+    /// which are written as `ALL_RANGES` by `finish`.
+    wasm_ranges: Vec<(u32, u32)>,
+    /// The bounds of each set written with the wasm profile in
+    /// `wasm_ranges`, by the name given to `ranges`.
+    wasm_sets: BTreeMap<String, (usize, usize)>,
+    /// The name of the slice table currently being written with the wasm
+    /// profile, along with the size of each of its entries, if known.
+    wasm_slice: Option<(String, Option<usize>)>,
+    /// The name and size in bytes of every table written with the wasm
+    /// profile. The size is `None` for tables whose entries contain
+    /// references or types whose size isn't known.
+    data_sizes: Vec<(String, Option<usize>)>,
+    /// The names of the FSTs that were split into shards.
+    sharded: BTreeSet<String>,
+    /// Whether the record types used by binary tables have been written.
+    wrote_binary_prelude: bool,
+    /// Whether the helpers used by tests written by `ranges_test` have been
+    /// written.
+    wrote_test_prelude: bool,
+    /// Whether the `NonZeroU32` import used by `codepoint_to_codepoint_fn`
+    /// has been written.
+    wrote_nonzero_use: bool,
+    /// When the previous table was written, used to report how long each
+    /// table took with --verbose.
+    last_table: Instant,
+    /// The path and contents of every FST written, if they are to be
+    /// verified by `finish`.
+    written_fsts: Vec<(PathBuf, Vec<u8>)>,
+    /// The variants of each enum in the enum manifest, in the order in which
+    /// they were first seen. This is loaded when the first enum is written.
+    manifest: Option<BTreeMap<String, Vec<String>>>,
+    /// The string pool that enum value names are interned in, along with
+    /// the path it is written to. This is loaded when the first name is
+    /// interned.
+    pool: Option<(PathBuf, String)>,
+    /// The files written to the staging directory so far, as pairs of their
+    /// final path and their path in the staging directory.
+    staged: Vec<(PathBuf, PathBuf)>,
+    /// The directory that files are written to before `finish` moves them
+    /// into place, created when the first file is staged.
+    staging: Option<TempDir>,
+    opts: WriterOptions,
+}
+
+/// A slice table whose entries are being buffered, so that they can be
+/// moved into a data file once we know how many there are.
+struct PendingSlice {
+    name: String,
+    ty: String,
+    wtr: Box<dyn io::Write + 'static>,
+    buf: SharedBuf,
+}
+
+impl Writer {
+    /// Finish writing.
+    ///
+    /// This emits any items that depend on all of the tables written so far
+    /// (such as the `BY_NAME` index and the `TABLE_LENS` manifest) and
+    /// flushes the underlying writer.
+    /// When writing to a directory, this is also when the Rust source file is
+    /// written, if its contents changed. Callers should call this exactly
+    /// once, after all tables have been written.
+    pub fn finish(&mut self) -> Result<()> {
+        self.write_pending_sets()?;
+        if !self.wasm_sets.is_empty() {
+            self.wasm_all_ranges()?;
+        }
+        if let Some(names) = self.by_name.take() {
+            let names = names
+                .into_iter()
+                .filter(|name| self.emitted_sets.contains(name))
+                .collect();
+            if self.opts.wasm {
+                if self.opts.by_name_fn {
+                    return err!(
+                        "--by-name-fn can't be used with the wasm profile, \
+                         which always writes a by_name function"
+                    );
+                }
+                self.wasm_by_name(names)?;
+            } else {
+                self.by_name_index(names)?;
             }
-            self.wtr.write_str(&format!(
-                "{} => Some(NonZeroU32::new_unchecked({})),",
-                from, to
-            ))?;
-            self.wtr.flush_line()?;
         }
-        self.wtr.write_str("_ => None,")?;
-        self.wtr.flush_line()?;
-        self.wtr.indent("        ");
-        self.wtr.write_str("}")?;
-        self.wtr.flush_line()?;
-        self.wtr.indent("    ");
-        self.wtr.write_str("}")?;
-        self.wtr.flush_line()?;
-        writeln!(self.wtr, "}}")?;
+        if self.opts.emit_len_consts && self.table_lens.len() > 1 {
+            self.separator()?;
+            writeln!(
+                self.wtr,
+                "pub const TABLE_LENS: &'static [(&'static str, usize)] = &[",
+            )?;
+            let lens = std::mem::take(&mut self.table_lens);
+            for (name, _) in &lens {
+                self.wtr
+                    .write_str(&format!("({:?}, {}_LEN), ", name, name))?;
+            }
+            writeln!(self.wtr, "];")?;
+        }
+        if self.opts.wasm && self.wrote_header {
+            self.wasm_data_size()?;
+        }
         self.wtr.flush()?;
+        if let (Some(path), Some(manifest)) =
+            (self.opts.enum_manifest.as_ref(), self.manifest.as_ref())
+        {
+            let mut contents = String::new();
+            for (enum_name, variants) in manifest {
+                for variant in variants {
+                    contents.push_str(&format!("{} {}\n", enum_name, variant));
+                }
+            }
+            if self.opts.check {
+                let path = path.clone();
+                self.stage(&path, contents.as_bytes())?;
+            } else if write_if_changed(path, contents.as_bytes(), false)?
+                && !self.opts.quiet
+            {
+                eprintln!("wrote {}", path.display());
+            }
+        }
+        if let Some((path, buf)) = self.rs_file.take() {
+            let contents = buf.0.borrow();
+            self.stage(&path, &contents)?;
+        }
+        if let Some((path, pool)) = self.pool.take() {
+            let contents = self.string_pool_module(&pool);
+            self.stage(&path, contents.as_bytes())?;
+        }
+        if self.opts.manifest {
+            self.stage_manifest()?;
+        }
+        if self.opts.check {
+            return self.check_staged();
+        }
+        // Only move files into place once every one of them has been
+        // written, so that a failure can't leave a mix of old and new files.
+        for (path, tmp_path) in std::mem::take(&mut self.staged) {
+            if rename_if_changed(&tmp_path, &path, self.opts.force_write)?
+                && !self.opts.quiet
+            {
+                eprintln!("wrote {}", path.display());
+            }
+        }
+        self.staging = None;
+        for (path, bytes) in std::mem::take(&mut self.written_fsts) {
+            verify_fst_file(&path, bytes)?;
+        }
         Ok(())
     }
 
-    /// Write a map that associates codepoints with other codepoints, where
-    /// each codepoint can be associated with possibly many other codepoints.
-    ///
-    /// This does not support the FST format.
-    pub fn multi_codepoint_to_codepoint(
-        &mut self,
-        name: &str,
-        map: &BTreeMap<u32, BTreeSet<u32>>,
-        emit_flat_table: bool,
-    ) -> Result<()> {
-        if self.opts.fst_dir.is_some() {
-            return err!("cannot emit codepoint multimaps as an FST");
+    /// Compare every staged file with the file it would replace, and print
+    /// a diff of each one that differs. Returns an error if any differ.
+    fn check_staged(&mut self) -> Result<()> {
+        util::set_checked();
+        let mut stale = 0;
+        for (path, tmp_path) in std::mem::take(&mut self.staged) {
+            if files_equal(&tmp_path, &path)? {
+                continue;
+            }
+            stale += 1;
+            let (name, old) = match fs::read(&path) {
+                Ok(old) => (path.display().to_string(), old),
+                Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+                    ("/dev/null".to_string(), vec![])
+                }
+                Err(err) => return Err(err.into()),
+            };
+            let diff = util::unified_diff(
+                &name,
+                &format!("{} (generated)", path.display()),
+                &old,
+                &fs::read(&tmp_path)?,
+            );
+            let mut stdout = io::stdout();
+            stdout.write_all(diff.as_bytes())?;
+            stdout.flush()?;
         }
+        self.staging = None;
+        if stale > 0 {
+            return err!("{} file(s) are out of date", stale);
+        }
+        Ok(())
+    }
 
-        let mut map2: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
-        for (&k, vs) in map {
-            let vs2 = vs.iter().cloned().collect();
-            map2.insert(k, vs2);
+    /// Return the path in the staging directory to write the file that will
+    /// end up at `path`.
+    fn stage_path(&mut self, path: &Path) -> Result<PathBuf> {
+        if self.staging.is_none() {
+            // When checking, nothing is written next to the files being
+            // checked.
+            let dir = if self.opts.check {
+                std::env::temp_dir()
+            } else {
+                path.parent().unwrap_or(Path::new(".")).to_path_buf()
+            };
+            self.staging = Some(TempDir::new(dir.join(format!(
+                ".{}.tmp-{}",
+                rust_module_name(&self.opts.name),
+                std::process::id()
+            )))?);
         }
-        self.codepoint_to_codepoints(name, &map2, emit_flat_table)
+        let tmp_path =
+            self.staging.as_ref().unwrap().0.join(path.file_name().unwrap());
+        self.staged.push((path.to_path_buf(), tmp_path.clone()));
+        Ok(tmp_path)
     }
 
-    /// Write a map that associates codepoints with a sequence of other
-    /// codepoints.
+    /// Write the given contents to the staging directory. They are moved to
+    /// `path` by `finish`.
+    fn stage(&mut self, path: &Path, contents: &[u8]) -> Result<()> {
+        let tmp_path = self.stage_path(path)?;
+        fs::write(tmp_path, contents)?;
+        Ok(())
+    }
+
+    /// Stage a `manifest.json` that describes every staged file, along with
+    /// the files described by an existing manifest that weren't staged.
+    fn stage_manifest(&mut self) -> Result<()> {
+        let out_dir = match self.opts.out_dir {
+            None => return err!("a manifest requires an output directory"),
+            Some(ref out_dir) => out_dir.clone(),
+        };
+        let command = util::json_string(&command_line()?);
+        let version = match self.opts.ucd_version {
+            None => "null".to_string(),
+            Some((major, minor, patch)) => {
+                format!("\"{}.{}.{}\"", major, minor, patch)
+            }
+        };
+        let mut entries = BTreeMap::new();
+        for (path, tmp_path) in &self.staged {
+            let name = path.file_name().unwrap().to_string_lossy();
+            let contents = fs::read(tmp_path)?;
+            let hash: String = sha256(&contents)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            entries.insert(
+                name.to_string(),
+                format!(
+                    "{{\"name\": {}, \"size\": {}, \"sha256\": \"{}\", \
+                     \"command\": {}, \"unicode_version\": {}}}",
+                    util::json_string(&name),
+                    contents.len(),
+                    hash,
+                    command,
+                    version,
+                ),
+            );
+        }
+        // Each entry is on its own line, so entries from other commands can
+        // be kept without a JSON parser.
+        let path = out_dir.join("manifest.json");
+        match fs::read_to_string(&path) {
+            Ok(existing) => {
+                for line in existing.lines() {
+                    let entry = line.trim().trim_end_matches(',');
+                    let name = match manifest_entry_name(entry) {
+                        None => continue,
+                        Some(name) => name,
+                    };
+                    entries.entry(name).or_insert_with(|| entry.to_string());
+                }
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        let mut contents = String::from("{\n  \"files\": [\n");
+        for (i, entry) in entries.values().enumerate() {
+            let comma = if i + 1 == entries.len() { "" } else { "," };
+            contents.push_str(&format!("    {}{}\n", entry, comma));
+        }
+        contents.push_str("  ]\n}\n");
+        self.stage(&path, contents.as_bytes())
+    }
+
+    /// Emit a `BY_NAME` index, which maps each of the given names to the
+    /// set of the same name, sorted by name.
     ///
-    /// This does not support the FST format.
-    pub fn codepoint_to_codepoints(
+    /// Names are sorted by their bytes, so the index can be searched with
+    /// `binary_search_by_key`, and an entry's position only changes when
+    /// names are added or removed.
+    ///
+    /// The index is written by `finish`, and only includes the names of sets
+    /// that were actually written with `ranges`, so this may be called before
+    /// or after writing them.
+    ///
+    /// When writing tables for regex-syntax, the index is instead written
+    /// immediately and includes every given name, as in regex-syntax's own
+    /// tables. It must then be called before writing the sets.
+    pub fn names<I: IntoIterator<Item = T>, T: AsRef<str>>(
         &mut self,
-        name: &str,
-        map: &BTreeMap<u32, Vec<u32>>,
-        emit_flat_table: bool,
+        names: I,
     ) -> Result<()> {
-        if self.opts.fst_dir.is_some() {
-            return err!("cannot emit codepoint->codepoints map as an FST");
+        self.header()?;
+        let names = names.into_iter().map(|name| name.as_ref().to_string());
+        if self.opts.regex_syntax {
+            return self.by_name_index(names.collect());
         }
+        self.by_name.get_or_insert_with(Vec::new).extend(names);
+        Ok(())
+    }
 
-        self.header()?;
+    fn by_name_index(&mut self, names: BTreeSet<String>) -> Result<()> {
+        if self.opts.by_name_fn
+            && (self.opts.c_output || self.opts.fst_dir.is_some())
+        {
+            return err!(
+                "--by-name-fn can only be used when writing Rust slices"
+            );
+        }
         self.separator()?;
-
-        let name = rust_const_name(name);
-        let ty = self.rust_codepoint_type();
-        if !emit_flat_table {
-            writeln!(
-                self.wtr,
-                "pub const {}: &'static [({}, &'static [{}])] = &[",
-                name, ty, ty
-            )?;
-        } else {
-            writeln!(
-                self.wtr,
-                "pub const {}: &'static [({}, [{}; 3])] = &[",
-                name, ty, ty
-            )?;
+        self.ensure_nonempty("BY_NAME", names.len())?;
+        let mut set_ty = String::new();
+        let mut trie_set = self.opts.trie_set;
+        if self.opts.optimize.is_some() {
+            let tries = names
+                .iter()
+                .filter(|n| self.trie_sets.contains(&rust_const_name(n)))
+                .count();
+            if tries != 0 && tries != names.len() {
+                return err!(
+                    "cannot emit BY_NAME, since {} of its {} sets were \
+                     written as tries and the rest as ranges",
+                    tries,
+                    names.len(),
+                );
+            }
+            trie_set = tries != 0;
         }
-        'LOOP: for (&k, vs) in map {
-            // Make sure both our keys and values can be represented in the
-            // user's chosen codepoint format.
-            let kstr = match self.rust_codepoint(k) {
-                None => continue 'LOOP,
-                Some(k) => k,
-            };
-
-            let (padded_vs, slice_prefix) = if emit_flat_table {
-                // These checks are for future-proofing and cannot be hit currently.
-                if vs.len() > 3 {
-                    return err!(
-                        "flat-table representation cannot be used when value \
-                         arrays may contain more than 3 entries"
-                    );
+        let u16s = names
+            .iter()
+            .filter(|n| self.u16_sets.contains(&rust_const_name(n)))
+            .count();
+        if u16s != 0 && u16s != names.len() {
+            return err!(
+                "cannot emit BY_NAME, since {} of its {} sets were written \
+                 as u16 ranges and the rest as u32 ranges",
+                u16s,
+                names.len(),
+            );
+        }
+        let reference = if self.opts.c_output {
+            // C doesn't permit empty arrays.
+            if names.is_empty() {
+                return Ok(());
+            }
+            writeln!(self.wtr, "static const struct {{")?;
+            writeln!(self.wtr, "  const char *name;")?;
+            writeln!(self.wtr, "  const struct ucd_trie_set *set;")?;
+            writeln!(self.wtr, "}} BY_NAME[] = {{")?;
+            "&"
+        } else if self.opts.fst_dir.is_some() {
+            let mut consts = names.iter().map(|n| rust_const_name(n));
+            if let Some(name) = consts.find(|n| self.sharded.contains(n)) {
+                return err!(
+                    "cannot emit BY_NAME, since {} is split into shards \
+                     (try a bigger --fst-max-file-size)",
+                    name
+                );
+            }
+            let set = "::fst::Set<&'static [u8]>";
+            match self.opts.fst_loader {
+                // A const can't refer to a static, so the index of lazily
+                // initialized statics must be a static too.
+                FstLoader::OnceCell => {
+                    writeln!(
+                        self.wtr,
+                        "pub static BY_NAME: &'static [(\n  \
+                         &'static str,\n  \
+                         &'static ::once_cell::sync::Lazy<{}>,\n\
+                         )] = &[",
+                        set
+                    )?;
+                    "&"
                 }
-                let flat_padding =
-                    if self.opts.char_literals { 0 } else { !0 };
-                if vs.contains(&flat_padding) {
+                FstLoader::LazyStatic => {
                     return err!(
-                        "flat-table --chars representation cannot be used when \
-                         the NUL character is present in the value array. (This \
-                         error probably can be fixed by removing `--chars`)"
+                        "cannot emit BY_NAME with --loader lazy_static, \
+                         since the types of its statics can't be named"
                     );
                 }
-                let res = vs
-                    .iter()
-                    .copied()
-                    .chain(std::iter::repeat(flat_padding))
-                    .take(3)
-                    .collect::<Vec<_>>();
-                (res, "")
+                FstLoader::OnceLock | FstLoader::Fn => {
+                    let reference = if self.opts.fst_loader == FstLoader::Fn {
+                        ""
+                    } else {
+                        "&'static "
+                    };
+                    writeln!(
+                        self.wtr,
+                        "pub const BY_NAME: &'static [(\n  \
+                         &'static str,\n  \
+                         fn() -> {}{},\n\
+                         )] = &[",
+                        reference, set
+                    )?;
+                    ""
+                }
+            }
+        } else {
+            let ty = if self.opts.binary {
+                "fn() -> &'static [Range]".to_string()
+            } else if trie_set {
+                "&'static ::ucd_trie::TrieSet".to_string()
+            } else if self.opts.css_unicode_range {
+                "&'static str".to_string()
+            } else if self.opts.group_by_plane {
+                let charty = self.rust_codepoint_type();
+                format!("&'static [(u8, &'static [({}, {})])]", charty, charty)
             } else {
-                (vs.clone(), "&")
+                let charty =
+                    if u16s != 0 { "u16" } else { self.rust_codepoint_type() };
+                format!("&'static [({}, {})]", charty, charty)
             };
-            let mut vstrs = vec![];
-            for v in padded_vs {
-                match self.rust_codepoint(v) {
-                    None => continue 'LOOP,
-                    Some(v) => vstrs.push(v),
-                }
+            writeln!(
+                self.wtr,
+                "pub const BY_NAME: &'static [(&'static str, {})] = &[",
+                ty,
+            )?;
+            set_ty = ty;
+            ""
+        };
+        let (open, close) =
+            if self.opts.c_output { ("{", "}") } else { ("(", ")") };
+        for name in &names {
+            let mut rustname = rust_const_name(name);
+            if self.opts.binary
+                || (self.opts.fst_dir.is_some()
+                    && self.opts.fst_loader != FstLoader::OnceCell)
+            {
+                // These sets are viewed through their functions.
+                rustname = rust_fn_name(&rustname);
+            } else if self.opts.group_by_plane
+                && !trie_set
+                && !self.opts.css_unicode_range
+            {
+                rustname.push_str("_PLANES");
+            }
+            self.wtr.write_str(&format!(
+                "{}{:?}, {}{}{}, ",
+                open, name, reference, rustname, close
+            ))?;
+            if self.opts.regex_syntax {
+                // regex-syntax puts each entry on its own line.
+                self.wtr.flush_line()?;
             }
+        }
+        writeln!(self.wtr, "{};", if self.opts.c_output { "}" } else { "]" })?;
+        self.len_const("BY_NAME", names.len())?;
+        if self.opts.c_output && self.opts.c_enum {
+            self.c_enum(&names)?;
+        }
+        if self.opts.by_name_fn {
+            self.set_by_name(&names, &set_ty)?;
+        }
+        Ok(())
+    }
 
-            self.wtr.write_str(&format!("({}, {}[", kstr, slice_prefix))?;
-            if vstrs.len() == 1 {
-                self.wtr.write_str(&format!("{}", &vstrs[0]))?;
-            } else {
-                for v in vstrs {
-                    self.wtr.write_str(&format!("{}, ", v))?;
-                }
+    /// Write the `set_by_name` function that follows the `BY_NAME` index of
+    /// the given names, whose sets have the given type.
+    ///
+    /// It searches a `BY_NAME_LOOSE` index from each normalized name to the
+    /// position of its entry in `BY_NAME`, normalizing the query the same way
+    /// as the `FromStr` impls written by `property_value_enum`.
+    fn set_by_name(
+        &mut self,
+        names: &BTreeSet<String>,
+        ty: &str,
+    ) -> Result<()> {
+        let mut loose = BTreeMap::new();
+        for (i, name) in names.iter().enumerate() {
+            let mut key = name.clone();
+            ucd_util::symbolic_name_normalize(&mut key);
+            if let Some(j) = loose.insert(key, i) {
+                let other = names.iter().nth(j).unwrap();
+                return err!(
+                    "cannot emit set_by_name, since {:?} and {:?} are the \
+                     same name when matched loosely",
+                    other,
+                    name
+                );
             }
-            self.wtr.write_str("]), ")?;
         }
-        writeln!(self.wtr, "];")?;
+        self.separator()?;
+        self.slice_start("BY_NAME_LOOSE", "(&'static str, usize)")?;
+        for (key, i) in &loose {
+            self.wtr.write_str(&format!("({:?}, {}), ", key, i))?;
+        }
+        self.slice_end(loose.len())?;
+        self.len_const("BY_NAME_LOOSE", loose.len())?;
+        writeln!(self.wtr)?;
 
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "/// Return the set in `BY_NAME` with the given name, which is \
+             matched"
+        )?;
+        writeln!(
+            self.wtr,
+            "/// loosely as in UAX44-LM3, e.g., `Upper_Case` finds \
+             `Uppercase`."
+        )?;
+        writeln!(
+            self.wtr,
+            "pub fn set_by_name(name: &str) -> Option<{}> {{",
+            ty
+        )?;
+        writeln!(self.wtr, "    let mut s = name.as_bytes();")?;
+        writeln!(
+            self.wtr,
+            "    if s.len() > 2 && s[..2].eq_ignore_ascii_case(b\"is\") {{"
+        )?;
+        writeln!(self.wtr, "        s = &s[2..];")?;
+        writeln!(self.wtr, "    }}")?;
+        writeln!(self.wtr, "    let query = s")?;
+        writeln!(self.wtr, "        .iter()")?;
+        writeln!(
+            self.wtr,
+            "        .filter(|&&b| b.is_ascii() && !b\" _-\".contains(&b))"
+        )?;
+        writeln!(self.wtr, "        .map(|b| b.to_ascii_lowercase());")?;
+        writeln!(self.wtr, "    BY_NAME_LOOSE")?;
+        writeln!(
+            self.wtr,
+            "        .binary_search_by(|&(k, _)| k.bytes().cmp(query.clone()))"
+        )?;
+        writeln!(self.wtr, "        .ok()")?;
+        writeln!(self.wtr, "        .map(|i| BY_NAME[BY_NAME_LOOSE[i].1].1)")?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
         self.wtr.flush()?;
         Ok(())
     }
 
-    /// Write a map that associates codepoints to strings.
+    /// Write a test of the set with the given name, as written by `ranges`
+    /// to the module at the given path, e.g., `my_crate::tables::prop_list`.
     ///
-    /// When the output format is an FST, then the FST map emitted is from
-    /// codepoint to u64, where the string is encoded into the u64. The least
-    /// significant byte of the u64 corresponds to the first byte in the
-    /// string. The end of a string is delimited by the zero byte. If a string
-    /// is more than 8 bytes or contains a `NUL` byte, then an error is
-    /// returned.
-    pub fn codepoint_to_string(
+    /// The test checks that the table is sorted, that it has the same number
+    /// of ranges and codepoints as the given set, and that each of the given
+    /// sample codepoints is in the table exactly when it is in the set. This
+    /// catches manual edits to the table, as well as a table that was
+    /// regenerated from a different version of the UCD than its tests.
+    pub fn ranges_test(
         &mut self,
         name: &str,
-        map: &BTreeMap<u32, String>,
+        module: &str,
+        codepoints: &BTreeSet<u32>,
+        samples: &BTreeSet<u32>,
     ) -> Result<()> {
         self.header()?;
         self.separator()?;
+        if !self.wrote_test_prelude {
+            self.wrote_test_prelude = true;
+            write!(self.wtr, "{}", TEST_PRELUDE)?;
+            self.separator()?;
+        }
 
-        let name = rust_const_name(name);
-        if self.opts.fst_dir.is_some() {
-            let mut builder = MapBuilder::memory();
-            for (&k, v) in map {
-                let v = pack_str(v)?;
-                builder.insert(u32_key(k), v)?;
-            }
-            let map = builder.into_map();
-            self.fst(&name, map.as_fst(), true)?;
-        } else {
-            let table: Vec<(u32, &str)> =
-                map.iter().map(|(&k, v)| (k, &**v)).collect();
-            self.codepoint_to_string_slice(&name, &table)?;
-        }
+        let const_name = rust_const_name(name);
+        let table = format!("{}::{}", module, const_name);
+        let ranges = util::to_ranges(codepoints.iter().cloned());
+        let hex = |cps: Vec<u32>| -> String {
+            let cps: Vec<String> =
+                cps.into_iter().map(|cp| format!("0x{:X}", cp)).collect();
+            cps.join(", ")
+        };
+        let (members, others): (Vec<u32>, Vec<u32>) =
+            samples.iter().partition(|cp| codepoints.contains(cp));
+
+        writeln!(self.wtr, "#[test]")?;
+        writeln!(self.wtr, "fn {}() {{", rust_fn_name(&const_name))?;
+        writeln!(self.wtr, "  let ranges = ranges({});", table)?;
+        writeln!(
+            self.wtr,
+            "  assert!(is_sorted(&ranges), \"{} is not sorted\");",
+            const_name
+        )?;
+        writeln!(
+            self.wtr,
+            "  assert_eq!(ranges.len(), {}, \"number of ranges in {}\");",
+            ranges.len(),
+            const_name
+        )?;
+        writeln!(
+            self.wtr,
+            "  assert_eq!(count(&ranges), {}, \"number of codepoints in {}\");",
+            codepoints.len(),
+            const_name
+        )?;
+        writeln!(self.wtr, "  let members: &[u32] = &[{}];", hex(members))?;
+        writeln!(self.wtr, "  for &cp in members {{")?;
+        writeln!(
+            self.wtr,
+            "    assert!(contains(&ranges, cp), \"U+{{:04X}} should be in {}\", cp);",
+            const_name
+        )?;
+        writeln!(self.wtr, "  }}")?;
+        writeln!(self.wtr, "  let others: &[u32] = &[{}];", hex(others))?;
+        writeln!(self.wtr, "  for &cp in others {{")?;
+        writeln!(
+            self.wtr,
+            "    assert!(!contains(&ranges, cp), \"U+{{:04X}} should not be in {}\", cp);",
+            const_name
+        )?;
+        writeln!(self.wtr, "  }}")?;
+        writeln!(self.wtr, "}}")?;
         self.wtr.flush()?;
         Ok(())
     }
 
-    fn codepoint_to_string_slice(
+    /// Write a sorted sequence of codepoints.
+    ///
+    /// Note that the specific representation of ranges may differ with the
+    /// output format. For example, if the output format is a slice, then a
+    /// straight-forward slice of sorted codepoint ranges is emitted. But if
+    /// the output format is an FST or similar, then all codepoints are
+    /// explicitly represented.
+    ///
+    /// When the format is chosen automatically, every set in the `BY_NAME`
+    /// index must have the same format. Those sets are therefore held back
+    /// and written together by `finish`, in one format chosen for all of
+    /// them.
+    pub fn ranges(
         &mut self,
         name: &str,
-        table: &[(u32, &str)],
+        codepoints: &BTreeSet<u32>,
+    ) -> Result<()> {
+        let codepoints = self.kept_set(codepoints);
+        let codepoints = &*codepoints;
+        self.ensure_nonempty(name, codepoints.len())?;
+        self.header()?;
+        if self.opts.emit_iter
+            && (self.opts.fst_dir.is_some() || self.opts.trie_set)
+        {
+            return err!("cannot emit an iterator over an FST or a trie");
+        }
+        if self.opts.css_unicode_range
+            && (self.opts.emit_iter || self.opts.wasm)
+        {
+            return err!(
+                "cannot emit an iterator or wasm tables with CSS unicode \
+                 ranges"
+            );
+        }
+        if self.opts.wasm {
+            return self.wasm_set(name, codepoints);
+        }
+        let indexed = self
+            .by_name
+            .as_ref()
+            .is_some_and(|names| names.iter().any(|n| n == name));
+        if (self.opts.optimize.is_some() || self.opts.u16_ranges) && indexed {
+            self.pending_sets.push((name.to_string(), codepoints.clone()));
+            return Ok(());
+        }
+        self.separator()?;
+        let sets = [(name, codepoints)];
+        let trie = self.choose_tries(&sets)?;
+        let u16 = self.fits_u16(&sets);
+        self.write_set(name, codepoints, trie.and_then(|mut t| t.pop()), u16)
+    }
+
+    /// Write the given set as the bounds of its ranges in `ALL_RANGES`.
+    fn wasm_set(
+        &mut self,
+        name: &str,
+        codepoints: &BTreeSet<u32>,
     ) -> Result<()> {
+        if self.opts.fst_dir.is_some()
+            || self.opts.trie_set
+            || self.opts.optimize.is_some()
+            || self.opts.binary
+            || self.opts.group_by_plane
+            || self.opts.emit_iter
+            || self.opts.c_output
+        {
+            return err!(
+                "the wasm profile always writes sets as ranges of indices \
+                 into ALL_RANGES"
+            );
+        }
+        self.separator()?;
+        self.emitted_sets.insert(name.to_string());
+        let start = self.wasm_ranges.len();
+        self.wasm_ranges.extend(util::to_ranges(codepoints.iter().cloned()));
+        let end = self.wasm_ranges.len();
+        self.wasm_sets.insert(name.to_string(), (start, end));
+        writeln!(
+            self.wtr,
+            "pub const {}: ::core::ops::Range<usize> = {}..{};",
+            rust_const_name(name),
+            start,
+            end,
+        )?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write the `ALL_RANGES` slice that sets written with the wasm profile
+    /// refer to, along with a function to look them up.
+    fn wasm_all_ranges(&mut self) -> Result<()> {
+        let ranges = std::mem::take(&mut self.wasm_ranges);
+        self.separator()?;
+        self.ranges_slice("ALL_RANGES", &ranges)?;
         let ty = self.rust_codepoint_type();
+        writeln!(self.wtr)?;
         writeln!(
             self.wtr,
-            "pub const {}: &'static [({}, &'static str)] = &[",
-            name, ty
+            "/// Return the ranges of the set with the given bounds in \
+             `ALL_RANGES`."
         )?;
-        for &(cp, ref s) in table {
-            if let Some(cp) = self.rust_codepoint(cp) {
-                self.wtr.write_str(&format!("({}, {:?}), ", cp, s))?;
+        writeln!(
+            self.wtr,
+            "pub fn ranges(set: ::core::ops::Range<usize>) \
+             -> &'static [({}, {})] {{",
+            ty, ty
+        )?;
+        writeln!(self.wtr, "  &ALL_RANGES[set]")?;
+        writeln!(self.wtr, "}}")?;
+        Ok(())
+    }
+
+    /// Write the `BY_NAME` index of sets written with the wasm profile.
+    ///
+    /// Names are written to a single `BY_NAME_NAMES` string, and each entry
+    /// of the index holds the bounds of a name in that string followed by
+    /// the bounds of its set in `ALL_RANGES`.
+    fn wasm_by_name(&mut self, names: BTreeSet<String>) -> Result<()> {
+        self.separator()?;
+        self.ensure_nonempty("BY_NAME", names.len())?;
+        let mut all_names = String::new();
+        let mut entries = vec![];
+        for name in &names {
+            let (start, end) = self.wasm_sets[name];
+            let name_start = all_names.len();
+            all_names.push_str(name);
+            entries.push((name_start, all_names.len(), start, end));
+        }
+        writeln!(
+            self.wtr,
+            "pub const BY_NAME_NAMES: &'static str = {:?};",
+            all_names
+        )?;
+        self.data_sizes
+            .push(("BY_NAME_NAMES".to_string(), Some(all_names.len())));
+        self.slice_start("BY_NAME", "(u32, u32, u32, u32)")?;
+        for &(name_start, name_end, start, end) in &entries {
+            self.wtr.write_str(&format!(
+                "({}, {}, {}, {}), ",
+                name_start, name_end, start, end
+            ))?;
+        }
+        self.slice_end(entries.len())?;
+        self.len_const("BY_NAME", entries.len())?;
+        let ty = self.rust_codepoint_type();
+        writeln!(self.wtr)?;
+        writeln!(
+            self.wtr,
+            "/// Return the ranges of the set with the given name, if there is \
+             one."
+        )?;
+        writeln!(
+            self.wtr,
+            "pub fn by_name(name: &str) -> Option<&'static [({}, {})]> {{",
+            ty, ty
+        )?;
+        writeln!(self.wtr, "  let i = BY_NAME")?;
+        writeln!(self.wtr, "    .binary_search_by(|&(s, e, _, _)| {{")?;
+        writeln!(
+            self.wtr,
+            "      BY_NAME_NAMES[s as usize..e as usize].cmp(name)"
+        )?;
+        writeln!(self.wtr, "    }})")?;
+        writeln!(self.wtr, "    .ok()?;")?;
+        writeln!(self.wtr, "  let (_, _, start, end) = BY_NAME[i];")?;
+        writeln!(
+            self.wtr,
+            "  Some(&ALL_RANGES[start as usize..end as usize])"
+        )?;
+        writeln!(self.wtr, "}}")?;
+        Ok(())
+    }
+
+    /// Write the total size of the tables written with the wasm profile as
+    /// a comment, and report it on stderr unless quiet.
+    fn wasm_data_size(&mut self) -> Result<()> {
+        let sizes = std::mem::take(&mut self.data_sizes);
+        let total: usize = sizes.iter().filter_map(|&(_, size)| size).sum();
+        let counted = sizes.iter().filter(|(_, size)| size.is_some()).count();
+        let uncounted: Vec<&str> = sizes
+            .iter()
+            .filter(|(_, size)| size.is_none())
+            .map(|(name, _)| name.as_str())
+            .collect();
+        self.separator()?;
+        let summary =
+            format!("Total data size: {} bytes in {} tables.", total, counted);
+        writeln!(self.wtr, "// {}", summary)?;
+        if !uncounted.is_empty() {
+            writeln!(
+                self.wtr,
+                "// Not counted, since their entries contain references or \
+                 have an unknown size: {}.",
+                uncounted.join(", ")
+            )?;
+        }
+        if !self.opts.quiet {
+            eprintln!("{}: {}", self.opts.name, summary);
+        }
+        Ok(())
+    }
+
+    /// Write every set held back by `ranges`, in the format chosen for all
+    /// of them.
+    fn write_pending_sets(&mut self) -> Result<()> {
+        let pending = std::mem::take(&mut self.pending_sets);
+        if pending.is_empty() {
+            return Ok(());
+        }
+        self.separator()?;
+        let sets: Vec<(&str, &BTreeSet<u32>)> =
+            pending.iter().map(|(name, set)| (name.as_str(), set)).collect();
+        let mut tries = self.choose_tries(&sets)?.map(Vec::into_iter);
+        let u16 = self.fits_u16(&sets);
+        for (i, (name, set)) in pending.iter().enumerate() {
+            if i > 0 {
+                self.separator()?;
             }
+            let trie = tries.as_mut().and_then(|t| t.next());
+            self.write_set(name, set, trie, u16)?;
         }
-        writeln!(self.wtr, "];")?;
         Ok(())
     }
 
-    /// Write a map that associates strings to codepoints.
-    pub fn string_to_codepoint(
+    /// Write the given set, as the given trie if there is one.
+    ///
+    /// When it is written as a slice of ranges, `u16` says whether to write
+    /// them as `u16` pairs, as returned by `fits_u16`.
+    fn write_set(
         &mut self,
         name: &str,
-        map: &BTreeMap<String, u32>,
+        codepoints: &BTreeSet<u32>,
+        trie: Option<TrieSetOwned>,
+        u16: bool,
     ) -> Result<()> {
-        self.header()?;
-        self.separator()?;
-
+        self.emitted_sets.insert(name.to_string());
         let name = rust_const_name(name);
         if self.opts.fst_dir.is_some() {
-            let mut builder = MapBuilder::memory();
-            for (k, &v) in map {
-                builder.insert(k.as_bytes(), v as u64)?;
+            self.ensure_not_c("an FST")?;
+            let mut builder = SetBuilder::memory();
+            builder.extend_iter(codepoints.iter().cloned().map(u32_key))?;
+            let set = builder.into_set();
+            self.fst(&name, set.as_fst(), false)?;
+        } else if self.opts.binary {
+            self.ensure_not_c("a binary table")?;
+            let ranges = util::to_ranges(codepoints.iter().cloned());
+            let records: Vec<Vec<u32>> =
+                ranges.iter().map(|&(s, e)| vec![s, e]).collect();
+            self.binary_table(&name, "Range", &records)?;
+        } else if let Some(trie) = trie {
+            self.trie_sets.insert(name.clone());
+            if self.opts.c_output {
+                self.trie_set_c(&name, &trie)?;
+            } else {
+                self.trie_set(&name, &trie)?;
             }
-            let map = builder.into_map();
-            self.fst(&name, map.as_fst(), true)?;
         } else {
-            let table: Vec<(&str, u32)> =
-                map.iter().map(|(k, &v)| (&**k, v)).collect();
-            self.string_to_codepoint_slice(&name, &table)?;
+            self.ensure_not_c("codepoint ranges without a trie")?;
+            let ranges = util::to_ranges(codepoints.iter().cloned());
+            if self.opts.css_unicode_range {
+                self.css_unicode_range(&name, &ranges)?;
+            } else if self.opts.group_by_plane {
+                self.ranges_by_plane(&name, &ranges)?;
+            } else if u16 {
+                self.u16_sets.insert(name.clone());
+                self.ranges_slice_of("u16", &name, &ranges)?;
+            } else {
+                self.ranges_slice(&name, &ranges)?;
+            }
+        }
+        if self.opts.emit_iter {
+            self.ranges_iter(&name)?;
         }
         self.wtr.flush()?;
         Ok(())
     }
 
-    fn string_to_codepoint_slice(
+    /// Return whether the given sets should be written as slices of `u16`
+    /// pairs if they're written as slices of ranges, which is when that's
+    /// enabled and every one of their codepoints fits.
+    fn fits_u16(&self, sets: &[(&str, &BTreeSet<u32>)]) -> bool {
+        self.opts.u16_ranges
+            && !self.opts.char_literals
+            && sets
+                .iter()
+                .all(|(_, set)| set.last().map_or(true, |&cp| cp <= 0xFFFF))
+    }
+
+    /// Return the tries to write for the given sets, or `None` if they
+    /// should be written as slices of ranges.
+    ///
+    /// When the format is chosen automatically, a comment recording the
+    /// choice is written too. Sizes count only the table data, and lookup
+    /// costs count the table entries read in the worst case: up to three for
+    /// a trie, and one per step of the binary search of a slice.
+    fn choose_tries(
         &mut self,
-        name: &str,
-        table: &[(&str, u32)],
-    ) -> Result<()> {
-        let ty = self.rust_codepoint_type();
+        sets: &[(&str, &BTreeSet<u32>)],
+    ) -> Result<Option<Vec<TrieSetOwned>>> {
+        if self.opts.fst_dir.is_some() || self.opts.binary {
+            return Ok(None);
+        }
+        if self.opts.optimize.is_none() && !self.opts.trie_set {
+            return Ok(None);
+        }
+        if self.opts.optimize.is_some() && self.opts.emit_iter {
+            return Ok(None);
+        }
+        let mut tries = vec![];
+        for (_, codepoints) in sets {
+            let set: Vec<u32> = codepoints.iter().cloned().collect();
+            tries.push(TrieSetOwned::from_codepoints(&set)?);
+        }
+        let optimize = match self.opts.optimize {
+            None => return Ok(Some(tries)),
+            Some(optimize) => optimize,
+        };
+
+        let bound_size = if self.fits_u16(sets) { 2 } else { 4 };
+        let (mut slice_size, mut slice_reads) = (0, 0);
+        for (_, codepoints) in sets {
+            let ranges = util::to_ranges(codepoints.iter().cloned()).len();
+            let reads = (usize::BITS - ranges.leading_zeros()) as usize;
+            slice_size += ranges * 2 * bound_size;
+            slice_reads = slice_reads.max(reads);
+        }
+        let mut trie_size = 0;
+        for trie in &tries {
+            let t = trie.as_slice();
+            trie_size += 8 * t.tree1_level1.len()
+                + t.tree2_level1.len()
+                + 8 * t.tree2_level2.len()
+                + t.tree3_level1.len()
+                + t.tree3_level2.len()
+                + 8 * t.tree3_level3.len();
+        }
+        let trie_reads = 3;
+        let (use_trie, target) = match optimize {
+            Optimize::Size => (trie_size < slice_size, "size"),
+            Optimize::Speed => {
+                ((trie_reads, trie_size) < (slice_reads, slice_size), "speed")
+            }
+        };
+        let (subject, trie) = match sets {
+            [(name, _)] => (format!("{} is", rust_const_name(name)), "a trie"),
+            _ => (format!("The {} sets in BY_NAME are", sets.len()), "tries"),
+        };
         writeln!(
             self.wtr,
-            "pub const {}: &'static [(&'static str, {})] = &[",
-            name, ty
+            "// {} written as {}, optimizing for {}.",
+            subject,
+            if use_trie { trie } else { "ranges" },
+            target,
         )?;
-        for &(ref s, cp) in table {
-            if let Some(cp) = self.rust_codepoint(cp) {
-                self.wtr.write_str(&format!("({:?}, {}), ", s, cp))?;
-            }
-        }
-        writeln!(self.wtr, "];")?;
+        writeln!(
+            self.wtr,
+            "// As ranges: {} bytes, {} read{} per lookup. \
+             As {}: {} bytes, {} reads.",
+            slice_size,
+            slice_reads,
+            if slice_reads == 1 { "" } else { "s" },
+            trie,
+            trie_size,
+            trie_reads,
+        )?;
+        Ok(if use_trie { Some(tries) } else { None })
+    }
+
+    /// Write a function that iterates over the characters of the set with
+    /// the given constant name, which was just written by `ranges`.
+    fn ranges_iter(&mut self, const_name: &str) -> Result<()> {
+        let ranges = if self.opts.binary {
+            format!(
+                "{}().iter().map(|r| (r.start.get(), r.end.get()))",
+                rust_fn_name(const_name)
+            )
+        } else if self.opts.group_by_plane {
+            format!(
+                "{}_PLANES.iter().flat_map(|&(_, r)| r.iter().copied())",
+                const_name
+            )
+        } else if self.u16_sets.contains(const_name) {
+            format!(
+                "{}.iter().map(|&(s, e)| (u32::from(s), u32::from(e)))",
+                const_name
+            )
+        } else {
+            format!("{}.iter().copied()", const_name)
+        };
+        // Ranges of chars already skip surrogates.
+        let chars = if self.opts.char_literals && !self.opts.binary {
+            "start..=end"
+        } else {
+            "(start..=end).filter_map(char::from_u32)"
+        };
+        self.separator()?;
+        writeln!(
+            self.wtr,
+            "pub fn {}_iter() -> impl Iterator<Item = char> {{",
+            rust_fn_name(const_name)
+        )?;
+        writeln!(self.wtr, "  {}", ranges)?;
+        writeln!(self.wtr, "    .flat_map(|(start, end)| {})", chars)?;
+        writeln!(self.wtr, "}}")?;
         Ok(())
     }
 
-    /// Write a map that associates strings to `u64` values.
-    pub fn string_to_u64(
+    /// Write one slice of ranges for each plane that has any codepoints,
+    /// followed by an index of those slices.
+    /// Write a sorted slice of strings, e.g., every emoji sequence with some
+    /// property, so that membership can be tested with `binary_search`.
+    ///
+    /// When writing FSTs, the strings are written as an FST set instead.
+    pub fn strings(
         &mut self,
         name: &str,
-        map: &BTreeMap<String, u64>,
+        strings: &BTreeSet<String>,
     ) -> Result<()> {
+        self.ensure_not_c("a set of strings")?;
+        self.ensure_nonempty(name, strings.len())?;
         self.header()?;
         self.separator()?;
 
         let name = rust_const_name(name);
         if self.opts.fst_dir.is_some() {
-            let mut builder = MapBuilder::memory();
-            for (k, &v) in map {
-                builder.insert(k.as_bytes(), v)?;
-            }
-            let map = builder.into_map();
-            self.fst(&name, map.as_fst(), true)?;
+            let mut builder = SetBuilder::memory();
+            builder.extend_iter(strings.iter())?;
+            let set = builder.into_set();
+            self.fst(&name, set.as_fst(), false)?;
         } else {
-            let table: Vec<(&str, u64)> =
-                map.iter().map(|(k, &v)| (&**k, v)).collect();
-            self.string_to_u64_slice(&name, &table)?;
+            let values: Vec<&str> = strings.iter().map(|s| &**s).collect();
+            self.string_slice(&name, &values)?;
         }
         self.wtr.flush()?;
         Ok(())
     }
 
-    fn string_to_u64_slice(
+    fn ranges_by_plane(
         &mut self,
         name: &str,
-        table: &[(&str, u64)],
+        table: &[(u32, u32)],
     ) -> Result<()> {
+        let mut planes: BTreeMap<u32, Vec<(u32, u32)>> = BTreeMap::new();
+        for &(mut start, end) in table {
+            // A range may span more than one plane, so split it at each
+            // plane boundary.
+            while start >> 16 < end >> 16 {
+                let plane_end = start | 0xFFFF;
+                planes
+                    .entry(start >> 16)
+                    .or_default()
+                    .push((start, plane_end));
+                start = plane_end + 1;
+            }
+            planes.entry(start >> 16).or_default().push((start, end));
+        }
+
+        let mut consts = vec![];
+        for (&plane, ranges) in &planes {
+            let const_name = format!("{}_{}", name, plane_name(plane));
+            self.ranges_slice(&const_name, ranges)?;
+            self.separator()?;
+            consts.push((plane, const_name));
+        }
+
+        let ty = self.rust_codepoint_type();
         writeln!(
             self.wtr,
-            "pub const {}: &'static [(&'static str, u64)] = &[",
-            name
+            "pub const {}_PLANES: &'static [(u8, &'static [({}, {})])] = &[",
+            name, ty, ty
         )?;
-        for &(ref s, n) in table {
-            self.wtr.write_str(&format!("({:?}, {}), ", s, n))?;
+        for &(plane, ref const_name) in &consts {
+            self.wtr.write_str(&format!("({}, {}), ", plane, const_name))?;
         }
         writeln!(self.wtr, "];")?;
         Ok(())
     }
 
-    fn fst<D: AsRef<[u8]>>(
+    fn ranges_slice(
         &mut self,
-        const_name: &str,
-        fst: &Fst<D>,
-        map: bool,
+        name: &str,
+        table: &[(u32, u32)],
     ) -> Result<()> {
-        let fst_dir = self.opts.fst_dir.as_ref().unwrap();
-        let fst_file_name = format!("{}.fst", rust_module_name(const_name));
-        let fst_file_path = fst_dir.join(&fst_file_name);
-        File::create(fst_file_path)?.write_all(&fst.to_vec())?;
-
-        let ty = if map { "Map" } else { "Set" };
-        writeln!(
-            self.wtr,
-            "pub static {}: ::once_cell::sync::Lazy<::fst::{}<&'static [u8]>> =",
-            const_name, ty
-        )?;
-        writeln!(self.wtr, "  ::once_cell::sync::Lazy::new(|| {{")?;
-        writeln!(self.wtr, "    ::fst::{}::from(::fst::raw::Fst::new(", ty)?;
-        writeln!(
-            self.wtr,
-            "      &include_bytes!({:?})[..]).unwrap())",
-            fst_file_name
-        )?;
-        writeln!(self.wtr, "  }});")?;
-        Ok(())
-    }
-
-    fn write_slice_u8(&mut self, xs: &[u8]) -> Result<()> {
-        for &x in xs {
-            self.wtr.write_str(&format!("{}, ", x))?;
-        }
-        Ok(())
+        self.ranges_slice_of(self.rust_codepoint_type(), name, table)
     }
 
-    fn write_slice_u64(&mut self, xs: &[u64]) -> Result<()> {
-        for &x in xs {
-            if x == 0 {
-                self.wtr.write_str("0, ")?;
-            } else {
-                self.wtr.write_str(&format!("0x{:X}, ", x))?;
+    /// Write the given ranges as a slice of pairs of the given type, which
+    /// every codepoint in them must fit in.
+    fn ranges_slice_of(
+        &mut self,
+        ty: &str,
+        name: &str,
+        table: &[(u32, u32)],
+    ) -> Result<()> {
+        self.slice_start(name, &format!("({}, {})", ty, ty))?;
+        let mut len = 0;
+        for &(start, end) in table {
+            let range = (self.rust_codepoint(start), self.rust_codepoint(end));
+            if let (Some(s), Some(e)) = range {
+                self.wtr.write_str(&format!("({}, {}), ", s, e))?;
+                self.annotate_range(start, end)?;
+                len += 1;
             }
         }
+        self.slice_end(len)?;
+        self.len_const(name, len)?;
         Ok(())
     }
 
-    fn header(&mut self) -> Result<()> {
-        if self.wrote_header {
+    /// Write the given ranges as a string constant in the syntax of the CSS
+    /// `unicode-range` descriptor.
+    ///
+    /// The string is split over as many lines as needed with escaped
+    /// newlines, which aren't part of its value.
+    fn css_unicode_range(
+        &mut self,
+        name: &str,
+        table: &[(u32, u32)],
+    ) -> Result<()> {
+        let ranges: Vec<String> = table
+            .iter()
+            .map(|&(start, end)| {
+                if start == end {
+                    format!("U+{:04X}", start)
+                } else {
+                    format!("U+{:04X}-{:04X}", start, end)
+                }
+            })
+            .collect();
+        if ranges.is_empty() {
+            writeln!(self.wtr, "pub const {}: &'static str = \"\";", name)?;
             return Ok(());
         }
-        let mut argv = vec![];
-        argv.push(
-            env::current_exe()?
-                .file_name()
-                .unwrap()
-                .to_string_lossy()
-                .into_owned(),
-        );
-        for arg in env::args_os().skip(1) {
-            let x = arg.to_string_lossy();
-            if x.contains("\n") {
-                argv.push("[snip (arg too long)]".to_string());
-            } else {
-                argv.push(x.into_owned());
+        writeln!(self.wtr, "pub const {}: &'static str = \"\\", name)?;
+        let width = (self.opts.columns as usize).saturating_sub(5);
+        let mut line = String::new();
+        for (i, range) in ranges.iter().enumerate() {
+            if !line.is_empty() && line.len() + range.len() + 2 > width {
+                writeln!(self.wtr, "  {} \\", line.trim_end())?;
+                line.clear();
+            }
+            line.push_str(range);
+            if i + 1 < ranges.len() {
+                line.push_str(", ");
             }
         }
-        writeln!(
-            self.wtr,
-            "// DO NOT EDIT THIS FILE. \
-             IT WAS AUTOMATICALLY GENERATED BY:"
+        writeln!(self.wtr, "  {}\";", line)?;
+        Ok(())
+    }
+
+    /// If annotations are enabled, end the current line with a comment
+    /// naming the first and last characters of the given range.
+    fn annotate_range(&mut self, start: u32, end: u32) -> Result<()> {
+        let names = match self.opts.annotate {
+            None => return Ok(()),
+            Some(ref names) => names.clone(),
+        };
+        let name =
+            |cp| names.get(cp).unwrap_or_else(|| format!("U+{:04X}", cp));
+        let comment = if start == end {
+            name(start)
+        } else {
+            format!("{}..{}", name(start), name(end))
+        };
+        self.wtr.end_line_with_comment(&comment)?;
+        Ok(())
+    }
+
+    fn trie_set(&mut self, name: &str, trie: &TrieSetOwned) -> Result<()> {
+        let trie = trie.as_slice();
+        writeln!(
+            self.wtr,
+            "pub const {}: &'static ::ucd_trie::TrieSet = \
+             &::ucd_trie::TrieSet {{",
+            name
         )?;
-        writeln!(self.wtr, "//")?;
-        writeln!(self.wtr, "//   {}", argv.join(" "))?;
-        writeln!(self.wtr, "//")?;
-        if let Some((major, minor, patch)) = self.opts.ucd_version {
+
+        self.wtr.indent("    ");
+
+        writeln!(self.wtr, "  tree1_level1: &[")?;
+        self.write_slice_u64(&trie.tree1_level1)?;
+        writeln!(self.wtr, "  ],")?;
+
+        writeln!(self.wtr, "  tree2_level1: &[")?;
+        self.write_slice_u8(&trie.tree2_level1)?;
+        writeln!(self.wtr, "  ],")?;
+
+        writeln!(self.wtr, "  tree2_level2: &[")?;
+        self.write_slice_u64(&trie.tree2_level2)?;
+        writeln!(self.wtr, "  ],")?;
+
+        writeln!(self.wtr, "  tree3_level1: &[")?;
+        self.write_slice_u8(&trie.tree3_level1)?;
+        writeln!(self.wtr, "  ],")?;
+
+        writeln!(self.wtr, "  tree3_level2: &[")?;
+        self.write_slice_u8(&trie.tree3_level2)?;
+        writeln!(self.wtr, "  ],")?;
+
+        writeln!(self.wtr, "  tree3_level3: &[")?;
+        self.write_slice_u64(&trie.tree3_level3)?;
+        writeln!(self.wtr, "  ],")?;
+
+        writeln!(self.wtr, "}};")?;
+        self.wtr.indent("  ");
+        Ok(())
+    }
+
+    fn trie_set_c(&mut self, name: &str, trie: &TrieSetOwned) -> Result<()> {
+        let trie = trie.as_slice();
+        let (u8, u64) = (self.c_uint(8), self.c_uint(64));
+        let arrays: [(&str, &str, Vec<u64>); 6] = [
+            ("tree1_level1", &u64, trie.tree1_level1.to_vec()),
+            ("tree2_level1", &u8, to_u64s(trie.tree2_level1)),
+            ("tree2_level2", &u64, trie.tree2_level2.to_vec()),
+            ("tree3_level1", &u8, to_u64s(trie.tree3_level1)),
+            ("tree3_level2", &u8, to_u64s(trie.tree3_level2)),
+            ("tree3_level3", &u64, trie.tree3_level3.to_vec()),
+        ];
+        // C doesn't permit empty arrays, so empty levels are represented by
+        // a null pointer instead. They are never dereferenced, since their
+        // length is 0.
+        for (field, ty, values) in &arrays {
+            if values.is_empty() {
+                continue;
+            }
             writeln!(
                 self.wtr,
-                "// Unicode version: {}.{}.{}.",
-                major, minor, patch
+                "static const {} {}_{}[] = {{",
+                ty,
+                name,
+                field.to_uppercase()
             )?;
-            writeln!(self.wtr, "//")?;
+            self.write_slice_u64(values)?;
+            writeln!(self.wtr, "}};")?;
+        }
+        writeln!(self.wtr, "static const struct ucd_trie_set {} = {{", name)?;
+        for (field, _, values) in &arrays {
+            if values.is_empty() {
+                writeln!(self.wtr, "  NULL, 0,")?;
+            } else {
+                let array = format!("{}_{}", name, field.to_uppercase());
+                writeln!(
+                    self.wtr,
+                    "  {}, sizeof({}) / sizeof({}[0]),",
+                    array, array, array
+                )?;
+            }
+        }
+        writeln!(self.wtr, "}};")?;
+        Ok(())
+    }
+
+    /// Write the C definitions that every set written by `trie_set_c`
+    /// depends on. This is guarded so that several generated files can be
+    /// included in the same translation unit.
+    fn c_prelude(&mut self) -> Result<()> {
+        self.separator()?;
+        writeln!(self.wtr, "#include <stddef.h>")?;
+        writeln!(self.wtr, "#include <stdint.h>")?;
+        self.separator()?;
+        let prelude = match self.opts.c_uint_type {
+            CUintType::Exact => C_TRIE_SET_PRELUDE.to_string(),
+            // Every integer type in the prelude is an exact-width type.
+            CUintType::Least => {
+                C_TRIE_SET_PRELUDE.replace("uint", "uint_least")
+            }
+        };
+        write!(self.wtr, "{}", prelude)?;
+        Ok(())
+    }
+
+    /// Return the C type of unsigned integers with the given number of bits,
+    /// according to `c_uint_type`.
+    fn c_uint(&self, bits: u32) -> String {
+        match self.opts.c_uint_type {
+            CUintType::Exact => format!("uint{}_t", bits),
+            CUintType::Least => format!("uint_least{}_t", bits),
+        }
+    }
+
+    /// Write an enum of the positions of the given names in the `BY_NAME`
+    /// index that was just written as C.
+    fn c_enum(&mut self, names: &BTreeSet<String>) -> Result<()> {
+        self.separator()?;
+        let prefix = rust_const_name(&self.opts.name);
+        writeln!(self.wtr, "// The position of each set in BY_NAME.")?;
+        writeln!(self.wtr, "enum {} {{", rust_module_name(&self.opts.name))?;
+        for (i, name) in names.iter().enumerate() {
+            writeln!(
+                self.wtr,
+                "  {}_{} = {},",
+                prefix,
+                rust_const_name(name),
+                i
+            )?;
+        }
+        writeln!(self.wtr, "}};")?;
+        Ok(())
+    }
+
+    /// Return an error if C output is enabled, since the given kind of table
+    /// can only be written as Rust.
+    fn ensure_not_c(&self, what: &str) -> Result<()> {
+        if self.opts.c_output {
+            return err!(
+                "cannot emit {} as C (only --trie-set supports C output)",
+                what
+            );
+        }
+        Ok(())
+    }
+
+    /// Write a map that associates codepoint ranges to a single value in an
+    /// enumeration. This usually emits two items: a map from codepoint range
+    /// to index and a map from index to one of the enum variants.
+    ///
+    /// The given map should be a map from the enum variant value to the set
+    /// of codepoints that have that value.
+    pub fn ranges_to_enum(
+        &mut self,
+        name: &str,
+        enum_map: &BTreeMap<String, BTreeSet<u32>>,
+    ) -> Result<()> {
+        self.ensure_not_c("ranges to enum")?;
+        self.header()?;
+        self.separator()?;
+
+        let variants: Vec<&str> = enum_map.keys().map(|v| &**v).collect();
+        self.enum_value_names(
+            &format!("{}_ENUM", rust_const_name(name)),
+            &variants,
+        )?;
+        self.len_const(
+            &format!("{}_ENUM", rust_const_name(name)),
+            enum_map.len(),
+        )?;
+
+        let map: BTreeMap<u32, u64> = self
+            .resolve_overlaps(enum_map)?
+            .into_iter()
+            .map(|(cp, i)| (cp, i as u64))
+            .collect();
+        self.ranges_to_unsigned_integer(name, &map)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Like `ranges_to_enum`, but the `_ENUM` table lists abbreviated
+    /// values and is followed by a parallel `_ENUM_LONG` table of long
+    /// values.
+    ///
+    /// `aliases` should contain an `(abbreviation, long)` pair for each key
+    /// in `enum_map`, in the same order. An `_ENUM_HASH` constant derived
+    /// from the long values is also emitted, so that downstream code can
+    /// detect when indices have been reshuffled between regenerations.
+    pub fn ranges_to_enum_with_aliases(
+        &mut self,
+        name: &str,
+        enum_map: &BTreeMap<String, BTreeSet<u32>>,
+        aliases: &[(String, String)],
+    ) -> Result<()> {
+        self.ensure_not_c("ranges to enum with aliases")?;
+        assert_eq!(enum_map.len(), aliases.len());
+
+        self.header()?;
+        self.separator()?;
+
+        let const_name = rust_const_name(name);
+        let shorts: Vec<&str> = aliases.iter().map(|(s, _)| &**s).collect();
+        self.enum_value_names(&format!("{}_ENUM", const_name), &shorts)?;
+        self.len_const(&format!("{}_ENUM", const_name), aliases.len())?;
+
+        let longs: Vec<&str> = aliases.iter().map(|(_, l)| &**l).collect();
+        self.enum_value_names(&format!("{}_ENUM_LONG", const_name), &longs)?;
+
+        let mut names = vec![];
+        for (_, long) in aliases {
+            names.extend_from_slice(long.as_bytes());
+            names.push(0);
         }
+        let hash = fnv1a64(&names);
         writeln!(
             self.wtr,
-            "// ucd-generate {} is available on crates.io.",
-            env!("CARGO_PKG_VERSION")
+            "pub const {}_ENUM_HASH: u64 = 0x{:016X};",
+            const_name, hash
         )?;
-        self.wrote_header = true;
+        if let Some(pinned) = self.opts.enum_hash {
+            self.wtr.indent_width(4)?;
+            writeln!(
+                self.wtr,
+                "const _: () = assert!(\n    \
+                 {}_ENUM_HASH == 0x{:016X},\n    \
+                 \"the values in {}_ENUM have changed\",\n);",
+                const_name, pinned, const_name,
+            )?;
+            self.wtr.indent_width(2)?;
+        }
+
+        let map: BTreeMap<u32, u64> = self
+            .resolve_overlaps(enum_map)?
+            .into_iter()
+            .map(|(cp, i)| (cp, i as u64))
+            .collect();
+        self.ranges_to_unsigned_integer(name, &map)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a map that associates codepoint ranges to a set of values in an
+    /// enumeration.
+    ///
+    /// This emits three items: the enum variants (`_ENUM`), the distinct sets
+    /// of variants as slices of indices into `_ENUM` (`_SETS`) and a map from
+    /// codepoint range to an index into `_SETS`.
+    ///
+    /// The given map should be a map from codepoint to the indices, in
+    /// `variants`, of the values that codepoint has.
+    pub fn ranges_to_enum_sets(
+        &mut self,
+        name: &str,
+        variants: &[String],
+        sets_by_cp: &BTreeMap<u32, BTreeSet<usize>>,
+    ) -> Result<()> {
+        self.ensure_not_c("ranges to enum sets")?;
+        self.header()?;
+        self.separator()?;
+
+        let const_name = rust_const_name(name);
+        let names: Vec<&str> = variants.iter().map(|v| &**v).collect();
+        self.enum_value_names(&format!("{}_ENUM", const_name), &names)?;
+        self.len_const(&format!("{}_ENUM", const_name), variants.len())?;
+
+        let sets: BTreeSet<&BTreeSet<usize>> = sets_by_cp.values().collect();
+        let sets: Vec<&BTreeSet<usize>> = sets.into_iter().collect();
+        let index_ty = smallest_unsigned_type(variants.len() as u64);
+        let sets_name = format!("{}_SETS", const_name);
+        self.slice_start(&sets_name, &format!("&'static [{}]", index_ty))?;
+        for set in &sets {
+            let mut src = "&[".to_string();
+            for index in set.iter() {
+                src.push_str(&format!("{}, ", index));
+            }
+            src.push_str("], ");
+            self.wtr.write_str(&src)?;
+        }
+        self.slice_end(sets.len())?;
+        self.len_const(&sets_name, sets.len())?;
+
+        let mut map = BTreeMap::new();
+        for (&cp, set) in sets_by_cp {
+            let i = sets.binary_search(&set).unwrap();
+            map.insert(cp, i as u64);
+        }
+        self.ranges_to_unsigned_integer(name, &map)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a table of the value names of an enum table, with the given
+    /// constant name.
+    ///
+    /// This is a slice of strings, or with `intern_strings`, a slice of
+    /// `(start, len)` spans into the string pool.
+    fn enum_value_names(
+        &mut self,
+        const_name: &str,
+        names: &[&str],
+    ) -> Result<()> {
+        if !self.opts.intern_strings {
+            writeln!(
+                self.wtr,
+                "pub const {}: &'static [&'static str] = &[",
+                const_name
+            )?;
+            for name in names {
+                self.wtr.write_str(&format!("{:?}, ", name))?;
+            }
+            writeln!(self.wtr, "];")?;
+            return Ok(());
+        }
+        let mut spans = vec![];
+        for name in names {
+            spans.push(self.intern(const_name, name)?);
+        }
+        writeln!(
+            self.wtr,
+            "// Each entry is a (start, len) span of strings::POOL."
+        )?;
+        writeln!(
+            self.wtr,
+            "pub const {}: &'static [(u16, u16)] = &[",
+            const_name
+        )?;
+        for (start, len) in spans {
+            self.wtr.write_str(&format!("({}, {}), ", start, len))?;
+        }
+        writeln!(self.wtr, "];")?;
+        Ok(())
+    }
+
+    /// Return the `(start, len)` span of the given string in the string
+    /// pool, appending it to the pool if it isn't already in it.
+    ///
+    /// The table name is only used in error messages.
+    fn intern(&mut self, const_name: &str, s: &str) -> Result<(usize, usize)> {
+        if self.pool.is_none() {
+            let out_dir = match self.opts.out_dir {
+                None => {
+                    return err!(
+                        "cannot intern the strings of {} without an output \
+                         directory",
+                        const_name
+                    )
+                }
+                Some(ref out_dir) => out_dir,
+            };
+            let path = out_dir.join("strings.rs");
+            let pool = read_string_pool(&path)?;
+            self.pool = Some((path, pool));
+        }
+        // The pool is read back by looking for string literals, so it can
+        // only contain characters that are written as they are.
+        if !s.chars().all(|c| c == ' ' || (c.is_ascii_graphic() && c != '"'))
+            || s.contains('\\')
+        {
+            return err!("cannot intern {:?} of {}", s, const_name);
+        }
+        let (path, pool) = self.pool.as_mut().unwrap();
+        let start = match pool.find(s) {
+            Some(start) => start,
+            None => {
+                pool.push_str(s);
+                pool.len() - s.len()
+            }
+        };
+        if pool.len() > u16::MAX as usize {
+            return err!(
+                "the string pool in {} is too big for u16 spans",
+                path.display()
+            );
+        }
+        Ok((start, s.len()))
+    }
+
+    /// Return the contents of `strings.rs`, which declares the given string
+    /// pool.
+    fn string_pool_module(&self, pool: &str) -> String {
+        let mut contents = String::from(
+            "// DO NOT EDIT THIS FILE. IT WAS AUTOMATICALLY GENERATED BY \
+             ucd-generate.\n// It is shared by the tables written with \
+             --intern-strings.\n\n\
+             pub const POOL: &'static str = concat!(\n",
+        );
+        // Each line is a string literal that fits in the configured number
+        // of columns along with its indent, quotes and comma.
+        let width = (self.opts.columns as usize).saturating_sub(6).max(16);
+        let mut rest = pool;
+        while !rest.is_empty() {
+            let (line, tail) = rest.split_at(width.min(rest.len()));
+            contents.push_str(&format!("  \"{}\",\n", line));
+            rest = tail;
+        }
+        contents.push_str(
+            ");\n\n\
+             /// Return the string in `POOL` that the given `(start, len)` \
+             span refers to.\n\
+             pub fn get(span: (u16, u16)) -> &'static str {\n    \
+             let start = span.0 as usize;\n    \
+             &POOL[start..start + span.1 as usize]\n\
+             }\n",
+        );
+        contents
+    }
+
+    /// Write a map that associates codepoint ranges to a single value in a
+    /// Rust enum.
+    ///
+    /// The given map should be a map from the enum variant value to the set
+    /// of codepoints that have that value.
+    pub fn ranges_to_rust_enum(
+        &mut self,
+        name: &str,
+        variants: &[&str],
+        enum_map: &BTreeMap<String, BTreeSet<u32>>,
+    ) -> Result<()> {
+        self.ensure_not_c("ranges to rust enum")?;
+        self.header()?;
+        self.separator()?;
+
+        let enum_name = rust_type_name(name);
+        let variants: Vec<String> =
+            variants.iter().map(|v| rust_type_name(v)).collect();
+        let variants = self.enum_variants(&enum_name, variants)?;
+        self.rust_enum_decl(&enum_name, &variants)?;
+        writeln!(self.wtr)?;
+
+        let values: Vec<&String> = enum_map.keys().collect();
+        let map = self.resolve_overlaps(enum_map)?;
+        let ranges = util::to_range_values(
+            map.iter().map(|(&k, &i)| (k, rust_type_name(values[i]))),
+        );
+        self.ranges_to_enum_slice(name, &enum_name, &ranges)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a map that associates codepoint ranges to a single value in a
+    /// Rust enum with custom discriminants.
+    ///
+    /// The given `variants_map` should be a map from the custom discriminant
+    /// to the enum variant value.
+    ///
+    /// The given `enum_map` should be a map from the enum variant value to the
+    /// set of codepoints that have that value.
+    pub fn ranges_to_rust_enum_with_custom_discriminants(
+        &mut self,
+        name: &str,
+        variants_map: &BTreeMap<isize, String>,
+        enum_map: &BTreeMap<String, BTreeSet<u32>>,
+    ) -> Result<()> {
+        self.ensure_not_c("ranges to rust enum with custom discriminants")?;
+        self.header()?;
+        self.separator()?;
+
+        let enum_name = rust_type_name(name);
+        let variants: Vec<(String, Option<isize>)> = variants_map
+            .iter()
+            .map(|(&d, variant)| (rust_type_name(variant), Some(d)))
+            .collect();
+        self.rust_enum_decl(&enum_name, &variants)?;
+        writeln!(self.wtr)?;
+
+        let values: Vec<&String> = enum_map.keys().collect();
+        let map = self.resolve_overlaps(enum_map)?;
+        let ranges = util::to_range_values(
+            map.iter().map(|(&k, &i)| (k, rust_type_name(values[i]))),
+        );
+        self.ranges_to_enum_slice(name, &enum_name, &ranges)?;
+        self.wtr.flush()?;
         Ok(())
     }
 
-    fn separator(&mut self) -> Result<()> {
-        write!(self.wtr, "\n")?;
-        Ok(())
+    /// Return the given variants of a Rust enum in the order in which they
+    /// should be declared, along with their discriminants, if explicit
+    /// discriminants are enabled.
+    ///
+    /// Without an enum manifest, the order is unchanged. With one, variants
+    /// in the manifest come first in the manifest's order, followed by new
+    /// variants in the given order. Discriminants are positions in the
+    /// manifest, so they don't change when a variant is removed.
+    fn enum_variants(
+        &mut self,
+        enum_name: &str,
+        variants: Vec<String>,
+    ) -> Result<Vec<(String, Option<isize>)>> {
+        let discriminants = self.opts.enum_discriminants;
+        let path = match self.opts.enum_manifest {
+            None => {
+                return Ok(variants
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, v)| {
+                        (v, Some(i as isize).filter(|_| discriminants))
+                    })
+                    .collect());
+            }
+            Some(ref path) => path.clone(),
+        };
+        if self.manifest.is_none() {
+            self.manifest = Some(read_enum_manifest(&path)?);
+        }
+        let known = self
+            .manifest
+            .as_mut()
+            .unwrap()
+            .entry(enum_name.to_string())
+            .or_default();
+        for variant in &variants {
+            if !known.contains(variant) {
+                known.push(variant.clone());
+            }
+        }
+        Ok(known
+            .iter()
+            .enumerate()
+            .filter(|&(_, v)| variants.contains(v))
+            .map(|(i, v)| {
+                (v.clone(), Some(i as isize).filter(|_| discriminants))
+            })
+            .collect())
+    }
+
+    /// Write the declaration of a Rust enum with the given variants, each
+    /// with an optional explicit discriminant.
+    fn rust_enum_decl(
+        &mut self,
+        enum_name: &str,
+        variants: &[(String, Option<isize>)],
+    ) -> Result<()> {
+        writeln!(
+            self.wtr,
+            "#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]",
+        )?;
+        if self.opts.enum_non_exhaustive {
+            writeln!(self.wtr, "#[non_exhaustive]")?;
+        }
+        writeln!(self.wtr, "pub enum {} {{", enum_name)?;
+        for (variant, discriminant) in variants {
+            match *discriminant {
+                None => self.wtr.write_str(&format!("{}, ", variant))?,
+                Some(d) => {
+                    self.wtr.write_str(&format!("{} = {}, ", variant, d))?
+                }
+            }
+        }
+        writeln!(self.wtr, "}}")?;
+        Ok(())
+    }
+
+    /// Write a map that combines codepoint ranges into a single table.
+    ///
+    /// The given map should be a map from the variant value to the set of
+    /// codepoints that have that value. The table written is the union of
+    /// all sets in the map. If this writer was configured to require
+    /// disjoint sets (or to reject overlapping values) and a codepoint is
+    /// in more than one of them, then an error is returned naming the first
+    /// such codepoint.
+    pub fn ranges_to_combined(
+        &mut self,
+        name: &str,
+        enum_map: &BTreeMap<String, BTreeSet<u32>>,
+    ) -> Result<()> {
+        let disjoint =
+            self.opts.combined_disjoint || self.opts.overlap == Overlap::Error;
+        let mut set = BTreeSet::new();
+        let mut owner: BTreeMap<u32, &str> = BTreeMap::new();
+        for (value, other_set) in enum_map {
+            if disjoint {
+                for &cp in other_set {
+                    if let Some(first) = owner.insert(cp, value) {
+                        return err!(
+                            "cannot emit disjoint combined table: \
+                             codepoint U+{:04X} is in both {:?} and {:?}",
+                            cp,
+                            first,
+                            value,
+                        );
+                    }
+                }
+            }
+            set.extend(other_set.iter().cloned());
+        }
+        self.ranges(name, &set)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Map every codepoint in the given enumeration to the index of its
+    /// value, resolving codepoints that have more than one value according
+    /// to this writer's overlap priority and then its overlap mode.
+    fn resolve_overlaps(
+        &self,
+        enum_map: &BTreeMap<String, BTreeSet<u32>>,
+    ) -> Result<BTreeMap<u32, usize>> {
+        let values: Vec<&String> = enum_map.keys().collect();
+        let priority = |i: usize| {
+            self.opts.overlap_priority.iter().position(|v| v == values[i])
+        };
+        let mut map = BTreeMap::new();
+        for (i, set) in enum_map.values().enumerate() {
+            for &cp in set {
+                let j = match map.get(&cp) {
+                    None => {
+                        map.insert(cp, i);
+                        continue;
+                    }
+                    Some(&j) => j,
+                };
+                let wins = match (priority(i), priority(j)) {
+                    (Some(pi), Some(pj)) => pi < pj,
+                    (Some(_), None) => true,
+                    (None, Some(_)) => false,
+                    (None, None) => match self.opts.overlap {
+                        Overlap::First => false,
+                        Overlap::Last => true,
+                        Overlap::Error => {
+                            return err!(
+                                "codepoint U+{:04X} has more than one value: \
+                                 {:?} and {:?}",
+                                cp,
+                                values[j],
+                                values[i],
+                            );
+                        }
+                    },
+                };
+                if wins {
+                    map.insert(cp, i);
+                }
+            }
+        }
+        Ok(self.kept(&map).into_owned())
+    }
+
+    /// Return the part of the given map that this writer keeps in its
+    /// tables, which is all of it unless a maximum age, a `--where` filter
+    /// or a sample was asked for.
+    ///
+    /// Codepoints left out by the maximum age or filter are removed first,
+    /// so that a sample is taken from what remains. Every table written
+    /// from a map or set of codepoints goes through here, but callers that
+    /// pack codepoints into the values of other maps, such as tagged names,
+    /// must leave them out themselves with `leaves_out`.
+    fn kept<'a, V: Clone + PartialEq>(
+        &self,
+        map: &'a BTreeMap<u32, V>,
+    ) -> Cow<'a, BTreeMap<u32, V>> {
+        let map = if self.opts.max_age.is_none() && self.opts.only.is_none() {
+            Cow::Borrowed(map)
+        } else {
+            Cow::Owned(
+                map.iter()
+                    .filter(|&(&cp, _)| !self.leaves_out(cp))
+                    .map(|(&cp, v)| (cp, v.clone()))
+                    .collect(),
+            )
+        };
+        let mut sample = BTreeMap::new();
+        match self.opts.sample {
+            None => return map,
+            Some(Sample::Window(start, end)) => {
+                for (&cp, v) in map.range(start..=end) {
+                    sample.insert(cp, v.clone());
+                }
+            }
+            Some(Sample::Ranges(count)) => {
+                let mut ranges = 0;
+                let mut prev: Option<(u32, &V)> = None;
+                for (&cp, v) in map.iter() {
+                    let extends =
+                        prev.is_some_and(|(p, pv)| p + 1 == cp && pv == v);
+                    if !extends {
+                        ranges += 1;
+                        if ranges > count {
+                            break;
+                        }
+                    }
+                    sample.insert(cp, v.clone());
+                    prev = Some((cp, v));
+                }
+            }
+        }
+        Cow::Owned(sample)
+    }
+
+    /// Like `kept`, but for a set of codepoints.
+    fn kept_set<'a>(&self, set: &'a BTreeSet<u32>) -> Cow<'a, BTreeSet<u32>> {
+        if self.opts.sample.is_none()
+            && self.opts.max_age.is_none()
+            && self.opts.only.is_none()
+        {
+            return Cow::Borrowed(set);
+        }
+        let map: BTreeMap<u32, ()> = set.iter().map(|&cp| (cp, ())).collect();
+        Cow::Owned(self.kept(&map).keys().copied().collect())
+    }
+
+    /// Returns true if the given codepoint is left out of every table by
+    /// this writer's maximum age or filter.
+    ///
+    /// Callers that pack codepoints into other values, such as tagged
+    /// names, use this to leave them out themselves.
+    pub fn leaves_out(&self, cp: u32) -> bool {
+        self.opts.max_age.as_ref().is_some_and(|max| max.excludes(cp))
+            || self.opts.only.as_ref().is_some_and(|only| only.excludes(cp))
+    }
+
+    /// Return the parts of the given inclusive range that aren't left out
+    /// by this writer's maximum age or filter.
+    fn clip(&self, start: u32, end: u32) -> Vec<(u32, u32)> {
+        let mut ranges = match self.opts.max_age {
+            None => vec![(start, end)],
+            Some(ref max_age) => max_age.clip(start, end),
+        };
+        if let Some(ref only) = self.opts.only {
+            ranges =
+                ranges.iter().flat_map(|&(s, e)| only.clip(s, e)).collect();
+        }
+        ranges
+    }
+
+    fn ranges_to_enum_slice<S>(
+        &mut self,
+        name: &str,
+        enum_ty: &str,
+        table: &[(u32, u32, S)],
+    ) -> Result<()>
+    where
+        S: fmt::Display,
+    {
+        let cp_ty = self.rust_codepoint_type();
+
+        self.slice_start(
+            name,
+            &format!("({}, {}, {})", cp_ty, cp_ty, enum_ty),
+        )?;
+        let mut len = 0;
+        for &(start, end, ref variant) in table {
+            let range = (self.rust_codepoint(start), self.rust_codepoint(end));
+            if let (Some(s), Some(e)) = range {
+                let src =
+                    format!("({}, {}, {}::{}), ", s, e, enum_ty, variant);
+                self.wtr.write_str(&src)?;
+                self.annotate_range(start, end)?;
+                len += 1;
+            }
+        }
+        self.slice_end(len)?;
+        self.len_const(name, len)?;
+        Ok(())
+    }
+
+    /// Write a map that associates ranges of codepoints with an arbitrary
+    /// integer.
+    ///
+    /// The smallest numeric type is used when applicable.
+    pub fn ranges_to_unsigned_integer(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<u32, u64>,
+    ) -> Result<()> {
+        let map = self.kept(map);
+        let map = &*map;
+        self.ensure_not_c("ranges to unsigned integer")?;
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        if self.opts.fst_dir.is_some() {
+            let mut builder = MapBuilder::memory();
+            for (&k, &v) in map {
+                builder.insert(u32_key(k), v)?;
+            }
+            let map = builder.into_map();
+            self.fst(&name, map.as_fst(), true)?;
+        } else if self.opts.binary {
+            let ranges =
+                util::to_range_values(map.iter().map(|(&k, &v)| (k, v)));
+            let mut records = vec![];
+            for (start, end, value) in ranges {
+                if value > u32::MAX as u64 {
+                    return err!(
+                        "cannot write {} for U+{:04X} in a binary table",
+                        value,
+                        start
+                    );
+                }
+                records.push(vec![start, end, value as u32]);
+            }
+            self.binary_table(&name, "RangeValue", &records)?;
+        } else {
+            let ranges =
+                util::to_range_values(map.iter().map(|(&k, &v)| (k, v)));
+            self.ranges_to_unsigned_integer_slice(&name, &ranges)?;
+        }
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a map from codepoint to `u8` as a direct index for the Basic
+    /// Multilingual Plane, along with a function that looks up a codepoint.
+    ///
+    /// The BMP is split into 256 pages of 256 codepoints each. Identical
+    /// pages are only written once, and an index maps the upper byte of a
+    /// codepoint to its page. Since page 0 is always all zeros, pages without
+    /// any values cost one byte. Codepoints outside the BMP are written as a
+    /// table of ranges. Codepoints not in the map have the value 0.
+    pub fn codepoint_to_u8_bmp_index(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<u32, u64>,
+    ) -> Result<()> {
+        let map = self.kept(map);
+        let map = &*map;
+        self.ensure_not_c("a BMP index")?;
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit a BMP index as an FST");
+        }
+        if let Some((cp, v)) = map.iter().find(|(_, &v)| v > 0xFF) {
+            return err!(
+                "cannot emit {} for U+{:04X} in a u8 BMP index",
+                v,
+                cp
+            );
+        }
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        let mut pages: Vec<Vec<u64>> = vec![vec![0; 256]];
+        let mut index = vec![];
+        for hi in 0..256 {
+            let page: Vec<u64> = (0..256)
+                .map(|lo| *map.get(&(hi << 8 | lo)).unwrap_or(&0))
+                .collect();
+            match pages.iter().position(|p| *p == page) {
+                Some(i) => index.push(i),
+                None => {
+                    index.push(pages.len());
+                    pages.push(page);
+                }
+            }
+        }
+        let index_ty = smallest_unsigned_type(pages.len() as u64 - 1);
+        writeln!(
+            self.wtr,
+            "pub const {}_BMP_INDEX: [{}; 256] = [",
+            name, index_ty
+        )?;
+        for i in &index {
+            self.wtr.write_str(&format!("{}, ", i))?;
+        }
+        writeln!(self.wtr, "];")?;
+
+        self.separator()?;
+        writeln!(
+            self.wtr,
+            "pub const {}_BMP_PAGES: [[u8; 256]; {}] = [",
+            name,
+            pages.len()
+        )?;
+        for page in &pages {
+            self.wtr.write_str("[")?;
+            for v in page {
+                self.wtr.write_str(&format!("{}, ", v))?;
+            }
+            self.wtr.write_str("], ")?;
+        }
+        writeln!(self.wtr, "];")?;
+
+        self.separator()?;
+        let supplementary_name = format!("{}_SUPPLEMENTARY", name);
+        let ranges = util::to_range_values(
+            map.range(0x10000..)
+                .filter(|(_, &v)| v != 0)
+                .map(|(&k, &v)| (k, v)),
+        );
+        self.ranges_to_unsigned_integer_slice(&supplementary_name, &ranges)?;
+
+        let cp_ty = self.rust_codepoint_type();
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "pub fn {}(cp: {}) -> u8 {{",
+            rust_fn_name(&name),
+            cp_ty
+        )?;
+        writeln!(self.wtr, "    let n = cp as u32;")?;
+        writeln!(self.wtr, "    if n < 0x10000 {{")?;
+        writeln!(
+            self.wtr,
+            "        let page = {}_BMP_INDEX[(n >> 8) as usize] as usize;",
+            name
+        )?;
+        writeln!(
+            self.wtr,
+            "        return {}_BMP_PAGES[page][(n & 0xFF) as usize];",
+            name
+        )?;
+        writeln!(self.wtr, "    }}")?;
+        writeln!(
+            self.wtr,
+            "    let found = {}.binary_search_by(|&(start, end, _)| {{",
+            supplementary_name
+        )?;
+        writeln!(self.wtr, "        if end < cp {{")?;
+        writeln!(self.wtr, "            ::core::cmp::Ordering::Less")?;
+        writeln!(self.wtr, "        }} else if start > cp {{")?;
+        writeln!(self.wtr, "            ::core::cmp::Ordering::Greater")?;
+        writeln!(self.wtr, "        }} else {{")?;
+        writeln!(self.wtr, "            ::core::cmp::Ordering::Equal")?;
+        writeln!(self.wtr, "        }}")?;
+        writeln!(self.wtr, "    }});")?;
+        writeln!(
+            self.wtr,
+            "    found.map(|i| {}[i].2).unwrap_or(0)",
+            supplementary_name
+        )?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a map from codepoint to enum value as a state machine over the
+    /// bytes of UTF-8, along with a function that classifies the codepoint
+    /// at the start of a `&[u8]` without decoding it.
+    ///
+    /// The value of each codepoint is its index in the `_ENUM` table, and
+    /// codepoints that aren't in the map have the value of the `_NONE`
+    /// constant. Bytes are first mapped to one of `_CLASSES` equivalence
+    /// classes by `_BYTE_CLASSES`, and `_TRANSITIONS` has one row of classes
+    /// for every state. Each entry is either a state, a value (marked by
+    /// having its high bit set) or 0 when the byte isn't valid in that
+    /// state. Identical states are only written once.
+    pub fn ranges_to_utf8_trie(
+        &mut self,
+        name: &str,
+        enum_map: &BTreeMap<String, BTreeSet<u32>>,
+    ) -> Result<()> {
+        self.ensure_not_c("a UTF-8 trie")?;
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit a UTF-8 trie as an FST");
+        }
+        if enum_map.len() > 0xFF {
+            return err!(
+                "cannot emit {} values in a UTF-8 trie (the maximum is 255)",
+                enum_map.len()
+            );
+        }
+        let none = enum_map.len() as u8;
+        let mut values = vec![none; 0x110000];
+        for (cp, i) in self.resolve_overlaps(enum_map)? {
+            values[cp as usize] = i as u8;
+        }
+        let trie = Utf8Trie::new(&values)?;
+
+        self.header()?;
+        self.separator()?;
+        let name = rust_const_name(name);
+        writeln!(
+            self.wtr,
+            "pub const {}_ENUM: &'static [&'static str] = &[",
+            name
+        )?;
+        for variant in enum_map.keys() {
+            self.wtr.write_str(&format!("{:?}, ", variant))?;
+        }
+        writeln!(self.wtr, "];")?;
+        self.len_const(&format!("{}_ENUM", name), enum_map.len())?;
+        writeln!(self.wtr, "pub const {}_NONE: u8 = {};", name, none)?;
+
+        self.separator()?;
+        writeln!(
+            self.wtr,
+            "pub const {}_CLASSES: usize = {};",
+            name, trie.classes
+        )?;
+        writeln!(self.wtr, "pub const {}_BYTE_CLASSES: [u8; 256] = [", name)?;
+        self.write_slice_u8(&trie.byte_classes)?;
+        writeln!(self.wtr, "];")?;
+
+        self.separator()?;
+        let transitions = format!("{}_TRANSITIONS", name);
+        self.slice_start(&transitions, "u16")?;
+        for &entry in &trie.transitions {
+            self.wtr.write_str(&format!("{}, ", entry))?;
+        }
+        self.slice_end(trie.transitions.len())?;
+        self.len_const(&transitions, trie.transitions.len())?;
+
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "pub fn {}(bytes: &[u8]) -> (u8, usize) {{",
+            rust_fn_name(&name)
+        )?;
+        writeln!(self.wtr, "    let mut state = 0;")?;
+        writeln!(self.wtr, "    for (i, &b) in bytes.iter().enumerate() {{")?;
+        writeln!(
+            self.wtr,
+            "        let class = {}_BYTE_CLASSES[b as usize] as usize;",
+            name
+        )?;
+        writeln!(
+            self.wtr,
+            "        let entry = {}[state * {}_CLASSES + class];",
+            transitions, name
+        )?;
+        writeln!(self.wtr, "        if entry & 0x8000 != 0 {{")?;
+        writeln!(self.wtr, "            return (entry as u8, i + 1);")?;
+        writeln!(self.wtr, "        }} else if entry == 0 {{")?;
+        writeln!(self.wtr, "            break;")?;
+        writeln!(self.wtr, "        }}")?;
+        writeln!(self.wtr, "        state = entry as usize;")?;
+        writeln!(self.wtr, "    }}")?;
+        writeln!(
+            self.wtr,
+            "    ({}_NONE, ::core::cmp::min(1, bytes.len()))",
+            name
+        )?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        if self.opts.validate_utf8_tables {
+            self.utf8_trie_tests(&name, &values, none)?;
+        }
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a test module for the UTF-8 trie with the given constant name,
+    /// given the value of every codepoint that it was built from.
+    ///
+    /// The expected results come from decoding each input with the standard
+    /// library and looking its codepoint up in `values`, not from the trie,
+    /// so that the tests check the trie against an independent reference.
+    fn utf8_trie_tests(
+        &mut self,
+        name: &str,
+        values: &[u8],
+        none: u8,
+    ) -> Result<()> {
+        let expected = |bytes: &[u8]| -> (u8, usize) {
+            let width = match bytes.first() {
+                None => return (none, 0),
+                Some(&b) if b < 0x80 => 1,
+                Some(&b) if b >= 0xF0 => 4,
+                Some(&b) if b >= 0xE0 => 3,
+                Some(_) => 2,
+            };
+            let decoded = bytes
+                .get(..width)
+                .and_then(|prefix| str::from_utf8(prefix).ok())
+                .and_then(|s| s.chars().next());
+            match decoded {
+                Some(c) => (values[c as usize], width),
+                None => (none, 1),
+            }
+        };
+
+        let mut inputs: BTreeSet<Vec<u8>> = BTreeSet::new();
+        inputs.insert(vec![]);
+        let boundaries: Vec<u32> = (1..values.len())
+            .filter(|&cp| values[cp] != values[cp - 1])
+            .map(|cp| cp as u32)
+            .collect();
+        let encode = |cp: u32| -> Vec<u8> {
+            match char::from_u32(cp) {
+                None => vec![0xED, 0xA0 | ((cp >> 6) & 0x1F) as u8, 0x80],
+                Some(c) => c.to_string().into_bytes(),
+            }
+        };
+        let mut rng = XorShift(fnv1a64(name.as_bytes()) | 1);
+        while inputs.len() < 256 {
+            let mut bytes = match rng.next() % 5 {
+                0 => encode(rng.next() as u32 % 0x110000),
+                1 => encode(rng.next() as u32 % 0x10000),
+                2 if !boundaries.is_empty() => {
+                    let i = rng.next() as usize % boundaries.len();
+                    encode(boundaries[i] - (rng.next() % 2) as u32)
+                }
+                3 => {
+                    let mut bytes = encode(rng.next() as u32 % 0x110000);
+                    if bytes.len() > 1 {
+                        bytes.pop();
+                    }
+                    bytes[0] = [0x80, 0xC0, 0xC1, 0xF5, 0xFF, bytes[0]]
+                        [rng.next() as usize % 6];
+                    bytes
+                }
+                _ => {
+                    (0..1 + rng.next() % 4).map(|_| rng.next() as u8).collect()
+                }
+            };
+            for _ in 0..rng.next() % 3 {
+                bytes.push(b'a' + (rng.next() % 26) as u8);
+            }
+            inputs.insert(bytes);
+        }
+
+        let fn_name = rust_fn_name(name);
+        self.separator()?;
+        writeln!(self.wtr, "#[cfg(test)]")?;
+        writeln!(self.wtr, "mod {}_tests {{", fn_name)?;
+        writeln!(
+            self.wtr,
+            "const CASES: &'static [(&'static [u8], u8, usize)] = &["
+        )?;
+        for bytes in &inputs {
+            let (value, len) = expected(bytes);
+            self.wtr.write_str(&format!(
+                "({}, {}, {}), ",
+                rust_byte_string(bytes),
+                value,
+                len
+            ))?;
+        }
+        writeln!(self.wtr, "];")?;
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(self.wtr, "#[test]")?;
+        writeln!(self.wtr, "fn {}() {{", fn_name)?;
+        writeln!(self.wtr, "    for &(bytes, value, len) in CASES {{")?;
+        writeln!(
+            self.wtr,
+            "        assert_eq!(super::{}(bytes), (value, len), \"{{:?}}\", bytes);",
+            fn_name
+        )?;
+        writeln!(self.wtr, "    }}")?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        writeln!(self.wtr, "}}")?;
+        Ok(())
+    }
+
+    fn ranges_to_unsigned_integer_slice(
+        &mut self,
+        name: &str,
+        table: &[(u32, u32, u64)],
+    ) -> Result<()> {
+        let cp_ty = self.rust_codepoint_type();
+        let num_ty = match table.iter().map(|&(_, _, n)| n).max() {
+            None => "u8",
+            Some(max_num) => smallest_unsigned_type(max_num),
+        };
+
+        self.slice_start(
+            name,
+            &format!("({}, {}, {})", cp_ty, cp_ty, num_ty),
+        )?;
+        let mut len = 0;
+        for &(start, end, num) in table {
+            let range = (self.rust_codepoint(start), self.rust_codepoint(end));
+            if let (Some(s), Some(e)) = range {
+                let src = format!("({}, {}, {}), ", s, e, num);
+                self.wtr.write_str(&src)?;
+                self.annotate_range(start, end)?;
+                len += 1;
+            }
+        }
+        self.slice_end(len)?;
+        self.len_const(name, len)?;
+        Ok(())
+    }
+
+    /// Write a map that associates strings to strings.
+    ///
+    /// The only supported output format is a sorted slice, which can be
+    /// binary searched.
+    pub fn string_to_string(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<String, String>,
+    ) -> Result<()> {
+        self.ensure_not_c("string to string")?;
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit string->string map as an FST");
+        }
+        let folded = self.fold_keys(map)?;
+        let map = folded.as_ref().unwrap_or(map);
+
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        self.slice_start(&name, "(&'static str, &'static str)")?;
+        for (k, v) in map {
+            self.wtr.write_str(&format!("({:?}, {:?}), ", k, v))?;
+        }
+        self.slice_end(map.len())?;
+        self.len_const(&name, map.len())?;
+        self.case_fold_lookup(&name, map.keys(), "&'static str")?;
+
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a Rust enum of the canonical values of a property.
+    ///
+    /// The given map should be a map from each normalized alias of a value
+    /// (as produced by `ucd_util::symbolic_name_normalize`) to its canonical
+    /// value. The enum implements `FromStr`, which accepts any alias using
+    /// the loose matching rules of UAX44-LM3, and has an `as_str` method
+    /// that returns the canonical value.
+    pub fn property_value_enum(
+        &mut self,
+        property: &str,
+        values: &BTreeMap<String, String>,
+    ) -> Result<()> {
+        self.ensure_not_c("property value enums")?;
+        let enum_name = rust_type_name(property);
+        let variants = value_variants(&enum_name, values)?;
+
+        self.header()?;
+        self.separator()?;
+        let decl = variants.keys().cloned().collect();
+        let decl = self.enum_variants(&enum_name, decl)?;
+        self.rust_enum_decl(&enum_name, &decl)?;
+
+        let name = format!("{}_ALIASES", rust_const_name(property));
+        self.enum_names(&enum_name, &name, values, &variants, None)
+    }
+
+    /// Write the names of the values of the enum written by
+    /// `ranges_to_rust_enum` with the same name.
+    ///
+    /// The given maps are from each normalized alias of a value to its
+    /// canonical value, as with `property_value_enum`, and from each
+    /// canonical value to its abbreviation. Like `property_value_enum`, the
+    /// enum implements `FromStr` and has an `as_str` method. It also has an
+    /// `abbreviation` method.
+    pub fn rust_enum_names(
+        &mut self,
+        name: &str,
+        values: &BTreeMap<String, String>,
+        abbreviations: &BTreeMap<String, String>,
+    ) -> Result<()> {
+        self.ensure_not_c("rust enum names")?;
+        let enum_name = rust_type_name(name);
+        let variants = value_variants(&enum_name, values)?;
+        for canonical in variants.values() {
+            if !abbreviations.contains_key(*canonical) {
+                return err!("missing abbreviation for {:?}", canonical);
+            }
+        }
+
+        self.header()?;
+        let name = format!("{}_ALIASES", rust_const_name(name));
+        self.enum_names(
+            &enum_name,
+            &name,
+            values,
+            &variants,
+            Some(abbreviations),
+        )
+    }
+
+    /// Write a table of the aliases of each variant of an enum and an impl
+    /// to convert between variants and names, for `property_value_enum` and
+    /// `rust_enum_names`.
+    fn enum_names(
+        &mut self,
+        enum_name: &str,
+        name: &str,
+        values: &BTreeMap<String, String>,
+        variants: &BTreeMap<String, &str>,
+        abbreviations: Option<&BTreeMap<String, String>>,
+    ) -> Result<()> {
+        self.separator()?;
+        self.slice_start(name, &format!("(&'static str, {})", enum_name))?;
+        for (alias, canonical) in values {
+            self.wtr.write_str(&format!(
+                "({:?}, {}::{}), ",
+                alias,
+                enum_name,
+                rust_type_name(canonical)
+            ))?;
+        }
+        self.slice_end(values.len())?;
+        self.len_const(name, values.len())?;
+
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(self.wtr, "impl {} {{", enum_name)?;
+        writeln!(
+            self.wtr,
+            "    /// Return the canonical name of this value.",
+        )?;
+        writeln!(self.wtr, "    pub fn as_str(self) -> &'static str {{")?;
+        writeln!(self.wtr, "        match self {{")?;
+        for (variant, canonical) in variants {
+            writeln!(
+                self.wtr,
+                "            {}::{} => {:?},",
+                enum_name, variant, canonical
+            )?;
+        }
+        writeln!(self.wtr, "        }}")?;
+        writeln!(self.wtr, "    }}")?;
+        if let Some(abbreviations) = abbreviations {
+            writeln!(self.wtr)?;
+            writeln!(
+                self.wtr,
+                "    /// Return the abbreviated name of this value.",
+            )?;
+            writeln!(
+                self.wtr,
+                "    pub fn abbreviation(self) -> &'static str {{"
+            )?;
+            writeln!(self.wtr, "        match self {{")?;
+            for (variant, canonical) in variants {
+                writeln!(
+                    self.wtr,
+                    "            {}::{} => {:?},",
+                    enum_name, variant, abbreviations[*canonical]
+                )?;
+            }
+            writeln!(self.wtr, "        }}")?;
+            writeln!(self.wtr, "    }}")?;
+        }
+        writeln!(self.wtr, "}}")?;
+        writeln!(self.wtr)?;
+        writeln!(self.wtr, "impl ::core::str::FromStr for {} {{", enum_name)?;
+        writeln!(self.wtr, "    type Err = ();")?;
+        writeln!(self.wtr)?;
+        writeln!(
+            self.wtr,
+            "    fn from_str(s: &str) -> Result<{}, ()> {{",
+            enum_name
+        )?;
+        writeln!(self.wtr, "        let mut s = s.as_bytes();")?;
+        writeln!(
+            self.wtr,
+            "        if s.len() > 2 && s[..2].eq_ignore_ascii_case(b\"is\") {{"
+        )?;
+        writeln!(self.wtr, "            s = &s[2..];")?;
+        writeln!(self.wtr, "        }}")?;
+        writeln!(self.wtr, "        let query = s")?;
+        writeln!(self.wtr, "            .iter()")?;
+        writeln!(
+            self.wtr,
+            "            .filter(|&&b| b.is_ascii() && !b\" _-\".contains(&b))"
+        )?;
+        writeln!(self.wtr, "            .map(|b| b.to_ascii_lowercase());")?;
+        writeln!(
+            self.wtr,
+            "        {}.binary_search_by(|&(k, _)| k.bytes().cmp(query.clone()))",
+            name
+        )?;
+        writeln!(self.wtr, "            .map(|i| {}[i].1)", name)?;
+        writeln!(self.wtr, "            .map_err(|_| ())")?;
+        writeln!(self.wtr, "    }}")?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a map that associates strings to another map from strings to
+    /// strings.
+    ///
+    /// The only supported output format is a sorted slice, which can be
+    /// binary searched.
+    pub fn string_to_string_to_string(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<String, BTreeMap<String, String>>,
+    ) -> Result<()> {
+        self.ensure_not_c("string to string to string")?;
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit string->string map as an FST");
+        }
+        let folded = match self.opts.case_fold_keys {
+            None => None,
+            Some(_) => {
+                let mut inner = BTreeMap::new();
+                for (k, kv) in map {
+                    let kv = self.fold_keys(kv)?.unwrap();
+                    inner.insert(k.clone(), kv);
+                }
+                self.fold_keys(&inner)?
+            }
+        };
+        let map = folded.as_ref().unwrap_or(map);
+
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        self.slice_start(
+            &name,
+            "(&'static str, &'static [(&'static str, &'static str)])",
+        )?;
+        let mut first = true;
+        for (k1, kv) in map {
+            if !first {
+                writeln!(self.wtr, "")?;
+            }
+            first = false;
+
+            self.wtr.write_str(&format!("({:?}, &[", k1))?;
+            for (k2, v) in kv {
+                self.wtr.write_str(&format!("({:?}, {:?}), ", k2, v))?;
+            }
+            self.wtr.write_str("]), ")?;
+        }
+        self.slice_end(map.len())?;
+        self.len_const(&name, map.len())?;
+        if self.opts.case_fold_keys.is_some() {
+            let keys = map
+                .iter()
+                .flat_map(|(k1, kv)| std::iter::once(k1).chain(kv.keys()));
+            let fold = self.case_fold_fn(&name, keys)?;
+            self.wtr.indent_width(4)?;
+            writeln!(
+                self.wtr,
+                "pub fn {}(property: &str, value: &str) -> \
+                 Option<&'static str> {{",
+                rust_fn_name(&name),
+            )?;
+            writeln!(self.wtr, "    let cmp = |key: &str, query: &str| {{")?;
+            writeln!(
+                self.wtr,
+                "        key.chars().cmp(query.chars().map({}))",
+                fold
+            )?;
+            writeln!(self.wtr, "    }};")?;
+            writeln!(
+                self.wtr,
+                "    let i = {}.binary_search_by(|&(k, _)| cmp(k, property))\
+                 .ok()?;",
+                name,
+            )?;
+            writeln!(self.wtr, "    let values = {}[i].1;", name)?;
+            writeln!(
+                self.wtr,
+                "    let j = values.binary_search_by(|&(k, _)| cmp(k, value))\
+                 .ok()?;",
+            )?;
+            writeln!(self.wtr, "    Some(values[j].1)")?;
+            writeln!(self.wtr, "}}")?;
+            self.wtr.indent_width(2)?;
+        }
+
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a map that associates codepoints with other codepoints.
+    ///
+    /// This is the one method that writes such maps, in whichever format
+    /// this writer was configured with: a sorted slice of pairs by default,
+    /// an FST, a binary table, a function using a match expression (see
+    /// `WriterBuilder::rust_match`) or a slice with a Latin-1 fast path (see
+    /// `WriterBuilder::latin1_fast_path`). When using an FST, the keys and
+    /// values are 32-bit big endian unsigned integers.
+    pub fn codepoint_to_codepoint(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<u32, u32>,
+    ) -> Result<()> {
+        let map = self.kept(map);
+        let map = &*map;
+        self.ensure_not_c("codepoint to codepoint")?;
+        if self.opts.rust_match || self.opts.latin1_fast_path {
+            if self.opts.fst_dir.is_some() || self.opts.binary {
+                return err!(
+                    "cannot emit a codepoint to codepoint function as an \
+                     FST or a binary table"
+                );
+            }
+            if self.opts.rust_match {
+                return self.codepoint_to_codepoint_fn(name, map);
+            }
+            return self.codepoint_to_codepoint_latin1(name, map);
+        }
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        if self.opts.fst_dir.is_some() {
+            let mut builder = MapBuilder::memory();
+            for (&k, &v) in map {
+                builder.insert(u32_key(k), v as u64)?;
+            }
+            let map = builder.into_map();
+            self.fst(&name, map.as_fst(), true)?;
+        } else if self.opts.binary {
+            let records: Vec<Vec<u32>> =
+                map.iter().map(|(&k, &v)| vec![k, v]).collect();
+            self.binary_table(&name, "Mapping", &records)?;
+        } else {
+            self.codepoint_pairs_slice(&name, map.iter())?;
+        }
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write the given pairs of codepoints as a sorted slice of `(from, to)`
+    /// tuples, followed by the assertions of `self_check` if enabled.
+    fn codepoint_pairs_slice<'m, I>(
+        &mut self,
+        name: &str,
+        pairs: I,
+    ) -> Result<()>
+    where
+        I: Iterator<Item = (&'m u32, &'m u32)>,
+    {
+        let pairs: Vec<(u32, u32)> = pairs.map(|(&k, &v)| (k, v)).collect();
+        if self.opts.self_check {
+            for &(k, v) in &pairs {
+                self.check_codepoint(k)?;
+                self.check_codepoint(v)?;
+            }
+        }
+        let ty = self.rust_codepoint_type();
+        self.slice_start(name, &format!("({}, {})", ty, ty))?;
+        let mut len = 0;
+        for &(k, v) in &pairs {
+            if let (Some(k), Some(v)) =
+                (self.rust_codepoint(k), self.rust_codepoint(v))
+            {
+                self.wtr.write_str(&format!("({}, {}), ", k, v))?;
+                len += 1;
+            }
+        }
+        self.slice_end(len)?;
+        self.len_const(name, len)?;
+        if self.opts.self_check {
+            self.self_check_table(name, false, None)?;
+        }
+        Ok(())
+    }
+
+    /// Write a map that associates codepoints with other codepoints, split
+    /// into a dense array for the first 256 codepoints and a sorted slice for
+    /// the rest. A function that dispatches between the two is emitted too.
+    ///
+    /// Codepoints that aren't in the map are mapped to themselves. This does
+    /// not support the FST format.
+    fn codepoint_to_codepoint_latin1(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<u32, u32>,
+    ) -> Result<()> {
+        self.ensure_not_c("codepoint to codepoint with a Latin-1 array")?;
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit a Latin-1 fast path as an FST");
+        }
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        let cp_ty = self.rust_codepoint_type();
+        let dense: Vec<u32> =
+            (0..256).map(|cp| *map.get(&cp).unwrap_or(&cp)).collect();
+        let small = dense.iter().all(|&cp| cp < 256);
+        let ty = if small { "u8" } else { cp_ty };
+        writeln!(self.wtr, "pub const {}_LATIN1: [{}; 256] = [", name, ty)?;
+        for &cp in &dense {
+            let value = if small {
+                cp.to_string()
+            } else {
+                // This is checked by the caller.
+                self.rust_codepoint(cp).unwrap()
+            };
+            self.wtr.write_str(&format!("{}, ", value))?;
+        }
+        writeln!(self.wtr, "];")?;
+
+        self.separator()?;
+        if self.opts.self_check {
+            for (&k, &v) in map.range(..256) {
+                self.check_codepoint(k)?;
+                self.check_codepoint(v)?;
+            }
+        }
+        self.codepoint_pairs_slice(&name, map.range(256..))?;
+
+        let cast = match (small, self.opts.char_literals) {
+            (false, _) => "",
+            (true, false) => " as u32",
+            (true, true) => " as char",
+        };
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "pub fn {}(cp: {}) -> {} {{",
+            rust_fn_name(&name),
+            cp_ty,
+            cp_ty
+        )?;
+        let key = if self.opts.char_literals { "(cp as u32)" } else { "cp" };
+        writeln!(self.wtr, "    if {} < 256 {{", key)?;
+        writeln!(
+            self.wtr,
+            "        return {}_LATIN1[cp as usize]{};",
+            name, cast
+        )?;
+        writeln!(self.wtr, "    }}")?;
+        writeln!(
+            self.wtr,
+            "    match {}.binary_search_by_key(&cp, |&(k, _)| k) {{",
+            name
+        )?;
+        writeln!(self.wtr, "        Ok(i) => {}[i].1,", name)?;
+        writeln!(self.wtr, "        Err(_) => cp,")?;
+        writeln!(self.wtr, "    }}")?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a function that associates codepoints with strings.
+    ///
+    /// The function will use a match expression to map codepoints to their
+    /// strings. The fallback branch of the match returns `None`. This is
+    /// meant for maps that are small enough that a match beats searching a
+    /// table.
+    pub fn codepoint_to_string_fn(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<u32, String>,
+    ) -> Result<()> {
+        let map = self.kept(map);
+        let map = &*map;
+        self.ensure_not_c("codepoint to string fn")?;
+        self.header()?;
+        self.separator()?;
+        self.ensure_max_value_len(map)?;
+
+        let fn_name = rust_fn_name(name);
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "pub fn {}(cp: u32) -> Option<&'static str> {{",
+            fn_name
+        )?;
+        self.wtr.indent("    ");
+        self.wtr.write_str("match cp {")?;
+        self.wtr.flush_line()?;
+        self.wtr.indent("        ");
+        for (cp, value) in map {
+            self.wtr.write_str(&format!("{} => Some({:?}),", cp, value))?;
+            self.wtr.flush_line()?;
+        }
+        self.wtr.write_str("_ => None,")?;
+        self.wtr.flush_line()?;
+        self.wtr.indent("    ");
+        self.wtr.write_str("}")?;
+        self.wtr.flush_line()?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a function that associates codepoints with other codepoints.
+    ///
+    /// The function will use a match expression to map between codepoints.
+    /// The fallback branch of the match returns 0.
+    fn codepoint_to_codepoint_fn(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<u32, u32>,
+    ) -> Result<()> {
+        self.ensure_not_c("codepoint to codepoint fn")?;
+        self.header()?;
+        self.separator()?;
+
+        if !self.wrote_nonzero_use {
+            self.wrote_nonzero_use = true;
+            writeln!(self.wtr, "use std::num::NonZeroU32;")?;
+            self.separator()?;
+        }
+
+        let fn_name = rust_fn_name(name);
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "pub fn {}(cp: u32) -> Option<NonZeroU32> {{",
+            fn_name
+        )?;
+        self.wtr.indent("    ");
+        self.wtr.write_str(
+            "// new_unchecked is safe as ucd-generate checks \
+             that the destination",
+        )?;
+        self.wtr.flush_line()?;
+        self.wtr.write_str(
+            "// codepoint is non-zero at \
+             code generation time.",
+        )?;
+        self.wtr.flush_line()?;
+        self.wtr.write_str("unsafe {")?;
+        self.wtr.flush_line()?;
+        self.wtr.indent("        ");
+        self.wtr.write_str("match cp {")?;
+        self.wtr.flush_line()?;
+        self.wtr.indent("            ");
+        for (from, to) in map {
+            if *to == 0 {
+                return err!(
+                    "destination codepoint must not be 0 (NUL) for \
+                     rust-match output format"
+                );
+            }
+            self.wtr.write_str(&format!(
+                "{} => Some(NonZeroU32::new_unchecked({})),",
+                from, to
+            ))?;
+            self.wtr.flush_line()?;
+        }
+        self.wtr.write_str("_ => None,")?;
+        self.wtr.flush_line()?;
+        self.wtr.indent("        ");
+        self.wtr.write_str("}")?;
+        self.wtr.flush_line()?;
+        self.wtr.indent("    ");
+        self.wtr.write_str("}")?;
+        self.wtr.flush_line()?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a map that associates codepoints with other codepoints, where
+    /// each codepoint can be associated with possibly many other codepoints.
+    ///
+    /// This does not support the FST format.
+    pub fn multi_codepoint_to_codepoint(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<u32, BTreeSet<u32>>,
+        emit_flat_table: bool,
+    ) -> Result<()> {
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit codepoint multimaps as an FST");
+        }
+
+        let mut map2: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for (&k, vs) in map {
+            let vs2 = vs.iter().cloned().collect();
+            map2.insert(k, vs2);
+        }
+        self.codepoint_to_codepoints(name, &map2, emit_flat_table)
+    }
+
+    /// Write a map that associates codepoints with a sequence of other
+    /// codepoints.
+    ///
+    /// When `emit_flat_table` is true, each sequence is written as a fixed
+    /// size array padded by a sentinel value (see
+    /// `WriterBuilder::flat_table_pad`). The sentinel must not occur in any
+    /// of the sequences. The arrays have 3 elements, or as many elements as
+    /// the longest sequence if that is longer.
+    ///
+    /// This does not support the FST format.
+    pub fn codepoint_to_codepoints(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<u32, Vec<u32>>,
+        emit_flat_table: bool,
+    ) -> Result<()> {
+        let map = self.kept(map);
+        let map = &*map;
+        self.ensure_not_c("codepoint to codepoints")?;
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit codepoint->codepoints map as an FST");
+        }
+        let ty = self.rust_codepoint_type();
+        let flat_padding = self.flat_table_pad();
+        let flat_width = map.values().map(|vs| vs.len()).max().unwrap_or(0);
+        let flat_width = std::cmp::max(3, flat_width);
+        if emit_flat_table && self.opts.flat_table_pad.is_some() {
+            if self.rust_codepoint(flat_padding).is_none() {
+                return err!(
+                    "flat-table padding {:X} is not a valid {}",
+                    flat_padding,
+                    ty,
+                );
+            }
+            if let Some((k, _)) =
+                map.iter().find(|(_, vs)| vs.contains(&flat_padding))
+            {
+                return err!(
+                    "flat-table padding {:X} cannot be used since it is \
+                     present in the value array for {:X}",
+                    flat_padding,
+                    k,
+                );
+            }
+        }
+
+        if self.opts.self_check {
+            for (&k, vs) in map {
+                self.check_codepoint(k)?;
+                for &v in vs {
+                    self.check_codepoint(v)?;
+                }
+            }
+        }
+
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        if !emit_flat_table {
+            self.slice_start(&name, &format!("({}, &'static [{}])", ty, ty))?;
+        } else {
+            self.slice_start(
+                &name,
+                &format!("({}, [{}; {}])", ty, ty, flat_width),
+            )?;
+        }
+        let mut len = 0;
+        'LOOP: for (&k, vs) in map {
+            // Make sure both our keys and values can be represented in the
+            // user's chosen codepoint format.
+            let kstr = match self.rust_codepoint(k) {
+                None => continue 'LOOP,
+                Some(k) => k,
+            };
+
+            let (padded_vs, slice_prefix) = if emit_flat_table {
+                // This check is for future-proofing and cannot be hit
+                // currently.
+                if vs.contains(&flat_padding) {
+                    return err!(
+                        "flat-table --chars representation cannot be used when \
+                         the NUL character is present in the value array. (This \
+                         error probably can be fixed by removing `--chars`)"
+                    );
+                }
+                let res = vs
+                    .iter()
+                    .copied()
+                    .chain(std::iter::repeat(flat_padding))
+                    .take(flat_width)
+                    .collect::<Vec<_>>();
+                (res, "")
+            } else {
+                (vs.clone(), "&")
+            };
+            let mut vstrs = vec![];
+            for v in padded_vs {
+                match self.rust_codepoint(v) {
+                    None => continue 'LOOP,
+                    Some(v) => vstrs.push(v),
+                }
+            }
+
+            self.wtr.write_str(&format!("({}, {}[", kstr, slice_prefix))?;
+            if vstrs.len() == 1 {
+                self.wtr.write_str(&format!("{}", &vstrs[0]))?;
+            } else {
+                for v in vstrs {
+                    self.wtr.write_str(&format!("{}, ", v))?;
+                }
+            }
+            self.wtr.write_str("]), ")?;
+            len += 1;
+        }
+        self.slice_end(len)?;
+        self.len_const(&name, len)?;
+        if self.opts.self_check {
+            let pad = if emit_flat_table { Some(flat_padding) } else { None };
+            self.self_check_table(&name, true, pad)?;
+        }
+        if emit_flat_table && self.opts.flat_table_trim {
+            self.flat_table_trim_fn(&name, flat_padding, flat_width)?;
+        }
+
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a set of pairs of codepoints, such as the two Regional
+    /// Indicators of each flag emoji, as a sorted slice, followed by a
+    /// function that tests whether a pair is in the set.
+    ///
+    /// Each pair is a sequence of two codepoints, not a range.
+    pub fn codepoint_pairs(
+        &mut self,
+        name: &str,
+        pairs: &BTreeSet<(u32, u32)>,
+    ) -> Result<()> {
+        self.ensure_not_c("codepoint pairs")?;
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit codepoint pairs as an FST");
+        }
+        let pairs: BTreeSet<(u32, u32)> = pairs
+            .iter()
+            .copied()
+            .filter(|&(a, b)| !self.leaves_out(a) && !self.leaves_out(b))
+            .collect();
+        let pairs = &pairs;
+        self.ensure_nonempty(name, pairs.len())?;
+        for &(a, b) in pairs {
+            self.check_codepoint(a)?;
+            self.check_codepoint(b)?;
+        }
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        let ty = self.rust_codepoint_type();
+        self.slice_start(&name, &format!("({}, {})", ty, ty))?;
+        for &(a, b) in pairs {
+            let (a, b) = (self.rust_codepoint(a), self.rust_codepoint(b));
+            self.wtr.write_str(&format!(
+                "({}, {}), ",
+                a.unwrap(),
+                b.unwrap()
+            ))?;
+        }
+        self.slice_end(pairs.len())?;
+        self.len_const(&name, pairs.len())?;
+
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        let key = if self.opts.char_literals {
+            "(a, b)"
+        } else {
+            "(a as u32, b as u32)"
+        };
+        writeln!(
+            self.wtr,
+            "pub fn {}_contains(a: char, b: char) -> bool {{",
+            rust_fn_name(&name)
+        )?;
+        writeln!(self.wtr, "    {}.binary_search(&{}).is_ok()", name, key)?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a map from pairs of codepoints to the codepoint they compose,
+    /// such as the canonical compositions used by NFC, along with a
+    /// `compose(a, b) -> Option<char>` function that looks pairs up.
+    ///
+    /// The table is a slice of `((first, second), composed)` entries,
+    /// sorted by pair. When `two_level` is true, the table instead has
+    /// `(first, start, end)` entries sorted by the first codepoint, where
+    /// `NAME_PAIRS[start..end]` holds the `(second, composed)` entries for
+    /// pairs with that first codepoint, sorted by the second codepoint.
+    ///
+    /// This does not support the FST format.
+    pub fn codepoint_pairs_to_codepoint(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<(u32, u32), u32>,
+        two_level: bool,
+    ) -> Result<()> {
+        self.ensure_not_c("codepoint pairs to codepoint")?;
+        if self.opts.fst_dir.is_some() {
+            return err!(
+                "cannot emit codepoint pairs->codepoint map as an FST"
+            );
+        }
+        let map: BTreeMap<(u32, u32), u32> = map
+            .iter()
+            .filter(|&(&(a, b), _)| !self.leaves_out(a) && !self.leaves_out(b))
+            .map(|(&k, &v)| (k, v))
+            .collect();
+        let map = &map;
+        for (&(a, b), &v) in map {
+            for cp in [a, b, v] {
+                self.check_codepoint(cp)?;
+            }
+        }
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        let ty = self.rust_codepoint_type();
+        let cp = |cp| self.rust_codepoint(cp).unwrap();
+        let (key, value) = if self.opts.char_literals {
+            ("a", "b")
+        } else {
+            ("a as u32", "b as u32")
+        };
+        let found = if self.opts.char_literals {
+            "Some(composed)"
+        } else {
+            "char::from_u32(composed)"
+        };
+        if !two_level {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(&(a, b), &v)| {
+                    format!("(({}, {}), {}), ", cp(a), cp(b), cp(v))
+                })
+                .collect();
+            self.slice_start(&name, &format!("(({}, {}), {})", ty, ty, ty))?;
+            for entry in &entries {
+                self.wtr.write_str(entry)?;
+            }
+            self.slice_end(entries.len())?;
+            self.len_const(&name, entries.len())?;
+            self.separator()?;
+            self.wtr.indent_width(4)?;
+            writeln!(
+                self.wtr,
+                "pub fn compose(a: char, b: char) -> Option<char> {{"
+            )?;
+            writeln!(self.wtr, "    let key = ({}, {});", key, value)?;
+            writeln!(
+                self.wtr,
+                "    let i = {}.binary_search_by_key(&key, |&(k, _)| k).ok()?;",
+                name
+            )?;
+            writeln!(self.wtr, "    let composed = {}[i].1;", name)?;
+            writeln!(self.wtr, "    {}", found)?;
+            writeln!(self.wtr, "}}")?;
+            self.wtr.indent_width(2)?;
+            self.wtr.flush()?;
+            return Ok(());
+        }
+
+        let pairs_name = format!("{}_PAIRS", name);
+        let mut firsts: Vec<String> = vec![];
+        let mut pairs: Vec<String> = vec![];
+        let mut last: Option<u32> = None;
+        for (&(a, b), &v) in map {
+            if last != Some(a) {
+                if last.is_some() {
+                    firsts
+                        .last_mut()
+                        .unwrap()
+                        .push_str(&format!("{}), ", pairs.len()));
+                }
+                firsts.push(format!("({}, {}, ", cp(a), pairs.len()));
+                last = Some(a);
+            }
+            pairs.push(format!("({}, {}), ", cp(b), cp(v)));
+        }
+        if let Some(first) = firsts.last_mut() {
+            first.push_str(&format!("{}), ", pairs.len()));
+        }
+        self.slice_start(&name, &format!("({}, u32, u32)", ty))?;
+        for first in &firsts {
+            self.wtr.write_str(first)?;
+        }
+        self.slice_end(firsts.len())?;
+        self.len_const(&name, firsts.len())?;
+        self.separator()?;
+        self.slice_start(&pairs_name, &format!("({}, {})", ty, ty))?;
+        for pair in &pairs {
+            self.wtr.write_str(pair)?;
+        }
+        self.slice_end(pairs.len())?;
+        self.len_const(&pairs_name, pairs.len())?;
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "pub fn compose(a: char, b: char) -> Option<char> {{"
+        )?;
+        if !self.opts.char_literals {
+            writeln!(self.wtr, "    let (a, b) = ({}, {});", key, value)?;
+        }
+        writeln!(
+            self.wtr,
+            "    let i = {}.binary_search_by_key(&a, |&(k, _, _)| k).ok()?;",
+            name
+        )?;
+        writeln!(self.wtr, "    let (_, start, end) = {}[i];", name)?;
+        writeln!(
+            self.wtr,
+            "    let pairs = &{}[start as usize..end as usize];",
+            pairs_name
+        )?;
+        writeln!(
+            self.wtr,
+            "    let j = pairs.binary_search_by_key(&b, |&(k, _)| k).ok()?;"
+        )?;
+        writeln!(self.wtr, "    let composed = pairs[j].1;")?;
+        writeln!(self.wtr, "    {}", found)?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write an enum of classes, an enum of the outcomes of a pair of
+    /// classes and a table of the outcome of every pair of classes, such as
+    /// the pair table of UAX #14.
+    ///
+    /// Each outcome is given as its variant name and a description, and
+    /// `table[a][b]` is the index of the outcome of the class `classes[a]`
+    /// followed by the class `classes[b]`. A function named after the table
+    /// that looks up the outcome of a pair is also written.
+    pub fn pair_table(
+        &mut self,
+        name: &str,
+        classes: &[String],
+        outcome_enum: &str,
+        outcomes: &[(&str, &str)],
+        table: &[Vec<usize>],
+    ) -> Result<()> {
+        self.ensure_not_c("pair tables")?;
+        if table.len() != classes.len()
+            || table.iter().any(|row| row.len() != classes.len())
+        {
+            return err!("pair table must have a row and column per class");
+        }
+        if table.iter().flatten().any(|&i| i >= outcomes.len()) {
+            return err!("pair table refers to an unknown outcome");
+        }
+        self.header()?;
+        self.separator()?;
+
+        let enum_name = rust_type_name(name);
+        let variants: Vec<(String, Option<isize>)> = classes
+            .iter()
+            .enumerate()
+            .map(|(i, class)| (rust_type_name(class), Some(i as isize)))
+            .collect();
+        self.rust_enum_decl(&enum_name, &variants)?;
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]",
+        )?;
+        writeln!(self.wtr, "pub enum {} {{", outcome_enum)?;
+        for &(variant, description) in outcomes {
+            writeln!(self.wtr, "    /// {}", description)?;
+            writeln!(self.wtr, "    {},", variant)?;
+        }
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.separator()?;
+
+        let table_name = format!("{}_PAIRS", rust_const_name(name));
+        self.slice_start(
+            &table_name,
+            &format!("[{}; {}]", outcome_enum, classes.len()),
+        )?;
+        for (class, row) in classes.iter().zip(table) {
+            for (j, &outcome) in row.iter().enumerate() {
+                let start = if j == 0 { "[" } else { "" };
+                let end = if j + 1 == row.len() { "]," } else { "," };
+                self.wtr.write_str(&format!(
+                    "{}{}::{}{} ",
+                    start, outcome_enum, outcomes[outcome].0, end
+                ))?;
+            }
+            self.wtr.end_line_with_comment(class)?;
+        }
+        self.slice_end(classes.len())?;
+        self.len_const(&table_name, classes.len())?;
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "/// Return what is allowed between the given classes, when `before` \
+             is"
+        )?;
+        writeln!(self.wtr, "/// followed by `after`.")?;
+        writeln!(
+            self.wtr,
+            "pub fn {}_pair(before: {}, after: {}) -> {} {{",
+            rust_fn_name(name),
+            enum_name,
+            enum_name,
+            outcome_enum
+        )?;
+        writeln!(
+            self.wtr,
+            "    {}[before as usize][after as usize]",
+            table_name
+        )?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a declaration for each of the given modules, in order.
+    pub fn modules(&mut self, modules: &[String]) -> Result<()> {
+        self.ensure_not_c("modules")?;
+        self.header()?;
+        self.separator()?;
+        for module in modules {
+            writeln!(self.wtr, "pub mod {};", module)?;
+        }
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a declaration for each of the given modules, each of which is
+    /// only compiled when its feature is enabled, given as `(feature,
+    /// module)` pairs.
+    ///
+    /// The contents of the first module whose feature is enabled are also
+    /// re-exported, so the modules should be given from most to least
+    /// preferred. It is an error to enable none of the features.
+    pub fn version_dispatch(
+        &mut self,
+        modules: &[(String, String)],
+    ) -> Result<()> {
+        self.ensure_not_c("version dispatch")?;
+        if modules.is_empty() {
+            return err!("cannot dispatch to an empty set of modules");
+        }
+        self.header()?;
+        self.separator()?;
+        for (feature, module) in modules {
+            writeln!(self.wtr, "#[cfg(feature = {:?})]", feature)?;
+            writeln!(self.wtr, "pub mod {};", module)?;
+        }
+        self.separator()?;
+        writeln!(
+            self.wtr,
+            "// The first module whose feature is enabled is re-exported."
+        )?;
+        for (i, (feature, module)) in modules.iter().enumerate() {
+            let earlier: Vec<String> = modules[..i]
+                .iter()
+                .map(|(feature, _)| format!("feature = {:?}", feature))
+                .collect();
+            if earlier.is_empty() {
+                writeln!(self.wtr, "#[cfg(feature = {:?})]", feature)?;
+            } else {
+                writeln!(
+                    self.wtr,
+                    "#[cfg(all(feature = {:?}, not(any({}))))]",
+                    feature,
+                    earlier.join(", ")
+                )?;
+            }
+            writeln!(self.wtr, "pub use self::{}::*;", module)?;
+        }
+        let all: Vec<String> = modules
+            .iter()
+            .map(|(feature, _)| format!("feature = {:?}", feature))
+            .collect();
+        self.separator()?;
+        writeln!(self.wtr, "#[cfg(not(any({})))]", all.join(", "))?;
+        let features: Vec<&str> =
+            modules.iter().map(|(feature, _)| feature.as_str()).collect();
+        writeln!(
+            self.wtr,
+            "compile_error!(\"one of these features must be enabled: {}\");",
+            features.join(", ")
+        )?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Return an error if the given codepoint can't be represented in this
+    /// writer's codepoint format.
+    fn check_codepoint(&self, cp: u32) -> Result<()> {
+        if self.rust_codepoint(cp).is_none() {
+            return err!(
+                "U+{:04X} cannot be written as a {}",
+                cp,
+                self.rust_codepoint_type()
+            );
+        }
+        Ok(())
+    }
+
+    /// Write compile time assertions checking that the keys of the given
+    /// codepoint table are sorted and that its keys and values are valid
+    /// scalar values.
+    ///
+    /// When `multi` is true, the value of each entry is a slice (or array)
+    /// of codepoints, where any occurrences of `pad` are ignored.
+    fn self_check_table(
+        &mut self,
+        name: &str,
+        multi: bool,
+        pad: Option<u32>,
+    ) -> Result<()> {
+        let cast = if self.opts.char_literals { " as u32" } else { "" };
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(self.wtr, "const _: () = {{")?;
+        writeln!(self.wtr, "    const fn is_scalar(cp: u32) -> bool {{")?;
+        writeln!(
+            self.wtr,
+            "        cp <= 0x10FFFF && !(0xD800 <= cp && cp <= 0xDFFF)"
+        )?;
+        writeln!(self.wtr, "    }}")?;
+        writeln!(self.wtr, "    let table = {};", name)?;
+        writeln!(self.wtr, "    let mut i = 0;")?;
+        writeln!(self.wtr, "    while i < table.len() {{")?;
+        writeln!(
+            self.wtr,
+            "        assert!(is_scalar(table[i].0{}), \"{}: invalid key\");",
+            cast, name
+        )?;
+        writeln!(
+            self.wtr,
+            "        assert!(\n            \
+             i == 0 || (table[i - 1].0{cast}) < (table[i].0{cast}),\n            \
+             \"{name}: keys are not sorted\",\n        \
+             );",
+            cast = cast,
+            name = name,
+        )?;
+        if multi {
+            writeln!(self.wtr, "        let values = &table[i].1;")?;
+            writeln!(self.wtr, "        let mut j = 0;")?;
+            writeln!(self.wtr, "        while j < values.len() {{")?;
+            let valid = match pad {
+                None => format!("is_scalar(values[j]{})", cast),
+                Some(pad) => format!(
+                    "is_scalar(values[j]{cast}) || values[j]{cast} == {pad}",
+                    cast = cast,
+                    pad = pad,
+                ),
+            };
+            writeln!(
+                self.wtr,
+                "            assert!({}, \"{}: invalid value\");",
+                valid, name
+            )?;
+            writeln!(self.wtr, "            j += 1;")?;
+            writeln!(self.wtr, "        }}")?;
+        } else {
+            writeln!(
+                self.wtr,
+                "        assert!(is_scalar(table[i].1{}), \"{}: invalid value\");",
+                cast, name
+            )?;
+        }
+        writeln!(self.wtr, "        i += 1;")?;
+        writeln!(self.wtr, "    }}")?;
+        writeln!(self.wtr, "}};")?;
+        self.wtr.indent_width(2)?;
+        Ok(())
+    }
+
+    /// Write the padding constant and trim helper for a flat table.
+    fn flat_table_trim_fn(
+        &mut self,
+        name: &str,
+        padding: u32,
+        width: usize,
+    ) -> Result<()> {
+        let ty = self.rust_codepoint_type();
+        // This is checked by the caller.
+        let pad = self.rust_codepoint(padding).unwrap();
+        self.separator()?;
+        writeln!(self.wtr, "pub const {}_PAD: {} = {};", name, ty, pad)?;
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "pub fn {}_trim(values: &[{}; {}]) -> &[{}] {{",
+            rust_fn_name(name),
+            ty,
+            width,
+            ty,
+        )?;
+        writeln!(
+            self.wtr,
+            "    match values.iter().position(|&v| v == {}_PAD) {{",
+            name,
+        )?;
+        writeln!(self.wtr, "        None => values,")?;
+        writeln!(self.wtr, "        Some(len) => &values[..len],")?;
+        writeln!(self.wtr, "    }}")?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        Ok(())
+    }
+
+    /// Return the sentinel used to pad unoccupied entries of flat tables.
+    fn flat_table_pad(&self) -> u32 {
+        match self.opts.flat_table_pad {
+            Some(pad) => pad,
+            None if self.opts.char_literals => 0,
+            None => !0,
+        }
+    }
+
+    /// Write a map that associates codepoints to strings.
+    ///
+    /// When the output format is an FST, then the FST map emitted is from
+    /// codepoint to u64, where the string is encoded into the u64. The least
+    /// significant byte of the u64 corresponds to the first byte in the
+    /// string. The end of a string is delimited by the zero byte. If any
+    /// string is more than 8 bytes or contains a `NUL` byte, then all of the
+    /// distinct strings are instead emitted in a sorted `_VALUES` slice, and
+    /// the FST maps each codepoint to an index into that slice.
+    ///
+    /// If a maximum value length is set, then an error is returned if any
+    /// string exceeds it, regardless of the output format.
+    pub fn codepoint_to_string(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<u32, String>,
+    ) -> Result<()> {
+        let map = self.kept(map);
+        let map = &*map;
+        self.ensure_not_c("codepoint to string")?;
+        self.header()?;
+        self.separator()?;
+        self.ensure_max_value_len(map)?;
+
+        let name = rust_const_name(name);
+        if self.opts.fst_dir.is_some() {
+            let mut builder = MapBuilder::memory();
+            if map.values().all(|v| pack_str(v).is_ok()) {
+                for (&k, v) in map {
+                    builder.insert(u32_key(k), pack_str(v)?)?;
+                }
+            } else {
+                // Some values can't be packed into a u64, so store every
+                // value in a side table and map codepoints to indices into
+                // that table instead.
+                let values: BTreeSet<&str> =
+                    map.values().map(|v| &**v).collect();
+                let values: Vec<&str> = values.into_iter().collect();
+                self.string_slice(&format!("{}_VALUES", name), &values)?;
+                for (&k, v) in map {
+                    let i = values.binary_search(&&**v).unwrap();
+                    builder.insert(u32_key(k), i as u64)?;
+                }
+            }
+            let map = builder.into_map();
+            self.fst(&name, map.as_fst(), true)?;
+        } else {
+            let table: Vec<(u32, &str)> =
+                map.iter().map(|(&k, v)| (k, &**v)).collect();
+            self.codepoint_to_string_slice(&name, &table)?;
+        }
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    fn string_slice(&mut self, name: &str, values: &[&str]) -> Result<()> {
+        self.slice_start(name, "&'static str")?;
+        for value in values {
+            self.wtr.write_str(&format!("{:?}, ", value))?;
+        }
+        self.slice_end(values.len())?;
+        self.len_const(name, values.len())?;
+        Ok(())
+    }
+
+    fn codepoint_to_string_slice(
+        &mut self,
+        name: &str,
+        table: &[(u32, &str)],
+    ) -> Result<()> {
+        let ty = self.rust_codepoint_type();
+        self.slice_start(name, &format!("({}, &'static str)", ty))?;
+        let mut len = 0;
+        for &(cp, ref s) in table {
+            if let Some(cp) = self.rust_codepoint(cp) {
+                self.wtr.write_str(&format!("({}, {:?}), ", cp, s))?;
+                len += 1;
+            }
+        }
+        self.slice_end(len)?;
+        self.len_const(name, len)?;
+        Ok(())
+    }
+
+    /// Write a table of codepoint ranges whose names are formed by
+    /// appending the codepoint's hexadecimal value to a prefix, along with a
+    /// `name_of` function that computes the name of a codepoint in one of
+    /// those ranges.
+    ///
+    /// The ranges given must be sorted and non-overlapping. This does not
+    /// support the FST format.
+    pub fn ranges_to_name_patterns(
+        &mut self,
+        name: &str,
+        table: &[(u32, u32, String)],
+    ) -> Result<()> {
+        self.ensure_not_c("ranges to name patterns")?;
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit name patterns as an FST");
+        }
+        let table: Vec<(u32, u32, String)> = table
+            .iter()
+            .flat_map(|(start, end, prefix)| {
+                self.clip(*start, *end)
+                    .into_iter()
+                    .map(move |(s, e)| (s, e, prefix.clone()))
+            })
+            .collect();
+
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        let ty = self.rust_codepoint_type();
+        self.slice_start(&name, &format!("({}, {}, &'static str)", ty, ty))?;
+        let mut len = 0;
+        for &(start, end, ref prefix) in &table {
+            let range = (self.rust_codepoint(start), self.rust_codepoint(end));
+            if let (Some(start), Some(end)) = range {
+                let src = format!("({}, {}, {:?}), ", start, end, prefix);
+                self.wtr.write_str(&src)?;
+                len += 1;
+            }
+        }
+        self.slice_end(len)?;
+        self.len_const(&name, len)?;
+
+        let hex = if self.opts.char_literals { "cp as u32" } else { "cp" };
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(self.wtr, "pub fn name_of(cp: {}) -> Option<String> {{", ty)?;
+        writeln!(self.wtr, "    use core::cmp::Ordering;")?;
+        writeln!(self.wtr)?;
+        writeln!(self.wtr, "    let i = {}", name)?;
+        writeln!(self.wtr, "        .binary_search_by(|&(start, end, _)| {{")?;
+        writeln!(self.wtr, "            if end < cp {{")?;
+        writeln!(self.wtr, "                Ordering::Less")?;
+        writeln!(self.wtr, "            }} else if start > cp {{")?;
+        writeln!(self.wtr, "                Ordering::Greater")?;
+        writeln!(self.wtr, "            }} else {{")?;
+        writeln!(self.wtr, "                Ordering::Equal")?;
+        writeln!(self.wtr, "            }}")?;
+        writeln!(self.wtr, "        }})")?;
+        writeln!(self.wtr, "        .ok()?;")?;
+        writeln!(
+            self.wtr,
+            "    Some(format!(\"{{}}{{:04X}}\", {}[i].2, {}))",
+            name, hex
+        )?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write constants describing a map from character names to codepoints,
+    /// without the map itself, for consumers that generate names
+    /// algorithmically themselves.
+    ///
+    /// These are the number of names, the number of codepoints with a name,
+    /// the length of the longest name, and the Hangul syllable and
+    /// ideograph ranges along with the number of Hangul syllables. Names of
+    /// codepoints left out by `max_age` or `only` are not counted.
+    pub fn name_constants(
+        &mut self,
+        name: &str,
+        names: &BTreeMap<String, u32>,
+        hangul: &[(u32, u32)],
+        ideographs: &[(u32, u32)],
+    ) -> Result<()> {
+        self.ensure_not_c("name constants")?;
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit name constants as an FST");
+        }
+        let (mut count, mut max_len) = (0, 0);
+        let mut codepoints = BTreeSet::new();
+        for (n, &cp) in names {
+            if self.leaves_out(cp) {
+                continue;
+            }
+            count += 1;
+            max_len = max_len.max(n.len());
+            codepoints.insert(cp);
+        }
+        let clip = |ranges: &[(u32, u32)]| -> Vec<(u32, u32)> {
+            ranges.iter().flat_map(|&(s, e)| self.clip(s, e)).collect()
+        };
+        let (hangul, ideographs) = (clip(hangul), clip(ideographs));
+        let hangul_count: u32 = hangul.iter().map(|&(s, e)| e - s + 1).sum();
+
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        writeln!(
+            self.wtr,
+            "/// The number of names, including aliases and algorithmically \
+             generated names."
+        )?;
+        writeln!(self.wtr, "pub const {}_COUNT: usize = {};", name, count)?;
+        writeln!(self.wtr, "/// The number of codepoints with a name.")?;
+        writeln!(
+            self.wtr,
+            "pub const {}_CODEPOINT_COUNT: usize = {};",
+            name,
+            codepoints.len()
+        )?;
+        writeln!(self.wtr, "/// The length, in bytes, of the longest name.")?;
+        writeln!(
+            self.wtr,
+            "pub const {}_MAX_LEN: usize = {};",
+            name, max_len
+        )?;
+        writeln!(self.wtr, "/// The number of Hangul syllables.")?;
+        writeln!(
+            self.wtr,
+            "pub const {}_HANGUL_SYLLABLE_COUNT: usize = {};",
+            name, hangul_count
+        )?;
+
+        let ty = self.rust_codepoint_type();
+        for (suffix, ranges) in [
+            ("HANGUL_SYLLABLE_RANGES", hangul),
+            ("IDEOGRAPH_RANGES", ideographs),
+        ] {
+            let const_name = format!("{}_{}", name, suffix);
+            self.separator()?;
+            self.slice_start(&const_name, &format!("({}, {})", ty, ty))?;
+            let mut len = 0;
+            for (start, end) in ranges {
+                let range =
+                    (self.rust_codepoint(start), self.rust_codepoint(end));
+                if let (Some(start), Some(end)) = range {
+                    self.wtr.write_str(&format!("({}, {}), ", start, end))?;
+                    len += 1;
+                }
+            }
+            self.slice_end(len)?;
+            self.len_const(&const_name, len)?;
+        }
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a list of break algorithm tests, where each test is the
+    /// expected sequence of segments (e.g., lines or grapheme clusters) of
+    /// the test's input.
+    pub fn break_tests(
+        &mut self,
+        name: &str,
+        tests: &[Vec<String>],
+    ) -> Result<()> {
+        self.ensure_not_c("break tests")?;
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        self.slice_start(&name, "&'static [&'static str]")?;
+        for segments in tests {
+            self.wtr.write_str("&[")?;
+            for segment in segments {
+                self.wtr.write_str(&format!("{:?}, ", segment))?;
+            }
+            self.wtr.write_str("], ")?;
+        }
+        self.slice_end(tests.len())?;
+        self.len_const(&name, tests.len())?;
+        Ok(())
+    }
+
+    /// Write the tests from `BidiTest.txt`.
+    ///
+    /// Consecutive tests sharing the same levels and reordering are grouped
+    /// together, as they are in `BidiTest.txt`. Each group is a tuple of the
+    /// levels (where `None` means "removed by rule X9"), the reordering and
+    /// the tests. Each test is a tuple of the input bidi classes and the
+    /// paragraph directions to run the test with, using the same numbering
+    /// as `BidiCharacterTest.txt` (0 for LTR, 1 for RTL and 2 for auto).
+    pub fn bidi_tests(
+        &mut self,
+        name: &str,
+        tests: &[BidiTest],
+    ) -> Result<()> {
+        self.ensure_not_c("bidi tests")?;
+        self.header()?;
+        self.separator()?;
+
+        type Group<'a> = (&'a [Option<u8>], &'a [usize], Vec<&'a BidiTest>);
+
+        let mut groups: Vec<Group<'_>> = vec![];
+        for test in tests {
+            match groups.last_mut() {
+                Some(&mut (levels, reorder, ref mut group))
+                    if levels == &*test.levels
+                        && reorder == &*test.reorder =>
+                {
+                    group.push(test);
+                }
+                _ => groups.push((&test.levels, &test.reorder, vec![test])),
+            }
+        }
+
+        let name = rust_const_name(name);
+        self.slice_start(
+            &name,
+            "(\n  \
+             &'static [Option<u8>],\n  \
+             &'static [usize],\n  \
+             &'static [(&'static [&'static str], &'static [u8])],\n\
+             )",
+        )?;
+        for (levels, reorder, group) in &groups {
+            self.wtr.write_str("(&[")?;
+            for level in levels.iter() {
+                self.wtr.write_str(&format!("{:?}, ", level))?;
+            }
+            self.wtr.write_str("], &[")?;
+            for index in reorder.iter() {
+                self.wtr.write_str(&format!("{}, ", index))?;
+            }
+            self.wtr.write_str("], &[")?;
+            for test in group {
+                let mut src = "(&[".to_string();
+                for class in &test.classes {
+                    src.push_str(&format!("{:?}, ", class));
+                }
+                src.push_str("], &[");
+                for &dir in &test.paragraph_directions {
+                    src.push_str(&format!("{}, ", bidi_direction_code(dir)));
+                }
+                src.push_str("]), ");
+                self.wtr.write_str(&src)?;
+            }
+            self.wtr.write_str("]), ")?;
+        }
+        self.slice_end(groups.len())?;
+        self.len_const(&name, groups.len())?;
+        Ok(())
+    }
+
+    /// Write the tests from `BidiCharacterTest.txt`.
+    ///
+    /// Each test is a tuple of the input text, the paragraph direction (0 for
+    /// LTR, 1 for RTL and 2 for auto), the resolved paragraph level, the
+    /// resolved level of each codepoint (where `None` means "removed by rule
+    /// X9") and the reordering.
+    pub fn bidi_character_tests(
+        &mut self,
+        name: &str,
+        tests: &[BidiCharacterTest],
+    ) -> Result<()> {
+        self.ensure_not_c("bidi character tests")?;
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        self.slice_start(
+            &name,
+            "(\n  \
+             &'static str,\n  \
+             u8,\n  \
+             u8,\n  \
+             &'static [Option<u8>],\n  \
+             &'static [usize],\n\
+             )",
+        )?;
+        for test in tests {
+            self.wtr.write_str(&format!(
+                "({:?}, {}, {}, &[",
+                test.text,
+                bidi_direction_code(test.paragraph_direction),
+                test.paragraph_level,
+            ))?;
+            for level in &test.levels {
+                self.wtr.write_str(&format!("{:?}, ", level))?;
+            }
+            self.wtr.write_str("], &[")?;
+            for index in &test.reorder {
+                self.wtr.write_str(&format!("{}, ", index))?;
+            }
+            self.wtr.write_str("]), ")?;
+        }
+        self.slice_end(tests.len())?;
+        self.len_const(&name, tests.len())?;
+        Ok(())
+    }
+
+    /// Write the tests from `NormalizationTest.txt`.
+    ///
+    /// Each test is a tuple of its part, followed by the source string and
+    /// its NFC, NFD, NFKC and NFKD forms (i.e., columns `c1` to `c5`).
+    pub fn normalization_tests(
+        &mut self,
+        name: &str,
+        tests: &[NormalizationTest],
+    ) -> Result<()> {
+        self.ensure_not_c("normalization tests")?;
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        self.slice_start(
+            &name,
+            "(\n  \
+             u8,\n  \
+             &'static str,\n  \
+             &'static str,\n  \
+             &'static str,\n  \
+             &'static str,\n  \
+             &'static str,\n\
+             )",
+        )?;
+        for test in tests {
+            self.wtr.write_str(&format!(
+                "({}, {:?}, {:?}, {:?}, {:?}, {:?}), ",
+                test.part,
+                test.source,
+                test.nfc,
+                test.nfd,
+                test.nfkc,
+                test.nfkd,
+            ))?;
+        }
+        self.slice_end(tests.len())?;
+        self.len_const(&name, tests.len())?;
+        Ok(())
+    }
+
+    /// Write the collation elements of the DUCET, as read from `allkeys.txt`.
+    ///
+    /// Each collation element is packed into a `u32`: the primary weight in
+    /// the top 16 bits, followed by 9 bits of secondary weight, 6 bits of
+    /// tertiary weight and a bit that is set for variable elements. The
+    /// elements of every entry are written to a single `NAME_ELEMENTS`
+    /// table, and each entry refers to its elements by their start and
+    /// length there. Entries with the same elements share them.
+    ///
+    /// Entries for single codepoints are written to `NAME`, sorted by
+    /// codepoint, and contractions to `NAME_CONTRACTIONS`, sorted by their
+    /// codepoints. The ranges given by `@implicitweights` lines are written
+    /// to `NAME_IMPLICIT_WEIGHTS`, along with the base of their primary
+    /// weights.
+    ///
+    /// Binary tables don't support contractions.
+    pub fn collation(
+        &mut self,
+        name: &str,
+        implicit_weights: &[ImplicitWeights],
+        entries: &[AllKeysEntry],
+    ) -> Result<()> {
+        self.ensure_not_c("collation elements")?;
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit collation elements as an FST");
+        }
+        let mut elements: Vec<u32> = vec![];
+        let mut starts: BTreeMap<Vec<u32>, u32> = BTreeMap::new();
+        let mut singles: BTreeMap<u32, (u32, u32)> = BTreeMap::new();
+        let mut contractions: BTreeMap<Vec<u32>, (u32, u32)> = BTreeMap::new();
+        for entry in entries {
+            let packed = entry
+                .elements
+                .iter()
+                .map(pack_collation_element)
+                .collect::<Result<Vec<u32>>>()?;
+            let len = packed.len() as u32;
+            let start = match starts.get(&packed) {
+                Some(&start) => start,
+                None => {
+                    let start = elements.len() as u32;
+                    elements.extend(&packed);
+                    starts.insert(packed, start);
+                    start
+                }
+            };
+            let cps: Vec<u32> =
+                entry.codepoints.iter().map(|cp| cp.value()).collect();
+            let duplicate = if cps.len() == 1 {
+                singles.insert(cps[0], (start, len)).is_some()
+            } else {
+                contractions.insert(cps, (start, len)).is_some()
+            };
+            if duplicate {
+                let cps: Vec<String> =
+                    entry.codepoints.iter().map(|cp| cp.to_string()).collect();
+                return err!(
+                    "duplicate collation entry for {}",
+                    cps.join(" ")
+                );
+            }
+        }
+
+        self.header()?;
+        self.separator()?;
+        let name = rust_const_name(name);
+        let contractions_name = format!("{}_CONTRACTIONS", name);
+        let elements_name = format!("{}_ELEMENTS", name);
+        let implicit_name = format!("{}_IMPLICIT_WEIGHTS", name);
+        if self.opts.binary {
+            if !contractions.is_empty() {
+                return err!(
+                    "cannot emit collation contractions as a binary table \
+                     (try --no-contractions)"
+                );
+            }
+            let records: Vec<Vec<u32>> = singles
+                .iter()
+                .map(|(&cp, &(start, len))| vec![cp, start, len])
+                .collect();
+            self.binary_table(&name, "Expansion", &records)?;
+            self.separator()?;
+            let records: Vec<Vec<u32>> =
+                elements.iter().map(|&e| vec![e]).collect();
+            self.binary_table(&elements_name, "U32Le", &records)?;
+            if !implicit_weights.is_empty() {
+                self.separator()?;
+                let records: Vec<Vec<u32>> = implicit_weights
+                    .iter()
+                    .map(|iw| {
+                        let range = iw.codepoints;
+                        vec![
+                            range.start.value(),
+                            range.end.value(),
+                            iw.base.into(),
+                        ]
+                    })
+                    .collect();
+                self.binary_table(&implicit_name, "RangeValue", &records)?;
+            }
+            self.wtr.flush()?;
+            return Ok(());
+        }
+
+        self.slice_start(&name, "(u32, u32, u32)")?;
+        for (&cp, &(start, len)) in &singles {
+            self.wtr.write_str(&format!("({}, {}, {}), ", cp, start, len))?;
+        }
+        self.slice_end(singles.len())?;
+        self.len_const(&name, singles.len())?;
+        if !contractions.is_empty() {
+            self.separator()?;
+            self.slice_start(
+                &contractions_name,
+                "(&'static [u32], u32, u32)",
+            )?;
+            for (cps, &(start, len)) in &contractions {
+                let cps: Vec<String> =
+                    cps.iter().map(|cp| cp.to_string()).collect();
+                self.wtr.write_str(&format!(
+                    "(&[{}], {}, {}), ",
+                    cps.join(", "),
+                    start,
+                    len
+                ))?;
+            }
+            self.slice_end(contractions.len())?;
+            self.len_const(&contractions_name, contractions.len())?;
+        }
+        self.separator()?;
+        self.slice_start(&elements_name, "u32")?;
+        for &element in &elements {
+            self.wtr.write_str(&format!("0x{:08X}, ", element))?;
+        }
+        self.slice_end(elements.len())?;
+        self.len_const(&elements_name, elements.len())?;
+        if !implicit_weights.is_empty() {
+            self.separator()?;
+            self.slice_start(&implicit_name, "(u32, u32, u16)")?;
+            for iw in implicit_weights {
+                self.wtr.write_str(&format!(
+                    "({}, {}, 0x{:04X}), ",
+                    iw.codepoints.start.value(),
+                    iw.codepoints.end.value(),
+                    iw.base
+                ))?;
+            }
+            self.slice_end(implicit_weights.len())?;
+            self.len_const(&implicit_name, implicit_weights.len())?;
+        }
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write the constants used by the Hangul syllable composition and
+    /// decomposition algorithms (see Unicode 3.12).
+    ///
+    /// Each of `l`, `v` and `t` is a pair of the base codepoint and the
+    /// number of jamo of that class. Note that the base of `t` should be one
+    /// less than the first trailing consonant, and its count should include
+    /// the absence of a trailing consonant.
+    pub fn hangul_syllable_constants(
+        &mut self,
+        l: (u32, u32),
+        v: (u32, u32),
+        t: (u32, u32),
+    ) -> Result<()> {
+        self.ensure_not_c("hangul syllable constants")?;
+        self.header()?;
+        self.separator()?;
+
+        let n_count = v.1 * t.1;
+        writeln!(self.wtr, "pub const S_BASE: u32 = 0xAC00;")?;
+        writeln!(self.wtr, "pub const L_BASE: u32 = 0x{:04X};", l.0)?;
+        writeln!(self.wtr, "pub const V_BASE: u32 = 0x{:04X};", v.0)?;
+        writeln!(self.wtr, "pub const T_BASE: u32 = 0x{:04X};", t.0)?;
+        writeln!(self.wtr, "pub const L_COUNT: u32 = {};", l.1)?;
+        writeln!(self.wtr, "pub const V_COUNT: u32 = {};", v.1)?;
+        writeln!(self.wtr, "pub const T_COUNT: u32 = {};", t.1)?;
+        writeln!(self.wtr, "pub const N_COUNT: u32 = {};", n_count)?;
+        writeln!(self.wtr, "pub const S_COUNT: u32 = {};", l.1 * n_count)?;
+        Ok(())
+    }
+
+    /// Write a map that associates strings to codepoints.
+    pub fn string_to_codepoint(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<String, u32>,
+    ) -> Result<()> {
+        self.ensure_not_c("string to codepoint")?;
+        let map: BTreeMap<String, u32> = map
+            .iter()
+            .filter(|&(_, &cp)| !self.leaves_out(cp))
+            .map(|(k, &cp)| (k.clone(), cp))
+            .collect();
+        let folded = self.fold_keys(&map)?;
+        let map = folded.as_ref().unwrap_or(&map);
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        if self.opts.fst_dir.is_some() {
+            let mut builder = MapBuilder::memory();
+            for (k, &v) in map {
+                builder.insert(k.as_bytes(), v as u64)?;
+            }
+            let map = builder.into_map();
+            self.fst(&name, map.as_fst(), true)?;
+        } else {
+            let table: Vec<(&str, u32)> =
+                map.iter().map(|(k, &v)| (&**k, v)).collect();
+            self.string_to_codepoint_slice(&name, &table)?;
+            let ty = self.rust_codepoint_type();
+            self.case_fold_lookup(&name, map.keys(), ty)?;
+        }
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    fn string_to_codepoint_slice(
+        &mut self,
+        name: &str,
+        table: &[(&str, u32)],
+    ) -> Result<()> {
+        let ty = self.rust_codepoint_type();
+        self.slice_start(name, &format!("(&'static str, {})", ty))?;
+        let mut len = 0;
+        for &(ref s, cp) in table {
+            if let Some(cp) = self.rust_codepoint(cp) {
+                self.wtr.write_str(&format!("({:?}, {}), ", s, cp))?;
+                len += 1;
+            }
+        }
+        self.slice_end(len)?;
+        self.len_const(name, len)?;
+        Ok(())
+    }
+
+    /// Write a map that associates strings with sequences of codepoints.
+    ///
+    /// When `emit_flat_table` is true, each sequence is written as a fixed
+    /// size array padded by a sentinel value, just like
+    /// `codepoint_to_codepoints`.
+    ///
+    /// This does not support the FST format.
+    pub fn string_to_codepoints(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<String, Vec<u32>>,
+        emit_flat_table: bool,
+    ) -> Result<()> {
+        self.ensure_not_c("string to codepoints")?;
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit string->codepoints map as an FST");
+        }
+        let map: BTreeMap<String, Vec<u32>> = map
+            .iter()
+            .filter(|&(_, cps)| !cps.iter().any(|&cp| self.leaves_out(cp)))
+            .map(|(k, cps)| (k.clone(), cps.clone()))
+            .collect();
+        let folded = self.fold_keys(&map)?;
+        let map = folded.as_ref().unwrap_or(&map);
+        let ty = self.rust_codepoint_type();
+        let flat_padding = self.flat_table_pad();
+        let flat_width = map.values().map(|vs| vs.len()).max().unwrap_or(0);
+        let flat_width = std::cmp::max(3, flat_width);
+        if emit_flat_table && self.opts.flat_table_pad.is_some() {
+            if self.rust_codepoint(flat_padding).is_none() {
+                return err!(
+                    "flat-table padding {:X} is not a valid {}",
+                    flat_padding,
+                    ty,
+                );
+            }
+            if let Some((k, _)) =
+                map.iter().find(|(_, vs)| vs.contains(&flat_padding))
+            {
+                return err!(
+                    "flat-table padding {:X} cannot be used since it is \
+                     present in the value array for {:?}",
+                    flat_padding,
+                    k,
+                );
+            }
+        }
+        if self.opts.self_check {
+            for &v in map.values().flatten() {
+                self.check_codepoint(v)?;
+            }
+        }
+
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        let value_ty = if emit_flat_table {
+            format!("[{}; {}]", ty, flat_width)
+        } else {
+            format!("&'static [{}]", ty)
+        };
+        self.slice_start(&name, &format!("(&'static str, {})", value_ty))?;
+        let mut len = 0;
+        'LOOP: for (k, vs) in map {
+            let (padded_vs, slice_prefix) = if emit_flat_table {
+                let padded = vs
+                    .iter()
+                    .copied()
+                    .chain(std::iter::repeat(flat_padding))
+                    .take(flat_width)
+                    .collect::<Vec<_>>();
+                (padded, "")
+            } else {
+                (vs.clone(), "&")
+            };
+            let mut vstrs = vec![];
+            for v in padded_vs {
+                match self.rust_codepoint(v) {
+                    None => continue 'LOOP,
+                    Some(v) => vstrs.push(v),
+                }
+            }
+            // Each entry is written at once, so that it's never split
+            // across lines.
+            let values = if vstrs.len() == 1 {
+                vstrs[0].clone()
+            } else {
+                vstrs.iter().map(|v| format!("{}, ", v)).collect()
+            };
+            self.wtr.write_str(&format!(
+                "({:?}, {}[{}]), ",
+                k, slice_prefix, values
+            ))?;
+            len += 1;
+        }
+        self.slice_end(len)?;
+        self.len_const(&name, len)?;
+        self.case_fold_lookup(&name, map.keys(), &value_ty)?;
+        if emit_flat_table && self.opts.flat_table_trim {
+            self.flat_table_trim_fn(&name, flat_padding, flat_width)?;
+        }
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a map that associates strings to `u64` values as an FST, where
+    /// the entries are given in sorted order.
+    ///
+    /// Unlike `string_to_u64`, neither the entries nor the FST are ever held
+    /// in memory. Instead, the FST is written to its file as the entries are
+    /// consumed. This is only supported when writing FSTs, and can't be
+    /// combined with hashed file names, sharding or case folded keys since
+    /// they all need to see the entire FST first.
+    pub fn string_to_u64_sorted<I>(
+        &mut self,
+        name: &str,
+        entries: I,
+    ) -> Result<()>
+    where
+        I: IntoIterator<Item = Result<(String, u64)>>,
+    {
+        self.ensure_not_c("sorted string to u64")?;
+        let fst_dir = match self.opts.fst_dir {
+            None => {
+                return err!("sorted entries can only be written as an FST")
+            }
+            Some(ref fst_dir) => fst_dir.clone(),
+        };
+        if self.opts.fst_hash_names
+            || self.opts.fst_max_file_size.is_some()
+            || self.opts.case_fold_keys.is_some()
+        {
+            return err!(
+                "cannot hash the file name of, shard or case fold the keys \
+                 of an FST that is built from sorted entries"
+            );
+        }
+        self.header()?;
+        self.separator()?;
+
+        let const_name = rust_const_name(name);
+        let fst_file_name = format!("{}.fst", rust_module_name(&const_name));
+        let fst_file_path = fst_dir.join(&fst_file_name);
+        if !self.out_files.insert(fst_file_name.clone()) {
+            return err!(
+                "more than one table would be written to {}",
+                fst_file_path.display(),
+            );
+        }
+        // The FST is written straight to the staging directory as the entries
+        // are consumed, and is only moved into place by `finish`.
+        let tmp_path = self.stage_path(&fst_file_path)?;
+        let wtr = io::BufWriter::new(fs::File::create(&tmp_path)?);
+        let mut builder = MapBuilder::new(wtr)?;
+        let mut len = 0;
+        for entry in entries {
+            let (k, v) = entry?;
+            builder.insert(k, v)?;
+            len += 1;
+        }
+        builder.finish()?;
+        self.ensure_nonempty(&const_name, len)?;
+        self.fst_static(&const_name, "Map", &fst_file_name)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a map that associates strings to `u64` values.
+    pub fn string_to_u64(
+        &mut self,
+        name: &str,
+        map: &BTreeMap<String, u64>,
+    ) -> Result<()> {
+        self.ensure_not_c("string to u64")?;
+        let folded = self.fold_keys(map)?;
+        let map = folded.as_ref().unwrap_or(map);
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        if self.opts.fst_dir.is_some() {
+            let mut builder = MapBuilder::memory();
+            for (k, &v) in map {
+                builder.insert(k.as_bytes(), v)?;
+            }
+            let map = builder.into_map();
+            self.fst(&name, map.as_fst(), true)?;
+        } else {
+            let table: Vec<(&str, u64)> =
+                map.iter().map(|(k, &v)| (&**k, v)).collect();
+            self.string_to_u64_slice(&name, &table)?;
+            self.case_fold_lookup(&name, map.keys(), "u64")?;
+        }
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write the tags that may be set in the high bits of the values of a
+    /// map written by `string_to_u64`, where each value is a codepoint in
+    /// its low 32 bits along with exactly one tag.
+    ///
+    /// Each tag is given as its name, a description and its bit. A constant
+    /// is written for each tag, along with a `NAME_TAG_MASK` constant and a
+    /// `name_decode` function that splits a value into its tag and codepoint.
+    pub fn codepoint_tags(
+        &mut self,
+        name: &str,
+        tags: &[(&str, &str, u64)],
+    ) -> Result<()> {
+        self.ensure_not_c("codepoint tags")?;
+        for &(tag, _, bit) in tags {
+            if bit.count_ones() != 1 || bit <= u64::from(u32::MAX) {
+                return err!("tag {} must be a single bit above 32 bits", tag);
+            }
+        }
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        writeln!(
+            self.wtr,
+            "// Each value in {} is a codepoint in its low 32 bits, with \
+             exactly one",
+            name
+        )?;
+        writeln!(self.wtr, "// of the following tags set in its high bits.")?;
+        for &(tag, description, bit) in tags {
+            writeln!(self.wtr, "/// {}", description)?;
+            writeln!(
+                self.wtr,
+                "pub const {}_TAG_{}: u64 = 1 << {};",
+                name,
+                rust_const_name(tag),
+                bit.trailing_zeros()
+            )?;
+        }
+        writeln!(
+            self.wtr,
+            "/// The high bits of a value, which hold its tag."
+        )?;
+        writeln!(
+            self.wtr,
+            "pub const {}_TAG_MASK: u64 = !0xFFFF_FFFF;",
+            name
+        )?;
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "/// Split a value in {} into its tag and codepoint.",
+            name
+        )?;
+        writeln!(
+            self.wtr,
+            "pub fn {}_decode(value: u64) -> (u64, u32) {{",
+            rust_fn_name(&name)
+        )?;
+        writeln!(self.wtr, "    (value & {}_TAG_MASK, value as u32)", name)?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    /// Write a constant for each bit of the values of a map written by
+    /// `ranges_to_unsigned_integer`, where each value is a bitmask.
+    ///
+    /// Each bit is given as its name and a description, where the bit of
+    /// the flag at index `i` is `1 << i`. The constants have the same type
+    /// as the values of the map.
+    pub fn bit_flags(
+        &mut self,
+        name: &str,
+        flags: &[(&str, &str)],
+    ) -> Result<()> {
+        self.ensure_not_c("bit flags")?;
+        if flags.is_empty() || flags.len() > 32 {
+            return err!("cannot write {} bit flags", flags.len());
+        }
+        self.header()?;
+        self.separator()?;
+
+        let name = rust_const_name(name);
+        let ty = if self.opts.fst_dir.is_some() {
+            "u64"
+        } else if self.opts.binary {
+            "u32"
+        } else {
+            smallest_unsigned_type(1 << (flags.len() - 1))
+        };
+        writeln!(
+            self.wtr,
+            "// Each value in {} is a bitmask of the following flags.",
+            name
+        )?;
+        for (i, &(flag, description)) in flags.iter().enumerate() {
+            writeln!(self.wtr, "/// {}", description)?;
+            writeln!(
+                self.wtr,
+                "pub const {}_{}: {} = 1 << {};",
+                name,
+                rust_const_name(flag),
+                ty,
+                i
+            )?;
+        }
+        self.wtr.flush()?;
+        Ok(())
+    }
+
+    fn string_to_u64_slice(
+        &mut self,
+        name: &str,
+        table: &[(&str, u64)],
+    ) -> Result<()> {
+        self.slice_start(name, "(&'static str, u64)")?;
+        for &(ref s, n) in table {
+            self.wtr.write_str(&format!("({:?}, {}), ", s, n))?;
+        }
+        self.slice_end(table.len())?;
+        self.len_const(name, table.len())?;
+        Ok(())
+    }
+
+    /// Return the given map with its keys case folded according to
+    /// `case_fold_keys`, or `None` if keys aren't case folded.
+    ///
+    /// It is an error for keys with different values to fold to the same
+    /// key.
+    fn fold_keys<V: Clone + PartialEq>(
+        &self,
+        map: &BTreeMap<String, V>,
+    ) -> Result<Option<BTreeMap<String, V>>> {
+        if self.opts.case_fold_keys.is_none() {
+            return Ok(None);
+        }
+        if self.opts.fst_dir.is_some() {
+            return err!("cannot emit case folded keys as an FST");
+        }
+        let mut folded: BTreeMap<String, (&str, V)> = BTreeMap::new();
+        for (k, v) in map {
+            let key = self.fold_str(k);
+            if let Some((other, ref ov)) = folded.get(&key) {
+                if ov != v {
+                    return err!(
+                        "keys {:?} and {:?} have different values, but \
+                         both case fold to {:?}",
+                        other,
+                        k,
+                        key
+                    );
+                }
+                continue;
+            }
+            folded.insert(key, (k, v.clone()));
+        }
+        Ok(Some(folded.into_iter().map(|(k, (_, v))| (k, v)).collect()))
+    }
+
+    fn fold_str(&self, s: &str) -> String {
+        match self.opts.case_fold_keys {
+            None => s.to_string(),
+            Some(CaseFold::Ascii) => s.to_ascii_lowercase(),
+            Some(CaseFold::Simple(ref map)) => s
+                .chars()
+                .map(|c| {
+                    map.get(&(c as u32))
+                        .and_then(|&cp| char::from_u32(cp))
+                        .unwrap_or(c)
+                })
+                .collect(),
+        }
+    }
+
+    /// Emit a function that looks up a key in the string table with the
+    /// given name case insensitively, if `case_fold_keys` is enabled.
+    ///
+    /// The keys given are the folded keys of the table, and `ty` is the type
+    /// of its values.
+    fn case_fold_lookup<'a, I: IntoIterator<Item = &'a String>>(
+        &mut self,
+        name: &str,
+        keys: I,
+        ty: &str,
+    ) -> Result<()> {
+        if self.opts.case_fold_keys.is_none() {
+            return Ok(());
+        }
+        let fold = self.case_fold_fn(name, keys)?;
+        self.wtr.indent_width(4)?;
+        writeln!(
+            self.wtr,
+            "pub fn {}(query: &str) -> Option<{}> {{",
+            rust_fn_name(name),
+            ty
+        )?;
+        writeln!(self.wtr, "    let cmp = |key: &str| {{")?;
+        writeln!(
+            self.wtr,
+            "        key.chars().cmp(query.chars().map({}))",
+            fold
+        )?;
+        writeln!(self.wtr, "    }};")?;
+        writeln!(
+            self.wtr,
+            "    let i = {}.binary_search_by(|&(k, _)| cmp(k)).ok()?;",
+            name
+        )?;
+        writeln!(self.wtr, "    Some({}[i].1)", name)?;
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        Ok(())
+    }
+
+    /// Emit the function that case folds queries for the string table with
+    /// the given name, whose folded keys are given, and return its name.
+    ///
+    /// For simple case folding, this also emits a `NAME_CASE_FOLD` table. It
+    /// only contains mappings to characters that occur in a key, since
+    /// folding any other character can't produce a match.
+    fn case_fold_fn<'a, I: IntoIterator<Item = &'a String>>(
+        &mut self,
+        name: &str,
+        keys: I,
+    ) -> Result<String> {
+        let fold_name = format!("{}_CASE_FOLD", name);
+        let fold_fn = format!("{}_fold", rust_fn_name(name));
+        let body: &[String] = match self.opts.case_fold_keys {
+            None => unreachable!(),
+            Some(CaseFold::Ascii) => &["c.to_ascii_lowercase()".to_string()],
+            Some(CaseFold::Simple(ref map)) => {
+                let used: BTreeSet<u32> = keys
+                    .into_iter()
+                    .flat_map(|k| k.chars().map(|c| c as u32))
+                    .collect();
+                let pairs: Vec<(char, char)> = map
+                    .iter()
+                    .filter(|&(_, to)| used.contains(to))
+                    .filter_map(|(&from, &to)| {
+                        Some((char::from_u32(from)?, char::from_u32(to)?))
+                    })
+                    .collect();
+                self.separator()?;
+                writeln!(
+                    self.wtr,
+                    "pub const {}: &'static [(char, char)] = &[",
+                    fold_name
+                )?;
+                for (from, to) in pairs {
+                    self.wtr
+                        .write_str(&format!("({:?}, {:?}), ", from, to))?;
+                }
+                writeln!(self.wtr, "];")?;
+                &[
+                    format!(
+                        "match {}.binary_search_by_key(&c, |&(k, _)| k) {{",
+                        fold_name
+                    ),
+                    format!("    Ok(i) => {}[i].1,", fold_name),
+                    "    Err(_) => c,".to_string(),
+                    "}".to_string(),
+                ]
+            }
+        };
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        writeln!(self.wtr, "fn {}(c: char) -> char {{", fold_fn)?;
+        for line in body {
+            writeln!(self.wtr, "    {}", line)?;
+        }
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        self.separator()?;
+        Ok(fold_fn)
+    }
+
+    fn fst<D: AsRef<[u8]>>(
+        &mut self,
+        const_name: &str,
+        fst: &Fst<D>,
+        map: bool,
+    ) -> Result<()> {
+        self.ensure_nonempty(const_name, fst.len())?;
+        self.log_table(const_name, fst.len());
+        let bytes = fst.to_vec();
+        let ty = if map { "Map" } else { "Set" };
+        match self.opts.fst_max_file_size {
+            Some(max) if bytes.len() > max => {
+                return self.fst_shards(const_name, fst, ty, max);
+            }
+            _ => {}
+        }
+        let fst_file_name = self.fst_file(const_name, None, &bytes)?;
+        self.fst_static(const_name, ty, &fst_file_name)
+    }
+
+    /// Write a lazy static named `const_name` that loads the FST in the
+    /// given file. `ty` is either `Map` or `Set`.
+    fn fst_static(
+        &mut self,
+        const_name: &str,
+        ty: &str,
+        fst_file_name: &str,
+    ) -> Result<()> {
+        self.fst_loader(
+            const_name,
+            &format!("::fst::{}<&'static [u8]>", ty),
+            &[
+                format!("::fst::{}::from(::fst::raw::Fst::new(", ty),
+                format!(
+                    "  &include_bytes!({:?})[..]).unwrap())",
+                    fst_file_name
+                ),
+            ],
+        )
+    }
+
+    /// Write the loader of a value of type `ty` named `const_name`, which
+    /// evaluates the given lines of code the first time it is used (or every
+    /// time it is used with `FstLoader::Fn`).
+    fn fst_loader(
+        &mut self,
+        const_name: &str,
+        ty: &str,
+        init: &[String],
+    ) -> Result<()> {
+        let fn_name = rust_fn_name(const_name);
+        match self.opts.fst_loader {
+            FstLoader::OnceCell => {
+                writeln!(
+                    self.wtr,
+                    "pub static {}: ::once_cell::sync::Lazy<{}> =",
+                    const_name, ty
+                )?;
+                writeln!(self.wtr, "  ::once_cell::sync::Lazy::new(|| {{")?;
+                for line in init {
+                    writeln!(self.wtr, "    {}", line)?;
+                }
+                writeln!(self.wtr, "  }});")?;
+            }
+            FstLoader::LazyStatic => {
+                writeln!(self.wtr, "::lazy_static::lazy_static! {{")?;
+                writeln!(
+                    self.wtr,
+                    "  pub static ref {}: {} = {{",
+                    const_name, ty
+                )?;
+                for line in init {
+                    writeln!(self.wtr, "    {}", line)?;
+                }
+                writeln!(self.wtr, "  }};")?;
+                writeln!(self.wtr, "}}")?;
+            }
+            FstLoader::OnceLock => {
+                writeln!(
+                    self.wtr,
+                    "pub fn {}() -> &'static {} {{",
+                    fn_name, ty
+                )?;
+                writeln!(
+                    self.wtr,
+                    "  static {}: ::std::sync::OnceLock<{}> =",
+                    const_name, ty
+                )?;
+                writeln!(self.wtr, "    ::std::sync::OnceLock::new();")?;
+                writeln!(self.wtr, "  {}.get_or_init(|| {{", const_name)?;
+                for line in init {
+                    writeln!(self.wtr, "    {}", line)?;
+                }
+                writeln!(self.wtr, "  }})")?;
+                writeln!(self.wtr, "}}")?;
+            }
+            FstLoader::Fn => {
+                writeln!(self.wtr, "pub fn {}() -> {} {{", fn_name, ty)?;
+                for line in init {
+                    writeln!(self.wtr, "  {}", line)?;
+                }
+                writeln!(self.wtr, "}}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the given FST as several shards, each in a file of at most
+    /// `max` bytes, along with a function that finds the shard for a key.
+    ///
+    /// Each shard holds a contiguous range of keys, found by repeatedly
+    /// halving any range that is too big.
+    fn fst_shards<D: AsRef<[u8]>>(
+        &mut self,
+        const_name: &str,
+        fst: &Fst<D>,
+        ty: &str,
+        max: usize,
+    ) -> Result<()> {
+        use fst::Streamer;
+
+        let mut entries = vec![];
+        let mut stream = fst.stream();
+        while let Some((key, out)) = stream.next() {
+            entries.push((key.to_vec(), out.value()));
+        }
+        let build = |entries: &[(Vec<u8>, u64)]| -> Result<Vec<u8>> {
+            let mut builder = fst::raw::Builder::memory();
+            for (key, value) in entries {
+                builder.insert(key, *value)?;
+            }
+            Ok(builder.into_inner()?)
+        };
+        // Bisect ranges of keys until each one fits...
+        let mut ranges = vec![];
+        let mut todo = vec![(0, entries.len())];
+        while let Some((start, end)) = todo.pop() {
+            if build(&entries[start..end])?.len() <= max {
+                ranges.push((start, end));
+            } else if end - start == 1 {
+                return err!(
+                    "cannot split {} into FSTs of at most {} bytes",
+                    const_name,
+                    max
+                );
+            } else {
+                let mid = start + (end - start) / 2;
+                todo.push((mid, end));
+                todo.push((start, mid));
+            }
+        }
+        // ... and then merge neighboring ranges that still fit together.
+        let mut shards: Vec<(usize, usize, Vec<u8>)> = vec![];
+        for (start, end) in ranges {
+            let bytes = build(&entries[start..end])?;
+            if let Some(last) = shards.last_mut() {
+                // Merging rarely helps unless both would fit on their own.
+                if last.2.len() + bytes.len() <= max {
+                    let merged = build(&entries[last.0..end])?;
+                    if merged.len() <= max {
+                        *last = (last.0, end, merged);
+                        continue;
+                    }
+                }
+            }
+            shards.push((start, end, bytes));
+        }
+
+        let mut file_names = vec![];
+        for (i, (_, _, bytes)) in shards.iter().enumerate() {
+            file_names.push(self.fst_file(const_name, Some(i), bytes)?);
+        }
+        self.sharded.insert(const_name.to_string());
+
+        let mut init = vec!["[".to_string()];
+        for file_name in &file_names {
+            init.push(format!(
+                "  ::fst::{}::from(::fst::raw::Fst::new(\
+                 &include_bytes!({:?})[..]).unwrap()),",
+                ty, file_name
+            ));
+        }
+        init.push("]".to_string());
+        self.fst_loader(
+            const_name,
+            &format!("[::fst::{}<&'static [u8]>; {}]", ty, shards.len()),
+            &init,
+        )?;
+
+        self.separator()?;
+        writeln!(
+            self.wtr,
+            "pub const {}_SHARD_STARTS: &'static [&'static [u8]] = &[",
+            const_name
+        )?;
+        for &(start, _, _) in &shards {
+            let key = rust_byte_string(&entries[start].0);
+            self.wtr.write_str(&format!("{}, ", key))?;
+        }
+        writeln!(self.wtr, "];")?;
+
+        self.separator()?;
+        self.wtr.indent_width(4)?;
+        let (reference, shards) = match self.opts.fst_loader {
+            FstLoader::OnceCell | FstLoader::LazyStatic => {
+                ("&'static ", format!("&{}", const_name))
+            }
+            FstLoader::OnceLock => {
+                ("&'static ", format!("&{}()", rust_fn_name(const_name)))
+            }
+            FstLoader::Fn => ("", format!("{}()", rust_fn_name(const_name))),
+        };
+        writeln!(
+            self.wtr,
+            "pub fn {}_shard(key: &[u8]) -> {}::fst::{}<&'static [u8]> {{",
+            rust_fn_name(const_name),
+            reference,
+            ty
+        )?;
+        writeln!(
+            self.wtr,
+            "    let i = {}_SHARD_STARTS.partition_point(|&start| start <= key);",
+            const_name
+        )?;
+        if self.opts.fst_loader == FstLoader::Fn {
+            writeln!(
+                self.wtr,
+                "    {}.into_iter().nth(i.saturating_sub(1)).unwrap()",
+                shards
+            )?;
+        } else {
+            writeln!(self.wtr, "    {}[i.saturating_sub(1)]", shards)?;
+        }
+        writeln!(self.wtr, "}}")?;
+        self.wtr.indent_width(2)?;
+        Ok(())
+    }
+
+    /// Write the bytes of an FST to its file in the FST directory, and
+    /// return the file's name. `shard` is the index of the shard the bytes
+    /// belong to, if the FST is sharded.
+    /// Write the given records to a binary file named after the table, as
+    /// consecutive little endian `u32`s, and emit the Rust code that views
+    /// them as a slice of `record` (see `binary_prelude`).
+    fn binary_table(
+        &mut self,
+        const_name: &str,
+        record: &str,
+        records: &[Vec<u32>],
+    ) -> Result<()> {
+        self.ensure_nonempty(const_name, records.len())?;
+        let out_dir = match self.opts.out_dir {
+            None => return err!("binary tables require an output directory"),
+            Some(ref out_dir) => out_dir.clone(),
+        };
+        self.binary_prelude()?;
+
+        let file_name = format!("{}.bin", rust_module_name(const_name));
+        if !self.out_files.insert(file_name.clone()) {
+            return err!(
+                "more than one table would be written to {}",
+                out_dir.join(&file_name).display(),
+            );
+        }
+        let mut bytes = vec![];
+        for value in records.iter().flatten() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        self.stage(&out_dir.join(&file_name), &bytes)?;
+
+        writeln!(
+            self.wtr,
+            "pub const {}_BYTES: &'static [u8] = include_bytes!({:?});",
+            const_name, file_name
+        )?;
+        self.len_const(const_name, records.len())?;
+        writeln!(
+            self.wtr,
+            "pub fn {}() -> &'static [{}] {{",
+            rust_fn_name(const_name),
+            record
+        )?;
+        writeln!(self.wtr, "  binary_view({}_BYTES)", const_name)?;
+        writeln!(self.wtr, "}}")?;
+        Ok(())
+    }
+
+    /// Write the record types of binary tables, along with the function
+    /// that views a table's bytes as a slice of records. This is only done
+    /// once per file.
+    fn binary_prelude(&mut self) -> Result<()> {
+        if self.wrote_binary_prelude {
+            return Ok(());
+        }
+        self.wrote_binary_prelude = true;
+        write!(self.wtr, "{}", BINARY_PRELUDE)?;
+        self.separator()?;
+        Ok(())
+    }
+
+    fn fst_file(
+        &mut self,
+        const_name: &str,
+        shard: Option<usize>,
+        bytes: &[u8],
+    ) -> Result<String> {
+        let fst_dir = self.opts.fst_dir.as_ref().unwrap();
+        let mut stem = rust_module_name(const_name);
+        if let Some(shard) = shard {
+            stem = format!("{}-{}", stem, shard);
+        }
+        let fst_file_name = if self.opts.fst_hash_names {
+            format!("{}-{:016x}.fst", stem, fnv1a64(bytes))
+        } else {
+            format!("{}.fst", stem)
+        };
+        if !self.out_files.insert(fst_file_name.clone()) {
+            return err!(
+                "more than one table would be written to {}, which would \
+                 silently overwrite an earlier table (try --fst-hash-names)",
+                fst_dir.join(&fst_file_name).display(),
+            );
+        }
+        let fst_file_path = fst_dir.join(&fst_file_name);
+        self.stage(&fst_file_path, bytes)?;
+        if self.opts.verify_fst {
+            self.written_fsts.push((fst_file_path, bytes.to_vec()));
+        }
+        Ok(fst_file_name)
+    }
+
+    /// Start writing a slice table with the given constant name, whose
+    /// entries have type `ty`. Entries are written with `wtr.write_str` and
+    /// the table must be completed with `slice_end`.
+    ///
+    /// When tables are split into data files, the entries are buffered
+    /// until `slice_end` instead.
+    fn slice_start(&mut self, const_name: &str, ty: &str) -> Result<()> {
+        if self.opts.wasm {
+            self.wasm_slice = Some((const_name.to_string(), entry_size(ty)));
+        }
+        if self.opts.split_min_len.is_none() {
+            writeln!(
+                self.wtr,
+                "pub const {}: &'static [{}] = &[",
+                const_name, ty
+            )?;
+            return Ok(());
+        }
+        self.wtr.flush()?;
+        let buf = SharedBuf::default();
+        let wtr = std::mem::replace(&mut self.wtr.wtr, Box::new(buf.clone()));
+        self.pending_slice = Some(PendingSlice {
+            name: const_name.to_string(),
+            ty: ty.to_string(),
+            wtr,
+            buf,
+        });
+        Ok(())
+    }
+
+    /// Finish writing the slice table started by `slice_start`, which has
+    /// `len` entries.
+    fn slice_end(&mut self, len: usize) -> Result<()> {
+        if let Some((name, size)) = self.wasm_slice.take() {
+            self.data_sizes.push((name, size.map(|size| size * len)));
+        }
+        let pending = match self.pending_slice.take() {
+            None => {
+                writeln!(self.wtr, "];")?;
+                return Ok(());
+            }
+            Some(pending) => pending,
+        };
+        self.wtr.flush()?;
+        self.wtr.wtr = pending.wtr;
+        let entries = pending.buf.0.take();
+        if len < self.opts.split_min_len.unwrap_or(0) {
+            writeln!(
+                self.wtr,
+                "pub const {}: &'static [{}] = &[",
+                pending.name, pending.ty
+            )?;
+            // The entries have already been through the line writer, so
+            // bypass it to avoid indenting them twice.
+            self.wtr.wtr.write_all(&entries)?;
+            writeln!(self.wtr, "];")?;
+            return Ok(());
+        }
+
+        let out_dir = match self.opts.out_dir {
+            None => {
+                return err!(
+                    "cannot split {} into a data file without an output \
+                     directory",
+                    pending.name
+                )
+            }
+            Some(ref out_dir) => out_dir,
+        };
+        let file_name = format!("{}.data.rs", rust_module_name(&pending.name));
+        if !self.out_files.insert(file_name.clone()) {
+            return err!(
+                "more than one table would be written to {}",
+                out_dir.join(&file_name).display(),
+            );
+        }
+        let mut contents = format!(
+            "// DO NOT EDIT THIS FILE. IT WAS AUTOMATICALLY GENERATED BY \
+             ucd-generate.\n// It is included by {}.rs.\n\n[\n",
+            rust_module_name(&self.opts.name),
+        )
+        .into_bytes();
+        contents.extend_from_slice(&entries);
+        contents.extend_from_slice(b"]\n");
+        let path = out_dir.join(&file_name);
+        self.stage(&path, &contents)?;
+        writeln!(
+            self.wtr,
+            "pub const {}: &'static [{}] =\n  &include!({:?});",
+            pending.name, pending.ty, file_name
+        )?;
+        Ok(())
+    }
+
+    /// Emit a `NAME_LEN` constant for the slice table with the given constant
+    /// name, if enabled, and remember it for the `TABLE_LENS` manifest.
+    fn len_const(&mut self, const_name: &str, len: usize) -> Result<()> {
+        self.ensure_nonempty(const_name, len)?;
+        self.log_table(const_name, len);
+        if !self.opts.emit_len_consts {
+            return Ok(());
+        }
+        if self.opts.c_output {
+            writeln!(self.wtr, "#define {}_LEN {}", const_name, len)?;
+            return Ok(());
+        }
+        writeln!(self.wtr, "pub const {}_LEN: usize = {};", const_name, len)?;
+        self.table_lens.push((const_name.to_string(), len));
+        Ok(())
+    }
+
+    /// Return an error if a maximum value length is set and any of the
+    /// strings in the given map exceeds it.
+    fn ensure_max_value_len(&self, map: &BTreeMap<u32, String>) -> Result<()> {
+        let max = match self.opts.max_value_len {
+            None => return Ok(()),
+            Some(max) => max,
+        };
+        for (&cp, v) in map {
+            if v.len() > max {
+                return err!(
+                    "value {:?} of U+{:04X} is {} bytes long, which \
+                     exceeds the maximum of {} bytes",
+                    v,
+                    cp,
+                    v.len(),
+                    max,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Report that the table with the given name was written, along with
+    /// how long it took since the previous one, when --verbose is given.
+    fn log_table(&mut self, const_name: &str, len: usize) {
+        log!(
+            "emitted {} ({} entries) in {:.2?}",
+            const_name,
+            len,
+            self.last_table.elapsed()
+        );
+        self.last_table = Instant::now();
+    }
+
+    /// Return an error if `assert_nonempty` is enabled and the table with
+    /// the given name has no entries.
+    fn ensure_nonempty(&self, name: &str, len: usize) -> Result<()> {
+        if self.opts.assert_nonempty && len == 0 {
+            return err!("table {} is empty (--assert-nonempty)", name);
+        }
+        Ok(())
+    }
+
+    fn write_slice_u8(&mut self, xs: &[u8]) -> Result<()> {
+        for &x in xs {
+            self.wtr.write_str(&format!("{}, ", x))?;
+        }
+        Ok(())
+    }
+
+    fn write_slice_u64(&mut self, xs: &[u64]) -> Result<()> {
+        for &x in xs {
+            if x == 0 {
+                self.wtr.write_str("0, ")?;
+            } else {
+                self.wtr.write_str(&format!("0x{:X}, ", x))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn header(&mut self) -> Result<()> {
+        if self.wrote_header {
+            return Ok(());
+        }
+        writeln!(
+            self.wtr,
+            "// DO NOT EDIT THIS FILE. \
+             IT WAS AUTOMATICALLY GENERATED BY:"
+        )?;
+        writeln!(self.wtr, "//")?;
+        writeln!(self.wtr, "//   {}", command_line()?)?;
+        writeln!(self.wtr, "//")?;
+        if let Some(ref config) = self.opts.config {
+            writeln!(self.wtr, "{}", config.to_comment())?;
+            writeln!(self.wtr, "//")?;
+        }
+        if let Some((major, minor, patch)) = self.opts.ucd_version {
+            writeln!(
+                self.wtr,
+                "// Unicode version: {}.{}.{}.",
+                major, minor, patch
+            )?;
+            writeln!(self.wtr, "//")?;
+        }
+        if let Some(ref max_age) = self.opts.max_age {
+            writeln!(
+                self.wtr,
+                "// Codepoints introduced after Unicode {}.{} are left out.",
+                max_age.version.0, max_age.version.1
+            )?;
+            writeln!(self.wtr, "//")?;
+        }
+        if let Some(ref only) = self.opts.only {
+            writeln!(
+                self.wtr,
+                "// Only codepoints with {} are included.",
+                only.description
+            )?;
+            writeln!(self.wtr, "//")?;
+        }
+        match self.opts.sample {
+            None => {}
+            Some(Sample::Ranges(count)) => {
+                writeln!(
+                    self.wtr,
+                    "// This is a sample: only the first {} ranges of each \
+                     table are included.",
+                    count
+                )?;
+                writeln!(self.wtr, "//")?;
+            }
+            Some(Sample::Window(start, end)) => {
+                writeln!(
+                    self.wtr,
+                    "// This is a sample: only codepoints in \
+                     U+{:04X}..U+{:04X} are included.",
+                    start, end
+                )?;
+                writeln!(self.wtr, "//")?;
+            }
+        }
+        writeln!(
+            self.wtr,
+            "// ucd-generate {} is available on crates.io.",
+            env!("CARGO_PKG_VERSION")
+        )?;
+        self.wrote_header = true;
+        if self.opts.c_output {
+            self.c_prelude()?;
+        }
+        Ok(())
+    }
+
+    fn separator(&mut self) -> Result<()> {
+        write!(self.wtr, "\n")?;
+        Ok(())
+    }
+
+    /// Return valid Rust source code that represents the given codepoint.
+    ///
+    /// The source code returned is either a u32 literal or a char literal,
+    /// depending on the configuration. If the configuration demands a char
+    /// literal and the given codepoint is a surrogate, then return None.
+    fn rust_codepoint(&self, cp: u32) -> Option<String> {
+        if self.opts.char_literals {
+            char::from_u32(cp).map(|c| format!("{:?}", c))
+        } else if cp == !0 {
+            // Used to represent missing entries in some cases (specifically
+            // --flat-table), and writing it as `!0` makes the whole table much
+            // easier to read while maintaining identical semantics, even if
+            // `--flat-table` is not in use.
+            Some("!0".to_string())
+        } else {
+            Some(cp.to_string())
+        }
+    }
+
+    /// Return valid Rust source code indicating the type of the codepoint
+    /// that we emit based on this writer's configuration.
+    fn rust_codepoint_type(&self) -> &'static str {
+        if self.opts.char_literals {
+            "char"
+        } else {
+            "u32"
+        }
+    }
+}
+
+/// A writer whose contents can be inspected after the `Writer` that owns it
+/// is done with it.
+#[derive(Clone, Debug, Default)]
+struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+
+impl io::Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Write the given contents to the given file path, unless the file already
+/// has exactly those contents and `force` is false.
+///
+/// This avoids churning the modification times of generated files (and thus
+/// rebuilding everything that depends on them) when regenerating them yields
+/// identical output. This returns true if and only if the file was written.
+/// Read an enum manifest, which has one line for each variant of each enum
+/// containing the enum's name and the variant's name, separated by a space.
+/// A missing manifest is treated as an empty one.
+/// Read the string pool declared by an existing `strings.rs`, or return an
+/// empty pool if it doesn't exist.
+fn read_string_pool(path: &Path) -> Result<String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+            return Ok(String::new())
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let mut pool = String::new();
+    let mut lines = contents.lines().map(|line| line.trim());
+    if !lines.any(|line| line.starts_with("pub const POOL:")) {
+        return err!("{} doesn't declare a string pool", path.display());
+    }
+    for line in lines.take_while(|&line| line != ");") {
+        match line.strip_prefix('"').and_then(|l| l.strip_suffix("\",")) {
+            Some(chunk) => pool.push_str(chunk),
+            None => {
+                return err!(
+                    "{}: invalid string pool line: {:?}",
+                    path.display(),
+                    line
+                )
+            }
+        }
+    }
+    Ok(pool)
+}
+
+fn read_enum_manifest(path: &Path) -> Result<BTreeMap<String, Vec<String>>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+            return Ok(BTreeMap::new())
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let mut manifest: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once(' ') {
+            Some((enum_name, variant)) if !variant.contains(' ') => {
+                manifest
+                    .entry(enum_name.to_string())
+                    .or_default()
+                    .push(variant.to_string());
+            }
+            _ => {
+                return err!(
+                    "{}:{}: invalid enum manifest line: {:?}",
+                    path.display(),
+                    i + 1,
+                    line
+                )
+            }
+        }
+    }
+    Ok(manifest)
+}
+
+/// Move the file at `from` to `to`, unless `to` already has the same
+/// contents, in which case `from` is removed. Returns true if `to` was
+/// written.
+/// Check that the FST at the given path has exactly the keys and values of
+/// the FST with the given contents.
+fn verify_fst_file(path: &Path, expected: Vec<u8>) -> Result<()> {
+    use fst::{IntoStreamer, Streamer};
+
+    let fail = |msg: String| {
+        err!("FST {} failed verification: {}", path.display(), msg)
+    };
+    let expected = Fst::new(expected)?;
+    let actual = match Fst::new(fs::read(path)?) {
+        Ok(actual) => actual,
+        Err(err) => return fail(err.to_string()),
+    };
+    if actual.len() != expected.len() {
+        return fail(format!(
+            "it has {} keys, but {} were written",
+            actual.len(),
+            expected.len()
+        ));
+    }
+    let mut stream = expected.stream().into_stream();
+    while let Some((key, output)) = stream.next() {
+        if actual.get(key) != Some(output) {
+            return fail(format!(
+                "key {} is missing or has the wrong value",
+                rust_byte_string(key)
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn rename_if_changed(from: &Path, to: &Path, force: bool) -> Result<bool> {
+    if !force && files_equal(from, to)? {
+        fs::remove_file(from)?;
+        return Ok(false);
+    }
+    fs::rename(from, to)?;
+    Ok(true)
+}
+
+/// Return true if both files exist and have the same contents.
+fn files_equal(a: &Path, b: &Path) -> Result<bool> {
+    use std::io::Read;
+
+    let (a, b) = match (fs::File::open(a), fs::File::open(b)) {
+        (Ok(a), Ok(b)) => (a, b),
+        (_, Err(ref err)) if err.kind() == io::ErrorKind::NotFound => {
+            return Ok(false)
+        }
+        (Err(err), _) | (_, Err(err)) => return Err(err.into()),
+    };
+    if a.metadata()?.len() != b.metadata()?.len() {
+        return Ok(false);
+    }
+    let (mut a, mut b) = (io::BufReader::new(a), io::BufReader::new(b));
+    let (mut abuf, mut bbuf) = (vec![0; 1 << 16], vec![0; 1 << 16]);
+    loop {
+        let n = a.read(&mut abuf)?;
+        if n == 0 {
+            return Ok(true);
+        }
+        b.read_exact(&mut bbuf[..n])?;
+        if abuf[..n] != bbuf[..n] {
+            return Ok(false);
+        }
+    }
+}
+
+pub fn write_if_changed(
+    path: &Path,
+    contents: &[u8],
+    force: bool,
+) -> Result<bool> {
+    if !force {
+        match fs::read(path) {
+            Ok(ref existing) if existing == contents => return Ok(false),
+            Ok(_) => {}
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    fs::write(path, contents)?;
+    Ok(true)
+}
+
+#[derive(Debug)]
+struct LineWriter<W> {
+    wtr: W,
+    line: String,
+    columns: usize,
+    indent: String,
+    /// The string written in place of each two spaces of leading
+    /// indentation, or `None` if indentation is written as is.
+    indent_unit: Option<String>,
+    /// The number of leading spaces that make up one level of indentation.
+    /// Tables use two spaces per level, while functions use four.
+    indent_width: usize,
+    /// The number of leading spaces seen so far on the current line, or
+    /// `None` if the indentation of the current line has been written.
+    leading: Option<usize>,
+}
+
+impl<W: io::Write> LineWriter<W> {
+    fn new(wtr: W, opts: &WriterOptions) -> LineWriter<W> {
+        let indent_unit =
+            if opts.indent == "  " { None } else { Some(opts.indent.clone()) };
+        LineWriter {
+            wtr,
+            line: String::new(),
+            columns: opts.columns as usize,
+            indent: "  ".to_string(),
+            indent_unit,
+            indent_width: 2,
+            leading: Some(0),
+        }
+    }
+
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        if self.line.len() + s.len() > self.columns {
+            self.flush_line()?;
+        }
+        if self.line.is_empty() {
+            self.line.push_str(&self.indent);
+        }
+        self.line.push_str(s);
+        Ok(())
+    }
+
+    fn indent(&mut self, s: &str) {
+        self.indent = s.to_string();
+    }
+
+    /// Append a `//` comment to the current line, regardless of its
+    /// length, and end the line.
+    fn end_line_with_comment(&mut self, comment: &str) -> io::Result<()> {
+        self.line.truncate(self.line.trim_end().len());
+        self.line.push_str(" // ");
+        self.line.push_str(comment);
+        self.flush_line()
+    }
+
+    /// Set the number of leading spaces in the lines written from now on
+    /// that make up one level of indentation.
+    fn indent_width(&mut self, width: usize) -> io::Result<()> {
+        self.flush_line()?;
+        self.indent_width = width;
+        Ok(())
+    }
+
+    fn flush_line(&mut self) -> io::Result<()> {
+        if self.line.is_empty() {
+            return Ok(());
+        }
+        let mut line = std::mem::take(&mut self.line);
+        line.truncate(line.trim_end().len());
+        line.push('\n');
+        self.write_indented(line.as_bytes())?;
+        line.clear();
+        self.line = line;
+        Ok(())
+    }
+
+    /// Write the given bytes to the underlying writer, replacing leading
+    /// indentation on each line according to the configured indent unit.
+    fn write_indented(&mut self, buf: &[u8]) -> io::Result<()> {
+        let unit = match self.indent_unit {
+            None => return self.wtr.write_all(buf),
+            Some(ref unit) => unit,
+        };
+        let mut out = Vec::with_capacity(buf.len());
+        for &b in buf {
+            match self.leading {
+                Some(n) if b == b' ' => self.leading = Some(n + 1),
+                Some(n) => {
+                    let (levels, rest) =
+                        (n / self.indent_width, n % self.indent_width);
+                    out.extend_from_slice(unit.repeat(levels).as_bytes());
+                    out.extend_from_slice(" ".repeat(rest).as_bytes());
+                    out.push(b);
+                    self.leading = if b == b'\n' { Some(0) } else { None };
+                }
+                None => {
+                    out.push(b);
+                    if b == b'\n' {
+                        self.leading = Some(0);
+                    }
+                }
+            }
+        }
+        self.wtr.write_all(&out)
+    }
+}
+
+impl<W: io::Write> io::Write for LineWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.flush_line()?;
+        self.write_indented(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_line()?;
+        self.wtr.flush()
+    }
+}
+
+/// Heuristically produce an appropriate constant Rust name.
+pub fn rust_const_name(s: &str) -> String {
+    // Property names/values seem pretty uniform, particularly the
+    // "canonical" variants we use to produce variable names. So we
+    // don't need to do much.
+    //
+    // N.B. Age names have a `.` in them, so get rid of that.
+    let mut s = s.replace('.', "_").to_string();
+    s.make_ascii_uppercase();
+    s
+}
+
+/// Return a map from the name of each variant of the enum with the given
+/// name to the canonical value it is for, given a map from aliases to
+/// canonical values. It is an error for two values to have the same
+/// variant name.
+fn value_variants<'a>(
+    enum_name: &str,
+    values: &'a BTreeMap<String, String>,
+) -> Result<BTreeMap<String, &'a str>> {
+    let mut variants: BTreeMap<String, &str> = BTreeMap::new();
+    for canonical in values.values() {
+        let variant = rust_type_name(canonical);
+        if let Some(other) = variants.insert(variant.clone(), canonical) {
+            if other != canonical {
+                return err!(
+                    "{:?} and {:?} both map to the {}::{} variant",
+                    other,
+                    canonical,
+                    enum_name,
+                    variant
+                );
+            }
+        }
+    }
+    Ok(variants)
+}
+
+/// Heuristically produce an appropriate Rust type name.
+fn rust_type_name(s: &str) -> String {
+    // If it's all uppercase or digits then leave as is
+    if s.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+        return s.to_string();
+    }
+
+    // Convert to PascalCase
+    s.split(|c: char| c.is_whitespace() || c == '.' || c == '_' || c == '-')
+        .map(|component| {
+            // Upper first char
+            let lower = component.to_ascii_lowercase();
+            let mut chars = lower.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(f) => {
+                    f.to_uppercase().collect::<String>() + chars.as_str()
+                }
+            }
+        })
+        .collect()
+}
+
+/// Heuristically produce an appropriate module Rust name.
+fn rust_module_name(s: &str) -> String {
+    // Property names/values seem pretty uniform, particularly the
+    // "canonical" variants we use to produce variable names. So we
+    // don't need to do much.
+    let mut s = s.to_string();
+    s.make_ascii_lowercase();
+    s
+}
+
+/// Pack a collation element into a `u32`, as described by
+/// `Writer::collation`.
+fn pack_collation_element(element: &CollationElement) -> Result<u32> {
+    if element.secondary >= 1 << 9 || element.tertiary >= 1 << 6 {
+        return err!(
+            "the weights of collation element {} are too big to pack into \
+             a u32",
+            element
+        );
+    }
+    Ok(u32::from(element.primary) << 16
+        | u32::from(element.secondary) << 7
+        | u32::from(element.tertiary) << 1
+        | u32::from(element.variable))
+}
+
+/// Return the size in bytes of a value of the given type, e.g., `u32` or
+/// `(char, char, u8)`, if it is made only of integers and chars.
+///
+/// Since every such field has a size that is a power of two, the fields of
+/// a tuple can always be ordered so that none of them need padding. Only
+/// the tuple as a whole is padded to its alignment.
+fn entry_size(ty: &str) -> Option<usize> {
+    let fields = ty.trim().trim_start_matches('(').trim_end_matches(')');
+    let (mut size, mut align) = (0usize, 1);
+    for field in fields.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+        let field_size = match field {
+            "u8" | "i8" | "bool" => 1,
+            "u16" | "i16" => 2,
+            "u32" | "i32" | "char" => 4,
+            "u64" | "i64" => 8,
+            _ => return None,
+        };
+        size += field_size;
+        align = std::cmp::max(align, field_size);
+    }
+    Some((size + align - 1) / align * align)
+}
+
+fn rust_fn_name(s: &str) -> String {
+    // Convert to snake_case
+    s.to_ascii_lowercase()
+        .chars()
+        .map(
+            |c| {
+                if c.is_whitespace() || c == '.' || c == '-' {
+                    '_'
+                } else {
+                    c
+                }
+            },
+        )
+        .collect()
+}
+
+/// Return the given u32 encoded in big-endian.
+pub fn u32_key(cp: u32) -> [u8; 4] {
+    cp.to_be_bytes()
+}
+
+/// Convert the given string into a u64, where the least significant byte of
+/// the u64 is the first byte of the string.
+///
+/// If the string contains any `NUL` bytes or has more than 8 bytes, then an
+/// error is returned.
+fn pack_str(s: &str) -> Result<u64> {
+    if s.len() > 8 {
+        return err!("cannot encode string {:?} (too long)", s);
+    }
+    if s.contains('\x00') {
+        return err!("cannot encode string {:?} (contains NUL byte)", s);
+    }
+    let mut value = 0;
+    for (i, &b) in s.as_bytes().iter().enumerate() {
+        assert!(i <= 7);
+        value |= (b as u64) << (8 * i as u64);
+    }
+    Ok(value)
+}
+
+/// Return the number used for the given paragraph direction in
+/// `BidiCharacterTest.txt`.
+fn bidi_direction_code(dir: BidiParagraphDirection) -> u8 {
+    match dir {
+        BidiParagraphDirection::LeftToRight => 0,
+        BidiParagraphDirection::RightToLeft => 1,
+        BidiParagraphDirection::Auto => 2,
+    }
+}
+
+/// The helpers emitted before the first test written by
+/// `Writer::ranges_test`. Tables of `u32` and `char` ranges are both
+/// accepted, so tests don't depend on whether `--chars` was used.
+const TEST_PRELUDE: &str = "\
+fn ranges<T: Copy + Into<u32>>(table: &[(T, T)]) -> Vec<(u32, u32)> {
+  table.iter().map(|&(start, end)| (start.into(), end.into())).collect()
+}
+
+fn is_sorted(ranges: &[(u32, u32)]) -> bool {
+  ranges.iter().all(|&(start, end)| start <= end)
+    && ranges.windows(2).all(|w| w[0].1 < w[1].0)
+}
+
+fn count(ranges: &[(u32, u32)]) -> u32 {
+  ranges.iter().map(|&(start, end)| end - start + 1).sum()
+}
+
+fn contains(ranges: &[(u32, u32)], cp: u32) -> bool {
+  ranges.iter().any(|&(start, end)| start <= cp && cp <= end)
+}
+";
+
+/// The Rust definitions emitted before the first binary table in a file.
+/// See `Writer::binary_prelude`.
+const BINARY_PRELUDE: &str = "\
+/// A little endian `u32`, which can be read from any alignment.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+#[cfg_attr(
+  feature = \"zerocopy\",
+  derive(
+    zerocopy::FromBytes,
+    zerocopy::IntoBytes,
+    zerocopy::Immutable,
+    zerocopy::KnownLayout,
+    zerocopy::Unaligned,
+  )
+)]
+#[cfg_attr(feature = \"bytemuck\", derive(bytemuck::Pod, bytemuck::Zeroable))]
+pub struct U32Le(pub [u8; 4]);
+
+impl U32Le {
+  pub const fn get(self) -> u32 {
+    u32::from_le_bytes(self.0)
+  }
+}
+
+/// A range of codepoints, from `start` to `end` inclusive.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(C)]
+#[cfg_attr(
+  feature = \"zerocopy\",
+  derive(
+    zerocopy::FromBytes,
+    zerocopy::IntoBytes,
+    zerocopy::Immutable,
+    zerocopy::KnownLayout,
+    zerocopy::Unaligned,
+  )
+)]
+#[cfg_attr(feature = \"bytemuck\", derive(bytemuck::Pod, bytemuck::Zeroable))]
+pub struct Range {
+  pub start: U32Le,
+  pub end: U32Le,
+}
+
+/// A range of codepoints, from `start` to `end` inclusive, with a value.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(C)]
+#[cfg_attr(
+  feature = \"zerocopy\",
+  derive(
+    zerocopy::FromBytes,
+    zerocopy::IntoBytes,
+    zerocopy::Immutable,
+    zerocopy::KnownLayout,
+    zerocopy::Unaligned,
+  )
+)]
+#[cfg_attr(feature = \"bytemuck\", derive(bytemuck::Pod, bytemuck::Zeroable))]
+pub struct RangeValue {
+  pub start: U32Le,
+  pub end: U32Le,
+  pub value: U32Le,
+}
+
+/// A codepoint and the codepoint it maps to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(C)]
+#[cfg_attr(
+  feature = \"zerocopy\",
+  derive(
+    zerocopy::FromBytes,
+    zerocopy::IntoBytes,
+    zerocopy::Immutable,
+    zerocopy::KnownLayout,
+    zerocopy::Unaligned,
+  )
+)]
+#[cfg_attr(feature = \"bytemuck\", derive(bytemuck::Pod, bytemuck::Zeroable))]
+pub struct Mapping {
+  pub key: U32Le,
+  pub value: U32Le,
+}
+
+/// A codepoint and its `len` values, which start at `start` in another
+/// table.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(C)]
+#[cfg_attr(
+  feature = \"zerocopy\",
+  derive(
+    zerocopy::FromBytes,
+    zerocopy::IntoBytes,
+    zerocopy::Immutable,
+    zerocopy::KnownLayout,
+    zerocopy::Unaligned,
+  )
+)]
+#[cfg_attr(feature = \"bytemuck\", derive(bytemuck::Pod, bytemuck::Zeroable))]
+pub struct Expansion {
+  pub key: U32Le,
+  pub start: U32Le,
+  pub len: U32Le,
+}
+
+/// The types of the records in binary tables.
+///
+/// # Safety
+///
+/// Implementations must be `U32Le` or `repr(C)` structs of `U32Le` fields
+/// only, so that they have an alignment of 1, no padding and no invalid bit
+/// patterns.
+unsafe trait BinaryRecord {}
+
+unsafe impl BinaryRecord for U32Le {}
+unsafe impl BinaryRecord for Range {}
+unsafe impl BinaryRecord for RangeValue {}
+unsafe impl BinaryRecord for Mapping {}
+unsafe impl BinaryRecord for Expansion {}
+
+/// View the bytes of a binary table as a slice of records.
+fn binary_view<T: BinaryRecord>(bytes: &'static [u8]) -> &'static [T] {
+  let size = ::core::mem::size_of::<T>();
+  assert_eq!(bytes.len() % size, 0, \"truncated binary table\");
+  // SAFETY: T has an alignment of 1 and every bit pattern is valid for it
+  // (see BinaryRecord), and `bytes` holds a whole number of records.
+  unsafe {
+    ::core::slice::from_raw_parts(bytes.as_ptr().cast(), bytes.len() / size)
+  }
+}
+";
+
+/// The C definitions emitted once at the top of every C file. See
+/// `Writer::c_prelude`.
+const C_TRIE_SET_PRELUDE: &str = "\
+#ifndef UCD_TRIE_SET_DEFINED
+#define UCD_TRIE_SET_DEFINED
+
+// A set of codepoints, represented as a trie. This has the same layout as
+// ucd_trie::TrieSet in Rust. Use ucd_trie_set_contains to query it.
+struct ucd_trie_set {
+  const uint64_t *tree1_level1;
+  size_t tree1_level1_len;
+  const uint8_t *tree2_level1;
+  size_t tree2_level1_len;
+  const uint64_t *tree2_level2;
+  size_t tree2_level2_len;
+  const uint8_t *tree3_level1;
+  size_t tree3_level1_len;
+  const uint8_t *tree3_level2;
+  size_t tree3_level2_len;
+  const uint64_t *tree3_level3;
+  size_t tree3_level3_len;
+};
+
+// Return 1 if the given codepoint is in the given set and 0 otherwise.
+// Values greater than 0x10FFFF are never in a set.
+static inline int ucd_trie_set_contains(
+  const struct ucd_trie_set *set,
+  uint32_t cp
+) {
+  uint64_t chunk;
+  if (cp < 0x800) {
+    chunk = set->tree1_level1[cp >> 6];
+  } else if (cp < 0x10000) {
+    size_t i = (cp >> 6) - 0x20;
+    if (i >= set->tree2_level1_len) {
+      return 0;
+    }
+    chunk = set->tree2_level2[set->tree2_level1[i]];
+  } else if (cp <= 0x10FFFF) {
+    size_t i = (cp >> 12) - 0x10;
+    if (i >= set->tree3_level1_len) {
+      return 0;
+    }
+    size_t j = (size_t)set->tree3_level1[i] * 64 + ((cp >> 6) & 0x3F);
+    chunk = set->tree3_level3[set->tree3_level2[j]];
+  } else {
+    return 0;
+  }
+  return (chunk >> (cp & 0x3F)) & 1;
+}
+
+#endif
+";
+
+/// Widen the given bytes, so that they can be written with
+/// `Writer::write_slice_u64`.
+fn to_u64s(xs: &[u8]) -> Vec<u64> {
+    xs.iter().map(|&x| x as u64).collect()
+}
+
+/// Return the given bytes as a Rust byte string literal.
+fn rust_byte_string(bytes: &[u8]) -> String {
+    let escaped: Vec<u8> =
+        bytes.iter().flat_map(|&b| std::ascii::escape_default(b)).collect();
+    format!("b\"{}\"", String::from_utf8(escaped).unwrap())
+}
+
+/// The flags that don't change the generated tables, and so aren't recorded
+/// in their headers. --check is also left out, along with its value.
+pub const UNRECORDED_FLAGS: &[&str] =
+    &["--force-write", "--quiet", "--verbose"];
+
+/// Return the command line that this program was run with, as written in
+/// the header of generated files.
+fn command_line() -> Result<String> {
+    let mut argv = vec![];
+    argv.push(
+        env::current_exe()?
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned(),
+    );
+    // --check is left out, so that the output it compares is the same as
+    // the output that was written without it. So are the other flags that
+    // don't change the tables.
+    let mut args = env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        let x = arg.to_string_lossy();
+        if x == "--check" {
+            args.next();
+            continue;
+        } else if x.starts_with("--check=") || UNRECORDED_FLAGS.contains(&&*x)
+        {
+            continue;
+        }
+        if x.contains("\n") {
+            argv.push("[snip (arg too long)]".to_string());
+        } else {
+            argv.push(x.into_owned());
+        }
+    }
+    Ok(argv.join(" "))
+}
+
+/// Return the file name of an entry in a `manifest.json`, or `None` if the
+/// given line isn't an entry.
+fn manifest_entry_name(entry: &str) -> Option<String> {
+    let rest = entry.strip_prefix("{\"name\": \"")?;
+    let mut name = String::new();
+    let mut chars = rest.chars();
+    loop {
+        match chars.next()? {
+            '"' => return Some(name),
+            '\\' => name.push(chars.next()?),
+            ch => name.push(ch),
+        }
+    }
+}
+
+/// Return the SHA-256 hash of the given bytes.
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b,
+        0x59f111f1, 0x923f82a4, 0xab1c5ed5, 0xd807aa98, 0x12835b01,
+        0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7,
+        0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc,
+        0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152,
+        0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147,
+        0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+        0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819,
+        0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116, 0x1e376c08,
+        0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f,
+        0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208,
+        0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+    ];
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f,
+        0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+
+    let mut padded = bytes.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&((bytes.len() as u64) * 8).to_be_bytes());
+
+    for block in padded.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7)
+                ^ w[i - 15].rotate_right(18)
+                ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17)
+                ^ w[i - 2].rotate_right(19)
+                ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 =
+                e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 =
+                a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (x, y) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *x = x.wrapping_add(y);
+        }
+    }
+    let mut hash = [0; 32];
+    for (i, x) in h.iter().enumerate() {
+        hash[i * 4..i * 4 + 4].copy_from_slice(&x.to_be_bytes());
+    }
+    hash
+}
+
+/// Return the 64-bit FNV-1a hash of the given bytes.
+///
+/// This is used to give FST files stable, content-derived names.
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let mut hash = OFFSET_BASIS;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(PRIME);
+    }
+    hash
+}
+
+/// Return a string representing the smallest unsigned integer type for the
+/// given value.
+/// Return the name used for the given plane in the names of consts.
+fn plane_name(plane: u32) -> String {
+    match plane {
+        0 => "BMP".to_string(),
+        1 => "SMP".to_string(),
+        2 => "SIP".to_string(),
+        3 => "TIP".to_string(),
+        14 => "SSP".to_string(),
+        15 => "SPUA_A".to_string(),
+        16 => "SPUA_B".to_string(),
+        _ => format!("PLANE{}", plane),
+    }
+}
+
+/// A state machine over the bytes of UTF-8 that maps every encoded
+/// codepoint to a `u8` value, as written by `ranges_to_utf8_trie`.
+#[derive(Debug)]
+struct Utf8Trie {
+    /// The equivalence class of each byte.
+    byte_classes: Vec<u8>,
+    /// The number of equivalence classes.
+    classes: usize,
+    /// One row of `classes` entries for each state, starting with the
+    /// initial state.
+    transitions: Vec<u16>,
+}
+
+impl Utf8Trie {
+    /// The bit set on entries that are values instead of states.
+    const VALUE: u16 = 0x8000;
+
+    /// Build a trie from the value of every codepoint, indexed by codepoint.
+    fn new(values: &[u8]) -> Result<Utf8Trie> {
+        let mut states: Vec<Vec<u16>> = vec![vec![0; 256]];
+        let mut ids: BTreeMap<Vec<u16>, u16> = BTreeMap::new();
+        let mut root = vec![0; 256];
+        for b in 0x00..=0x7Fu32 {
+            root[b as usize] = Utf8Trie::VALUE | values[b as usize] as u16;
+        }
+        // The lead bytes of multi-byte sequences, along with the number of
+        // continuation bytes that follow and the range of the first one.
+        // The ranges exclude overlong encodings, surrogates and codepoints
+        // above U+10FFFF.
+        for b in 0xC2..=0xF4u32 {
+            let (rest, lo, hi, bits) = match b {
+                0xC2..=0xDF => (1, 0x80, 0xBF, b & 0x1F),
+                0xE0 => (2, 0xA0, 0xBF, b & 0x0F),
+                0xED => (2, 0x80, 0x9F, b & 0x0F),
+                0xE1..=0xEF => (2, 0x80, 0xBF, b & 0x0F),
+                0xF0 => (3, 0x90, 0xBF, b & 0x07),
+                0xF4 => (3, 0x80, 0x8F, b & 0x07),
+                _ => (3, 0x80, 0xBF, b & 0x07),
+            };
+            root[b as usize] = Utf8Trie::state(
+                values,
+                &mut states,
+                &mut ids,
+                rest,
+                bits << 6,
+                lo,
+                hi,
+            )?;
+        }
+        states[0] = root;
+
+        // Two bytes are equivalent when every state treats them the same.
+        let mut byte_classes = vec![0; 256];
+        let mut columns: Vec<Vec<u16>> = vec![];
+        for b in 0..256 {
+            let column: Vec<u16> = states.iter().map(|s| s[b]).collect();
+            let class = match columns.iter().position(|c| *c == column) {
+                Some(class) => class,
+                None => {
+                    columns.push(column);
+                    columns.len() - 1
+                }
+            };
+            byte_classes[b] = class as u8;
+        }
+        let classes = columns.len();
+        let mut transitions = vec![0; states.len() * classes];
+        for (i, state) in states.iter().enumerate() {
+            for (b, &entry) in state.iter().enumerate() {
+                transitions[i * classes + byte_classes[b] as usize] = entry;
+            }
+        }
+        Ok(Utf8Trie { byte_classes, classes, transitions })
+    }
+
+    /// Add the state reached after reading a byte that is followed by `rest`
+    /// continuation bytes, the first of which must be in `lo..=hi`, and
+    /// return its entry. `prefix` holds the bits of the codepoint that have
+    /// been read so far, shifted into place for the next byte.
+    fn state(
+        values: &[u8],
+        states: &mut Vec<Vec<u16>>,
+        ids: &mut BTreeMap<Vec<u16>, u16>,
+        rest: u32,
+        prefix: u32,
+        lo: u32,
+        hi: u32,
+    ) -> Result<u16> {
+        let mut state = vec![0; 256];
+        for b in lo..=hi {
+            let bits = prefix | (b & 0x3F);
+            state[b as usize] = if rest == 1 {
+                Utf8Trie::VALUE | values[bits as usize] as u16
+            } else {
+                Utf8Trie::state(
+                    values,
+                    states,
+                    ids,
+                    rest - 1,
+                    bits << 6,
+                    0x80,
+                    0xBF,
+                )?
+            };
+        }
+        if let Some(&id) = ids.get(&state) {
+            return Ok(id);
+        }
+        if states.len() >= Utf8Trie::VALUE as usize {
+            return err!("too many states for a UTF-8 trie");
+        }
+        let id = states.len() as u16;
+        ids.insert(state.clone(), id);
+        states.push(state);
+        Ok(id)
+    }
+}
+
+fn smallest_unsigned_type(n: u64) -> &'static str {
+    if n <= ::std::u8::MAX as u64 {
+        "u8"
+    } else if n <= ::std::u16::MAX as u64 {
+        "u16"
+    } else if n <= ::std::u32::MAX as u64 {
+        "u32"
+    } else {
+        "u64"
+    }
+}
+
+/// A xorshift64 pseudo-random number generator, used to pick test inputs
+/// that are the same every time the same table is generated.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SharedBuf;
+    use super::Utf8Trie;
+    use super::WriterBuilder;
+    use super::{fnv1a64, pack_str, rust_type_name, sha256, write_if_changed};
+    use super::{
+        CUintType, CaseFold, FstLoader, MaxAge, Optimize, Overlap, Sample,
+        Where,
+    };
+    use crate::error::Error;
+    use crate::regen::{Config, Output};
+    use crate::util::{temp_path, TempDir};
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::io::Cursor;
+    use ucd_parse::{AllKeysEntry, AllKeysLine};
+
+    impl SharedBuf {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    fn unpack_str(mut encoded: u64) -> String {
+        let mut value = String::new();
+        while encoded != 0 {
+            value.push((encoded & 0xFF) as u8 as char);
+            encoded = encoded >> 8;
+        }
+        value
+    }
+
+    #[test]
+    fn packed() {
+        assert_eq!("G", unpack_str(pack_str("G").unwrap()));
+        assert_eq!("GG", unpack_str(pack_str("GG").unwrap()));
+        assert_eq!("YEO", unpack_str(pack_str("YEO").unwrap()));
+        assert_eq!("ABCDEFGH", unpack_str(pack_str("ABCDEFGH").unwrap()));
+        assert_eq!("", unpack_str(pack_str("").unwrap()));
+
+        assert!(pack_str("ABCDEFGHI").is_err());
+        assert!(pack_str("AB\x00CD").is_err());
+    }
+
+    #[test]
+    fn test_rust_type_name() {
+        assert_eq!(&rust_type_name("simple"), "Simple");
+        assert_eq!(&rust_type_name("SCRIPT"), "SCRIPT");
+        assert_eq!(&rust_type_name("dot.separated"), "DotSeparated");
+        assert_eq!(&rust_type_name("dash-separated"), "DashSeparated");
+        assert_eq!(&rust_type_name("white \tspace"), "WhiteSpace");
+        assert_eq!(&rust_type_name("snake_case"), "SnakeCase");
+    }
+
+    #[test]
+    fn codepoint_to_string_fn() {
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        let map = [(0x1100, "G".to_string()), (0x110B, "".to_string())]
+            .into_iter()
+            .collect();
+        writer.codepoint_to_string_fn("JAMO_SHORT_NAME", &map).unwrap();
+        writer.finish().unwrap();
+        assert!(buf.contents().contains(
+            "pub fn jamo_short_name(cp: u32) -> Option<&'static str> {\n    \
+             match cp {\n        \
+             4352 => Some(\"G\"),\n        \
+             4363 => Some(\"\"),\n        \
+             _ => None,\n    \
+             }\n\
+             }\n"
+        ));
+    }
+
+    #[test]
+    fn codepoint_to_codepoint_formats() {
+        let map: BTreeMap<u32, u32> =
+            [(0x41, 0x61), (0x391, 0x3B1)].iter().copied().collect();
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.rust_match(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.codepoint_to_codepoint("lower", &map).unwrap();
+        writer.codepoint_to_codepoint("upper", &map).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert_eq!(out.matches("use std::num::NonZeroU32;").count(), 1);
+        assert!(out.contains("pub fn lower(cp: u32) -> Option<NonZeroU32>"));
+        assert!(out.contains("pub fn upper(cp: u32) -> Option<NonZeroU32>"));
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.latin1_fast_path(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.codepoint_to_codepoint("lower", &map).unwrap();
+        let out = buf.contents();
+        assert!(out.contains("pub const LOWER_LATIN1: [u8; 256] = ["));
+        assert!(out.contains(
+            "pub const LOWER: &'static [(u32, u32)] = &[\n  \
+                              (913, 945),\n];"
+        ));
+
+        let dir = TempDir::new(temp_path("ucd-generate-match-test")).unwrap();
+        let mut builder = WriterBuilder::new("test");
+        builder.rust_match(true);
+        let mut writer = builder.from_fst_dir(&dir.0).unwrap();
+        assert!(writer.codepoint_to_codepoint("lower", &map).is_err());
+    }
+
+    #[test]
+    fn codepoint_to_codepoint_fn_error() {
+        let cursor = Cursor::new(Vec::new());
+        let builder = WriterBuilder::new("test");
+        let mut writer = builder.from_writer(cursor);
+
+        // Ensure that a destination codepoint of zero is rejected
+        let map = [(1, 0)].iter().copied().collect();
+        match writer.codepoint_to_codepoint_fn("err", &map) {
+            Err(Error::Other(msg)) => {
+                assert!(msg.contains("destination codepoint must not be 0"))
+            }
+            res => panic!(
+                "expected error matching, \
+                 'destination codepoint must not be 0' \
+                 got: {:?}",
+                res
+            ),
+        }
+    }
+
+    #[test]
+    fn emit_len_consts() {
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.emit_len_consts(true);
+        let mut writer = builder.from_writer(buf.clone());
+
+        let set: BTreeSet<u32> = [1, 2, 3, 10].iter().copied().collect();
+        writer.names(["Foo", "Bar"]).unwrap();
+        writer.ranges("Foo", &set).unwrap();
+        writer.ranges("Bar", &BTreeSet::new()).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(out.contains("pub const BY_NAME_LEN: usize = 2;"));
+        assert!(out.contains("pub const FOO_LEN: usize = 2;"));
+        assert!(out.contains("pub const BAR_LEN: usize = 0;"));
+        assert!(out.contains("(\"FOO\", FOO_LEN)"));
+    }
+
+    #[test]
+    fn by_name_only_emitted() {
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+
+        let set: BTreeSet<u32> = [1].iter().copied().collect();
+        writer.names(["Foo", "Bar", "Quux"]).unwrap();
+        writer.ranges("Quux", &set).unwrap();
+        writer.ranges("Foo", &set).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(out.contains("= &[\n  (\"Foo\", FOO), (\"Quux\", QUUX),\n];"));
+        assert!(!out.contains("BAR"));
+    }
+
+    #[test]
+    fn by_name_fn() {
+        let set: BTreeSet<u32> = [1].iter().copied().collect();
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.by_name_fn(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.names(["White_Space", "Dash"]).unwrap();
+        writer.ranges("White_Space", &set).unwrap();
+        writer.ranges("Dash", &set).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(out.contains("  (\"dash\", 0), (\"whitespace\", 1),\n];"));
+        assert!(out.contains(
+            "pub fn set_by_name(name: &str) -> Option<&'static [(u32, u32)]>"
+        ));
+        assert!(out.contains(".map(|i| BY_NAME[BY_NAME_LOOSE[i].1].1)"));
+
+        let mut builder = WriterBuilder::new("test");
+        builder.by_name_fn(true);
+        let mut writer = builder.from_writer(vec![]);
+        writer.names(["Foo_Bar", "foobar"]).unwrap();
+        writer.ranges("Foo_Bar", &set).unwrap();
+        writer.ranges("foobar", &set).unwrap();
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    fn columns_and_indent() {
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.columns(30).indent("\t");
+        let mut writer = builder.from_writer(buf.clone());
+        let patterns: Vec<(u32, u32, String)> = (0..4)
+            .map(|i| (i * 0x100, i * 0x100 + 0xFF, "FOO-".to_string()))
+            .collect();
+        writer.ranges_to_name_patterns("foo", &patterns).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(
+            out.contains("\n\t(0, 255, \"FOO-\"),\n\t(256, 511, \"FOO-\"),")
+        );
+        assert!(out.contains("\n\tuse core::cmp::Ordering;\n"));
+        assert!(out.contains("\n\t\t.binary_search_by("));
+        assert!(!out.contains("\n "));
+    }
+
+    #[test]
+    fn name_constants() {
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.max_age(Some(MaxAge::new((1, 0), vec![(0x4E00, 0x4E00)])));
+        let mut writer = builder.from_writer(buf.clone());
+        let names = BTreeMap::from([
+            ("LATIN CAPITAL LETTER A".to_string(), 0x41),
+            ("NEW".to_string(), 0x4E00),
+            ("NULL".to_string(), 0x0),
+            ("NUL".to_string(), 0x0),
+        ]);
+        writer
+            .name_constants(
+                "names",
+                &names,
+                &[(0xAC00, 0xD7A3)],
+                &[(0x3400, 0x4DBF), (0x4E00, 0x9FFF)],
+            )
+            .unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(out.contains("pub const NAMES_COUNT: usize = 3;"));
+        assert!(out.contains("pub const NAMES_CODEPOINT_COUNT: usize = 2;"));
+        assert!(out.contains("pub const NAMES_MAX_LEN: usize = 22;"));
+        assert!(out.contains("NAMES_HANGUL_SYLLABLE_COUNT: usize = 11172;"));
+        assert!(out.contains("  (44032, 55203),\n"));
+        assert!(out.contains("  (13312, 19903), (19969, 40959),\n"));
+    }
+
+    #[test]
+    fn latin1_fast_path() {
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        let map =
+            BTreeMap::from([(0x41, 0x61), (0xFF, 0x178), (0x100, 0x101)]);
+        writer.codepoint_to_codepoint_latin1("upper", &map).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(
+            out.contains("pub const UPPER_LATIN1: [u32; 256] = [\n  0, 1,")
+        );
+        assert!(out.contains(" 64, 97, 66,"));
+        assert!(out.contains(" 254, 376,\n];"));
+        assert!(out.contains(
+            "pub const UPPER: &'static [(u32, u32)] = &[\n  (256, 257),\n];"
+        ));
+        assert!(
+            out.contains("pub fn upper(cp: u32) -> u32 {\n    if cp < 256 {")
+        );
+    }
+
+    #[test]
+    fn c_trie_set() {
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.trie_set(true).c_output(true);
+        let mut writer = builder.from_writer(buf.clone());
+        let set: BTreeSet<u32> = [0x41, 0x10000].iter().copied().collect();
+        writer.names(["Foo"]).unwrap();
+        writer.ranges("Foo", &set).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(out.contains("#include <stdint.h>"));
+        assert!(out.contains("static const uint64_t FOO_TREE1_LEVEL1[] = {"));
+        assert!(out.contains("static const struct ucd_trie_set FOO = {"));
+        assert!(out.contains("  NULL, 0,"));
+        assert!(out.contains("} BY_NAME[] = {\n  {\"Foo\", &FOO},\n};"));
+
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        let map = BTreeMap::from([("A".to_string(), set)]);
+        assert!(writer.ranges_to_enum("foo", &map).is_err());
+    }
+
+    #[test]
+    fn c_uint_type_and_enum() {
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder
+            .trie_set(true)
+            .c_output(true)
+            .c_uint_type(CUintType::Least)
+            .c_enum(true);
+        let mut writer = builder.from_writer(buf.clone());
+        let set: BTreeSet<u32> = [0x41].iter().copied().collect();
+        writer.names(["Foo", "Bar"]).unwrap();
+        writer.ranges("Foo", &set).unwrap();
+        writer.ranges("Bar", &set).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(!out.contains(" uint64_t") && !out.contains(" uint8_t"));
+        assert!(out.contains("  const uint_least64_t *tree1_level1;"));
+        assert!(out.contains("  uint_least32_t cp\n"));
+        assert!(
+            out.contains("static const uint_least64_t FOO_TREE1_LEVEL1[] = {")
+        );
+        assert!(
+            out.contains("enum test {\n  TEST_BAR = 0,\n  TEST_FOO = 1,\n};")
+        );
+    }
+
+    #[test]
+    fn combined_disjoint() {
+        let mut map = BTreeMap::new();
+        map.insert("A".to_string(), [1, 2].iter().copied().collect());
+        map.insert("B".to_string(), [2, 3].iter().copied().collect());
+
+        let builder = WriterBuilder::new("test");
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        assert!(writer.ranges_to_combined("union", &map).is_ok());
+
+        let mut builder = WriterBuilder::new("test");
+        builder.combined_disjoint(true);
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        match writer.ranges_to_combined("disjoint", &map) {
+            Err(Error::Other(msg)) => assert!(msg.contains("U+0002")),
+            res => panic!("expected overlap error, got: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn overlap() {
+        let mut map = BTreeMap::new();
+        map.insert("A".to_string(), [1, 2].iter().copied().collect());
+        map.insert("B".to_string(), [2, 3].iter().copied().collect());
+        map.insert("C".to_string(), [3, 4].iter().copied().collect());
+
+        let table = |overlap, priority: &[&str]| {
+            let buf = SharedBuf::default();
+            let mut builder = WriterBuilder::new("test");
+            builder.overlap(overlap).overlap_priority(priority.to_vec());
+            let mut writer = builder.from_writer(buf.clone());
+            writer.ranges_to_enum("foo", &map).map(|_| buf.contents())
+        };
+        let last = table(Overlap::Last, &[]).unwrap();
+        assert!(last.contains("(1, 1, 0), (2, 2, 1), (3, 4, 2)"));
+        let first = table(Overlap::First, &[]).unwrap();
+        assert!(first.contains("(1, 2, 0), (3, 3, 1), (4, 4, 2)"));
+        let priority = table(Overlap::Last, &["B"]).unwrap();
+        assert!(priority.contains("(1, 1, 0), (2, 3, 1), (4, 4, 2)"));
+
+        match table(Overlap::Error, &[]) {
+            Err(Error::Other(msg)) => assert!(msg.contains("U+0002")),
+            res => panic!("expected overlap error, got: {:?}", res),
+        }
+        // Overlaps resolved by priority aren't errors.
+        let resolved = table(Overlap::Error, &["B", "C"]).unwrap();
+        assert!(resolved.contains("(1, 1, 0), (2, 3, 1), (4, 4, 2)"));
+    }
+
+    #[test]
+    fn optimize() {
+        let small: BTreeSet<u32> = (0x41..=0x5A).collect();
+        let large: BTreeSet<u32> = (0..0x200).step_by(2).collect();
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.optimize(Some(Optimize::Speed));
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges("small", &small).unwrap();
+        writer.ranges("large", &large).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains("// SMALL is written as ranges"));
+        assert!(out.contains("pub const SMALL: &'static [(u32, u32)]"));
+        assert!(out.contains("// LARGE is written as a trie"));
+        assert!(out.contains("pub const LARGE: &'static ::ucd_trie::TrieSet"));
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.optimize(Some(Optimize::Size));
+        let mut writer = builder.from_writer(buf.clone());
+        writer.names(["small", "large"]).unwrap();
+        writer.ranges("small", &small).unwrap();
+        writer.ranges("large", &large).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains(
+            "// The 2 sets in BY_NAME are written as tries, \
+             optimizing for size."
+        ));
+        assert!(out.contains("pub const SMALL: &'static ::ucd_trie::TrieSet"));
+        assert!(out.contains(
+            "pub const BY_NAME: &'static [(&'static str, \
+             &'static ::ucd_trie::TrieSet)]"
+        ));
+    }
+
+    #[test]
+    fn annotate() {
+        let names = BTreeMap::from([
+            (0x41, "LATIN CAPITAL LETTER A".to_string()),
+            (0x5A, "LATIN CAPITAL LETTER Z".to_string()),
+        ]);
+        let set: BTreeSet<u32> = (0x41..=0x5A).chain([0x100]).collect();
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.annotate(Some(names.into()));
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges("upper", &set).unwrap();
+        assert!(buf.contents().contains(
+            "pub const UPPER: &'static [(u32, u32)] = &[\n  \
+             (65, 90), // LATIN CAPITAL LETTER A..LATIN CAPITAL LETTER Z\n  \
+             (256, 256), // U+0100\n\
+             ];"
+        ));
+    }
+
+    #[test]
+    fn wasm() {
+        let upper: BTreeSet<u32> = (0x41..=0x5A).collect();
+        let digits: BTreeSet<u32> = (0x30..=0x39).chain([0x660]).collect();
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.wasm(true).quiet(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.names(["upper", "digits"]).unwrap();
+        writer.ranges("upper", &upper).unwrap();
+        writer.ranges("digits", &digits).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(
+            out.contains("pub const UPPER: ::core::ops::Range<usize> = 0..1;")
+        );
+        assert!(out
+            .contains("pub const DIGITS: ::core::ops::Range<usize> = 1..3;"));
+        assert!(out.contains(
+            "pub const ALL_RANGES: &'static [(u32, u32)] = &[\n  \
+             (65, 90), (48, 57), (1632, 1632),\n];"
+        ));
+        assert!(out.contains(
+            "pub const BY_NAME_NAMES: &'static str = \"digitsupper\";"
+        ));
+        assert!(out.contains("  (0, 6, 1, 3), (6, 11, 0, 1),\n"));
+        assert!(out.contains("// Total data size: 67 bytes in 3 tables."));
+    }
+
+    #[test]
+    fn flat_table_pad() {
+        let mut map = BTreeMap::new();
+        map.insert(0x41, vec![0x61]);
+        map.insert(0xDF, vec![0x73, 0x73]);
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.flat_table_pad(Some(0xFFFF)).flat_table_trim(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.codepoint_to_codepoints("lower", &map, true).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(out.contains("(65, [97, 65535, 65535, ])"));
+        assert!(out.contains("pub const LOWER_PAD: u32 = 65535;"));
+        assert!(out.contains("pub fn lower_trim(values: &[u32; 3])"));
+
+        let mut builder = WriterBuilder::new("test");
+        builder.flat_table_pad(Some(0x73));
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        assert!(writer.codepoint_to_codepoints("lower", &map, true).is_err());
+    }
+
+    #[test]
+    fn flat_table_wide() {
+        let mut map = BTreeMap::new();
+        map.insert(0x41, vec![0x61]);
+        map.insert(0x3316, vec![0x30AD, 0x30ED, 0x30E1, 0x30FC, 0x30C8]);
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.flat_table_trim(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.codepoint_to_codepoints("fold", &map, true).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(out.contains("&'static [(u32, [u32; 5])]"));
+        assert!(out.contains("(65, [97, !0, !0, !0, !0, ])"));
+        assert!(out.contains("pub fn fold_trim(values: &[u32; 5])"));
+    }
+
+    #[test]
+    fn string_to_codepoints() {
+        let mut map = BTreeMap::new();
+        map.insert(
+            "KEYCAP NUMBER SIGN".to_string(),
+            vec![0x23, 0xFE0F, 0x20E3],
+        );
+        map.insert("A WITH GRAVE".to_string(), vec![0x41, 0x300]);
+
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        writer.string_to_codepoints("seqs", &map, false).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains(
+            "pub const SEQS: &'static [(&'static str, &'static [u32])] = &["
+        ));
+        assert!(out.contains("(\"A WITH GRAVE\", &[65, 768, ]),"));
+        assert!(
+            out.contains("(\"KEYCAP NUMBER SIGN\", &[35, 65039, 8419, ]),")
+        );
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.flat_table_trim(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.string_to_codepoints("seqs", &map, true).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains("&'static [(&'static str, [u32; 3])]"));
+        assert!(out.contains("(\"A WITH GRAVE\", [65, 768, !0, ])"));
+        assert!(out.contains("pub fn seqs_trim(values: &[u32; 3])"));
+    }
+
+    #[test]
+    fn pair_table() {
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        let classes = vec!["AL".to_string(), "ZW".to_string()];
+        let outcomes = [("Direct", "Break."), ("Prohibited", "No break.")];
+        let table = vec![vec![0, 1], vec![0, 1]];
+        writer
+            .pair_table("lb", &classes, "Outcome", &outcomes, &table)
+            .unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains("pub enum Lb {\n  AL = 0, ZW = 1,"));
+        assert!(out.contains("    /// No break.\n    Prohibited,\n"));
+        assert!(
+            out.contains("[Outcome::Direct, Outcome::Prohibited], // AL\n")
+        );
+        assert!(out.contains("pub fn lb_pair(before: Lb, after: Lb)"));
+
+        let mut writer = WriterBuilder::new("test").from_writer(vec![]);
+        let table = vec![vec![0, 2], vec![0, 1]];
+        assert!(writer
+            .pair_table("lb", &classes, "Outcome", &outcomes, &table)
+            .is_err());
+    }
+
+    #[test]
+    fn version_dispatch() {
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        let modules = vec![
+            ("unicode-15-1".to_string(), "unicode_15_1".to_string()),
+            ("unicode-14-0".to_string(), "unicode_14_0".to_string()),
+        ];
+        writer.version_dispatch(&modules).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains(
+            "#[cfg(feature = \"unicode-15-1\")]\npub mod unicode_15_1;"
+        ));
+        assert!(out.contains(
+            "#[cfg(all(feature = \"unicode-14-0\", \
+             not(any(feature = \"unicode-15-1\"))))]\n\
+             pub use self::unicode_14_0::*;"
+        ));
+        assert!(out.contains("compile_error!("));
+
+        let mut writer = WriterBuilder::new("test").from_writer(vec![]);
+        assert!(writer.version_dispatch(&[]).is_err());
+    }
+
+    #[test]
+    fn codepoint_tags() {
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        let tags =
+            [("Explicit", "Listed.", 1 << 33), ("Alias", "Alias.", 1 << 34)];
+        writer.codepoint_tags("names", &tags).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains("pub const NAMES_TAG_EXPLICIT: u64 = 1 << 33;"));
+        assert!(out.contains("pub const NAMES_TAG_ALIAS: u64 = 1 << 34;"));
+        assert!(out.contains("pub fn names_decode(value: u64) -> (u64, u32)"));
+
+        let mut writer = WriterBuilder::new("test").from_writer(vec![]);
+        assert!(writer
+            .codepoint_tags("names", &[("Low", "", 1 << 3)])
+            .is_err());
+    }
+
+    #[test]
+    fn bit_flags() {
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        let flags = [("Lowercased", "Lower."), ("Uppercased", "Upper.")];
+        writer.bit_flags("changes_when", &flags).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(
+            out.contains("pub const CHANGES_WHEN_LOWERCASED: u8 = 1 << 0;")
+        );
+        assert!(
+            out.contains("pub const CHANGES_WHEN_UPPERCASED: u8 = 1 << 1;")
+        );
+
+        let mut writer = WriterBuilder::new("test").from_writer(vec![]);
+        assert!(writer.bit_flags("changes_when", &[]).is_err());
+    }
+
+    #[test]
+    fn codepoint_pairs() {
+        let pairs: BTreeSet<(u32, u32)> =
+            [(0x1F1E8, 0x1F1E6), (0x1F1E6, 0x1F1E8)].iter().copied().collect();
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        writer.codepoint_pairs("flags", &pairs).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains("(127462, 127464), (127464, 127462),"));
+        assert!(out.contains(
+            "pub fn flags_contains(a: char, b: char) -> bool {\n    \
+             FLAGS.binary_search(&(a as u32, b as u32)).is_ok()\n}"
+        ));
+    }
+
+    #[test]
+    fn codepoint_pairs_to_codepoint() {
+        let mut map = BTreeMap::new();
+        map.insert((0x41, 0x300), 0xC0);
+        map.insert((0x41, 0x301), 0xC1);
+        map.insert((0x45, 0x300), 0xC8);
+
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        writer.codepoint_pairs_to_codepoint("comp", &map, false).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains("pub const COMP: &'static [((u32, u32), u32)]"));
+        assert!(out.contains("((65, 768), 192), ((65, 769), 193),"));
+        assert!(out.contains("pub fn compose(a: char, b: char)"));
+
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        writer.codepoint_pairs_to_codepoint("comp", &map, true).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains("&'static [(u32, u32, u32)]"));
+        assert!(out.contains("(65, 0, 2), (69, 2, 3),"));
+        assert!(out.contains("(768, 192), (769, 193), (768, 200),"));
+        assert!(out.contains("COMP_PAIRS[start as usize..end as usize]"));
+    }
+
+    #[test]
+    fn enum_aliases() {
+        let mut map = BTreeMap::new();
+        map.insert("Latin".to_string(), [0x41].iter().copied().collect());
+        map.insert("Greek".to_string(), [0x3B1].iter().copied().collect());
+        let aliases = vec![
+            ("Grek".to_string(), "Greek".to_string()),
+            ("Latn".to_string(), "Latin".to_string()),
+        ];
+        let hash = fnv1a64(b"Greek\0Latin\0");
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.enum_hash(Some(hash));
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges_to_enum_with_aliases("script", &map, &aliases).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(out.contains("\"Grek\", \"Latn\","));
+        assert!(out.contains("SCRIPT_ENUM_LONG"));
+        assert!(out.contains("\"Greek\", \"Latin\","));
+        let pinned = format!("SCRIPT_ENUM_HASH == 0x{:016X}", hash);
+        assert!(out.contains(&pinned));
+    }
+
+    #[test]
+    fn enum_sets() {
+        let variants = vec!["Greek".to_string(), "Latin".to_string()];
+        let mut sets = BTreeMap::new();
+        sets.insert(0x41, [1].iter().copied().collect());
+        sets.insert(0x300, [0, 1].iter().copied().collect());
+        sets.insert(0x3B1, [0].iter().copied().collect());
+
+        let buf = SharedBuf::default();
+        let builder = WriterBuilder::new("test");
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges_to_enum_sets("scx", &variants, &sets).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(out.contains("SCX_SETS: &'static [&'static [u8]]"));
+        assert!(out.contains("&[0, ], &[0, 1, ], &[1, ],"));
+        assert!(out.contains("(65, 65, 2), (768, 768, 1), (945, 945, 0),"));
+    }
+
+    #[test]
+    fn fst_shards() {
+        let dir = TempDir::new(temp_path("ucd-generate-shard-test")).unwrap();
+        let map: BTreeMap<String, u32> =
+            (0..2000).map(|i| (format!("KEY {:x}", i * 7919), i)).collect();
+
+        let mut builder = WriterBuilder::new("test");
+        builder.fst_max_file_size(Some(4096));
+        let mut writer = builder.from_fst_dir(&dir.0).unwrap();
+        writer.string_to_codepoint("names", &map).unwrap();
+        writer.finish().unwrap();
+
+        let out = std::fs::read_to_string(dir.0.join("test.rs")).unwrap();
+        assert!(out.contains("pub fn names_shard(key: &[u8])"));
+        let mut count = 0;
+        for i in 0.. {
+            let path = dir.0.join(format!("names-{}.fst", i));
+            if !path.exists() {
+                break;
+            }
+            let bytes = std::fs::read(path).unwrap();
+            assert!(bytes.len() <= 4096);
+            count += fst::Map::new(bytes).unwrap().len();
+        }
+        assert_eq!(count, map.len());
+    }
+
+    #[test]
+    fn css_unicode_range() {
+        let set: BTreeSet<u32> = (0..=0xFF).chain([0x131]).collect();
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.css_unicode_range(true).columns(20);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.names(["Foo"]).unwrap();
+        writer.ranges("Foo", &set).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains(
+            "pub const FOO: &'static str = \"\\\n  U+0000-00FF, \\\n  \
+             U+0131\";"
+        ));
+        assert!(
+            out.contains("BY_NAME: &'static [(&'static str, &'static str)]")
+        );
+    }
+
+    #[test]
+    fn sample() {
+        let set: BTreeSet<u32> =
+            [1, 2, 5, 9, 10, 20].iter().copied().collect();
+        let mut map = BTreeMap::new();
+        map.insert("A".to_string(), [1, 2, 3].iter().copied().collect());
+        map.insert("B".to_string(), [4, 5, 30].iter().copied().collect());
+
+        let table = |sample| {
+            let buf = SharedBuf::default();
+            let mut builder = WriterBuilder::new("test");
+            builder.sample(Some(sample));
+            let mut writer = builder.from_writer(buf.clone());
+            writer.ranges("set", &set).unwrap();
+            writer.ranges_to_enum("enum", &map).unwrap();
+            buf.contents()
+        };
+        let first = table(Sample::Ranges(2));
+        assert!(first.contains("only the first 2 ranges"));
+        assert!(first.contains("(1, 2), (5, 5),\n];"));
+        assert!(first.contains("(1, 3, 0), (4, 5, 1),\n];"));
+
+        let window = table(Sample::Window(2, 9));
+        assert!(window.contains("U+0002..U+0009"));
+        assert!(window.contains("(2, 2), (5, 5), (9, 9),\n];"));
+        assert!(window.contains("(2, 3, 0), (4, 5, 1),\n];"));
+    }
+
+    #[test]
+    fn fst_loader() {
+        let mut set = BTreeSet::new();
+        set.insert(0x41);
+        for (loader, expected) in [
+            (
+                FstLoader::LazyStatic,
+                "::lazy_static::lazy_static! {\n  pub static ref ALPHA: \
+                 ::fst::Set<&'static [u8]> = {",
+            ),
+            (
+                FstLoader::OnceLock,
+                "pub fn alpha() -> &'static ::fst::Set<&'static [u8]> {\n  \
+                 static ALPHA: ::std::sync::OnceLock<",
+            ),
+            (
+                FstLoader::Fn,
+                "pub fn alpha() -> ::fst::Set<&'static [u8]> {\n  \
+                 ::fst::Set::from(",
+            ),
+        ] {
+            let dir = TempDir::new(temp_path("ucd-generate-fst-loader-test"))
+                .unwrap();
+            let mut builder = WriterBuilder::new("test");
+            builder.fst_loader(loader);
+            let mut writer = builder.from_fst_dir(&dir.0).unwrap();
+            writer.ranges("alpha", &set).unwrap();
+            writer.finish().unwrap();
+            let out = std::fs::read_to_string(dir.0.join("test.rs")).unwrap();
+            assert!(out.contains(expected), "{:?}:\n{}", loader, out);
+        }
+    }
+
+    #[test]
+    fn long_fst_values() {
+        let dir = TempDir::new(temp_path("ucd-generate-fst-test")).unwrap();
+        let mut map = BTreeMap::new();
+        map.insert(0x41, "A".to_string());
+        map.insert(0x42, "MUCH LONGER THAN EIGHT".to_string());
+
+        let mut builder = WriterBuilder::new("test");
+        builder.max_value_len(Some(8));
+        let mut writer = builder.from_fst_dir(&dir.0).unwrap();
+        assert!(writer.codepoint_to_string("long", &map).is_err());
+
+        let builder = WriterBuilder::new("test");
+        let mut writer = builder.from_fst_dir(&dir.0).unwrap();
+        writer.codepoint_to_string("long", &map).unwrap();
+        writer.finish().unwrap();
+        let out = std::fs::read_to_string(dir.0.join("test.rs")).unwrap();
+        assert!(out.contains("\"A\", \"MUCH LONGER THAN EIGHT\","));
+    }
+
+    #[test]
+    fn sorted_fst() {
+        let dir = TempDir::new(temp_path("ucd-generate-sorted-test")).unwrap();
+        let map: BTreeMap<String, u64> =
+            (0..100).map(|i| (format!("KEY {:03}", i), i)).collect();
+
+        let builder = WriterBuilder::new("names");
+        let mut writer = builder.from_fst_dir(&dir.0).unwrap();
+        writer
+            .string_to_u64_sorted(
+                "names",
+                map.iter().map(|(k, &v)| Ok((k.clone(), v))),
+            )
+            .unwrap();
+        writer.finish().unwrap();
+        let sorted = std::fs::read(dir.0.join("names.fst")).unwrap();
+        assert!(!dir.0.join("names.fst.tmp").exists());
+
+        let builder = WriterBuilder::new("names");
+        let mut writer = builder.from_fst_dir(&dir.0).unwrap();
+        writer.string_to_u64("names", &map).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(sorted, std::fs::read(dir.0.join("names.fst")).unwrap());
+
+        let builder = WriterBuilder::new("names");
+        let mut writer = builder.from_fst_dir(&dir.0).unwrap();
+        let unsorted =
+            vec![Ok(("B".to_string(), 1)), Ok(("A".to_string(), 2))];
+        assert!(writer.string_to_u64_sorted("names", unsorted).is_err());
+        assert!(!dir.0.join("names.fst.tmp").exists());
+    }
+
+    #[test]
+    fn case_fold_keys() {
+        let mut map = BTreeMap::new();
+        map.insert("Foo".to_string(), 1);
+        map.insert("FOO".to_string(), 1);
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.case_fold_keys(Some(CaseFold::Ascii));
+        let mut writer = builder.from_writer(buf.clone());
+        writer.string_to_codepoint("names", &map).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains("= &[\n  (\"foo\", 1),\n];"));
+        assert!(out.contains("pub fn names(query: &str) -> Option<u32> {"));
+
+        map.insert("fOO".to_string(), 2);
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        assert!(writer.string_to_codepoint("names", &map).is_err());
+    }
+
+    #[test]
+    fn assert_nonempty() {
+        let set: BTreeSet<u32> = [1].iter().copied().collect();
+        let mut builder = WriterBuilder::new("test");
+        builder.assert_nonempty(true);
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        writer.ranges("Foo", &set).unwrap();
+        assert!(writer.ranges("Bar", &BTreeSet::new()).is_err());
+
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        writer.names(["Foo"]).unwrap();
+        assert!(writer.finish().is_err());
+
+        let map = BTreeMap::new();
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        assert!(writer.codepoint_to_codepoint("lower", &map).is_err());
+    }
+
+    #[test]
+    fn enum_manifest() {
+        let dir =
+            TempDir::new(temp_path("ucd-generate-manifest-test")).unwrap();
+        let manifest = dir.0.join("manifest");
+        std::fs::write(&manifest, "Foo C\nFoo B\n").unwrap();
+        let mut map = BTreeMap::new();
+        for variant in &["A", "B", "D"] {
+            map.insert(variant.to_string(), BTreeSet::from([1]));
+        }
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder
+            .enum_non_exhaustive(true)
+            .enum_discriminants(true)
+            .enum_manifest(Some(&manifest));
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges_to_rust_enum("foo", &["A", "B", "D"], &map).unwrap();
+        writer.finish().unwrap();
+        assert!(buf.contents().contains(
+            "#[non_exhaustive]\npub enum Foo {\n  B = 1, A = 2, D = 3,\n}"
+        ));
+        assert_eq!(
+            std::fs::read_to_string(&manifest).unwrap(),
+            "Foo C\nFoo B\nFoo A\nFoo D\n"
+        );
+    }
+
+    #[test]
+    fn property_value_enum() {
+        let mut values = BTreeMap::new();
+        values.insert("l".to_string(), "Left".to_string());
+        values.insert("left".to_string(), "Left".to_string());
+        values.insert("r".to_string(), "Right".to_string());
+
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        writer.property_value_enum("Side_Of_Road", &values).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains("pub enum SideOfRoad {\n  Left, Right,\n}"));
+        assert!(out.contains("(\"left\", SideOfRoad::Left)"));
+        assert!(out.contains("SideOfRoad::Right => \"Right\","));
+        assert!(out.contains("impl ::core::str::FromStr for SideOfRoad {"));
+
+        values.insert("rr".to_string(), "R_R".to_string());
+        values.insert("rr2".to_string(), "RR".to_string());
+        let mut writer =
+            WriterBuilder::new("test").from_writer(Cursor::new(Vec::new()));
+        assert!(writer.property_value_enum("Side_Of_Road", &values).is_err());
+    }
+
+    #[test]
+    fn rust_enum_names() {
+        let mut map = BTreeMap::new();
+        map.insert("Left".to_string(), BTreeSet::from([1, 2]));
+        let mut values = BTreeMap::new();
+        values.insert("l".to_string(), "Left".to_string());
+        values.insert("left".to_string(), "Left".to_string());
+        values.insert("r".to_string(), "Right".to_string());
+        let mut abbreviations = BTreeMap::new();
+        abbreviations.insert("Left".to_string(), "L".to_string());
+
+        let mut writer =
+            WriterBuilder::new("test").from_writer(Cursor::new(Vec::new()));
+        assert!(writer
+            .rust_enum_names("side", &values, &abbreviations)
+            .is_err());
+
+        abbreviations.insert("Right".to_string(), "R".to_string());
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        writer.ranges_to_rust_enum("side", &["Left", "Right"], &map).unwrap();
+        writer.rust_enum_names("side", &values, &abbreviations).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert_eq!(out.matches("pub enum Side {").count(), 1);
+        assert!(out.contains("pub const SIDE_ALIASES:"));
+        assert!(out.contains("pub fn abbreviation(self) -> &'static str {"));
+        assert!(out.contains("Side::Right => \"R\","));
+    }
+
+    #[test]
+    fn group_by_plane() {
+        let set: BTreeSet<u32> =
+            (0xFFF0..0x10010).chain(0xE0001..0xE0002).collect();
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.group_by_plane(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges("foo", &set).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains(
+            "FOO_BMP: &'static [(u32, u32)] = &[\n  (65520, 65535),"
+        ));
+        assert!(out.contains(
+            "FOO_SMP: &'static [(u32, u32)] = &[\n  (65536, 65551),"
+        ));
+        assert!(out.contains("FOO_SSP"));
+        assert!(out.contains("(0, FOO_BMP), (1, FOO_SMP), (14, FOO_SSP),"));
+    }
+
+    #[test]
+    fn strings() {
+        let set: BTreeSet<String> =
+            ["\u{231B}", "#\u{FE0F}\u{20E3}", "\u{231A}"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        writer.strings("rgi_emoji", &set).unwrap();
+        writer.finish().unwrap();
+        assert!(buf.contents().contains(
+            "RGI_EMOJI: &'static [&'static str] = &[\n  \
+             \"#\\u{fe0f}\\u{20e3}\", \"\u{231A}\", \"\u{231B}\",\n];"
+        ));
+    }
+
+    #[test]
+    fn utf8_trie() {
+        let mut values = vec![0xFF; 0x110000];
+        values[0x41] = 0;
+        values[0xE9] = 1;
+        values[0x20AC] = 1;
+        values[0x1F600] = 2;
+        let trie = Utf8Trie::new(&values).unwrap();
+        let lookup = |bytes: &[u8]| -> (u8, usize) {
+            let mut state = 0;
+            for (i, &b) in bytes.iter().enumerate() {
+                let class = trie.byte_classes[b as usize] as usize;
+                let entry = trie.transitions[state * trie.classes + class];
+                if entry & Utf8Trie::VALUE != 0 {
+                    return (entry as u8, i + 1);
+                } else if entry == 0 {
+                    break;
+                }
+                state = entry as usize;
+            }
+            (0xFF, std::cmp::min(1, bytes.len()))
+        };
+        for (cp, &value) in values.iter().enumerate() {
+            let ch = match char::from_u32(cp as u32) {
+                None => continue,
+                Some(ch) => ch,
+            };
+            let mut buf = [0; 4];
+            let encoded = ch.encode_utf8(&mut buf).as_bytes();
+            assert_eq!(lookup(encoded), (value, encoded.len()), "{:?}", ch);
+        }
+        // Invalid UTF-8, including an overlong encoding, a surrogate, a
+        // codepoint above U+10FFFF and a truncated sequence.
+        assert_eq!(lookup(b"\x80"), (0xFF, 1));
+        assert_eq!(lookup(b"\xC0\x80"), (0xFF, 1));
+        assert_eq!(lookup(b"\xED\xA0\x80"), (0xFF, 1));
+        assert_eq!(lookup(b"\xF4\x90\x80\x80"), (0xFF, 1));
+        assert_eq!(lookup(b"\xE2\x82"), (0xFF, 1));
+        assert_eq!(lookup(b""), (0xFF, 0));
+    }
+
+    #[test]
+    fn ranges_test() {
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        let set = BTreeSet::from([0x41, 0x42, 0x61]);
+        let samples = BTreeSet::from([0x40, 0x41, 0x61]);
+        writer.ranges_test("foo", "tables", &set, &samples).unwrap();
+        writer.ranges_test("bar", "tables", &set, &samples).unwrap();
+        writer.finish().unwrap();
+
+        let got = buf.contents();
+        assert_eq!(got.matches("fn is_sorted(").count(), 1);
+        assert!(got.contains("let ranges = ranges(tables::FOO);\n"));
+        assert!(got.contains("assert_eq!(ranges.len(), 2, "));
+        assert!(got.contains("assert_eq!(count(&ranges), 3, "));
+        assert!(got.contains("let members: &[u32] = &[0x41, 0x61];\n"));
+        assert!(got.contains("let others: &[u32] = &[0x40];\n"));
+        assert!(got.contains("#[test]\nfn bar() {\n"));
     }
 
-    /// Return valid Rust source code that represents the given codepoint.
-    ///
-    /// The source code returned is either a u32 literal or a char literal,
-    /// depending on the configuration. If the configuration demands a char
-    /// literal and the given codepoint is a surrogate, then return None.
-    fn rust_codepoint(&self, cp: u32) -> Option<String> {
-        if self.opts.char_literals {
-            char::from_u32(cp).map(|c| format!("{:?}", c))
-        } else if cp == !0 {
-            // Used to represent missing entries in some cases (specifically
-            // --flat-table), and writing it as `!0` makes the whole table much
-            // easier to read while maintaining identical semantics, even if
-            // `--flat-table` is not in use.
-            Some("!0".to_string())
-        } else {
-            Some(cp.to_string())
-        }
+    #[test]
+    fn regex_syntax() {
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.char_literals(true).regex_syntax(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.names(["Foo", "Bar"]).unwrap();
+        writer.ranges("Bar", &BTreeSet::from([0x61])).unwrap();
+        writer.ranges("Foo", &BTreeSet::from([0x41])).unwrap();
+        writer.finish().unwrap();
+
+        let got = buf.contents();
+        let by_name = got.find("pub const BY_NAME").unwrap();
+        assert!(by_name < got.find("pub const BAR").unwrap());
+        assert!(got.contains("= &[\n  (\"Bar\", BAR),\n  (\"Foo\", FOO),\n];"));
+        assert_eq!(got.matches("BY_NAME").count(), 1);
     }
 
-    /// Return valid Rust source code indicating the type of the codepoint
-    /// that we emit based on this writer's configuration.
-    fn rust_codepoint_type(&self) -> &'static str {
-        if self.opts.char_literals {
-            "char"
-        } else {
-            "u32"
-        }
+    #[test]
+    fn emit_iter() {
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.emit_iter(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges("foo", &BTreeSet::from([0x41, 0x42])).unwrap();
+        writer.finish().unwrap();
+        assert!(buf.contents().contains(
+            "pub fn foo_iter() -> impl Iterator<Item = char> {\n  \
+             FOO.iter().copied()\n    \
+             .flat_map(|(start, end)| (start..=end).filter_map(char::from_u32))\n\
+             }"
+        ));
+
+        builder.trie_set(true);
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        assert!(writer.ranges("foo", &BTreeSet::from([0x41])).is_err());
     }
-}
 
-#[derive(Debug)]
-struct LineWriter<W> {
-    wtr: W,
-    line: String,
-    columns: usize,
-    indent: String,
-}
+    #[test]
+    fn bmp_index() {
+        let mut map = BTreeMap::new();
+        map.insert(0x300, 230);
+        map.insert(0x301, 230);
+        map.insert(0x1E94A, 7);
 
-impl<W: io::Write> LineWriter<W> {
-    fn new(wtr: W) -> LineWriter<W> {
-        LineWriter {
-            wtr,
-            line: String::new(),
-            columns: 79,
-            indent: "  ".to_string(),
-        }
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        writer.codepoint_to_u8_bmp_index("ccc", &map).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains("pub const CCC_BMP_INDEX: [u8; 256] = ["));
+        assert!(out.contains("pub const CCC_BMP_PAGES: [[u8; 256]; 2] = ["));
+        assert!(out.contains("(125258, 125258, 7)"));
+        assert!(out.contains("pub fn ccc(cp: u32) -> u8 {"));
+
+        map.insert(0x41, 256);
+        let mut writer =
+            WriterBuilder::new("test").from_writer(Cursor::new(Vec::new()));
+        assert!(writer.codepoint_to_u8_bmp_index("ccc", &map).is_err());
     }
 
-    fn write_str(&mut self, s: &str) -> io::Result<()> {
-        if self.line.len() + s.len() > self.columns {
-            self.flush_line()?;
-        }
-        if self.line.is_empty() {
-            self.line.push_str(&self.indent);
-        }
-        self.line.push_str(s);
-        Ok(())
+    #[test]
+    fn self_check() {
+        let mut map = BTreeMap::new();
+        map.insert(0x41, vec![0x61]);
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.self_check(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.codepoint_to_codepoints("lower", &map, false).unwrap();
+        writer.finish().unwrap();
+        assert!(buf.contents().contains("\"LOWER: keys are not sorted\""));
+
+        map.insert(0xD800, vec![0x61]);
+        let mut builder = WriterBuilder::new("test");
+        builder.self_check(true).char_literals(true);
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        assert!(writer.codepoint_to_codepoints("lower", &map, false).is_err());
     }
 
-    fn indent(&mut self, s: &str) {
-        self.indent = s.to_string();
+    #[test]
+    fn fnv() {
+        assert_eq!(fnv1a64(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a64(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a64(b"foobar"), 0x85944171f73967e8);
     }
 
-    fn flush_line(&mut self) -> io::Result<()> {
-        if self.line.is_empty() {
-            return Ok(());
-        }
-        self.wtr.write_all(self.line.trim_end().as_bytes())?;
-        self.wtr.write_all(b"\n")?;
-        self.line.clear();
-        Ok(())
+    #[test]
+    fn sha256_hash() {
+        let hex = |bytes: &[u8]| -> String {
+            sha256(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+        };
+        assert_eq!(
+            hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&[b'a'; 64]),
+            "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb"
+        );
     }
-}
 
-impl<W: io::Write> io::Write for LineWriter<W> {
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.flush_line()?;
-        self.wtr.write(buf)
+    #[test]
+    fn manifest() {
+        let dir = TempDir::new(temp_path("ucd-generate-manifest")).unwrap();
+        std::fs::write(
+            dir.0.join("manifest.json"),
+            "{\n  \"files\": [\n    {\"name\": \"other.rs\", \"size\": 0},\n    \
+             {\"name\": \"test.rs\", \"size\": 0}\n  ]\n}\n",
+        )
+        .unwrap();
+
+        let mut builder = WriterBuilder::new("test");
+        builder.manifest(true);
+        let mut writer = builder.from_out_dir(&dir.0).unwrap();
+        writer.ranges("foo", &BTreeSet::from([1])).unwrap();
+        writer.finish().unwrap();
+        let contents = std::fs::read(dir.0.join("test.rs")).unwrap();
+        let manifest =
+            std::fs::read_to_string(dir.0.join("manifest.json")).unwrap();
+        let lines: Vec<&str> = manifest.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[2], "    {\"name\": \"other.rs\", \"size\": 0},");
+        assert!(lines[3].starts_with(&format!(
+            "    {{\"name\": \"test.rs\", \"size\": {}, \"sha256\": ",
+            contents.len()
+        )));
+        // Nothing but the generated files should be left behind.
+        assert_eq!(std::fs::read_dir(&dir.0).unwrap().count(), 2);
     }
 
-    fn flush(&mut self) -> io::Result<()> {
-        self.flush_line()?;
-        self.wtr.flush()
+    #[test]
+    fn check() {
+        let dir = TempDir::new(temp_path("ucd-generate-check")).unwrap();
+        let write = |check: bool, cp: u32| {
+            let mut builder = WriterBuilder::new("test");
+            builder.check(check);
+            let mut writer = builder.from_out_dir(&dir.0).unwrap();
+            writer.ranges("foo", &BTreeSet::from([cp])).unwrap();
+            writer.finish()
+        };
+
+        write(false, 1).unwrap();
+        let contents = std::fs::read(dir.0.join("test.rs")).unwrap();
+        write(true, 1).unwrap();
+        assert!(write(true, 2).is_err());
+        // Checking never writes anything.
+        assert_eq!(std::fs::read(dir.0.join("test.rs")).unwrap(), contents);
+        assert_eq!(std::fs::read_dir(&dir.0).unwrap().count(), 1);
     }
-}
 
-/// Heuristically produce an appropriate constant Rust name.
-fn rust_const_name(s: &str) -> String {
-    // Property names/values seem pretty uniform, particularly the
-    // "canonical" variants we use to produce variable names. So we
-    // don't need to do much.
-    //
-    // N.B. Age names have a `.` in them, so get rid of that.
-    let mut s = s.replace('.', "_").to_string();
-    s.make_ascii_uppercase();
-    s
-}
+    #[test]
+    fn config_header() {
+        let config = Config {
+            args: vec![
+                "general-category".to_string(),
+                "{ucd-dir}".to_string(),
+                "--name".to_string(),
+                "a \"quoted\" name\t".to_string(),
+            ],
+            output: Output::OutDir,
+            version: "1.2.3".to_string(),
+            unicode_version: Some((15, 1, 0)),
+        };
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.config(Some(config.clone()));
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges("TEST", &[1].iter().copied().collect()).unwrap();
+        writer.finish().unwrap();
 
-/// Heuristically produce an appropriate Rust type name.
-fn rust_type_name(s: &str) -> String {
-    // If it's all uppercase or digits then leave as is
-    if s.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
-        return s.to_string();
+        let out = buf.contents();
+        let line = out
+            .lines()
+            .find(|line| line.starts_with("// ucd-generate-config: "))
+            .unwrap();
+        assert_eq!(Config::from_comment(line), Some(config));
+        assert_eq!(Config::from_comment("// ucd-generate-config: {}"), None);
     }
 
-    // Convert to PascalCase
-    s.split(|c: char| c.is_whitespace() || c == '.' || c == '_' || c == '-')
-        .map(|component| {
-            // Upper first char
-            let lower = component.to_ascii_lowercase();
-            let mut chars = lower.chars();
-            match chars.next() {
-                None => String::new(),
-                Some(f) => {
-                    f.to_uppercase().collect::<String>() + chars.as_str()
-                }
-            }
+    #[test]
+    fn binary() {
+        let dir = TempDir::new(temp_path("ucd-generate-binary")).unwrap();
+        let mut builder = WriterBuilder::new("test");
+        builder.binary(true).quiet(true);
+        let mut writer = builder.from_out_dir(&dir.0).unwrap();
+        writer.ranges("foo", &BTreeSet::from([0x41, 0x42, 0x1F600])).unwrap();
+        writer
+            .codepoint_to_codepoint("bar", &BTreeMap::from([(0x41, 0x61)]))
+            .unwrap();
+        writer.finish().unwrap();
+
+        let bytes = std::fs::read(dir.0.join("foo.bin")).unwrap();
+        assert_eq!(
+            bytes,
+            [
+                0x41, 0, 0, 0, 0x42, 0, 0, 0, 0x00, 0xF6, 0x01, 0, 0x00, 0xF6,
+                0x01, 0,
+            ]
+        );
+        let bytes = std::fs::read(dir.0.join("bar.bin")).unwrap();
+        assert_eq!(bytes, [0x41, 0, 0, 0, 0x61, 0, 0, 0]);
+        let rust = std::fs::read_to_string(dir.0.join("test.rs")).unwrap();
+        assert_eq!(rust.matches("pub struct Range {").count(), 1);
+        assert!(rust.contains("pub fn foo() -> &'static [Range] {"));
+        assert!(rust.contains("pub fn bar() -> &'static [Mapping] {"));
+    }
+
+    #[test]
+    fn collation() {
+        let entries: Vec<AllKeysEntry> = [
+            "0020 ; [*0209.0020.0002]",
+            "0041 ; [.1FA1.0020.0008]",
+            "00C5 ; [.1FA1.0020.0008][.0000.0029.0002]",
+            "212B ; [.1FA1.0020.0008][.0000.0029.0002]",
+            "0627 0653 ; [.2361.0020.0002]",
+        ]
+        .iter()
+        .map(|line| match line.parse().unwrap() {
+            AllKeysLine::Entry(entry) => entry,
+            row => panic!("expected an entry, got {:?}", row),
         })
-        .collect()
-}
+        .collect();
 
-/// Heuristically produce an appropriate module Rust name.
-fn rust_module_name(s: &str) -> String {
-    // Property names/values seem pretty uniform, particularly the
-    // "canonical" variants we use to produce variable names. So we
-    // don't need to do much.
-    let mut s = s.to_string();
-    s.make_ascii_lowercase();
-    s
-}
+        let buf = SharedBuf::default();
+        let mut writer = WriterBuilder::new("test").from_writer(buf.clone());
+        writer.collation("ducet", &[], &entries).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains(
+            "pub const DUCET: &'static [(u32, u32, u32)] = &[\n  \
+             (32, 0, 1), (65, 1, 1), (197, 2, 2), (8491, 2, 2),\n];"
+        ));
+        assert!(out.contains("  (&[1575, 1619], 4, 1),\n"));
+        assert!(out.contains(
+            "  0x02091005, 0x1FA11010, 0x1FA11010, 0x00001484, 0x23611004,\n"
+        ));
 
-fn rust_fn_name(s: &str) -> String {
-    // Convert to snake_case
-    s.to_ascii_lowercase()
-        .chars()
-        .map(
-            |c| {
-                if c.is_whitespace() || c == '.' || c == '-' {
-                    '_'
-                } else {
-                    c
-                }
-            },
-        )
-        .collect()
-}
+        let mut writer =
+            WriterBuilder::new("test").from_writer(Cursor::new(Vec::new()));
+        let mut entry = entries[1].clone();
+        entry.elements[0].tertiary = 0x40;
+        assert!(writer.collation("ducet", &[], &[entry]).is_err());
+    }
 
-/// Return the given u32 encoded in big-endian.
-pub fn u32_key(cp: u32) -> [u8; 4] {
-    cp.to_be_bytes()
-}
+    #[test]
+    fn verify_fst() {
+        let dir = TempDir::new(temp_path("ucd-generate-verify-fst")).unwrap();
+        let mut builder = WriterBuilder::new("test");
+        builder.verify_fst(true).quiet(true);
+        let mut writer = builder.from_fst_dir(&dir.0).unwrap();
+        writer.ranges("foo", &BTreeSet::from([1, 2, 3])).unwrap();
+        writer.finish().unwrap();
 
-/// Convert the given string into a u64, where the least significant byte of
-/// the u64 is the first byte of the string.
-///
-/// If the string contains any `NUL` bytes or has more than 8 bytes, then an
-/// error is returned.
-fn pack_str(s: &str) -> Result<u64> {
-    if s.len() > 8 {
-        return err!("cannot encode string {:?} (too long)", s);
+        let path = dir.0.join("foo.fst");
+        let expected = std::fs::read(&path).unwrap();
+        assert!(super::verify_fst_file(&path, expected.clone()).is_ok());
+        let set = fst::Set::from_iter(vec![super::u32_key(1)]).unwrap();
+        std::fs::write(&path, set.as_fst().as_bytes()).unwrap();
+        assert!(super::verify_fst_file(&path, expected.clone()).is_err());
+        std::fs::write(&path, b"garbage").unwrap();
+        assert!(super::verify_fst_file(&path, expected).is_err());
     }
-    if s.contains('\x00') {
-        return err!("cannot encode string {:?} (contains NUL byte)", s);
+
+    #[test]
+    fn unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk";
+        let diff = crate::util::unified_diff(
+            "old",
+            "new",
+            old.as_bytes(),
+            new.as_bytes(),
+        );
+        assert_eq!(
+            diff,
+            "--- old\n+++ new\n\
+             @@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -8,3 +8,4 @@\n h\n i\n j\n+k\n\\ No newline at end of file\n"
+        );
+        let diff = crate::util::unified_diff("old", "new", b"a\n", b"\xFF");
+        assert_eq!(diff, "Binary files old and new differ\n");
     }
-    let mut value = 0;
-    for (i, &b) in s.as_bytes().iter().enumerate() {
-        assert!(i <= 7);
-        value |= (b as u64) << (8 * i as u64);
+
+    #[test]
+    fn unchanged_files_are_not_rewritten() {
+        let dir = TempDir::new(temp_path("ucd-generate-test")).unwrap();
+        let path = dir.0.join("unchanged.rs");
+
+        assert!(write_if_changed(&path, b"foo", false).unwrap());
+        assert!(!write_if_changed(&path, b"foo", false).unwrap());
+        assert!(write_if_changed(&path, b"foo", true).unwrap());
+        assert!(write_if_changed(&path, b"bar", false).unwrap());
+        assert_eq!(std::fs::read(&path).unwrap(), b"bar");
     }
-    Ok(value)
-}
 
-/// Return a string representing the smallest unsigned integer type for the
-/// given value.
-fn smallest_unsigned_type(n: u64) -> &'static str {
-    if n <= ::std::u8::MAX as u64 {
-        "u8"
-    } else if n <= ::std::u16::MAX as u64 {
-        "u16"
-    } else if n <= ::std::u32::MAX as u64 {
-        "u32"
-    } else {
-        "u64"
+    #[test]
+    fn max_age() {
+        let max_age = MaxAge::new((6, 0), vec![(20, 29), (3, 4)]);
+        assert!(max_age.excludes(3) && max_age.excludes(29));
+        assert!(!max_age.excludes(2) && !max_age.excludes(30));
+        assert_eq!(max_age.clip(0, 40), vec![(0, 2), (5, 19), (30, 40)]);
+        assert_eq!(max_age.clip(21, 25), vec![]);
+        assert_eq!(max_age.clip(4, 21), vec![(5, 19)]);
+
+        let set: BTreeSet<u32> =
+            [1, 2, 3, 9, 25, 30].iter().copied().collect();
+        let mut names = BTreeMap::new();
+        names.insert("OLD".to_string(), 2);
+        names.insert("NEW".to_string(), 21);
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.max_age(Some(max_age));
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges("set", &set).unwrap();
+        writer.string_to_codepoint("names", &names).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(out.contains("introduced after Unicode 6.0 are left out"));
+        assert!(out.contains("(1, 2), (9, 9), (30, 30),\n];"));
+        assert!(out.contains("(\"OLD\", 2),\n];"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::WriterBuilder;
-    use super::{pack_str, rust_type_name};
-    use crate::error::Error;
-    use std::io::Cursor;
+    #[test]
+    fn u16_ranges() {
+        let bmp: BTreeSet<u32> = [0x41, 0x42, 0xFFFF].into();
+        let smp: BTreeSet<u32> = [0x41, 0x10000].into();
 
-    fn unpack_str(mut encoded: u64) -> String {
-        let mut value = String::new();
-        while encoded != 0 {
-            value.push((encoded & 0xFF) as u8 as char);
-            encoded = encoded >> 8;
-        }
-        value
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.u16_ranges(true).emit_iter(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges("bmp", &bmp).unwrap();
+        writer.ranges("smp", &smp).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains(
+            "pub const BMP: &'static [(u16, u16)] = &[\n  (65, 66), (65535, \
+             65535),\n];"
+        ));
+        assert!(out.contains("pub const SMP: &'static [(u32, u32)] = &["));
+        assert!(out.contains(
+            "BMP.iter().map(|&(s, e)| (u32::from(s), u32::from(e)))"
+        ));
+
+        // The sets in BY_NAME share a type, so they're only written as u16
+        // pairs if all of them fit.
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.u16_ranges(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.names(["bmp", "smp"]).unwrap();
+        writer.ranges("bmp", &bmp).unwrap();
+        writer.ranges("smp", &smp).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains("pub const BMP: &'static [(u32, u32)] = &["));
+        assert!(out.contains(
+            "pub const BY_NAME: &'static [(&'static str, &'static [(u32, \
+             u32)])]"
+        ));
+
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.u16_ranges(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.names(["bmp"]).unwrap();
+        writer.ranges("bmp", &bmp).unwrap();
+        writer.finish().unwrap();
+        let out = buf.contents();
+        assert!(out.contains(
+            "pub const BY_NAME: &'static [(&'static str, &'static [(u16, \
+             u16)])]"
+        ));
     }
 
     #[test]
-    fn packed() {
-        assert_eq!("G", unpack_str(pack_str("G").unwrap()));
-        assert_eq!("GG", unpack_str(pack_str("GG").unwrap()));
-        assert_eq!("YEO", unpack_str(pack_str("YEO").unwrap()));
-        assert_eq!("ABCDEFGH", unpack_str(pack_str("ABCDEFGH").unwrap()));
-        assert_eq!("", unpack_str(pack_str("").unwrap()));
+    fn validate_utf8_tables() {
+        let mut enum_map = BTreeMap::new();
+        enum_map.insert("A".to_string(), BTreeSet::from([0x41, 0xE9]));
+        enum_map.insert("B".to_string(), BTreeSet::from([0x1F600]));
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder.validate_utf8_tables(true);
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges_to_utf8_trie("test", &enum_map).unwrap();
+        writer.finish().unwrap();
 
-        assert!(pack_str("ABCDEFGHI").is_err());
-        assert!(pack_str("AB\x00CD").is_err());
+        let out = buf.contents();
+        assert!(out.contains("#[cfg(test)]\nmod test_tests {"));
+        assert!(out.contains("(b\"\", 2, 0),"));
+        assert!(out.contains("(b\"A\", 0, 1),"));
+        assert!(out.contains("(b\"\\xc3\\xa9\", 0, 2),"));
     }
 
     #[test]
-    fn test_rust_type_name() {
-        assert_eq!(&rust_type_name("simple"), "Simple");
-        assert_eq!(&rust_type_name("SCRIPT"), "SCRIPT");
-        assert_eq!(&rust_type_name("dot.separated"), "DotSeparated");
-        assert_eq!(&rust_type_name("dash-separated"), "DashSeparated");
-        assert_eq!(&rust_type_name("white \tspace"), "WhiteSpace");
-        assert_eq!(&rust_type_name("snake_case"), "SnakeCase");
+    fn only() {
+        let kept: BTreeSet<u32> = [1, 2, 3, 9, 20, 21].into();
+        let only = Where::new("Foo=Bar", &kept);
+        assert!(!only.excludes(1) && !only.excludes(21));
+        assert!(only.excludes(0) && only.excludes(4) && only.excludes(22));
+        assert_eq!(only.clip(0, 40), vec![(1, 3), (9, 9), (20, 21)]);
+        assert_eq!(only.clip(2, 20), vec![(2, 3), (9, 9), (20, 20)]);
+        assert_eq!(only.clip(4, 8), vec![]);
+
+        let set: BTreeSet<u32> = [2, 3, 4, 9, 30].into();
+        let mut pairs = BTreeSet::new();
+        pairs.insert((1, 2));
+        pairs.insert((3, 4));
+        let buf = SharedBuf::default();
+        let mut builder = WriterBuilder::new("test");
+        builder
+            .max_age(Some(MaxAge::new((6, 0), vec![(3, 3)])))
+            .only(Some(only));
+        let mut writer = builder.from_writer(buf.clone());
+        writer.ranges("set", &set).unwrap();
+        writer.codepoint_pairs("pairs", &pairs).unwrap();
+        writer.finish().unwrap();
+
+        let out = buf.contents();
+        assert!(out.contains("// Only codepoints with Foo=Bar are included."));
+        assert!(out.contains("(2, 2), (9, 9),\n];"));
+        assert!(out.contains("(1, 2),\n];"));
     }
 
     #[test]
-    fn codepoint_to_codepoint_fn_error() {
-        let cursor = Cursor::new(Vec::new());
-        let builder = WriterBuilder::new("test");
-        let mut writer = builder.from_writer(cursor);
+    fn intern_strings() {
+        let dir = TempDir::new(temp_path("ucd-generate-intern-test")).unwrap();
+        let enum_map = |names: &[&str]| -> BTreeMap<String, BTreeSet<u32>> {
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, &n)| (n.to_string(), [i as u32].into()))
+                .collect()
+        };
+        let mut builder = WriterBuilder::new("first");
+        builder.intern_strings(true);
+        let mut writer = builder.from_out_dir(&dir.0).unwrap();
+        writer
+            .ranges_to_enum("FIRST", &enum_map(&["Extend", "Other"]))
+            .unwrap();
+        writer.finish().unwrap();
+        let mut builder = WriterBuilder::new("second");
+        builder.intern_strings(true);
+        let mut writer = builder.from_out_dir(&dir.0).unwrap();
+        writer.ranges_to_enum("SECOND", &enum_map(&["Ext", "ZWJ"])).unwrap();
+        writer.finish().unwrap();
 
-        // Ensure that a destination codepoint of zero is rejected
-        let map = [(1, 0)].iter().copied().collect();
-        match writer.codepoint_to_codepoint_fn("err", &map) {
-            Err(Error::Other(msg)) => {
-                assert!(msg.contains("destination codepoint must not be 0"))
-            }
-            res => panic!(
-                "expected error matching, \
-                 'destination codepoint must not be 0' \
-                 got: {:?}",
-                res
-            ),
-        }
+        let first = std::fs::read_to_string(dir.0.join("first.rs")).unwrap();
+        assert!(first.contains(
+            "pub const FIRST_ENUM: &'static [(u16, u16)] = &[\n  \
+             (0, 6), (6, 5),\n];"
+        ));
+        let second = std::fs::read_to_string(dir.0.join("second.rs")).unwrap();
+        assert!(second.contains(
+            "pub const SECOND_ENUM: &'static [(u16, u16)] = &[\n  \
+             (0, 3), (11, 3),\n];"
+        ));
+        let pool = std::fs::read_to_string(dir.0.join("strings.rs")).unwrap();
+        assert!(pool.contains("concat!(\n  \"ExtendOtherZWJ\",\n);"));
+
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        assert!(writer.ranges_to_enum("X", &enum_map(&["X"])).is_err());
+    }
+
+    #[test]
+    fn split_data() {
+        let dir = TempDir::new(temp_path("ucd-generate-split-test")).unwrap();
+        let mut builder = WriterBuilder::new("test");
+        builder.split_data(Some(2));
+        let mut writer = builder.from_out_dir(&dir.0).unwrap();
+        let big: BTreeSet<u32> = [1, 3, 5].iter().cloned().collect();
+        let small: BTreeSet<u32> = [1].iter().cloned().collect();
+        writer.ranges("big", &big).unwrap();
+        writer.ranges("small", &small).unwrap();
+        writer.finish().unwrap();
+
+        let module = std::fs::read_to_string(dir.0.join("test.rs")).unwrap();
+        assert!(module.contains(
+            "pub const BIG: &'static [(u32, u32)] =\n  \
+             &include!(\"big.data.rs\");"
+        ));
+        assert!(module.contains(
+            "pub const SMALL: &'static [(u32, u32)] = &[\n  (1, 1),\n];"
+        ));
+        let data = std::fs::read_to_string(dir.0.join("big.data.rs")).unwrap();
+        assert!(data.ends_with("\n[\n  (1, 1), (3, 3), (5, 5),\n]\n"));
+        assert!(!dir.0.join("small.data.rs").exists());
+
+        let mut writer = builder.from_writer(Cursor::new(Vec::new()));
+        assert!(writer.ranges("big", &big).is_err());
     }
 }
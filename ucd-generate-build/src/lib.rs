@@ -0,0 +1,354 @@
+/*!
+The `ucd-generate-build` crate makes it easy to run `ucd-generate` from a Cargo
+build script.
+
+A [`UcdBuild`] describes a list of tables to generate from a single UCD
+directory. Each table is a `ucd-generate` subcommand along with its flags.
+Running the build invokes `ucd-generate` once per table, writes each table's
+Rust module into `OUT_DIR` (or another directory, see [`UcdBuild::out_dir`])
+and tells Cargo to only rerun the build script when the UCD directory
+changes.
+
+The module written by each subcommand is named after the subcommand. For
+example, `general-category` writes `general_category.rs`, which can be used
+like so:
+
+```rust,ignore
+mod general_category {
+    include!(concat!(env!("OUT_DIR"), "/general_category.rs"));
+}
+```
+
+# Example
+
+This generates a trie for every general category and a slice of ranges for
+the Latin and Greek scripts:
+
+```no_run
+ucd_generate_build::UcdBuild::new("/tmp/ucd-15.0.0")
+    .general_category()
+    .trie_set()
+    .script()
+    .include("Latin,Greek")
+    .run()
+    .unwrap();
+```
+
+# Finding `ucd-generate`
+
+This crate doesn't contain the table generation logic itself. Instead, it runs
+the `ucd-generate` program, which must be installed (e.g., with
+`cargo install ucd-generate`). If the `UCD_GENERATE` environment variable is
+set, then it is used as the path to the program. Otherwise, `ucd-generate` is
+looked up in `PATH`. [`UcdBuild::program`] overrides both.
+*/
+
+#![deny(missing_docs)]
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The environment variable that overrides which `ucd-generate` to run.
+const PROGRAM_ENV: &str = "UCD_GENERATE";
+
+/// A builder for generating one or more tables from a UCD directory.
+///
+/// Methods that configure a table, such as [`UcdBuild::trie_set`] or
+/// [`UcdBuild::include`], apply to the table most recently added with
+/// [`UcdBuild::table`] (or one of its shorthands, such as
+/// [`UcdBuild::general_category`]). Calling them before adding any table
+/// panics.
+#[derive(Clone, Debug)]
+pub struct UcdBuild {
+    ucd_dir: PathBuf,
+    out_dir: Option<PathBuf>,
+    program: Option<OsString>,
+    rerun_if_changed: bool,
+    tables: Vec<Table>,
+}
+
+/// A single invocation of a `ucd-generate` subcommand.
+#[derive(Clone, Debug)]
+struct Table {
+    command: String,
+    args: Vec<OsString>,
+}
+
+impl UcdBuild {
+    /// Create a new build that reads from the given UCD directory.
+    ///
+    /// The directory should contain the extracted contents of a UCD zip
+    /// file, e.g., `UnicodeData.txt`, `PropList.txt` and so on.
+    pub fn new<P: AsRef<Path>>(ucd_dir: P) -> UcdBuild {
+        UcdBuild {
+            ucd_dir: ucd_dir.as_ref().to_path_buf(),
+            out_dir: None,
+            program: None,
+            rerun_if_changed: true,
+            tables: vec![],
+        }
+    }
+
+    /// Set the directory that tables are written to.
+    ///
+    /// By default, this is the `OUT_DIR` that Cargo gives to build scripts.
+    pub fn out_dir<P: AsRef<Path>>(&mut self, out_dir: P) -> &mut UcdBuild {
+        self.out_dir = Some(out_dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the `ucd-generate` program to run.
+    ///
+    /// By default, this is the value of the `UCD_GENERATE` environment
+    /// variable, or `ucd-generate` if it isn't set.
+    pub fn program<P: AsRef<OsStr>>(&mut self, program: P) -> &mut UcdBuild {
+        self.program = Some(program.as_ref().to_os_string());
+        self
+    }
+
+    /// Whether to print `cargo:rerun-if-changed` directives for the UCD
+    /// directory (and `cargo:rerun-if-env-changed` for `UCD_GENERATE`).
+    ///
+    /// This is enabled by default. Disable it when the build script has
+    /// directives of its own, since Cargo only reruns a build script for the
+    /// directives it prints.
+    pub fn rerun_if_changed(&mut self, yes: bool) -> &mut UcdBuild {
+        self.rerun_if_changed = yes;
+        self
+    }
+
+    /// Add a table generated by the given `ucd-generate` subcommand, such as
+    /// `general-category` or `case-folding-simple`.
+    pub fn table(&mut self, command: &str) -> &mut UcdBuild {
+        self.tables.push(Table { command: command.to_string(), args: vec![] });
+        self
+    }
+
+    /// Add a table generated by `ucd-generate general-category`.
+    pub fn general_category(&mut self) -> &mut UcdBuild {
+        self.table("general-category")
+    }
+
+    /// Add a table generated by `ucd-generate script`.
+    pub fn script(&mut self) -> &mut UcdBuild {
+        self.table("script")
+    }
+
+    /// Add a table generated by `ucd-generate script-extension`.
+    pub fn script_extension(&mut self) -> &mut UcdBuild {
+        self.table("script-extension")
+    }
+
+    /// Add a table generated by `ucd-generate property-bool`.
+    pub fn property_bool(&mut self) -> &mut UcdBuild {
+        self.table("property-bool")
+    }
+
+    /// Add a table generated by `ucd-generate case-folding-simple`.
+    pub fn case_folding_simple(&mut self) -> &mut UcdBuild {
+        self.table("case-folding-simple")
+    }
+
+    /// Pass an additional argument to the most recently added table's
+    /// subcommand.
+    pub fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut UcdBuild {
+        self.last_table().args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    /// Write the most recently added table as a trie (`--trie-set`).
+    pub fn trie_set(&mut self) -> &mut UcdBuild {
+        self.arg("--trie-set")
+    }
+
+    /// Write the most recently added table with `char` literals instead of
+    /// `u32` values (`--chars`).
+    pub fn chars(&mut self) -> &mut UcdBuild {
+        self.arg("--chars")
+    }
+
+    /// Only include the given comma separated values in the most recently
+    /// added table (`--include`).
+    pub fn include(&mut self, values: &str) -> &mut UcdBuild {
+        self.arg("--include").arg(values)
+    }
+
+    /// Exclude the given comma separated values from the most recently
+    /// added table (`--exclude`).
+    pub fn exclude(&mut self, values: &str) -> &mut UcdBuild {
+        self.arg("--exclude").arg(values)
+    }
+
+    /// Set the name of the most recently added table (`--name`).
+    pub fn name(&mut self, name: &str) -> &mut UcdBuild {
+        self.arg("--name").arg(name)
+    }
+
+    /// Generate every table.
+    ///
+    /// This returns an error if the output directory can't be determined,
+    /// if `ucd-generate` can't be run or if it fails.
+    pub fn run(&self) -> Result<(), Error> {
+        let out_dir = match self.out_dir {
+            Some(ref out_dir) => out_dir.clone(),
+            None => match env::var_os("OUT_DIR") {
+                Some(out_dir) => PathBuf::from(out_dir),
+                None => return Err(Error::NoOutDir),
+            },
+        };
+        if self.rerun_if_changed {
+            println!("cargo:rerun-if-changed={}", self.ucd_dir.display());
+            println!("cargo:rerun-if-env-changed={}", PROGRAM_ENV);
+        }
+        let program = self.program_or_default();
+        for args in self.command_lines(&out_dir) {
+            let output = Command::new(&program)
+                .args(&args)
+                .output()
+                .map_err(|err| Error::Spawn(program.clone(), err))?;
+            if !output.status.success() {
+                return Err(Error::Failed {
+                    args,
+                    stderr: String::from_utf8_lossy(&output.stderr)
+                        .into_owned(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the program that `run` executes.
+    fn program_or_default(&self) -> OsString {
+        match self.program {
+            Some(ref program) => program.clone(),
+            None => env::var_os(PROGRAM_ENV)
+                .unwrap_or_else(|| OsString::from("ucd-generate")),
+        }
+    }
+
+    /// Return the arguments for each invocation of `ucd-generate`.
+    fn command_lines(&self, out_dir: &Path) -> Vec<Vec<OsString>> {
+        let mut lines = vec![];
+        for table in &self.tables {
+            let mut args = vec![
+                OsString::from(&table.command),
+                self.ucd_dir.clone().into_os_string(),
+            ];
+            args.extend(table.args.iter().cloned());
+            args.push(OsString::from("--out-dir"));
+            args.push(out_dir.to_path_buf().into_os_string());
+            lines.push(args);
+        }
+        lines
+    }
+
+    fn last_table(&mut self) -> &mut Table {
+        self.tables
+            .last_mut()
+            .expect("a table must be added before it can be configured")
+    }
+}
+
+/// An error that occurred while generating tables.
+#[derive(Debug)]
+pub enum Error {
+    /// No output directory was given and `OUT_DIR` isn't set, which usually
+    /// means the build isn't running from a build script.
+    NoOutDir,
+    /// The `ucd-generate` program could not be run.
+    Spawn(OsString, io::Error),
+    /// `ucd-generate` ran, but exited with an error.
+    Failed {
+        /// The arguments given to `ucd-generate`.
+        args: Vec<OsString>,
+        /// What `ucd-generate` wrote to stderr.
+        stderr: String,
+    },
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Error::NoOutDir => write!(
+                f,
+                "no output directory was given and OUT_DIR is not set"
+            ),
+            Error::Spawn(ref program, ref err) => write!(
+                f,
+                "failed to run {}: {} (is ucd-generate installed? set {} \
+                 to use a specific binary)",
+                Path::new(program).display(),
+                err,
+                PROGRAM_ENV,
+            ),
+            Error::Failed { ref args, ref stderr } => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|arg| arg.to_string_lossy().into_owned())
+                    .collect();
+                write!(
+                    f,
+                    "ucd-generate {} failed: {}",
+                    args.join(" "),
+                    stderr.trim_end()
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+    use std::path::Path;
+
+    use super::UcdBuild;
+
+    fn strings(lines: Vec<Vec<OsString>>) -> Vec<Vec<String>> {
+        lines
+            .into_iter()
+            .map(|args| {
+                args.into_iter().map(|a| a.into_string().unwrap()).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn command_lines() {
+        let mut build = UcdBuild::new("/ucd");
+        build.general_category().trie_set().script().include("Latin,Greek");
+        let got = strings(build.command_lines(Path::new("/out")));
+        assert_eq!(
+            got,
+            vec![
+                vec![
+                    "general-category",
+                    "/ucd",
+                    "--trie-set",
+                    "--out-dir",
+                    "/out"
+                ],
+                vec![
+                    "script",
+                    "/ucd",
+                    "--include",
+                    "Latin,Greek",
+                    "--out-dir",
+                    "/out"
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn configure_without_table() {
+        UcdBuild::new("/ucd").trie_set();
+    }
+}
@@ -38,6 +38,12 @@ impl std::str::FromStr for Age {
     }
 }
 
+impl std::fmt::Display for Age {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.age)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Age;
@@ -0,0 +1,287 @@
+use std::path::Path;
+
+use crate::{
+    common::{parse_codepoint_sequence, Codepoint, CodepointRange, UcdFile},
+    error::Error,
+};
+
+/// A single line in the `allkeys.txt` file, which is the Default Unicode
+/// Collation Element Table (DUCET) of UTS #10.
+///
+/// Note that `allkeys.txt` is not part of the Unicode Character Database.
+/// It is published with the Unicode Collation Algorithm, and can be
+/// downloaded here: https://www.unicode.org/Public/UCA/
+///
+/// Only the format used since UCA 9.0.0 is supported, in which every
+/// collation element has three weights.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AllKeysLine {
+    /// A `@version` line, giving the version of the table.
+    Version(String),
+    /// An `@implicitweights` line.
+    ImplicitWeights(ImplicitWeights),
+    /// A mapping from a sequence of codepoints to its collation elements.
+    Entry(AllKeysEntry),
+}
+
+impl Default for AllKeysLine {
+    fn default() -> AllKeysLine {
+        AllKeysLine::Entry(AllKeysEntry::default())
+    }
+}
+
+/// A range of codepoints whose implicit weights are computed from a base
+/// other than the ones for unified ideographs, e.g., Tangut.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ImplicitWeights {
+    /// The codepoints that this applies to.
+    pub codepoints: CodepointRange,
+    /// The base of the first primary weight, e.g., `FB00`.
+    pub base: u16,
+}
+
+/// A sequence of codepoints and the collation elements that it maps to.
+///
+/// When the sequence has more than one codepoint, it is a contraction.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AllKeysEntry {
+    /// The codepoints in this entry.
+    pub codepoints: Vec<Codepoint>,
+    /// The collation elements of this entry. There is always at least one.
+    pub elements: Vec<CollationElement>,
+}
+
+/// A single collation element, e.g., `[.1C47.0020.0008]`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CollationElement {
+    /// Whether this element is variable, which is written with a `*`
+    /// instead of a `.`.
+    pub variable: bool,
+    /// The primary weight.
+    pub primary: u16,
+    /// The secondary weight.
+    pub secondary: u16,
+    /// The tertiary weight.
+    pub tertiary: u16,
+}
+
+impl UcdFile for AllKeysLine {
+    fn relative_file_path() -> &'static Path {
+        Path::new("allkeys.txt")
+    }
+}
+
+impl std::str::FromStr for AllKeysLine {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<AllKeysLine, Error> {
+        let line = match line.find('#') {
+            Some(i) => line[..i].trim(),
+            None => line.trim(),
+        };
+        if let Some(version) = line.strip_prefix("@version") {
+            return Ok(AllKeysLine::Version(version.trim().to_string()));
+        }
+        if let Some(implicit) = line.strip_prefix("@implicitweights") {
+            return match implicit.split_once(';') {
+                Some((range, base)) => {
+                    Ok(AllKeysLine::ImplicitWeights(ImplicitWeights {
+                        codepoints: range.trim().parse()?,
+                        base: parse_weight(base.trim())?,
+                    }))
+                }
+                None => err!("invalid @implicitweights line: '{}'", line),
+            };
+        }
+        if line.starts_with('@') {
+            return err!("unrecognized allkeys directive: '{}'", line);
+        }
+
+        let (codepoints, elements) = match line.split_once(';') {
+            Some((cps, elements)) if !cps.trim().is_empty() => {
+                (parse_codepoint_sequence(cps)?, elements.trim())
+            }
+            _ => return err!("invalid allkeys line: '{}'", line),
+        };
+        let mut parsed = vec![];
+        let mut rest = elements;
+        while !rest.is_empty() {
+            let end = match rest.find(']') {
+                Some(end) if rest.starts_with('[') => end,
+                _ => {
+                    return err!("invalid collation elements: '{}'", elements)
+                }
+            };
+            parsed.push(rest[..end + 1].parse()?);
+            rest = rest[end + 1..].trim_start();
+        }
+        if parsed.is_empty() {
+            return err!("missing collation elements in line: '{}'", line);
+        }
+        Ok(AllKeysLine::Entry(AllKeysEntry { codepoints, elements: parsed }))
+    }
+}
+
+impl std::str::FromStr for CollationElement {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<CollationElement, Error> {
+        let re_parts = regex!(
+            r"(?x)
+                ^
+                \[
+                (?P<variable>[.*])
+                (?P<primary>[0-9A-F]{4})
+                \.
+                (?P<secondary>[0-9A-F]{4})
+                \.
+                (?P<tertiary>[0-9A-F]{4})
+                \]
+                $
+                ",
+        );
+        let caps = match re_parts.captures(s) {
+            Some(caps) => caps,
+            None => return err!("invalid collation element: '{}'", s),
+        };
+        Ok(CollationElement {
+            variable: &caps["variable"] == "*",
+            primary: parse_weight(&caps["primary"])?,
+            secondary: parse_weight(&caps["secondary"])?,
+            tertiary: parse_weight(&caps["tertiary"])?,
+        })
+    }
+}
+
+impl std::fmt::Display for AllKeysLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            AllKeysLine::Version(ref version) => {
+                write!(f, "@version {}", version)
+            }
+            AllKeysLine::ImplicitWeights(ref implicit) => write!(
+                f,
+                "@implicitweights {}; {:04X}",
+                implicit.codepoints, implicit.base
+            ),
+            AllKeysLine::Entry(ref entry) => {
+                let cps: Vec<String> =
+                    entry.codepoints.iter().map(|cp| cp.to_string()).collect();
+                write!(f, "{} ; ", cps.join(" "))?;
+                for element in &entry.elements {
+                    write!(f, "{}", element)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for CollationElement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}{:04X}.{:04X}.{:04X}]",
+            if self.variable { '*' } else { '.' },
+            self.primary,
+            self.secondary,
+            self.tertiary
+        )
+    }
+}
+
+/// Parse a weight written as 4 hexadecimal digits.
+fn parse_weight(s: &str) -> Result<u16, Error> {
+    match u16::from_str_radix(s, 16) {
+        Ok(weight) => Ok(weight),
+        Err(err) => err!("failed to parse weight '{}': {}", s, err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AllKeysLine, CollationElement};
+
+    #[test]
+    fn parse_entry() {
+        let line = "00C5  ; [.1FA1.0020.0008][.0000.0029.0002] \
+                    # LATIN CAPITAL LETTER A WITH RING ABOVE\n";
+        let entry = match line.parse().unwrap() {
+            AllKeysLine::Entry(entry) => entry,
+            row => panic!("expected an entry, got {:?}", row),
+        };
+        assert_eq!(entry.codepoints, vec![0x00C5]);
+        assert_eq!(
+            entry.elements,
+            vec![
+                CollationElement {
+                    variable: false,
+                    primary: 0x1FA1,
+                    secondary: 0x0020,
+                    tertiary: 0x0008,
+                },
+                CollationElement {
+                    variable: false,
+                    primary: 0x0000,
+                    secondary: 0x0029,
+                    tertiary: 0x0002,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_contraction() {
+        let line = "0627 0653 ; [.2361.0020.0002] # ARABIC LETTER ALEF...\n";
+        let entry = match line.parse().unwrap() {
+            AllKeysLine::Entry(entry) => entry,
+            row => panic!("expected an entry, got {:?}", row),
+        };
+        assert_eq!(entry.codepoints, vec![0x0627, 0x0653]);
+        assert_eq!(entry.elements.len(), 1);
+    }
+
+    #[test]
+    fn parse_variable() {
+        let element: CollationElement = "[*0209.0020.0002]".parse().unwrap();
+        assert!(element.variable);
+        assert_eq!(element.primary, 0x0209);
+    }
+
+    #[test]
+    fn parse_directives() {
+        let row: AllKeysLine = "@version 14.0.0\n".parse().unwrap();
+        assert_eq!(row, AllKeysLine::Version("14.0.0".to_string()));
+
+        let line = "@implicitweights 17000..18AFF; FB00 # Tangut\n";
+        let implicit = match line.parse().unwrap() {
+            AllKeysLine::ImplicitWeights(implicit) => implicit,
+            row => panic!("expected implicit weights, got {:?}", row),
+        };
+        assert_eq!(implicit.codepoints, (0x17000, 0x18AFF));
+        assert_eq!(implicit.base, 0xFB00);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("0041 ; ".parse::<AllKeysLine>().is_err());
+        assert!("0041 ; [.1C47.0020]".parse::<AllKeysLine>().is_err());
+        assert!("0041 ; [.1C47.0020.0008.0041]"
+            .parse::<AllKeysLine>()
+            .is_err());
+        assert!("@foo".parse::<AllKeysLine>().is_err());
+    }
+
+    #[test]
+    fn display() {
+        for line in &[
+            "@version 14.0.0",
+            "@implicitweights 17000..18AFF; FB00",
+            "0041 ; [.1C47.0020.0008]",
+            "0627 0653 ; [*2361.0020.0002][.0000.0029.0002]",
+        ] {
+            let row: AllKeysLine = line.parse().unwrap();
+            assert_eq!(row.to_string(), *line);
+        }
+    }
+}
@@ -75,6 +75,20 @@ impl std::str::FromStr for JoiningType {
     }
 }
 
+impl std::fmt::Display for JoiningType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match *self {
+            JoiningType::RightJoining => "R",
+            JoiningType::LeftJoining => "L",
+            JoiningType::DualJoining => "D",
+            JoiningType::JoinCausing => "C",
+            JoiningType::NonJoining => "U",
+            JoiningType::Transparent => "T",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 impl UcdFile for ArabicShaping {
     fn relative_file_path() -> &'static Path {
         Path::new("ArabicShaping.txt")
@@ -115,6 +129,19 @@ impl std::str::FromStr for ArabicShaping {
     }
 }
 
+impl std::fmt::Display for ArabicShaping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}; {}; {}; {}",
+            self.codepoint,
+            self.schematic_name,
+            self.joining_type,
+            self.joining_group
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::common::Codepoint;
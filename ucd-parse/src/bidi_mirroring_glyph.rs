@@ -15,6 +15,9 @@ pub struct BidiMirroring {
     /// The codepoint that has typically has a glyph that is the mirror image
     /// of `codepoint`.
     pub bidi_mirroring_glyph: Codepoint,
+    /// Whether this mapping is marked as `[BEST FIT]`, i.e., the mirrored
+    /// glyph is only an approximation of the mirror image of `codepoint`.
+    pub best_fit: bool,
 }
 
 impl UcdFile for BidiMirroring {
@@ -38,8 +41,7 @@ impl std::str::FromStr for BidiMirroring {
                 ^
                 \s*(?P<codepoint>[A-F0-9]+)\s*;
                 \s*(?P<substitute_codepoint>[A-F0-9]+)
-                \s+
-                \#(?:.+)
+                (?:\s+\#\s*(?P<best_fit>\[BEST\sFIT\])?.*)?
                 $
                 ",
         );
@@ -51,14 +53,17 @@ impl std::str::FromStr for BidiMirroring {
         Ok(BidiMirroring {
             codepoint: caps["codepoint"].parse()?,
             bidi_mirroring_glyph: caps["substitute_codepoint"].parse()?,
+            best_fit: caps.name("best_fit").is_some(),
         })
     }
 }
 
 impl std::fmt::Display for BidiMirroring {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{};", self.codepoint)?;
-        write!(f, "{};", self.bidi_mirroring_glyph)?;
+        write!(f, "{}; {}", self.codepoint, self.bidi_mirroring_glyph)?;
+        if self.best_fit {
+            write!(f, " # [BEST FIT]")?;
+        }
         Ok(())
     }
 }
@@ -82,6 +87,7 @@ mod tests {
             BidiMirroring {
                 codepoint: codepoint(0x0028),
                 bidi_mirroring_glyph: codepoint(0x0029),
+                best_fit: false,
             }
         );
     }
@@ -95,7 +101,17 @@ mod tests {
             BidiMirroring {
                 codepoint: codepoint(0x228A),
                 bidi_mirroring_glyph: codepoint(0x228B),
+                best_fit: true,
             }
         );
+        assert_eq!(data.to_string(), "228A; 228B # [BEST FIT]");
+    }
+
+    #[test]
+    fn display() {
+        let line = "0028; 0029 # LEFT PARENTHESIS\n";
+        let data: BidiMirroring = line.parse().unwrap();
+        assert_eq!(data.to_string(), "0028; 0029");
+        assert_eq!(data.to_string().parse::<BidiMirroring>().unwrap(), data);
     }
 }
@@ -0,0 +1,402 @@
+use std::path::Path;
+
+use crate::{
+    common::{parse_codepoint_sequence, UcdFile},
+    error::Error,
+};
+
+/// The paragraph direction that a bidi conformance test is run with.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum BidiParagraphDirection {
+    /// The paragraph level is determined from the text (rules P2 and P3).
+    #[default]
+    Auto,
+    /// The paragraph level is 0.
+    LeftToRight,
+    /// The paragraph level is 1.
+    RightToLeft,
+}
+
+/// A single test in the `BidiTest.txt` file.
+///
+/// The file itself only lists the resolved levels and the reordering once
+/// for a whole group of tests, via `@Levels` and `@Reorder` lines. To get
+/// tests where this information is filled in, use
+/// [`parse_bidi_tests`](fn.parse_bidi_tests.html).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BidiTest {
+    /// The bidi classes of the input, in logical order.
+    pub classes: Vec<String>,
+    /// The paragraph directions that this test should be run with.
+    pub paragraph_directions: Vec<BidiParagraphDirection>,
+    /// The resolved level of each input class. `None` indicates that the
+    /// corresponding class is removed by rule X9.
+    pub levels: Vec<Option<u8>>,
+    /// The visual ordering of the input, as indices into `classes`. Classes
+    /// removed by rule X9 are omitted.
+    pub reorder: Vec<usize>,
+}
+
+/// A single line in the `BidiTest.txt` file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BidiTestLine {
+    /// An `@Levels` line, giving the resolved levels of all subsequent tests
+    /// up to the next `@Levels` line.
+    Levels(Vec<Option<u8>>),
+    /// An `@Reorder` line, giving the visual ordering of all subsequent
+    /// tests up to the next `@Reorder` line.
+    Reorder(Vec<usize>),
+    /// A test whose levels and reordering are given by the most recent
+    /// `@Levels` and `@Reorder` lines.
+    ///
+    /// The `levels` and `reorder` fields are always empty.
+    Test(BidiTest),
+}
+
+impl Default for BidiTestLine {
+    fn default() -> BidiTestLine {
+        BidiTestLine::Test(BidiTest::default())
+    }
+}
+
+impl UcdFile for BidiTestLine {
+    fn relative_file_path() -> &'static Path {
+        Path::new("BidiTest.txt")
+    }
+}
+
+impl std::str::FromStr for BidiTestLine {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<BidiTestLine, Error> {
+        let line = match line.find('#') {
+            None => line.trim(),
+            Some(i) => line[..i].trim(),
+        };
+        if let Some(levels) = line.strip_prefix("@Levels:") {
+            return Ok(BidiTestLine::Levels(parse_levels(levels)?));
+        }
+        if let Some(reorder) = line.strip_prefix("@Reorder:") {
+            return Ok(BidiTestLine::Reorder(parse_reorder(reorder)?));
+        }
+        if line.starts_with('@') {
+            return err!("unrecognized BidiTest directive: '{}'", line);
+        }
+
+        let (classes, bitset) = match line.split_once(';') {
+            Some(fields) => fields,
+            None => return err!("invalid BidiTest line: '{}'", line),
+        };
+        let bitset: u8 = match bitset.trim().parse() {
+            Ok(bitset) => bitset,
+            Err(err) => {
+                return err!(
+                    "failed to parse paragraph levels '{}': {}",
+                    bitset.trim(),
+                    err
+                )
+            }
+        };
+        let mut paragraph_directions = vec![];
+        for &(bit, dir) in &[
+            (1, BidiParagraphDirection::Auto),
+            (2, BidiParagraphDirection::LeftToRight),
+            (4, BidiParagraphDirection::RightToLeft),
+        ] {
+            if bitset & bit != 0 {
+                paragraph_directions.push(dir);
+            }
+        }
+        Ok(BidiTestLine::Test(BidiTest {
+            classes: classes.split_whitespace().map(String::from).collect(),
+            paragraph_directions,
+            levels: vec![],
+            reorder: vec![],
+        }))
+    }
+}
+
+impl std::fmt::Display for BidiTestLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            BidiTestLine::Levels(ref levels) => {
+                write!(f, "@Levels:\t")?;
+                write_levels(f, levels)
+            }
+            BidiTestLine::Reorder(ref reorder) => {
+                write!(f, "@Reorder:\t")?;
+                write_reorder(f, reorder)
+            }
+            BidiTestLine::Test(ref test) => {
+                let mut bitset = 0;
+                for dir in &test.paragraph_directions {
+                    bitset |= match *dir {
+                        BidiParagraphDirection::Auto => 1,
+                        BidiParagraphDirection::LeftToRight => 2,
+                        BidiParagraphDirection::RightToLeft => 4,
+                    };
+                }
+                write!(f, "{}; {}", test.classes.join(" "), bitset)
+            }
+        }
+    }
+}
+
+/// Parse every test in `BidiTest.txt`, filling in the levels and reordering
+/// that apply to each one.
+///
+/// The given directory should be the directory to the UCD.
+pub fn parse_bidi_tests<P: AsRef<Path>>(
+    ucd_dir: P,
+) -> Result<Vec<BidiTest>, Error> {
+    let mut levels = None;
+    let mut reorder = None;
+    let mut tests = vec![];
+    for result in BidiTestLine::from_dir(ucd_dir)? {
+        match result? {
+            BidiTestLine::Levels(x) => levels = Some(x),
+            BidiTestLine::Reorder(x) => reorder = Some(x),
+            BidiTestLine::Test(mut test) => {
+                match (&levels, &reorder) {
+                    (Some(levels), Some(reorder)) => {
+                        test.levels = levels.clone();
+                        test.reorder = reorder.clone();
+                    }
+                    _ => {
+                        return err!(
+                            "found BidiTest test before @Levels and \
+                             @Reorder lines: {:?}",
+                            test.classes
+                        )
+                    }
+                }
+                tests.push(test);
+            }
+        }
+    }
+    Ok(tests)
+}
+
+/// A single row in the `BidiCharacterTest.txt` file.
+///
+/// This file defines tests for the bidi algorithm on actual text, as
+/// opposed to sequences of bidi classes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BidiCharacterTest {
+    /// The input text, as a UTF-8 encoded string.
+    pub text: String,
+    /// The paragraph direction that this test should be run with.
+    pub paragraph_direction: BidiParagraphDirection,
+    /// The resolved paragraph embedding level.
+    pub paragraph_level: u8,
+    /// The resolved level of each codepoint in `text`. `None` indicates that
+    /// the corresponding codepoint is removed by rule X9.
+    pub levels: Vec<Option<u8>>,
+    /// The visual ordering of the input, as indices into the codepoints of
+    /// `text`. Codepoints removed by rule X9 are omitted.
+    pub reorder: Vec<usize>,
+}
+
+impl UcdFile for BidiCharacterTest {
+    fn relative_file_path() -> &'static Path {
+        Path::new("BidiCharacterTest.txt")
+    }
+}
+
+impl std::str::FromStr for BidiCharacterTest {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<BidiCharacterTest, Error> {
+        let line = match line.find('#') {
+            None => line.trim(),
+            Some(i) => line[..i].trim(),
+        };
+        let fields: Vec<&str> = line.split(';').collect();
+        if fields.len() != 5 {
+            return err!("invalid BidiCharacterTest line: '{}'", line);
+        }
+
+        let mut text = String::new();
+        for cp in parse_codepoint_sequence(fields[0])? {
+            match cp.scalar() {
+                Some(ch) => text.push(ch),
+                None => {
+                    return err!(
+                        "invalid codepoint '{:X}' in line: '{}'",
+                        cp.value(),
+                        line
+                    )
+                }
+            }
+        }
+        let paragraph_direction = match fields[1].trim() {
+            "0" => BidiParagraphDirection::LeftToRight,
+            "1" => BidiParagraphDirection::RightToLeft,
+            "2" => BidiParagraphDirection::Auto,
+            dir => return err!("invalid paragraph direction: '{}'", dir),
+        };
+        let paragraph_level = match fields[2].trim().parse() {
+            Ok(level) => level,
+            Err(err) => {
+                return err!(
+                    "failed to parse paragraph level '{}': {}",
+                    fields[2].trim(),
+                    err
+                )
+            }
+        };
+        Ok(BidiCharacterTest {
+            text,
+            paragraph_direction,
+            paragraph_level,
+            levels: parse_levels(fields[3])?,
+            reorder: parse_reorder(fields[4])?,
+        })
+    }
+}
+
+impl std::fmt::Display for BidiCharacterTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, ch) in self.text.chars().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:04X}", u32::from(ch))?;
+        }
+        let dir = match self.paragraph_direction {
+            BidiParagraphDirection::LeftToRight => 0,
+            BidiParagraphDirection::RightToLeft => 1,
+            BidiParagraphDirection::Auto => 2,
+        };
+        write!(f, ";{};{};", dir, self.paragraph_level)?;
+        write_levels(f, &self.levels)?;
+        write!(f, ";")?;
+        write_reorder(f, &self.reorder)
+    }
+}
+
+/// Parse a space separated list of levels, where `x` indicates a removed
+/// character.
+fn parse_levels(s: &str) -> Result<Vec<Option<u8>>, Error> {
+    let mut levels = vec![];
+    for level in s.split_whitespace() {
+        if level == "x" {
+            levels.push(None);
+            continue;
+        }
+        match level.parse() {
+            Ok(level) => levels.push(Some(level)),
+            Err(err) => {
+                return err!("failed to parse level '{}': {}", level, err)
+            }
+        }
+    }
+    Ok(levels)
+}
+
+/// Parse a space separated list of indices. The list may be empty.
+fn parse_reorder(s: &str) -> Result<Vec<usize>, Error> {
+    let mut reorder = vec![];
+    for index in s.split_whitespace() {
+        match index.parse() {
+            Ok(index) => reorder.push(index),
+            Err(err) => {
+                return err!("failed to parse index '{}': {}", index, err)
+            }
+        }
+    }
+    Ok(reorder)
+}
+
+/// Write a space separated list of levels, where `x` indicates a removed
+/// character.
+fn write_levels(
+    f: &mut std::fmt::Formatter<'_>,
+    levels: &[Option<u8>],
+) -> std::fmt::Result {
+    for (i, level) in levels.iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        match *level {
+            None => write!(f, "x")?,
+            Some(level) => write!(f, "{}", level)?,
+        }
+    }
+    Ok(())
+}
+
+/// Write a space separated list of indices.
+fn write_reorder(
+    f: &mut std::fmt::Formatter<'_>,
+    reorder: &[usize],
+) -> std::fmt::Result {
+    for (i, index) in reorder.iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", index)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BidiCharacterTest, BidiParagraphDirection, BidiTest, BidiTestLine,
+    };
+
+    #[test]
+    fn parse_levels() {
+        let line = "@Levels:\tx 1 2\n";
+        let row: BidiTestLine = line.parse().unwrap();
+        assert_eq!(row, BidiTestLine::Levels(vec![None, Some(1), Some(2)]));
+    }
+
+    #[test]
+    fn parse_empty_reorder() {
+        let line = "@Reorder:\t\n";
+        let row: BidiTestLine = line.parse().unwrap();
+        assert_eq!(row, BidiTestLine::Reorder(vec![]));
+    }
+
+    #[test]
+    fn parse_test() {
+        let line = "L LRE R; 5\n";
+        let row: BidiTestLine = line.parse().unwrap();
+        assert_eq!(
+            row,
+            BidiTestLine::Test(BidiTest {
+                classes: vec!["L".into(), "LRE".into(), "R".into()],
+                paragraph_directions: vec![
+                    BidiParagraphDirection::Auto,
+                    BidiParagraphDirection::RightToLeft,
+                ],
+                levels: vec![],
+                reorder: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_character_test() {
+        let line = "05D0 0028 0061 0029;2;1;1 1 2 1;3 2 1 0\n";
+        let row: BidiCharacterTest = line.parse().unwrap();
+        assert_eq!(row.text, "\u{05D0}(a)");
+        assert_eq!(row.paragraph_direction, BidiParagraphDirection::Auto);
+        assert_eq!(row.paragraph_level, 1);
+        assert_eq!(row.levels, vec![Some(1), Some(1), Some(2), Some(1)]);
+        assert_eq!(row.reorder, vec![3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn display() {
+        for line in &["@Levels:\tx 1 2", "@Reorder:\t", "L LRE R; 5"] {
+            let row: BidiTestLine = line.parse().unwrap();
+            assert_eq!(row.to_string().trim(), line.trim());
+        }
+        let line = "05D0 0028 0061 0029;2;1;1 1 2 1;3 2 1 0";
+        let row: BidiCharacterTest = line.parse().unwrap();
+        assert_eq!(row.to_string(), line);
+    }
+}
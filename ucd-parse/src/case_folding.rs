@@ -1,7 +1,10 @@
 use std::path::Path;
 
 use crate::{
-    common::{Codepoint, CodepointIter, UcdFile, UcdFileByCodepoint},
+    common::{
+        write_codepoint_sequence, Codepoint, CodepointIter, UcdFile,
+        UcdFileByCodepoint,
+    },
     error::Error,
 };
 
@@ -65,6 +68,14 @@ impl std::str::FromStr for CaseFold {
     }
 }
 
+impl std::fmt::Display for CaseFold {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}; ", self.codepoint, self.status)?;
+        write_codepoint_sequence(f, &self.mapping)?;
+        write!(f, ";")
+    }
+}
+
 /// The status of a particular case mapping.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum CaseStatus {
@@ -113,6 +124,18 @@ impl std::str::FromStr for CaseStatus {
     }
 }
 
+impl std::fmt::Display for CaseStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match *self {
+            CaseStatus::Common => "C",
+            CaseStatus::Full => "F",
+            CaseStatus::Simple => "S",
+            CaseStatus::Special => "T",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{CaseFold, CaseStatus};
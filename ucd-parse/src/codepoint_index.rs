@@ -0,0 +1,269 @@
+use std::{
+    any::{Any, TypeId},
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    common::{
+        parse_many_by_codepoint, Codepoint, UcdFile, UcdFileByCodepoint,
+    },
+    error::Error,
+    extracted, Age, ArabicShaping, BidiMirroring, CaseFold, CoreProperty,
+    DerivedNormalizationProperty, EastAsianWidth, EmojiProperty,
+    GraphemeClusterBreak, JamoShortName, NameAlias, Property, Script,
+    ScriptExtension, SentenceBreak, SpecialCaseMapping, UnicodeData,
+    WordBreak,
+};
+
+/// An index of the records in the files of a UCD directory by codepoint,
+/// for looking up the records of individual codepoints.
+///
+/// Each file is parsed into a map from codepoint to records the first time
+/// that a codepoint is looked up in it. The map is then reused for every
+/// later lookup, so looking up many codepoints only parses each file once.
+#[derive(Debug)]
+pub struct CodepointIndex {
+    ucd_dir: PathBuf,
+    maps: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl CodepointIndex {
+    /// Create a new index of the UCD directory given. No files are read
+    /// until a codepoint is looked up.
+    pub fn new<P: AsRef<Path>>(ucd_dir: P) -> CodepointIndex {
+        CodepointIndex {
+            ucd_dir: ucd_dir.as_ref().to_path_buf(),
+            maps: HashMap::new(),
+        }
+    }
+
+    /// Return the records of the given codepoint in a particular file, in
+    /// the order in which they appear in the file.
+    ///
+    /// This returns an error if the file doesn't exist or couldn't be
+    /// parsed. Note that a codepoint in a range of `UnicodeData.txt` that
+    /// is given by a pair of `First` and `Last` rows has no records here,
+    /// unlike with [`records`](struct.CodepointIndex.html#method.records).
+    pub fn find<D>(&mut self, cp: Codepoint) -> Result<&[D], Error>
+    where
+        D: UcdFileByCodepoint + 'static,
+    {
+        let id = TypeId::of::<D>();
+        if !self.maps.contains_key(&id) {
+            let map = parse_many_by_codepoint::<_, D>(&self.ucd_dir)?;
+            self.maps.insert(id, Box::new(map));
+        }
+        let map = self.maps[&id]
+            .downcast_ref::<BTreeMap<Codepoint, Vec<D>>>()
+            .unwrap();
+        Ok(map.get(&cp).map_or(&[], |records| records.as_slice()))
+    }
+
+    /// Return the records of the given codepoint in every file known to
+    /// this crate that is in the UCD directory.
+    ///
+    /// Records are grouped by file, in the order of the variants of
+    /// [`CodepointRecord`](enum.CodepointRecord.html). Files that don't
+    /// exist are skipped. A codepoint in a range of `UnicodeData.txt` that
+    /// is given by a pair of `First` and `Last` rows has both of those rows
+    /// as its records.
+    pub fn records(
+        &mut self,
+        cp: Codepoint,
+    ) -> Result<Vec<CodepointRecord>, Error> {
+        let mut records = vec![];
+        if UnicodeData::file_path(&self.ucd_dir).exists() {
+            records.extend(
+                self.unicode_data(cp)?
+                    .into_iter()
+                    .map(CodepointRecord::UnicodeData),
+            );
+        }
+        self.others(cp, &mut records)?;
+        Ok(records)
+    }
+
+    /// Return the rows of `UnicodeData.txt` for the given codepoint, which
+    /// are the `First` and `Last` rows of its range if it isn't listed.
+    fn unicode_data(
+        &mut self,
+        cp: Codepoint,
+    ) -> Result<Vec<UnicodeData>, Error> {
+        let rows = self.find::<UnicodeData>(cp)?;
+        if !rows.is_empty() {
+            return Ok(rows.to_vec());
+        }
+        let map = self.maps[&TypeId::of::<UnicodeData>()]
+            .downcast_ref::<BTreeMap<Codepoint, Vec<UnicodeData>>>()
+            .unwrap();
+        let first = map.range(..cp).next_back().and_then(|(_, r)| r.first());
+        let last = map.range(cp..).next().and_then(|(_, r)| r.first());
+        match (first, last) {
+            (Some(first), Some(last))
+                if first.is_range_start() && last.is_range_end() =>
+            {
+                Ok(vec![first.clone(), last.clone()])
+            }
+            _ => Ok(vec![]),
+        }
+    }
+}
+
+macro_rules! codepoint_records {
+    ($($variant:ident($ty:ty) = $file:literal,)*) => {
+        /// A record of a codepoint in one of the files of the UCD, as
+        /// returned by the
+        /// [`records`](struct.CodepointIndex.html#method.records) method of
+        /// `CodepointIndex`.
+        #[derive(Clone, Debug, Eq, PartialEq)]
+        pub enum CodepointRecord {
+            /// A row of `UnicodeData.txt`.
+            UnicodeData(UnicodeData),
+            $(
+                #[doc = concat!("A row of `", $file, "`.")]
+                $variant($ty),
+            )*
+        }
+
+        impl CodepointRecord {
+            /// The file path of this record, relative to the UCD directory.
+            pub fn relative_file_path(&self) -> &'static Path {
+                match *self {
+                    CodepointRecord::UnicodeData(_) => {
+                        UnicodeData::relative_file_path()
+                    }
+                    $(CodepointRecord::$variant(_) => {
+                        <$ty>::relative_file_path()
+                    })*
+                }
+            }
+        }
+
+        impl CodepointIndex {
+            /// Add the records of the given codepoint in every known file
+            /// other than `UnicodeData.txt`.
+            fn others(
+                &mut self,
+                cp: Codepoint,
+                records: &mut Vec<CodepointRecord>,
+            ) -> Result<(), Error> {
+                $(
+                    if <$ty>::file_path(&self.ucd_dir).exists() {
+                        records.extend(
+                            self.find::<$ty>(cp)?
+                                .iter()
+                                .cloned()
+                                .map(CodepointRecord::$variant),
+                        );
+                    }
+                )*
+                Ok(())
+            }
+        }
+    };
+}
+
+codepoint_records! {
+    NameAlias(NameAlias) = "NameAliases.txt",
+    SpecialCaseMapping(SpecialCaseMapping) = "SpecialCasing.txt",
+    CaseFold(CaseFold) = "CaseFolding.txt",
+    Property(Property) = "PropList.txt",
+    CoreProperty(CoreProperty) = "DerivedCoreProperties.txt",
+    DerivedNormalizationProperty(DerivedNormalizationProperty) =
+        "DerivedNormalizationProps.txt",
+    Age(Age) = "DerivedAge.txt",
+    Script(Script) = "Scripts.txt",
+    ScriptExtension(ScriptExtension) = "ScriptExtensions.txt",
+    EastAsianWidth(EastAsianWidth) = "EastAsianWidth.txt",
+    ArabicShaping(ArabicShaping) = "ArabicShaping.txt",
+    BidiMirroring(BidiMirroring) = "BidiMirroring.txt",
+    JamoShortName(JamoShortName) = "Jamo.txt",
+    EmojiProperty(EmojiProperty) = "emoji/emoji-data.txt",
+    GraphemeClusterBreak(GraphemeClusterBreak) =
+        "auxiliary/GraphemeBreakProperty.txt",
+    WordBreak(WordBreak) = "auxiliary/WordBreakProperty.txt",
+    SentenceBreak(SentenceBreak) = "auxiliary/SentenceBreakProperty.txt",
+    DerivedBidiClass(extracted::DerivedBidiClass) =
+        "extracted/DerivedBidiClass.txt",
+    DerivedBinaryProperties(extracted::DerivedBinaryProperties) =
+        "extracted/DerivedBinaryProperties.txt",
+    DerivedCombiningClass(extracted::DerivedCombiningClass) =
+        "extracted/DerivedCombiningClass.txt",
+    DerivedDecompositionType(extracted::DerivedDecompositionType) =
+        "extracted/DerivedDecompositionType.txt",
+    DerivedEastAsianWidth(extracted::DerivedEastAsianWidth) =
+        "extracted/DerivedEastAsianWidth.txt",
+    DerivedGeneralCategory(extracted::DerivedGeneralCategory) =
+        "extracted/DerivedGeneralCategory.txt",
+    DerivedJoiningGroup(extracted::DerivedJoiningGroup) =
+        "extracted/DerivedJoiningGroup.txt",
+    DerivedJoiningType(extracted::DerivedJoiningType) =
+        "extracted/DerivedJoiningType.txt",
+    DerivedLineBreak(extracted::DerivedLineBreak) =
+        "extracted/DerivedLineBreak.txt",
+    DerivedName(extracted::DerivedName) = "extracted/DerivedName.txt",
+    DerivedNumericType(extracted::DerivedNumericType) =
+        "extracted/DerivedNumericType.txt",
+    DerivedNumericValues(extracted::DerivedNumericValues) =
+        "extracted/DerivedNumericValues.txt",
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{CodepointIndex, CodepointRecord};
+    use crate::{Codepoint, Property, UnicodeData};
+
+    fn cp(n: u32) -> Codepoint {
+        Codepoint::from_u32(n).unwrap()
+    }
+
+    #[test]
+    fn records() {
+        let dir = std::env::temp_dir()
+            .join(format!("ucd-parse-codepoint-index-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("UnicodeData.txt"),
+            "0041;LATIN CAPITAL LETTER A;Lu;0;L;;;;;N;;;;0061;\n\
+             AC00;<Hangul Syllable, First>;Lo;0;L;;;;;N;;;;;\n\
+             D7A3;<Hangul Syllable, Last>;Lo;0;L;;;;;N;;;;;\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("PropList.txt"),
+            "0041..0046 ; Hex_Digit\n\
+             0041..0046 ; ASCII_Hex_Digit\n",
+        )
+        .unwrap();
+
+        let mut index = CodepointIndex::new(&dir);
+        let records = index.records(cp(0x41)).unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(matches!(
+            records[0],
+            CodepointRecord::UnicodeData(ref row)
+                if row.name == "LATIN CAPITAL LETTER A"
+        ));
+        match records[2] {
+            CodepointRecord::Property(ref row) => {
+                assert_eq!(row.property, "ASCII_Hex_Digit");
+            }
+            ref record => panic!("unexpected record: {:?}", record),
+        }
+        assert_eq!(
+            records[2].relative_file_path(),
+            std::path::Path::new("PropList.txt")
+        );
+
+        let records = index.records(cp(0xAC01)).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(index.find::<UnicodeData>(cp(0xAC01)).unwrap().is_empty());
+        assert!(index.find::<Property>(cp(0x47)).unwrap().is_empty());
+        assert!(index.records(cp(0x10FFFF)).unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
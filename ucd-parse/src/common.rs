@@ -7,7 +7,10 @@ use std::{
     str::FromStr,
 };
 
-use crate::error::{Error, ErrorKind};
+use crate::{
+    error::{Error, ErrorKind},
+    missing::Missing,
+};
 
 /// Parse a particular file in the UCD into a sequence of rows.
 ///
@@ -70,6 +73,26 @@ where
     Ok(map)
 }
 
+/// Parse all of the `@missing` annotations in a particular file in the UCD.
+///
+/// The annotations are returned in the order in which they appear in the
+/// file. Since later annotations take precedence over earlier ones, callers
+/// looking up the default value for a codepoint should search this list in
+/// reverse.
+///
+/// The given directory should be the directory to the UCD.
+pub fn parse_missing<P, D>(ucd_dir: P) -> Result<Vec<Missing>, Error>
+where
+    P: AsRef<Path>,
+    D: UcdFile,
+{
+    let mut parser = D::from_dir(ucd_dir)?;
+    for result in &mut parser {
+        result?;
+    }
+    Ok(parser.missing().to_vec())
+}
+
 /// Given a path pointing at the root of the `ucd_dir`, attempts to determine
 /// it's unicode version.
 ///
@@ -220,6 +243,42 @@ pub fn parse_break_test(line: &str) -> Result<(Vec<String>, String), Error> {
     Ok((groups, comment))
 }
 
+/// A helper function for writing a sequence of space separated codepoints,
+/// in the format accepted by `parse_codepoint_sequence`.
+pub fn write_codepoint_sequence(
+    f: &mut fmt::Formatter<'_>,
+    cps: &[Codepoint],
+) -> fmt::Result {
+    for (i, cp) in cps.iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", cp)?;
+    }
+    Ok(())
+}
+
+/// A helper function for writing a single test for the various break
+/// algorithms, in the format accepted by `parse_break_test`.
+///
+/// Each group is written as a sequence of codepoints joined by `×`, and
+/// groups are separated by `÷`.
+pub fn write_break_test(
+    f: &mut fmt::Formatter<'_>,
+    groups: &[String],
+    comment: &str,
+) -> fmt::Result {
+    write!(f, "÷")?;
+    for group in groups {
+        let mut chars = group.chars().peekable();
+        while let Some(ch) = chars.next() {
+            let kind = if chars.peek().is_some() { "×" } else { "÷" };
+            write!(f, " {:04X} {}", u32::from(ch), kind)?;
+        }
+    }
+    write!(f, "\t# {}", comment)
+}
+
 /// Describes a single UCD file.
 pub trait UcdFile:
     Clone + fmt::Debug + Default + Eq + FromStr<Err = Error> + PartialEq
@@ -261,12 +320,17 @@ pub trait UcdFileByCodepoint: UcdFile {
 ///
 /// The `D` type parameter refers to the type of the record parsed out of each
 /// line.
+///
+/// While comment lines are otherwise skipped, any `@missing` annotations
+/// found in them are collected and made available via the
+/// [`missing`](struct.UcdLineParser.html#method.missing) method.
 #[derive(Debug)]
 pub struct UcdLineParser<R, D> {
     path: Option<PathBuf>,
     rdr: io::BufReader<R>,
     line: String,
     line_number: u64,
+    missing: Vec<Missing>,
     _data: std::marker::PhantomData<D>,
 }
 
@@ -299,9 +363,20 @@ impl<R: io::Read, D> UcdLineParser<R, D> {
             rdr: io::BufReader::new(rdr),
             line: String::new(),
             line_number: 0,
+            missing: vec![],
             _data: std::marker::PhantomData,
         }
     }
+
+    /// Return the `@missing` annotations seen so far, in the order in which
+    /// they appeared.
+    ///
+    /// Annotations are collected as the parser advances, so this only
+    /// returns every annotation in the file once the parser has been
+    /// exhausted.
+    pub fn missing(&self) -> &[Missing] {
+        &self.missing
+    }
 }
 
 impl<R: io::Read, D: FromStr<Err = Error>> Iterator for UcdLineParser<R, D> {
@@ -324,6 +399,16 @@ impl<R: io::Read, D: FromStr<Err = Error>> Iterator for UcdLineParser<R, D> {
             if n == 0 {
                 return None;
             }
+            if Missing::is_missing_line(&self.line) {
+                match self.line.parse::<Missing>() {
+                    Ok(missing) => self.missing.push(missing),
+                    Err(mut err) => {
+                        err.line = Some(self.line_number);
+                        return Some(Err(err));
+                    }
+                }
+                continue;
+            }
             if !self.line.starts_with('#') && !self.line.trim().is_empty() {
                 break;
             }
@@ -345,6 +430,23 @@ pub enum Codepoints {
     Range(CodepointRange),
 }
 
+impl Codepoints {
+    /// Return these codepoints as a range, which has the same start and end
+    /// for a single codepoint.
+    pub fn range(self) -> CodepointRange {
+        match self {
+            Codepoints::Single(x) => CodepointRange { start: x, end: x },
+            Codepoints::Range(x) => x,
+        }
+    }
+
+    /// Return an iterator over the Unicode scalar values in these
+    /// codepoints, which skips surrogates.
+    pub fn scalars(self) -> ScalarIter {
+        self.range().scalars()
+    }
+}
+
 impl Default for Codepoints {
     fn default() -> Codepoints {
         Codepoints::Single(Codepoint::default())
@@ -432,6 +534,74 @@ pub struct CodepointRange {
     pub end: Codepoint,
 }
 
+impl CodepointRange {
+    /// Return the range of every codepoint, `0000..10FFFF`.
+    pub fn all() -> CodepointRange {
+        CodepointRange { start: Codepoint(0), end: Codepoint(0x10FFFF) }
+    }
+
+    /// Return the range of surrogate codepoints, `D800..DFFF`.
+    pub fn surrogates() -> CodepointRange {
+        CodepointRange { start: Codepoint(0xD800), end: Codepoint(0xDFFF) }
+    }
+
+    /// Returns true if and only if the given codepoint is in this range.
+    pub fn contains(self, cp: Codepoint) -> bool {
+        self.start <= cp && cp <= self.end
+    }
+
+    /// Return the codepoints in both this range and the given range, or
+    /// `None` if they don't overlap.
+    pub fn intersection(
+        self,
+        other: CodepointRange,
+    ) -> Option<CodepointRange> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        if start > end {
+            None
+        } else {
+            Some(CodepointRange { start, end })
+        }
+    }
+
+    /// Return the codepoints in this range that aren't in the given range.
+    ///
+    /// There are at most two such ranges: the one before the given range
+    /// and the one after it, in that order.
+    pub fn difference(
+        self,
+        other: CodepointRange,
+    ) -> [Option<CodepointRange>; 2] {
+        if self.intersection(other).is_none() {
+            return [Some(self), None];
+        }
+        let before = if self.start < other.start {
+            other
+                .start
+                .checked_sub(1)
+                .map(|end| CodepointRange { start: self.start, end })
+        } else {
+            None
+        };
+        let after = if other.end < self.end {
+            other
+                .end
+                .checked_add(1)
+                .map(|start| CodepointRange { start, end: self.end })
+        } else {
+            None
+        };
+        [before, after]
+    }
+
+    /// Return an iterator over the Unicode scalar values in this range,
+    /// which skips surrogates.
+    pub fn scalars(self) -> ScalarIter {
+        ScalarIter(self.into_iter())
+    }
+}
+
 impl IntoIterator for CodepointRange {
     type IntoIter = CodepointIter;
     type Item = Codepoint;
@@ -507,6 +677,24 @@ impl Codepoint {
         self.0
     }
 
+    /// Return the codepoint `n` codepoints after this one, or `None` if that
+    /// is greater than `10FFFF`.
+    pub fn checked_add(self, n: u32) -> Option<Codepoint> {
+        self.0.checked_add(n).and_then(|n| Codepoint::from_u32(n).ok())
+    }
+
+    /// Return the codepoint `n` codepoints before this one, or `None` if
+    /// that is less than `0`.
+    pub fn checked_sub(self, n: u32) -> Option<Codepoint> {
+        self.0.checked_sub(n).map(Codepoint)
+    }
+
+    /// Returns true if and only if this is a surrogate codepoint, i.e., in
+    /// the range `D800..DFFF`.
+    pub fn is_surrogate(self) -> bool {
+        CodepointRange::surrogates().contains(self)
+    }
+
     /// Attempt to convert this codepoint to a Unicode scalar value.
     ///
     /// If this is a surrogate codepoint, then this returns `None`.
@@ -579,3 +767,80 @@ impl Iterator for CodepointIter {
         Some(Codepoint::from_u32(current).unwrap())
     }
 }
+
+/// An iterator over the Unicode scalar values in a range of codepoints.
+#[derive(Debug)]
+pub struct ScalarIter(CodepointIter);
+
+impl Iterator for ScalarIter {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        loop {
+            if let Some(ch) = self.0.next()?.scalar() {
+                return Some(ch);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Codepoint, CodepointRange, Codepoints};
+
+    fn range(start: u32, end: u32) -> CodepointRange {
+        CodepointRange {
+            start: Codepoint::from_u32(start).unwrap(),
+            end: Codepoint::from_u32(end).unwrap(),
+        }
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        let max = Codepoint::from_u32(0x10FFFF).unwrap();
+        assert_eq!(max.checked_add(1), None);
+        assert_eq!(max.checked_sub(1), Some(Codepoint(0x10FFFE)));
+        assert_eq!(Codepoint(0).checked_sub(1), None);
+        assert!(Codepoint(0xDFFF).is_surrogate());
+        assert!(!Codepoint(0xE000).is_surrogate());
+    }
+
+    #[test]
+    fn intersection() {
+        let r = range(0x10, 0x20);
+        assert_eq!(r.intersection(range(0x18, 0x30)), Some(range(0x18, 0x20)));
+        assert_eq!(r.intersection(range(0x20, 0x20)), Some(range(0x20, 0x20)));
+        assert_eq!(r.intersection(range(0x21, 0x30)), None);
+    }
+
+    #[test]
+    fn difference() {
+        let r = range(0x10, 0x20);
+        assert_eq!(
+            r.difference(range(0x14, 0x18)),
+            [Some(range(0x10, 0x13)), Some(range(0x19, 0x20))]
+        );
+        assert_eq!(
+            r.difference(range(0x10, 0x18)),
+            [None, Some(range(0x19, 0x20))]
+        );
+        assert_eq!(r.difference(range(0, 0x10FFFF)), [None, None]);
+        assert_eq!(r.difference(range(0x21, 0x30)), [Some(r), None]);
+        assert_eq!(
+            CodepointRange::all().difference(CodepointRange::surrogates()),
+            [Some(range(0, 0xD7FF)), Some(range(0xE000, 0x10FFFF))]
+        );
+    }
+
+    #[test]
+    fn scalars() {
+        let scalars: Vec<char> = range(0xD7FE, 0xE001).scalars().collect();
+        assert_eq!(
+            scalars,
+            vec!['\u{D7FE}', '\u{D7FF}', '\u{E000}', '\u{E001}']
+        );
+        let single = Codepoints::Single(Codepoint(0xD800));
+        assert_eq!(single.scalars().count(), 0);
+        assert_eq!(CodepointRange::all().scalars().count(), 0x110000 - 0x800);
+    }
+}
@@ -1,10 +1,7 @@
 use std::path::Path;
 
 use crate::{
-    common::{
-        parse_codepoint_association, CodepointIter, Codepoints, UcdFile,
-        UcdFileByCodepoint,
-    },
+    common::{CodepointIter, Codepoints, UcdFile, UcdFileByCodepoint},
     error::Error,
 };
 
@@ -15,6 +12,11 @@ pub struct CoreProperty {
     pub codepoints: Codepoints,
     /// The property name assigned to the codepoints in this entry.
     pub property: String,
+    /// The value of the property, for properties that aren't binary.
+    ///
+    /// The only such property is `InCB` (Indic_Conjunct_Break), which was
+    /// added in Unicode 15.1. Rows of binary properties have no value.
+    pub value: Option<String>,
 }
 
 impl UcdFile for CoreProperty {
@@ -33,8 +35,35 @@ impl std::str::FromStr for CoreProperty {
     type Err = Error;
 
     fn from_str(line: &str) -> Result<CoreProperty, Error> {
-        let (codepoints, property) = parse_codepoint_association(line)?;
-        Ok(CoreProperty { codepoints, property: property.to_string() })
+        let re_parts = regex!(
+            r"(?x)
+                ^
+                \s*(?P<codepoints>[^\s;]+)\s*;
+                \s*(?P<property>[^;\x23]+?)\s*
+                (?:;\s*(?P<value>[^;\x23]+?)\s*)?
+                (?:\x23|$)
+                ",
+        );
+
+        let caps = match re_parts.captures(line.trim()) {
+            Some(caps) => caps,
+            None => return err!("invalid DerivedCoreProperties line"),
+        };
+        Ok(CoreProperty {
+            codepoints: caps["codepoints"].parse()?,
+            property: caps["property"].to_string(),
+            value: caps.name("value").map(|v| v.as_str().to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for CoreProperty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.property)?;
+        if let Some(ref value) = self.value {
+            write!(f, "; {}", value)?;
+        }
+        Ok(())
     }
 }
 
@@ -57,5 +86,16 @@ mod tests {
         let row: CoreProperty = line.parse().unwrap();
         assert_eq!(row.codepoints, (0x11133, 0x11134));
         assert_eq!(row.property, "Grapheme_Link");
+        assert_eq!(row.value, None);
+    }
+
+    #[test]
+    fn parse_value() {
+        let line = "0915..0939    ; InCB; Consonant # Lo  [37] DEVANAGARI LETTER KA..DEVANAGARI LETTER HA\n";
+        let row: CoreProperty = line.parse().unwrap();
+        assert_eq!(row.codepoints, (0x0915, 0x0939));
+        assert_eq!(row.property, "InCB");
+        assert_eq!(row.value.as_deref(), Some("Consonant"));
+        assert_eq!(row.to_string(), "0915..0939; InCB; Consonant");
     }
 }
@@ -2,8 +2,8 @@ use std::path::Path;
 
 use crate::{
     common::{
-        parse_codepoint_association, CodepointIter, Codepoints, UcdFile,
-        UcdFileByCodepoint,
+        parse_codepoint_association, parse_codepoint_sequence, Codepoint,
+        CodepointIter, Codepoints, UcdFile, UcdFileByCodepoint,
     },
     error::Error,
 };
@@ -15,6 +15,13 @@ pub struct DerivedNormalizationProperty {
     pub codepoints: Codepoints,
     /// The property name assigned to the codepoints in this entry.
     pub property: String,
+    /// The value of the property, for properties that aren't binary.
+    ///
+    /// For example, this is `Some("N")` for an `NFKC_QC` entry, and the
+    /// space separated codepoints of the mapping for an `NFKC_CF` entry. An
+    /// `NFKC_CF` entry may map to the empty string, in which case this is
+    /// `Some("")`. For binary properties, this is `None`.
+    pub value: Option<String>,
 }
 
 impl UcdFile for DerivedNormalizationProperty {
@@ -34,13 +41,42 @@ impl std::str::FromStr for DerivedNormalizationProperty {
 
     fn from_str(line: &str) -> Result<DerivedNormalizationProperty, Error> {
         let (codepoints, property) = parse_codepoint_association(line)?;
+        let fields = match line.find('#') {
+            None => line,
+            Some(i) => &line[..i],
+        };
+        let value = fields.splitn(3, ';').nth(2).map(|v| v.trim().to_string());
         Ok(DerivedNormalizationProperty {
             codepoints,
             property: property.to_string(),
+            value,
         })
     }
 }
 
+impl std::fmt::Display for DerivedNormalizationProperty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.property)?;
+        if let Some(ref value) = self.value {
+            write!(f, "; {}", value)?;
+        }
+        Ok(())
+    }
+}
+
+impl DerivedNormalizationProperty {
+    /// Parse the value of this entry as a sequence of codepoints.
+    ///
+    /// This is useful for the entries of mapping properties such as
+    /// `NFKC_CF`. If this entry has no value, then an error is returned.
+    pub fn mapping(&self) -> Result<Vec<Codepoint>, Error> {
+        match self.value {
+            None => err!("{} entry has no mapping", self.property),
+            Some(ref value) => parse_codepoint_sequence(value),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedNormalizationProperty;
@@ -52,6 +88,7 @@ mod tests {
         let row: DerivedNormalizationProperty = line.parse().unwrap();
         assert_eq!(row.codepoints, 0xA0);
         assert_eq!(row.property, "Changes_When_NFKC_Casefolded");
+        assert_eq!(row.value, None);
     }
 
     #[test]
@@ -61,4 +98,26 @@ mod tests {
         assert_eq!(row.codepoints, (0x41, 0x5A));
         assert_eq!(row.property, "Changes_When_NFKC_Casefolded");
     }
+
+    #[test]
+    fn parse_value() {
+        let line = "0340..0341    ; NFKC_QC; N # Mn   [2] COMBINING GRAVE TONE MARK..COMBINING ACUTE TONE MARK\n";
+        let row: DerivedNormalizationProperty = line.parse().unwrap();
+        assert_eq!(row.property, "NFKC_QC");
+        assert_eq!(row.value, Some("N".to_string()));
+    }
+
+    #[test]
+    fn parse_mapping() {
+        let line = "00BD          ; NFKC_CF; 0031 2044 0032 # No       VULGAR FRACTION ONE HALF\n";
+        let row: DerivedNormalizationProperty = line.parse().unwrap();
+        assert_eq!(row.property, "NFKC_CF");
+        assert_eq!(row.mapping().unwrap(), vec![0x31, 0x2044, 0x32]);
+
+        let line =
+            "00AD          ; NFKC_CF;                # Cf       SOFT HYPHEN\n";
+        let row: DerivedNormalizationProperty = line.parse().unwrap();
+        assert_eq!(row.value, Some("".to_string()));
+        assert!(row.mapping().unwrap().is_empty());
+    }
 }
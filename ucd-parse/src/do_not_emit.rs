@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use crate::{
+    common::{
+        parse_codepoint_sequence, write_codepoint_sequence, Codepoint, UcdFile,
+    },
+    error::Error,
+};
+
+/// A single row in the `DoNotEmit.txt` file.
+///
+/// This file lists sequences of codepoints that should not be emitted by
+/// input methods or other text producers, along with the sequence that
+/// should be emitted instead. Each row also says why the sequence is
+/// discouraged, e.g., `Deprecated` or `Preferred_Spelling`.
+///
+/// This file was added in Unicode 16.0.0.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DoNotEmit {
+    /// The sequence of codepoints that should not be emitted.
+    pub sequence: Vec<Codepoint>,
+    /// The sequence of codepoints that should be emitted instead.
+    pub replacement: Vec<Codepoint>,
+    /// The type of this entry, which explains why the sequence should not
+    /// be emitted.
+    pub kind: String,
+}
+
+impl UcdFile for DoNotEmit {
+    fn relative_file_path() -> &'static Path {
+        Path::new("DoNotEmit.txt")
+    }
+}
+
+impl std::str::FromStr for DoNotEmit {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<DoNotEmit, Error> {
+        let re_parts = regex!(
+            r"(?x)
+                ^
+                \s*(?P<sequence>[^;]+)\s*;
+                \s*(?P<replacement>[^;]+)\s*;
+                \s*(?P<kind>[^;\s\#]+)\s*
+                (?:\#|$)
+                ",
+        );
+
+        let caps = match re_parts.captures(line.trim()) {
+            Some(caps) => caps,
+            None => return err!("invalid DoNotEmit line: '{}'", line),
+        };
+        Ok(DoNotEmit {
+            sequence: parse_codepoint_sequence(&caps["sequence"])?,
+            replacement: parse_codepoint_sequence(&caps["replacement"])?,
+            kind: caps["kind"].to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for DoNotEmit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_codepoint_sequence(f, &self.sequence)?;
+        write!(f, "; ")?;
+        write_codepoint_sequence(f, &self.replacement)?;
+        write!(f, "; {}", self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DoNotEmit;
+
+    #[test]
+    fn parse_single() {
+        let line = "0F73 ; 0F71 0F72 ; Discouraged # TIBETAN VOWEL SIGN II\n";
+        let row: DoNotEmit = line.parse().unwrap();
+        assert_eq!(row.sequence.len(), 1);
+        assert_eq!(row.sequence[0], 0x0F73);
+        assert_eq!(row.replacement.len(), 2);
+        assert_eq!(row.replacement[0], 0x0F71);
+        assert_eq!(row.replacement[1], 0x0F72);
+        assert_eq!(row.kind, "Discouraged");
+    }
+
+    #[test]
+    fn parse_sequence() {
+        let line = "0627 0655;0625;Preferred_Spelling";
+        let row: DoNotEmit = line.parse().unwrap();
+        assert_eq!(row.sequence.len(), 2);
+        assert_eq!(row.replacement.len(), 1);
+        assert_eq!(row.kind, "Preferred_Spelling");
+        assert_eq!(row.to_string(), "0627 0655; 0625; Preferred_Spelling");
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("0627 0655; 0625".parse::<DoNotEmit>().is_err());
+    }
+}
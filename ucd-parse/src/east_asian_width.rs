@@ -42,6 +42,12 @@ impl std::str::FromStr for EastAsianWidth {
     }
 }
 
+impl std::fmt::Display for EastAsianWidth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.width)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::EastAsianWidth;
@@ -65,6 +65,12 @@ impl std::str::FromStr for EmojiProperty {
     }
 }
 
+impl std::fmt::Display for EmojiProperty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.property)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::EmojiProperty;
@@ -0,0 +1,210 @@
+use std::path::Path;
+
+use crate::{
+    common::{parse_codepoint_sequence, Codepoint, CodepointRange, UcdFile},
+    error::Error,
+};
+
+/// A single row in the `emoji/emoji-sequences.txt` file.
+///
+/// This file lists the RGI emoji that aren't ZWJ sequences, grouped by the
+/// kind of sequence they are, e.g., `Basic_Emoji` or
+/// `RGI_Emoji_Flag_Sequence`.
+///
+/// Note that the emoji sequence files are not formally part of the Unicode
+/// Character Database. They are included in the UCD's `emoji` directory
+/// starting with 13.0.0, and can also be downloaded separately here:
+/// https://unicode.org/Public/emoji/
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EmojiSequence {
+    /// The codepoints in this entry.
+    pub codepoints: EmojiSequenceCodepoints,
+    /// The property (the kind of sequence) assigned to this entry.
+    pub property: String,
+    /// A short description of this entry, e.g., `watch` or
+    /// `flag: Canada`.
+    pub description: String,
+}
+
+/// The codepoints of an emoji sequence entry.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EmojiSequenceCodepoints {
+    /// A single sequence of one or more codepoints.
+    Sequence(Vec<Codepoint>),
+    /// A range of codepoints, each of which is a sequence on its own. These
+    /// only appear in `Basic_Emoji` entries.
+    Range(CodepointRange),
+}
+
+impl Default for EmojiSequenceCodepoints {
+    fn default() -> EmojiSequenceCodepoints {
+        EmojiSequenceCodepoints::Sequence(vec![])
+    }
+}
+
+impl EmojiSequenceCodepoints {
+    /// Return every sequence described by this entry. That is, either the
+    /// single sequence or one sequence for each codepoint in the range.
+    pub fn sequences(&self) -> Vec<Vec<Codepoint>> {
+        match *self {
+            EmojiSequenceCodepoints::Sequence(ref cps) => vec![cps.clone()],
+            EmojiSequenceCodepoints::Range(range) => {
+                range.into_iter().map(|cp| vec![cp]).collect()
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for EmojiSequenceCodepoints {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            EmojiSequenceCodepoints::Sequence(ref cps) => {
+                let cps: Vec<String> =
+                    cps.iter().map(|cp| cp.to_string()).collect();
+                write!(f, "{}", cps.join(" "))
+            }
+            EmojiSequenceCodepoints::Range(ref range) => range.fmt(f),
+        }
+    }
+}
+
+impl UcdFile for EmojiSequence {
+    fn relative_file_path() -> &'static Path {
+        Path::new("emoji/emoji-sequences.txt")
+    }
+}
+
+impl std::str::FromStr for EmojiSequence {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<EmojiSequence, Error> {
+        let (codepoints, property, description) = parse_emoji_sequence(line)?;
+        let codepoints = if codepoints.contains("..") {
+            EmojiSequenceCodepoints::Range(codepoints.parse()?)
+        } else {
+            EmojiSequenceCodepoints::Sequence(parse_codepoint_sequence(
+                codepoints,
+            )?)
+        };
+        Ok(EmojiSequence {
+            codepoints,
+            property: property.to_string(),
+            description: description.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for EmojiSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}; {}; {}",
+            self.codepoints, self.property, self.description
+        )
+    }
+}
+
+/// A single row in the `emoji/emoji-zwj-sequences.txt` file.
+///
+/// Every row is an RGI emoji ZWJ sequence, i.e., a sequence of emoji joined
+/// by U+200D ZERO WIDTH JOINER.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EmojiZwjSequence {
+    /// The codepoints in this sequence.
+    pub codepoints: Vec<Codepoint>,
+    /// The property assigned to this sequence, which is always
+    /// `RGI_Emoji_ZWJ_Sequence`.
+    pub property: String,
+    /// A short description of this sequence, e.g., `family: man, woman,
+    /// boy`.
+    pub description: String,
+}
+
+impl UcdFile for EmojiZwjSequence {
+    fn relative_file_path() -> &'static Path {
+        Path::new("emoji/emoji-zwj-sequences.txt")
+    }
+}
+
+impl std::str::FromStr for EmojiZwjSequence {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<EmojiZwjSequence, Error> {
+        let (codepoints, property, description) = parse_emoji_sequence(line)?;
+        Ok(EmojiZwjSequence {
+            codepoints: parse_codepoint_sequence(codepoints)?,
+            property: property.to_string(),
+            description: description.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for EmojiZwjSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cps: Vec<String> =
+            self.codepoints.iter().map(|cp| cp.to_string()).collect();
+        write!(f, "{}; {}; {}", cps.join(" "), self.property, self.description)
+    }
+}
+
+/// Split a line from one of the emoji sequence files into its codepoints,
+/// property and description, dropping the trailing comment.
+fn parse_emoji_sequence(line: &str) -> Result<(&str, &str, &str), Error> {
+    let line = match line.find('#') {
+        Some(i) => &line[..i],
+        None => line,
+    };
+    let mut fields = line.split(';').map(|field| field.trim());
+    match (fields.next(), fields.next(), fields.next(), fields.next()) {
+        (Some(cps), Some(property), Some(description), None)
+            if !cps.is_empty() && !property.is_empty() =>
+        {
+            Ok((cps, property, description))
+        }
+        _ => err!("invalid emoji sequence line: '{}'", line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EmojiSequence, EmojiSequenceCodepoints, EmojiZwjSequence};
+
+    #[test]
+    fn parse_range() {
+        let line = "231A..231B    ; Basic_Emoji                  ; watch                                                          # E0.6   [2] (⌚..⌛)\n";
+        let row: EmojiSequence = line.parse().unwrap();
+        match row.codepoints {
+            EmojiSequenceCodepoints::Range(range) => {
+                assert_eq!(range, (0x231A, 0x231B))
+            }
+            ref cps => panic!("expected a range, got {:?}", cps),
+        }
+        assert_eq!(row.codepoints.sequences().len(), 2);
+        assert_eq!(row.property, "Basic_Emoji");
+        assert_eq!(row.description, "watch");
+    }
+
+    #[test]
+    fn parse_sequence() {
+        let line = "1F1E8 1F1E6   ; RGI_Emoji_Flag_Sequence      ; flag: Canada                                                   # E2.0   [1] (🇨🇦)\n";
+        let row: EmojiSequence = line.parse().unwrap();
+        assert_eq!(
+            row.codepoints,
+            EmojiSequenceCodepoints::Sequence(vec![
+                "1F1E8".parse().unwrap(),
+                "1F1E6".parse().unwrap(),
+            ])
+        );
+        assert_eq!(row.property, "RGI_Emoji_Flag_Sequence");
+        assert_eq!(row.description, "flag: Canada");
+    }
+
+    #[test]
+    fn parse_zwj() {
+        let line = "1F468 200D 2764 FE0F 200D 1F468                         ; RGI_Emoji_ZWJ_Sequence  ; couple with heart: man, man                                    # E2.0   [1] (👨‍❤️‍👨)\n";
+        let row: EmojiZwjSequence = line.parse().unwrap();
+        assert_eq!(row.codepoints.len(), 6);
+        assert_eq!(row.property, "RGI_Emoji_ZWJ_Sequence");
+        assert_eq!(row.description, "couple with heart: man, man");
+    }
+}
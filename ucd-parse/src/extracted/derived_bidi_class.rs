@@ -40,6 +40,12 @@ impl std::str::FromStr for DerivedBidiClass {
     }
 }
 
+impl std::fmt::Display for DerivedBidiClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.bidi_class)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedBidiClass;
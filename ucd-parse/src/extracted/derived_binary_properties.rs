@@ -44,6 +44,12 @@ impl std::str::FromStr for DerivedBinaryProperties {
     }
 }
 
+impl std::fmt::Display for DerivedBinaryProperties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.property)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedBinaryProperties;
@@ -44,6 +44,12 @@ impl std::str::FromStr for DerivedCombiningClass {
     }
 }
 
+impl std::fmt::Display for DerivedCombiningClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.combining_class)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedCombiningClass;
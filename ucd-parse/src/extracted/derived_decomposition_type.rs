@@ -45,6 +45,12 @@ impl std::str::FromStr for DerivedDecompositionType {
     }
 }
 
+impl std::fmt::Display for DerivedDecompositionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.decomposition_type)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedDecompositionType;
@@ -45,6 +45,12 @@ impl std::str::FromStr for DerivedEastAsianWidth {
     }
 }
 
+impl std::fmt::Display for DerivedEastAsianWidth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.east_asian_width)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedEastAsianWidth;
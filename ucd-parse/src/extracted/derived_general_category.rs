@@ -44,6 +44,12 @@ impl std::str::FromStr for DerivedGeneralCategory {
     }
 }
 
+impl std::fmt::Display for DerivedGeneralCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.general_category)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedGeneralCategory;
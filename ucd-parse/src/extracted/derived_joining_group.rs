@@ -43,6 +43,12 @@ impl std::str::FromStr for DerivedJoiningGroup {
     }
 }
 
+impl std::fmt::Display for DerivedJoiningGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.joining_group)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedJoiningGroup;
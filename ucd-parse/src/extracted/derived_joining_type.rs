@@ -43,6 +43,12 @@ impl std::str::FromStr for DerivedJoiningType {
     }
 }
 
+impl std::fmt::Display for DerivedJoiningType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.joining_type)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedJoiningType;
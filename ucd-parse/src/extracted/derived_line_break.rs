@@ -40,6 +40,12 @@ impl std::str::FromStr for DerivedLineBreak {
     }
 }
 
+impl std::fmt::Display for DerivedLineBreak {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.line_break)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedLineBreak;
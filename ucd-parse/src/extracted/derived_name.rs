@@ -40,6 +40,12 @@ impl std::str::FromStr for DerivedName {
     }
 }
 
+impl std::fmt::Display for DerivedName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.name)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedName;
@@ -43,6 +43,12 @@ impl std::str::FromStr for DerivedNumericType {
     }
 }
 
+impl std::fmt::Display for DerivedNumericType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.numeric_type)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedNumericType;
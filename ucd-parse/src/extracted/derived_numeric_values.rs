@@ -63,6 +63,18 @@ impl std::str::FromStr for DerivedNumericValues {
     }
 }
 
+impl std::fmt::Display for DerivedNumericValues {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}; {}; ; {}",
+            self.codepoints,
+            self.numeric_value_decimal,
+            self.numeric_value_fraction
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DerivedNumericValues;
@@ -84,4 +96,16 @@ mod tests {
         assert_eq!(row.numeric_value_decimal, "0.0625");
         assert_eq!(row.numeric_value_fraction, "1/16");
     }
+
+    #[test]
+    fn display_range() {
+        let line =
+            "11FC9..11FCA  ; 0.0625 ; ; 1/16 # No   [2] TAMIL FRACTION\n";
+        let row: DerivedNumericValues = line.parse().unwrap();
+        assert_eq!(row.to_string(), "11FC9..11FCA; 0.0625; ; 1/16");
+        assert_eq!(
+            row.to_string().parse::<DerivedNumericValues>().unwrap(),
+            row
+        );
+    }
 }
@@ -2,8 +2,8 @@ use std::path::Path;
 
 use crate::{
     common::{
-        parse_break_test, parse_codepoint_association, CodepointIter,
-        Codepoints, UcdFile, UcdFileByCodepoint,
+        parse_break_test, parse_codepoint_association, write_break_test,
+        CodepointIter, Codepoints, UcdFile, UcdFileByCodepoint,
     },
     error::Error,
 };
@@ -38,6 +38,12 @@ impl std::str::FromStr for GraphemeClusterBreak {
     }
 }
 
+impl std::fmt::Display for GraphemeClusterBreak {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.value)
+    }
+}
+
 /// A single row in the `auxiliary/GraphemeBreakTest.txt` file.
 ///
 /// This file defines tests for the grapheme cluster break algorithm.
@@ -65,6 +71,12 @@ impl std::str::FromStr for GraphemeClusterBreakTest {
     }
 }
 
+impl std::fmt::Display for GraphemeClusterBreakTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_break_test(f, &self.grapheme_clusters, &self.comment)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{GraphemeClusterBreak, GraphemeClusterBreakTest};
@@ -53,6 +53,12 @@ impl std::str::FromStr for JamoShortName {
     }
 }
 
+impl std::fmt::Display for JamoShortName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoint, self.name)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::JamoShortName;
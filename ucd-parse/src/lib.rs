@@ -5,27 +5,42 @@ A library for parsing the Unicode character database.
 #![deny(missing_docs)]
 
 pub use crate::{
+    codepoint_index::{CodepointIndex, CodepointRecord},
     common::{
-        parse, parse_by_codepoint, parse_many_by_codepoint,
+        parse, parse_by_codepoint, parse_many_by_codepoint, parse_missing,
         ucd_directory_version, Codepoint, CodepointIter, CodepointRange,
-        Codepoints, UcdFile, UcdFileByCodepoint, UcdLineParser,
+        Codepoints, ScalarIter, UcdFile, UcdFileByCodepoint, UcdLineParser,
     },
     error::{Error, ErrorKind},
+    missing::Missing,
 };
 
 pub use crate::{
     age::Age,
+    allkeys::{AllKeysEntry, AllKeysLine, CollationElement, ImplicitWeights},
     arabic_shaping::ArabicShaping,
     bidi_mirroring_glyph::BidiMirroring,
+    bidi_test::{
+        parse_bidi_tests, BidiCharacterTest, BidiParagraphDirection, BidiTest,
+        BidiTestLine,
+    },
     case_folding::{CaseFold, CaseStatus},
     core_properties::CoreProperty,
     derived_normalization_properties::DerivedNormalizationProperty,
+    do_not_emit::DoNotEmit,
     east_asian_width::EastAsianWidth,
     emoji_properties::EmojiProperty,
+    emoji_sequences::{
+        EmojiSequence, EmojiSequenceCodepoints, EmojiZwjSequence,
+    },
     grapheme_cluster_break::{GraphemeClusterBreak, GraphemeClusterBreakTest},
     jamo_short_name::JamoShortName,
     line_break::LineBreakTest,
     name_aliases::{NameAlias, NameAliasLabel},
+    named_sequences::NamedSequence,
+    normalization_test::{
+        parse_normalization_tests, NormalizationTest, NormalizationTestLine,
+    },
     prop_list::Property,
     property_aliases::PropertyAlias,
     property_value_aliases::PropertyValueAlias,
@@ -40,6 +55,9 @@ pub use crate::{
     word_break::{WordBreak, WordBreakTest},
 };
 
+#[cfg(feature = "xml")]
+pub use crate::xml::UcdXml;
+
 macro_rules! err {
     ($($tt:tt)*) => {
         Err(crate::error::Error::parse(format!($($tt)*)))
@@ -58,21 +76,29 @@ macro_rules! regex {
 
 pub mod extracted;
 
+mod codepoint_index;
 mod common;
 mod error;
 
 mod age;
+mod allkeys;
 mod arabic_shaping;
 mod bidi_mirroring_glyph;
+mod bidi_test;
 mod case_folding;
 mod core_properties;
 mod derived_normalization_properties;
+mod do_not_emit;
 mod east_asian_width;
 mod emoji_properties;
+mod emoji_sequences;
 mod grapheme_cluster_break;
 mod jamo_short_name;
 mod line_break;
+mod missing;
 mod name_aliases;
+mod named_sequences;
+mod normalization_test;
 mod prop_list;
 mod property_aliases;
 mod property_value_aliases;
@@ -82,3 +108,5 @@ mod sentence_break;
 mod special_casing;
 mod unicode_data;
 mod word_break;
+#[cfg(feature = "xml")]
+mod xml;
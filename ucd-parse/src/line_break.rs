@@ -1,7 +1,7 @@
 use std::path::Path;
 
 use crate::{
-    common::{parse_break_test, UcdFile},
+    common::{parse_break_test, write_break_test, UcdFile},
     error::Error,
 };
 
@@ -32,6 +32,12 @@ impl std::str::FromStr for LineBreakTest {
     }
 }
 
+impl std::fmt::Display for LineBreakTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_break_test(f, &self.lines, &self.comment)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::LineBreakTest;
@@ -0,0 +1,138 @@
+use crate::{common::Codepoints, error::Error};
+
+/// A single `@missing` annotation found in a comment of a UCD file.
+///
+/// Many UCD files do not list every codepoint. Instead, they document the
+/// default value of codepoints that aren't explicitly listed via specially
+/// formatted comment lines, e.g.,
+///
+/// ```text
+/// # @missing: 0000..10FFFF; Left_To_Right
+/// # @missing: 0590..05FF; Right_To_Left
+/// ```
+///
+/// When several annotations cover the same codepoint, the one appearing
+/// later in the file takes precedence.
+///
+/// Some files (like `DerivedNormalizationProps.txt`) contain values for
+/// several properties. In that case, the annotation also names the property
+/// that its default value applies to. Annotations with more than two fields
+/// (like those in `DerivedNumericValues.txt`) have no property and keep all
+/// of their fields, unsplit, in `value`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Missing {
+    /// The codepoint or codepoint range for this annotation.
+    pub codepoints: Codepoints,
+    /// The property that this default applies to, if the annotation names
+    /// one.
+    pub property: Option<String>,
+    /// The default value of the codepoints in this annotation.
+    pub value: String,
+}
+
+impl Missing {
+    /// Returns true if and only if the given line looks like an `@missing`
+    /// annotation.
+    ///
+    /// This does not check whether the annotation is well formed.
+    pub fn is_missing_line(line: &str) -> bool {
+        match line.trim_start().strip_prefix('#') {
+            None => false,
+            Some(rest) => rest.trim_start().starts_with("@missing:"),
+        }
+    }
+}
+
+impl std::str::FromStr for Missing {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Missing, Error> {
+        let re_parts = regex!(
+            r"(?x)
+                ^
+                \x23\s*@missing:\s*
+                (?P<codepoints>[^\s;]+)\s*;
+                (?P<fields>[^\x23]*)
+                ",
+        );
+
+        let caps = match re_parts.captures(line.trim()) {
+            Some(caps) => caps,
+            None => return err!("invalid @missing line: '{}'", line),
+        };
+        let codepoints = caps["codepoints"].parse()?;
+        let raw = caps["fields"].trim();
+        let fields: Vec<&str> = raw.split(';').map(|f| f.trim()).collect();
+        let (property, value) = match fields[..] {
+            [property, value] => (Some(property.to_string()), value),
+            _ => (None, raw),
+        };
+        Ok(Missing { codepoints, property, value: value.to_string() })
+    }
+}
+
+impl std::fmt::Display for Missing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "# @missing: {}; ", self.codepoints)?;
+        if let Some(ref property) = self.property {
+            write!(f, "{}; ", property)?;
+        }
+        write!(f, "{}", self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Missing;
+
+    #[test]
+    fn parse_value() {
+        let line = "# @missing: 0590..05FF; Right_To_Left\n";
+        let row: Missing = line.parse().unwrap();
+        assert_eq!(row.codepoints, (0x0590, 0x05FF));
+        assert_eq!(row.property, None);
+        assert_eq!(row.value, "Right_To_Left");
+    }
+
+    #[test]
+    fn parse_property_value() {
+        let line = "# @missing: 0000..10FFFF; NFKC_QC; Yes\n";
+        let row: Missing = line.parse().unwrap();
+        assert_eq!(row.codepoints, (0x0000, 0x10FFFF));
+        assert_eq!(row.property, Some("NFKC_QC".to_string()));
+        assert_eq!(row.value, "Yes");
+    }
+
+    #[test]
+    fn parse_empty_value() {
+        let line = "# @missing: 0000..10FFFF; NFKC_CF; <code point>\n";
+        let row: Missing = line.parse().unwrap();
+        assert_eq!(row.value, "<code point>");
+    }
+
+    #[test]
+    fn parse_many_fields() {
+        let line = "# @missing: 0000..10FFFF; NaN; ; NaN\n";
+        let row: Missing = line.parse().unwrap();
+        assert_eq!(row.property, None);
+        assert_eq!(row.value, "NaN; ; NaN");
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Missing::is_missing_line("# @missing: 0000..10FFFF; XX"));
+        assert!(!Missing::is_missing_line("# Some other comment"));
+        assert!(!Missing::is_missing_line("0041; L"));
+    }
+
+    #[test]
+    fn display() {
+        for line in &[
+            "# @missing: 0590..05FF; Right_To_Left",
+            "# @missing: 0000..10FFFF; NFKC_QC; Yes",
+        ] {
+            let row: Missing = line.parse().unwrap();
+            assert_eq!(row.to_string(), *line);
+        }
+    }
+}
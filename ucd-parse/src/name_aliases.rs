@@ -58,6 +58,12 @@ impl std::str::FromStr for NameAlias {
     }
 }
 
+impl std::fmt::Display for NameAlias {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{};{};{}", self.codepoint, self.alias, self.label)
+    }
+}
+
 /// The label of a name alias.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum NameAliasLabel {
@@ -98,6 +104,19 @@ impl std::str::FromStr for NameAliasLabel {
     }
 }
 
+impl std::fmt::Display for NameAliasLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match *self {
+            NameAliasLabel::Correction => "correction",
+            NameAliasLabel::Control => "control",
+            NameAliasLabel::Alternate => "alternate",
+            NameAliasLabel::Figment => "figment",
+            NameAliasLabel::Abbreviation => "abbreviation",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{NameAlias, NameAliasLabel};
@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use crate::{
+    common::{parse_codepoint_sequence, Codepoint, UcdFile},
+    error::Error,
+};
+
+/// A single row in the `NamedSequences.txt` file.
+///
+/// Each row gives a name to a sequence of codepoints. Named sequences share
+/// the namespace of character names, so each name is unique among both.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NamedSequence {
+    /// The name of this sequence, e.g., `LATIN SMALL LETTER A WITH ACUTE
+    /// AND OGONEK`.
+    pub name: String,
+    /// The codepoints in this sequence. There are always at least two.
+    pub codepoints: Vec<Codepoint>,
+}
+
+impl UcdFile for NamedSequence {
+    fn relative_file_path() -> &'static Path {
+        Path::new("NamedSequences.txt")
+    }
+}
+
+impl std::str::FromStr for NamedSequence {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<NamedSequence, Error> {
+        let line = match line.find('#') {
+            Some(i) => &line[..i],
+            None => line,
+        };
+        let mut fields = line.split(';').map(|field| field.trim());
+        let (name, codepoints) =
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(name), Some(cps), None)
+                    if !name.is_empty() && !cps.is_empty() =>
+                {
+                    (name, cps)
+                }
+                _ => return err!("invalid NamedSequences line: '{}'", line),
+            };
+        Ok(NamedSequence {
+            name: name.to_string(),
+            codepoints: parse_codepoint_sequence(codepoints)?,
+        })
+    }
+}
+
+impl std::fmt::Display for NamedSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cps: Vec<String> =
+            self.codepoints.iter().map(|cp| cp.to_string()).collect();
+        write!(f, "{};{}", self.name, cps.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NamedSequence;
+
+    #[test]
+    fn parse() {
+        let line = "LATIN SMALL LETTER A WITH ACUTE AND OGONEK;0105 0301\n";
+        let row: NamedSequence = line.parse().unwrap();
+        assert_eq!(row.name, "LATIN SMALL LETTER A WITH ACUTE AND OGONEK");
+        assert_eq!(row.codepoints, vec![0x0105, 0x0301]);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("LATIN SMALL LETTER A".parse::<NamedSequence>().is_err());
+        assert!(";0105 0301".parse::<NamedSequence>().is_err());
+        assert!("A;0105;0301".parse::<NamedSequence>().is_err());
+    }
+
+    #[test]
+    fn display() {
+        let line = "KEYCAP NUMBER SIGN;0023 FE0F 20E3";
+        let row: NamedSequence = line.parse().unwrap();
+        assert_eq!(row.to_string(), line);
+    }
+}
@@ -0,0 +1,229 @@
+use std::path::Path;
+
+use crate::{
+    common::{parse_codepoint_sequence, UcdFile},
+    error::Error,
+};
+
+/// A single test in the `NormalizationTest.txt` file.
+///
+/// Each test gives a source string (`c1`) along with its NFC (`c2`), NFD
+/// (`c3`), NFKC (`c4`) and NFKD (`c5`) forms. The file itself only says
+/// which part a test belongs to once for a whole group of tests, via
+/// `@Part` lines. To get tests where this information is filled in, use
+/// [`parse_normalization_tests`](fn.parse_normalization_tests.html).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NormalizationTest {
+    /// The part of the file that this test is in, e.g., `1` for the tests
+    /// of single codepoints.
+    ///
+    /// Part 1 is special: every codepoint that isn't the source of a test
+    /// in part 1 is unchanged by all four normalization forms.
+    pub part: u8,
+    /// The source string (`c1`).
+    pub source: String,
+    /// The NFC form of the source (`c2`).
+    pub nfc: String,
+    /// The NFD form of the source (`c3`).
+    pub nfd: String,
+    /// The NFKC form of the source (`c4`).
+    pub nfkc: String,
+    /// The NFKD form of the source (`c5`).
+    pub nfkd: String,
+}
+
+/// A single line in the `NormalizationTest.txt` file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NormalizationTestLine {
+    /// An `@Part` line, giving the part of all subsequent tests up to the
+    /// next `@Part` line.
+    Part(u8),
+    /// A test in the part given by the most recent `@Part` line.
+    ///
+    /// The `part` field is always `0`.
+    Test(NormalizationTest),
+}
+
+impl Default for NormalizationTestLine {
+    fn default() -> NormalizationTestLine {
+        NormalizationTestLine::Test(NormalizationTest::default())
+    }
+}
+
+impl UcdFile for NormalizationTestLine {
+    fn relative_file_path() -> &'static Path {
+        Path::new("NormalizationTest.txt")
+    }
+}
+
+impl std::str::FromStr for NormalizationTestLine {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<NormalizationTestLine, Error> {
+        let line = match line.find('#') {
+            None => line.trim(),
+            Some(i) => line[..i].trim(),
+        };
+        if let Some(part) = line.strip_prefix("@Part") {
+            return match part.trim().parse() {
+                Ok(part) => Ok(NormalizationTestLine::Part(part)),
+                Err(err) => {
+                    err!("failed to parse part '{}': {}", part.trim(), err)
+                }
+            };
+        }
+        if line.starts_with('@') {
+            return err!(
+                "unrecognized NormalizationTest directive: '{}'",
+                line
+            );
+        }
+
+        // Each of the five columns is terminated by a `;`, so splitting
+        // leaves an empty sixth field.
+        let fields: Vec<&str> = line.split(';').collect();
+        if fields.len() != 6 || !fields[5].trim().is_empty() {
+            return err!("invalid NormalizationTest line: '{}'", line);
+        }
+        let mut columns = vec![];
+        for field in &fields[..5] {
+            columns.push(parse_string(field, line)?);
+        }
+        let mut columns = columns.into_iter();
+        let mut next = || columns.next().unwrap();
+        Ok(NormalizationTestLine::Test(NormalizationTest {
+            part: 0,
+            source: next(),
+            nfc: next(),
+            nfd: next(),
+            nfkc: next(),
+            nfkd: next(),
+        }))
+    }
+}
+
+impl std::fmt::Display for NormalizationTestLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            NormalizationTestLine::Part(part) => write!(f, "@Part{}", part),
+            NormalizationTestLine::Test(ref test) => {
+                for s in &[
+                    &test.source,
+                    &test.nfc,
+                    &test.nfd,
+                    &test.nfkc,
+                    &test.nfkd,
+                ] {
+                    write_string(f, s)?;
+                    write!(f, ";")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Parse every test in `NormalizationTest.txt`, filling in the part that
+/// each one belongs to.
+///
+/// The given directory should be the directory to the UCD.
+pub fn parse_normalization_tests<P: AsRef<Path>>(
+    ucd_dir: P,
+) -> Result<Vec<NormalizationTest>, Error> {
+    let mut part = None;
+    let mut tests = vec![];
+    for result in NormalizationTestLine::from_dir(ucd_dir)? {
+        match result? {
+            NormalizationTestLine::Part(x) => part = Some(x),
+            NormalizationTestLine::Test(mut test) => {
+                match part {
+                    Some(part) => test.part = part,
+                    None => {
+                        return err!(
+                            "found NormalizationTest test before an @Part \
+                             line: {:?}",
+                            test.source
+                        )
+                    }
+                }
+                tests.push(test);
+            }
+        }
+    }
+    Ok(tests)
+}
+
+/// Parse a space separated sequence of codepoints into a string.
+fn parse_string(s: &str, line: &str) -> Result<String, Error> {
+    let mut string = String::new();
+    for cp in parse_codepoint_sequence(s)? {
+        match cp.scalar() {
+            Some(ch) => string.push(ch),
+            None => {
+                return err!(
+                    "invalid codepoint '{:X}' in line: '{}'",
+                    cp.value(),
+                    line
+                )
+            }
+        }
+    }
+    Ok(string)
+}
+
+/// Write a string as a space separated sequence of codepoints.
+fn write_string(f: &mut std::fmt::Formatter<'_>, s: &str) -> std::fmt::Result {
+    for (i, ch) in s.chars().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "{:04X}", u32::from(ch))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NormalizationTest, NormalizationTestLine};
+
+    #[test]
+    fn parse_part() {
+        let line = "@Part1 # Character by character test\n";
+        let row: NormalizationTestLine = line.parse().unwrap();
+        assert_eq!(row, NormalizationTestLine::Part(1));
+    }
+
+    #[test]
+    fn parse_test() {
+        let line = "1E0A;1E0A;0044 0307;1E0A;0044 0307; \
+                    # LATIN CAPITAL LETTER D WITH DOT ABOVE\n";
+        let row: NormalizationTestLine = line.parse().unwrap();
+        assert_eq!(
+            row,
+            NormalizationTestLine::Test(NormalizationTest {
+                part: 0,
+                source: "\u{1E0A}".into(),
+                nfc: "\u{1E0A}".into(),
+                nfd: "D\u{0307}".into(),
+                nfkc: "\u{1E0A}".into(),
+                nfkd: "D\u{0307}".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("1E0A;1E0A;0044 0307;1E0A;"
+            .parse::<NormalizationTestLine>()
+            .is_err());
+        assert!("@Partx".parse::<NormalizationTestLine>().is_err());
+    }
+
+    #[test]
+    fn display() {
+        for line in &["@Part0", "1E0A;1E0A;0044 0307;1E0A;0044 0307;"] {
+            let row: NormalizationTestLine = line.parse().unwrap();
+            assert_eq!(row.to_string(), *line);
+        }
+    }
+}
@@ -41,6 +41,12 @@ impl std::str::FromStr for Property {
     }
 }
 
+impl std::fmt::Display for Property {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.property)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Property;
@@ -52,6 +52,16 @@ impl std::str::FromStr for PropertyAlias {
     }
 }
 
+impl std::fmt::Display for PropertyAlias {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.abbreviation, self.long)?;
+        for alias in &self.aliases {
+            write!(f, "; {}", alias)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::PropertyAlias;
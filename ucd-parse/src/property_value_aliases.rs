@@ -102,6 +102,20 @@ impl std::str::FromStr for PropertyValueAlias {
     }
 }
 
+impl std::fmt::Display for PropertyValueAlias {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; ", self.property)?;
+        if let Some(n) = self.numeric {
+            write!(f, "{}; ", n)?;
+        }
+        write!(f, "{}; {}", self.abbreviation, self.long)?;
+        for alias in &self.aliases {
+            write!(f, "; {}", alias)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::PropertyValueAlias;
@@ -172,4 +186,16 @@ mod tests {
         assert_eq!(row.long, "Punctuation");
         assert_eq!(row.aliases, vec!["punct"]);
     }
+
+    #[test]
+    fn display() {
+        for line in &[
+            "ccc; 0; NR; Not_Reordered",
+            "AHex; N; No; F; False",
+            "age; 1.1; V1_1",
+        ] {
+            let row: PropertyValueAlias = line.parse().unwrap();
+            assert_eq!(row.to_string(), *line);
+        }
+    }
 }
@@ -41,6 +41,12 @@ impl std::str::FromStr for ScriptExtension {
     }
 }
 
+impl std::fmt::Display for ScriptExtension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.scripts.join(" "))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ScriptExtension;
@@ -38,6 +38,12 @@ impl std::str::FromStr for Script {
     }
 }
 
+impl std::fmt::Display for Script {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.script)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Script;
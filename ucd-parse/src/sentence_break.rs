@@ -2,8 +2,8 @@ use std::path::Path;
 
 use crate::{
     common::{
-        parse_break_test, parse_codepoint_association, CodepointIter,
-        Codepoints, UcdFile, UcdFileByCodepoint,
+        parse_break_test, parse_codepoint_association, write_break_test,
+        CodepointIter, Codepoints, UcdFile, UcdFileByCodepoint,
     },
     error::Error,
 };
@@ -38,6 +38,12 @@ impl std::str::FromStr for SentenceBreak {
     }
 }
 
+impl std::fmt::Display for SentenceBreak {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.value)
+    }
+}
+
 /// A single row in the `auxiliary/SentenceBreakTest.txt` file.
 ///
 /// This file defines tests for the sentence break algorithm.
@@ -65,6 +71,12 @@ impl std::str::FromStr for SentenceBreakTest {
     }
 }
 
+impl std::fmt::Display for SentenceBreakTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_break_test(f, &self.sentences, &self.comment)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{SentenceBreak, SentenceBreakTest};
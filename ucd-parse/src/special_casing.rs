@@ -2,8 +2,8 @@ use std::path::Path;
 
 use crate::{
     common::{
-        parse_codepoint_sequence, Codepoint, CodepointIter, UcdFile,
-        UcdFileByCodepoint,
+        parse_codepoint_sequence, write_codepoint_sequence, Codepoint,
+        CodepointIter, UcdFile, UcdFileByCodepoint,
     },
     error::Error,
 };
@@ -79,6 +79,21 @@ impl std::str::FromStr for SpecialCaseMapping {
     }
 }
 
+impl std::fmt::Display for SpecialCaseMapping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{};", self.codepoint)?;
+        for cps in &[&self.lowercase, &self.titlecase, &self.uppercase] {
+            write!(f, " ")?;
+            write_codepoint_sequence(f, cps)?;
+            write!(f, ";")?;
+        }
+        if !self.conditions.is_empty() {
+            write!(f, " {};", self.conditions.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::SpecialCaseMapping;
@@ -104,4 +119,15 @@ mod tests {
         assert_eq!(row.uppercase, vec![0x0307]);
         assert_eq!(row.conditions, vec!["tr", "After_I"]);
     }
+
+    #[test]
+    fn display_conds() {
+        let line = "0307; ; 0307; 0307; tr After_I; # COMBINING DOT ABOVE\n";
+        let row: SpecialCaseMapping = line.parse().unwrap();
+        assert_eq!(row.to_string(), "0307; ; 0307; 0307; tr After_I;");
+        assert_eq!(
+            row.to_string().parse::<SpecialCaseMapping>().unwrap(),
+            row
+        );
+    }
 }
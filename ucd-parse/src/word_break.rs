@@ -2,8 +2,8 @@ use std::path::Path;
 
 use crate::{
     common::{
-        parse_break_test, parse_codepoint_association, CodepointIter,
-        Codepoints, UcdFile, UcdFileByCodepoint,
+        parse_break_test, parse_codepoint_association, write_break_test,
+        CodepointIter, Codepoints, UcdFile, UcdFileByCodepoint,
     },
     error::Error,
 };
@@ -38,6 +38,12 @@ impl std::str::FromStr for WordBreak {
     }
 }
 
+impl std::fmt::Display for WordBreak {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}; {}", self.codepoints, self.value)
+    }
+}
+
 /// A single row in the `auxiliary/WordBreakTest.txt` file.
 ///
 /// This file defines tests for the word break algorithm.
@@ -65,6 +71,12 @@ impl std::str::FromStr for WordBreakTest {
     }
 }
 
+impl std::fmt::Display for WordBreakTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_break_test(f, &self.words, &self.comment)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{WordBreak, WordBreakTest};
@@ -101,4 +113,12 @@ mod tests {
         );
         assert!(row.comment.contains("[4.0] COMBINING DIAERESIS (Extend_FE)"));
     }
+
+    #[test]
+    fn display_test() {
+        let line = "÷ 0031 ÷ 0027 × 0308 ÷\t#  ÷ [0.2] DIGIT ONE (Numeric)";
+        let row: WordBreakTest = line.parse().unwrap();
+        assert_eq!(row.to_string(), line.replace("#  ", "# "));
+        assert_eq!(row.to_string().parse::<WordBreakTest>().unwrap(), row);
+    }
 }
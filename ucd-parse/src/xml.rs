@@ -0,0 +1,697 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    common::{UcdFile, UcdLineParser},
+    error::{Error, ErrorKind},
+    property_aliases::PropertyAlias,
+    property_value_aliases::PropertyValueAlias,
+};
+
+/// The names of the single file XML distributions of the UCD, in the order
+/// in which they're looked for in a UCD directory.
+const FILE_NAMES: &[&str] = &[
+    "ucd.all.flat.xml",
+    "ucd.nounihan.flat.xml",
+    "ucd.all.grouped.xml",
+    "ucd.nounihan.grouped.xml",
+];
+
+/// The boolean properties of PropList.txt that can be read from the XML.
+const PROP_LIST: &[&str] = &[
+    "White_Space",
+    "Bidi_Control",
+    "Join_Control",
+    "Dash",
+    "Hyphen",
+    "Quotation_Mark",
+    "Terminal_Punctuation",
+    "Other_Math",
+    "Hex_Digit",
+    "ASCII_Hex_Digit",
+    "Other_Alphabetic",
+    "Ideographic",
+    "Diacritic",
+    "Extender",
+    "Other_Lowercase",
+    "Other_Uppercase",
+    "Noncharacter_Code_Point",
+    "Other_Grapheme_Extend",
+    "IDS_Binary_Operator",
+    "IDS_Trinary_Operator",
+    "IDS_Unary_Operator",
+    "Radical",
+    "Unified_Ideograph",
+    "Other_Default_Ignorable_Code_Point",
+    "Deprecated",
+    "Soft_Dotted",
+    "Logical_Order_Exception",
+    "Other_ID_Start",
+    "Other_ID_Continue",
+    "ID_Compat_Math_Start",
+    "ID_Compat_Math_Continue",
+    "Sentence_Terminal",
+    "Variation_Selector",
+    "Pattern_White_Space",
+    "Pattern_Syntax",
+    "Prepended_Concatenation_Mark",
+    "Regional_Indicator",
+];
+
+/// The boolean properties of DerivedCoreProperties.txt that can be read
+/// from the XML.
+const DERIVED_CORE_PROPERTIES: &[&str] = &[
+    "Math",
+    "Alphabetic",
+    "Lowercase",
+    "Uppercase",
+    "Cased",
+    "Case_Ignorable",
+    "Changes_When_Lowercased",
+    "Changes_When_Uppercased",
+    "Changes_When_Titlecased",
+    "Changes_When_Casefolded",
+    "Changes_When_Casemapped",
+    "ID_Start",
+    "ID_Continue",
+    "XID_Start",
+    "XID_Continue",
+    "Default_Ignorable_Code_Point",
+    "Grapheme_Extend",
+    "Grapheme_Base",
+    "Grapheme_Link",
+];
+
+/// The UCD in the single file XML format of UAX #42, e.g.,
+/// `ucd.nounihan.flat.xml`.
+///
+/// The XML can stand in for the most common text files of the UCD:
+/// `UnicodeData.txt`, `Scripts.txt`, `ScriptExtensions.txt`,
+/// `DerivedAge.txt`, `PropList.txt` and `DerivedCoreProperties.txt`. Their
+/// records are parsed from lines written in the format of each file, so
+/// they're the same records that the text parsers return.
+///
+/// Property and value names are looked up in `PropertyAliases.txt` and
+/// `PropertyValueAliases.txt`, which aren't part of the XML, so they must
+/// be in the same directory. Zipped XML isn't supported.
+#[derive(Clone, Debug)]
+pub struct UcdXml {
+    path: PathBuf,
+    text: String,
+    version: Option<(u64, u64, u64)>,
+    elements: Vec<Element>,
+    /// The attributes of each `group` element, which its children inherit.
+    groups: Vec<Range<usize>>,
+    /// A map from each property's long name to its abbreviation, which is
+    /// the name of its attribute.
+    properties: BTreeMap<String, String>,
+    /// A map from each abbreviated Script value to its long name.
+    scripts: BTreeMap<String, String>,
+}
+
+/// A single codepoint, or range of codepoints, in the repertoire.
+#[derive(Clone, Debug)]
+struct Element {
+    first: u32,
+    last: u32,
+    /// Whether this is a `char` or `surrogate` element, as opposed to a
+    /// `reserved` or `noncharacter` element. Only the former are listed in
+    /// UnicodeData.txt.
+    assigned: bool,
+    attrs: Range<usize>,
+    group: Option<usize>,
+}
+
+impl UcdXml {
+    /// Return the path to the XML file in the given UCD directory, if it has
+    /// one.
+    pub fn find<P: AsRef<Path>>(ucd_dir: P) -> Option<PathBuf> {
+        FILE_NAMES
+            .iter()
+            .map(|name| ucd_dir.as_ref().join(name))
+            .find(|path| path.exists())
+    }
+
+    /// Returns true when the records of the given UCD file can be read from
+    /// the XML, where the path is relative to the UCD directory.
+    pub fn supports(relative_path: &Path) -> bool {
+        [
+            "UnicodeData.txt",
+            "Scripts.txt",
+            "ScriptExtensions.txt",
+            "DerivedAge.txt",
+            "PropList.txt",
+            "DerivedCoreProperties.txt",
+        ]
+        .iter()
+        .any(|&name| relative_path == Path::new(name))
+    }
+
+    /// Read the XML file in the given UCD directory, along with its
+    /// PropertyAliases.txt and PropertyValueAliases.txt.
+    pub fn from_dir<P: AsRef<Path>>(ucd_dir: P) -> Result<UcdXml, Error> {
+        let ucd_dir = ucd_dir.as_ref();
+        let path = match UcdXml::find(ucd_dir) {
+            Some(path) => path,
+            None => {
+                return err!(
+                    "no UCD XML file (one of {}) in {}",
+                    FILE_NAMES.join(", "),
+                    ucd_dir.display()
+                )
+            }
+        };
+        let text = std::fs::read_to_string(&path).map_err(|e| Error {
+            kind: ErrorKind::Io(e),
+            line: None,
+            path: Some(path.clone()),
+        })?;
+        let properties = crate::parse(ucd_dir)?;
+        let values = crate::parse(ucd_dir)?;
+        let mut xml = UcdXml::new(text, properties, values)?;
+        xml.path = path;
+        Ok(xml)
+    }
+
+    fn new(
+        text: String,
+        properties: Vec<PropertyAlias>,
+        values: Vec<PropertyValueAlias>,
+    ) -> Result<UcdXml, Error> {
+        let mut xml = UcdXml {
+            path: PathBuf::new(),
+            text,
+            version: None,
+            elements: vec![],
+            groups: vec![],
+            properties: properties
+                .into_iter()
+                .map(|p| (p.long, p.abbreviation))
+                .collect(),
+            scripts: values
+                .into_iter()
+                .filter(|v| v.property == "sc")
+                .map(|v| (v.abbreviation, v.long))
+                .collect(),
+        };
+        xml.scan()?;
+        Ok(xml)
+    }
+
+    /// Find the description and every element of the repertoire.
+    fn scan(&mut self) -> Result<(), Error> {
+        let text = &self.text;
+        let mut group = None;
+        let mut pos = 0;
+        while let Some(i) = text[pos..].find('<') {
+            let start = pos + i;
+            let rest = &text[start..];
+            let close = if rest.starts_with("<!--") {
+                "-->"
+            } else if rest.starts_with("<?") {
+                "?>"
+            } else {
+                ">"
+            };
+            let end = match rest.find(close) {
+                Some(end) => start + end,
+                None => return err!("unterminated tag at byte {}", start),
+            };
+            pos = end + close.len();
+            if close != ">" {
+                continue;
+            }
+
+            let tag = &text[start + 1..end];
+            if tag == "/group" {
+                group = None;
+                continue;
+            }
+            let name_len = tag.find(|c: char| c.is_whitespace() || c == '/');
+            let name = &tag[..name_len.unwrap_or(tag.len())];
+            let attrs = start + 1 + name.len()..end;
+            match name {
+                "description" => {
+                    let end = match text[pos..].find("</description>") {
+                        Some(end) => pos + end,
+                        None => return err!("unterminated description"),
+                    };
+                    self.version = parse_version(&text[pos..end]);
+                }
+                "group" => {
+                    self.groups.push(attrs);
+                    if !tag.ends_with('/') {
+                        group = Some(self.groups.len() - 1);
+                    }
+                }
+                "char" | "surrogate" | "reserved" | "noncharacter" => {
+                    let span = &text[attrs.clone()];
+                    let (first, last) = match attr(span, "cp") {
+                        Some(cp) => (parse_cp(cp)?, parse_cp(cp)?),
+                        None => match (
+                            attr(span, "first-cp"),
+                            attr(span, "last-cp"),
+                        ) {
+                            (Some(first), Some(last)) => {
+                                (parse_cp(first)?, parse_cp(last)?)
+                            }
+                            _ => {
+                                return err!(
+                                    "{} element without a codepoint: <{}>",
+                                    name,
+                                    tag
+                                )
+                            }
+                        },
+                    };
+                    let assigned = name == "char" || name == "surrogate";
+                    self.elements.push(Element {
+                        first,
+                        last,
+                        assigned,
+                        attrs,
+                        group,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the Unicode version given in the description of the XML, e.g.,
+    /// `Unicode 15.1.0`.
+    pub fn version(&self) -> Option<(u64, u64, u64)> {
+        self.version
+    }
+
+    /// Parse the records of the given UCD file out of the XML.
+    ///
+    /// If the file can't be read from the XML (see `supports`), then an
+    /// error is returned.
+    pub fn parse<D: UcdFile>(&self) -> Result<Vec<D>, Error> {
+        let file = D::relative_file_path();
+        let contents = if file == Path::new("UnicodeData.txt") {
+            self.unicode_data()?
+        } else if file == Path::new("Scripts.txt") {
+            self.ranges(|xml, attrs| {
+                let sc = get(attrs, "sc")?;
+                if sc == "Zzzz" {
+                    return None;
+                }
+                Some(xml.scripts.get(sc).map_or(sc, |long| long).to_string())
+            })
+        } else if file == Path::new("ScriptExtensions.txt") {
+            self.ranges(|_, attrs| {
+                let scx = get(attrs, "scx")?;
+                if Some(scx) == get(attrs, "sc") {
+                    return None;
+                }
+                Some(scx.to_string())
+            })
+        } else if file == Path::new("DerivedAge.txt") {
+            self.ranges(|_, attrs| {
+                get(attrs, "age")
+                    .filter(|&age| age != "unassigned")
+                    .map(|age| age.to_string())
+            })
+        } else if file == Path::new("PropList.txt") {
+            self.booleans(PROP_LIST)
+        } else if file == Path::new("DerivedCoreProperties.txt") {
+            self.booleans(DERIVED_CORE_PROPERTIES)
+        } else {
+            return err!(
+                "{} can't be read from {}",
+                file.display(),
+                self.path.display()
+            );
+        };
+        let path = Some(self.path.clone());
+        UcdLineParser::new(path, contents.as_bytes()).collect()
+    }
+
+    /// Return the attributes of the given element, followed by those of
+    /// its group, so that the first attribute with a name is the one that
+    /// applies.
+    fn attrs(&self, element: &Element) -> Vec<(&str, Cow<'_, str>)> {
+        let mut attrs = attrs(&self.text[element.attrs.clone()]);
+        if let Some(group) = element.group {
+            attrs.extend(self.attrs_of_group(group));
+        }
+        attrs
+    }
+
+    fn attrs_of_group(&self, group: usize) -> Vec<(&str, Cow<'_, str>)> {
+        attrs(&self.text[self.groups[group].clone()])
+    }
+
+    /// Write a line for every element for which the given function returns
+    /// a value, in the format `first..last ; value`.
+    fn ranges<F>(&self, mut value: F) -> String
+    where
+        F: FnMut(&UcdXml, &[(&str, Cow<'_, str>)]) -> Option<String>,
+    {
+        let mut contents = String::new();
+        for element in &self.elements {
+            if let Some(value) = value(self, &self.attrs(element)) {
+                write_range(&mut contents, element);
+                writeln!(contents, " ; {}", value).unwrap();
+            }
+        }
+        contents
+    }
+
+    /// Write a line for every element that has each of the given boolean
+    /// properties, in the format `first..last ; property`.
+    fn booleans(&self, properties: &[&str]) -> String {
+        let mut contents = String::new();
+        for &property in properties {
+            let name = match self.properties.get(property) {
+                Some(name) => name,
+                None => continue,
+            };
+            for element in &self.elements {
+                if get(&self.attrs(element), name) == Some("Y") {
+                    write_range(&mut contents, element);
+                    writeln!(contents, " ; {}", property).unwrap();
+                }
+            }
+        }
+        contents
+    }
+
+    /// Write the lines of UnicodeData.txt for every assigned element.
+    fn unicode_data(&self) -> Result<String, Error> {
+        let mut contents = String::new();
+        for element in self.elements.iter().filter(|e| e.assigned) {
+            let attrs = self.attrs(element);
+            let field = |name| get(&attrs, name).unwrap_or("");
+            let mapping = |name| match field(name) {
+                "#" => "",
+                cp => cp,
+            };
+            let decomposition = match (field("dt"), field("dm")) {
+                ("none", _) | (_, "#") => String::new(),
+                ("can", dm) => dm.to_string(),
+                (dt, dm) => match decomposition_tag(dt) {
+                    Some(tag) => format!("<{}> {}", tag, dm),
+                    None => {
+                        return err!(
+                        "unrecognized decomposition type {:?} for U+{:04X}",
+                        dt,
+                        element.first
+                    )
+                    }
+                },
+            };
+            let nv = field("nv");
+            let numeric = match field("nt") {
+                "De" => [nv, nv, nv],
+                "Di" => ["", nv, nv],
+                "Nu" => ["", "", nv],
+                _ => ["", "", ""],
+            };
+            let mirrored = field("Bidi_M");
+            let rest = format!(
+                "{};{};{};{};{};{};{};{};{};;{};{};{}",
+                field("gc"),
+                field("ccc"),
+                field("bc"),
+                decomposition,
+                numeric[0],
+                numeric[1],
+                numeric[2],
+                mirrored,
+                field("na1"),
+                mapping("suc"),
+                mapping("slc"),
+                mapping("stc"),
+            );
+            let name = field("na");
+            if element.first == element.last {
+                let hex = format!("{:04X}", element.first);
+                // Controls have no name, but are listed as <control>.
+                let name = match (name, field("gc")) {
+                    ("", "Cc") => "<control>".to_string(),
+                    (name, _) => name.replace('#', &hex),
+                };
+                writeln!(contents, "{};{};{}", hex, name, rest).unwrap();
+                continue;
+            }
+            let label = name.trim_end_matches('#').trim_end_matches('-');
+            let label = match label {
+                "" => unnamed_range_label(element.first),
+                label => label,
+            };
+            writeln!(
+                contents,
+                "{:04X};<{}, First>;{}\n{:04X};<{}, Last>;{}",
+                element.first, label, rest, element.last, label, rest
+            )
+            .unwrap();
+        }
+        Ok(contents)
+    }
+}
+
+/// Return the value of the attribute with the given name.
+fn get<'a>(attrs: &'a [(&str, Cow<'_, str>)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|&&(n, _)| n == name).map(|(_, v)| &**v)
+}
+
+/// Return the raw value of the attribute with the given name in the given
+/// attributes of a tag.
+fn attr<'a>(span: &'a str, name: &str) -> Option<&'a str> {
+    let mut found = None;
+    for_each_attr(span, |n, v| {
+        if found.is_none() && n == name {
+            found = Some(v);
+        }
+    });
+    found
+}
+
+/// Return every attribute in the given attributes of a tag, with entities
+/// in their values replaced.
+fn attrs(span: &str) -> Vec<(&str, Cow<'_, str>)> {
+    let mut attrs = vec![];
+    for_each_attr(span, |name, value| attrs.push((name, unescape(value))));
+    attrs
+}
+
+fn for_each_attr<'a, F: FnMut(&'a str, &'a str)>(span: &'a str, mut f: F) {
+    let mut rest = span;
+    while let Some(eq) = rest.find('=') {
+        let name = rest[..eq].trim();
+        let after = rest[eq + 1..].trim_start();
+        let quote = match after.chars().next() {
+            Some(q @ ('"' | '\'')) => q,
+            _ => return,
+        };
+        let end = match after[1..].find(quote) {
+            Some(end) => end + 1,
+            None => return,
+        };
+        f(name, &after[1..end]);
+        rest = &after[end + 1..];
+    }
+}
+
+/// Replace the XML entities in the given attribute value.
+fn unescape(value: &str) -> Cow<'_, str> {
+    if !value.contains('&') {
+        return Cow::Borrowed(value);
+    }
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        let end = match rest[i..].find(';') {
+            Some(end) => i + end,
+            None => break,
+        };
+        let entity = &rest[i + 1..end];
+        let ch = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse()))
+                .and_then(|cp| cp.ok())
+                .and_then(char::from_u32),
+        };
+        match ch {
+            Some(ch) => out.push(ch),
+            None => out.push_str(&rest[i..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+fn write_range(contents: &mut String, element: &Element) {
+    if element.first == element.last {
+        write!(contents, "{:04X}", element.first).unwrap();
+    } else {
+        write!(contents, "{:04X}..{:04X}", element.first, element.last)
+            .unwrap();
+    }
+}
+
+fn parse_cp(cp: &str) -> Result<u32, Error> {
+    match u32::from_str_radix(cp, 16) {
+        Ok(cp) => Ok(cp),
+        Err(err) => err!("invalid codepoint {:?}: {}", cp, err),
+    }
+}
+
+/// Parse a version out of a description such as `Unicode 15.1.0`.
+fn parse_version(description: &str) -> Option<(u64, u64, u64)> {
+    let version = description.split_whitespace().last()?;
+    let mut parts = version.split('.').map(|part| part.parse().ok());
+    Some((parts.next()??, parts.next()??, parts.next().unwrap_or(Some(0))?))
+}
+
+/// Return the label that UnicodeData.txt gives the range of surrogates or
+/// private use codepoints starting at the given codepoint.
+fn unnamed_range_label(first: u32) -> &'static str {
+    match first {
+        0xD800 => "Non Private Use High Surrogate",
+        0xDB80 => "Private Use High Surrogate",
+        0xDC00 => "Low Surrogate",
+        0xF0000 => "Plane 15 Private Use",
+        0x100000 => "Plane 16 Private Use",
+        _ => "Private Use",
+    }
+}
+
+/// Return the tag that UnicodeData.txt uses for the given abbreviated
+/// Decomposition_Type.
+fn decomposition_tag(dt: &str) -> Option<&'static str> {
+    Some(match dt {
+        "com" => "compat",
+        "enc" => "circle",
+        "fin" => "final",
+        "font" => "font",
+        "fra" => "fraction",
+        "init" => "initial",
+        "iso" => "isolated",
+        "med" => "medial",
+        "nar" => "narrow",
+        "nb" => "noBreak",
+        "sml" => "small",
+        "sqr" => "square",
+        "sub" => "sub",
+        "sup" => "super",
+        "vert" => "vertical",
+        "wide" => "wide",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UcdXml;
+    use crate::{
+        Age, CoreProperty, Property, PropertyAlias, PropertyValueAlias,
+        Script, ScriptExtension, UnicodeData,
+    };
+
+    const XML: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<ucd xmlns="http://www.unicode.org/ns/2003/ucd/1.0">
+  <description>Unicode 15.1.0</description>
+  <repertoire>
+    <char cp="0009" age="1.1" na="" gc="Cc" ccc="0" bc="S" dt="none"
+      dm="#" nt="None" nv="NaN" Bidi_M="N" na1="CHARACTER TABULATION"
+      suc="#" slc="#" stc="#" sc="Zyyy" scx="Zyyy" WSpace="Y" Alpha="N"/>
+    <char cp="0041" age="1.1" na="LATIN CAPITAL LETTER A" gc="Lu" ccc="0"
+      bc="L" dt="none" dm="#" nt="None" nv="NaN" Bidi_M="N" na1=""
+      suc="#" slc="0061" stc="#" sc="Latn" scx="Latn" WSpace="N"
+      Alpha="Y"/>
+    <group age="1.1" gc="Nd" ccc="0" bc="EN" dt="none" dm="#" nt="De"
+      Bidi_M="N" na1="" suc="#" slc="#" stc="#" sc="Zyyy" WSpace="N"
+      Alpha="N">
+      <char cp="0031" na="DIGIT ONE" nv="1" scx="Zyyy"/>
+      <char cp="00B9" na="SUPERSCRIPT ONE" gc="No" bc="EN" dt="sup"
+        dm="0031" nt="Di" nv="1" scx="Zyyy Latn"/>
+    </group>
+    <reserved first-cp="0378" last-cp="0379" age="unassigned" sc="Zzzz"/>
+    <char first-cp="4E00" last-cp="9FFF" age="1.1"
+      na="CJK UNIFIED IDEOGRAPH-#" gc="Lo" ccc="0" bc="L" dt="none"
+      dm="#" nt="None" nv="NaN" Bidi_M="N" na1="" suc="#" slc="#"
+      stc="#" sc="Hani" scx="Hani" WSpace="N" Alpha="Y"/>
+  </repertoire>
+</ucd>
+"##;
+
+    fn xml() -> UcdXml {
+        let properties = vec![
+            "Alpha ; Alphabetic".parse::<PropertyAlias>().unwrap(),
+            "WSpace ; White_Space ; space".parse().unwrap(),
+        ];
+        let values = vec![
+            "sc ; Latn ; Latin".parse::<PropertyValueAlias>().unwrap(),
+            "sc ; Zyyy ; Common".parse().unwrap(),
+            "sc ; Hani ; Han".parse().unwrap(),
+        ];
+        UcdXml::new(XML.to_string(), properties, values).unwrap()
+    }
+
+    #[test]
+    fn version() {
+        assert_eq!(xml().version(), Some((15, 1, 0)));
+    }
+
+    #[test]
+    fn unicode_data() {
+        let rows: Vec<UnicodeData> = xml().parse().unwrap();
+        let lines: Vec<String> = rows.iter().map(|r| r.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "0009;<control>;Cc;0;S;;;;;N;CHARACTER TABULATION;;;;",
+                "0041;LATIN CAPITAL LETTER A;Lu;0;L;;;;;N;;;;0061;",
+                "0031;DIGIT ONE;Nd;0;EN;;1;1;1;N;;;;;",
+                "00B9;SUPERSCRIPT ONE;No;0;EN;<super> 0031;;1;1;N;;;;;",
+                "4E00;<CJK UNIFIED IDEOGRAPH, First>;Lo;0;L;;;;;N;;;;;",
+                "9FFF;<CJK UNIFIED IDEOGRAPH, Last>;Lo;0;L;;;;;N;;;;;",
+            ]
+        );
+    }
+
+    #[test]
+    fn properties() {
+        let xml = xml();
+        let scripts: Vec<Script> = xml.parse().unwrap();
+        let scripts: Vec<&str> =
+            scripts.iter().map(|s| s.script.as_str()).collect();
+        assert_eq!(
+            scripts,
+            vec!["Common", "Latin", "Common", "Common", "Han"]
+        );
+
+        let extensions: Vec<ScriptExtension> = xml.parse().unwrap();
+        assert_eq!(extensions.len(), 1);
+        assert_eq!(extensions[0].codepoints.to_string(), "00B9");
+
+        let ages: Vec<Age> = xml.parse().unwrap();
+        assert_eq!(ages.len(), 5);
+
+        let alphabetic: Vec<CoreProperty> = xml.parse().unwrap();
+        let ranges: Vec<String> =
+            alphabetic.iter().map(|p| p.codepoints.to_string()).collect();
+        assert_eq!(ranges, vec!["0041", "4E00..9FFF"]);
+
+        let props: Vec<Property> = xml.parse().unwrap();
+        assert_eq!(props.len(), 1);
+        assert_eq!(props[0].property, "White_Space");
+    }
+}
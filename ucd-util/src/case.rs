@@ -0,0 +1,82 @@
+/// Look up the case mapping for a codepoint in a flat case mapping table.
+///
+/// A flat table is what `ucd-generate case-mapping --flat-table` emits: a
+/// sequence of tuples sorted by codepoint, where the second element of each
+/// tuple is a fixed size array holding the mapping, padded at the end with
+/// `!0`. The padding is trimmed off of the slice returned.
+///
+/// If the codepoint has no mapping in the table, then `None` is returned.
+/// (Typically, this means that the codepoint maps to itself.)
+pub fn case_lookup<const N: usize>(
+    table: &[(u32, [u32; N])],
+    cp: u32,
+) -> Option<&[u32]> {
+    case_lookup_padded(table, cp, !0)
+}
+
+/// Look up the case mapping for a character in a flat case mapping table.
+///
+/// This is like `case_lookup`, except it works on tables emitted with
+/// `--chars`, which are padded with `'\0'`.
+pub fn case_lookup_char<const N: usize>(
+    table: &[(char, [char; N])],
+    c: char,
+) -> Option<&[char]> {
+    case_lookup_padded(table, c, '\0')
+}
+
+/// Look up the case mapping for a key in a flat case mapping table that is
+/// padded with the given value.
+///
+/// This is useful for tables emitted with `--flat-table-pad`.
+pub fn case_lookup_padded<T: Copy + Ord, const N: usize>(
+    table: &[(T, [T; N])],
+    key: T,
+    pad: T,
+) -> Option<&[T]> {
+    let i = table.binary_search_by_key(&key, |&(k, _)| k).ok()?;
+    let mapping = &table[i].1;
+    let len = mapping.iter().position(|&v| v == pad).unwrap_or(N);
+    Some(&mapping[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{case_lookup, case_lookup_char, case_lookup_padded};
+
+    const LOWER: &[(u32, [u32; 3])] = &[
+        (0x41, [0x61, !0, !0]),
+        (0x130, [0x69, 0x307, !0]),
+        (0x1F88, [0x1F00, 0x3B9, !0]),
+    ];
+
+    const UPPER: &[(char, [char; 3])] = &[
+        ('a', ['A', '\0', '\0']),
+        ('ß', ['S', 'S', '\0']),
+        ('ΐ', ['Ι', '\u{308}', '\u{301}']),
+    ];
+
+    #[test]
+    fn lookup() {
+        assert_eq!(case_lookup(LOWER, 0x41), Some(&[0x61][..]));
+        assert_eq!(case_lookup(LOWER, 0x130), Some(&[0x69, 0x307][..]));
+        assert_eq!(case_lookup(LOWER, 0x42), None);
+    }
+
+    #[test]
+    fn lookup_char() {
+        assert_eq!(case_lookup_char(UPPER, 'a'), Some(&['A'][..]));
+        assert_eq!(case_lookup_char(UPPER, 'ß'), Some(&['S', 'S'][..]));
+        assert_eq!(
+            case_lookup_char(UPPER, 'ΐ'),
+            Some(&['Ι', '\u{308}', '\u{301}'][..])
+        );
+        assert_eq!(case_lookup_char(UPPER, 'b'), None);
+    }
+
+    #[test]
+    fn lookup_padded() {
+        let table: &[(u32, [u32; 2])] = &[(0x41, [0x61, 0])];
+        assert_eq!(case_lookup_padded(table, 0x41, 0), Some(&[0x61][..]));
+    }
+}
@@ -13,12 +13,14 @@ be generated using `ucd-generate`.
 #![allow(unknown_lints)]
 #![allow(ellipsis_inclusive_range_patterns)]
 
+mod case;
 mod hangul;
 mod ideograph;
 mod name;
 mod property;
 mod unicode_tables;
 
+pub use crate::case::{case_lookup, case_lookup_char, case_lookup_padded};
 pub use crate::hangul::{
     hangul_full_canonical_decomposition, hangul_name, RANGE_HANGUL_SYLLABLE,
 };